@@ -0,0 +1,61 @@
+// benches/generator.rs
+
+// compares batch allocation against repeated single generation, and the nanoid and sequence engines
+// run with: cargo bench --bench generator
+
+use criterion::{Criterion, Throughput, black_box, criterion_group, criterion_main};
+use url_shortener_ztm_lib::generator::{NanoIdEngine, SequenceEngine, ShortCodeGenerator};
+
+fn sequence_engine() -> SequenceEngine {
+    SequenceEngine::new(7, None, 65_536, 1_024, None)
+}
+
+fn bench_sequence_generate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sequence_engine");
+
+    let engine = sequence_engine();
+    group.bench_function("generate_n(100)", |b| {
+        b.iter(|| black_box(engine.generate_n(black_box(100)).unwrap()))
+    });
+
+    let engine = sequence_engine();
+    group.bench_function("100 x generate()", |b| {
+        b.iter(|| {
+            let codes: Vec<String> = (0..100).map(|_| engine.generate().unwrap()).collect();
+            black_box(codes)
+        })
+    });
+
+    group.finish();
+}
+
+// one sample is a million codes, so keep the sample count low
+fn bench_engines_1m(c: &mut Criterion) {
+    const CODES: u64 = 1_000_000;
+    let mut group = c.benchmark_group("engines_1m");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(CODES));
+
+    let engine = NanoIdEngine::new(7, None);
+    group.bench_function("nanoid generate()", |b| {
+        b.iter(|| {
+            for _ in 0..CODES {
+                black_box(engine.generate().unwrap());
+            }
+        })
+    });
+
+    let engine = sequence_engine();
+    group.bench_function("sequence generate()", |b| {
+        b.iter(|| {
+            for _ in 0..CODES {
+                black_box(engine.generate().unwrap());
+            }
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_sequence_generate, bench_engines_1m);
+criterion_main!(benches);
@@ -0,0 +1,88 @@
+// benches/normalize_url.rs
+
+// normalize_url runs on every shorten request; these cases cover the inputs most likely to be slow
+// plus validate_alias at the longest allowed alias
+// run with: cargo bench --bench normalize_url
+
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use std::sync::Arc;
+use url_shortener_ztm_lib::database::{SqliteUrlDatabase, UrlDatabase};
+use url_shortener_ztm_lib::generator::DEFAULT_ALPHABET;
+use url_shortener_ztm_lib::state::{AppState, AppStateBuilder};
+use url_shortener_ztm_lib::{get_configuration, normalize_url, validate_alias};
+
+// the longest URL the shorten endpoint accepts
+const MAX_URL_LENGTH: usize = 2048;
+
+fn cases() -> Vec<(&'static str, String)> {
+    let query = (0..50)
+        .map(|i| format!("key{}=value{}", i, i))
+        .collect::<Vec<_>>()
+        .join("&");
+    let nested = ('a'..='z').map(String::from).collect::<Vec<_>>().join("/");
+    let prefix = "https://example.com/";
+
+    vec![
+        (
+            "plain",
+            "https://example.com/path/to/page?ref=home".to_string(),
+        ),
+        (
+            "2048 chars",
+            format!("{}{}", prefix, "a".repeat(MAX_URL_LENGTH - prefix.len())),
+        ),
+        (
+            "50 query params",
+            format!("https://example.com/search?{}", query),
+        ),
+        ("idn host", "https://bücher.example/straße?q=ä".to_string()),
+        ("nested path", format!("{}{}", prefix, nested)),
+        ("port", "https://example.com:8443/path".to_string()),
+    ]
+}
+
+fn bench_normalize_url(c: &mut Criterion) {
+    let mut group = c.benchmark_group("normalize_url");
+    for (name, url) in cases() {
+        assert!(url.len() <= MAX_URL_LENGTH);
+        group.bench_function(name, |b| {
+            b.iter(|| black_box(normalize_url(black_box(&url)).unwrap()))
+        });
+    }
+    group.finish();
+}
+
+fn alias_state() -> AppState {
+    tokio::runtime::Runtime::new()
+        .expect("Failed to build the Tokio runtime")
+        .block_on(async {
+            let mut configuration = get_configuration().expect("Failed to read configuration");
+            configuration.database.url = "sqlite::memory:".to_string();
+            // the alias below is drawn from the default alphabet, unprefixed
+            configuration.shortener.alphabet = None;
+            configuration.shortener.alias_prefix = None;
+            let database = SqliteUrlDatabase::from_config(&configuration.database)
+                .await
+                .expect("Failed to open the database");
+            let database: Arc<dyn UrlDatabase> = Arc::new(database);
+            AppStateBuilder::new()
+                .config(configuration)
+                .database(database)
+                .build()
+                .await
+                .expect("Failed to build the app state")
+        })
+}
+
+fn bench_validate_alias(c: &mut Criterion) {
+    let state = alias_state();
+    // the maximum alias length, cycling through the default alphabet
+    let alias: String = DEFAULT_ALPHABET.iter().cycle().take(64).collect();
+
+    c.bench_function("validate_alias (64 chars)", |b| {
+        b.iter(|| black_box(validate_alias(black_box(&alias), &state).unwrap()))
+    });
+}
+
+criterion_group!(benches, bench_normalize_url, bench_validate_alias);
+criterion_main!(benches);
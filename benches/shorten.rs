@@ -0,0 +1,188 @@
+// benches/shorten.rs
+
+// hot paths of shortening and redirecting: the shorten handler, the URL lookup and the Bloom filter check
+// run with: cargo bench --bench shorten
+
+use axum::Router;
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::http::{Request, StatusCode};
+use criterion::{Criterion, Throughput, black_box, criterion_group, criterion_main};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tokio::runtime::Runtime;
+use tower::ServiceExt;
+use url_shortener_ztm_lib::database::{SqliteUrlDatabase, UrlDatabase};
+use url_shortener_ztm_lib::shortcode::bloom_filter::build_bloom_state;
+use url_shortener_ztm_lib::shortcode::snapshot_store::{
+    BloomSnapshotStore, DatabaseBloomSnapshotStore,
+};
+use url_shortener_ztm_lib::startup::build_router;
+use url_shortener_ztm_lib::state::{AppState, AppStateBuilder};
+use url_shortener_ztm_lib::{Settings, get_configuration};
+
+// number of URLs stored before measuring
+const STORED_URLS: usize = 10_000;
+
+fn runtime() -> Runtime {
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .expect("Failed to build the Tokio runtime")
+}
+
+fn configuration() -> Settings {
+    let mut c = get_configuration().expect("Failed to read configuration");
+    c.database.url = "sqlite::memory:".to_string();
+    // every request comes from the same client and would otherwise be throttled
+    c.rate_limiting.enabled = false;
+    c
+}
+
+fn stored_code(i: usize) -> String {
+    format!("bench{:05}", i % STORED_URLS)
+}
+
+async fn populated_database(configuration: &Settings) -> Arc<dyn UrlDatabase> {
+    let database = SqliteUrlDatabase::from_config(&configuration.database)
+        .await
+        .expect("Failed to open the database");
+    database.migrate().await.expect("Failed to migrate");
+
+    let rows: Vec<(String, String)> = (0..STORED_URLS)
+        .map(|i| (stored_code(i), format!("https://example.com/stored/{}", i)))
+        .collect();
+    let items: Vec<(&str, &str)> = rows
+        .iter()
+        .map(|(code, url)| (code.as_str(), url.as_str()))
+        .collect();
+    database
+        .insert_urls_batch(&items)
+        .await
+        .expect("Failed to store URLs");
+
+    Arc::new(database)
+}
+
+async fn app_state(configuration: &Settings, database: Arc<dyn UrlDatabase>) -> AppState {
+    AppStateBuilder::new()
+        .config(configuration.clone())
+        .database(database)
+        .build()
+        .await
+        .expect("Failed to build the app state")
+}
+
+// prints the p50 and p99 of the latencies collected over every sample
+fn report_percentiles(name: &str, latencies: &mut [Duration]) {
+    if latencies.is_empty() {
+        return;
+    }
+    latencies.sort_unstable();
+    let at = |p: f64| latencies[((latencies.len() - 1) as f64 * p) as usize];
+    println!(
+        "{}: p50 {:?}, p99 {:?} over {} calls",
+        name,
+        at(0.50),
+        at(0.99),
+        latencies.len()
+    );
+}
+
+fn bench_post_shorten(c: &mut Criterion) {
+    let rt = runtime();
+    let (router, api_key): (Router, String) = rt.block_on(async {
+        let configuration = configuration();
+        let database = populated_database(&configuration).await;
+        let state = app_state(&configuration, database).await;
+        let router = build_router(state.clone())
+            .await
+            .expect("Failed to build the router")
+            .with_state(state);
+        (
+            router,
+            configuration.application.api_keys[0]
+                .key
+                .plaintext()
+                .expect("the local API key is not hashed")
+                .to_string(),
+        )
+    });
+
+    // a fresh URL per iteration, so each request takes the insert path
+    let next = AtomicU64::new(0);
+    c.bench_function("post_shorten", |b| {
+        b.iter(|| {
+            let i = next.fetch_add(1, Ordering::Relaxed);
+            let request = Request::post("/api/shorten")
+                .header("host", "localhost")
+                .header("x-api-key", api_key.as_str())
+                .header("content-type", "text/plain")
+                .extension(ConnectInfo(SocketAddr::from(([127, 0, 0, 1], 40_000))))
+                .body(Body::from(format!("https://example.com/new/{}", i)))
+                .unwrap();
+            let response = rt.block_on(router.clone().oneshot(request)).unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        })
+    });
+}
+
+fn bench_get_url(c: &mut Criterion) {
+    let rt = runtime();
+    let database = rt.block_on(populated_database(&configuration()));
+
+    let mut latencies = Vec::new();
+    c.bench_function("get_url (10k rows)", |b| {
+        b.iter_custom(|iters| {
+            let mut total = Duration::ZERO;
+            for i in 0..iters {
+                let code = stored_code(i as usize);
+                let start = Instant::now();
+                let url = rt.block_on(database.get_url(black_box(&code))).unwrap();
+                let elapsed = start.elapsed();
+                black_box(url);
+                latencies.push(elapsed);
+                total += elapsed;
+            }
+            total
+        })
+    });
+    report_percentiles("get_url (10k rows)", &mut latencies);
+}
+
+fn bench_bloom_lookup(c: &mut Criterion) {
+    let rt = runtime();
+    let configuration = configuration();
+    let blooms = rt.block_on(async {
+        let database = populated_database(&configuration).await;
+        let store: Arc<dyn BloomSnapshotStore> =
+            Arc::new(DatabaseBloomSnapshotStore::new(database.clone()));
+        let blooms = build_bloom_state(&database, &store, &configuration.bloom)
+            .await
+            .expect("Failed to build the Bloom filters");
+        blooms.wait_until_ready().await;
+        blooms
+    });
+
+    // reported as ops/sec; the target is 10M, or 100ns per lookup
+    let mut group = c.benchmark_group("bloom_s2l");
+    group.throughput(Throughput::Elements(1));
+    let hit = stored_code(42);
+    group.bench_function("may_contain (hit)", |b| {
+        b.iter(|| black_box(blooms.s2l.may_contain(black_box(&hit))))
+    });
+    group.bench_function("may_contain (miss)", |b| {
+        b.iter(|| black_box(blooms.s2l.may_contain(black_box("missing"))))
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_post_shorten,
+    bench_get_url,
+    bench_bloom_lookup
+);
+criterion_main!(benches);
@@ -0,0 +1,39 @@
+//! Sets `GIT_SHA` for the startup banner to the short hash of the checked-out
+//! commit.
+//!
+//! A `GIT_SHA` already in the environment wins, for builds without a `.git`
+//! directory such as the Docker image. Without either, the banner says
+//! `unknown`.
+
+use std::path::Path;
+use std::process::Command;
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=GIT_SHA");
+    if Path::new(".git/HEAD").exists() {
+        println!("cargo:rerun-if-changed=.git/HEAD");
+        println!("cargo:rerun-if-changed=.git/refs");
+    }
+
+    let sha = std::env::var("GIT_SHA")
+        .ok()
+        .filter(|sha| !sha.trim().is_empty())
+        .or_else(git_sha)
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=GIT_SHA={}", sha.trim());
+}
+
+/// Asks git for the short hash of `HEAD`; `None` when git is not installed
+/// or this is not a repository.
+fn git_sha() -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8(output.stdout)
+        .ok()
+        .filter(|sha| !sha.trim().is_empty())
+}
@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS clicks_target_id_idx;
+DROP TABLE IF EXISTS clicks;
+
+DROP TRIGGER IF EXISTS urls_set_created_at;
+ALTER TABLE urls DROP COLUMN created_at;
@@ -0,0 +1,20 @@
+PRAGMA foreign_keys = ON;
+
+-- SQLite cannot add a column with a non-constant default, so existing rows are
+-- backfilled here and new rows are stamped by the trigger below.
+ALTER TABLE urls ADD COLUMN created_at DATETIME;
+UPDATE urls SET created_at = strftime('%Y-%m-%dT%H:%M:%fZ','now') WHERE created_at IS NULL;
+
+CREATE TRIGGER IF NOT EXISTS urls_set_created_at
+AFTER INSERT ON urls
+WHEN NEW.created_at IS NULL
+BEGIN
+  UPDATE urls SET created_at = strftime('%Y-%m-%dT%H:%M:%fZ','now') WHERE id = NEW.id;
+END;
+
+CREATE TABLE IF NOT EXISTS clicks (
+  id          INTEGER PRIMARY KEY,
+  target_id   INTEGER NOT NULL REFERENCES urls(id) ON DELETE CASCADE,
+  clicked_at  DATETIME NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now'))
+);
+CREATE INDEX IF NOT EXISTS clicks_target_id_idx ON clicks(target_id, clicked_at);
@@ -0,0 +1,10 @@
+PRAGMA foreign_keys = ON;
+
+CREATE TABLE IF NOT EXISTS tags (
+  id          INTEGER PRIMARY KEY,
+  code        TEXT NOT NULL REFERENCES urls(code) ON DELETE CASCADE,
+  tag         TEXT NOT NULL,
+  created_at  DATETIME NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+  UNIQUE (code, tag)
+);
+CREATE INDEX IF NOT EXISTS tags_tag_idx ON tags(tag);
@@ -0,0 +1,2 @@
+-- Views of the /{id}/preview page, kept apart from redirect clicks.
+ALTER TABLE urls ADD COLUMN preview_views INTEGER NOT NULL DEFAULT 0;
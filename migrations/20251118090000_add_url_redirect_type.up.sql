@@ -0,0 +1,3 @@
+-- Whether redirects for this URL are permanent (308) or temporary (307).
+ALTER TABLE urls ADD COLUMN redirect_type TEXT NOT NULL DEFAULT 'permanent'
+  CHECK (redirect_type IN ('permanent', 'temporary'));
@@ -0,0 +1,2 @@
+ALTER TABLE clicks DROP COLUMN referrer;
+ALTER TABLE clicks DROP COLUMN ip;
@@ -0,0 +1,3 @@
+-- Who clicked and where from, for the per-URL analytics page.
+ALTER TABLE clicks ADD COLUMN ip TEXT;
+ALTER TABLE clicks ADD COLUMN referrer TEXT;
@@ -0,0 +1,11 @@
+-- Accounts at an OAuth2 provider (GitHub, Google) that sign in as a user.
+CREATE TABLE IF NOT EXISTS oauth_identities (
+    user_id      TEXT NOT NULL,
+    provider     TEXT NOT NULL,
+    subject_id   TEXT NOT NULL,
+    created_at   DATETIME NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+    PRIMARY KEY (provider, subject_id),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_oauth_identities_user_id ON oauth_identities (user_id);
@@ -0,0 +1,5 @@
+-- The user's TOTP secret, AES-256-GCM encrypted. It is stored on enrollment
+-- and only required at sign-in once the first code has been confirmed.
+ALTER TABLE users ADD COLUMN totp_secret_encrypted BLOB;
+ALTER TABLE users ADD COLUMN totp_enabled INTEGER NOT NULL DEFAULT 0
+  CHECK (totp_enabled IN (0,1));
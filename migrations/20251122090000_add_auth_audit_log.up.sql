@@ -0,0 +1,15 @@
+-- Authentication events per user (sign-ins, password changes, 2FA changes,
+-- lockouts, token revocations), kept for security monitoring.
+CREATE TABLE IF NOT EXISTS auth_audit_log (
+    id           INTEGER PRIMARY KEY,
+    user_id      TEXT NOT NULL,
+    event_type   TEXT NOT NULL,
+    ip           TEXT,
+    user_agent   TEXT,
+    metadata     TEXT,
+    created_at   DATETIME NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ','now')),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_auth_audit_log_user_created
+    ON auth_audit_log (user_id, created_at DESC, id DESC);
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_urls_owner_id;
+ALTER TABLE urls DROP COLUMN owner_id;
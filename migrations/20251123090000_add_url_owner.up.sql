@@ -0,0 +1,6 @@
+-- The account that shortened the URL, when it was shortened while signed in.
+-- Accounts are only backed by Postgres, so this column carries no foreign key;
+-- the UUID is stored as a 16-byte blob.
+ALTER TABLE urls ADD COLUMN owner_id BLOB;
+
+CREATE INDEX IF NOT EXISTS idx_urls_owner_id ON urls (owner_id, id);
@@ -0,0 +1,2 @@
+-- Nothing to undo; the up migration only documents the stored URL format.
+SELECT 1;
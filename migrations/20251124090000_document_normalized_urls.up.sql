@@ -0,0 +1,5 @@
+-- Destinations are stored as normalized by the application: lowercase host, no
+-- fragment, and non-ASCII path characters percent-encoded with uppercase hex,
+-- so equal URLs compare equal for deduplication and the Bloom filter. SQLite
+-- keeps no column comments, so the schema itself is unchanged.
+SELECT 1;
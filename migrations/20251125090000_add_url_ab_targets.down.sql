@@ -0,0 +1,2 @@
+ALTER TABLE clicks DROP COLUMN variant;
+DROP TABLE IF EXISTS urls_ab_targets;
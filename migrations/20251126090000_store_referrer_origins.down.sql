@@ -0,0 +1 @@
+ALTER TABLE clicks RENAME COLUMN referrer_origin TO referrer;
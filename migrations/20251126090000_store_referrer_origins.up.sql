@@ -0,0 +1,18 @@
+-- Only the origin (scheme://host[:port]) of a click's Referer is kept, so
+-- paths and query strings of the referring pages are not stored.
+ALTER TABLE clicks RENAME COLUMN referrer TO referrer_origin;
+
+UPDATE clicks
+SET referrer_origin = CASE
+    WHEN instr(substr(referrer_origin, instr(referrer_origin, '://') + 3), '/') > 0
+        THEN substr(
+            referrer_origin,
+            1,
+            instr(referrer_origin, '://') + 1
+                + instr(substr(referrer_origin, instr(referrer_origin, '://') + 3), '/')
+        )
+    ELSE referrer_origin
+END
+WHERE instr(referrer_origin, '://') > 0;
+
+UPDATE clicks SET referrer_origin = NULL WHERE instr(referrer_origin, '://') = 0;
@@ -0,0 +1,2 @@
+ALTER TABLE clicks DROP COLUMN device_type;
+ALTER TABLE urls DROP COLUMN mobile_url;
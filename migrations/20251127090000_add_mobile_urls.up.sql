@@ -0,0 +1,3 @@
+-- Destination for visitors on mobile devices, and the device class of each click.
+ALTER TABLE urls ADD COLUMN mobile_url TEXT;
+ALTER TABLE clicks ADD COLUMN device_type TEXT;
@@ -0,0 +1,2 @@
+-- ISO 3166-1 alpha-2 country of each click, from the GeoLite2 lookup of its IP.
+ALTER TABLE clicks ADD COLUMN country_code CHAR(2);
@@ -0,0 +1,2 @@
+ALTER TABLE urls DROP COLUMN deactivated_at;
+ALTER TABLE urls DROP COLUMN max_clicks;
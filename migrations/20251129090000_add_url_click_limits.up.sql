@@ -0,0 +1,3 @@
+-- Optional click limit of each URL, and when the URL was deactivated for reaching it.
+ALTER TABLE urls ADD COLUMN max_clicks INTEGER;
+ALTER TABLE urls ADD COLUMN deactivated_at DATETIME;
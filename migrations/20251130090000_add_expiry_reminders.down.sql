@@ -0,0 +1 @@
+ALTER TABLE urls DROP COLUMN expiry_reminder_sent_at;
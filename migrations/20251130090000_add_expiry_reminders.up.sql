@@ -0,0 +1,2 @@
+-- When the owner of a URL was warned about its upcoming expiry, so they are warned once.
+ALTER TABLE urls ADD COLUMN expiry_reminder_sent_at DATETIME;
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_users_vanity_domain;
+ALTER TABLE users DROP COLUMN vanity_domain;
@@ -0,0 +1,3 @@
+-- When a URL's destination last changed or it was last clicked; NULL until the
+-- first change, in which case readers fall back to created_at.
+ALTER TABLE urls ADD COLUMN updated_at DATETIME;
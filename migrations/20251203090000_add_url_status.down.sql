@@ -0,0 +1,11 @@
+DROP VIEW IF EXISTS all_short_codes;
+CREATE VIEW all_short_codes AS
+  SELECT u.code AS code, u.id AS target_id, u.url AS url, 'code'  AS source
+  FROM urls u
+  UNION ALL
+  SELECT a.alias AS code, a.target_id, u.url,       'alias' AS source
+  FROM aliases a
+  JOIN urls u ON u.id = a.target_id;
+
+DELETE FROM urls WHERE status = 'deleted';
+ALTER TABLE urls DROP COLUMN status;
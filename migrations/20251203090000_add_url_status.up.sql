@@ -0,0 +1,25 @@
+-- Lifecycle status of each URL. `deactivated_at`, `expires_at` and
+-- `password_hash` stay the source of the details; `status` says which of them
+-- currently applies, and marks soft-deleted URLs.
+ALTER TABLE urls ADD COLUMN status TEXT NOT NULL DEFAULT 'active'
+  CHECK (status IN ('active', 'expired', 'deleted', 'password_protected', 'max_clicks_reached'));
+
+UPDATE urls SET status = 'max_clicks_reached' WHERE deactivated_at IS NOT NULL;
+UPDATE urls SET status = 'expired'
+  WHERE status = 'active'
+    AND expires_at IS NOT NULL
+    AND julianday(expires_at) <= julianday('now');
+UPDATE urls SET status = 'password_protected'
+  WHERE status = 'active' AND password_hash IS NOT NULL;
+
+-- Deleted URLs and their aliases no longer resolve
+DROP VIEW IF EXISTS all_short_codes;
+CREATE VIEW all_short_codes AS
+  SELECT u.code AS code, u.id AS target_id, u.url AS url, 'code'  AS source
+  FROM urls u
+  WHERE u.status <> 'deleted'
+  UNION ALL
+  SELECT a.alias AS code, a.target_id, u.url,       'alias' AS source
+  FROM aliases a
+  JOIN urls u ON u.id = a.target_id
+  WHERE u.status <> 'deleted';
@@ -0,0 +1,2 @@
+-- `User-Agent` header of each click, for the per-link click history.
+ALTER TABLE clicks ADD COLUMN user_agent TEXT;
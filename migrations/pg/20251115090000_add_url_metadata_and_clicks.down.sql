@@ -0,0 +1,8 @@
+BEGIN;
+
+DROP INDEX IF EXISTS clicks_target_id_idx;
+DROP TABLE IF EXISTS clicks;
+
+ALTER TABLE urls DROP COLUMN IF EXISTS created_at;
+
+COMMIT;
@@ -0,0 +1,13 @@
+BEGIN;
+
+ALTER TABLE urls
+    ADD COLUMN IF NOT EXISTS created_at TIMESTAMPTZ NOT NULL DEFAULT now();
+
+CREATE TABLE IF NOT EXISTS clicks (
+  id          BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+  target_id   BIGINT NOT NULL REFERENCES urls(id) ON DELETE CASCADE,
+  clicked_at  TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+CREATE INDEX IF NOT EXISTS clicks_target_id_idx ON clicks (target_id, clicked_at);
+
+COMMIT;
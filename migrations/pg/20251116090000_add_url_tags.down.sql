@@ -0,0 +1,6 @@
+BEGIN;
+
+DROP INDEX IF EXISTS tags_tag_idx;
+DROP TABLE IF EXISTS tags;
+
+COMMIT;
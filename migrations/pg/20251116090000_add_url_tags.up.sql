@@ -0,0 +1,12 @@
+BEGIN;
+
+CREATE TABLE IF NOT EXISTS tags (
+  id          BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+  code        VARCHAR(12) COLLATE "C" NOT NULL REFERENCES urls(code) ON DELETE CASCADE,
+  tag         VARCHAR(32) NOT NULL,
+  created_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+  UNIQUE (code, tag)
+);
+CREATE INDEX IF NOT EXISTS tags_tag_idx ON tags (tag);
+
+COMMIT;
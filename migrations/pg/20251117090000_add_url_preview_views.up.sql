@@ -0,0 +1,7 @@
+BEGIN;
+
+-- Views of the /{id}/preview page, kept apart from redirect clicks.
+ALTER TABLE urls
+    ADD COLUMN IF NOT EXISTS preview_views BIGINT NOT NULL DEFAULT 0;
+
+COMMIT;
@@ -0,0 +1,8 @@
+BEGIN;
+
+-- Whether redirects for this URL are permanent (308) or temporary (307).
+ALTER TABLE urls
+    ADD COLUMN IF NOT EXISTS redirect_type TEXT NOT NULL DEFAULT 'permanent'
+    CHECK (redirect_type IN ('permanent', 'temporary'));
+
+COMMIT;
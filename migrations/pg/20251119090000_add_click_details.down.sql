@@ -0,0 +1,7 @@
+BEGIN;
+
+ALTER TABLE clicks
+    DROP COLUMN IF EXISTS referrer,
+    DROP COLUMN IF EXISTS ip;
+
+COMMIT;
@@ -0,0 +1,8 @@
+BEGIN;
+
+-- Who clicked and where from, for the per-URL analytics page.
+ALTER TABLE clicks
+    ADD COLUMN IF NOT EXISTS ip TEXT,
+    ADD COLUMN IF NOT EXISTS referrer TEXT;
+
+COMMIT;
@@ -0,0 +1,14 @@
+BEGIN;
+
+-- Accounts at an OAuth2 provider (GitHub, Google) that sign in as a user.
+CREATE TABLE IF NOT EXISTS oauth_identities (
+    user_id      UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    provider     TEXT NOT NULL,
+    subject_id   TEXT NOT NULL,
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (provider, subject_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_oauth_identities_user_id ON oauth_identities (user_id);
+
+COMMIT;
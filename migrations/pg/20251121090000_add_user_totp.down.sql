@@ -0,0 +1,7 @@
+BEGIN;
+
+ALTER TABLE users
+    DROP COLUMN IF EXISTS totp_enabled,
+    DROP COLUMN IF EXISTS totp_secret_encrypted;
+
+COMMIT;
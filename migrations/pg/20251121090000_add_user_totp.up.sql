@@ -0,0 +1,9 @@
+BEGIN;
+
+-- The user's TOTP secret, AES-256-GCM encrypted. It is stored on enrollment
+-- and only required at sign-in once the first code has been confirmed.
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS totp_secret_encrypted BYTEA,
+    ADD COLUMN IF NOT EXISTS totp_enabled BOOLEAN NOT NULL DEFAULT FALSE;
+
+COMMIT;
@@ -0,0 +1,18 @@
+BEGIN;
+
+-- Authentication events per user (sign-ins, password changes, 2FA changes,
+-- lockouts, token revocations), kept for security monitoring.
+CREATE TABLE IF NOT EXISTS auth_audit_log (
+    id           BIGSERIAL PRIMARY KEY,
+    user_id      UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    event_type   TEXT NOT NULL,
+    ip           INET,
+    user_agent   TEXT,
+    metadata     JSONB,
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS idx_auth_audit_log_user_created
+    ON auth_audit_log (user_id, created_at DESC, id DESC);
+
+COMMIT;
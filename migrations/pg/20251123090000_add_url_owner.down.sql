@@ -0,0 +1,6 @@
+BEGIN;
+
+DROP INDEX IF EXISTS idx_urls_owner_id;
+ALTER TABLE urls RENAME COLUMN owner_id TO created_by;
+
+COMMIT;
@@ -0,0 +1,10 @@
+BEGIN;
+
+-- The account that shortened the URL, when it was shortened while signed in.
+-- Reuses the never-populated `created_by` column, which already references
+-- users(id) ON DELETE SET NULL.
+ALTER TABLE urls RENAME COLUMN created_by TO owner_id;
+
+CREATE INDEX IF NOT EXISTS idx_urls_owner_id ON urls (owner_id, id);
+
+COMMIT;
@@ -0,0 +1,5 @@
+BEGIN;
+
+COMMENT ON COLUMN urls.url IS NULL;
+
+COMMIT;
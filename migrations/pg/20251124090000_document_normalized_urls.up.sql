@@ -0,0 +1,8 @@
+BEGIN;
+
+-- Destinations are stored as normalized by the application: lowercase host, no
+-- fragment, and non-ASCII path characters percent-encoded with uppercase hex,
+-- so equal URLs compare equal for deduplication and the Bloom filter.
+COMMENT ON COLUMN urls.url IS 'Normalized destination URL; non-ASCII path characters are percent-encoded with uppercase hex';
+
+COMMIT;
@@ -0,0 +1,6 @@
+BEGIN;
+
+ALTER TABLE clicks DROP COLUMN IF EXISTS variant;
+DROP TABLE IF EXISTS urls_ab_targets;
+
+COMMIT;
@@ -0,0 +1,16 @@
+BEGIN;
+
+-- Destinations of an A/B tested URL, drawn by weight on every redirect.
+-- Weights of one URL sum to 100; `idx` is the variant recorded with a click.
+CREATE TABLE IF NOT EXISTS urls_ab_targets (
+  url_id      BIGINT NOT NULL REFERENCES urls(id) ON DELETE CASCADE,
+  idx         SMALLINT NOT NULL,
+  target_url  TEXT NOT NULL,
+  weight      SMALLINT NOT NULL CHECK (weight BETWEEN 1 AND 100),
+  PRIMARY KEY (url_id, idx)
+);
+
+-- The A/B variant a click was sent to, NULL for URLs without variants.
+ALTER TABLE clicks ADD COLUMN IF NOT EXISTS variant SMALLINT;
+
+COMMIT;
@@ -0,0 +1,5 @@
+BEGIN;
+
+ALTER TABLE clicks RENAME COLUMN referrer_origin TO referrer;
+
+COMMIT;
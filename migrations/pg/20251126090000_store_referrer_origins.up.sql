@@ -0,0 +1,11 @@
+BEGIN;
+
+-- Only the origin (scheme://host[:port]) of a click's Referer is kept, so
+-- paths and query strings of the referring pages are not stored.
+ALTER TABLE clicks RENAME COLUMN referrer TO referrer_origin;
+
+UPDATE clicks
+SET referrer_origin = substring(referrer_origin from '^[A-Za-z][A-Za-z0-9+.-]*://[^/?#]+')
+WHERE referrer_origin IS NOT NULL;
+
+COMMIT;
@@ -0,0 +1,6 @@
+BEGIN;
+
+ALTER TABLE clicks DROP COLUMN IF EXISTS device_type;
+ALTER TABLE urls DROP COLUMN IF EXISTS mobile_url;
+
+COMMIT;
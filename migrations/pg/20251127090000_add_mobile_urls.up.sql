@@ -0,0 +1,7 @@
+BEGIN;
+
+-- Destination for visitors on mobile devices, and the device class of each click.
+ALTER TABLE urls ADD COLUMN IF NOT EXISTS mobile_url TEXT;
+ALTER TABLE clicks ADD COLUMN IF NOT EXISTS device_type TEXT;
+
+COMMIT;
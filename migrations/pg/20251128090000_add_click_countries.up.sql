@@ -0,0 +1,6 @@
+BEGIN;
+
+-- ISO 3166-1 alpha-2 country of each click, from the GeoLite2 lookup of its IP.
+ALTER TABLE clicks ADD COLUMN IF NOT EXISTS country_code CHAR(2);
+
+COMMIT;
@@ -0,0 +1,6 @@
+BEGIN;
+
+ALTER TABLE urls DROP COLUMN IF EXISTS deactivated_at;
+ALTER TABLE urls DROP COLUMN IF EXISTS max_clicks;
+
+COMMIT;
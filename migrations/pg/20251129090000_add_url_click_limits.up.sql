@@ -0,0 +1,7 @@
+BEGIN;
+
+-- Optional click limit of each URL, and when the URL was deactivated for reaching it.
+ALTER TABLE urls ADD COLUMN IF NOT EXISTS max_clicks BIGINT;
+ALTER TABLE urls ADD COLUMN IF NOT EXISTS deactivated_at TIMESTAMPTZ;
+
+COMMIT;
@@ -0,0 +1,6 @@
+BEGIN;
+
+-- When the owner of a URL was warned about its upcoming expiry, so they are warned once.
+ALTER TABLE urls ADD COLUMN IF NOT EXISTS expiry_reminder_sent_at TIMESTAMPTZ;
+
+COMMIT;
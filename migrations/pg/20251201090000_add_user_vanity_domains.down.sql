@@ -0,0 +1,6 @@
+BEGIN;
+
+DROP INDEX IF EXISTS idx_users_vanity_domain;
+ALTER TABLE users DROP COLUMN IF EXISTS vanity_domain;
+
+COMMIT;
@@ -0,0 +1,8 @@
+BEGIN;
+
+-- The host a user's short URLs are shared on instead of the shortener's own,
+-- e.g. go.mycompany.com; a domain belongs to at most one user.
+ALTER TABLE users ADD COLUMN IF NOT EXISTS vanity_domain TEXT;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_vanity_domain ON users (vanity_domain);
+
+COMMIT;
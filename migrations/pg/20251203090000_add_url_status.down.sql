@@ -0,0 +1,48 @@
+BEGIN;
+
+CREATE OR REPLACE FUNCTION public.upsert_url(_code text, _url text)
+RETURNS TABLE(id bigint, created boolean)
+LANGUAGE plpgsql AS $$
+BEGIN
+  RETURN QUERY
+  INSERT INTO urls(code, url) VALUES (_code, _url) ON CONFLICT (url_hash) DO NOTHING
+  RETURNING urls.id, TRUE;
+
+  IF FOUND THEN
+    RETURN;
+  END IF;
+
+  RETURN QUERY
+  SELECT u.id, FALSE FROM urls u WHERE u.url_hash = digest(_url, 'sha256') LIMIT 1;
+
+  RETURN;
+END $$;
+
+CREATE OR REPLACE VIEW all_short_codes AS
+  SELECT
+    u.code        AS code,
+    u.id          AS target_id,
+    u.url         AS url,
+    'code'::text  AS source
+  FROM urls u
+
+  UNION ALL
+
+  SELECT
+    a.alias       AS code,
+    a.target_id   AS target_id,
+    u.url         AS url,
+    'alias'::text AS source
+  FROM aliases a
+  JOIN urls u ON u.id = a.target_id;
+
+-- Soft-deleted rows may share a destination with a live one
+DELETE FROM urls WHERE status = 'deleted';
+
+DROP INDEX IF EXISTS uniq_url_hash;
+CREATE UNIQUE INDEX uniq_url_hash ON urls (url_hash) INCLUDE (id);
+ALTER INDEX uniq_url_hash SET (fillfactor = 90);
+
+ALTER TABLE urls DROP COLUMN IF EXISTS status;
+
+COMMIT;
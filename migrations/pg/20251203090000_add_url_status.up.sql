@@ -0,0 +1,63 @@
+BEGIN;
+
+-- Lifecycle status of each URL. `deactivated_at`, `expires_at` and
+-- `password_hash` stay the source of the details; `status` says which of them
+-- currently applies, and marks soft-deleted URLs.
+ALTER TABLE urls ADD COLUMN IF NOT EXISTS status TEXT NOT NULL DEFAULT 'active'
+  CHECK (status IN ('active', 'expired', 'deleted', 'password_protected', 'max_clicks_reached'));
+
+UPDATE urls SET status = 'max_clicks_reached' WHERE deactivated_at IS NOT NULL;
+UPDATE urls SET status = 'expired'
+  WHERE status = 'active' AND expires_at IS NOT NULL AND expires_at <= now();
+UPDATE urls SET status = 'password_protected'
+  WHERE status = 'active' AND password_hash IS NOT NULL;
+
+-- A deleted URL keeps its row, so only live URLs need unique destinations
+DROP INDEX IF EXISTS uniq_url_hash;
+CREATE UNIQUE INDEX uniq_url_hash ON urls (url_hash) INCLUDE (id)
+  WHERE status <> 'deleted';
+ALTER INDEX uniq_url_hash SET (fillfactor = 90);
+
+-- Deleted URLs and their aliases no longer resolve
+CREATE OR REPLACE VIEW all_short_codes AS
+  SELECT
+    u.code        AS code,
+    u.id          AS target_id,
+    u.url         AS url,
+    'code'::text  AS source
+  FROM urls u
+  WHERE u.status <> 'deleted'
+
+  UNION ALL
+
+  SELECT
+    a.alias       AS code,
+    a.target_id   AS target_id,
+    u.url         AS url,
+    'alias'::text AS source
+  FROM aliases a
+  JOIN urls u ON u.id = a.target_id
+  WHERE u.status <> 'deleted';
+
+CREATE OR REPLACE FUNCTION public.upsert_url(_code text, _url text)
+RETURNS TABLE(id bigint, created boolean)
+LANGUAGE plpgsql AS $$
+BEGIN
+  RETURN QUERY
+  INSERT INTO urls(code, url) VALUES (_code, _url)
+  ON CONFLICT (url_hash) WHERE status <> 'deleted' DO NOTHING
+  RETURNING urls.id, TRUE;
+
+  IF FOUND THEN
+    RETURN;
+  END IF;
+
+  RETURN QUERY
+  SELECT u.id, FALSE FROM urls u
+  WHERE u.url_hash = digest(_url, 'sha256') AND u.status <> 'deleted'
+  LIMIT 1;
+
+  RETURN;
+END $$;
+
+COMMIT;
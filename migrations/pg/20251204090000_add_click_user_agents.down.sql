@@ -0,0 +1,5 @@
+BEGIN;
+
+ALTER TABLE clicks DROP COLUMN IF EXISTS user_agent;
+
+COMMIT;
@@ -0,0 +1,6 @@
+BEGIN;
+
+-- `User-Agent` header of each click, for the per-link click history.
+ALTER TABLE clicks ADD COLUMN IF NOT EXISTS user_agent TEXT;
+
+COMMIT;
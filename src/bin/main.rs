@@ -14,6 +14,18 @@
 //!
 //! # Run with custom configuration
 //! APP_APPLICATION__PORT=3000 cargo run
+//!
+//! # Print a bcrypt hash of an API key for `application.api_keys`
+//! cargo run -- --hash-api-key e4125dd1-3d3e-43a1-bc9c-dc0ba12ad4b5
+//!
+//! # Run the database migrations and exit, e.g. in an init container
+//! cargo run -- migrate
+//!
+//! # Refill the Bloom filter from the database, e.g. after a restore
+//! cargo run -- rebuild-bloom
+//!
+//! # Print a new API key
+//! cargo run -- create-api-key
 //! ```
 //!
 //! ## Configuration
@@ -21,13 +33,78 @@
 //! The application reads configuration from YAML files in the `configuration/` directory
 //! and environment variables. See the library documentation for more details.
 
-use url_shortener_ztm_lib::configuration::get_configuration;
-use url_shortener_ztm_lib::startup::Application;
-use url_shortener_ztm_lib::telemetry::{get_subscriber, init_subscriber};
-use uuid::Uuid;
+use clap::{Arg, Command};
+use url_shortener_ztm_lib::configuration::{Environment, ValidatedSettings, get_configuration};
+use url_shortener_ztm_lib::core::security::api_key::hash_api_key;
+use url_shortener_ztm_lib::shortcode::bloom_filter::{build_bloom_state, rebuild_bloom_from_db};
+use url_shortener_ztm_lib::shortcode::snapshot_store::build_snapshot_store;
+use url_shortener_ztm_lib::startup::{Application, connect_database};
+use url_shortener_ztm_lib::telemetry::{get_subscriber, init_subscriber, log_startup_banner};
+#[cfg(feature = "opentelemetry")]
+use url_shortener_ztm_lib::telemetry::{init_otlp_subscriber, shutdown_otlp};
+
+/// Command line interface; without a subcommand the server is started.
+fn cli() -> Command {
+    Command::new("url-shortener-ztm")
+        .about("URL shortener service")
+        .arg(
+            Arg::new("hash-api-key")
+                .long("hash-api-key")
+                .value_name("UUID")
+                .help("Print a bcrypt hash of an API key for `application.api_keys` and exit"),
+        )
+        .subcommand(Command::new("serve").about("Start the HTTP server (default)"))
+        .subcommand(
+            Command::new("rebuild-bloom")
+                .about("Refill the Bloom filter from the database and save its snapshot"),
+        )
+        .subcommand(Command::new("migrate").about("Run the database migrations and exit"))
+        .subcommand(Command::new("create-api-key").about("Print a new random API key"))
+}
 
 /// Main function - the application entry point.
 ///
+/// Runs the requested subcommand, or [`serve`] when none is given.
+///
+/// # Errors
+///
+/// Returns an error if the subcommand fails.
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let matches = cli().get_matches();
+
+    // `--hash-api-key <uuid>` prints a hash for the configuration and exits
+    if let Some(key) = matches.get_one::<String>("hash-api-key") {
+        println!("{}", hash_api_key(key)?);
+        return Ok(());
+    }
+
+    match matches.subcommand_name() {
+        Some("create-api-key") => {
+            println!("{}", uuid::Uuid::new_v4());
+            Ok(())
+        }
+        Some("migrate") => {
+            let configuration = get_configuration()?;
+            connect_database(&configuration.database).await?;
+            println!("Database migrations applied.");
+            Ok(())
+        }
+        Some("rebuild-bloom") => {
+            let configuration = get_configuration()?;
+            let db = connect_database(&configuration.database).await?;
+            let store = build_snapshot_store(&configuration.bloom.storage, &db).await?;
+            let blooms = build_bloom_state(&db, &store, &configuration.bloom).await?;
+            let items = rebuild_bloom_from_db(&db, &store, &blooms, &configuration.bloom).await?;
+            println!("Bloom filter rebuilt with {} short codes.", items);
+            Ok(())
+        }
+        _ => serve().await,
+    }
+}
+
+/// Starts the HTTP server.
+///
 /// This function:
 /// 1. Initializes structured logging with tracing
 /// 2. Loads application configuration from files and environment variables
@@ -37,32 +114,40 @@ use uuid::Uuid;
 /// # Errors
 ///
 /// Returns an error if:
-/// - Configuration cannot be loaded
+/// - Configuration cannot be loaded or fails validation
 /// - Database connection fails
 /// - Server fails to start
 /// - Any other critical error occurs
-#[tokio::main]
-async fn main() -> anyhow::Result<()> {
+async fn serve() -> anyhow::Result<()> {
+    // Load application configuration from YAML files and environment variables.
+    // This comes first so the subscriber can include OTLP export when configured.
+    let configuration = get_configuration().expect("Failed to read configuration files.");
+
     // Initialize structured logging with tracing
-    tracing::info!("Initializing tracing...");
     let subscriber = get_subscriber("url-shortener-ztm".into(), "info".into(), std::io::stdout);
+    #[cfg(feature = "opentelemetry")]
+    let subscriber = init_otlp_subscriber(subscriber, &configuration.otlp)?;
     init_subscriber(subscriber);
-
-    // Load application configuration from YAML files and environment variables
-    tracing::info!("Reading configuration...");
-    let configuration = get_configuration().expect("Failed to read configuration files.");
+    #[cfg(not(feature = "opentelemetry"))]
+    if configuration.otlp.enabled {
+        tracing::warn!(
+            "otlp.enabled is set, but this binary was built without the `opentelemetry` feature"
+        );
+    }
     tracing::info!(%configuration, "Configuration loaded");
 
-    // Detect default development API key and emit a prominent warning
-    // This warns developers if they are accidentally using the insecure default key.
-    let default_dev_key = Uuid::parse_str("e4125dd1-3d3e-43a1-bc9c-dc0ba12ad4b5").unwrap();
-    if configuration.application.api_key == default_dev_key {
+    // Detect default development API key and emit a prominent warning in local
+    // and staging, so developers notice if they are accidentally using the insecure
+    // default key before it reaches production.
+    if configuration.environment != Environment::Production
+        && configuration.application.uses_default_api_key()
+    {
         // Use a big banner to make this stand out in logs
         tracing::warn!(
             "\n============================================================\n\
             SECURITY WARNING: USING DEFAULT DEVELOPMENT API KEY\n\
             ----------------------------------------------------\n\
-            The configured API key matches the development default.\n\
+            A configured API key, or API key hash, matches the development default.\n\
             This key is PUBLIC and MUST NOT be used in production.\n\
 \n\
             To set a secure key (UUID v4):\n\
@@ -71,16 +156,20 @@ async fn main() -> anyhow::Result<()> {
 \n\
             Current environment: {env}\n\
 ============================================================",
-            env = std::env::var("APP_ENVIRONMENT").unwrap_or_else(|_| "local".into())
+            env = configuration.environment.as_str()
         );
     }
 
     // Build the application with database connection and router setup
     tracing::info!("Starting up the application...");
+    let configuration = ValidatedSettings::try_from(configuration)?;
     let application = Application::build(configuration.clone()).await?;
+    log_startup_banner(&configuration, application.port());
 
     // Run the server until stopped (handles graceful shutdown)
     application.run_until_stopped().await?;
+    #[cfg(feature = "opentelemetry")]
+    shutdown_otlp();
 
     Ok(())
 }
@@ -3,7 +3,7 @@ use crate::{
     ApiError, core::security::jwt::JwtKeys, features::users::repositories::UserRepository,
 };
 use axum::{
-    extract::{FromRef, FromRequestParts},
+    extract::{FromRef, FromRequestParts, OptionalFromRequestParts},
     http::request::Parts,
 };
 use axum_extra::TypedHeader;
@@ -55,19 +55,44 @@ where
     }
 }
 
+/// Lets handlers take `Option<AuthenticatedUser>` where signing in is optional.
+///
+/// Yields `None` when no access token was sent; a token that was sent but does
+/// not verify is still rejected rather than treated as anonymous.
+impl<S> OptionalFromRequestParts<S> for AuthenticatedUser
+where
+    S: Send + Sync,
+    Arc<AuthService>: FromRef<S>,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &S,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        if extract_token(parts, state).await.is_none() {
+            return Ok(None);
+        }
+        <Self as FromRequestParts<S>>::from_request_parts(parts, state)
+            .await
+            .map(Some)
+    }
+}
+
 async fn extract_token<S>(parts: &mut Parts, state: &S) -> Option<String>
 where
     S: Send + Sync,
 {
     // Try cookie first
-    if let Ok(TypedHeader(cookies)) = TypedHeader::<Cookie>::from_request_parts(parts, state).await
+    if let Ok(TypedHeader(cookies)) =
+        <TypedHeader<Cookie> as FromRequestParts<S>>::from_request_parts(parts, state).await
         && let Some(token) = cookies.get("access_token")
     {
         return Some(token.to_owned());
     }
 
     // Fall back to Authorization header
-    TypedHeader::<Authorization<Bearer>>::from_request_parts(parts, state)
+    <TypedHeader<Authorization<Bearer>> as FromRequestParts<S>>::from_request_parts(parts, state)
         .await
         .ok()
         .map(|TypedHeader(Authorization(bearer))| bearer.token().to_owned())
@@ -0,0 +1,232 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::configuration::{ApiKey, ApiKeySecret};
+use moka::sync::Cache;
+use parking_lot::RwLock;
+use sha2::{Digest, Sha256};
+use subtle::{Choice, ConstantTimeEq};
+use tokio::sync::Semaphore;
+use uuid::Uuid;
+
+/// Unknown keys remembered at most, so they are not checked with bcrypt again.
+const REJECTED_CAPACITY: u64 = 10_000;
+
+/// How long an unknown key is remembered; a key added to the configuration
+/// takes effect on restart, so this only has to bound memory.
+const REJECTED_TTL: Duration = Duration::from_secs(600);
+
+/// bcrypt verifications allowed to run at once. A flood of fresh random keys
+/// waits here instead of filling the blocking thread pool.
+const MAX_CONCURRENT_VERIFICATIONS: usize = 2;
+
+/// The API keys accepted by [`check_api_key`](crate::middleware::check_api_key).
+///
+/// Plain keys are compared in constant time. Hashed keys cost a bcrypt
+/// verification, so once a key has matched a hash, a SHA-256 digest of it is
+/// remembered and later requests with the same key skip bcrypt. Keys that
+/// matched no hash are remembered for ten minutes, and only two bcrypt checks
+/// run at a time, so unknown keys cannot keep every core busy.
+///
+/// `Debug` only reports how many keys there are.
+pub struct ApiKeyStore {
+    plain: Vec<[u8; 16]>,
+    hashed: Arc<[String]>,
+    verified: RwLock<HashSet<[u8; 32]>>,
+    rejected: Cache<[u8; 32], ()>,
+    verifying: Semaphore,
+}
+
+impl Default for ApiKeyStore {
+    fn default() -> Self {
+        Self::new(&[])
+    }
+}
+
+impl fmt::Debug for ApiKeyStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ApiKeyStore")
+            .field("plain", &self.plain.len())
+            .field("hashed", &self.hashed.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl ApiKeyStore {
+    pub fn new(keys: &[ApiKey]) -> Self {
+        let mut plain = Vec::new();
+        let mut hashed = Vec::new();
+        for key in keys {
+            match &key.key {
+                ApiKeySecret::Plain(key) => plain.push(key.into_bytes()),
+                ApiKeySecret::Bcrypt(hash) => hashed.push(hash.clone()),
+            }
+        }
+        Self {
+            plain,
+            hashed: hashed.into(),
+            verified: RwLock::new(HashSet::new()),
+            rejected: Cache::builder()
+                .max_capacity(REJECTED_CAPACITY)
+                .time_to_live(REJECTED_TTL)
+                .build(),
+            verifying: Semaphore::new(MAX_CONCURRENT_VERIFICATIONS),
+        }
+    }
+
+    /// Number of configured keys, plain and hashed.
+    pub fn len(&self) -> usize {
+        self.plain.len() + self.hashed.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether `provided` is one of the configured keys.
+    ///
+    /// bcrypt runs on the blocking thread pool, so an unknown key does not
+    /// stall the async workers while every hash is tried.
+    pub async fn contains(&self, provided: &Uuid) -> bool {
+        if is_known_api_key(&self.plain, provided.as_bytes()) {
+            return true;
+        }
+        if self.hashed.is_empty() {
+            return false;
+        }
+
+        let digest: [u8; 32] = Sha256::digest(provided.as_bytes()).into();
+        if self.verified.read().contains(&digest) {
+            return true;
+        }
+        if self.rejected.contains_key(&digest) {
+            return false;
+        }
+
+        let Ok(_permit) = self.verifying.acquire().await else {
+            return false;
+        };
+        let hashed = self.hashed.clone();
+        let candidate = provided.hyphenated().to_string();
+        let matched = tokio::task::spawn_blocking(move || {
+            hashed
+                .iter()
+                .any(|hash| bcrypt::verify(&candidate, hash).unwrap_or(false))
+        })
+        .await
+        .unwrap_or(false);
+
+        if matched {
+            self.verified.write().insert(digest);
+        } else {
+            self.rejected.insert(digest, ());
+        }
+        matched
+    }
+}
+
+/// Whether `provided` equals one of `stored`, in constant time.
+///
+/// Every stored key is compared and the results are OR-ed together, so the
+/// time taken depends only on how many keys are configured, not on which key
+/// matched or how many leading bytes a wrong guess got right.
+fn is_known_api_key(stored: &[[u8; 16]], provided: &[u8; 16]) -> bool {
+    stored
+        .iter()
+        .fold(Choice::from(0), |found, key| found | key.ct_eq(provided))
+        .into()
+}
+
+/// Hashes an API key for the `api_keys` configuration, as done by
+/// `url-shortener-ztm --hash-api-key <uuid>`.
+///
+/// # Errors
+///
+/// Returns an error if `key` is not a UUID.
+pub fn hash_api_key(key: &str) -> anyhow::Result<String> {
+    hash_api_key_with_cost(key, bcrypt::DEFAULT_COST)
+}
+
+fn hash_api_key_with_cost(key: &str, cost: u32) -> anyhow::Result<String> {
+    let key = Uuid::parse_str(key.trim())?;
+    Ok(bcrypt::hash(key.hyphenated().to_string(), cost)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the lowest cost bcrypt allows, to keep the tests fast
+    const TEST_COST: u32 = 4;
+
+    fn plain(key: Uuid) -> ApiKey {
+        ApiKey {
+            key: key.into(),
+            label: None,
+        }
+    }
+
+    fn hashed(key: Uuid) -> ApiKey {
+        ApiKey {
+            key: ApiKeySecret::Bcrypt(hash_api_key_with_cost(&key.to_string(), TEST_COST).unwrap()),
+            label: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn plain_keys_are_matched() {
+        let key = Uuid::new_v4();
+        let store = ApiKeyStore::new(&[plain(key)]);
+
+        assert!(store.contains(&key).await);
+        assert!(!store.contains(&Uuid::new_v4()).await);
+    }
+
+    #[tokio::test]
+    async fn hashed_keys_are_matched_with_bcrypt() {
+        let key = Uuid::new_v4();
+        let store = ApiKeyStore::new(&[plain(Uuid::new_v4()), hashed(key)]);
+
+        assert!(store.contains(&key).await);
+        assert!(!store.contains(&Uuid::new_v4()).await);
+        assert_eq!(store.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn a_verified_hashed_key_is_remembered() {
+        let key = Uuid::new_v4();
+        let store = ApiKeyStore::new(&[hashed(key)]);
+
+        assert!(store.contains(&key).await);
+
+        let digest: [u8; 32] = Sha256::digest(key.as_bytes()).into();
+        assert!(store.verified.read().contains(&digest));
+        assert_eq!(store.verified.read().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn an_unknown_key_is_remembered_as_rejected() {
+        let key = Uuid::new_v4();
+        let unknown = Uuid::new_v4();
+        let store = ApiKeyStore::new(&[hashed(key)]);
+
+        assert!(!store.contains(&unknown).await);
+
+        let digest: [u8; 32] = Sha256::digest(unknown.as_bytes()).into();
+        assert!(store.rejected.contains_key(&digest));
+        assert!(!store.contains(&unknown).await);
+        assert!(store.contains(&key).await);
+    }
+
+    #[test]
+    fn hash_api_key_output_verifies_against_the_key() {
+        let key = Uuid::new_v4();
+        let hash = hash_api_key_with_cost(&key.to_string().to_uppercase(), TEST_COST).unwrap();
+
+        assert!(hash.starts_with("$2b$"));
+        assert!(ApiKeySecret::try_from(hash).unwrap().matches(&key));
+        assert!(hash_api_key("not-a-uuid").is_err());
+    }
+}
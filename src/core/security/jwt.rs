@@ -4,6 +4,7 @@ use chrono::{Duration, Utc};
 use hmac::Mac;
 use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, encode};
 use serde::{Deserialize, Serialize};
+use std::fmt;
 use uuid::Uuid;
 
 use crate::core::security::HmacSha256;
@@ -22,6 +23,12 @@ pub struct JwtKeys {
     validation: Validation,
 }
 
+impl fmt::Debug for JwtKeys {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("JwtKeys([REDACTED])")
+    }
+}
+
 impl JwtKeys {
     pub fn new(secret: &[u8]) -> Self {
         let mut val = Validation::new(Algorithm::HS256);
@@ -1,5 +1,7 @@
+pub mod api_key;
 pub mod jwt;
 pub mod password;
+pub mod unlock;
 
 use hmac::Hmac;
 use sha2::Sha256;
@@ -1,5 +1,6 @@
 use std::ops::Deref;
 
+use crate::configuration::Argon2Settings;
 use anyhow::{Result, anyhow};
 use argon2::{
     Algorithm, Argon2, Params, PasswordHash, PasswordHasher, PasswordVerifier, Version,
@@ -41,19 +42,30 @@ fn is_disallowed_format_char(c: char) -> bool {
     )
 }
 
-fn normalize(pw: &str) -> Result<String> {
-    let norm: String = pw.nfc().collect();
+/// NFC-normalizes user-entered `text`, rejecting control characters and
+/// invisible formatting characters such as zero-width spaces; `what` names
+/// the value in the error.
+pub(crate) fn normalize_text(text: &str, what: &str) -> Result<String> {
+    let norm: String = text.nfc().collect();
 
     anyhow::ensure!(
         !norm.chars().any(|c| c.is_control()),
-        "password contains disallowed control characters"
+        "{} contains disallowed control characters",
+        what
     );
 
     anyhow::ensure!(
         !norm.chars().any(is_disallowed_format_char),
-        "password contains disallowed invisible characters"
+        "{} contains disallowed invisible characters",
+        what
     );
 
+    Ok(norm)
+}
+
+fn normalize(pw: &str) -> Result<String> {
+    let norm = normalize_text(pw, "password")?;
+
     let bytes = norm.as_bytes();
     anyhow::ensure!(bytes.len() <= MAX_PW_BYTES, "password too long");
 
@@ -99,16 +111,19 @@ fn validate_verification_code(code: &str) -> anyhow::Result<Vec<u8>> {
     Ok(code.as_bytes().to_vec())
 }
 
-fn build_argon2(pepper: &[u8]) -> Result<Argon2<'_>> {
-    let params =
-        Params::new(16 * 1024, 3, 1, None).map_err(|e| anyhow!("invalid argon2 params: {e}"))?;
+fn build_argon2(pepper: &[u8], params: Params) -> Result<Argon2<'_>> {
     Argon2::new_with_secret(pepper, Algorithm::Argon2id, Version::V0x13, params)
         .map_err(|e| anyhow!("argon2 init failed: {e}"))
 }
 
-fn hash_with_argon2(material: &[u8], pepper: &str) -> Result<Vec<u8>> {
+fn argon2_params(settings: &Argon2Settings) -> Result<Params> {
+    Params::new(settings.m_cost_kib, settings.t_cost, settings.p_cost, None)
+        .map_err(|e| anyhow!("invalid argon2 params: {e}"))
+}
+
+fn hash_with_argon2(material: &[u8], pepper: &str, settings: &Argon2Settings) -> Result<Vec<u8>> {
     let salt = SaltString::generate(&mut OsRng);
-    let hasher = build_argon2(pepper.as_bytes())?;
+    let hasher = build_argon2(pepper.as_bytes(), argon2_params(settings)?)?;
     let phc = hasher
         .hash_password(material, &salt)
         .map_err(|e| anyhow!("argon2 hash error: {e}"))?
@@ -118,15 +133,39 @@ fn hash_with_argon2(material: &[u8], pepper: &str) -> Result<Vec<u8>> {
     Ok(phc)
 }
 
-fn verify_with_argon2(material: &[u8], stored_phc: &[u8], pepper: &str) -> Result<bool> {
-    let parsed = PasswordHash::new(std::str::from_utf8(stored_phc)?)
-        .map_err(|e| anyhow::Error::msg(e.to_string()))?;
-    let hasher = build_argon2(pepper.as_bytes())?;
-    let ok = hasher.verify_password(material, &parsed).is_ok();
+fn parse_phc(stored_phc: &[u8]) -> Result<PasswordHash<'_>> {
+    PasswordHash::new(std::str::from_utf8(stored_phc)?)
+        .map_err(|e| anyhow::Error::msg(e.to_string()))
+}
+
+// The hash is checked with the parameters recorded in it, not the configured ones
+fn verify_with_argon2(material: &[u8], parsed: &PasswordHash<'_>, pepper: &str) -> Result<bool> {
+    let params = Params::try_from(parsed).map_err(|e| anyhow!("invalid argon2 params: {e}"))?;
+    let hasher = build_argon2(pepper.as_bytes(), params)?;
+    let ok = hasher.verify_password(material, parsed).is_ok();
 
     Ok(ok)
 }
 
+/// Hashes `material` again when `parsed` was made with other parameters
+/// than `settings`; returns `None` when the stored hash is current.
+fn rehash_if_params_changed(
+    material: &[u8],
+    parsed: &PasswordHash<'_>,
+    pepper: &str,
+    settings: &Argon2Settings,
+) -> Result<Option<Vec<u8>>> {
+    let stored = Params::try_from(parsed).map_err(|e| anyhow!("invalid argon2 params: {e}"))?;
+    let current = argon2_params(settings)?;
+    if (stored.m_cost(), stored.t_cost(), stored.p_cost())
+        == (current.m_cost(), current.t_cost(), current.p_cost())
+    {
+        return Ok(None);
+    }
+
+    hash_with_argon2(material, pepper, settings).map(Some)
+}
+
 pub fn validate_policy(norm: &NormalizedPassword) -> Result<()> {
     let char_count = norm.graphemes(true).count();
     anyhow::ensure!(
@@ -140,26 +179,75 @@ pub fn validate_policy(norm: &NormalizedPassword) -> Result<()> {
     Ok(())
 }
 
-pub fn hash_password(norm: &NormalizedPassword, pepper: &str) -> Result<Vec<u8>> {
-    hash_with_argon2(norm.as_bytes(), pepper)
+/// Result of checking a password against its stored hash.
+#[derive(Debug, PartialEq, Eq)]
+pub enum PasswordVerification {
+    /// The password does not match
+    Invalid,
+    /// The password matches a hash made with the configured parameters
+    Valid,
+    /// The password matches a hash made with older parameters; the fresh
+    /// hash should replace the stored one
+    Rehashed(Vec<u8>),
+}
+
+impl PasswordVerification {
+    /// Whether the password matched, with or without a rehash.
+    pub fn is_valid(&self) -> bool {
+        !matches!(self, PasswordVerification::Invalid)
+    }
+}
+
+pub fn hash_password(
+    norm: &NormalizedPassword,
+    pepper: &str,
+    settings: &Argon2Settings,
+) -> Result<Vec<u8>> {
+    hash_with_argon2(norm.as_bytes(), pepper, settings)
 }
 
-pub fn verify_password(pwd: &SecretString, stored_phc: &[u8], pepper: &str) -> Result<bool> {
+/// Checks `pwd` against `stored_phc`.
+///
+/// Hashes made before the Argon2 parameters were changed still verify, since
+/// the PHC string records the parameters it was made with. A matching
+/// password whose hash uses other parameters than `settings` is hashed again
+/// and returned as [`PasswordVerification::Rehashed`] for the caller to store,
+/// so accounts move to the new parameters as their users sign in.
+pub fn verify_password(
+    pwd: &SecretString,
+    stored_phc: &[u8],
+    pepper: &str,
+    settings: &Argon2Settings,
+) -> Result<PasswordVerification> {
     let norm = NormalizedPassword::try_from(pwd)?;
+    let parsed = parse_phc(stored_phc)?;
 
-    verify_with_argon2(norm.as_bytes(), stored_phc, pepper)
+    if !verify_with_argon2(norm.as_bytes(), &parsed, pepper)? {
+        return Ok(PasswordVerification::Invalid);
+    }
+    Ok(
+        match rehash_if_params_changed(norm.as_bytes(), &parsed, pepper, settings)? {
+            Some(phc) => PasswordVerification::Rehashed(phc),
+            None => PasswordVerification::Valid,
+        },
+    )
 }
 
-pub fn hash_verification_code(code: &str, pepper: &str) -> Result<Vec<u8>> {
+pub fn hash_verification_code(
+    code: &str,
+    pepper: &str,
+    settings: &Argon2Settings,
+) -> Result<Vec<u8>> {
     let mut material = validate_verification_code(code)?;
-    let result = hash_with_argon2(&material, pepper);
+    let result = hash_with_argon2(&material, pepper, settings);
     material.zeroize();
     result
 }
 
 pub fn verify_verification_code(code: &str, stored_phc: &[u8], pepper: &str) -> Result<bool> {
     let mut material = validate_verification_code(code)?;
-    let result = verify_with_argon2(&material, stored_phc, pepper);
+    let result =
+        parse_phc(stored_phc).and_then(|parsed| verify_with_argon2(&material, &parsed, pepper));
     material.zeroize();
     result
 }
@@ -181,6 +269,10 @@ mod tests {
 
     const TEST_PEPPER: &str = "test-pepper-secret";
 
+    fn settings() -> Argon2Settings {
+        Argon2Settings::default()
+    }
+
     #[test]
     fn test_normalized_password_deref() {
         let norm = NormalizedPassword::try_from("TestPassword").unwrap();
@@ -386,10 +478,10 @@ mod tests {
         let password = SecretString::new("MySecurePassword123!".into());
         let norm = NormalizedPassword::try_from(&password).unwrap();
 
-        let hash = hash_password(&norm, TEST_PEPPER).unwrap();
-        let verify_result = verify_password(&password, &hash, TEST_PEPPER).unwrap();
+        let hash = hash_password(&norm, TEST_PEPPER, &settings()).unwrap();
+        let verify_result = verify_password(&password, &hash, TEST_PEPPER, &settings()).unwrap();
 
-        assert!(verify_result);
+        assert_eq!(verify_result, PasswordVerification::Valid);
     }
 
     #[test]
@@ -398,16 +490,69 @@ mod tests {
         let wrong_password = SecretString::new("WrongPassword123!".into());
         let norm = NormalizedPassword::try_from(&password).unwrap();
 
-        let hash = hash_password(&norm, TEST_PEPPER).unwrap();
-        let verify_result = verify_password(&wrong_password, &hash, TEST_PEPPER).unwrap();
+        let hash = hash_password(&norm, TEST_PEPPER, &settings()).unwrap();
+        let verify_result =
+            verify_password(&wrong_password, &hash, TEST_PEPPER, &settings()).unwrap();
 
-        assert!(!verify_result);
+        assert_eq!(verify_result, PasswordVerification::Invalid);
+    }
+
+    #[test]
+    fn test_hash_from_old_params_verifies_and_is_rehashed_with_new_params() {
+        let password = SecretString::new("MySecurePassword123!".into());
+        let norm = NormalizedPassword::try_from(&password).unwrap();
+        let old = Argon2Settings {
+            m_cost_kib: 8 * 1024,
+            t_cost: 1,
+            p_cost: 1,
+        };
+        let new = Argon2Settings {
+            m_cost_kib: 12 * 1024,
+            t_cost: 2,
+            p_cost: 2,
+        };
+        let old_hash = hash_password(&norm, TEST_PEPPER, &old).unwrap();
+
+        // Reconfigured: the old hash still verifies and comes back rehashed
+        let PasswordVerification::Rehashed(new_hash) =
+            verify_password(&password, &old_hash, TEST_PEPPER, &new).unwrap()
+        else {
+            panic!("expected a rehash after the params changed");
+        };
+        let parsed = parse_phc(&new_hash).unwrap();
+        let params = Params::try_from(&parsed).unwrap();
+        assert_eq!(
+            (params.m_cost(), params.t_cost(), params.p_cost()),
+            (12 * 1024, 2, 2)
+        );
+
+        // The rehashed password verifies under the new params without another rehash
+        assert_eq!(
+            verify_password(&password, &new_hash, TEST_PEPPER, &new).unwrap(),
+            PasswordVerification::Valid
+        );
+    }
+
+    #[test]
+    fn test_wrong_password_is_not_rehashed_after_reconfiguration() {
+        let password = SecretString::new("MySecurePassword123!".into());
+        let wrong_password = SecretString::new("WrongPassword123!".into());
+        let norm = NormalizedPassword::try_from(&password).unwrap();
+        let hash = hash_password(&norm, TEST_PEPPER, &settings()).unwrap();
+        let new = Argon2Settings {
+            t_cost: 1,
+            ..settings()
+        };
+
+        let result = verify_password(&wrong_password, &hash, TEST_PEPPER, &new).unwrap();
+
+        assert_eq!(result, PasswordVerification::Invalid);
     }
 
     #[test]
     fn test_hash_and_verify_verification_code() {
         let code = "ABCD1234";
-        let hash = hash_verification_code(code, TEST_PEPPER).unwrap();
+        let hash = hash_verification_code(code, TEST_PEPPER, &settings()).unwrap();
         let verify_result = verify_verification_code(code, &hash, TEST_PEPPER).unwrap();
 
         assert!(verify_result);
@@ -417,7 +562,7 @@ mod tests {
     fn test_verify_verification_code_wrong_code() {
         let code = "ABCD1234";
         let wrong_code = "WXYZ5678";
-        let hash = hash_verification_code(code, TEST_PEPPER).unwrap();
+        let hash = hash_verification_code(code, TEST_PEPPER, &settings()).unwrap();
         let verify_result = verify_verification_code(wrong_code, &hash, TEST_PEPPER).unwrap();
 
         assert!(!verify_result);
@@ -0,0 +1,110 @@
+//! Tokens proving that a visitor entered the passphrase of a protected
+//! short URL.
+//!
+//! A token is `{expires}.{mac}`: the Unix time it stops being accepted and the
+//! hex HMAC-SHA256 of `{code}.{expires}.` followed by the stored passphrase
+//! hash. It holds no secret of its own, so it only needs to be unforgeable.
+//! Binding the code means a token for one link does not open another, and
+//! binding the hash means changing or removing the passphrase revokes every
+//! token issued under the old one.
+
+use chrono::{DateTime, Duration, Utc};
+use hmac::Mac;
+
+use crate::core::security::HmacSha256;
+
+fn mac(secret: &[u8], code: &str, password_hash: &[u8], expires: i64) -> HmacSha256 {
+    let mut mac =
+        <HmacSha256 as Mac>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(format!("{}.{}.", code, expires).as_bytes());
+    mac.update(password_hash);
+    mac
+}
+
+/// Issues a token unlocking `code`, protected by `password_hash`, until `ttl`
+/// after `now`.
+pub fn issue_unlock_token(
+    secret: &[u8],
+    code: &str,
+    password_hash: &[u8],
+    now: DateTime<Utc>,
+    ttl: Duration,
+) -> String {
+    let expires = (now + ttl).timestamp();
+    let tag = mac(secret, code, password_hash, expires)
+        .finalize()
+        .into_bytes();
+    format!("{}.{}", expires, hex::encode(tag))
+}
+
+/// Whether `token` was issued for `code` and `password_hash` with `secret`
+/// and is still valid at `now`. The MAC is compared in constant time.
+pub fn verify_unlock_token(
+    secret: &[u8],
+    code: &str,
+    password_hash: &[u8],
+    token: &str,
+    now: DateTime<Utc>,
+) -> bool {
+    let Some((expires, tag)) = token.split_once('.') else {
+        return false;
+    };
+    let (Ok(expires), Ok(tag)) = (expires.parse::<i64>(), hex::decode(tag)) else {
+        return false;
+    };
+    expires > now.timestamp()
+        && mac(secret, code, password_hash, expires)
+            .verify_slice(&tag)
+            .is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &[u8] = b"unlock-secret";
+    const HASH: &[u8] = b"$argon2id$v=19$m=8192,t=1,p=1$old";
+
+    #[test]
+    fn a_fresh_token_unlocks_its_code_only() {
+        let now = Utc::now();
+        let token = issue_unlock_token(SECRET, "AbC123", HASH, now, Duration::minutes(10));
+
+        assert!(verify_unlock_token(SECRET, "AbC123", HASH, &token, now));
+        assert!(!verify_unlock_token(SECRET, "XyZ789", HASH, &token, now));
+        assert!(!verify_unlock_token(
+            b"other-secret",
+            "AbC123",
+            HASH,
+            &token,
+            now
+        ));
+    }
+
+    #[test]
+    fn changing_the_passphrase_revokes_the_token() {
+        let now = Utc::now();
+        let token = issue_unlock_token(SECRET, "AbC123", HASH, now, Duration::minutes(10));
+        let changed = b"$argon2id$v=19$m=8192,t=1,p=1$new";
+
+        assert!(!verify_unlock_token(SECRET, "AbC123", changed, &token, now));
+    }
+
+    #[test]
+    fn expired_or_tampered_tokens_are_rejected() {
+        let now = Utc::now();
+        let token = issue_unlock_token(SECRET, "AbC123", HASH, now, Duration::minutes(10));
+        let (expires, tag) = token.split_once('.').unwrap();
+        let extended = format!("{}.{}", expires.parse::<i64>().unwrap() + 3600, tag);
+
+        assert!(!verify_unlock_token(
+            SECRET,
+            "AbC123",
+            HASH,
+            &token,
+            now + Duration::minutes(11)
+        ));
+        assert!(!verify_unlock_token(SECRET, "AbC123", HASH, &extended, now));
+        assert!(!verify_unlock_token(SECRET, "AbC123", HASH, "garbage", now));
+    }
+}
@@ -0,0 +1,884 @@
+//! # In-Memory Database Implementation
+//!
+//! This module provides an implementation of the [`UrlDatabase`] trait that
+//! keeps everything in [`DashMap`]s, for tests that should not pay for
+//! creating and migrating a SQLite database. Nothing is persisted.
+//!
+//! It is only compiled for the crate's own tests and with the `testing`
+//! feature:
+//!
+//! ```bash
+//! cargo test --features testing
+//! ```
+//!
+//! ## Behaviour
+//!
+//! The backend follows the SQLite and Postgres implementations, and passes
+//! the same contract tests:
+//! - Inserting a URL that is already stored returns its existing code, like
+//!   the upsert on the URL hash of the real databases
+//! - Aliases resolve to the URL they were created for, and are removed with it
+//! - Expired and deactivated URLs are reported as [`DatabaseError::Gone`]
+//! - Clicks keep only the origin of their referrer
+//!
+//! ## Usage
+//!
+//! ```rust
+//! use url_shortener_ztm_lib::database::{InMemoryUrlDatabase, UrlDatabase};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let db = InMemoryUrlDatabase::new();
+//! db.insert_url("abc123", "https://example.com").await?;
+//! assert_eq!(db.get_url("abc123").await?, "https://example.com");
+//! # Ok(())
+//! # }
+//! ```
+
+use super::{CLICK_BUCKETS, DatabaseError, UrlDatabase, referrer_origin};
+use crate::models::{
+    AbTarget, BatchInsertResult, ClickBucket, ClickRecord, ClickSummary, CountryCount,
+    DatabaseStats, ExpiringUrl, ExportRecord, MaintenanceStep, ReferrerCount, UpsertResult,
+    UrlMetadata, UrlRecord, UrlStatus, Urls, VariantCount,
+};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Timelike, Utc};
+use dashmap::DashMap;
+use dashmap::mapref::entry::Entry;
+use dashmap::mapref::one::RefMut;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::sync::atomic::{AtomicI64, Ordering};
+use uuid::Uuid;
+
+/// A stored URL, keyed by its primary code.
+#[derive(Debug, Clone)]
+struct UrlEntry {
+    id: i64,
+    url: String,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    expires_at: Option<DateTime<Utc>>,
+    preview_views: u64,
+    redirect_type: String,
+    owner_id: Option<Uuid>,
+    password_hash: Option<Vec<u8>>,
+    ab_targets: Vec<AbTarget>,
+    mobile_url: Option<String>,
+    max_clicks: Option<u64>,
+    deactivated_at: Option<DateTime<Utc>>,
+    expiry_reminder_sent_at: Option<DateTime<Utc>>,
+}
+
+impl UrlEntry {
+    fn new(id: i64, url: &str, expires_at: Option<DateTime<Utc>>) -> Self {
+        let now = Utc::now();
+        Self {
+            id,
+            url: url.to_string(),
+            created_at: now,
+            updated_at: now,
+            expires_at,
+            preview_views: 0,
+            redirect_type: "permanent".to_string(),
+            owner_id: None,
+            password_hash: None,
+            ab_targets: Vec::new(),
+            mobile_url: None,
+            max_clicks: None,
+            deactivated_at: None,
+            expiry_reminder_sent_at: None,
+        }
+    }
+
+    /// The status the real databases report for this entry.
+    fn status(&self) -> UrlStatus {
+        if self.deactivated_at.is_some() {
+            UrlStatus::MaxClicksReached
+        } else if self.expires_at.is_some_and(|at| at <= Utc::now()) {
+            UrlStatus::Expired
+        } else if self.password_hash.is_some() {
+            UrlStatus::PasswordProtected
+        } else {
+            UrlStatus::Active
+        }
+    }
+
+    fn record(&self, code: &str) -> UrlRecord {
+        UrlRecord {
+            code: code.to_string(),
+            url: self.url.clone(),
+            owner_id: self.owner_id,
+        }
+    }
+}
+
+/// A recorded click, with the A/B variant [`ClickRecord`] does not carry.
+#[derive(Debug, Clone)]
+struct StoredClick {
+    record: ClickRecord,
+    variant: Option<u8>,
+}
+
+/// [`UrlDatabase`] kept in concurrent hash maps.
+///
+/// Each map is locked on its own, so operations touching several of them
+/// are not atomic as a whole; the maps are always locked in field order,
+/// which keeps concurrent calls from deadlocking.
+#[derive(Debug, Default)]
+pub struct InMemoryUrlDatabase {
+    /// Primary code of each stored URL, the index the upsert checks
+    by_url: DashMap<String, String>,
+    /// Stored URLs by primary code
+    urls: DashMap<String, UrlEntry>,
+    /// URL id of each alias
+    aliases: DashMap<String, i64>,
+    /// Primary code of each URL id
+    codes_by_id: DashMap<i64, String>,
+    /// Clicks by primary code, oldest first
+    clicks: DashMap<String, Vec<StoredClick>>,
+    /// Tags by primary code
+    tags: DashMap<String, BTreeSet<String>>,
+    /// Bloom filter snapshots by name
+    snapshots: DashMap<String, Vec<u8>>,
+    next_id: AtomicI64,
+    next_click_id: AtomicI64,
+}
+
+impl InMemoryUrlDatabase {
+    /// Creates an empty database.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resolves a short code or alias to the primary code of its URL.
+    fn primary_code(&self, code: &str) -> Result<String, DatabaseError> {
+        if self.urls.contains_key(code) {
+            return Ok(code.to_string());
+        }
+        let id = *self.aliases.get(code).ok_or(DatabaseError::NotFound)?;
+        self.codes_by_id
+            .get(&id)
+            .map(|primary| primary.clone())
+            .ok_or(DatabaseError::NotFound)
+    }
+
+    /// The entry of a short code or alias, locked for writing; no other
+    /// `urls` entry may be touched while it is held.
+    fn entry_mut(&self, code: &str) -> Result<RefMut<'_, String, UrlEntry>, DatabaseError> {
+        let primary = self.primary_code(code)?;
+        self.urls.get_mut(&primary).ok_or(DatabaseError::NotFound)
+    }
+
+    /// A copy of the entry of a short code or alias, with its primary code.
+    fn entry(&self, code: &str) -> Result<(String, UrlEntry), DatabaseError> {
+        let primary = self.primary_code(code)?;
+        let entry = self
+            .urls
+            .get(&primary)
+            .map(|entry| entry.clone())
+            .ok_or(DatabaseError::NotFound)?;
+        Ok((primary, entry))
+    }
+
+    /// The clicks of a primary code, oldest first.
+    fn clicks_of(&self, primary: &str) -> Vec<StoredClick> {
+        self.clicks
+            .get(primary)
+            .map(|clicks| clicks.clone())
+            .unwrap_or_default()
+    }
+
+    /// Every stored URL in insertion order, like `ORDER BY id`.
+    fn records(&self) -> Vec<(String, UrlEntry)> {
+        let mut records: Vec<_> = self
+            .urls
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect();
+        records.sort_by_key(|(_, entry)| entry.id);
+        records
+    }
+
+    fn insert(
+        &self,
+        code: &str,
+        url: &str,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<(UpsertResult, Urls), DatabaseError> {
+        let slot = match self.by_url.entry(url.to_string()) {
+            Entry::Occupied(existing) => {
+                let code = existing.get().clone();
+                let id = self
+                    .urls
+                    .get(&code)
+                    .map(|entry| entry.id)
+                    .ok_or(DatabaseError::NotFound)?;
+                return Ok((UpsertResult { id, created: false }, Urls { id, code }));
+            }
+            Entry::Vacant(slot) => slot,
+        };
+        // Codes and aliases share one namespace, like the databases' triggers
+        if self.aliases.contains_key(code) {
+            return Err(DatabaseError::Duplicate);
+        }
+        let Entry::Vacant(free) = self.urls.entry(code.to_string()) else {
+            return Err(DatabaseError::Duplicate);
+        };
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        free.insert(UrlEntry::new(id, url, expires_at));
+        self.codes_by_id.insert(id, code.to_string());
+        slot.insert(code.to_string());
+        Ok((
+            UpsertResult { id, created: true },
+            Urls {
+                id,
+                code: code.to_string(),
+            },
+        ))
+    }
+}
+
+fn page<T>(items: impl IntoIterator<Item = T>, offset: u64, limit: u64) -> Vec<T> {
+    items
+        .into_iter()
+        .skip(offset as usize)
+        .take(limit as usize)
+        .collect()
+}
+
+fn count_referrers(clicks: &[StoredClick], limit: u8) -> Vec<ReferrerCount> {
+    let mut origins: BTreeMap<String, u64> = BTreeMap::new();
+    for origin in clicks
+        .iter()
+        .filter_map(|c| c.record.referrer_origin.clone())
+    {
+        *origins.entry(origin).or_default() += 1;
+    }
+    let mut referrers: Vec<ReferrerCount> = origins
+        .into_iter()
+        .map(|(referrer_origin, count)| ReferrerCount {
+            referrer_origin,
+            count,
+        })
+        .collect();
+    // Stable sort keeps referrers with equal counts in name order
+    referrers.sort_by_key(|r| std::cmp::Reverse(r.count));
+    referrers.truncate(limit as usize);
+    referrers
+}
+
+#[async_trait]
+impl UrlDatabase for InMemoryUrlDatabase {
+    async fn insert_url(
+        &self,
+        code: &str,
+        url: &str,
+    ) -> Result<(UpsertResult, Urls), DatabaseError> {
+        self.insert(code, url, None)
+    }
+
+    async fn insert_url_with_expiry(
+        &self,
+        code: &str,
+        url: &str,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<(UpsertResult, Urls), DatabaseError> {
+        self.insert(code, url, expires_at)
+    }
+
+    async fn insert_urls_batch(
+        &self,
+        items: &[(&str, &str)],
+    ) -> Result<Vec<BatchInsertResult>, DatabaseError> {
+        let mut results = Vec::with_capacity(items.len());
+        for (code, url) in items {
+            // A taken code is skipped, like `INSERT OR IGNORE`
+            match self.insert(code, url, None) {
+                Ok((upsert, urls)) => results.push(BatchInsertResult {
+                    code: urls.code,
+                    url: url.to_string(),
+                    created: upsert.created,
+                }),
+                Err(DatabaseError::Duplicate) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(results)
+    }
+
+    async fn insert_alias(&self, alias_code: &str, code_id: i64) -> Result<(), DatabaseError> {
+        if self.urls.contains_key(alias_code) {
+            return Err(DatabaseError::Duplicate);
+        }
+        if !self.codes_by_id.contains_key(&code_id) {
+            return Err(DatabaseError::QueryError(format!(
+                "no URL with id {}",
+                code_id
+            )));
+        }
+        match self.aliases.entry(alias_code.to_string()) {
+            Entry::Occupied(_) => Err(DatabaseError::Duplicate),
+            Entry::Vacant(slot) => {
+                slot.insert(code_id);
+                Ok(())
+            }
+        }
+    }
+
+    async fn get_id_by_url(&self, url: &str) -> Result<Urls, DatabaseError> {
+        let code = self
+            .by_url
+            .get(url)
+            .map(|code| code.clone())
+            .ok_or(DatabaseError::NotFound)?;
+        let (_, entry) = self.entry(&code)?;
+        Ok(Urls { id: entry.id, code })
+    }
+
+    async fn get_url(&self, id: &str) -> Result<String, DatabaseError> {
+        let (_, entry) = self.entry(id)?;
+        match entry.status() {
+            UrlStatus::Active | UrlStatus::PasswordProtected => Ok(entry.url),
+            status => Err(DatabaseError::Gone(status)),
+        }
+    }
+
+    async fn get_url_with_metadata(&self, code: &str) -> Result<UrlMetadata, DatabaseError> {
+        let (primary, entry) = self.entry(code)?;
+        let click_count = self.clicks.get(&primary).map_or(0, |c| c.len() as u64);
+        Ok(UrlMetadata {
+            code: code.to_string(),
+            is_active: entry.deactivated_at.is_none()
+                && entry.expires_at.is_none_or(|at| at > Utc::now()),
+            status: entry.status(),
+            url: entry.url,
+            created_at: entry.created_at,
+            click_count,
+            preview_views: entry.preview_views,
+            redirect_type: entry.redirect_type,
+            expires_at: entry.expires_at,
+            owner_id: entry.owner_id,
+            password_hash: entry.password_hash,
+            mobile_url: entry.mobile_url,
+            max_clicks: entry.max_clicks.map(|max| max as i64),
+            deactivated_at: entry.deactivated_at,
+            updated_at: entry.updated_at,
+        })
+    }
+
+    async fn delete_url(&self, code: &str) -> Result<(), DatabaseError> {
+        if self.aliases.remove(code).is_some() {
+            return Ok(());
+        }
+        let (_, entry) = self.urls.remove(code).ok_or(DatabaseError::NotFound)?;
+        self.by_url.remove(&entry.url);
+        self.aliases.retain(|_, id| *id != entry.id);
+        self.codes_by_id.remove(&entry.id);
+        self.clicks.remove(code);
+        self.tags.remove(code);
+        Ok(())
+    }
+
+    async fn update_url(&self, code: &str, new_url: &str) -> Result<(), DatabaseError> {
+        let primary = self.primary_code(code)?;
+        match self.by_url.entry(new_url.to_string()) {
+            Entry::Occupied(existing) if *existing.get() != primary => {
+                return Err(DatabaseError::Duplicate);
+            }
+            Entry::Occupied(_) => {}
+            Entry::Vacant(slot) => {
+                slot.insert(primary.clone());
+            }
+        }
+
+        let old_url = {
+            let mut entry = self.urls.get_mut(&primary).ok_or(DatabaseError::NotFound)?;
+            entry.updated_at = Utc::now();
+            std::mem::replace(&mut entry.url, new_url.to_string())
+        };
+        if old_url != new_url {
+            self.by_url.remove(&old_url);
+        }
+        Ok(())
+    }
+
+    async fn record_preview_view(&self, code: &str) -> Result<(), DatabaseError> {
+        self.entry_mut(code)?.preview_views += 1;
+        Ok(())
+    }
+
+    async fn record_click(
+        &self,
+        code: &str,
+        clicked_at: DateTime<Utc>,
+        ip: Option<&str>,
+        referrer: Option<&str>,
+        variant: Option<u8>,
+        device_type: Option<&str>,
+        country_code: Option<&str>,
+        user_agent: Option<&str>,
+    ) -> Result<(), DatabaseError> {
+        let primary = {
+            let mut entry = self.entry_mut(code)?;
+            entry.updated_at = clicked_at;
+            entry.key().clone()
+        };
+        let record = ClickRecord {
+            id: self.next_click_id.fetch_add(1, Ordering::Relaxed) + 1,
+            code: primary.clone(),
+            ip: ip.map(str::to_string),
+            user_agent: user_agent.map(str::to_string),
+            referrer_origin: referrer.and_then(referrer_origin),
+            country_code: country_code.map(str::to_string),
+            device_type: device_type.map(str::to_string),
+            clicked_at,
+        };
+        self.clicks
+            .entry(primary)
+            .or_default()
+            .push(StoredClick { record, variant });
+        Ok(())
+    }
+
+    async fn get_recent_clicks(
+        &self,
+        code: &str,
+        limit: u8,
+    ) -> Result<Vec<ClickRecord>, DatabaseError> {
+        let primary = self.primary_code(code)?;
+        let mut records: Vec<ClickRecord> = self
+            .clicks_of(&primary)
+            .into_iter()
+            .map(|click| click.record)
+            .collect();
+        records.sort_by(|a, b| b.clicked_at.cmp(&a.clicked_at).then(b.id.cmp(&a.id)));
+        records.truncate(usize::from(limit));
+        Ok(records)
+    }
+
+    async fn get_clicks_over_time(
+        &self,
+        code: &str,
+        bucket: &str,
+    ) -> Result<Vec<ClickBucket>, DatabaseError> {
+        if !CLICK_BUCKETS.contains(&bucket) {
+            return Err(DatabaseError::QueryError(format!(
+                "unsupported click bucket {:?}",
+                bucket
+            )));
+        }
+        let primary = self.primary_code(code)?;
+
+        let mut counts: BTreeMap<DateTime<Utc>, u64> = BTreeMap::new();
+        for click in self.clicks_of(&primary) {
+            let hour = click
+                .record
+                .clicked_at
+                .with_nanosecond(0)
+                .and_then(|at| at.with_second(0))
+                .and_then(|at| at.with_minute(0));
+            let timestamp = match bucket {
+                "day" => hour.and_then(|at| at.with_hour(0)),
+                _ => hour,
+            }
+            .expect("truncated click time is valid");
+            *counts.entry(timestamp).or_default() += 1;
+        }
+        Ok(counts
+            .into_iter()
+            .map(|(timestamp, count)| ClickBucket { timestamp, count })
+            .collect())
+    }
+
+    async fn get_top_referrers(
+        &self,
+        code: &str,
+        limit: u8,
+    ) -> Result<Vec<ReferrerCount>, DatabaseError> {
+        let primary = self.primary_code(code)?;
+        Ok(count_referrers(&self.clicks_of(&primary), limit))
+    }
+
+    async fn get_clicks_by_country(&self, code: &str) -> Result<Vec<CountryCount>, DatabaseError> {
+        let primary = self.primary_code(code)?;
+        let mut counts: BTreeMap<String, u64> = BTreeMap::new();
+        for country in self
+            .clicks_of(&primary)
+            .into_iter()
+            .filter_map(|c| c.record.country_code)
+        {
+            *counts.entry(country).or_default() += 1;
+        }
+        let mut countries: Vec<CountryCount> = counts
+            .into_iter()
+            .map(|(country_code, count)| CountryCount {
+                country_code,
+                count,
+            })
+            .collect();
+        // Stable sort keeps countries with equal counts in code order
+        countries.sort_by_key(|c| std::cmp::Reverse(c.count));
+        Ok(countries)
+    }
+
+    async fn get_click_summary(
+        &self,
+        code: &str,
+        top_referrers: u8,
+    ) -> Result<ClickSummary, DatabaseError> {
+        let (primary, entry) = self.entry(code)?;
+        let clicks = self.clicks_of(&primary);
+
+        let unique_ips: HashSet<&str> = clicks
+            .iter()
+            .filter_map(|c| c.record.ip.as_deref())
+            .collect();
+        let variants = entry
+            .ab_targets
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, target)| {
+                let variant = idx as u8;
+                let count = clicks.iter().filter(|c| c.variant == Some(variant)).count() as u64;
+                (count > 0).then(|| VariantCount {
+                    variant,
+                    url: target.url.clone(),
+                    count,
+                })
+            })
+            .collect();
+        let device_clicks = |device: &str| {
+            clicks
+                .iter()
+                .filter(|c| c.record.device_type.as_deref() == Some(device))
+                .count() as u64
+        };
+
+        Ok(ClickSummary {
+            total_clicks: clicks.len() as u64,
+            unique_ips: unique_ips.len() as u64,
+            desktop_clicks: device_clicks("desktop"),
+            mobile_clicks: device_clicks("mobile"),
+            top_referrers: count_referrers(&clicks, top_referrers),
+            variants,
+            top_countries: None,
+        })
+    }
+
+    async fn set_redirect_type(
+        &self,
+        code: &str,
+        redirect_type: &str,
+    ) -> Result<(), DatabaseError> {
+        self.entry_mut(code)?.redirect_type = redirect_type.to_string();
+        Ok(())
+    }
+
+    async fn set_url_owner(&self, code: &str, owner_id: Uuid) -> Result<(), DatabaseError> {
+        self.entry_mut(code)?.owner_id = Some(owner_id);
+        Ok(())
+    }
+
+    async fn set_url_password(
+        &self,
+        code: &str,
+        password_hash: &[u8],
+    ) -> Result<(), DatabaseError> {
+        self.entry_mut(code)?.password_hash = Some(password_hash.to_vec());
+        Ok(())
+    }
+
+    async fn set_ab_targets(&self, code: &str, targets: &[AbTarget]) -> Result<(), DatabaseError> {
+        self.entry_mut(code)?.ab_targets = targets.to_vec();
+        Ok(())
+    }
+
+    async fn get_ab_targets(&self, code: &str) -> Result<Vec<AbTarget>, DatabaseError> {
+        Ok(self
+            .entry(code)
+            .map(|(_, entry)| entry.ab_targets)
+            .unwrap_or_default())
+    }
+
+    async fn set_mobile_url(&self, code: &str, mobile_url: &str) -> Result<(), DatabaseError> {
+        self.entry_mut(code)?.mobile_url = Some(mobile_url.to_string());
+        Ok(())
+    }
+
+    async fn set_max_clicks(&self, code: &str, max_clicks: u64) -> Result<(), DatabaseError> {
+        self.entry_mut(code)?.max_clicks = Some(max_clicks);
+        Ok(())
+    }
+
+    async fn get_click_count(&self, code: &str) -> Result<u64, DatabaseError> {
+        let primary = self.primary_code(code)?;
+        Ok(self.clicks.get(&primary).map_or(0, |c| c.len() as u64))
+    }
+
+    async fn deactivate_url(&self, code: &str) -> Result<(), DatabaseError> {
+        self.entry_mut(code)?
+            .deactivated_at
+            .get_or_insert_with(Utc::now);
+        Ok(())
+    }
+
+    async fn list_expiring_urls(
+        &self,
+        from: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<Vec<ExpiringUrl>, DatabaseError> {
+        let mut expiring: Vec<ExpiringUrl> = self
+            .records()
+            .into_iter()
+            .filter(|(_, e)| e.expiry_reminder_sent_at.is_none() && e.deactivated_at.is_none())
+            .filter_map(|(code, e)| {
+                let expires_at = e.expires_at.filter(|at| (from..=until).contains(at))?;
+                Some(ExpiringUrl {
+                    code,
+                    url: e.url,
+                    expires_at,
+                    owner_id: e.owner_id?,
+                })
+            })
+            .collect();
+        expiring.sort_by_key(|u| u.expires_at);
+        Ok(expiring)
+    }
+
+    async fn mark_expiry_reminder_sent(
+        &self,
+        code: &str,
+        sent_at: DateTime<Utc>,
+    ) -> Result<(), DatabaseError> {
+        self.entry_mut(code)?.expiry_reminder_sent_at = Some(sent_at);
+        Ok(())
+    }
+
+    async fn list_short_codes(
+        &self,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<String>, DatabaseError> {
+        let mut aliases: Vec<String> = self.aliases.iter().map(|a| a.key().clone()).collect();
+        aliases.sort();
+        let codes = self
+            .records()
+            .into_iter()
+            .map(|(code, _)| code)
+            .chain(aliases);
+        Ok(page(codes, offset, limit))
+    }
+
+    async fn list_urls(
+        &self,
+        offset: u64,
+        limit: u64,
+        owner_id: Option<Uuid>,
+    ) -> Result<Vec<UrlRecord>, DatabaseError> {
+        let owned = self
+            .records()
+            .into_iter()
+            .filter(|(_, entry)| owner_id.is_none() || entry.owner_id == owner_id)
+            .map(|(code, entry)| entry.record(&code));
+        Ok(page(owned, offset, limit))
+    }
+
+    async fn count_urls_by_owner(&self, owner_id: Uuid) -> Result<u64, DatabaseError> {
+        Ok(self
+            .urls
+            .iter()
+            .filter(|entry| entry.owner_id == Some(owner_id))
+            .count() as u64)
+    }
+
+    async fn sum_clicks_by_owner(&self, owner_id: Uuid) -> Result<u64, DatabaseError> {
+        let owned: Vec<String> = self
+            .urls
+            .iter()
+            .filter(|entry| entry.owner_id == Some(owner_id))
+            .map(|entry| entry.key().clone())
+            .collect();
+        Ok(owned
+            .iter()
+            .map(|code| self.clicks.get(code).map_or(0, |c| c.len() as u64))
+            .sum())
+    }
+
+    async fn export_urls(
+        &self,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<ExportRecord>, DatabaseError> {
+        let records = self.records().into_iter().map(|(code, entry)| {
+            let click_count = self.clicks.get(&code).map_or(0, |c| c.len() as u64);
+            ExportRecord {
+                code,
+                url: entry.url,
+                created_at: entry.created_at,
+                click_count,
+            }
+        });
+        Ok(page(records, offset, limit))
+    }
+
+    async fn add_tag(&self, code: &str, tag: &str) -> Result<(), DatabaseError> {
+        let primary = self.primary_code(code)?;
+        self.tags
+            .entry(primary)
+            .or_default()
+            .insert(tag.to_string());
+        Ok(())
+    }
+
+    async fn remove_tag(&self, code: &str, tag: &str) -> Result<(), DatabaseError> {
+        let primary = self.primary_code(code)?;
+        if let Some(mut tags) = self.tags.get_mut(&primary) {
+            tags.remove(tag);
+        }
+        Ok(())
+    }
+
+    async fn search_urls(
+        &self,
+        query: &str,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<UrlRecord>, DatabaseError> {
+        // `LIKE` is case-insensitive for ASCII
+        let query = query.to_ascii_lowercase();
+        let matches = |text: &str| text.to_ascii_lowercase().contains(&query);
+        let matching_ids: HashSet<i64> = self
+            .aliases
+            .iter()
+            .filter(|alias| matches(alias.key()))
+            .map(|alias| *alias.value())
+            .collect();
+        let records = self
+            .records()
+            .into_iter()
+            .filter(|(code, entry)| {
+                matches(&entry.url) || matches(code) || matching_ids.contains(&entry.id)
+            })
+            .map(|(code, entry)| entry.record(&code));
+        Ok(page(records, offset, limit))
+    }
+
+    async fn get_urls_by_tag(
+        &self,
+        tag: &str,
+        offset: u64,
+        limit: u64,
+        owner_id: Option<Uuid>,
+    ) -> Result<Vec<UrlRecord>, DatabaseError> {
+        let tagged: HashSet<String> = self
+            .tags
+            .iter()
+            .filter(|tags| tags.contains(tag))
+            .map(|tags| tags.key().clone())
+            .collect();
+        let records = self
+            .records()
+            .into_iter()
+            .filter(|(code, entry)| {
+                tagged.contains(code) && (owner_id.is_none() || entry.owner_id == owner_id)
+            })
+            .map(|(code, entry)| entry.record(&code));
+        Ok(page(records, offset, limit))
+    }
+
+    async fn load_bloom_snapshot(&self, name: &str) -> Result<Option<Vec<u8>>, DatabaseError> {
+        Ok(self.snapshots.get(name).map(|data| data.clone()))
+    }
+
+    async fn save_bloom_snapshot(&self, name: &str, data: &[u8]) -> Result<(), DatabaseError> {
+        self.snapshots.insert(name.to_string(), data.to_vec());
+        Ok(())
+    }
+
+    async fn health_check(&self) -> Result<(), DatabaseError> {
+        Ok(())
+    }
+
+    async fn get_stats(&self) -> Result<DatabaseStats, DatabaseError> {
+        Ok(DatabaseStats {
+            total_urls: self.urls.len() as u64,
+            total_clicks: self.clicks.iter().map(|c| c.len() as u64).sum(),
+            size_bytes: 0,
+        })
+    }
+
+    async fn pending_migrations(&self) -> Result<usize, DatabaseError> {
+        Ok(0)
+    }
+
+    async fn run_maintenance(&self) -> Vec<MaintenanceStep> {
+        // Nothing to compact or analyze
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[tokio::test]
+    async fn aliases_resolve_to_their_url_and_go_with_it() {
+        let db = InMemoryUrlDatabase::new();
+        let (upsert, _) = db
+            .insert_url("abc123", "https://example.com/a")
+            .await
+            .unwrap();
+        db.insert_alias("promo", upsert.id).await.unwrap();
+
+        assert_eq!(db.get_url("promo").await.unwrap(), "https://example.com/a");
+        assert!(matches!(
+            db.insert_alias("abc123", upsert.id).await,
+            Err(DatabaseError::Duplicate)
+        ));
+
+        db.delete_url("abc123").await.unwrap();
+        assert!(matches!(
+            db.get_url("promo").await,
+            Err(DatabaseError::NotFound)
+        ));
+        // The URL can be shortened again under a new code
+        let (again, urls) = db
+            .insert_url("def456", "https://example.com/a")
+            .await
+            .unwrap();
+        assert!(again.created);
+        assert_eq!(urls.code, "def456");
+    }
+
+    #[tokio::test]
+    async fn expired_urls_are_gone() {
+        let db = InMemoryUrlDatabase::new();
+        let past = Utc::now() - Duration::minutes(1);
+        db.insert_url_with_expiry("old", "https://example.com/old", Some(past))
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            db.get_url("old").await,
+            Err(DatabaseError::Gone(UrlStatus::Expired))
+        ));
+    }
+
+    #[tokio::test]
+    async fn updating_to_a_stored_url_is_a_duplicate() {
+        let db = InMemoryUrlDatabase::new();
+        db.insert_url("one", "https://example.com/1").await.unwrap();
+        db.insert_url("two", "https://example.com/2").await.unwrap();
+
+        assert!(matches!(
+            db.update_url("one", "https://example.com/2").await,
+            Err(DatabaseError::Duplicate)
+        ));
+        db.update_url("one", "https://example.com/3").await.unwrap();
+        // The old URL is free again
+        let (upsert, _) = db.insert_url("new", "https://example.com/1").await.unwrap();
+        assert!(upsert.created);
+    }
+}
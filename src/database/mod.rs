@@ -8,13 +8,17 @@
 //!
 //! The database layer consists of:
 //! - [`UrlDatabase`] trait - Defines the interface for URL storage operations
-//! - [`DatabaseError`] enum - Comprehensive error handling for database operations
+//! - [`DatabaseError`] enum - Comprehensive error handling for database operations.
+//!   It is `#[non_exhaustive]`, so code outside this crate must match it with a
+//!   wildcard arm to handle variants added later
 //! - Concrete implementations (currently SQLite)
 //!
 //! ## Supported Databases
 //!
 //! - **SQLite** - File-based database with automatic migrations (default)
 //! - **In-memory SQLite** - For testing and development
+//! - **[`InMemoryUrlDatabase`]** - `DashMap`-backed, without SQL, for fast
+//!   tests (`testing` feature only)
 //!
 //! ## Usage
 //!
@@ -31,6 +35,7 @@
 //!     create_if_missing: true,
 //!     max_connections: Some(16),
 //!     min_connections: Some(4),
+//!     busy_timeout_ms: None,
 //! }).await?;
 //!
 //! // Run migrations
@@ -46,30 +51,110 @@
 //! ```
 
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
 use std::fmt;
+use uuid::Uuid;
 
 // module declarations
+#[cfg(any(test, feature = "testing"))]
+pub mod memory;
 pub mod postgres_sql;
+pub mod retry;
 pub mod sqlite;
 
 // Re-exports for convenience
-use crate::models::{UpsertResult, Urls};
+use crate::models::{
+    AbTarget, BatchInsertResult, ClickBucket, ClickRecord, ClickSummary, CountryCount,
+    DatabaseStats, ExpiringUrl, ExportRecord, MaintenanceStep, ReferrerCount, UpsertResult,
+    UrlMetadata, UrlRecord, UrlStatus, Urls,
+};
+#[cfg(any(test, feature = "testing"))]
+pub use memory::InMemoryUrlDatabase;
 pub use postgres_sql::PostgresUrlDatabase;
+pub use retry::retry_transient;
 pub use sqlite::*;
 
 pub const MAX_ALIAS_LENGTH: usize = 64;
 
+/// Longest primary code the Postgres schema stores (`urls.code VARCHAR(12)`).
+pub const POSTGRES_MAX_CODE_LENGTH: usize = 12;
+
+/// Rows per multi-row `INSERT` issued by [`UrlDatabase::insert_urls_batch`].
+///
+/// Keeps each statement well below SQLite's bound-variable limit.
+pub const BATCH_INSERT_CHUNK: usize = 500;
+
+/// Bucket sizes accepted by [`UrlDatabase::get_clicks_over_time`].
+pub const CLICK_BUCKETS: &[&str] = &["hour", "day"];
+
 /// Database operation errors.
 ///
 /// This enum represents all possible errors that can occur during database operations,
 /// providing detailed error information for debugging and error handling.
+///
+/// New variants may be added in minor releases, so a `match` outside this
+/// crate needs a wildcard arm:
+///
+/// ```rust
+/// use url_shortener_ztm_lib::database::DatabaseError;
+///
+/// fn is_retryable(error: &DatabaseError) -> bool {
+///     match error {
+///         DatabaseError::ConnectionError(_) => true,
+///         _ => false,
+///     }
+/// }
+/// # assert!(!is_retryable(&DatabaseError::NotFound));
+/// ```
+///
+/// Listing every current variant is not enough:
+///
+/// ```rust,compile_fail
+/// use url_shortener_ztm_lib::database::DatabaseError;
+///
+/// fn describe(error: &DatabaseError) -> &'static str {
+///     match error {
+///         DatabaseError::ConnectionError(_) => "connection",
+///         DatabaseError::QueryError(_) => "query",
+///         DatabaseError::Transient(_) => "transient",
+///         DatabaseError::MigrationError(_) => "migration",
+///         DatabaseError::NotFound => "not found",
+///         DatabaseError::Duplicate => "duplicate",
+///         DatabaseError::Gone(_) => "gone",
+///     }
+/// }
+/// ```
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum DatabaseError {
     ConnectionError(String),
     QueryError(String),
+    /// The statement was aborted for a reason that may go away when it is
+    /// run again, such as a serialization failure; see [`retry_transient`]
+    Transient(String),
     MigrationError(String),
     NotFound,
     Duplicate,
+    /// The code exists but its URL expired or reached its click limit
+    Gone(UrlStatus),
+}
+
+/// Names every variant, in step with the `compile_fail` example on
+/// [`DatabaseError`]: a new variant breaks this match, which is the cue to add
+/// it to that example too, so it keeps failing only for `#[non_exhaustive]`.
+#[allow(dead_code)]
+#[deny(unreachable_patterns)]
+fn database_error_variant(error: &DatabaseError) -> &'static str {
+    match error {
+        DatabaseError::ConnectionError(_) => "connection",
+        DatabaseError::QueryError(_) => "query",
+        DatabaseError::Transient(_) => "transient",
+        DatabaseError::MigrationError(_) => "migration",
+        DatabaseError::NotFound => "not found",
+        DatabaseError::Duplicate => "duplicate",
+        DatabaseError::Gone(_) => "gone",
+    }
 }
 
 impl fmt::Display for DatabaseError {
@@ -77,8 +162,10 @@ impl fmt::Display for DatabaseError {
         match self {
             DatabaseError::ConnectionError(msg) => write!(f, "Database connection error: {}", msg),
             DatabaseError::QueryError(msg) => write!(f, "Database query error: {}", msg),
+            DatabaseError::Transient(msg) => write!(f, "Transient database error: {}", msg),
             DatabaseError::NotFound => write!(f, "Record not found"),
             DatabaseError::Duplicate => write!(f, "Duplicate record"),
+            DatabaseError::Gone(status) => write!(f, "Record is no longer active: {}", status),
             DatabaseError::MigrationError(msg) => write!(f, "Database migration error: {}", msg),
         }
     }
@@ -148,6 +235,72 @@ pub trait UrlDatabase: Send + Sync {
         code: &str,
         url: &str,
     ) -> Result<(UpsertResult, Urls), DatabaseError>;
+
+    /// Stores a URL like [`UrlDatabase::insert_url`] and gives it an expiry.
+    ///
+    /// The expiry is only written when this call creates the row. A URL that was
+    /// already stored keeps whatever expiry it had, since other callers share it.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The short identifier for the URL
+    /// * `url` - The original URL to store
+    /// * `expires_at` - When the short URL stops resolving, or `None` for never
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use chrono::{Duration, Utc};
+    /// use url_shortener_ztm_lib::database::UrlDatabase;
+    ///
+    /// # async fn example<DB: UrlDatabase>(db: &DB) -> Result<(), Box<dyn std::error::Error>> {
+    /// let expires_at = Utc::now() + Duration::hours(1);
+    /// db.insert_url_with_expiry("abc123", "https://example.com", Some(expires_at))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn insert_url_with_expiry(
+        &self,
+        code: &str,
+        url: &str,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<(UpsertResult, Urls), DatabaseError>;
+
+    /// Stores many `(code, url)` pairs in a single transaction.
+    ///
+    /// Rows are written with multi-row statements of at most [`BATCH_INSERT_CHUNK`]
+    /// entries, so a bulk import costs a handful of round-trips instead of one per URL.
+    /// The transaction is rolled back if any statement fails.
+    ///
+    /// # Arguments
+    ///
+    /// * `items` - `(code, url)` pairs; URLs are expected to be normalized already
+    ///
+    /// # Returns
+    ///
+    /// Returns one [`BatchInsertResult`] per item whose URL is stored after the call,
+    /// in input order. URLs that already existed report their existing code with
+    /// `created == false`. Items whose code collided with a different URL or an
+    /// alias are left out, so callers can retry them with a fresh code.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use url_shortener_ztm_lib::database::UrlDatabase;
+    ///
+    /// # async fn example<DB: UrlDatabase>(db: &DB) -> Result<(), Box<dyn std::error::Error>> {
+    /// let results = db
+    ///     .insert_urls_batch(&[("abc123", "https://example.com/"), ("def456", "https://example.org/")])
+    ///     .await?;
+    /// assert_eq!(results.len(), 2);
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn insert_urls_batch(
+        &self,
+        items: &[(&str, &str)],
+    ) -> Result<Vec<BatchInsertResult>, DatabaseError>;
     async fn insert_alias(&self, alias_code: &str, code_id: i64) -> Result<(), DatabaseError>;
     async fn get_id_by_url(&self, url: &str) -> Result<Urls, DatabaseError>;
 
@@ -159,8 +312,11 @@ pub trait UrlDatabase: Send + Sync {
     ///
     /// # Returns
     ///
-    /// Returns `Ok(String)` with the original URL if found, or an error if:
-    /// - The ID was not found (`DatabaseError::NotFound`)
+    /// Returns `Ok(String)` with the original URL if its status is `active` or
+    /// `password_protected`, or an error if:
+    /// - The ID was not found or its URL was deleted (`DatabaseError::NotFound`)
+    /// - The URL expired or reached its click limit (`DatabaseError::Gone`); an
+    ///   URL found past its `expires_at` is stored as `expired` on the way
     /// - A database error occurred (`DatabaseError::QueryError`)
     /// - A connection error occurred (`DatabaseError::ConnectionError`)
     ///
@@ -176,8 +332,636 @@ pub trait UrlDatabase: Send + Sync {
     /// # }
     /// ```
     async fn get_url(&self, id: &str) -> Result<String, DatabaseError>;
+
+    /// Retrieves a short code together with its creation time, click count and expiry.
+    ///
+    /// Unlike [`UrlDatabase::get_url`], this does not discard the row metadata.
+    /// Aliases resolve to the metadata of the URL they point at, while `code`
+    /// echoes the alias that was looked up.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The short code or alias to look up
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(UrlMetadata)` if found, or an error if:
+    /// - The code was not found (`DatabaseError::NotFound`)
+    /// - A database error occurred (`DatabaseError::QueryError`)
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use url_shortener_ztm_lib::database::UrlDatabase;
+    ///
+    /// # async fn example<DB: UrlDatabase>(db: &DB) -> Result<(), Box<dyn std::error::Error>> {
+    /// let meta = db.get_url_with_metadata("abc123").await?;
+    /// println!("{} has been clicked {} times", meta.url, meta.click_count);
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn get_url_with_metadata(&self, code: &str) -> Result<UrlMetadata, DatabaseError>;
+
+    /// Removes a short code.
+    ///
+    /// Deleting a primary code soft-deletes the URL: its status becomes
+    /// `deleted` and every lookup treats it, and its aliases, as unknown. The
+    /// row keeps its tags and click history, and neither the code nor its
+    /// aliases are issued again. The same destination can be shortened anew.
+    /// Deleting an alias removes only that alias.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The short code or alias to remove
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` if something was deleted, or an error if:
+    /// - The code was not found (`DatabaseError::NotFound`)
+    /// - A database error occurred (`DatabaseError::QueryError`)
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use url_shortener_ztm_lib::database::UrlDatabase;
+    ///
+    /// # async fn example<DB: UrlDatabase>(db: &DB) -> Result<(), Box<dyn std::error::Error>> {
+    /// db.delete_url("abc123").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn delete_url(&self, code: &str) -> Result<(), DatabaseError>;
+
+    /// Points an existing short code (or alias) at a new target URL.
+    ///
+    /// The code, its aliases, tags and click history are kept. Updating an alias
+    /// updates the URL row it points at, so every code for that row follows.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The short code or alias to update
+    /// * `new_url` - The new target URL; expected to be normalized already
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an error if:
+    /// - The code was not found (`DatabaseError::NotFound`)
+    /// - `new_url` is already stored under another code (`DatabaseError::Duplicate`)
+    /// - A database error occurred (`DatabaseError::QueryError`)
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use url_shortener_ztm_lib::database::UrlDatabase;
+    ///
+    /// # async fn example<DB: UrlDatabase>(db: &DB) -> Result<(), Box<dyn std::error::Error>> {
+    /// db.update_url("abc123", "https://example.com/fixed").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn update_url(&self, code: &str, new_url: &str) -> Result<(), DatabaseError>;
+
+    /// Increments the preview page counter for a short code or alias.
+    ///
+    /// Preview views are counted separately from redirect clicks so that
+    /// inspecting a link does not inflate its click statistics.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The short code or alias that was previewed
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an error if:
+    /// - The code was not found (`DatabaseError::NotFound`)
+    /// - A database error occurred (`DatabaseError::QueryError`)
+    async fn record_preview_view(&self, code: &str) -> Result<(), DatabaseError>;
+
+    /// Records a redirect through a short code or alias.
+    ///
+    /// Clicks are stored against the URL row, so clicks through any of its
+    /// codes count towards the same statistics. The row's `updated_at` is set
+    /// to `clicked_at`.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The short code or alias that was followed
+    /// * `clicked_at` - When the redirect happened
+    /// * `ip` - The client IP, if known
+    /// * `referrer` - The `Referer` header sent with the click, if any; only
+    ///   its [`referrer_origin`] is stored
+    /// * `variant` - The A/B variant the click was sent to, if the URL has any
+    /// * `device_type` - `"mobile"`, `"desktop"` or `"unknown"`, from the
+    ///   `User-Agent` of the click
+    /// * `country_code` - ISO 3166-1 alpha-2 country of `ip`, if geolocation
+    ///   is enabled and found one
+    /// * `user_agent` - The `User-Agent` header sent with the click, if any
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an error if:
+    /// - The code was not found (`DatabaseError::NotFound`)
+    /// - A database error occurred (`DatabaseError::QueryError`)
+    #[allow(clippy::too_many_arguments)]
+    async fn record_click(
+        &self,
+        code: &str,
+        clicked_at: DateTime<Utc>,
+        ip: Option<&str>,
+        referrer: Option<&str>,
+        variant: Option<u8>,
+        device_type: Option<&str>,
+        country_code: Option<&str>,
+        user_agent: Option<&str>,
+    ) -> Result<(), DatabaseError>;
+
+    /// Lists the most recent clicks on a short code's URL, newest first.
+    ///
+    /// Clicks through any of the URL's codes are listed, each under the URL's
+    /// primary code.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The short code or alias to report on
+    /// * `limit` - Maximum number of clicks to return
+    ///
+    /// # Returns
+    ///
+    /// Returns the clicks on success, or an error if:
+    /// - The code was not found (`DatabaseError::NotFound`)
+    /// - A database error occurred (`DatabaseError::QueryError`)
+    async fn get_recent_clicks(
+        &self,
+        code: &str,
+        limit: u8,
+    ) -> Result<Vec<ClickRecord>, DatabaseError>;
+
+    /// Counts the clicks on a short code's URL per hour or day, oldest first.
+    ///
+    /// Buckets without clicks are left out.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The short code or alias to report on
+    /// * `bucket` - One of [`CLICK_BUCKETS`]
+    ///
+    /// # Returns
+    ///
+    /// Returns the buckets on success, or an error if:
+    /// - The code was not found (`DatabaseError::NotFound`)
+    /// - `bucket` is not supported or a database error occurred
+    ///   (`DatabaseError::QueryError`)
+    async fn get_clicks_over_time(
+        &self,
+        code: &str,
+        bucket: &str,
+    ) -> Result<Vec<ClickBucket>, DatabaseError>;
+
+    /// Counts the clicks on a short code's URL per referrer origin, most
+    /// clicks first.
+    ///
+    /// Clicks without a referrer are left out; origins with equal counts are
+    /// ordered by name.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The short code or alias to report on
+    /// * `limit` - Maximum number of origins to return
+    ///
+    /// # Returns
+    ///
+    /// Returns the origins on success, or an error if:
+    /// - The code was not found (`DatabaseError::NotFound`)
+    /// - A database error occurred (`DatabaseError::QueryError`)
+    async fn get_top_referrers(
+        &self,
+        code: &str,
+        limit: u8,
+    ) -> Result<Vec<ReferrerCount>, DatabaseError>;
+
+    /// Counts the clicks on a short code's URL per country, most clicks
+    /// first.
+    ///
+    /// Clicks without a country are left out; countries with equal counts are
+    /// ordered by code.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The short code or alias to report on
+    ///
+    /// # Returns
+    ///
+    /// Returns the countries on success, or an error if:
+    /// - The code was not found (`DatabaseError::NotFound`)
+    /// - A database error occurred (`DatabaseError::QueryError`)
+    async fn get_clicks_by_country(&self, code: &str) -> Result<Vec<CountryCount>, DatabaseError>;
+
+    /// Summarizes the clicks on a short code's URL.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The short code or alias to report on
+    /// * `top_referrers` - Maximum number of referrers to return
+    ///
+    /// # Returns
+    ///
+    /// Returns the summary on success, or an error if:
+    /// - The code was not found (`DatabaseError::NotFound`)
+    /// - A database error occurred (`DatabaseError::QueryError`)
+    async fn get_click_summary(
+        &self,
+        code: &str,
+        top_referrers: u8,
+    ) -> Result<ClickSummary, DatabaseError>;
+
+    /// Sets how redirects for a short code's URL are issued.
+    ///
+    /// The setting lives on the URL row, so every code and alias pointing at
+    /// that URL shares it.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The short code or alias to update
+    /// * `redirect_type` - Either `"permanent"` or `"temporary"`
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an error if:
+    /// - The code was not found (`DatabaseError::NotFound`)
+    /// - A database error occurred (`DatabaseError::QueryError`)
+    async fn set_redirect_type(&self, code: &str, redirect_type: &str)
+    -> Result<(), DatabaseError>;
+
+    /// Records the account that owns a short code's URL.
+    ///
+    /// Like the redirect type, ownership lives on the URL row, so an alias
+    /// belongs to whoever owns the URL it points at.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an error if:
+    /// - The code was not found (`DatabaseError::NotFound`)
+    /// - A database error occurred (`DatabaseError::QueryError`)
+    async fn set_url_owner(&self, code: &str, owner_id: Uuid) -> Result<(), DatabaseError>;
+
+    /// Guards a short code's URL with a passphrase, stored as its Argon2id
+    /// PHC hash.
+    ///
+    /// The hash lives on the URL row, so every alias of the URL asks for the
+    /// same passphrase. An `active` URL becomes `password_protected`.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an error if:
+    /// - The code was not found (`DatabaseError::NotFound`)
+    /// - A database error occurred (`DatabaseError::QueryError`)
+    async fn set_url_password(&self, code: &str, password_hash: &[u8])
+    -> Result<(), DatabaseError>;
+
+    /// Replaces the A/B destinations of a short code's URL.
+    ///
+    /// The position of each target is its variant number, recorded with the
+    /// clicks sent to it. Like the redirect type, the targets live on the URL
+    /// row, so every alias splits traffic the same way.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an error if:
+    /// - The code was not found (`DatabaseError::NotFound`)
+    /// - A database error occurred (`DatabaseError::QueryError`)
+    async fn set_ab_targets(&self, code: &str, targets: &[AbTarget]) -> Result<(), DatabaseError>;
+
+    /// Lists the A/B destinations of a short code's URL in variant order.
+    ///
+    /// Returns an empty list for codes without A/B targets, including unknown
+    /// codes.
+    async fn get_ab_targets(&self, code: &str) -> Result<Vec<AbTarget>, DatabaseError>;
+
+    /// Sets the destination of a short code's URL for visitors on mobile
+    /// devices.
+    ///
+    /// Like the A/B targets, it lives on the URL row, so every alias sends
+    /// mobile visitors to the same place.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an error if:
+    /// - The code was not found (`DatabaseError::NotFound`)
+    /// - A database error occurred (`DatabaseError::QueryError`)
+    async fn set_mobile_url(&self, code: &str, mobile_url: &str) -> Result<(), DatabaseError>;
+
+    /// Limits a short code's URL to `max_clicks` redirects, after which it is
+    /// deactivated.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an error if:
+    /// - The code was not found (`DatabaseError::NotFound`)
+    /// - A database error occurred (`DatabaseError::QueryError`)
+    async fn set_max_clicks(&self, code: &str, max_clicks: u64) -> Result<(), DatabaseError>;
+
+    /// Counts every click recorded for a short code's URL, through any of its
+    /// codes.
+    ///
+    /// # Returns
+    ///
+    /// Returns the count on success, or an error if:
+    /// - The code was not found (`DatabaseError::NotFound`)
+    /// - A database error occurred (`DatabaseError::QueryError`)
+    async fn get_click_count(&self, code: &str) -> Result<u64, DatabaseError>;
+
+    /// Stops a short code's URL from redirecting, through any of its codes,
+    /// by setting its status to `max_clicks_reached`.
+    ///
+    /// Deactivating an already deactivated URL keeps its original
+    /// `deactivated_at`. Nothing reactivates it automatically.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an error if:
+    /// - The code was not found (`DatabaseError::NotFound`)
+    /// - A database error occurred (`DatabaseError::QueryError`)
+    async fn deactivate_url(&self, code: &str) -> Result<(), DatabaseError>;
+
+    /// Lists the owned URLs expiring between `from` and `until` whose owner
+    /// has not been reminded yet, soonest first.
+    ///
+    /// Deactivated URLs are left out.
+    ///
+    /// # Returns
+    ///
+    /// Returns the URLs on success, or `DatabaseError::QueryError` if a
+    /// database error occurred.
+    async fn list_expiring_urls(
+        &self,
+        from: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<Vec<ExpiringUrl>, DatabaseError>;
+
+    /// Records that the owner of a short code's URL was reminded of its
+    /// expiry, so [`list_expiring_urls`](Self::list_expiring_urls) skips it.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an error if:
+    /// - The code was not found (`DatabaseError::NotFound`)
+    /// - A database error occurred (`DatabaseError::QueryError`)
+    async fn mark_expiry_reminder_sent(
+        &self,
+        code: &str,
+        sent_at: DateTime<Utc>,
+    ) -> Result<(), DatabaseError>;
     async fn list_short_codes(&self, offset: u64, limit: u64)
     -> Result<Vec<String>, DatabaseError>;
+
+    /// Lists stored URLs by their primary code, oldest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - Number of rows to skip
+    /// * `limit` - Maximum number of rows to return
+    /// * `owner_id` - When set, only URLs owned by this account are listed
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use url_shortener_ztm_lib::database::UrlDatabase;
+    ///
+    /// # async fn example<DB: UrlDatabase>(db: &DB) -> Result<(), Box<dyn std::error::Error>> {
+    /// for record in db.list_urls(0, 50, None).await? {
+    ///     println!("{} -> {}", record.code, record.url);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn list_urls(
+        &self,
+        offset: u64,
+        limit: u64,
+        owner_id: Option<Uuid>,
+    ) -> Result<Vec<UrlRecord>, DatabaseError>;
+
+    /// Counts the stored URLs owned by an account; aliases are not counted.
+    async fn count_urls_by_owner(&self, owner_id: Uuid) -> Result<u64, DatabaseError>;
+
+    /// Counts the clicks recorded on the URLs owned by an account.
+    async fn sum_clicks_by_owner(&self, owner_id: Uuid) -> Result<u64, DatabaseError>;
+
+    /// Lists stored URLs with their creation time and click count, oldest first.
+    ///
+    /// Used to page through the whole table when exporting a backup.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - Number of rows to skip
+    /// * `limit` - Maximum number of rows to return
+    async fn export_urls(
+        &self,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<ExportRecord>, DatabaseError>;
+
+    /// Attaches a tag to the URL behind a short code or alias.
+    ///
+    /// Tags are stored against the URL's primary code, so tagging an alias tags
+    /// the URL it points at. Adding a tag that is already present is a no-op.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The short code or alias to tag
+    /// * `tag` - The tag name; callers are expected to validate it first
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or an error if:
+    /// - The code was not found (`DatabaseError::NotFound`)
+    /// - A database error occurred (`DatabaseError::QueryError`)
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use url_shortener_ztm_lib::database::UrlDatabase;
+    ///
+    /// # async fn example<DB: UrlDatabase>(db: &DB) -> Result<(), Box<dyn std::error::Error>> {
+    /// db.add_tag("abc123", "campaign-q3").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn add_tag(&self, code: &str, tag: &str) -> Result<(), DatabaseError>;
+
+    /// Detaches a tag from the URL behind a short code or alias.
+    ///
+    /// Removing a tag that is not present is a no-op.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` on success, or `DatabaseError::NotFound` if the code
+    /// does not exist.
+    async fn remove_tag(&self, code: &str, tag: &str) -> Result<(), DatabaseError>;
+
+    /// Searches stored URLs by destination, primary code or alias.
+    ///
+    /// Matching is a case-insensitive substring match. `query` is matched
+    /// literally: `%`, `_` and `\` are escaped before building the pattern.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The substring to look for
+    /// * `offset` - Number of rows to skip
+    /// * `limit` - Maximum number of rows to return
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use url_shortener_ztm_lib::database::UrlDatabase;
+    ///
+    /// # async fn example<DB: UrlDatabase>(db: &DB) -> Result<(), Box<dyn std::error::Error>> {
+    /// let hits = db.search_urls("example.com", 0, 20).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn search_urls(
+        &self,
+        query: &str,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<UrlRecord>, DatabaseError>;
+
+    /// Lists the URLs carrying `tag`, ordered by when the tag was attached.
+    ///
+    /// # Arguments
+    ///
+    /// * `tag` - The tag to filter by
+    /// * `offset` - Number of rows to skip
+    /// * `limit` - Maximum number of rows to return
+    /// * `owner_id` - When set, only URLs owned by this account are listed
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use url_shortener_ztm_lib::database::UrlDatabase;
+    ///
+    /// # async fn example<DB: UrlDatabase>(db: &DB) -> Result<(), Box<dyn std::error::Error>> {
+    /// let tagged = db.get_urls_by_tag("campaign-q3", 0, 50, None).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn get_urls_by_tag(
+        &self,
+        tag: &str,
+        offset: u64,
+        limit: u64,
+        owner_id: Option<Uuid>,
+    ) -> Result<Vec<UrlRecord>, DatabaseError>;
     async fn load_bloom_snapshot(&self, name: &str) -> Result<Option<Vec<u8>>, DatabaseError>;
     async fn save_bloom_snapshot(&self, name: &str, data: &[u8]) -> Result<(), DatabaseError>;
+
+    /// Verifies that the database is reachable by running a trivial query.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` if the database answered, or
+    /// `Err(DatabaseError::ConnectionError)` if it could not be reached.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use url_shortener_ztm_lib::database::UrlDatabase;
+    ///
+    /// # async fn example<DB: UrlDatabase>(db: &DB) -> Result<(), Box<dyn std::error::Error>> {
+    /// db.health_check().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn health_check(&self) -> Result<(), DatabaseError>;
+
+    /// Counts the stored URLs and recorded clicks, and measures the database.
+    ///
+    /// Deleted URLs are not counted; their clicks are.
+    ///
+    /// SQLite reports `page_count * page_size`; PostgreSQL reports
+    /// `pg_database_size` of the current database.
+    ///
+    /// # Returns
+    ///
+    /// Returns the totals, or `Err(DatabaseError::QueryError)` if a query fails.
+    async fn get_stats(&self) -> Result<DatabaseStats, DatabaseError>;
+
+    /// Counts embedded migrations that have not been applied to the database.
+    ///
+    /// Compares the number of migrations compiled into the binary with the
+    /// number recorded as successful in `_sqlx_migrations`.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(0)` when the schema is up to date, or
+    /// `Err(DatabaseError::QueryError)` if the migrations table cannot be read.
+    async fn pending_migrations(&self) -> Result<usize, DatabaseError>;
+
+    /// Reclaims free space and refreshes the query planner statistics.
+    ///
+    /// SQLite runs `VACUUM` and then `ANALYZE`; PostgreSQL runs
+    /// `VACUUM ANALYZE urls`. Every statement is run even if an earlier one
+    /// failed, so a failure is reported in its step rather than as an error.
+    ///
+    /// # Returns
+    ///
+    /// Returns one [`MaintenanceStep`] per statement, in the order they ran.
+    async fn run_maintenance(&self) -> Vec<MaintenanceStep>;
+}
+
+// Every backend implements the same trait, with the same signatures, and stays
+// usable behind the `Arc<dyn UrlDatabase>` held by `AppState`
+const _: fn() = || {
+    fn assert_url_database<T: UrlDatabase + Send + Sync + 'static>() {}
+    assert_url_database::<SqliteUrlDatabase>();
+    assert_url_database::<PostgresUrlDatabase>();
+    #[cfg(any(test, feature = "testing"))]
+    assert_url_database::<InMemoryUrlDatabase>();
+};
+
+/// Maps the outcome of a batch insert back onto the caller's input order.
+///
+/// `created` holds the codes the batch actually inserted and `stored` maps every
+/// batch URL that is now present to the code it is stored under.
+pub(crate) fn collect_batch_results(
+    items: &[(&str, &str)],
+    created: &HashSet<String>,
+    stored: &HashMap<String, String>,
+) -> Vec<BatchInsertResult> {
+    items
+        .iter()
+        .filter_map(|(code, url)| {
+            let stored_code = stored.get(*url)?;
+            Some(BatchInsertResult {
+                code: stored_code.clone(),
+                url: url.to_string(),
+                created: stored_code == code && created.contains(*code),
+            })
+        })
+        .collect()
+}
+
+/// The origin (`scheme://host[:port]`) of a `Referer` value, which is all
+/// that is stored of it; `None` unless it is an absolute http(s) URL.
+pub fn referrer_origin(referrer: &str) -> Option<String> {
+    let url = url::Url::parse(referrer).ok()?;
+    matches!(url.scheme(), "http" | "https").then(|| url.origin().ascii_serialization())
+}
+
+/// Builds a `LIKE` pattern matching `query` anywhere in a column.
+///
+/// `\`, `%` and `_` are escaped with `\`, so queries must declare
+/// `ESCAPE '\'` for the wildcards in user input to match literally.
+pub(crate) fn like_pattern(query: &str) -> String {
+    let mut pattern = String::with_capacity(query.len() + 2);
+    pattern.push('%');
+    for c in query.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    pattern
 }
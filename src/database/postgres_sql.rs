@@ -8,7 +8,9 @@
 //! - **Type-safe queries** — Compile-time SQL query validation (when `sqlx` offline is set up)
 //! - **Automatic migrations** — Database schema management via `sqlx::migrate!`
 //! - **Connection pooling** — Efficient `PgPool` management
-//! - **Detailed error mapping** — Duplicate key detection and friendly errors
+//! - **Detailed error mapping** — Duplicate key detection, transient errors
+//!   (serialization failures, lock timeouts) as `DatabaseError::Transient`, and
+//!   friendly errors
 //!
 //! ## Database Schema
 //!
@@ -38,6 +40,7 @@
 //!     create_if_missing: false, // Not used by Postgres connector
 //!     max_connections: Some(16),
 //!     min_connections: Some(4),
+//!     busy_timeout_ms: None,
 //! };
 //! let db = PostgresUrlDatabase::from_config(&config).await?;
 //!
@@ -57,15 +60,25 @@
 //! This struct is `Send + Sync` and can be safely used across thread boundaries.
 //! The underlying `PgPool` is designed for concurrent access.
 
-use super::{DatabaseError, UrlDatabase};
-use crate::configuration::DatabaseSettings;
-use crate::models::{UpsertResult, Urls};
+use super::{
+    BATCH_INSERT_CHUNK, CLICK_BUCKETS, DatabaseError, UrlDatabase, collect_batch_results,
+    like_pattern, referrer_origin, retry::classify_sqlx_error,
+};
+use crate::configuration::{DatabaseSettings, DatabaseType};
+use crate::models::{
+    AbTarget, BatchInsertResult, ClickBucket, ClickRecord, ClickSummary, CountryCount,
+    DatabaseStats, ExpiringUrl, ExportRecord, MaintenanceStep, ReferrerCount, UpsertResult,
+    UrlMetadata, UrlRecord, UrlStatus, Urls, VariantCount,
+};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use sqlx::{
     Error as SqlxError, PgPool,
     postgres::{PgConnectOptions, PgPoolOptions},
 };
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
+use uuid::Uuid;
 
 const MAX_CAP: u32 = 96;
 const MIN_CAP: u32 = 2;
@@ -130,6 +143,7 @@ impl PostgresUrlDatabase {
     ///     create_if_missing: false,
     ///     max_connections: Some(16),
     ///     min_connections: Some(4),
+    ///     busy_timeout_ms: None,
     /// };
     /// let db = PostgresUrlDatabase::from_config(&config).await?;
     /// # Ok(())
@@ -142,6 +156,34 @@ impl PostgresUrlDatabase {
         Ok(Self::new(pool))
     }
 
+    /// Connects to the test database at `database_url` and migrates it, for
+    /// integration tests that run against a real PostgreSQL in CI.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use url_shortener_ztm_lib::database::PostgresUrlDatabase;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let url = std::env::var("TEST_DATABASE_URL")?;
+    /// let db = PostgresUrlDatabase::for_test(&url).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn for_test(database_url: &str) -> Result<Self, DatabaseError> {
+        let db = Self::from_config(&DatabaseSettings {
+            r#type: DatabaseType::Postgres,
+            url: database_url.to_string(),
+            create_if_missing: false,
+            max_connections: None,
+            min_connections: None,
+            busy_timeout_ms: None,
+        })
+        .await?;
+        db.migrate().await?;
+        Ok(db)
+    }
+
     /// Runs database migrations to set up the schema.
     ///
     /// This method applies all migration files found in the `./migrations/pg` directory.
@@ -167,6 +209,7 @@ impl PostgresUrlDatabase {
     ///     create_if_missing: false,
     ///     max_connections: Some(16),
     ///     min_connections: Some(4),
+    ///     busy_timeout_ms: None,
     /// };
     /// let db = PostgresUrlDatabase::from_config(&config).await?;
     /// db.migrate().await?; // Set up the database schema
@@ -180,6 +223,30 @@ impl PostgresUrlDatabase {
             .map_err(|e| DatabaseError::MigrationError(e.to_string()))?;
         Ok(())
     }
+
+    /// Resolves a short code or alias to the primary code of its URL row.
+    async fn primary_code(&self, code: &str) -> Result<String, DatabaseError> {
+        sqlx::query_scalar::<_, String>(
+            "SELECT u.code FROM all_short_codes s JOIN urls u ON u.id = s.target_id WHERE s.code = $1 LIMIT 1",
+        )
+        .bind(code)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(classify_sqlx_error)?
+        .ok_or(DatabaseError::NotFound)
+    }
+
+    /// Resolves a short code or alias to the id of its URL row.
+    async fn target_id(&self, code: &str) -> Result<i64, DatabaseError> {
+        sqlx::query_scalar::<_, i64>(
+            "SELECT target_id FROM all_short_codes WHERE code = $1 LIMIT 1",
+        )
+        .bind(code)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(classify_sqlx_error)?
+        .ok_or(DatabaseError::NotFound)
+    }
 }
 
 #[async_trait]
@@ -187,12 +254,12 @@ impl UrlDatabase for PostgresUrlDatabase {
     /// Retrieves the short ID by original URL from the PostgreSQL database.
     async fn get_id_by_url(&self, url: &str) -> Result<Urls, DatabaseError> {
         let row = sqlx::query_as::<_, Urls>(
-            "SELECT id, code FROM urls WHERE url_hash = digest($1, 'sha256') LIMIT 1",
+            "SELECT id, code FROM urls WHERE url_hash = digest($1, 'sha256') AND status <> 'deleted' LIMIT 1",
         )
         .bind(url)
         .fetch_optional(&self.pool)
         .await
-        .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+        .map_err(classify_sqlx_error)?;
 
         match row {
             Some(record) => Ok(record),
@@ -223,7 +290,7 @@ impl UrlDatabase for PostgresUrlDatabase {
                 if is_unique_violation(&e) {
                     DatabaseError::Duplicate
                 } else {
-                    DatabaseError::QueryError(e.to_string())
+                    classify_sqlx_error(e)
                 }
             })?;
 
@@ -241,11 +308,85 @@ impl UrlDatabase for PostgresUrlDatabase {
             .bind(upsert_result.id)
             .fetch_one(&self.pool)
             .await
-            .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+            .map_err(classify_sqlx_error)?;
 
         Ok((upsert_result, existing_urls))
     }
 
+    async fn insert_url_with_expiry(
+        &self,
+        code: &str,
+        url: &str,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<(UpsertResult, Urls), DatabaseError> {
+        let (upsert, urls) = self.insert_url(code, url).await?;
+        if upsert.created && expires_at.is_some() {
+            sqlx::query("UPDATE urls SET expires_at = $1 WHERE id = $2")
+                .bind(expires_at)
+                .bind(upsert.id)
+                .execute(&self.pool)
+                .await
+                .map_err(classify_sqlx_error)?;
+        }
+        Ok((upsert, urls))
+    }
+
+    /// Stores many URLs in one transaction using `UNNEST`-based multi-row inserts.
+    ///
+    /// `ON CONFLICT DO NOTHING` skips both code and URL-hash conflicts, and rows
+    /// whose code is an existing alias are filtered out before the overlap
+    /// trigger can fail the statement; the stored codes are then read back by
+    /// URL hash so existing URLs resolve to their original code.
+    async fn insert_urls_batch(
+        &self,
+        items: &[(&str, &str)],
+    ) -> Result<Vec<BatchInsertResult>, DatabaseError> {
+        let mut tx = self.pool.begin().await.map_err(classify_sqlx_error)?;
+
+        let mut created: HashSet<String> = HashSet::new();
+        let mut stored: HashMap<String, String> = HashMap::new();
+
+        for chunk in items.chunks(BATCH_INSERT_CHUNK) {
+            let codes: Vec<&str> = chunk.iter().map(|(code, _)| *code).collect();
+            let urls: Vec<&str> = chunk.iter().map(|(_, url)| *url).collect();
+
+            let inserted: Vec<String> = sqlx::query_scalar(
+                r#"
+                    INSERT INTO urls (code, url)
+                    SELECT c, u FROM UNNEST($1::text[], $2::text[]) AS t(c, u)
+                    WHERE NOT EXISTS (SELECT 1 FROM aliases WHERE alias = t.c)
+                    ON CONFLICT DO NOTHING
+                    RETURNING code
+                "#,
+            )
+            .bind(&codes)
+            .bind(&urls)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(classify_sqlx_error)?;
+            created.extend(inserted);
+
+            let rows: Vec<(String, String)> = sqlx::query_as(
+                r#"
+                    SELECT code, url FROM urls
+                    WHERE url_hash IN (
+                        SELECT digest(u, 'sha256') FROM UNNEST($1::text[]) AS t(u)
+                    )
+                      AND status <> 'deleted'
+                "#,
+            )
+            .bind(&urls)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(classify_sqlx_error)?;
+            stored.extend(rows.into_iter().map(|(code, url)| (url, code)));
+        }
+
+        tx.commit().await.map_err(classify_sqlx_error)?;
+
+        Ok(collect_batch_results(items, &created, &stored))
+    }
+
     /// Retrieves a URL by its short ID from the PostgreSQL database.
     ///
     /// Uses a prepared statement with optional result handling
@@ -257,21 +398,582 @@ impl UrlDatabase for PostgresUrlDatabase {
     ///
     /// # Returns
     ///
-    /// Returns `Ok(String)` with the original URL if found, or
+    /// Returns `Ok(String)` with the original URL if it still resolves,
+    /// `Err(DatabaseError::Gone)` if it expired or reached its click limit, or
     /// `Err(DatabaseError::NotFound)` if no record exists.
     async fn get_url(&self, code: &str) -> Result<String, DatabaseError> {
-        let row = sqlx::query_as::<_, (String,)>(
-            "SELECT url FROM all_short_codes u WHERE u.code = $1 LIMIT 1;",
+        let row = sqlx::query_as::<_, (String, i64, UrlStatus)>(
+            r#"
+                SELECT s.url,
+                       u.id,
+                       CASE WHEN u.status IN ('active', 'password_protected')
+                                 AND u.expires_at <= now()
+                            THEN 'expired'
+                            ELSE u.status
+                       END AS status
+                FROM all_short_codes s
+                JOIN urls u ON u.id = s.target_id
+                WHERE s.code = $1
+                LIMIT 1;
+            "#,
         )
         .bind(code)
         .fetch_optional(&self.pool)
         .await
-        .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+        .map_err(classify_sqlx_error)?;
 
-        match row {
-            Some(record) => Ok(record.0),
-            None => Err(DatabaseError::NotFound),
+        let Some((url, id, status)) = row else {
+            return Err(DatabaseError::NotFound);
+        };
+        match status {
+            UrlStatus::Active | UrlStatus::PasswordProtected => Ok(url),
+            UrlStatus::Deleted => Err(DatabaseError::NotFound),
+            UrlStatus::Expired => {
+                sqlx::query(
+                    "UPDATE urls SET status = 'expired' WHERE id = $1 AND status IN ('active', 'password_protected')",
+                )
+                .bind(id)
+                .execute(&self.pool)
+                .await
+                .map_err(classify_sqlx_error)?;
+                Err(DatabaseError::Gone(status))
+            }
+            UrlStatus::MaxClicksReached => Err(DatabaseError::Gone(status)),
+        }
+    }
+
+    /// Retrieves a short code and its metadata from the PostgreSQL database.
+    async fn get_url_with_metadata(&self, code: &str) -> Result<UrlMetadata, DatabaseError> {
+        let row = sqlx::query_as::<_, UrlMetadata>(
+            r#"
+                SELECT s.code,
+                       s.url,
+                       u.created_at,
+                       COALESCE(c.click_count, 0) AS click_count,
+                       u.preview_views,
+                       u.redirect_type,
+                       u.expires_at,
+                       (u.deactivated_at IS NULL AND (u.expires_at IS NULL OR u.expires_at > now()))
+                           AS is_active,
+                       u.owner_id,
+                       u.password_hash,
+                       u.mobile_url,
+                       u.max_clicks,
+                       u.deactivated_at,
+                       COALESCE(u.updated_at, u.created_at) AS updated_at,
+                       CASE WHEN u.status IN ('active', 'password_protected')
+                                 AND u.expires_at <= now()
+                            THEN 'expired'
+                            ELSE u.status
+                       END AS status
+                FROM all_short_codes s
+                JOIN urls u ON u.id = s.target_id
+                LEFT JOIN LATERAL (
+                    SELECT COUNT(*)::BIGINT AS click_count
+                    FROM clicks
+                    WHERE clicks.target_id = u.id
+                ) c ON TRUE
+                WHERE s.code = $1
+                LIMIT 1;
+            "#,
+        )
+        .bind(code)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(classify_sqlx_error)?;
+
+        row.ok_or(DatabaseError::NotFound)
+    }
+
+    /// Soft-deletes a URL; `uniq_url_hash` only covers rows that are not
+    /// deleted, so the destination can be shortened again.
+    async fn delete_url(&self, code: &str) -> Result<(), DatabaseError> {
+        let alias = sqlx::query(
+            r#"
+            DELETE FROM aliases
+            WHERE alias = $1
+              AND target_id IN (SELECT id FROM urls WHERE status <> 'deleted')
+            "#,
+        )
+        .bind(code)
+        .execute(&self.pool)
+        .await
+        .map_err(classify_sqlx_error)?;
+        if alias.rows_affected() > 0 {
+            return Ok(());
+        }
+
+        let url = sqlx::query(
+            "UPDATE urls SET status = 'deleted' WHERE code = $1 AND status <> 'deleted'",
+        )
+        .bind(code)
+        .execute(&self.pool)
+        .await
+        .map_err(classify_sqlx_error)?;
+        if url.rows_affected() == 0 {
+            return Err(DatabaseError::NotFound);
+        }
+        Ok(())
+    }
+
+    async fn update_url(&self, code: &str, new_url: &str) -> Result<(), DatabaseError> {
+        // `url_hash` is a generated column, so only `url` needs updating.
+        let result = sqlx::query(
+            r#"
+            UPDATE urls SET url = $1, updated_at = now()
+            WHERE id = (SELECT target_id FROM all_short_codes WHERE code = $2)
+            "#,
+        )
+        .bind(new_url)
+        .bind(code)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            if is_unique_violation(&e) {
+                DatabaseError::Duplicate
+            } else {
+                classify_sqlx_error(e)
+            }
+        })?;
+
+        if result.rows_affected() == 0 {
+            return Err(DatabaseError::NotFound);
+        }
+        Ok(())
+    }
+
+    async fn record_preview_view(&self, code: &str) -> Result<(), DatabaseError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE urls SET preview_views = preview_views + 1
+            WHERE id = (SELECT target_id FROM all_short_codes WHERE code = $1)
+            "#,
+        )
+        .bind(code)
+        .execute(&self.pool)
+        .await
+        .map_err(classify_sqlx_error)?;
+
+        if result.rows_affected() == 0 {
+            return Err(DatabaseError::NotFound);
         }
+        Ok(())
+    }
+
+    async fn record_click(
+        &self,
+        code: &str,
+        clicked_at: DateTime<Utc>,
+        ip: Option<&str>,
+        referrer: Option<&str>,
+        variant: Option<u8>,
+        device_type: Option<&str>,
+        country_code: Option<&str>,
+        user_agent: Option<&str>,
+    ) -> Result<(), DatabaseError> {
+        let target_id = self.target_id(code).await?;
+        sqlx::query(
+            r#"
+            INSERT INTO clicks
+                (target_id, clicked_at, ip, referrer_origin, variant, device_type, country_code,
+                 user_agent)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+        )
+        .bind(target_id)
+        .bind(clicked_at)
+        .bind(ip)
+        .bind(referrer.and_then(referrer_origin))
+        .bind(variant.map(i16::from))
+        .bind(device_type)
+        .bind(country_code)
+        .bind(user_agent)
+        .execute(&self.pool)
+        .await
+        .map_err(classify_sqlx_error)?;
+
+        sqlx::query("UPDATE urls SET updated_at = $1 WHERE id = $2")
+            .bind(clicked_at)
+            .bind(target_id)
+            .execute(&self.pool)
+            .await
+            .map_err(classify_sqlx_error)?;
+        Ok(())
+    }
+
+    async fn get_recent_clicks(
+        &self,
+        code: &str,
+        limit: u8,
+    ) -> Result<Vec<ClickRecord>, DatabaseError> {
+        let target_id = self.target_id(code).await?;
+        sqlx::query_as::<_, ClickRecord>(
+            r#"
+            SELECT c.id,
+                   u.code,
+                   c.ip,
+                   c.user_agent,
+                   c.referrer_origin,
+                   c.country_code,
+                   c.device_type,
+                   c.clicked_at
+            FROM clicks c
+            JOIN urls u ON u.id = c.target_id
+            WHERE c.target_id = $1
+            ORDER BY c.clicked_at DESC, c.id DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(target_id)
+        .bind(i64::from(limit))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(classify_sqlx_error)
+    }
+
+    async fn get_clicks_over_time(
+        &self,
+        code: &str,
+        bucket: &str,
+    ) -> Result<Vec<ClickBucket>, DatabaseError> {
+        if !CLICK_BUCKETS.contains(&bucket) {
+            return Err(DatabaseError::QueryError(format!(
+                "unsupported click bucket {:?}",
+                bucket
+            )));
+        }
+        let target_id = self.target_id(code).await?;
+        sqlx::query_as::<_, ClickBucket>(
+            r#"
+            SELECT date_trunc($1, clicked_at) AS timestamp, COUNT(*)::BIGINT AS count
+            FROM clicks
+            WHERE target_id = $2
+            GROUP BY 1
+            ORDER BY 1
+            "#,
+        )
+        .bind(bucket)
+        .bind(target_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(classify_sqlx_error)
+    }
+
+    async fn get_top_referrers(
+        &self,
+        code: &str,
+        limit: u8,
+    ) -> Result<Vec<ReferrerCount>, DatabaseError> {
+        let target_id = self.target_id(code).await?;
+        sqlx::query_as::<_, ReferrerCount>(
+            r#"
+            SELECT referrer_origin, COUNT(*)::BIGINT AS count
+            FROM clicks
+            WHERE target_id = $1 AND referrer_origin IS NOT NULL
+            GROUP BY referrer_origin
+            ORDER BY count DESC, referrer_origin
+            LIMIT $2
+            "#,
+        )
+        .bind(target_id)
+        .bind(i64::from(limit))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(classify_sqlx_error)
+    }
+
+    async fn get_clicks_by_country(&self, code: &str) -> Result<Vec<CountryCount>, DatabaseError> {
+        let target_id = self.target_id(code).await?;
+        sqlx::query_as::<_, CountryCount>(
+            r#"
+            SELECT country_code, COUNT(*)::BIGINT AS count
+            FROM clicks
+            WHERE target_id = $1 AND country_code IS NOT NULL
+            GROUP BY country_code
+            ORDER BY count DESC, country_code
+            "#,
+        )
+        .bind(target_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(classify_sqlx_error)
+    }
+
+    async fn get_click_summary(
+        &self,
+        code: &str,
+        top_referrers: u8,
+    ) -> Result<ClickSummary, DatabaseError> {
+        let target_id = self.target_id(code).await?;
+        let (total_clicks, unique_ips, desktop_clicks, mobile_clicks) =
+            sqlx::query_as::<_, (i64, i64, i64, i64)>(
+                r#"
+                SELECT COUNT(*)::BIGINT,
+                       COUNT(DISTINCT ip)::BIGINT,
+                       COUNT(CASE WHEN device_type = 'desktop' THEN 1 END)::BIGINT,
+                       COUNT(CASE WHEN device_type = 'mobile' THEN 1 END)::BIGINT
+                FROM clicks
+                WHERE target_id = $1
+                "#,
+            )
+            .bind(target_id)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(classify_sqlx_error)?;
+        let top_referrers = self.get_top_referrers(code, top_referrers).await?;
+        let variants = sqlx::query_as::<_, VariantCount>(
+            r#"
+            SELECT c.variant, t.target_url AS url, COUNT(*)::BIGINT AS count
+            FROM clicks c
+            JOIN urls_ab_targets t ON t.url_id = c.target_id AND t.idx = c.variant
+            WHERE c.target_id = $1
+            GROUP BY c.variant, t.target_url
+            ORDER BY c.variant
+            "#,
+        )
+        .bind(target_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(classify_sqlx_error)?;
+
+        Ok(ClickSummary {
+            total_clicks: total_clicks as u64,
+            unique_ips: unique_ips as u64,
+            desktop_clicks: desktop_clicks as u64,
+            mobile_clicks: mobile_clicks as u64,
+            top_referrers,
+            variants,
+            top_countries: None,
+        })
+    }
+
+    async fn set_redirect_type(
+        &self,
+        code: &str,
+        redirect_type: &str,
+    ) -> Result<(), DatabaseError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE urls SET redirect_type = $1
+            WHERE id = (SELECT target_id FROM all_short_codes WHERE code = $2)
+            "#,
+        )
+        .bind(redirect_type)
+        .bind(code)
+        .execute(&self.pool)
+        .await
+        .map_err(classify_sqlx_error)?;
+
+        if result.rows_affected() == 0 {
+            return Err(DatabaseError::NotFound);
+        }
+        Ok(())
+    }
+
+    async fn set_url_owner(&self, code: &str, owner_id: Uuid) -> Result<(), DatabaseError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE urls SET owner_id = $1
+            WHERE id = (SELECT target_id FROM all_short_codes WHERE code = $2)
+            "#,
+        )
+        .bind(owner_id)
+        .bind(code)
+        .execute(&self.pool)
+        .await
+        .map_err(classify_sqlx_error)?;
+
+        if result.rows_affected() == 0 {
+            return Err(DatabaseError::NotFound);
+        }
+        Ok(())
+    }
+
+    async fn set_url_password(
+        &self,
+        code: &str,
+        password_hash: &[u8],
+    ) -> Result<(), DatabaseError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE urls
+            SET password_hash = $1,
+                status = CASE WHEN status = 'active' THEN 'password_protected' ELSE status END
+            WHERE id = (SELECT target_id FROM all_short_codes WHERE code = $2)
+            "#,
+        )
+        .bind(password_hash)
+        .bind(code)
+        .execute(&self.pool)
+        .await
+        .map_err(classify_sqlx_error)?;
+
+        if result.rows_affected() == 0 {
+            return Err(DatabaseError::NotFound);
+        }
+        Ok(())
+    }
+
+    async fn set_ab_targets(&self, code: &str, targets: &[AbTarget]) -> Result<(), DatabaseError> {
+        let target_id = self.target_id(code).await?;
+        let mut tx = self.pool.begin().await.map_err(classify_sqlx_error)?;
+
+        sqlx::query("DELETE FROM urls_ab_targets WHERE url_id = $1")
+            .bind(target_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(classify_sqlx_error)?;
+        for (idx, target) in targets.iter().enumerate() {
+            sqlx::query(
+                "INSERT INTO urls_ab_targets (url_id, idx, target_url, weight) VALUES ($1, $2, $3, $4)",
+            )
+            .bind(target_id)
+            .bind(idx as i16)
+            .bind(&target.url)
+            .bind(i16::from(target.weight))
+            .execute(&mut *tx)
+            .await
+            .map_err(classify_sqlx_error)?;
+        }
+
+        tx.commit().await.map_err(classify_sqlx_error)
+    }
+
+    async fn get_ab_targets(&self, code: &str) -> Result<Vec<AbTarget>, DatabaseError> {
+        sqlx::query_as::<_, AbTarget>(
+            r#"
+            SELECT t.target_url AS url, t.weight
+            FROM urls_ab_targets t
+            JOIN all_short_codes s ON s.target_id = t.url_id
+            WHERE s.code = $1
+            ORDER BY t.idx
+            "#,
+        )
+        .bind(code)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(classify_sqlx_error)
+    }
+
+    async fn set_mobile_url(&self, code: &str, mobile_url: &str) -> Result<(), DatabaseError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE urls SET mobile_url = $1
+            WHERE id = (SELECT target_id FROM all_short_codes WHERE code = $2)
+            "#,
+        )
+        .bind(mobile_url)
+        .bind(code)
+        .execute(&self.pool)
+        .await
+        .map_err(classify_sqlx_error)?;
+
+        if result.rows_affected() == 0 {
+            return Err(DatabaseError::NotFound);
+        }
+        Ok(())
+    }
+
+    async fn set_max_clicks(&self, code: &str, max_clicks: u64) -> Result<(), DatabaseError> {
+        let max_clicks = i64::try_from(max_clicks)
+            .map_err(|_| DatabaseError::QueryError("max_clicks is too large".to_string()))?;
+        let result = sqlx::query(
+            r#"
+            UPDATE urls SET max_clicks = $1
+            WHERE id = (SELECT target_id FROM all_short_codes WHERE code = $2)
+            "#,
+        )
+        .bind(max_clicks)
+        .bind(code)
+        .execute(&self.pool)
+        .await
+        .map_err(classify_sqlx_error)?;
+
+        if result.rows_affected() == 0 {
+            return Err(DatabaseError::NotFound);
+        }
+        Ok(())
+    }
+
+    async fn get_click_count(&self, code: &str) -> Result<u64, DatabaseError> {
+        let target_id = self.target_id(code).await?;
+        let (count,) =
+            sqlx::query_as::<_, (i64,)>("SELECT COUNT(*)::BIGINT FROM clicks WHERE target_id = $1")
+                .bind(target_id)
+                .fetch_one(&self.pool)
+                .await
+                .map_err(classify_sqlx_error)?;
+        Ok(count as u64)
+    }
+
+    async fn deactivate_url(&self, code: &str) -> Result<(), DatabaseError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE urls
+            SET deactivated_at = COALESCE(deactivated_at, $1),
+                status = CASE WHEN status IN ('active', 'password_protected')
+                              THEN 'max_clicks_reached'
+                              ELSE status
+                         END
+            WHERE id = (SELECT target_id FROM all_short_codes WHERE code = $2)
+            "#,
+        )
+        .bind(Utc::now())
+        .bind(code)
+        .execute(&self.pool)
+        .await
+        .map_err(classify_sqlx_error)?;
+
+        if result.rows_affected() == 0 {
+            return Err(DatabaseError::NotFound);
+        }
+        Ok(())
+    }
+
+    async fn list_expiring_urls(
+        &self,
+        from: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<Vec<ExpiringUrl>, DatabaseError> {
+        sqlx::query_as::<_, ExpiringUrl>(
+            r#"
+            SELECT code, url, expires_at, owner_id
+            FROM urls
+            WHERE owner_id IS NOT NULL
+              AND expiry_reminder_sent_at IS NULL
+              AND status IN ('active', 'password_protected')
+              AND expires_at BETWEEN $1 AND $2
+            ORDER BY expires_at
+            "#,
+        )
+        .bind(from)
+        .bind(until)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(classify_sqlx_error)
+    }
+
+    async fn mark_expiry_reminder_sent(
+        &self,
+        code: &str,
+        sent_at: DateTime<Utc>,
+    ) -> Result<(), DatabaseError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE urls SET expiry_reminder_sent_at = $1
+            WHERE id = (SELECT target_id FROM all_short_codes WHERE code = $2)
+            "#,
+        )
+        .bind(sent_at)
+        .bind(code)
+        .execute(&self.pool)
+        .await
+        .map_err(classify_sqlx_error)?;
+
+        if result.rows_affected() == 0 {
+            return Err(DatabaseError::NotFound);
+        }
+        Ok(())
     }
 
     async fn list_short_codes(
@@ -285,11 +987,168 @@ impl UrlDatabase for PostgresUrlDatabase {
                 .bind(offset as i64)
                 .fetch_all(&self.pool)
                 .await
-                .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+                .map_err(classify_sqlx_error)?;
 
         Ok(codes)
     }
 
+    async fn list_urls(
+        &self,
+        offset: u64,
+        limit: u64,
+        owner_id: Option<Uuid>,
+    ) -> Result<Vec<UrlRecord>, DatabaseError> {
+        sqlx::query_as::<_, UrlRecord>(
+            r#"
+            SELECT code, url, owner_id
+            FROM urls
+            WHERE status <> 'deleted'
+              AND ($1::uuid IS NULL OR owner_id = $1)
+            ORDER BY id
+            LIMIT $2 OFFSET $3
+            "#,
+        )
+        .bind(owner_id)
+        .bind(limit as i64)
+        .bind(offset as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(classify_sqlx_error)
+    }
+
+    async fn count_urls_by_owner(&self, owner_id: Uuid) -> Result<u64, DatabaseError> {
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM urls WHERE status <> 'deleted' AND owner_id = $1",
+        )
+        .bind(owner_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(classify_sqlx_error)?;
+        Ok(count as u64)
+    }
+
+    async fn sum_clicks_by_owner(&self, owner_id: Uuid) -> Result<u64, DatabaseError> {
+        let count: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*)
+            FROM clicks c
+            JOIN urls u ON u.id = c.target_id
+            WHERE u.status <> 'deleted' AND u.owner_id = $1
+            "#,
+        )
+        .bind(owner_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(classify_sqlx_error)?;
+        Ok(count as u64)
+    }
+
+    async fn export_urls(
+        &self,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<ExportRecord>, DatabaseError> {
+        sqlx::query_as::<_, ExportRecord>(
+            r#"
+            SELECT u.code,
+                   u.url,
+                   u.created_at,
+                   (SELECT COUNT(*)::BIGINT FROM clicks c WHERE c.target_id = u.id) AS click_count
+            FROM urls u
+            WHERE u.status <> 'deleted'
+            ORDER BY u.id
+            LIMIT $1 OFFSET $2
+            "#,
+        )
+        .bind(limit as i64)
+        .bind(offset as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(classify_sqlx_error)
+    }
+
+    async fn add_tag(&self, code: &str, tag: &str) -> Result<(), DatabaseError> {
+        let primary = self.primary_code(code).await?;
+        sqlx::query(
+            "INSERT INTO tags (code, tag) VALUES ($1, $2) ON CONFLICT (code, tag) DO NOTHING",
+        )
+        .bind(primary)
+        .bind(tag)
+        .execute(&self.pool)
+        .await
+        .map_err(classify_sqlx_error)?;
+        Ok(())
+    }
+
+    async fn remove_tag(&self, code: &str, tag: &str) -> Result<(), DatabaseError> {
+        let primary = self.primary_code(code).await?;
+        sqlx::query("DELETE FROM tags WHERE code = $1 AND tag = $2")
+            .bind(primary)
+            .bind(tag)
+            .execute(&self.pool)
+            .await
+            .map_err(classify_sqlx_error)?;
+        Ok(())
+    }
+
+    async fn get_urls_by_tag(
+        &self,
+        tag: &str,
+        offset: u64,
+        limit: u64,
+        owner_id: Option<Uuid>,
+    ) -> Result<Vec<UrlRecord>, DatabaseError> {
+        sqlx::query_as::<_, UrlRecord>(
+            r#"
+            SELECT u.code, u.url, u.owner_id
+            FROM tags t
+            JOIN urls u ON u.code = t.code
+            WHERE t.tag = $1
+              AND u.status <> 'deleted'
+              AND ($2::uuid IS NULL OR u.owner_id = $2)
+            ORDER BY t.id
+            LIMIT $3 OFFSET $4
+            "#,
+        )
+        .bind(tag)
+        .bind(owner_id)
+        .bind(limit as i64)
+        .bind(offset as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(classify_sqlx_error)
+    }
+
+    async fn search_urls(
+        &self,
+        query: &str,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<UrlRecord>, DatabaseError> {
+        let pattern = like_pattern(query);
+        sqlx::query_as::<_, UrlRecord>(
+            r#"
+            SELECT u.code, u.url, u.owner_id
+            FROM urls u
+            WHERE u.status <> 'deleted'
+              AND (u.url ILIKE $1 ESCAPE '\'
+                   OR u.code ILIKE $1 ESCAPE '\'
+                   OR EXISTS (
+                        SELECT 1 FROM aliases a
+                        WHERE a.target_id = u.id AND a.alias ILIKE $1 ESCAPE '\'
+                   ))
+            ORDER BY u.id
+            LIMIT $2 OFFSET $3
+            "#,
+        )
+        .bind(&pattern)
+        .bind(limit as i64)
+        .bind(offset as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(classify_sqlx_error)
+    }
+
     async fn insert_alias(&self, alias_code: &str, code_id: i64) -> Result<(), DatabaseError> {
         sqlx::query("INSERT INTO aliases (alias, target_id) VALUES ($1, $2)")
             .bind(alias_code)
@@ -300,7 +1159,7 @@ impl UrlDatabase for PostgresUrlDatabase {
                 if is_unique_violation(&e) {
                     DatabaseError::Duplicate
                 } else {
-                    DatabaseError::QueryError(e.to_string())
+                    classify_sqlx_error(e)
                 }
             })?;
         Ok(())
@@ -313,7 +1172,7 @@ impl UrlDatabase for PostgresUrlDatabase {
         .bind(name)
         .fetch_optional(&self.pool)
         .await
-        .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+        .map_err(classify_sqlx_error)?;
 
         Ok(data)
     }
@@ -333,10 +1192,62 @@ impl UrlDatabase for PostgresUrlDatabase {
         .bind(data)
         .execute(&self.pool)
         .await
-        .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+        .map_err(classify_sqlx_error)?;
         Ok(())
     }
+
+    async fn health_check(&self) -> Result<(), DatabaseError> {
+        sqlx::query("SELECT 1")
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| DatabaseError::ConnectionError(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn get_stats(&self) -> Result<DatabaseStats, DatabaseError> {
+        let (total_urls, total_clicks, size_bytes) = sqlx::query_as::<_, (i64, i64, i64)>(
+            r#"
+            SELECT (SELECT COUNT(*) FROM urls WHERE status <> 'deleted'),
+                   (SELECT COUNT(*) FROM clicks),
+                   pg_database_size(current_database())
+            "#,
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(classify_sqlx_error)?;
+
+        Ok(DatabaseStats {
+            total_urls: total_urls as u64,
+            total_clicks: total_clicks as u64,
+            size_bytes: size_bytes as u64,
+        })
+    }
+
+    async fn pending_migrations(&self) -> Result<usize, DatabaseError> {
+        let embedded = sqlx::migrate!("./migrations/pg")
+            .iter()
+            .filter(|m| !m.migration_type.is_down_migration())
+            .count();
+        let applied: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM _sqlx_migrations WHERE success")
+                .fetch_one(&self.pool)
+                .await
+                .map_err(classify_sqlx_error)?;
+        Ok(embedded.saturating_sub(applied as usize))
+    }
+
+    async fn run_maintenance(&self) -> Vec<MaintenanceStep> {
+        let operation = "VACUUM ANALYZE urls";
+        let started = std::time::Instant::now();
+        let result = sqlx::query(operation).execute(&self.pool).await;
+        vec![MaintenanceStep {
+            operation,
+            duration: started.elapsed(),
+            error: result.err().map(|e| e.to_string()),
+        }]
+    }
 }
+
 /// Creates a PostgreSQL connection pool from configuration settings.
 ///
 /// # Arguments
@@ -362,6 +1273,7 @@ impl UrlDatabase for PostgresUrlDatabase {
 ///     create_if_missing: false,
 ///     max_connections: Some(16),
 ///     min_connections: Some(4),
+///     busy_timeout_ms: None,
 /// };
 /// let pool = get_connection_pool(&config).await?;
 /// # Ok(())
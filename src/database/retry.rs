@@ -0,0 +1,139 @@
+//! # Transient Error Retries
+//!
+//! PostgreSQL aborts some statements for reasons that go away on their own:
+//! a serialization failure (`40001`), a lock that could not be taken
+//! (`55P03`) or a cancelled statement (`57014`). These surface as
+//! [`DatabaseError::Transient`], and [`retry_transient`] runs the operation
+//! again, with exponential backoff, instead of answering the request with a
+//! 500.
+
+use super::DatabaseError;
+
+use std::time::Duration;
+
+/// Number of times an operation is retried after a transient error.
+pub const MAX_TRANSIENT_RETRIES: usize = 3;
+
+/// Wait before the first retry; doubled before each further one.
+const TRANSIENT_RETRY_BASE_DELAY: Duration = Duration::from_millis(10);
+
+/// PostgreSQL error codes of statements that may succeed when run again.
+pub const TRANSIENT_PG_CODES: [&str; 3] = ["40001", "55P03", "57014"];
+
+/// Maps an sqlx error to [`DatabaseError::Transient`] when its PostgreSQL
+/// error code is one of [`TRANSIENT_PG_CODES`], or to
+/// [`DatabaseError::QueryError`] otherwise.
+pub fn classify_sqlx_error(error: sqlx::Error) -> DatabaseError {
+    let transient = error
+        .as_database_error()
+        .and_then(|db| db.code())
+        .is_some_and(|code| TRANSIENT_PG_CODES.contains(&code.as_ref()));
+    if transient {
+        DatabaseError::Transient(error.to_string())
+    } else {
+        DatabaseError::QueryError(error.to_string())
+    }
+}
+
+/// Runs `operation`, running it again up to [`MAX_TRANSIENT_RETRIES`] times
+/// while it fails with [`DatabaseError::Transient`].
+///
+/// Retries wait 10, 20 and 40 ms. Any other error, and the last transient
+/// one, is returned as is. `name` labels the retry log lines.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// let meta = retry_transient("get_url_with_metadata", || {
+///     state.database().get_url_with_metadata(id)
+/// })
+/// .await?;
+/// ```
+pub async fn retry_transient<T, F, Fut>(name: &str, mut operation: F) -> Result<T, DatabaseError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, DatabaseError>>,
+{
+    let mut delay = TRANSIENT_RETRY_BASE_DELAY;
+    for attempt in 1..=MAX_TRANSIENT_RETRIES {
+        match operation().await {
+            Err(DatabaseError::Transient(e)) => {
+                tracing::warn!(
+                    operation = name,
+                    attempt,
+                    delay_ms = delay.as_millis() as u64,
+                    "transient database error, retrying: {}",
+                    e
+                );
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            }
+            result => return result,
+        }
+    }
+    operation().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // An operation failing transiently `failures` times before succeeding
+    fn flaky(
+        calls: &AtomicUsize,
+        failures: usize,
+    ) -> impl Future<Output = Result<u8, DatabaseError>> {
+        let call = calls.fetch_add(1, Ordering::SeqCst);
+        async move {
+            if call < failures {
+                Err(DatabaseError::Transient(
+                    "serialization failure".to_string(),
+                ))
+            } else {
+                Ok(7)
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn succeeds_after_two_transient_errors() {
+        let calls = AtomicUsize::new(0);
+
+        let result = retry_transient("op", || flaky(&calls, 2)).await;
+
+        assert_eq!(result.unwrap(), 7);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_retries() {
+        let calls = AtomicUsize::new(0);
+
+        let result = retry_transient("op", || flaky(&calls, usize::MAX)).await;
+
+        assert!(matches!(result, Err(DatabaseError::Transient(_))));
+        assert_eq!(calls.load(Ordering::SeqCst), MAX_TRANSIENT_RETRIES + 1);
+    }
+
+    #[tokio::test]
+    async fn other_errors_are_not_retried() {
+        let calls = AtomicUsize::new(0);
+
+        let result: Result<u8, _> = retry_transient("op", || {
+            calls.fetch_add(1, Ordering::SeqCst);
+            async { Err(DatabaseError::NotFound) }
+        })
+        .await;
+
+        assert!(matches!(result, Err(DatabaseError::NotFound)));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn non_database_sqlx_errors_are_not_transient() {
+        let error = classify_sqlx_error(sqlx::Error::RowNotFound);
+
+        assert!(matches!(error, DatabaseError::QueryError(_)));
+    }
+}
@@ -36,6 +36,7 @@
 //!     create_if_missing: true,
 //!     max_connections: Some(16),
 //!     min_connections: Some(4),
+//!     busy_timeout_ms: None,
 //! };
 //! let db = SqliteUrlDatabase::from_config(&config).await?;
 //!
@@ -49,17 +50,30 @@
 //! # }
 //! ```
 
-use super::{DatabaseError, UrlDatabase};
-use crate::configuration::DatabaseSettings;
-use crate::models::{UpsertResult, Urls};
+use super::{
+    BATCH_INSERT_CHUNK, DatabaseError, UrlDatabase, collect_batch_results, like_pattern,
+    referrer_origin,
+};
+use crate::configuration::{DatabaseSettings, DatabaseType};
+use crate::models::{
+    AbTarget, BatchInsertResult, ClickBucket, ClickRecord, ClickSummary, CountryCount,
+    DatabaseStats, ExpiringUrl, ExportRecord, MaintenanceStep, ReferrerCount, UpsertResult,
+    UrlMetadata, UrlRecord, UrlStatus, Urls, VariantCount,
+};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use sha2::{Digest, Sha256};
-use sqlx::sqlite::SqlitePoolOptions;
-use sqlx::{SqlitePool, sqlite::SqliteConnectOptions};
+use sqlx::sqlite::{SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous};
+use sqlx::{QueryBuilder, Sqlite, SqlitePool, sqlite::SqliteConnectOptions};
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
+use std::time::Duration;
+use uuid::Uuid;
 
 const MAX_CAP: u32 = 64;
 const MIN_CAP: u32 = 1;
+/// Lock wait used when `busy_timeout_ms` is not configured.
+const DEFAULT_BUSY_TIMEOUT_MS: u32 = 5000;
 
 /// SQLite implementation of the [`UrlDatabase`] trait.
 ///
@@ -86,6 +100,7 @@ const MIN_CAP: u32 = 1;
 ///     create_if_missing: true,
 ///     max_connections: Some(16),
 ///     min_connections: Some(4),
+///     busy_timeout_ms: None,
 /// };
 /// let db = SqliteUrlDatabase::from_config(&config).await?;
 /// # Ok(())
@@ -147,6 +162,7 @@ impl SqliteUrlDatabase {
     ///     create_if_missing: true,
     ///     max_connections: Some(16),
     ///     min_connections: Some(4),
+    ///     busy_timeout_ms: None,
     /// };
     /// let db = SqliteUrlDatabase::from_config(&config).await?;
     /// # Ok(())
@@ -160,6 +176,44 @@ impl SqliteUrlDatabase {
         Ok(Self::new(pool))
     }
 
+    /// Creates an empty in-memory database, e.g. for tests.
+    ///
+    /// The pool's connections share the one database, which lives as long as
+    /// the pool keeps a connection open; at least one always is.
+    pub async fn in_memory() -> Result<Self, DatabaseError> {
+        Self::from_config(&DatabaseSettings {
+            r#type: DatabaseType::Sqlite,
+            url: ":memory:".to_string(),
+            create_if_missing: true,
+            max_connections: None,
+            min_connections: Some(1),
+            busy_timeout_ms: None,
+        })
+        .await
+    }
+
+    /// Creates an in-memory database, like [`in_memory`](Self::in_memory),
+    /// with the schema migrated.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use url_shortener_ztm_lib::database::{SqliteUrlDatabase, UrlDatabase};
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let db = SqliteUrlDatabase::in_memory_with_migrations().await?;
+    /// db.insert_url("abc123", "https://example.com/").await?;
+    /// assert_eq!(db.get_url("abc123").await?, "https://example.com/");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn in_memory_with_migrations() -> Result<Self, DatabaseError> {
+        let db = Self::in_memory().await?;
+        db.migrate().await?;
+        Ok(db)
+    }
+
     /// Runs database migrations to set up the schema.
     ///
     /// This method applies all migration files found in the `./migrations` directory.
@@ -179,7 +233,7 @@ impl SqliteUrlDatabase {
     /// use url_shortener_ztm_lib::configuration::DatabaseSettings;
     ///
     /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
-    /// let config = DatabaseSettings { r#type: DatabaseType::Sqlite, url: "database.db".to_string(), create_if_missing: true, max_connections: Some(16),  min_connections: Some(4), }; let db = SqliteUrlDatabase::from_config(&config).await?;
+    /// let config = DatabaseSettings { r#type: DatabaseType::Sqlite, url: "database.db".to_string(), create_if_missing: true, max_connections: Some(16),  min_connections: Some(4), busy_timeout_ms: None, }; let db = SqliteUrlDatabase::from_config(&config).await?;
     /// db.migrate().await?; // Set up the database schema
     /// # Ok(())
     /// # }
@@ -192,6 +246,28 @@ impl SqliteUrlDatabase {
 
         Ok(())
     }
+
+    /// Resolves a short code or alias to the primary code of its URL row.
+    async fn primary_code(&self, code: &str) -> Result<String, DatabaseError> {
+        sqlx::query_scalar::<_, String>(
+            "SELECT u.code FROM all_short_codes s JOIN urls u ON u.id = s.target_id WHERE s.code = ? LIMIT 1",
+        )
+        .bind(code)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(e.to_string()))?
+        .ok_or(DatabaseError::NotFound)
+    }
+
+    /// Resolves a short code or alias to the id of its URL row.
+    async fn target_id(&self, code: &str) -> Result<i64, DatabaseError> {
+        sqlx::query_scalar::<_, i64>("SELECT target_id FROM all_short_codes WHERE code = ? LIMIT 1")
+            .bind(code)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| DatabaseError::QueryError(e.to_string()))?
+            .ok_or(DatabaseError::NotFound)
+    }
 }
 
 #[async_trait]
@@ -200,11 +276,13 @@ impl UrlDatabase for SqliteUrlDatabase {
     async fn get_id_by_url(&self, url: &str) -> Result<Urls, DatabaseError> {
         let hash = sha256_bytes(url);
 
-        let row = sqlx::query_as::<_, Urls>("SELECT id, code FROM urls WHERE url_hash = ? LIMIT 1")
-            .bind(&hash[..]) // BLOB
-            .fetch_optional(&self.pool)
-            .await
-            .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+        let row = sqlx::query_as::<_, Urls>(
+            "SELECT id, code FROM urls WHERE url_hash = ? AND status <> 'deleted' LIMIT 1",
+        )
+        .bind(&hash[..]) // BLOB
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
 
         match row {
             Some(record) => Ok(record),
@@ -235,7 +313,7 @@ impl UrlDatabase for SqliteUrlDatabase {
     /// use url_shortener_ztm_lib::configuration::DatabaseSettings;
     ///
     /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
-    /// let config = DatabaseSettings { r#type: DatabaseType::Sqlite, url: "database.db".to_string(), create_if_missing: true, max_connections: Some(16),  min_connections: Some(4),}; let db = SqliteUrlDatabase::from_config(&config).await?;
+    /// let config = DatabaseSettings { r#type: DatabaseType::Sqlite, url: "database.db".to_string(), create_if_missing: true, max_connections: Some(16),  min_connections: Some(4), busy_timeout_ms: None, }; let db = SqliteUrlDatabase::from_config(&config).await?;
     /// db.insert_url("abc123", "https://example.com").await?;
     /// # Ok(())
     /// # }
@@ -294,6 +372,85 @@ impl UrlDatabase for SqliteUrlDatabase {
         Ok((upsert_result, existing_urls))
     }
 
+    async fn insert_url_with_expiry(
+        &self,
+        code: &str,
+        url: &str,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<(UpsertResult, Urls), DatabaseError> {
+        let (upsert, urls) = self.insert_url(code, url).await?;
+        if upsert.created && expires_at.is_some() {
+            sqlx::query("UPDATE urls SET expires_at = ? WHERE id = ?")
+                .bind(expires_at)
+                .bind(upsert.id)
+                .execute(&self.pool)
+                .await
+                .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+        }
+        Ok((upsert, urls))
+    }
+
+    /// Stores many URLs in one transaction using chunked `INSERT OR IGNORE` statements.
+    ///
+    /// Each chunk inserts up to [`BATCH_INSERT_CHUNK`] rows and then reads back the
+    /// stored codes for the chunk's URL hashes, so existing URLs resolve to their
+    /// original code. Rows whose code is an existing alias are filtered out up
+    /// front, since `OR IGNORE` does not suppress the overlap trigger's `ABORT`.
+    async fn insert_urls_batch(
+        &self,
+        items: &[(&str, &str)],
+    ) -> Result<Vec<BatchInsertResult>, DatabaseError> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+
+        let mut created: HashSet<String> = HashSet::new();
+        let mut stored: HashMap<String, String> = HashMap::new();
+
+        for chunk in items.chunks(BATCH_INSERT_CHUNK) {
+            let mut insert = QueryBuilder::<Sqlite>::new(
+                "INSERT OR IGNORE INTO urls (code, url, url_hash) \
+                 SELECT column1, column2, column3 FROM (",
+            );
+            insert.push_values(chunk, |mut row, (code, url)| {
+                row.push_bind(*code)
+                    .push_bind(*url)
+                    .push_bind(sha256_bytes(url).to_vec());
+            });
+            insert.push(") WHERE column1 NOT IN (SELECT alias FROM aliases) RETURNING code");
+
+            let inserted: Vec<String> = insert
+                .build_query_scalar()
+                .fetch_all(&mut *tx)
+                .await
+                .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+            created.extend(inserted);
+
+            let mut select =
+                QueryBuilder::<Sqlite>::new("SELECT code, url FROM urls WHERE url_hash IN (");
+            let mut hashes = select.separated(", ");
+            for (_, url) in chunk {
+                hashes.push_bind(sha256_bytes(url).to_vec());
+            }
+            hashes.push_unseparated(")");
+
+            let rows: Vec<(String, String)> = select
+                .build_query_as()
+                .fetch_all(&mut *tx)
+                .await
+                .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+            stored.extend(rows.into_iter().map(|(code, url)| (url, code)));
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+
+        Ok(collect_batch_results(items, &created, &stored))
+    }
+
     /// Retrieves a URL by its short ID from the SQLite database.
     ///
     /// This implementation uses a prepared statement with optional result handling
@@ -305,8 +462,10 @@ impl UrlDatabase for SqliteUrlDatabase {
     ///
     /// # Returns
     ///
-    /// Returns `Ok(String)` with the original URL if found, or an error if:
+    /// Returns `Ok(String)` with the original URL if it still resolves, or an
+    /// error if:
     /// - The ID was not found (`DatabaseError::NotFound`)
+    /// - The URL expired or reached its click limit (`DatabaseError::Gone`)
     /// - A database error occurred (`DatabaseError::QueryError`)
     ///
     /// # Examples
@@ -317,25 +476,601 @@ impl UrlDatabase for SqliteUrlDatabase {
     /// use url_shortener_ztm_lib::configuration::DatabaseSettings;
     ///
     /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
-    /// let config = DatabaseSettings { r#type: DatabaseType::Sqlite, url: "database.db".to_string(), create_if_missing: true, max_connections: Some(16),  min_connections: Some(4),}; let db = SqliteUrlDatabase::from_config(&config).await?;
+    /// let config = DatabaseSettings { r#type: DatabaseType::Sqlite, url: "database.db".to_string(), create_if_missing: true, max_connections: Some(16),  min_connections: Some(4), busy_timeout_ms: None, }; let db = SqliteUrlDatabase::from_config(&config).await?;
     /// let url = db.get_url("abc123").await?;
     /// println!("Original URL: {}", url);
     /// # Ok(())
     /// # }
     /// ```
     async fn get_url(&self, id: &str) -> Result<String, DatabaseError> {
-        let row = sqlx::query_as::<_, (String,)>(
-            "SELECT url FROM all_short_codes u WHERE u.code = ? LIMIT 1;",
+        let row = sqlx::query_as::<_, (String, i64, UrlStatus)>(
+            r#"
+                SELECT s.url,
+                       u.id,
+                       CASE WHEN u.status IN ('active', 'password_protected')
+                                 AND julianday(u.expires_at) <= julianday('now')
+                            THEN 'expired'
+                            ELSE u.status
+                       END AS status
+                FROM all_short_codes s
+                JOIN urls u ON u.id = s.target_id
+                WHERE s.code = ?
+                LIMIT 1;
+            "#,
         )
         .bind(id)
         .fetch_optional(&self.pool)
         .await
         .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
 
-        match row {
-            Some(record) => Ok(record.0),
-            None => Err(DatabaseError::NotFound),
+        let Some((url, target_id, status)) = row else {
+            return Err(DatabaseError::NotFound);
+        };
+        match status {
+            UrlStatus::Active | UrlStatus::PasswordProtected => Ok(url),
+            UrlStatus::Deleted => Err(DatabaseError::NotFound),
+            UrlStatus::Expired => {
+                sqlx::query(
+                    "UPDATE urls SET status = 'expired' WHERE id = ? AND status IN ('active', 'password_protected')",
+                )
+                .bind(target_id)
+                .execute(&self.pool)
+                .await
+                .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+                Err(DatabaseError::Gone(status))
+            }
+            UrlStatus::MaxClicksReached => Err(DatabaseError::Gone(status)),
+        }
+    }
+
+    /// Retrieves a short code and its metadata from the SQLite database.
+    ///
+    /// SQLite has no `LATERAL`, so the click aggregate is a correlated subquery
+    /// that stays on the `clicks_target_id_idx` index.
+    async fn get_url_with_metadata(&self, code: &str) -> Result<UrlMetadata, DatabaseError> {
+        let row = sqlx::query_as::<_, UrlMetadata>(
+            r#"
+                SELECT s.code,
+                       s.url,
+                       u.created_at,
+                       (SELECT COUNT(*) FROM clicks c WHERE c.target_id = u.id) AS click_count,
+                       u.preview_views,
+                       u.redirect_type,
+                       u.expires_at,
+                       (u.deactivated_at IS NULL
+                        AND (u.expires_at IS NULL OR julianday(u.expires_at) > julianday('now')))
+                           AS is_active,
+                       u.owner_id,
+                       u.password_hash,
+                       u.mobile_url,
+                       u.max_clicks,
+                       u.deactivated_at,
+                       COALESCE(u.updated_at, u.created_at) AS updated_at,
+                       CASE WHEN u.status IN ('active', 'password_protected')
+                                 AND julianday(u.expires_at) <= julianday('now')
+                            THEN 'expired'
+                            ELSE u.status
+                       END AS status
+                FROM all_short_codes s
+                JOIN urls u ON u.id = s.target_id
+                WHERE s.code = ?
+                LIMIT 1;
+            "#,
+        )
+        .bind(code)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+
+        row.ok_or(DatabaseError::NotFound)
+    }
+
+    /// Soft-deletes a URL. `url_hash` is `UNIQUE` on every row, so the deleted
+    /// row's hash is swapped for one of its code, freeing the destination to be
+    /// shortened again.
+    async fn delete_url(&self, code: &str) -> Result<(), DatabaseError> {
+        let alias = sqlx::query(
+            r#"
+            DELETE FROM aliases
+            WHERE alias = ?
+              AND target_id IN (SELECT id FROM urls WHERE status <> 'deleted')
+            "#,
+        )
+        .bind(code)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+        if alias.rows_affected() > 0 {
+            return Ok(());
+        }
+
+        let tombstone = sha256_bytes(&format!("deleted:{code}"));
+        let url = sqlx::query(
+            "UPDATE urls SET status = 'deleted', url_hash = ? WHERE code = ? AND status <> 'deleted'",
+        )
+        .bind(&tombstone[..])
+        .bind(code)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+        if url.rows_affected() == 0 {
+            return Err(DatabaseError::NotFound);
+        }
+        Ok(())
+    }
+
+    async fn update_url(&self, code: &str, new_url: &str) -> Result<(), DatabaseError> {
+        let hash = sha256_bytes(new_url);
+        let result = sqlx::query(
+            r#"
+            UPDATE urls SET url = ?, url_hash = ?, updated_at = ?
+            WHERE id = (SELECT target_id FROM all_short_codes WHERE code = ?)
+            "#,
+        )
+        .bind(new_url)
+        .bind(&hash[..])
+        .bind(Utc::now())
+        .bind(code)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            if e.to_string()
+                .contains("UNIQUE constraint failed: urls.url_hash")
+            {
+                DatabaseError::Duplicate
+            } else {
+                DatabaseError::QueryError(e.to_string())
+            }
+        })?;
+
+        if result.rows_affected() == 0 {
+            return Err(DatabaseError::NotFound);
+        }
+        Ok(())
+    }
+
+    async fn record_preview_view(&self, code: &str) -> Result<(), DatabaseError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE urls SET preview_views = preview_views + 1
+            WHERE id = (SELECT target_id FROM all_short_codes WHERE code = ?)
+            "#,
+        )
+        .bind(code)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+
+        if result.rows_affected() == 0 {
+            return Err(DatabaseError::NotFound);
+        }
+        Ok(())
+    }
+
+    async fn record_click(
+        &self,
+        code: &str,
+        clicked_at: DateTime<Utc>,
+        ip: Option<&str>,
+        referrer: Option<&str>,
+        variant: Option<u8>,
+        device_type: Option<&str>,
+        country_code: Option<&str>,
+        user_agent: Option<&str>,
+    ) -> Result<(), DatabaseError> {
+        let target_id = self.target_id(code).await?;
+        sqlx::query(
+            r#"
+            INSERT INTO clicks
+                (target_id, clicked_at, ip, referrer_origin, variant, device_type, country_code,
+                 user_agent)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(target_id)
+        .bind(clicked_at)
+        .bind(ip)
+        .bind(referrer.and_then(referrer_origin))
+        .bind(variant.map(i16::from))
+        .bind(device_type)
+        .bind(country_code)
+        .bind(user_agent)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+
+        sqlx::query("UPDATE urls SET updated_at = ? WHERE id = ?")
+            .bind(clicked_at)
+            .bind(target_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn get_recent_clicks(
+        &self,
+        code: &str,
+        limit: u8,
+    ) -> Result<Vec<ClickRecord>, DatabaseError> {
+        let target_id = self.target_id(code).await?;
+        sqlx::query_as::<_, ClickRecord>(
+            r#"
+            SELECT c.id,
+                   u.code,
+                   c.ip,
+                   c.user_agent,
+                   c.referrer_origin,
+                   c.country_code,
+                   c.device_type,
+                   c.clicked_at
+            FROM clicks c
+            JOIN urls u ON u.id = c.target_id
+            WHERE c.target_id = ?
+            ORDER BY julianday(c.clicked_at) DESC, c.id DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(target_id)
+        .bind(i64::from(limit))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(e.to_string()))
+    }
+
+    async fn get_clicks_over_time(
+        &self,
+        code: &str,
+        bucket: &str,
+    ) -> Result<Vec<ClickBucket>, DatabaseError> {
+        let format = match bucket {
+            "hour" => "%Y-%m-%dT%H:00:00Z",
+            "day" => "%Y-%m-%dT00:00:00Z",
+            other => {
+                return Err(DatabaseError::QueryError(format!(
+                    "unsupported click bucket {:?}",
+                    other
+                )));
+            }
+        };
+        let target_id = self.target_id(code).await?;
+        sqlx::query_as::<_, ClickBucket>(
+            r#"
+            SELECT strftime(?, clicked_at) AS timestamp, COUNT(*) AS count
+            FROM clicks
+            WHERE target_id = ?
+            GROUP BY 1
+            ORDER BY 1
+            "#,
+        )
+        .bind(format)
+        .bind(target_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(e.to_string()))
+    }
+
+    async fn get_top_referrers(
+        &self,
+        code: &str,
+        limit: u8,
+    ) -> Result<Vec<ReferrerCount>, DatabaseError> {
+        let target_id = self.target_id(code).await?;
+        sqlx::query_as::<_, ReferrerCount>(
+            r#"
+            SELECT referrer_origin, COUNT(*) AS count
+            FROM clicks
+            WHERE target_id = ? AND referrer_origin IS NOT NULL
+            GROUP BY referrer_origin
+            ORDER BY count DESC, referrer_origin
+            LIMIT ?
+            "#,
+        )
+        .bind(target_id)
+        .bind(i64::from(limit))
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(e.to_string()))
+    }
+
+    async fn get_clicks_by_country(&self, code: &str) -> Result<Vec<CountryCount>, DatabaseError> {
+        let target_id = self.target_id(code).await?;
+        sqlx::query_as::<_, CountryCount>(
+            r#"
+            SELECT country_code, COUNT(*) AS count
+            FROM clicks
+            WHERE target_id = ? AND country_code IS NOT NULL
+            GROUP BY country_code
+            ORDER BY count DESC, country_code
+            "#,
+        )
+        .bind(target_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(e.to_string()))
+    }
+
+    async fn get_click_summary(
+        &self,
+        code: &str,
+        top_referrers: u8,
+    ) -> Result<ClickSummary, DatabaseError> {
+        let target_id = self.target_id(code).await?;
+        let (total_clicks, unique_ips, desktop_clicks, mobile_clicks) =
+            sqlx::query_as::<_, (i64, i64, i64, i64)>(
+                r#"
+                SELECT COUNT(*),
+                       COUNT(DISTINCT ip),
+                       COUNT(CASE WHEN device_type = 'desktop' THEN 1 END),
+                       COUNT(CASE WHEN device_type = 'mobile' THEN 1 END)
+                FROM clicks
+                WHERE target_id = ?
+                "#,
+            )
+            .bind(target_id)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+        let top_referrers = self.get_top_referrers(code, top_referrers).await?;
+        let variants = sqlx::query_as::<_, VariantCount>(
+            r#"
+            SELECT c.variant, t.target_url AS url, COUNT(*) AS count
+            FROM clicks c
+            JOIN urls_ab_targets t ON t.url_id = c.target_id AND t.idx = c.variant
+            WHERE c.target_id = ?
+            GROUP BY c.variant, t.target_url
+            ORDER BY c.variant
+            "#,
+        )
+        .bind(target_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+
+        Ok(ClickSummary {
+            total_clicks: total_clicks as u64,
+            unique_ips: unique_ips as u64,
+            desktop_clicks: desktop_clicks as u64,
+            mobile_clicks: mobile_clicks as u64,
+            top_referrers,
+            variants,
+            top_countries: None,
+        })
+    }
+
+    async fn set_redirect_type(
+        &self,
+        code: &str,
+        redirect_type: &str,
+    ) -> Result<(), DatabaseError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE urls SET redirect_type = ?
+            WHERE id = (SELECT target_id FROM all_short_codes WHERE code = ?)
+            "#,
+        )
+        .bind(redirect_type)
+        .bind(code)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+
+        if result.rows_affected() == 0 {
+            return Err(DatabaseError::NotFound);
+        }
+        Ok(())
+    }
+
+    async fn set_url_owner(&self, code: &str, owner_id: Uuid) -> Result<(), DatabaseError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE urls SET owner_id = ?
+            WHERE id = (SELECT target_id FROM all_short_codes WHERE code = ?)
+            "#,
+        )
+        .bind(owner_id)
+        .bind(code)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+
+        if result.rows_affected() == 0 {
+            return Err(DatabaseError::NotFound);
+        }
+        Ok(())
+    }
+
+    async fn set_url_password(
+        &self,
+        code: &str,
+        password_hash: &[u8],
+    ) -> Result<(), DatabaseError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE urls
+            SET password_hash = ?,
+                status = CASE WHEN status = 'active' THEN 'password_protected' ELSE status END
+            WHERE id = (SELECT target_id FROM all_short_codes WHERE code = ?)
+            "#,
+        )
+        .bind(password_hash)
+        .bind(code)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+
+        if result.rows_affected() == 0 {
+            return Err(DatabaseError::NotFound);
+        }
+        Ok(())
+    }
+
+    async fn set_ab_targets(&self, code: &str, targets: &[AbTarget]) -> Result<(), DatabaseError> {
+        let target_id = self.target_id(code).await?;
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+
+        sqlx::query("DELETE FROM urls_ab_targets WHERE url_id = ?")
+            .bind(target_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+        for (idx, target) in targets.iter().enumerate() {
+            sqlx::query(
+                "INSERT INTO urls_ab_targets (url_id, idx, target_url, weight) VALUES (?, ?, ?, ?)",
+            )
+            .bind(target_id)
+            .bind(idx as i16)
+            .bind(&target.url)
+            .bind(i16::from(target.weight))
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| DatabaseError::QueryError(e.to_string()))
+    }
+
+    async fn get_ab_targets(&self, code: &str) -> Result<Vec<AbTarget>, DatabaseError> {
+        sqlx::query_as::<_, AbTarget>(
+            r#"
+            SELECT t.target_url AS url, t.weight
+            FROM urls_ab_targets t
+            JOIN all_short_codes s ON s.target_id = t.url_id
+            WHERE s.code = ?
+            ORDER BY t.idx
+            "#,
+        )
+        .bind(code)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(e.to_string()))
+    }
+
+    async fn set_mobile_url(&self, code: &str, mobile_url: &str) -> Result<(), DatabaseError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE urls SET mobile_url = ?
+            WHERE id = (SELECT target_id FROM all_short_codes WHERE code = ?)
+            "#,
+        )
+        .bind(mobile_url)
+        .bind(code)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+
+        if result.rows_affected() == 0 {
+            return Err(DatabaseError::NotFound);
+        }
+        Ok(())
+    }
+
+    async fn set_max_clicks(&self, code: &str, max_clicks: u64) -> Result<(), DatabaseError> {
+        let max_clicks = i64::try_from(max_clicks)
+            .map_err(|_| DatabaseError::QueryError("max_clicks is too large".to_string()))?;
+        let result = sqlx::query(
+            r#"
+            UPDATE urls SET max_clicks = ?
+            WHERE id = (SELECT target_id FROM all_short_codes WHERE code = ?)
+            "#,
+        )
+        .bind(max_clicks)
+        .bind(code)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+
+        if result.rows_affected() == 0 {
+            return Err(DatabaseError::NotFound);
         }
+        Ok(())
+    }
+
+    async fn get_click_count(&self, code: &str) -> Result<u64, DatabaseError> {
+        let target_id = self.target_id(code).await?;
+        let (count,) =
+            sqlx::query_as::<_, (i64,)>("SELECT COUNT(*) FROM clicks WHERE target_id = ?")
+                .bind(target_id)
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+        Ok(count as u64)
+    }
+
+    async fn deactivate_url(&self, code: &str) -> Result<(), DatabaseError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE urls
+            SET deactivated_at = COALESCE(deactivated_at, ?),
+                status = CASE WHEN status IN ('active', 'password_protected')
+                              THEN 'max_clicks_reached'
+                              ELSE status
+                         END
+            WHERE id = (SELECT target_id FROM all_short_codes WHERE code = ?)
+            "#,
+        )
+        .bind(Utc::now())
+        .bind(code)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+
+        if result.rows_affected() == 0 {
+            return Err(DatabaseError::NotFound);
+        }
+        Ok(())
+    }
+
+    async fn list_expiring_urls(
+        &self,
+        from: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<Vec<ExpiringUrl>, DatabaseError> {
+        sqlx::query_as::<_, ExpiringUrl>(
+            r#"
+            SELECT code, url, expires_at, owner_id
+            FROM urls
+            WHERE owner_id IS NOT NULL
+              AND expiry_reminder_sent_at IS NULL
+              AND status IN ('active', 'password_protected')
+              AND julianday(expires_at) BETWEEN julianday(?) AND julianday(?)
+            ORDER BY julianday(expires_at)
+            "#,
+        )
+        .bind(from)
+        .bind(until)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(e.to_string()))
+    }
+
+    async fn mark_expiry_reminder_sent(
+        &self,
+        code: &str,
+        sent_at: DateTime<Utc>,
+    ) -> Result<(), DatabaseError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE urls SET expiry_reminder_sent_at = ?
+            WHERE id = (SELECT target_id FROM all_short_codes WHERE code = ?)
+            "#,
+        )
+        .bind(sent_at)
+        .bind(code)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+
+        if result.rows_affected() == 0 {
+            return Err(DatabaseError::NotFound);
+        }
+        Ok(())
     }
 
     async fn list_short_codes(
@@ -354,6 +1089,165 @@ impl UrlDatabase for SqliteUrlDatabase {
         Ok(codes)
     }
 
+    async fn list_urls(
+        &self,
+        offset: u64,
+        limit: u64,
+        owner_id: Option<Uuid>,
+    ) -> Result<Vec<UrlRecord>, DatabaseError> {
+        sqlx::query_as::<_, UrlRecord>(
+            r#"
+            SELECT code, url, owner_id
+            FROM urls
+            WHERE status <> 'deleted'
+              AND (? IS NULL OR owner_id = ?)
+            ORDER BY id
+            LIMIT ? OFFSET ?
+            "#,
+        )
+        .bind(owner_id)
+        .bind(owner_id)
+        .bind(limit as i64)
+        .bind(offset as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(e.to_string()))
+    }
+
+    async fn count_urls_by_owner(&self, owner_id: Uuid) -> Result<u64, DatabaseError> {
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM urls WHERE status <> 'deleted' AND owner_id = ?",
+        )
+        .bind(owner_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+        Ok(count as u64)
+    }
+
+    async fn sum_clicks_by_owner(&self, owner_id: Uuid) -> Result<u64, DatabaseError> {
+        let count: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*)
+            FROM clicks c
+            JOIN urls u ON u.id = c.target_id
+            WHERE u.status <> 'deleted' AND u.owner_id = ?
+            "#,
+        )
+        .bind(owner_id)
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+        Ok(count as u64)
+    }
+
+    async fn export_urls(
+        &self,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<ExportRecord>, DatabaseError> {
+        sqlx::query_as::<_, ExportRecord>(
+            r#"
+            SELECT u.code,
+                   u.url,
+                   u.created_at,
+                   (SELECT COUNT(*) FROM clicks c WHERE c.target_id = u.id) AS click_count
+            FROM urls u
+            WHERE u.status <> 'deleted'
+            ORDER BY u.id
+            LIMIT ? OFFSET ?
+            "#,
+        )
+        .bind(limit as i64)
+        .bind(offset as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(e.to_string()))
+    }
+
+    async fn add_tag(&self, code: &str, tag: &str) -> Result<(), DatabaseError> {
+        let primary = self.primary_code(code).await?;
+        sqlx::query("INSERT OR IGNORE INTO tags (code, tag) VALUES (?, ?)")
+            .bind(primary)
+            .bind(tag)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn remove_tag(&self, code: &str, tag: &str) -> Result<(), DatabaseError> {
+        let primary = self.primary_code(code).await?;
+        sqlx::query("DELETE FROM tags WHERE code = ? AND tag = ?")
+            .bind(primary)
+            .bind(tag)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn get_urls_by_tag(
+        &self,
+        tag: &str,
+        offset: u64,
+        limit: u64,
+        owner_id: Option<Uuid>,
+    ) -> Result<Vec<UrlRecord>, DatabaseError> {
+        sqlx::query_as::<_, UrlRecord>(
+            r#"
+            SELECT u.code, u.url, u.owner_id
+            FROM tags t
+            JOIN urls u ON u.code = t.code
+            WHERE t.tag = ?
+              AND u.status <> 'deleted'
+              AND (? IS NULL OR u.owner_id = ?)
+            ORDER BY t.id
+            LIMIT ? OFFSET ?
+            "#,
+        )
+        .bind(tag)
+        .bind(owner_id)
+        .bind(owner_id)
+        .bind(limit as i64)
+        .bind(offset as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(e.to_string()))
+    }
+
+    async fn search_urls(
+        &self,
+        query: &str,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<UrlRecord>, DatabaseError> {
+        let pattern = like_pattern(query);
+        sqlx::query_as::<_, UrlRecord>(
+            r#"
+            SELECT u.code, u.url, u.owner_id
+            FROM urls u
+            WHERE u.status <> 'deleted'
+              AND (u.url LIKE ? ESCAPE '\'
+                   OR u.code LIKE ? ESCAPE '\'
+                   OR EXISTS (
+                        SELECT 1 FROM aliases a
+                        WHERE a.target_id = u.id AND a.alias LIKE ? ESCAPE '\'
+                   ))
+            ORDER BY u.id
+            LIMIT ? OFFSET ?
+            "#,
+        )
+        .bind(&pattern)
+        .bind(&pattern)
+        .bind(&pattern)
+        .bind(limit as i64)
+        .bind(offset as i64)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(e.to_string()))
+    }
+
     async fn insert_alias(&self, alias_code: &str, code_id: i64) -> Result<(), DatabaseError> {
         sqlx::query("INSERT INTO aliases (alias, target_id) VALUES (?, ?)")
             .bind(alias_code)
@@ -403,6 +1297,62 @@ impl UrlDatabase for SqliteUrlDatabase {
 
         Ok(())
     }
+
+    async fn health_check(&self) -> Result<(), DatabaseError> {
+        sqlx::query("SELECT 1")
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| DatabaseError::ConnectionError(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn get_stats(&self) -> Result<DatabaseStats, DatabaseError> {
+        let (total_urls, total_clicks) = sqlx::query_as::<_, (i64, i64)>(
+            "SELECT (SELECT COUNT(*) FROM urls WHERE status <> 'deleted'), (SELECT COUNT(*) FROM clicks)",
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+        let size_bytes: i64 = sqlx::query_scalar(
+            "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+
+        Ok(DatabaseStats {
+            total_urls: total_urls as u64,
+            total_clicks: total_clicks as u64,
+            size_bytes: size_bytes as u64,
+        })
+    }
+
+    async fn pending_migrations(&self) -> Result<usize, DatabaseError> {
+        let embedded = sqlx::migrate!("./migrations")
+            .iter()
+            .filter(|m| !m.migration_type.is_down_migration())
+            .count();
+        let applied: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM _sqlx_migrations WHERE success")
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| DatabaseError::QueryError(e.to_string()))?;
+        Ok(embedded.saturating_sub(applied as usize))
+    }
+
+    async fn run_maintenance(&self) -> Vec<MaintenanceStep> {
+        let mut steps = Vec::new();
+        for operation in ["VACUUM", "ANALYZE"] {
+            let started = std::time::Instant::now();
+            let result = sqlx::query(operation).execute(&self.pool).await;
+            steps.push(MaintenanceStep {
+                operation,
+                duration: started.elapsed(),
+                error: result.err().map(|e| e.to_string()),
+            });
+        }
+        steps
+    }
 }
 
 /// Creates a SQLite connection pool from configuration settings.
@@ -410,6 +1360,11 @@ impl UrlDatabase for SqliteUrlDatabase {
 /// This function sets up the SQLite connection with the appropriate options,
 /// including creating the database file if specified in the configuration.
 ///
+/// Every connection runs in WAL journal mode with `synchronous=NORMAL`. WAL lets
+/// one writer and many readers work concurrently, so reads no longer stall while
+/// a write is in flight. Writers still serialize; `busy_timeout_ms` controls how
+/// long a second writer waits for the lock before giving up with `SQLITE_BUSY`.
+///
 /// # Arguments
 ///
 /// * `config` - Database configuration settings
@@ -433,24 +1388,39 @@ impl UrlDatabase for SqliteUrlDatabase {
 ///     create_if_missing: true,
 ///     max_connections: Some(16),
 ///     min_connections: Some(4),
+///     busy_timeout_ms: Some(5000),
 /// };
 /// let pool = get_connection_pool(&config).await?;
 /// # Ok(())
 /// # }
 /// ```
 pub async fn get_connection_pool(config: &DatabaseSettings) -> Result<SqlitePool, sqlx::Error> {
+    let busy_timeout = config.busy_timeout_ms.unwrap_or(DEFAULT_BUSY_TIMEOUT_MS);
     let options = SqliteConnectOptions::from_str(&config.connection_string())?
         .create_if_missing(config.create_if_missing)
-        .foreign_keys(true);
+        .foreign_keys(true)
+        .journal_mode(SqliteJournalMode::Wal)
+        .synchronous(SqliteSynchronous::Normal)
+        .busy_timeout(Duration::from_millis(busy_timeout as u64));
 
     let cores = num_cpus::get().max(MIN_CAP as usize);
     let default_max = cores.saturating_mul(2).max(4) as u32; // minimum 4
     let mut max_conn = config.max_connections.unwrap_or(default_max);
+    let mut min_conn = config.min_connections.unwrap_or(0);
 
     max_conn = max_conn.clamp(MIN_CAP, MAX_CAP);
+    if min_conn > max_conn {
+        tracing::warn!(
+            requested_min = %min_conn,
+            requested_max = %max_conn,
+            "min_connections > max_connections, adjusting min_connections to max_connections"
+        );
+        min_conn = max_conn;
+    }
 
     SqlitePoolOptions::new()
         .max_connections(max_conn)
+        .min_connections(min_conn)
         .connect_with(options)
         .await
 }
@@ -460,3 +1430,194 @@ fn sha256_bytes(s: &str) -> [u8; 32] {
     hasher.update(s.as_bytes());
     hasher.finalize().into()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::configuration::DatabaseType;
+    use sqlx::Acquire;
+
+    #[tokio::test]
+    async fn pending_migrations_counts_unapplied_versions() {
+        let db = SqliteUrlDatabase::in_memory_with_migrations()
+            .await
+            .unwrap();
+        assert_eq!(db.pending_migrations().await.unwrap(), 0);
+
+        sqlx::query(
+            "DELETE FROM _sqlx_migrations WHERE version = (SELECT MAX(version) FROM _sqlx_migrations)",
+        )
+        .execute(&db.pool)
+        .await
+        .unwrap();
+        assert_eq!(db.pending_migrations().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn compressed_bloom_snapshot_survives_store_and_load() {
+        use crate::shortcode::bloom_filter::{LocalBloom, ProbSet, decode_snapshot};
+
+        let db = SqliteUrlDatabase::in_memory_with_migrations()
+            .await
+            .unwrap();
+
+        let bloom = LocalBloom::from_items(["stored1", "stored2"], 10_000, 0.01);
+        let blob = bloom.snapshot().unwrap();
+        db.save_bloom_snapshot("test", &blob).await.unwrap();
+
+        let loaded = db.load_bloom_snapshot("test").await.unwrap().unwrap();
+        assert_eq!(loaded, blob);
+        let restored = LocalBloom::from_snapshot(&loaded).unwrap();
+        assert_eq!(
+            decode_snapshot(&restored.snapshot().unwrap()).unwrap(),
+            decode_snapshot(&blob).unwrap()
+        );
+        assert!(restored.may_contain("stored2"));
+    }
+
+    #[tokio::test]
+    async fn url_status_follows_password_expiry_and_click_limit() {
+        let db = SqliteUrlDatabase::in_memory_with_migrations()
+            .await
+            .unwrap();
+        db.insert_url("stlock", "https://example.com/locked")
+            .await
+            .unwrap();
+        db.insert_url("stlimit", "https://example.com/limited")
+            .await
+            .unwrap();
+        db.insert_url_with_expiry(
+            "stexpire",
+            "https://example.com/expired",
+            Some(Utc::now() - chrono::Duration::minutes(1)),
+        )
+        .await
+        .unwrap();
+
+        let db = &db;
+        let status = move |code: &'static str| async move {
+            db.get_url_with_metadata(code).await.unwrap().status
+        };
+        assert_eq!(status("stlock").await, UrlStatus::Active);
+
+        db.set_url_password("stlock", b"hash").await.unwrap();
+        assert_eq!(status("stlock").await, UrlStatus::PasswordProtected);
+        assert_eq!(
+            db.get_url("stlock").await.unwrap(),
+            "https://example.com/locked"
+        );
+
+        db.deactivate_url("stlimit").await.unwrap();
+        assert_eq!(status("stlimit").await, UrlStatus::MaxClicksReached);
+        assert!(matches!(
+            db.get_url("stlimit").await,
+            Err(DatabaseError::Gone(UrlStatus::MaxClicksReached))
+        ));
+
+        assert_eq!(status("stexpire").await, UrlStatus::Expired);
+        assert!(matches!(
+            db.get_url("stexpire").await,
+            Err(DatabaseError::Gone(UrlStatus::Expired))
+        ));
+        let stored: String = sqlx::query_scalar("SELECT status FROM urls WHERE code = 'stexpire'")
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+        assert_eq!(stored, "expired");
+    }
+
+    #[tokio::test]
+    async fn deleted_urls_stop_resolving_but_keep_their_codes() {
+        let db = SqliteUrlDatabase::in_memory_with_migrations()
+            .await
+            .unwrap();
+        let (upsert, _) = db
+            .insert_url("stdel", "https://example.com/deleted")
+            .await
+            .unwrap();
+        db.insert_alias("stdelalias", upsert.id).await.unwrap();
+        db.record_click("stdel", Utc::now(), None, None, None, None, None, None)
+            .await
+            .unwrap();
+
+        db.delete_url("stdel").await.unwrap();
+        assert!(matches!(
+            db.get_url("stdel").await,
+            Err(DatabaseError::NotFound)
+        ));
+        assert!(matches!(
+            db.get_url("stdelalias").await,
+            Err(DatabaseError::NotFound)
+        ));
+        assert!(matches!(
+            db.delete_url("stdel").await,
+            Err(DatabaseError::NotFound)
+        ));
+        assert!(db.list_urls(0, 10, None).await.unwrap().is_empty());
+        assert_eq!(db.get_stats().await.unwrap().total_urls, 0);
+        assert_eq!(db.get_stats().await.unwrap().total_clicks, 1);
+
+        // The destination gets a new code; the deleted ones stay taken
+        let (again, urls) = db
+            .insert_url("stnew", "https://example.com/deleted")
+            .await
+            .unwrap();
+        assert!(again.created);
+        assert_eq!(urls.code, "stnew");
+        assert!(matches!(
+            db.insert_url("stdel", "https://example.com/other").await,
+            Err(DatabaseError::Duplicate)
+        ));
+        assert!(db.insert_alias("stdelalias", again.id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn wal_mode_lets_readers_run_while_a_write_is_open() {
+        let path = std::env::temp_dir().join(format!("wal-test-{}.db", Uuid::new_v4()));
+        let config = DatabaseSettings {
+            r#type: DatabaseType::Sqlite,
+            url: path.to_string_lossy().into_owned(),
+            create_if_missing: true,
+            max_connections: Some(3),
+            min_connections: Some(3),
+            busy_timeout_ms: Some(100),
+        };
+        let db = SqliteUrlDatabase::from_config(&config).await.unwrap();
+        db.migrate().await.unwrap();
+        db.insert_url("walread", "https://example.com/wal")
+            .await
+            .unwrap();
+
+        let mode: String = sqlx::query_scalar("PRAGMA journal_mode")
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+        assert_eq!(mode, "wal");
+
+        // Hold an uncommitted write on one connection...
+        let mut writer = db.pool.acquire().await.unwrap();
+        let mut tx = writer.begin().await.unwrap();
+        sqlx::query("INSERT INTO urls (code, url, url_hash) VALUES ('walwrite', 'x', x'00')")
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+
+        // ...while two other connections read at the same time.
+        let mut first = db.pool.acquire().await.unwrap();
+        let mut second = db.pool.acquire().await.unwrap();
+        let query = "SELECT url FROM urls WHERE code = 'walread'";
+        let (a, b) = tokio::join!(
+            sqlx::query_scalar::<_, String>(query).fetch_one(&mut *first),
+            sqlx::query_scalar::<_, String>(query).fetch_one(&mut *second),
+        );
+        assert_eq!(a.unwrap(), "https://example.com/wal");
+        assert_eq!(b.unwrap(), "https://example.com/wal");
+
+        tx.rollback().await.unwrap();
+        drop((writer, first, second));
+        db.pool.close().await;
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+    }
+}
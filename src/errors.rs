@@ -10,6 +10,11 @@
 //! request processing. Each variant maps to a specific HTTP status code and provides
 //! detailed error information.
 //!
+//! `ApiError` is `#[non_exhaustive]`, like [`DatabaseError`](crate::database::DatabaseError)
+//! and [`GeneratorError`](crate::generator::GeneratorError): variants may be added
+//! without a breaking release, so code outside this crate that matches on it
+//! must handle unknown variants with a wildcard arm.
+//!
 //! ## Error Response Format
 //!
 //! All errors are automatically converted to standardized JSON responses using the
@@ -21,10 +26,14 @@
 //!   "message": "Error description",
 //!   "status": 400,
 //!   "time": "2025-01-18T12:00:00Z",
-//!   "data": null
+//!   "error_code": "BAD_REQUEST"
 //! }
 //! ```
 //!
+//! Clients sending `Accept: application/problem+json` instead receive an
+//! RFC 7807 [`ProblemDetail`] body, see
+//! [`negotiate_problem_details`](crate::middleware::negotiate_problem_details).
+//!
 //! ## Usage
 //!
 //! ```rust,no_run
@@ -40,9 +49,14 @@
 //! let response = handler().into_response();
 //! ```
 
-use crate::response::ApiResponse;
+use crate::response::{ApiResponse, ProblemDetail};
+use crate::telemetry::error_reporting::report_internal_error;
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
+use std::borrow::Cow;
+use utoipa::openapi::RefOr;
+use utoipa::openapi::schema::{KnownFormat, ObjectBuilder, Schema, SchemaFormat, Type};
+use utoipa::{PartialSchema, ToSchema};
 
 /// API error variants with corresponding HTTP status codes.
 ///
@@ -54,11 +68,18 @@ use axum::response::{IntoResponse, Response};
 ///
 /// - `BadRequest` - Client sent invalid request data (400)
 /// - `NotFound` - Requested resource was not found (404)
+/// - `Gone` - Resource existed but was deactivated for good (410)
 /// - `Unauthorized` - Authentication required or failed (401)
 /// - `Forbidden` - Access denied (403)
 /// - `Conflict` - Resource conflict (409)
 /// - `Internal` - Server internal error (500)
 /// - `Unprocessable` - Request data is valid but cannot be processed (422)
+/// - `PayloadTooLarge` - Request body exceeds the configured limit (413)
+/// - `Timeout` - Request did not complete within the configured timeout (504)
+/// - `UrlTooLong` - URL exceeds the maximum length (422)
+/// - `InvalidScheme` - URL uses a scheme other than http/https (422)
+/// - `AliasTaken` - Requested alias is already in use (409)
+/// - `RateLimited` - Client exceeded the rate limit (429)
 /// - `Tera` - Template rendering error (500)
 ///
 /// # Examples
@@ -72,6 +93,7 @@ use axum::response::{IntoResponse, Response};
 /// let internal = ApiError::Internal("Database connection failed".to_string());
 /// ```
 #[derive(thiserror::Error)]
+#[non_exhaustive]
 pub enum ApiError {
     #[error("cooldown not finished")]
     Cooldown,
@@ -90,6 +112,10 @@ pub enum ApiError {
     #[error("Not found: {0}")]
     NotFound(String),
 
+    /// Gone error - the resource existed but was deactivated for good
+    #[error("Gone: {0}")]
+    Gone(String),
+
     /// Unauthorized error - authentication required or failed
     #[error("Unauthorized: {0}")]
     Unauthorized(String),
@@ -110,6 +136,30 @@ pub enum ApiError {
     #[error("Unprocessable entity: {0}")]
     Unprocessable(String),
 
+    /// Payload too large error - request body exceeds the configured limit
+    #[error("Payload too large: {0}")]
+    PayloadTooLarge(String),
+
+    /// Gateway timeout error - request took longer than the configured timeout
+    #[error("Timeout: {0}")]
+    Timeout(String),
+
+    /// URL exceeds the maximum allowed length
+    #[error("URL too long: {0}")]
+    UrlTooLong(String),
+
+    /// URL uses a scheme other than http or https
+    #[error("Invalid scheme: {0}")]
+    InvalidScheme(String),
+
+    /// Custom alias is already in use
+    #[error("alias already taken")]
+    AliasTaken,
+
+    /// Client exceeded the rate limit
+    #[error("Rate limited: {0}")]
+    RateLimited(String),
+
     /// Template rendering error from Tera
     #[error(transparent)]
     Tera(#[from] tera::Error),
@@ -140,7 +190,96 @@ impl IntoResponse for ApiError {
     /// // Response will have 404 status and JSON error body
     /// ```
     fn into_response(self) -> Response {
-        let (status, message) = match self {
+        // Unexpected failures are reported to Sentry, when it is enabled
+        if let ApiError::Internal(message) = &self {
+            report_internal_error(message);
+        }
+        let problem = self.problem();
+        let mut response = problem.envelope().into_response();
+        // Kept so middleware can re-render the body, e.g. as Problem Details
+        response.extensions_mut().insert(problem);
+        response
+    }
+}
+
+impl ApiError {
+    /// Stable slug identifying this error's problem type, appended to the
+    /// configured problem type base to form the RFC 7807 `type` URI.
+    pub fn problem_type(&self) -> &'static str {
+        match self {
+            ApiError::Cooldown => "cooldown",
+            ApiError::AlreadyActive => "challenge-already-active",
+            ApiError::EmailTaken => "email-taken",
+            ApiError::InvalidOrExpired => "challenge-invalid",
+            ApiError::BadRequest(_) => "bad-request",
+            ApiError::NotFound(_) => "not-found",
+            ApiError::Gone(_) => "gone",
+            ApiError::Unauthorized(_) => "unauthorized",
+            ApiError::Forbidden(_) => "forbidden",
+            ApiError::Conflict(_) => "conflict",
+            ApiError::Unprocessable(_) => "unprocessable-entity",
+            ApiError::PayloadTooLarge(_) => "payload-too-large",
+            ApiError::Timeout(_) => "timeout",
+            ApiError::UrlTooLong(_) => "url-too-long",
+            ApiError::InvalidScheme(_) => "invalid-scheme",
+            ApiError::AliasTaken => "alias-taken",
+            ApiError::RateLimited(_) => "rate-limited",
+            ApiError::Internal(_) => "internal-error",
+            ApiError::Tera(_) => "template-error",
+        }
+    }
+
+    /// Stable machine-readable code sent as `error_code` in the error envelope.
+    ///
+    /// Unlike the message, codes never change once published, so clients can
+    /// branch on them.
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            ApiError::Cooldown => "COOLDOWN",
+            ApiError::AlreadyActive => "CHALLENGE_ALREADY_ACTIVE",
+            ApiError::EmailTaken => "EMAIL_TAKEN",
+            ApiError::InvalidOrExpired => "CHALLENGE_INVALID",
+            ApiError::BadRequest(_) => "BAD_REQUEST",
+            ApiError::NotFound(_) => "NOT_FOUND",
+            ApiError::Gone(_) => "GONE",
+            ApiError::Unauthorized(_) => "UNAUTHORIZED",
+            ApiError::Forbidden(_) => "FORBIDDEN",
+            ApiError::Conflict(_) => "CONFLICT",
+            ApiError::Unprocessable(_) => "UNPROCESSABLE",
+            ApiError::PayloadTooLarge(_) => "PAYLOAD_TOO_LARGE",
+            ApiError::Timeout(_) => "TIMEOUT",
+            ApiError::UrlTooLong(_) => "URL_TOO_LONG",
+            ApiError::InvalidScheme(_) => "INVALID_SCHEME",
+            ApiError::AliasTaken => "ALIAS_TAKEN",
+            ApiError::RateLimited(_) => "RATE_LIMITED",
+            ApiError::Internal(_) => "INTERNAL_ERROR",
+            ApiError::Tera(_) => "TEMPLATE_ERROR",
+        }
+    }
+
+    /// The message shown to clients, without the variant prefix of `Display`.
+    pub fn message(&self) -> String {
+        self.problem().detail
+    }
+
+    /// Builds the RFC 7807 representation of this error.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use url_shortener_ztm_lib::errors::ApiError;
+    ///
+    /// let problem = ApiError::NotFound("URL not found".to_string())
+    ///     .into_problem_detail("https://errors.example.com/", "/AbC123");
+    /// assert_eq!(problem.r#type, "https://errors.example.com/not-found");
+    /// assert_eq!(problem.status, 404);
+    /// ```
+    pub fn into_problem_detail(&self, type_base: &str, path: &str) -> ProblemDetail {
+        self.problem().into_problem_detail(type_base, path)
+    }
+
+    fn problem(&self) -> ErrorProblem {
+        let (status, detail) = match self {
             ApiError::Cooldown => (
                 StatusCode::TOO_MANY_REQUESTS,
                 "Cooldown not finished".into(),
@@ -154,20 +293,69 @@ impl IntoResponse for ApiError {
                 StatusCode::BAD_REQUEST,
                 "Challenge expired or invalid".into(),
             ),
-            ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
-            ApiError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
-            ApiError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg),
-            ApiError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
-            ApiError::Conflict(msg) => (StatusCode::CONFLICT, msg),
-            ApiError::Unprocessable(msg) => (StatusCode::UNPROCESSABLE_ENTITY, msg),
-            ApiError::Internal(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+            ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg.clone()),
+            ApiError::NotFound(msg) => (StatusCode::NOT_FOUND, msg.clone()),
+            ApiError::Gone(msg) => (StatusCode::GONE, msg.clone()),
+            ApiError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg.clone()),
+            ApiError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg.clone()),
+            ApiError::Conflict(msg) => (StatusCode::CONFLICT, msg.clone()),
+            ApiError::Unprocessable(msg) => (StatusCode::UNPROCESSABLE_ENTITY, msg.clone()),
+            ApiError::PayloadTooLarge(msg) => (StatusCode::PAYLOAD_TOO_LARGE, msg.clone()),
+            ApiError::Timeout(msg) => (StatusCode::GATEWAY_TIMEOUT, msg.clone()),
+            ApiError::UrlTooLong(msg) | ApiError::InvalidScheme(msg) => {
+                (StatusCode::UNPROCESSABLE_ENTITY, msg.clone())
+            }
+            ApiError::AliasTaken => (StatusCode::CONFLICT, "Alias is already taken".into()),
+            ApiError::RateLimited(msg) => (StatusCode::TOO_MANY_REQUESTS, msg.clone()),
+            ApiError::Internal(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg.clone()),
             ApiError::Tera(msg) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 format!("Tera template rendering error: {msg}"),
             ),
         };
 
-        ApiResponse::<()>::error(&message, status).into_response()
+        ErrorProblem {
+            problem_type: self.problem_type(),
+            error_code: self.error_code(),
+            status,
+            detail,
+        }
+    }
+}
+
+/// Status, codes and message of an [`ApiError`] response, stored in the
+/// response extensions so middleware can re-render it, e.g. as a [`ProblemDetail`].
+#[derive(Debug, Clone)]
+pub struct ErrorProblem {
+    /// Slug from [`ApiError::problem_type`]
+    pub problem_type: &'static str,
+    /// Code from [`ApiError::error_code`]
+    pub error_code: &'static str,
+    /// HTTP status code of the response
+    pub status: StatusCode,
+    /// Error message shown to the client
+    pub detail: String,
+}
+
+impl ErrorProblem {
+    /// Builds the [`ApiResponse`] error envelope for this error.
+    pub fn envelope(&self) -> ApiResponse<()> {
+        ApiResponse::error(&self.detail, self.status, Some(self.error_code))
+    }
+
+    /// Builds a [`ProblemDetail`] whose `type` is `type_base` followed by the slug.
+    pub fn into_problem_detail(self, type_base: &str, path: &str) -> ProblemDetail {
+        ProblemDetail {
+            r#type: format!("{}{}", type_base, self.problem_type),
+            title: self
+                .status
+                .canonical_reason()
+                .unwrap_or("Error")
+                .to_string(),
+            status: self.status.as_u16(),
+            detail: self.detail,
+            instance: Some(path.to_string()),
+        }
     }
 }
 
@@ -182,6 +370,62 @@ impl std::fmt::Debug for ApiError {
     }
 }
 
+/// Documents the JSON body every `ApiError` is sent as, named
+/// `ErrorResponse` in the OpenAPI spec.
+///
+/// The variants are not part of the wire format; clients tell errors apart
+/// by `status` and `error_code`.
+impl PartialSchema for ApiError {
+    fn schema() -> RefOr<Schema> {
+        let string = || ObjectBuilder::new().schema_type(Type::String);
+        ObjectBuilder::new()
+            .description(Some(
+                "Error envelope sent with every 4xx and 5xx JSON response",
+            ))
+            .property(
+                "success",
+                ObjectBuilder::new()
+                    .schema_type(Type::Boolean)
+                    .description(Some("Always `false`")),
+            )
+            .required("success")
+            .property("message", string().description(Some("What went wrong")))
+            .required("message")
+            .property(
+                "status",
+                ObjectBuilder::new()
+                    .schema_type(Type::Integer)
+                    .description(Some("HTTP status code")),
+            )
+            .required("status")
+            .property(
+                "time",
+                string()
+                    .format(Some(SchemaFormat::KnownFormat(KnownFormat::DateTime)))
+                    .description(Some("When the response was generated")),
+            )
+            .required("time")
+            .property(
+                "error_code",
+                string().description(Some(
+                    "Stable machine-readable code, e.g. `NOT_FOUND` or `ALIAS_TAKEN`",
+                )),
+            )
+            .required("error_code")
+            .property(
+                "request_id",
+                string().description(Some("`x-request-id` of the request")),
+            )
+            .into()
+    }
+}
+
+impl ToSchema for ApiError {
+    fn name() -> Cow<'static, str> {
+        Cow::Borrowed("ErrorResponse")
+    }
+}
+
 /// Formats an error with its complete error chain for debugging.
 ///
 /// This utility function displays an error and all its underlying causes in a
@@ -212,3 +456,85 @@ pub fn error_chain_fmt(
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn every_variant() -> Vec<ApiError> {
+        let msg = || "message".to_string();
+        vec![
+            ApiError::Cooldown,
+            ApiError::AlreadyActive,
+            ApiError::EmailTaken,
+            ApiError::InvalidOrExpired,
+            ApiError::BadRequest(msg()),
+            ApiError::NotFound(msg()),
+            ApiError::Gone(msg()),
+            ApiError::Unauthorized(msg()),
+            ApiError::Forbidden(msg()),
+            ApiError::Conflict(msg()),
+            ApiError::Internal(msg()),
+            ApiError::Unprocessable(msg()),
+            ApiError::PayloadTooLarge(msg()),
+            ApiError::Timeout(msg()),
+            ApiError::UrlTooLong(msg()),
+            ApiError::InvalidScheme(msg()),
+            ApiError::AliasTaken,
+            ApiError::RateLimited(msg()),
+            ApiError::Tera(tera::Error::msg("message")),
+        ]
+    }
+
+    #[test]
+    fn every_variant_has_a_unique_error_code() {
+        let variants = every_variant();
+        let codes: HashSet<&str> = variants.iter().map(ApiError::error_code).collect();
+        assert_eq!(codes.len(), variants.len());
+    }
+
+    #[test]
+    fn error_codes_are_screaming_snake_case() {
+        for error in every_variant() {
+            let code = error.error_code();
+            assert!(
+                code.chars().all(|c| c.is_ascii_uppercase() || c == '_'),
+                "{code} is not SCREAMING_SNAKE_CASE"
+            );
+        }
+    }
+
+    #[test]
+    fn only_internal_errors_are_reported_to_sentry() {
+        let events = sentry::test::with_captured_events(|| {
+            for error in every_variant() {
+                let _ = error.into_response();
+            }
+        });
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].message.as_deref(), Some("message"));
+        assert_eq!(events[0].level, sentry::Level::Error);
+    }
+
+    #[test]
+    fn sentry_events_carry_the_request_id() {
+        let events = sentry::test::with_captured_events(|| {
+            crate::telemetry::error_reporting::record_request_id("req-42");
+            let _ = ApiError::Internal("database is gone".to_string()).into_response();
+        });
+
+        assert_eq!(
+            events[0].tags.get("request_id").map(String::as_str),
+            Some("req-42")
+        );
+        assert!(
+            events[0]
+                .breadcrumbs
+                .values
+                .iter()
+                .any(|b| b.message.as_deref() == Some("request_id req-42"))
+        );
+    }
+}
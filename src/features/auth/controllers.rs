@@ -1,13 +1,17 @@
 use super::{dto::*, services::AuthService};
 use crate::{
-    ApiError, ApiResponse, AppState, ClientMeta,
-    core::extractors::auth_user::AuthenticatedUser,
-    features::{auth::repositories::AuthenticationAction, users::UserService},
+    ApiError, ApiResponse, AppState, ClientMeta, PaginatedResponse,
+    core::{extractors::auth_user::AuthenticatedUser, security::jwt::gen_refresh_token},
+    features::{
+        auth::repositories::{AuthAuditEntry, AuthenticationAction, DeviceSummary},
+        users::UserService,
+    },
+    routes::{DEFAULT_QR_SIZE, render_qr_png},
 };
 use axum::{
     Extension, Json,
-    extract::{FromRef, State},
-    response::IntoResponse,
+    extract::{FromRef, Path, Query, State},
+    response::{IntoResponse, Redirect, Response},
 };
 use axum_extra::{
     TypedHeader,
@@ -17,6 +21,7 @@ use axum_extra::{
     },
     headers::{Authorization, Cookie as AxCookie, authorization::Bearer},
 };
+use base64::Engine;
 
 use std::sync::Arc;
 
@@ -29,8 +34,8 @@ pub struct AuthController {
 impl FromRef<AppState> for AuthController {
     fn from_ref(app: &AppState) -> Self {
         Self {
-            auth_svc: app.auth_service.clone(),
-            user_svc: app.user_service.clone(),
+            auth_svc: app.auth_service().clone(),
+            user_svc: app.user_service().clone(),
         }
     }
 }
@@ -59,12 +64,39 @@ pub async fn sign_in(
     Extension(meta): Extension<ClientMeta>,
     jar: CookieJar,
     Json(req): Json<SignInReq>,
+) -> Result<Response, ApiError> {
+    let outcome = ctrl
+        .auth_svc
+        .sign_in(req, meta)
+        .await
+        .map_err(sign_in_error)?;
+
+    match outcome {
+        SignInOutcome::Authenticated(bundle) => {
+            let at = make_access_cookie(bundle.access_token, 30);
+            let rt = make_refresh_cookie(bundle.refresh_token, 30);
+            let jar = jar.add(at).add(rt);
+
+            Ok((jar, Json(ApiResponse::success(()))).into_response())
+        }
+        SignInOutcome::MfaRequired(challenge) => {
+            Ok(Json(ApiResponse::success(challenge)).into_response())
+        }
+    }
+}
+
+/// Completes a sign-in that asked for a TOTP code.
+pub async fn sign_in_mfa(
+    State(ctrl): State<AuthController>,
+    Extension(meta): Extension<ClientMeta>,
+    jar: CookieJar,
+    Json(req): Json<MfaSignInReq>,
 ) -> Result<impl IntoResponse, ApiError> {
     let bundle = ctrl
         .auth_svc
-        .sign_in(req, meta)
+        .sign_in_mfa(req, meta)
         .await
-        .map_err(|e| ApiError::Unauthorized(e.to_string()))?;
+        .map_err(sign_in_error)?;
 
     let at = make_access_cookie(bundle.access_token, 30);
     let rt = make_refresh_cookie(bundle.refresh_token, 30);
@@ -73,6 +105,72 @@ pub async fn sign_in(
     Ok((jar, Json(ApiResponse::success(()))))
 }
 
+// A locked account comes back as Forbidden; anything else is a failed sign-in
+fn sign_in_error(e: anyhow::Error) -> ApiError {
+    e.downcast::<ApiError>()
+        .unwrap_or_else(|e| ApiError::Unauthorized(e.to_string()))
+}
+
+/// `GET /api/v1/auth/config`: the verification code limits, for clients to
+/// display; needs no sign-in.
+pub async fn auth_config(State(ctrl): State<AuthController>) -> ApiResponse<AuthConfigResp> {
+    let cfg = ctrl.auth_svc.verification_settings();
+    ApiResponse::success(AuthConfigResp {
+        cooldown_seconds: cfg.verification_cooldown_seconds,
+        code_ttl_seconds: u64::from(cfg.verification_ttl_hours) * 3600,
+        max_attempts: cfg.max_verification_attempts,
+    })
+}
+
+pub async fn totp_enroll(
+    State(ctrl): State<AuthController>,
+    user: AuthenticatedUser,
+) -> Result<ApiResponse<TotpEnrollResp>, ApiError> {
+    let enrollment = ctrl
+        .auth_svc
+        .totp_enroll(user.user_id)
+        .await
+        .map_err(|e| ApiError::Unprocessable(e.to_string()))?;
+
+    let png = render_qr_png(&enrollment.otpauth_url, DEFAULT_QR_SIZE)?;
+    let qr_code_url = format!(
+        "data:image/png;base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(png)
+    );
+
+    Ok(ApiResponse::success(TotpEnrollResp {
+        secret: enrollment.secret,
+        otpauth_url: enrollment.otpauth_url,
+        qr_code_url,
+    }))
+}
+
+pub async fn totp_confirm(
+    State(ctrl): State<AuthController>,
+    Extension(meta): Extension<ClientMeta>,
+    user: AuthenticatedUser,
+    Json(req): Json<TotpConfirmReq>,
+) -> Result<ApiResponse<()>, ApiError> {
+    ctrl.auth_svc
+        .totp_confirm(user.user_id, &req.code, meta)
+        .await
+        .map_err(|e| ApiError::Unprocessable(e.to_string()))?;
+    Ok(ApiResponse::success(()))
+}
+
+pub async fn totp_disable(
+    State(ctrl): State<AuthController>,
+    Extension(meta): Extension<ClientMeta>,
+    user: AuthenticatedUser,
+    Json(req): Json<TotpDisableReq>,
+) -> Result<ApiResponse<()>, ApiError> {
+    ctrl.auth_svc
+        .totp_disable(user.user_id, &req.password, meta)
+        .await
+        .map_err(|e| ApiError::Unprocessable(e.to_string()))?;
+    Ok(ApiResponse::success(()))
+}
+
 pub async fn refresh(
     State(ctrl): State<AuthController>,
     TypedHeader(cookies): TypedHeader<AxCookie>,
@@ -104,11 +202,12 @@ pub async fn refresh(
 
 pub async fn sign_out(
     State(ctrl): State<AuthController>,
+    Extension(meta): Extension<ClientMeta>,
     user: AuthenticatedUser,
     Json(req): Json<RefreshReq>,
 ) -> Result<ApiResponse<()>, ApiError> {
     ctrl.auth_svc
-        .sign_out(user.user_id, &req.device_id)
+        .sign_out(user.user_id, &req.device_id, meta)
         .await
         .map_err(|e| ApiError::Internal(e.to_string()))?;
     Ok(ApiResponse::success(()))
@@ -116,22 +215,61 @@ pub async fn sign_out(
 
 pub async fn sign_out_all(
     State(ctrl): State<AuthController>,
+    Extension(meta): Extension<ClientMeta>,
     user: AuthenticatedUser,
 ) -> Result<ApiResponse<()>, ApiError> {
     ctrl.auth_svc
-        .sign_out_all(user.user_id)
+        .sign_out_all(user.user_id, meta)
         .await
         .map_err(|e| ApiError::Internal(e.to_string()))?;
     Ok(ApiResponse::success(()))
 }
 
+/// `GET /api/v1/auth/devices?device_id=...`: the devices the user is signed
+/// in on; the one matching `device_id` is marked as the current device.
+pub async fn list_devices(
+    State(ctrl): State<AuthController>,
+    user: AuthenticatedUser,
+    Query(params): Query<DevicesQuery>,
+) -> Result<ApiResponse<Vec<DeviceSummary>>, ApiError> {
+    let mut devices = ctrl
+        .auth_svc
+        .devices(user.user_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    for dev in &mut devices {
+        dev.is_current = params.device_id.as_deref() == Some(dev.device_id.as_str());
+    }
+
+    Ok(ApiResponse::success(devices))
+}
+
+/// `DELETE /api/v1/auth/devices/{device_id}`: signs that device out.
+pub async fn revoke_device(
+    State(ctrl): State<AuthController>,
+    Extension(meta): Extension<ClientMeta>,
+    user: AuthenticatedUser,
+    Path(device_id): Path<String>,
+) -> Result<ApiResponse<()>, ApiError> {
+    ctrl.auth_svc
+        .revoke_device(user.user_id, &device_id, meta)
+        .await
+        .map_err(|e| {
+            e.downcast::<ApiError>()
+                .unwrap_or_else(|e| ApiError::Internal(e.to_string()))
+        })?;
+    Ok(ApiResponse::success(()))
+}
+
 pub async fn change_password(
     State(ctrl): State<AuthController>,
+    Extension(meta): Extension<ClientMeta>,
     user: AuthenticatedUser,
     Json(req): Json<ChangePasswordReq>,
 ) -> Result<ApiResponse<()>, ApiError> {
     ctrl.auth_svc
-        .change_password(user.user_id, &req.old_password, &req.new_password)
+        .change_password(user.user_id, &req.old_password, &req.new_password, meta)
         .await
         .map_err(|e| ApiError::Unprocessable(e.to_string()))?;
     Ok(ApiResponse::success(()))
@@ -195,11 +333,12 @@ pub async fn change_email_request(
 
 pub async fn change_email_confirm(
     State(ctrl): State<AuthController>,
+    Extension(meta): Extension<ClientMeta>,
     user: AuthenticatedUser,
     Json(req): Json<ChangeEmailConfirmReq>,
 ) -> Result<ApiResponse<()>, ApiError> {
     ctrl.auth_svc
-        .confirm_email_change(user.user_id, &req.code)
+        .confirm_email_change(user.user_id, &req.code, meta)
         .await
         .map_err(|e| ApiError::Unprocessable(e.to_string()))?;
 
@@ -232,6 +371,7 @@ pub async fn pw_reset_request(
 
 pub async fn pw_reset_confirm(
     State(ctrl): State<AuthController>,
+    Extension(meta): Extension<ClientMeta>,
     Json(req): Json<PwResetConfirmReq>,
 ) -> Result<ApiResponse<()>, ApiError> {
     let usr = ctrl
@@ -246,13 +386,99 @@ pub async fn pw_reset_confirm(
         .map_err(|e| ApiError::Unprocessable(e.to_string()))?;
 
     ctrl.auth_svc
-        .reset_password(usr.id, &req.new_password)
+        .reset_password(usr.id, &req.new_password, meta)
         .await
         .map_err(|e| ApiError::Internal(e.to_string()))?;
 
     Ok(ApiResponse::success(()))
 }
 
+/// `GET /api/v1/user/security-events?page=1&per_page=50`: the signed-in
+/// user's authentication audit log, newest first.
+pub async fn security_events(
+    State(ctrl): State<AuthController>,
+    user: AuthenticatedUser,
+    Query(params): Query<SecurityEventsQuery>,
+) -> Result<ApiResponse<PaginatedResponse<AuthAuditEntry>>, ApiError> {
+    let page = params.page.unwrap_or(1).max(1);
+    let per_page = params
+        .per_page
+        .unwrap_or(DEFAULT_SECURITY_EVENTS_PER_PAGE)
+        .clamp(1, MAX_SECURITY_EVENTS_PER_PAGE);
+    let offset = (page - 1).saturating_mul(per_page);
+
+    // Fetch one extra row to learn whether another page follows
+    let events = ctrl
+        .auth_svc
+        .auth_events(user.user_id, offset, per_page + 1)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    Ok(ApiResponse::success(PaginatedResponse::from_overfetch(
+        events, page, per_page,
+    )))
+}
+
+/// Starts a sign-in with `provider` by redirecting to its authorization page.
+///
+/// A random `state` goes along and is kept in a cookie, so the callback only
+/// accepts a sign-in this browser started.
+pub async fn oauth_start(
+    State(ctrl): State<AuthController>,
+    Path(provider): Path<String>,
+    jar: CookieJar,
+) -> Result<impl IntoResponse, ApiError> {
+    let state = gen_refresh_token();
+    let url = ctrl
+        .auth_svc
+        .oauth_authorize_url(&provider, &state)
+        .map_err(|e| ApiError::NotFound(e.to_string()))?;
+
+    let jar = jar.add(make_oauth_state_cookie(state));
+    Ok((jar, Redirect::to(&url)))
+}
+
+/// Completes a sign-in with `provider` once it redirects back with a `code`.
+pub async fn oauth_callback(
+    State(ctrl): State<AuthController>,
+    Path(provider): Path<String>,
+    Query(query): Query<OAuthCallbackQuery>,
+    jar: CookieJar,
+) -> Result<Response, ApiError> {
+    if !ctrl.auth_svc.is_oauth_provider_enabled(&provider) {
+        return Err(ApiError::NotFound("unknown OAuth provider".into()));
+    }
+    let expected_state = jar.get(OAUTH_STATE_COOKIE).map(|c| c.value().to_owned());
+    if expected_state.as_deref() != Some(query.state.as_str()) {
+        return Err(ApiError::Unauthorized("invalid OAuth state".into()));
+    }
+
+    let outcome = ctrl
+        .auth_svc
+        .oauth_exchange(&provider, &query.code)
+        .await
+        .map_err(|e| ApiError::Unauthorized(e.to_string()))?;
+
+    let jar = jar.remove(Cookie::build(OAUTH_STATE_COOKIE).path(OAUTH_COOKIE_PATH));
+    match outcome {
+        SignInOutcome::Authenticated(bundle) => {
+            let at = make_access_cookie(bundle.access_token, 30);
+            let rt = make_refresh_cookie(bundle.refresh_token, 30);
+            let jar = jar.add(at).add(rt);
+
+            Ok((jar, Json(ApiResponse::success(()))).into_response())
+        }
+        SignInOutcome::MfaRequired(challenge) => {
+            Ok((jar, Json(ApiResponse::success(challenge))).into_response())
+        }
+    }
+}
+
+const DEFAULT_SECURITY_EVENTS_PER_PAGE: u64 = 50;
+const MAX_SECURITY_EVENTS_PER_PAGE: u64 = 100;
+const OAUTH_STATE_COOKIE: &str = "oauth_state";
+const OAUTH_COOKIE_PATH: &str = "/api/v1/auth/oauth";
+
 fn is_production() -> bool {
     std::env::var("APP_ENV")
         .map(|v| v == "production")
@@ -269,6 +495,17 @@ fn make_access_cookie(token: String, max_age_minutes: i64) -> Cookie<'static> {
         .build()
 }
 
+fn make_oauth_state_cookie(state: String) -> Cookie<'static> {
+    // Lax, so the cookie comes along on the provider's top-level redirect back
+    Cookie::build((OAUTH_STATE_COOKIE, state))
+        .http_only(true)
+        .secure(is_production())
+        .same_site(SameSite::Lax)
+        .path(OAUTH_COOKIE_PATH)
+        .max_age(time::Duration::minutes(10))
+        .build()
+}
+
 fn make_refresh_cookie(token: String, max_age_days: i64) -> Cookie<'static> {
     Cookie::build(("refresh_token", token))
         .http_only(true)
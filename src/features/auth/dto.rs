@@ -16,6 +16,25 @@ pub struct SignInReq {
     pub device_id: Option<String>,
 }
 
+#[derive(Deserialize)]
+pub struct MfaSignInReq {
+    pub mfa_token: String,
+    pub code: String,
+    pub device_id: Option<String>,
+}
+
+/// Returned by sign-in instead of tokens when the user has TOTP enabled.
+#[derive(Serialize)]
+pub struct MfaChallengeResp {
+    pub mfa_required: bool,
+    pub mfa_token: String,
+}
+
+pub enum SignInOutcome {
+    Authenticated(AuthBundle),
+    MfaRequired(MfaChallengeResp),
+}
+
 #[derive(Serialize)]
 pub struct TokenResp {
     pub access_token: String,
@@ -27,6 +46,18 @@ pub struct AuthBundle {
     pub refresh_token: String,
 }
 
+#[derive(Deserialize)]
+pub struct OAuthCallbackQuery {
+    pub code: String,
+    pub state: String,
+}
+
+#[derive(Deserialize)]
+pub struct SecurityEventsQuery {
+    pub page: Option<u64>,
+    pub per_page: Option<u64>,
+}
+
 #[derive(Deserialize)]
 pub struct VerifyEmailReq {
     pub code: String,
@@ -70,3 +101,42 @@ pub struct ChangeEmailRequestReq {
 pub struct ChangeEmailConfirmReq {
     pub code: String,
 }
+
+/// A new TOTP secret, base32 encoded, and the `otpauth://` URL apps scan.
+pub struct TotpEnrollment {
+    pub secret: String,
+    pub otpauth_url: String,
+}
+
+#[derive(Serialize)]
+pub struct TotpEnrollResp {
+    pub secret: String,
+    pub otpauth_url: String,
+    /// The `otpauth_url` as a QR code, in a `data:image/png` URL
+    pub qr_code_url: String,
+}
+
+#[derive(Deserialize)]
+pub struct TotpConfirmReq {
+    pub code: String,
+}
+
+#[derive(Deserialize)]
+pub struct TotpDisableReq {
+    pub password: SecretString,
+}
+
+#[derive(Deserialize)]
+pub struct DevicesQuery {
+    /// The device making the request, marked `is_current` in the list.
+    pub device_id: Option<String>,
+}
+
+/// Returned by `GET /api/v1/auth/config`, so clients can show how long a code
+/// lasts and when another can be requested.
+#[derive(Serialize)]
+pub struct AuthConfigResp {
+    pub cooldown_seconds: u32,
+    pub code_ttl_seconds: u64,
+    pub max_attempts: u8,
+}
@@ -1,8 +1,10 @@
 pub mod controllers;
 pub mod dto;
+pub mod oauth;
 pub mod repositories;
 pub mod routes;
 pub mod services;
+pub mod totp;
 
 // Re-export
 pub use routes::router;
@@ -7,6 +7,8 @@ use serde_json::Value;
 use sqlx::Type;
 use uuid::Uuid;
 
+use crate::ClientMeta;
+
 #[derive(Debug, Clone)]
 pub struct RefreshDevice {
     pub id: i32,
@@ -21,6 +23,19 @@ pub struct RefreshDevice {
     pub last_rotated_at: Option<DateTime<Utc>>,
 }
 
+/// A signed-in device, as shown to its user.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceSummary {
+    pub id: i32,
+    pub device_id: String,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_used: DateTime<Utc>,
+    /// Whether this is the device making the request.
+    pub is_current: bool,
+}
+
 #[derive(Debug, Clone)]
 pub struct EmailVerification {
     pub id: i32,
@@ -62,6 +77,87 @@ pub struct AuthenticationChallenge {
     pub confirmed_at: Option<DateTime<Utc>>,
 }
 
+/// Kinds of entries in the authentication audit log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthEvent {
+    SignIn,
+    SignOut,
+    PasswordChanged,
+    EmailChanged,
+    MfaEnrolled,
+    MfaDisabled,
+    AccountLocked,
+    TokenRevoked,
+}
+
+impl AuthEvent {
+    /// The `event_type` stored in `auth_audit_log`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::SignIn => "sign_in",
+            Self::SignOut => "sign_out",
+            Self::PasswordChanged => "password_changed",
+            Self::EmailChanged => "email_changed",
+            Self::MfaEnrolled => "mfa_enrolled",
+            Self::MfaDisabled => "mfa_disabled",
+            Self::AccountLocked => "account_locked",
+            Self::TokenRevoked => "token_revoked",
+        }
+    }
+
+    pub fn from_db(value: &str) -> Option<Self> {
+        [
+            Self::SignIn,
+            Self::SignOut,
+            Self::PasswordChanged,
+            Self::EmailChanged,
+            Self::MfaEnrolled,
+            Self::MfaDisabled,
+            Self::AccountLocked,
+            Self::TokenRevoked,
+        ]
+        .into_iter()
+        .find(|event| event.as_str() == value)
+    }
+}
+
+/// Where an audited event came from, and details specific to the event.
+#[derive(Debug, Clone, Default)]
+pub struct AuthEventMeta {
+    pub ip: Option<IpAddr>,
+    pub user_agent: Option<String>,
+    pub metadata: Option<Value>,
+}
+
+impl AuthEventMeta {
+    pub fn with_metadata(mut self, metadata: Value) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+}
+
+impl From<&ClientMeta> for AuthEventMeta {
+    fn from(meta: &ClientMeta) -> Self {
+        Self {
+            ip: meta.ip,
+            user_agent: meta.user_agent.clone(),
+            metadata: None,
+        }
+    }
+}
+
+/// One row of a user's authentication audit log.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuthAuditEntry {
+    pub id: i64,
+    pub event_type: AuthEvent,
+    pub ip: Option<IpAddr>,
+    pub user_agent: Option<String>,
+    pub metadata: Option<Value>,
+    pub created_at: DateTime<Utc>,
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum AuthRepoError {
     #[error("cooldown: {0} seconds remaining")]
@@ -113,6 +209,9 @@ pub trait AuthRepository: Send + Sync {
 
     async fn revoke_device(&self, id: i32) -> anyhow::Result<()>;
     async fn revoke_all(&self, user_id: Uuid) -> anyhow::Result<()>;
+    /// The user's devices holding an unrevoked, unexpired refresh token,
+    /// most recently used first.
+    async fn list_devices_for_user(&self, user_id: Uuid) -> anyhow::Result<Vec<DeviceSummary>>;
 
     async fn is_user_ip_blocked(
         &self,
@@ -166,6 +265,20 @@ pub trait AuthRepository: Send + Sync {
         action: AuthenticationAction,
         confirmed_at: DateTime<Utc>,
     ) -> Result<(), AuthRepoError>;
+
+    async fn log_auth_event(
+        &self,
+        user_id: Uuid,
+        event: AuthEvent,
+        meta: AuthEventMeta,
+    ) -> Result<(), AuthRepoError>;
+    /// A user's audit log, newest first.
+    async fn get_auth_events(
+        &self,
+        user_id: Uuid,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<AuthAuditEntry>, AuthRepoError>;
 }
 
 // A no-operation implementation of AuthRepository for testing purposes.
@@ -223,6 +336,10 @@ impl AuthRepository for NoopAuthRepo {
         Ok(())
     }
 
+    async fn list_devices_for_user(&self, _user_id: Uuid) -> anyhow::Result<Vec<DeviceSummary>> {
+        Ok(Vec::new())
+    }
+
     async fn create_or_refresh_auth_challenge(
         &self,
         _user_id: Uuid,
@@ -287,4 +404,22 @@ impl AuthRepository for NoopAuthRepo {
     ) -> Result<(), AuthRepoError> {
         Ok(())
     }
+
+    async fn log_auth_event(
+        &self,
+        _user_id: Uuid,
+        _event: AuthEvent,
+        _meta: AuthEventMeta,
+    ) -> Result<(), AuthRepoError> {
+        Ok(())
+    }
+
+    async fn get_auth_events(
+        &self,
+        _user_id: Uuid,
+        _offset: u64,
+        _limit: u64,
+    ) -> Result<Vec<AuthAuditEntry>, AuthRepoError> {
+        Ok(Vec::new())
+    }
 }
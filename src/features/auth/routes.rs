@@ -3,15 +3,19 @@ use crate::AppState;
 
 use axum::{
     Router,
-    routing::{get, post},
+    routing::{delete, get, post},
 };
 
 pub fn router() -> Router<AppState> {
     Router::new()
+        .route("/config", get(c::auth_config))
         .route("/sign-up", post(c::sign_up))
         .route("/sign-in", post(c::sign_in))
+        .route("/sign-in/mfa", post(c::sign_in_mfa))
         .route("/sign-out", post(c::sign_out))
         .route("/sign-out-all", post(c::sign_out_all))
+        .route("/devices", get(c::list_devices))
+        .route("/devices/{device_id}", delete(c::revoke_device))
         .route("/refresh", post(c::refresh))
         .route("/change-password", post(c::change_password))
         .route("/verify-email/request", get(c::email_verification_request))
@@ -20,4 +24,9 @@ pub fn router() -> Router<AppState> {
         .route("/password-reset/confirm", post(c::pw_reset_confirm))
         .route("/change-email/request", post(c::change_email_request))
         .route("/change-email/confirm", post(c::change_email_confirm))
+        .route("/totp/enroll", post(c::totp_enroll))
+        .route("/totp/confirm", post(c::totp_confirm))
+        .route("/totp/disable", post(c::totp_disable))
+        .route("/oauth/{provider}", get(c::oauth_start))
+        .route("/oauth/{provider}/callback", get(c::oauth_callback))
 }
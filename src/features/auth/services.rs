@@ -1,18 +1,25 @@
 use crate::{
     ApiError, ClientMeta,
+    configuration::{Argon2Settings, AuthSettings, LockoutSettings, OAuthSettings},
     core::security::{
         jwt::{Claims, JwtKeys, gen_refresh_token, hash_refresh_token},
         password::{
-            NormalizedPassword, generate_verification_code, hash_password, hash_verification_code,
-            validate_policy, verify_password, verify_verification_code,
+            NormalizedPassword, PasswordVerification, generate_verification_code, hash_password,
+            hash_verification_code, validate_policy, verify_password, verify_verification_code,
         },
     },
     features::{
         auth::{
-            dto::{AuthBundle, SignInReq, SignUpReq},
+            dto::{
+                AuthBundle, MfaChallengeResp, MfaSignInReq, SignInOutcome, SignInReq, SignUpReq,
+                TotpEnrollment,
+            },
+            oauth::OAuthProvider,
             repositories::{
-                AuthRepoError, AuthRepository, AuthenticationAction, AuthenticationChallenge,
+                AuthAuditEntry, AuthEvent, AuthEventMeta, AuthRepoError, AuthRepository,
+                AuthenticationAction, AuthenticationChallenge, DeviceSummary,
             },
+            totp::{self, TotpKeys},
         },
         users::repositories::UserRepository,
     },
@@ -24,17 +31,15 @@ use secrecy::{ExposeSecret, SecretString};
 use serde_json::json;
 use std::{net::IpAddr, sync::Arc};
 use uuid::Uuid;
-const MAX_USER_NAME_LENGTH: usize = 30;
+pub(crate) const MAX_USER_NAME_LENGTH: usize = 30;
 const GRACE_SECONDS: i64 = 120;
 const REFRESH_TTL_DAYS: i64 = 30;
-const MAX_ATTEMPTS_ALLOWED: u8 = 5;
 const DEFAULT_DEVICE_ID: &str = "default";
 
 const MAX_SIGNIN_ATTEMPTS_PER_IP: i32 = 5;
-const MAX_SIGNIN_ATTEMPTS_PER_USER: i32 = 12;
 const MAX_IP_SIGNIN_ATTEMPT_WINDOW_MINS: i32 = 5;
-const MAX_USER_SIGNIN_ATTEMPT_WINDOW_MINS: i32 = 30;
-const ACCOUNT_LOCK_HOURS: i64 = 6;
+const ACCOUNT_LOCKED: &str = "Account temporarily locked";
+const OAUTH_HTTP_TIMEOUT_SECS: u64 = 10;
 
 pub struct AuthService {
     users_repo: Arc<dyn UserRepository>,
@@ -42,7 +47,13 @@ pub struct AuthService {
     jwt: JwtKeys,
     access_ttl: Duration,
     pwd_pepper: SecretString,
+    argon2: Argon2Settings,
     email_service: EmailService,
+    oauth: OAuthSettings,
+    http: reqwest::Client,
+    lockout: LockoutSettings,
+    verification: AuthSettings,
+    totp_keys: TotpKeys,
 }
 
 impl AuthService {
@@ -52,18 +63,173 @@ impl AuthService {
         jwt: JwtKeys,
         access_ttl: Duration,
         pwd_pepper: SecretString,
+        argon2: Argon2Settings,
         email_service: EmailService,
     ) -> Self {
+        // the pepper doubles as the server secret the TOTP keys are derived from
+        let totp_keys = TotpKeys::new(pwd_pepper.expose_secret().as_bytes());
         Self {
             users_repo,
             auth_repo,
             jwt,
             access_ttl,
             pwd_pepper,
+            argon2,
             email_service,
+            oauth: OAuthSettings::default(),
+            http: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(OAUTH_HTTP_TIMEOUT_SECS))
+                .build()
+                .unwrap_or_default(),
+            lockout: LockoutSettings::default(),
+            verification: AuthSettings::default(),
+            totp_keys,
         }
     }
 
+    /// Replaces the default account lockout thresholds.
+    pub fn with_lockout(mut self, lockout: LockoutSettings) -> Self {
+        self.lockout = lockout;
+        self
+    }
+
+    /// Replaces the default cooldown, lifetime and attempt limit of emailed
+    /// verification codes.
+    pub fn with_verification(mut self, verification: AuthSettings) -> Self {
+        self.verification = verification;
+        self
+    }
+
+    /// The cooldown, lifetime and attempt limit of emailed verification codes.
+    pub fn verification_settings(&self) -> &AuthSettings {
+        &self.verification
+    }
+
+    /// Enables sign-in with the providers configured in `oauth`.
+    pub fn with_oauth(mut self, oauth: OAuthSettings) -> Self {
+        self.oauth = oauth;
+        self
+    }
+
+    /// Sends the OAuth token and profile requests with `http` instead of a
+    /// client of its own.
+    pub fn with_http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// The configured provider named `provider`, with its settings.
+    fn oauth_provider(
+        &self,
+        provider: &str,
+    ) -> anyhow::Result<(OAuthProvider, &crate::configuration::OAuthProviderSettings)> {
+        OAuthProvider::from_name(provider)
+            .zip(self.oauth.provider(provider))
+            .ok_or_else(|| anyhow::anyhow!("unknown OAuth provider"))
+    }
+
+    pub fn is_oauth_provider_enabled(&self, provider: &str) -> bool {
+        self.oauth_provider(provider).is_ok()
+    }
+
+    /// The provider page that starts a sign-in, carrying `state` back to the callback.
+    pub fn oauth_authorize_url(&self, provider: &str, state: &str) -> anyhow::Result<String> {
+        let (provider, settings) = self.oauth_provider(provider)?;
+        provider.authorize_url(settings, state)
+    }
+
+    /// Signs in with an authorization code returned by `provider`.
+    ///
+    /// The code is exchanged for an access token and the account's email is
+    /// fetched. A known provider account signs in as its user; otherwise the
+    /// user with that email is linked to it, or a new user is created. New
+    /// users get a random password they can replace with a password reset.
+    ///
+    /// A user whose email is not verified is never linked: anyone can sign up
+    /// with an address they do not own, and linking would let the provider
+    /// account's owner in next to whoever set that account's password.
+    ///
+    /// Like [`Self::sign_in`], users with TOTP enabled get an MFA token to
+    /// finish with [`Self::sign_in_mfa`] instead of a token bundle.
+    pub async fn oauth_exchange(
+        &self,
+        provider: &str,
+        code: &str,
+    ) -> anyhow::Result<SignInOutcome> {
+        let (provider, settings) = self.oauth_provider(provider)?;
+        let access_token = provider.exchange_code(&self.http, settings, code).await?;
+        let profile = provider
+            .fetch_profile(&self.http, settings, &access_token)
+            .await?;
+
+        let usr = match self
+            .users_repo
+            .find_user_by_oauth_identity(provider.name(), &profile.subject_id)
+            .await?
+        {
+            Some(usr) => usr,
+            None => {
+                let usr = match self.users_repo.find_user_by_email(&profile.email).await? {
+                    Some(usr) if usr.is_email_verified => usr,
+                    Some(_) => anyhow::bail!(
+                        "an account with this email exists but its email is not verified"
+                    ),
+                    None => {
+                        let random_pwd = SecretString::from(gen_refresh_token());
+                        let norm_pwd = NormalizedPassword::try_from(&random_pwd)?;
+                        let pw_hash = hash_password(
+                            &norm_pwd,
+                            self.pwd_pepper.expose_secret(),
+                            &self.argon2,
+                        )?;
+                        let usr = self
+                            .users_repo
+                            .create(&profile.email, &pw_hash, None)
+                            .await?;
+                        self.users_repo.confirm_email(usr.id).await?;
+                        usr
+                    }
+                };
+                self.users_repo
+                    .link_oauth_identity(usr.id, provider.name(), &profile.subject_id)
+                    .await?;
+                usr
+            }
+        };
+
+        if let Some(locked_until) = usr.locked_until
+            && locked_until > Utc::now()
+        {
+            anyhow::bail!("account locked");
+        }
+
+        // The provider vouches for the first factor only
+        if self
+            .users_repo
+            .get_totp(usr.id)
+            .await?
+            .is_some_and(|totp| totp.enabled)
+        {
+            return Ok(SignInOutcome::MfaRequired(MfaChallengeResp {
+                mfa_required: true,
+                mfa_token: self
+                    .totp_keys
+                    .issue_mfa_token(usr.id, usr.jwt_token_version),
+            }));
+        }
+
+        let bundle = self
+            .issue_bundle(usr.id, usr.jwt_token_version, None, None, None)
+            .await?;
+        self.audit(
+            usr.id,
+            AuthEvent::SignIn,
+            AuthEventMeta::default().with_metadata(json!({ "method": provider.name() })),
+        )
+        .await;
+        Ok(SignInOutcome::Authenticated(bundle))
+    }
+
     pub async fn sign_up(&self, req: SignUpReq, ip: Option<IpAddr>) -> anyhow::Result<AuthBundle> {
         let email = req.email.trim();
         if !EmailAddress::is_valid(email) {
@@ -80,13 +246,14 @@ impl AuthService {
         let norm_pwd = NormalizedPassword::try_from(&req.password)?;
 
         validate_policy(&norm_pwd)?;
-        let pw_hash = hash_password(&norm_pwd, self.pwd_pepper.expose_secret())?;
+        let pw_hash = hash_password(&norm_pwd, self.pwd_pepper.expose_secret(), &self.argon2)?;
         let usr = self
             .users_repo
             .create(email, &pw_hash, req.display_name)
             .await?;
         let code = generate_verification_code();
-        let code_hash = hash_verification_code(&code, self.pwd_pepper.expose_secret())?;
+        let code_hash =
+            hash_verification_code(&code, self.pwd_pepper.expose_secret(), &self.argon2)?;
 
         self.auth_repo
             .create_or_refresh_auth_challenge(
@@ -95,8 +262,11 @@ impl AuthService {
                 None,
                 &code_hash,
                 None,
-                Utc::now() + Duration::hours(1),
-                None,
+                Utc::now() + Duration::hours(i64::from(self.verification.verification_ttl_hours)),
+                Some(
+                    i32::try_from(self.verification.verification_cooldown_seconds)
+                        .unwrap_or(i32::MAX),
+                ),
             )
             .await?;
 
@@ -110,7 +280,7 @@ impl AuthService {
         let email_fut = async {
             if let Err(err) = self
                 .email_service
-                .send_verification_code(email, &code)
+                .send_verification_code(email, &code, self.verification.verification_ttl_hours)
                 .await
             {
                 tracing::warn!(email=email, error=%err, "send verification code failed");
@@ -121,7 +291,9 @@ impl AuthService {
         bundle
     }
 
-    pub async fn sign_in(&self, req: SignInReq, meta: ClientMeta) -> anyhow::Result<AuthBundle> {
+    /// Checks the email and password; users with TOTP enabled then get an MFA
+    /// token to finish with [`Self::sign_in_mfa`] instead of a token bundle.
+    pub async fn sign_in(&self, req: SignInReq, meta: ClientMeta) -> anyhow::Result<SignInOutcome> {
         let usr_email = req.email.trim();
         if !EmailAddress::is_valid(usr_email) {
             return Err(anyhow::anyhow!("Invalid email address"));
@@ -133,10 +305,16 @@ impl AuthService {
             None => anyhow::bail!("wrong email or password"),
         };
 
+        // A locked account is refused before the password is checked, so the
+        // response time does not reveal whether the password was right
         if let Some(locked_until) = usr.locked_until
             && locked_until > Utc::now()
         {
-            anyhow::bail!("account locked");
+            return Err(ApiError::Forbidden(ACCOUNT_LOCKED.into()).into());
+        }
+        if self.has_too_many_failures(&usr).await? {
+            self.lock_account(&usr, AuthEventMeta::from(&meta)).await?;
+            return Err(ApiError::Forbidden(ACCOUNT_LOCKED.into()).into());
         }
 
         let ip = meta.ip.unwrap_or_else(|| "0.0.0.0".parse().unwrap());
@@ -158,28 +336,102 @@ impl AuthService {
         self.authenticate_user(&usr, &req.password, ip, meta.user_agent.as_deref())
             .await?;
 
+        // With TOTP on, the password alone does not count as a sign-in, so
+        // the failure count is only reset once the code is checked too
+        if self
+            .users_repo
+            .get_totp(usr.id)
+            .await?
+            .is_some_and(|totp| totp.enabled)
+        {
+            return Ok(SignInOutcome::MfaRequired(MfaChallengeResp {
+                mfa_required: true,
+                mfa_token: self
+                    .totp_keys
+                    .issue_mfa_token(usr.id, usr.jwt_token_version),
+            }));
+        }
+
+        self.complete_sign_in(&usr, req.device_id.as_deref(), &meta, "password")
+            .await
+            .map(SignInOutcome::Authenticated)
+    }
+
+    /// Finishes a sign-in that returned an MFA token, given a TOTP code.
+    ///
+    /// A wrong code counts as a failed sign-in towards the account lockout.
+    pub async fn sign_in_mfa(
+        &self,
+        req: MfaSignInReq,
+        meta: ClientMeta,
+    ) -> anyhow::Result<AuthBundle> {
+        let (user_id, jwt_version) = self.totp_keys.verify_mfa_token(&req.mfa_token)?;
+        let usr = self
+            .users_repo
+            .find_user_by_id(user_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("invalid MFA token"))?;
+        // Signing out everywhere bumps the version, which voids pending sign-ins too
+        if usr.jwt_token_version != jwt_version {
+            anyhow::bail!("invalid MFA token");
+        }
+        if let Some(locked_until) = usr.locked_until
+            && locked_until > Utc::now()
+        {
+            return Err(ApiError::Forbidden(ACCOUNT_LOCKED.into()).into());
+        }
+
+        let totp = self
+            .users_repo
+            .get_totp(usr.id)
+            .await?
+            .filter(|totp| totp.enabled)
+            .ok_or_else(|| anyhow::anyhow!("TOTP is not enabled"))?;
+        let secret = self
+            .totp_keys
+            .decrypt_secret(usr.id, &totp.secret_encrypted)?;
+
+        let ip = meta.ip.unwrap_or_else(|| "0.0.0.0".parse().unwrap());
+        if !totp::check_code(secret, &usr.email, &req.code)? {
+            self.handle_failed_signin(&usr, ip, meta.user_agent.as_deref())
+                .await?;
+            anyhow::bail!("invalid code");
+        }
+
+        self.complete_sign_in(&usr, req.device_id.as_deref(), &meta, "totp")
+            .await
+    }
+
+    /// Issues tokens for a verified sign-in and resets the failure count.
+    ///
+    /// `method` is what was checked last, recorded in the audit log.
+    async fn complete_sign_in(
+        &self,
+        usr: &crate::features::users::repositories::User,
+        device_id: Option<&str>,
+        meta: &ClientMeta,
+        method: &str,
+    ) -> anyhow::Result<AuthBundle> {
+        let ip = meta.ip.unwrap_or_else(|| "0.0.0.0".parse().unwrap());
         let bundle_fut = self.issue_bundle(
             usr.id,
             usr.jwt_token_version,
-            req.device_id.as_deref(),
-            None,
+            device_id,
+            meta.user_agent.as_deref(),
             Some(ip),
         );
 
-        let add_attempt_fut = {
-            let user_agent = meta.user_agent.clone();
-            async move {
-                if let Err(err) = self
-                    .auth_repo
-                    .add_sign_in_attempt(&usr.id, ip, usr_email, true, user_agent.as_deref())
-                    .await
-                {
-                    tracing::warn!(usr.id=%usr.id, error=%err, "failed to record sign in attempt");
-                }
+        let add_attempt_fut = async {
+            if let Err(err) = self
+                .auth_repo
+                .add_sign_in_attempt(&usr.id, ip, &usr.email, true, meta.user_agent.as_deref())
+                .await
+            {
+                tracing::warn!(usr.id=%usr.id, error=%err, "failed to record sign in attempt");
             }
         };
 
-        let fail_count_fut = async move {
+        let fail_count_fut = async {
             if let Err(err) = self
                 .users_repo
                 .update_fail_count_since(usr.id, Utc::now())
@@ -191,10 +443,99 @@ impl AuthService {
 
         let (bundle_res, _, _) = tokio::join!(bundle_fut, add_attempt_fut, fail_count_fut);
 
-        bundle_res
+        let bundle = bundle_res?;
+        self.audit(
+            usr.id,
+            AuthEvent::SignIn,
+            AuthEventMeta::from(meta).with_metadata(json!({ "method": method })),
+        )
+        .await;
+        Ok(bundle)
+    }
+
+    /// Starts TOTP enrollment with a new secret, replacing any unconfirmed one.
+    ///
+    /// The secret is not asked for at sign-in until [`Self::totp_confirm`]
+    /// has seen a code generated from it.
+    pub async fn totp_enroll(&self, user_id: Uuid) -> anyhow::Result<TotpEnrollment> {
+        let usr = self
+            .users_repo
+            .find_user_by_id(user_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("user not found"))?;
+        if self
+            .users_repo
+            .get_totp(user_id)
+            .await?
+            .is_some_and(|totp| totp.enabled)
+        {
+            anyhow::bail!("TOTP is already enabled");
+        }
+
+        let secret = totp::generate_secret();
+        let generator = totp::totp(secret.clone(), &usr.email)?;
+        let sealed = self.totp_keys.encrypt_secret(user_id, &secret)?;
+        self.users_repo.set_totp_secret(user_id, &sealed).await?;
+
+        Ok(TotpEnrollment {
+            secret: generator.get_secret_base32(),
+            otpauth_url: generator.get_url(),
+        })
     }
 
-    pub async fn sign_out(&self, user_id: Uuid, device_id: &str) -> anyhow::Result<()> {
+    /// Turns TOTP on once `code` matches the secret from enrollment.
+    pub async fn totp_confirm(
+        &self,
+        user_id: Uuid,
+        code: &str,
+        meta: ClientMeta,
+    ) -> anyhow::Result<()> {
+        let usr = self
+            .users_repo
+            .find_user_by_id(user_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("user not found"))?;
+        let totp = self
+            .users_repo
+            .get_totp(user_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("TOTP enrollment not started"))?;
+        if totp.enabled {
+            anyhow::bail!("TOTP is already enabled");
+        }
+
+        let secret = self
+            .totp_keys
+            .decrypt_secret(user_id, &totp.secret_encrypted)?;
+        if !totp::check_code(secret, &usr.email, code)? {
+            anyhow::bail!("invalid code");
+        }
+        self.users_repo.enable_totp(user_id).await?;
+        self.audit(user_id, AuthEvent::MfaEnrolled, AuthEventMeta::from(&meta))
+            .await;
+        Ok(())
+    }
+
+    /// Turns TOTP off and forgets the secret, given the current password.
+    pub async fn totp_disable(
+        &self,
+        user_id: Uuid,
+        password: &SecretString,
+        meta: ClientMeta,
+    ) -> anyhow::Result<()> {
+        self.verify_password(user_id, password).await?;
+        self.users_repo.clear_totp(user_id).await?;
+        self.audit(user_id, AuthEvent::MfaDisabled, AuthEventMeta::from(&meta))
+            .await;
+        Ok(())
+    }
+
+    pub async fn sign_out(
+        &self,
+        user_id: Uuid,
+        device_id: &str,
+        meta: ClientMeta,
+    ) -> anyhow::Result<()> {
         if let Some(dev) = self
             .auth_repo
             .get_refresh_device_by_user_id(device_id, user_id)
@@ -202,15 +543,82 @@ impl AuthService {
         {
             self.auth_repo.revoke_device(dev.id).await?;
         }
+        self.audit(
+            user_id,
+            AuthEvent::SignOut,
+            AuthEventMeta::from(&meta).with_metadata(json!({ "device_id": device_id })),
+        )
+        .await;
         Ok(())
     }
 
-    pub async fn sign_out_all(&self, user_id: Uuid) -> anyhow::Result<()> {
+    pub async fn sign_out_all(&self, user_id: Uuid, meta: ClientMeta) -> anyhow::Result<()> {
         self.auth_repo.revoke_all(user_id).await?;
         self.users_repo.bump_jwt_version(user_id).await?;
+        self.audit(
+            user_id,
+            AuthEvent::TokenRevoked,
+            AuthEventMeta::from(&meta).with_metadata(json!({ "scope": "all_devices" })),
+        )
+        .await;
         Ok(())
     }
 
+    /// The user's signed-in devices, most recently used first.
+    pub async fn devices(&self, user_id: Uuid) -> anyhow::Result<Vec<DeviceSummary>> {
+        self.auth_repo.list_devices_for_user(user_id).await
+    }
+
+    /// Signs one of the user's devices out, e.g. one they do not recognise.
+    ///
+    /// Unlike [`Self::sign_out`], a device that is unknown or already signed
+    /// out is an error, so the caller can tell the user nothing was revoked.
+    pub async fn revoke_device(
+        &self,
+        user_id: Uuid,
+        device_id: &str,
+        meta: ClientMeta,
+    ) -> anyhow::Result<()> {
+        let dev = self
+            .auth_repo
+            .get_refresh_device_by_user_id(device_id, user_id)
+            .await?
+            .filter(|dev| dev.revoked_at.is_none())
+            .ok_or_else(|| ApiError::NotFound("device not found".into()))?;
+
+        self.auth_repo.revoke_device(dev.id).await?;
+        self.audit(
+            user_id,
+            AuthEvent::TokenRevoked,
+            AuthEventMeta::from(&meta).with_metadata(json!({ "device_id": device_id })),
+        )
+        .await;
+        Ok(())
+    }
+
+    /// A page of the user's authentication audit log, newest first.
+    pub async fn auth_events(
+        &self,
+        user_id: Uuid,
+        offset: u64,
+        limit: u64,
+    ) -> anyhow::Result<Vec<AuthAuditEntry>> {
+        Ok(self
+            .auth_repo
+            .get_auth_events(user_id, offset, limit)
+            .await?)
+    }
+
+    /// Records `event` in the audit log.
+    ///
+    /// A failed write is logged rather than returned, so an audit outage
+    /// does not undo or block the action being audited.
+    async fn audit(&self, user_id: Uuid, event: AuthEvent, meta: AuthEventMeta) {
+        if let Err(err) = self.auth_repo.log_auth_event(user_id, event, meta).await {
+            tracing::warn!(%user_id, event = event.as_str(), error=%err, "failed to record auth event");
+        }
+    }
+
     pub async fn request_email_change(
         &self,
         user_id: Uuid,
@@ -271,13 +679,24 @@ impl AuthService {
         result
     }
 
-    pub async fn confirm_email_change(&self, user_id: Uuid, code: &str) -> anyhow::Result<()> {
+    pub async fn confirm_email_change(
+        &self,
+        user_id: Uuid,
+        code: &str,
+        meta: ClientMeta,
+    ) -> anyhow::Result<()> {
         let challenge = self
             .verify_code(user_id, AuthenticationAction::ChangeEmail, code)
             .await?;
 
         if let Some(target_email) = challenge.target {
             self.users_repo.update_email(user_id, &target_email).await?;
+            self.audit(
+                user_id,
+                AuthEvent::EmailChanged,
+                AuthEventMeta::from(&meta).with_metadata(json!({ "new_email": target_email })),
+            )
+            .await;
         } else {
             return Err(anyhow::anyhow!("no target email in challenge"));
         }
@@ -315,17 +734,16 @@ impl AuthService {
             .unwrap_or(false);
 
         if !matches_current && matches_previous {
-            if let Some(rot) = dev.last_rotated_at {
-                if (Utc::now() - rot).num_seconds() > GRACE_SECONDS {
-                    let _ = self.auth_repo.revoke_device(dev.id).await;
-                    return Err(anyhow::anyhow!("stale refresh token"));
-                }
-            } else {
-                let _ = self.auth_repo.revoke_device(dev.id).await;
+            let within_grace = dev
+                .last_rotated_at
+                .is_some_and(|rot| (Utc::now() - rot).num_seconds() <= GRACE_SECONDS);
+            if !within_grace {
+                self.revoke_reused_device(&dev, "stale refresh token").await;
                 return Err(anyhow::anyhow!("stale refresh token"));
             }
         } else if !matches_current {
-            let _ = self.auth_repo.revoke_device(dev.id).await;
+            self.revoke_reused_device(&dev, "invalid refresh token")
+                .await;
             return Err(anyhow::anyhow!("invalid refresh token"));
         }
 
@@ -351,28 +769,55 @@ impl AuthService {
         })
     }
 
+    /// Revokes a device whose refresh token was replayed, which suggests it leaked.
+    async fn revoke_reused_device(
+        &self,
+        dev: &crate::features::auth::repositories::RefreshDevice,
+        reason: &str,
+    ) {
+        let _ = self.auth_repo.revoke_device(dev.id).await;
+        self.audit(
+            dev.user_id,
+            AuthEvent::TokenRevoked,
+            AuthEventMeta {
+                ip: dev.ip,
+                user_agent: dev.user_agent.clone(),
+                metadata: Some(json!({ "device_id": dev.device_id, "reason": reason })),
+            },
+        )
+        .await;
+    }
+
     pub async fn change_password(
         &self,
         user_id: Uuid,
         old_pwd: &SecretString,
         new_pwd: &SecretString,
+        meta: ClientMeta,
     ) -> anyhow::Result<()> {
         self.verify_password(user_id, old_pwd).await?;
 
-        self.reset_password(user_id, new_pwd).await
+        self.reset_password(user_id, new_pwd, meta).await
     }
 
     pub async fn reset_password(
         &self,
         user_id: Uuid,
         new_pwd: &SecretString,
+        meta: ClientMeta,
     ) -> anyhow::Result<()> {
         let norm_pwd = NormalizedPassword::try_from(new_pwd)?;
         validate_policy(&norm_pwd)?;
-        let new_hash = hash_password(&norm_pwd, self.pwd_pepper.expose_secret())?;
+        let new_hash = hash_password(&norm_pwd, self.pwd_pepper.expose_secret(), &self.argon2)?;
         self.users_repo.update_password(user_id, &new_hash).await?;
+        self.audit(
+            user_id,
+            AuthEvent::PasswordChanged,
+            AuthEventMeta::from(&meta),
+        )
+        .await;
 
-        self.sign_out_all(user_id).await
+        self.sign_out_all(user_id, meta).await
     }
 
     pub async fn send_verification_code(
@@ -384,7 +829,8 @@ impl AuthService {
         meta: Option<&serde_json::Value>,
     ) -> anyhow::Result<()> {
         let code = generate_verification_code();
-        let code_hash = hash_verification_code(&code, self.pwd_pepper.expose_secret())?;
+        let code_hash =
+            hash_verification_code(&code, self.pwd_pepper.expose_secret(), &self.argon2)?;
 
         self.auth_repo
             .create_or_refresh_auth_challenge(
@@ -393,21 +839,28 @@ impl AuthService {
                 target,
                 &code_hash,
                 meta,
-                Utc::now() + Duration::hours(1),
-                None,
+                Utc::now() + Duration::hours(i64::from(self.verification.verification_ttl_hours)),
+                Some(
+                    i32::try_from(self.verification.verification_cooldown_seconds)
+                        .unwrap_or(i32::MAX),
+                ),
             )
             .await?;
 
         match action {
             AuthenticationAction::VerifyEmail | AuthenticationAction::ChangeEmail => {
                 self.email_service
-                    .send_verification_code(email, &code)
+                    .send_verification_code(email, &code, self.verification.verification_ttl_hours)
                     .await
                     .map_err(|e| anyhow::anyhow!("failed to send email: {}", e))?;
             }
             AuthenticationAction::ResetPassword => {
                 self.email_service
-                    .send_password_reset_code(email, &code)
+                    .send_password_reset_code(
+                        email,
+                        &code,
+                        self.verification.verification_ttl_hours,
+                    )
                     .await
                     .map_err(|e| anyhow::anyhow!("failed to send email: {}", e))?;
             }
@@ -430,7 +883,7 @@ impl AuthService {
             return Err(anyhow::anyhow!("challenge not found"));
         };
 
-        if challenge.attempts >= MAX_ATTEMPTS_ALLOWED {
+        if challenge.attempts >= self.verification.max_verification_attempts {
             return Err(anyhow::anyhow!(
                 "too many attempts, please request a new code"
             ));
@@ -457,11 +910,22 @@ impl AuthService {
     pub async fn verify_password(&self, uuid: Uuid, password: &SecretString) -> anyhow::Result<()> {
         let stored = self.users_repo.get_password_hash_by_id(uuid).await?;
 
-        if !verify_password(password, &stored, self.pwd_pepper.expose_secret())? {
-            return Err(anyhow::anyhow!("invalid password"));
+        match verify_password(
+            password,
+            &stored,
+            self.pwd_pepper.expose_secret(),
+            &self.argon2,
+        )? {
+            PasswordVerification::Invalid => Err(anyhow::anyhow!("invalid password")),
+            PasswordVerification::Valid => Ok(()),
+            PasswordVerification::Rehashed(new_hash) => {
+                // A failed upgrade leaves the old hash in place, which still verifies
+                if let Err(e) = self.users_repo.update_password(uuid, &new_hash).await {
+                    tracing::warn!(user_id = %uuid, "failed to store rehashed password: {}", e);
+                }
+                Ok(())
+            }
         }
-
-        Ok(())
     }
 
     pub async fn verify_token(&self, token: &str) -> anyhow::Result<Claims> {
@@ -534,22 +998,66 @@ impl AuthService {
             .add_sign_in_attempt(&usr.id, ip, &usr.email, false, user_agent)
             .await?;
 
-        let should_lock = self
+        if self.has_too_many_failures(usr).await? {
+            let meta = AuthEventMeta {
+                ip: Some(ip),
+                user_agent: user_agent.map(str::to_owned),
+                metadata: None,
+            };
+            self.lock_account(usr, meta).await?;
+            return Err(ApiError::Forbidden(ACCOUNT_LOCKED.into()).into());
+        }
+
+        Ok(())
+    }
+
+    /// Whether `lockout.max_attempts` sign-ins have failed within the window,
+    /// counting only failures since the last success or lock.
+    async fn has_too_many_failures(
+        &self,
+        usr: &crate::features::users::repositories::User,
+    ) -> anyhow::Result<bool> {
+        Ok(self
             .auth_repo
             .should_lock_user_for_failures(
                 &usr.id,
-                MAX_SIGNIN_ATTEMPTS_PER_USER,
-                MAX_USER_SIGNIN_ATTEMPT_WINDOW_MINS,
+                i32::from(self.lockout.max_attempts),
+                i32::try_from(self.lockout.window_minutes).unwrap_or(i32::MAX),
                 usr.fail_count_since,
             )
-            .await?;
+            .await?)
+    }
 
-        if should_lock {
-            let until = Utc::now() + Duration::hours(ACCOUNT_LOCK_HOURS);
-            self.users_repo.lock_user_until(usr.id, until).await?;
-            anyhow::bail!("account locked");
+    /// Locks the account for `lockout.lockout_duration_minutes` and emails its owner.
+    ///
+    /// Failures are counted again from the end of the lock, so the account is
+    /// not locked again by the failures that locked it.
+    async fn lock_account(
+        &self,
+        usr: &crate::features::users::repositories::User,
+        meta: AuthEventMeta,
+    ) -> anyhow::Result<()> {
+        let until =
+            Utc::now() + Duration::minutes(i64::from(self.lockout.lockout_duration_minutes));
+        self.users_repo.lock_user_until(usr.id, until).await?;
+        self.users_repo
+            .update_fail_count_since(usr.id, until)
+            .await?;
+        tracing::warn!(usr.id=%usr.id, %until, "account locked after failed sign-ins");
+        self.audit(
+            usr.id,
+            AuthEvent::AccountLocked,
+            meta.with_metadata(json!({ "locked_until": until })),
+        )
+        .await;
+
+        if let Err(e) = self
+            .email_service
+            .send_account_locked(&usr.email, self.lockout.max_attempts, until)
+            .await
+        {
+            tracing::warn!(email=%usr.email, error=%e, "failed to send account locked email");
         }
-
         Ok(())
     }
 }
@@ -0,0 +1,240 @@
+use crate::core::security::HmacSha256;
+use aes_gcm::{
+    Aes256Gcm, KeyInit, Nonce,
+    aead::{Aead, AeadCore, OsRng, Payload},
+};
+use base64::Engine;
+use chrono::{Duration, Utc};
+use hkdf::Hkdf;
+use hmac::Mac;
+use sha2::Sha256;
+use totp_rs::{Algorithm, Secret, TOTP};
+use uuid::Uuid;
+
+const ISSUER: &str = "url-shortener-ztm";
+const DIGITS: usize = 6;
+const STEP_SECS: u64 = 30;
+// codes from the previous and next step are accepted, for clock drift
+const SKEW_STEPS: u8 = 1;
+const NONCE_LEN: usize = 12;
+/// How long a sign-in waits for its TOTP code after the password was accepted.
+pub const MFA_TOKEN_TTL_MINUTES: i64 = 5;
+
+/// Keys derived from the server secret for storing TOTP secrets and issuing
+/// MFA tokens.
+///
+/// TOTP secrets are sealed with AES-256-GCM, bound to their user's id, so a
+/// secret copied to another user's row does not decrypt.
+pub struct TotpKeys {
+    cipher: Aes256Gcm,
+    mfa_key: [u8; 32],
+}
+
+impl TotpKeys {
+    pub fn new(server_secret: &[u8]) -> Self {
+        let hk = Hkdf::<Sha256>::new(None, server_secret);
+        let mut cipher_key = [0u8; 32];
+        let mut mfa_key = [0u8; 32];
+        // both outputs are 32 bytes, well within what HKDF-SHA256 can expand to
+        hk.expand(b"totp-secret-encryption", &mut cipher_key)
+            .expect("32 bytes is a valid HKDF-SHA256 length");
+        hk.expand(b"mfa-token", &mut mfa_key)
+            .expect("32 bytes is a valid HKDF-SHA256 length");
+
+        Self {
+            cipher: Aes256Gcm::new(&cipher_key.into()),
+            mfa_key,
+        }
+    }
+
+    /// Encrypts `secret` for storage, as the nonce followed by the ciphertext.
+    pub fn encrypt_secret(&self, user_id: Uuid, secret: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: secret,
+                    aad: user_id.as_bytes(),
+                },
+            )
+            .map_err(|_| anyhow::anyhow!("failed to encrypt TOTP secret"))?;
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        Ok(sealed)
+    }
+
+    pub fn decrypt_secret(&self, user_id: Uuid, sealed: &[u8]) -> anyhow::Result<Vec<u8>> {
+        if sealed.len() < NONCE_LEN {
+            anyhow::bail!("stored TOTP secret is truncated");
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let nonce: [u8; NONCE_LEN] = nonce.try_into().expect("split at NONCE_LEN");
+        self.cipher
+            .decrypt(
+                &Nonce::from(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: user_id.as_bytes(),
+                },
+            )
+            .map_err(|_| anyhow::anyhow!("failed to decrypt TOTP secret"))
+    }
+
+    /// Issues the token that stands in for a password-verified sign-in until
+    /// the TOTP code is checked.
+    ///
+    /// It is `user_id.jwt_version.expires_at.mac`; it is not a JWT, so it
+    /// cannot be mistaken for an access token.
+    pub fn issue_mfa_token(&self, user_id: Uuid, jwt_version: u32) -> String {
+        let exp = (Utc::now() + Duration::minutes(MFA_TOKEN_TTL_MINUTES)).timestamp();
+        let payload = format!("{}.{}.{}", user_id, jwt_version, exp);
+        let mac = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(self.mfa_mac(&payload));
+        format!("{}.{}", payload, mac)
+    }
+
+    /// The user id and JWT version an unexpired MFA token was issued for.
+    pub fn verify_mfa_token(&self, token: &str) -> anyhow::Result<(Uuid, u32)> {
+        let invalid = || anyhow::anyhow!("invalid MFA token");
+        let (payload, mac) = token.rsplit_once('.').ok_or_else(invalid)?;
+        let mac = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(mac)
+            .map_err(|_| invalid())?;
+
+        let mut expected = self.mfa_hmac();
+        expected.update(payload.as_bytes());
+        expected.verify_slice(&mac).map_err(|_| invalid())?;
+
+        let mut parts = payload.split('.');
+        let (Some(user_id), Some(version), Some(exp), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+        let exp: i64 = exp.parse().map_err(|_| invalid())?;
+        if Utc::now().timestamp() > exp {
+            anyhow::bail!("MFA token expired");
+        }
+
+        Ok((
+            user_id.parse().map_err(|_| invalid())?,
+            version.parse().map_err(|_| invalid())?,
+        ))
+    }
+
+    fn mfa_hmac(&self) -> HmacSha256 {
+        <HmacSha256 as Mac>::new_from_slice(&self.mfa_key).expect("HMAC accepts keys of any length")
+    }
+
+    fn mfa_mac(&self, payload: &str) -> Vec<u8> {
+        let mut mac = self.mfa_hmac();
+        mac.update(payload.as_bytes());
+        mac.finalize().into_bytes().to_vec()
+    }
+}
+
+/// A new random 160-bit TOTP secret.
+pub fn generate_secret() -> Vec<u8> {
+    match Secret::generate_secret() {
+        Secret::Raw(bytes) => bytes,
+        encoded => encoded.to_bytes().unwrap_or_default(),
+    }
+}
+
+/// The RFC 6238 generator for `secret`: SHA-1, 6 digits, 30-second steps,
+/// which is what authenticator apps assume.
+pub fn totp(secret: Vec<u8>, account: &str) -> anyhow::Result<TOTP> {
+    Ok(TOTP::new(
+        Algorithm::SHA1,
+        DIGITS,
+        SKEW_STEPS,
+        STEP_SECS,
+        secret,
+        Some(ISSUER.to_string()),
+        account.to_string(),
+    )?)
+}
+
+/// Whether `code` is valid for `secret` right now.
+pub fn check_code(secret: Vec<u8>, account: &str, code: &str) -> anyhow::Result<bool> {
+    Ok(totp(secret, account)?.check_current(code.trim())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secrets_round_trip_only_for_their_user() {
+        let keys = TotpKeys::new(b"server-secret");
+        let user = Uuid::new_v4();
+        let secret = generate_secret();
+
+        let sealed = keys.encrypt_secret(user, &secret).unwrap();
+
+        assert_ne!(&sealed[NONCE_LEN..], secret.as_slice());
+        assert_eq!(keys.decrypt_secret(user, &sealed).unwrap(), secret);
+        assert!(keys.decrypt_secret(Uuid::new_v4(), &sealed).is_err());
+        assert!(
+            TotpKeys::new(b"another-secret")
+                .decrypt_secret(user, &sealed)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn mfa_tokens_verify_and_reject_tampering() {
+        let keys = TotpKeys::new(b"server-secret");
+        let user = Uuid::new_v4();
+
+        let token = keys.issue_mfa_token(user, 3);
+
+        assert_eq!(keys.verify_mfa_token(&token).unwrap(), (user, 3));
+        let tampered = token.replacen(".3.", ".4.", 1);
+        assert!(keys.verify_mfa_token(&tampered).is_err());
+        assert!(
+            TotpKeys::new(b"another-secret")
+                .verify_mfa_token(&token)
+                .is_err()
+        );
+        assert!(keys.verify_mfa_token("not-a-token").is_err());
+    }
+
+    #[test]
+    fn expired_mfa_tokens_are_rejected() {
+        let keys = TotpKeys::new(b"server-secret");
+        let payload = format!("{}.0.{}", Uuid::new_v4(), Utc::now().timestamp() - 1);
+        let mac = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(keys.mfa_mac(&payload));
+
+        let result = keys.verify_mfa_token(&format!("{}.{}", payload, mac));
+
+        assert!(result.unwrap_err().to_string().contains("expired"));
+    }
+
+    #[test]
+    fn codes_follow_rfc_6238() {
+        // RFC 6238 appendix B, SHA-1 secret, truncated to 6 digits
+        let totp = totp(b"12345678901234567890".to_vec(), "someone@example.com").unwrap();
+
+        assert_eq!(totp.generate(59), "287082");
+        assert_eq!(totp.generate(1_111_111_109), "081804");
+        assert!(
+            totp.get_url()
+                .starts_with("otpauth://totp/url-shortener-ztm:")
+        );
+    }
+
+    #[test]
+    fn the_current_code_checks_out() {
+        let secret = generate_secret();
+        let code = totp(secret.clone(), "someone@example.com")
+            .unwrap()
+            .generate_current()
+            .unwrap();
+
+        assert!(check_code(secret.clone(), "someone@example.com", &code).unwrap());
+        assert!(!check_code(secret, "someone@example.com", "000000x").unwrap());
+    }
+}
@@ -1,42 +1,194 @@
 use crate::core::extractors::auth_user::AuthenticatedUser;
-use crate::features::users::dto::MeResp;
-use crate::features::users::services::UserService;
+use crate::database::UrlDatabase;
+use crate::features::auth::AuthService;
+use crate::features::users::dto::{
+    DeleteAccountReq, MeResp, UpdateProfileReq, VanityDomainReq, VanityDomainResp,
+};
+use crate::features::users::services::{UserService, normalize_vanity_domain};
+use crate::models::UrlRecord;
+use crate::routes::redirect::{RedirectCache, forget_cached_redirects};
 use crate::{ApiError, ApiResponse, AppState};
+use axum::Json;
 use axum::extract::{FromRef, State};
+use axum::http::StatusCode;
 use axum::response::IntoResponse;
+use axum_extra::extract::{CookieJar, cookie::Cookie};
 use std::sync::Arc;
 
 #[derive(Clone)]
 pub struct UserController {
     pub svc: Arc<UserService>,
+    pub auth_svc: Arc<AuthService>,
+    pub database: Arc<dyn UrlDatabase>,
+    pub redirect_cache: Arc<RedirectCache>,
 }
 
 impl FromRef<AppState> for UserController {
     fn from_ref(app: &AppState) -> Self {
         Self {
-            svc: app.user_service.clone(),
+            svc: app.user_service().clone(),
+            auth_svc: app.auth_service().clone(),
+            database: app.database().clone(),
+            redirect_cache: app.redirect_cache().clone(),
         }
     }
 }
 
-pub async fn me(
+/// `GET /api/v1/user/me`: the signed-in user's profile, with how many short
+/// URLs they own and how often those were clicked.
+pub async fn get_me(
     State(ctrl): State<UserController>,
     user: AuthenticatedUser,
 ) -> Result<impl IntoResponse, ApiError> {
-    let u = ctrl
+    let profile = ctrl
         .svc
         .me(user.user_id)
         .await
         .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let total_urls = ctrl
+        .database
+        .count_urls_by_owner(user.user_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    let total_clicks = ctrl
+        .database
+        .sum_clicks_by_owner(user.user_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
 
     let response = MeResp {
-        id: u.id,
-        email: u.email,
-        display_name: u.display_name,
-        is_email_verified: u.is_email_verified,
-        created_at: u.created_at,
-        last_login_at: u.last_login_at,
+        member_since: profile.created_at,
+        email_verified: profile.is_email_verified,
+        user: profile,
+        total_urls,
+        total_clicks,
     };
 
     Ok(ApiResponse::success(response))
 }
+
+/// `PATCH /api/v1/user/profile`: sets the signed-in user's display name and
+/// returns the updated profile. No password is needed; a name that is empty,
+/// longer than 30 characters or holds control or invisible characters is
+/// rejected with 422.
+pub async fn patch_user_profile(
+    State(ctrl): State<UserController>,
+    user: AuthenticatedUser,
+    Json(req): Json<UpdateProfileReq>,
+) -> Result<impl IntoResponse, ApiError> {
+    ctrl.svc
+        .update_display_name(user.user_id, &req.display_name)
+        .await
+        .map_err(|e| ApiError::Unprocessable(e.to_string()))?;
+    let profile = ctrl
+        .svc
+        .me(user.user_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    Ok(ApiResponse::success(profile))
+}
+
+/// `DELETE /api/v1/user`: deletes the signed-in user's account and data once
+/// the password is confirmed, and clears the auth cookies.
+///
+/// A wrong password is answered with 403. Accounts created by an OAuth
+/// sign-in only have a random password, so they set one with a password
+/// reset first. The cached redirects of the deleted short links are dropped,
+/// so they stop redirecting at once.
+pub async fn delete_account(
+    State(ctrl): State<UserController>,
+    user: AuthenticatedUser,
+    jar: CookieJar,
+    Json(req): Json<DeleteAccountReq>,
+) -> Result<impl IntoResponse, ApiError> {
+    // Read before deleting, as the rows go with the account
+    let owned = owned_urls(ctrl.database.as_ref(), user.user_id).await?;
+    ctrl.svc
+        .delete_account(user.user_id, &req.password, &ctrl.auth_svc)
+        .await
+        .map_err(|e| {
+            e.downcast::<ApiError>()
+                .unwrap_or_else(|e| ApiError::Internal(e.to_string()))
+        })?;
+    for record in &owned {
+        forget_cached_redirects(&ctrl.redirect_cache, &record.code, Some(&record.url));
+    }
+
+    // Expired cookies on the paths they were set on; the refresh cookie is
+    // never sent to this route, so it cannot just be removed from the jar
+    let jar = jar
+        .add(expired_cookie("access_token", "/"))
+        .add(expired_cookie("refresh_token", "/api/v1/auth/refresh"));
+
+    Ok((StatusCode::NO_CONTENT, jar))
+}
+
+/// Rows of [`UrlDatabase::list_urls`] fetched at a time by [`owned_urls`].
+const OWNED_URLS_PAGE_SIZE: u64 = 500;
+
+// Every URL owned by `owner_id`, a page at a time
+async fn owned_urls(
+    database: &dyn UrlDatabase,
+    owner_id: uuid::Uuid,
+) -> Result<Vec<UrlRecord>, ApiError> {
+    let mut owned = Vec::new();
+    loop {
+        let page = database
+            .list_urls(owned.len() as u64, OWNED_URLS_PAGE_SIZE, Some(owner_id))
+            .await
+            .map_err(|e| ApiError::Internal(e.to_string()))?;
+        let done = (page.len() as u64) < OWNED_URLS_PAGE_SIZE;
+        owned.extend(page);
+        if done {
+            return Ok(owned);
+        }
+    }
+}
+
+/// `GET /api/v1/user/vanity-domain`: the signed-in user's vanity domain, or
+/// `null` when they have not set one.
+pub async fn get_vanity_domain(
+    State(ctrl): State<UserController>,
+    user: AuthenticatedUser,
+) -> Result<impl IntoResponse, ApiError> {
+    let vanity_domain = ctrl
+        .svc
+        .vanity_domain(user.user_id)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    Ok(ApiResponse::success(VanityDomainResp { vanity_domain }))
+}
+
+/// `POST /api/v1/user/vanity-domain`: sets the host the signed-in user's
+/// short URLs are shared on. The domain must be a valid hostname no other
+/// user has claimed.
+pub async fn set_vanity_domain(
+    State(ctrl): State<UserController>,
+    user: AuthenticatedUser,
+    Json(req): Json<VanityDomainReq>,
+) -> Result<impl IntoResponse, ApiError> {
+    let domain = normalize_vanity_domain(&req.domain)
+        .ok_or_else(|| ApiError::Unprocessable("domain must be a valid hostname".to_string()))?;
+    let claimed = ctrl
+        .svc
+        .set_vanity_domain(user.user_id, &domain)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    if !claimed {
+        return Err(ApiError::Conflict(
+            "domain is already claimed by another user".to_string(),
+        ));
+    }
+
+    Ok(ApiResponse::success(VanityDomainResp {
+        vanity_domain: Some(domain),
+    }))
+}
+
+fn expired_cookie(name: &'static str, path: &'static str) -> Cookie<'static> {
+    let mut cookie = Cookie::build((name, "")).path(path).build();
+    cookie.make_removal();
+    cookie
+}
@@ -1,9 +1,10 @@
 use chrono::{DateTime, Utc};
+use secrecy::SecretString;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 #[derive(Serialize)]
-pub struct MeResp {
+pub struct UserProfile {
     pub id: Uuid,
     pub email: String,
     pub display_name: Option<String>,
@@ -12,6 +13,18 @@ pub struct MeResp {
     pub last_login_at: Option<DateTime<Utc>>,
 }
 
+/// Body of `GET /api/v1/user/me`.
+#[derive(Serialize)]
+pub struct MeResp {
+    pub user: UserProfile,
+    /// Short URLs the user owns
+    pub total_urls: u64,
+    /// Clicks recorded on those URLs
+    pub total_clicks: u64,
+    pub member_since: DateTime<Utc>,
+    pub email_verified: bool,
+}
+
 #[derive(Deserialize)]
 pub struct ChangeEmailReq {
     pub new_email: String,
@@ -22,3 +35,23 @@ pub struct ChangePasswordReq {
     pub old_password: String,
     pub new_password: String,
 }
+
+#[derive(Deserialize)]
+pub struct UpdateProfileReq {
+    pub display_name: String,
+}
+
+#[derive(Deserialize)]
+pub struct DeleteAccountReq {
+    pub password: SecretString,
+}
+
+#[derive(Deserialize)]
+pub struct VanityDomainReq {
+    pub domain: String,
+}
+
+#[derive(Serialize)]
+pub struct VanityDomainResp {
+    pub vanity_domain: Option<String>,
+}
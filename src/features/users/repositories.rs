@@ -15,6 +15,17 @@ pub struct User {
     pub jwt_token_version: u32,
     pub locked_until: Option<DateTime<Utc>>,
     pub fail_count_since: Option<DateTime<Utc>>,
+    /// Host the user's short URLs are shared on, e.g. `go.mycompany.com`
+    pub vanity_domain: Option<String>,
+}
+
+/// A user's stored TOTP secret, and whether sign-in asks for a code.
+///
+/// The secret is stored on enrollment and enabled once a first code confirms it.
+#[derive(Clone, Debug)]
+pub struct TotpState {
+    pub secret_encrypted: Vec<u8>,
+    pub enabled: bool,
 }
 
 #[async_trait]
@@ -38,9 +49,34 @@ pub trait UserRepository: Send + Sync {
 
     async fn update_password(&self, id: Uuid, new_hash: &[u8]) -> anyhow::Result<()>;
     async fn update_email(&self, id: Uuid, new_email: &str) -> anyhow::Result<()>;
+    async fn update_display_name(&self, id: Uuid, name: &str) -> anyhow::Result<()>;
 
     async fn lock_user_until(&self, id: Uuid, until: DateTime<Utc>) -> anyhow::Result<()>;
     async fn update_fail_count_since(&self, id: Uuid, since: DateTime<Utc>) -> anyhow::Result<()>;
+
+    async fn find_user_by_oauth_identity(
+        &self,
+        provider: &str,
+        subject_id: &str,
+    ) -> anyhow::Result<Option<User>>;
+    async fn link_oauth_identity(
+        &self,
+        user_id: Uuid,
+        provider: &str,
+        subject_id: &str,
+    ) -> anyhow::Result<()>;
+
+    async fn get_totp(&self, id: Uuid) -> anyhow::Result<Option<TotpState>>;
+    async fn set_totp_secret(&self, id: Uuid, secret_encrypted: &[u8]) -> anyhow::Result<()>;
+    async fn enable_totp(&self, id: Uuid) -> anyhow::Result<()>;
+    async fn clear_totp(&self, id: Uuid) -> anyhow::Result<()>;
+
+    async fn find_user_by_vanity_domain(&self, domain: &str) -> anyhow::Result<Option<User>>;
+    async fn set_vanity_domain(&self, id: Uuid, domain: &str) -> anyhow::Result<()>;
+
+    /// Deletes the user and everything recorded about them, including the
+    /// short links they created, in one transaction.
+    async fn delete_user(&self, id: Uuid) -> anyhow::Result<()>;
 }
 
 // A no-operation implementation of UserRepository for testing purposes.
@@ -76,6 +112,9 @@ impl UserRepository for NoopUserRepo {
     async fn update_email(&self, _id: Uuid, _new_email: &str) -> anyhow::Result<()> {
         Ok(())
     }
+    async fn update_display_name(&self, _id: Uuid, _name: &str) -> anyhow::Result<()> {
+        Ok(())
+    }
     async fn get_password_hash_by_id(&self, _id: Uuid) -> anyhow::Result<Vec<u8>> {
         anyhow::bail!("NoopUserRepo: get_password_hash_by_id not supported")
     }
@@ -91,4 +130,49 @@ impl UserRepository for NoopUserRepo {
     ) -> anyhow::Result<()> {
         Ok(())
     }
+
+    async fn find_user_by_oauth_identity(
+        &self,
+        _provider: &str,
+        _subject_id: &str,
+    ) -> anyhow::Result<Option<User>> {
+        Ok(None)
+    }
+
+    async fn link_oauth_identity(
+        &self,
+        _user_id: Uuid,
+        _provider: &str,
+        _subject_id: &str,
+    ) -> anyhow::Result<()> {
+        anyhow::bail!("NoopUserRepo: link_oauth_identity not supported")
+    }
+
+    async fn get_totp(&self, _id: Uuid) -> anyhow::Result<Option<TotpState>> {
+        Ok(None)
+    }
+
+    async fn set_totp_secret(&self, _id: Uuid, _secret: &[u8]) -> anyhow::Result<()> {
+        anyhow::bail!("NoopUserRepo: set_totp_secret not supported")
+    }
+
+    async fn enable_totp(&self, _id: Uuid) -> anyhow::Result<()> {
+        anyhow::bail!("NoopUserRepo: enable_totp not supported")
+    }
+
+    async fn clear_totp(&self, _id: Uuid) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    async fn find_user_by_vanity_domain(&self, _domain: &str) -> anyhow::Result<Option<User>> {
+        Ok(None)
+    }
+
+    async fn set_vanity_domain(&self, _id: Uuid, _domain: &str) -> anyhow::Result<()> {
+        anyhow::bail!("NoopUserRepo: set_vanity_domain not supported")
+    }
+
+    async fn delete_user(&self, _id: Uuid) -> anyhow::Result<()> {
+        Ok(())
+    }
 }
@@ -1,8 +1,20 @@
 use crate::AppState;
 
 use super::controllers as c;
-use axum::{Router, routing::get};
+use crate::features::auth::controllers::security_events;
+use axum::{
+    Router,
+    routing::{delete, get, patch},
+};
 
 pub fn router() -> Router<AppState> {
-    Router::new().route("/me", get(c::me))
+    Router::new()
+        .route("/", delete(c::delete_account))
+        .route("/me", get(c::get_me))
+        .route("/profile", patch(c::patch_user_profile))
+        .route("/security-events", get(security_events))
+        .route(
+            "/vanity-domain",
+            get(c::get_vanity_domain).post(c::set_vanity_domain),
+        )
 }
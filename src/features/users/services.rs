@@ -1,31 +1,52 @@
 // features/users/services.rs
-use crate::features::users::dto::MeResp;
+use crate::ApiError;
+use crate::core::security::password::normalize_text;
+use crate::features::auth::AuthService;
+use crate::features::auth::services::MAX_USER_NAME_LENGTH;
+use crate::features::users::dto::UserProfile;
 use crate::features::users::repositories::UserRepository;
 use anyhow::{Result, anyhow};
 use email_address::EmailAddress;
+use moka::sync::Cache;
+use secrecy::SecretString;
 use uuid::Uuid;
 // use chrono::Utc;
 use std::sync::Arc;
+use std::time::Duration;
 // use uuid::Uuid;
 
+/// Hosts whose vanity domain owner, or lack of one, is remembered at most.
+const VANITY_OWNER_CAPACITY: u64 = 10_000;
+
+/// How long a vanity domain owner is remembered. Changes made through this
+/// service clear the cache at once; the limit covers other instances.
+const VANITY_OWNER_TTL: Duration = Duration::from_secs(60);
+
 #[derive(Clone)]
 pub struct UserService {
     repo: Arc<dyn UserRepository>,
+    vanity_owners: Cache<String, Option<Uuid>>,
 }
 
 impl UserService {
     pub fn new(repo: Arc<dyn UserRepository>) -> Self {
-        Self { repo }
+        Self {
+            repo,
+            vanity_owners: Cache::builder()
+                .max_capacity(VANITY_OWNER_CAPACITY)
+                .time_to_live(VANITY_OWNER_TTL)
+                .build(),
+        }
     }
 
-    pub async fn me(&self, id: Uuid) -> Result<MeResp> {
+    pub async fn me(&self, id: Uuid) -> Result<UserProfile> {
         let usr = self
             .repo
             .find_user_by_id(id)
             .await?
             .ok_or_else(|| anyhow!("User not found"))?;
 
-        Ok(MeResp {
+        Ok(UserProfile {
             id: usr.id,
             email: usr.email,
             display_name: usr.display_name,
@@ -35,7 +56,7 @@ impl UserService {
         })
     }
 
-    pub async fn get_user_by_email(&self, email: &str) -> Result<MeResp> {
+    pub async fn get_user_by_email(&self, email: &str) -> Result<UserProfile> {
         if !EmailAddress::is_valid(email) {
             return Err(anyhow::anyhow!("invalid email"));
         }
@@ -46,7 +67,7 @@ impl UserService {
             .await?
             .ok_or_else(|| anyhow!("User not found"))?;
 
-        Ok(MeResp {
+        Ok(UserProfile {
             id: usr.id,
             email: usr.email,
             display_name: usr.display_name,
@@ -59,4 +80,111 @@ impl UserService {
     pub async fn confirm_email(&self, id: Uuid) -> Result<()> {
         self.repo.confirm_email(id).await
     }
+
+    /// Deletes the account and all its data, once `password` is confirmed
+    /// by `auth_svc`.
+    ///
+    /// A wrong password fails with [`ApiError::Forbidden`]; other errors are
+    /// passed on as they are.
+    pub async fn delete_account(
+        &self,
+        user_id: Uuid,
+        password: &SecretString,
+        auth_svc: &AuthService,
+    ) -> Result<()> {
+        match auth_svc.verify_password(user_id, password).await {
+            Ok(()) => {}
+            Err(e) if e.to_string().contains("invalid password") => {
+                return Err(ApiError::Forbidden("invalid password".into()).into());
+            }
+            Err(e) => return Err(e),
+        }
+        self.repo.delete_user(user_id).await?;
+        // The account may have owned a vanity domain
+        self.vanity_owners.invalidate_all();
+        Ok(())
+    }
+
+    /// Sets the display name of user `user_id`.
+    ///
+    /// The name is trimmed and NFC-normalized, and must be 1 to
+    /// `MAX_USER_NAME_LENGTH` characters without control or invisible
+    /// characters.
+    pub async fn update_display_name(&self, user_id: Uuid, new_name: &str) -> Result<()> {
+        let name = normalize_text(new_name.trim(), "display name")?;
+        let length = name.chars().count();
+        anyhow::ensure!(length > 0, "display name must not be empty");
+        anyhow::ensure!(
+            length <= MAX_USER_NAME_LENGTH,
+            "display name must be at most {} characters",
+            MAX_USER_NAME_LENGTH
+        );
+        self.repo.update_display_name(user_id, &name).await
+    }
+
+    /// The vanity domain of user `id`, if they set one.
+    pub async fn vanity_domain(&self, id: Uuid) -> Result<Option<String>> {
+        let usr = self
+            .repo
+            .find_user_by_id(id)
+            .await?
+            .ok_or_else(|| anyhow!("User not found"))?;
+        Ok(usr.vanity_domain)
+    }
+
+    /// The user who claimed `domain` as their vanity domain, if anyone did.
+    ///
+    /// Every redirect on a host other than the shortener's own asks this, so
+    /// answers, including "no one", are cached for up to a minute.
+    pub async fn vanity_domain_owner(&self, domain: &str) -> Result<Option<Uuid>> {
+        if let Some(owner) = self.vanity_owners.get(domain) {
+            return Ok(owner);
+        }
+        let owner = self.find_vanity_domain_owner(domain).await?;
+        self.vanity_owners.insert(domain.to_string(), owner);
+        Ok(owner)
+    }
+
+    async fn find_vanity_domain_owner(&self, domain: &str) -> Result<Option<Uuid>> {
+        Ok(self
+            .repo
+            .find_user_by_vanity_domain(domain)
+            .await?
+            .map(|u| u.id))
+    }
+
+    /// Sets `domain`, already normalized by [`normalize_vanity_domain`], as
+    /// the vanity domain of user `id`.
+    ///
+    /// Returns `false`, changing nothing, when another user claimed it first.
+    pub async fn set_vanity_domain(&self, id: Uuid, domain: &str) -> Result<bool> {
+        match self.find_vanity_domain_owner(domain).await? {
+            Some(owner) if owner != id => Ok(false),
+            _ => {
+                self.repo.set_vanity_domain(id, domain).await?;
+                // Drops the new domain and the one the user gave up
+                self.vanity_owners.invalidate_all();
+                Ok(true)
+            }
+        }
+    }
+}
+
+/// Lowercases `raw` if it is a hostname that can serve as a vanity domain:
+/// at least two dot-separated labels of letters, digits and inner hyphens,
+/// without a port, and not an IP address.
+pub fn normalize_vanity_domain(raw: &str) -> Option<String> {
+    let domain = raw.trim().trim_end_matches('.').to_ascii_lowercase();
+    let labels: Vec<&str> = domain.split('.').collect();
+    let valid_label = |label: &&str| {
+        (1..=63).contains(&label.len())
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    };
+    let numeric_tld = labels
+        .last()
+        .is_some_and(|tld| tld.chars().all(|c| c.is_ascii_digit()));
+    (domain.len() <= 253 && labels.len() >= 2 && labels.iter().all(valid_label) && !numeric_tld)
+        .then_some(domain)
 }
@@ -0,0 +1,69 @@
+use super::{GeneratorError, ShortCodeGenerator};
+
+/// Bitcoin base58 alphabet: base62 without `0`, `O`, `I` and `l`, which are
+/// easily confused in many fonts.
+pub const BASE58_ALPHABET: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Random short codes drawn from [`BASE58_ALPHABET`].
+///
+/// With `length = 7` the engine covers 58^7 ≈ 2.2 × 10¹² codes, so codes stay
+/// easy to read out loud or retype without giving up much key space.
+pub struct Base58Engine {
+    len: usize,
+    alphabet: Vec<char>,
+}
+
+impl Base58Engine {
+    pub fn new(len: usize) -> Self {
+        Base58Engine {
+            len,
+            alphabet: BASE58_ALPHABET.chars().collect(),
+        }
+    }
+}
+
+impl ShortCodeGenerator for Base58Engine {
+    fn generate(&self) -> Result<String, GeneratorError> {
+        let len: usize = self.len;
+        Ok(nanoid::nanoid!(len, &self.alphabet))
+    }
+
+    fn name(&self) -> &'static str {
+        "base58"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_only_use_base58_characters() {
+        let engine = Base58Engine::new(7);
+        for _ in 0..1_000 {
+            let code = engine.generate().unwrap();
+            assert_eq!(code.chars().count(), 7);
+            assert!(
+                code.chars().all(|c| BASE58_ALPHABET.contains(c)),
+                "unexpected character in {code}"
+            );
+            assert!(!code.contains(['0', 'O', 'I', 'l']));
+        }
+    }
+
+    #[test]
+    fn lengths_one_through_ten_are_accepted() {
+        for len in 1..=10 {
+            let code = Base58Engine::new(len).generate().unwrap();
+            assert_eq!(code.chars().count(), len);
+        }
+    }
+
+    #[test]
+    fn alphabet_has_58_distinct_characters() {
+        let mut chars: Vec<char> = BASE58_ALPHABET.chars().collect();
+        chars.sort_unstable();
+        chars.dedup();
+        assert_eq!(chars.len(), 58);
+    }
+}
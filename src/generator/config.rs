@@ -1,40 +1,121 @@
-use serde::Deserialize;
+use crate::generator::ulid::ULID_BASE62_LEN;
+use crate::generator::{DEFAULT_ALPHABET, PronounceableEngine};
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
+/// Smallest number of distinct codes an engine must be able to produce.
+pub const MIN_CODE_SPACE: u128 = 1_000_000_000;
+
+/// Longest `alias_prefix` accepted, in characters.
+pub const MAX_ALIAS_PREFIX_LENGTH: usize = 10;
+
+/// Number of distinct codes of `length` characters over `radix` symbols,
+/// saturating at `u128::MAX`.
+pub fn code_space(radix: u128, length: usize) -> u128 {
+    (0..length).fold(1u128, |acc, _| acc.saturating_mul(radix))
+}
+
 /// Top-level configuration for the short code generator.
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct ShortenerConfig {
     pub length: usize,
     pub alphabet: Option<String>,
     pub engine: EngineConfig,
     pub bit_layout: Option<BitLayoutConfig>,
+    /// Delay before the first retry after a short code collision, in
+    /// milliseconds; it doubles on every further retry
+    #[serde(default = "default_retry_base_delay_ms")]
+    pub retry_base_delay_ms: u64,
+    /// Prepended to every custom alias before it is stored, e.g. `"t1"` for
+    /// one tenant of a shared database
+    #[serde(default)]
+    pub alias_prefix: Option<String>,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+fn default_retry_base_delay_ms() -> u64 {
+    10
+}
+
+/// Seven-character nanoid codes over [`DEFAULT_ALPHABET`].
+impl Default for ShortenerConfig {
+    fn default() -> Self {
+        Self {
+            length: 7,
+            alphabet: None,
+            engine: EngineConfig::default(),
+            bit_layout: None,
+            retry_base_delay_ms: default_retry_base_delay_ms(),
+            alias_prefix: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct EngineConfig {
     pub kind: EngineKind,
     pub nanoid: Option<NanoIdConfig>,
     pub sequence: Option<SequenceConfig>,
+    pub base58: Option<Base58Config>,
+    pub pronounceable: Option<PronounceableConfig>,
+    pub ulid: Option<UlidConfig>,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 #[serde(rename_all = "lowercase")]
 pub enum EngineKind {
     Sequence,
+    #[default]
     Nanoid,
+    Base58,
+    Pronounceable,
+    Ulid,
+}
+
+impl EngineKind {
+    /// Returns the configuration spelling of the engine kind.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EngineKind::Sequence => "sequence",
+            EngineKind::Nanoid => "nanoid",
+            EngineKind::Base58 => "base58",
+            EngineKind::Pronounceable => "pronounceable",
+            EngineKind::Ulid => "ulid",
+        }
+    }
 }
 
-#[derive(Clone, Debug, Deserialize, Default)]
+#[derive(Clone, Debug, Deserialize, Serialize, Default)]
 pub struct NanoIdConfig {}
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct SequenceConfig {
     pub block_size: u64,
     pub persist_interval: u64,
     pub state_path: Option<PathBuf>,
 }
 
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Base58Config {
+    /// Code length; 58^length must be at least [`MIN_CODE_SPACE`].
+    pub length: usize,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PronounceableConfig {
+    /// Code length; 10 or more is recommended for production.
+    pub length: usize,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+pub struct UlidConfig {
+    /// Code length; defaults to the top-level `length`. Only 22 keeps the
+    /// whole ULID, so that codes sort by creation time; shorter codes keep
+    /// its random low digits and are not ordered. Postgres stores codes of at
+    /// most 12 characters.
+    pub truncate_to: Option<usize>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct BitLayoutConfig {
     pub enabled: bool,
     pub region_bits: u8,
@@ -61,8 +142,50 @@ impl ShortenerConfig {
             }
         }
 
+        self.validate_alias_prefix()?;
+
         match self.engine.kind {
             EngineKind::Nanoid => {}
+            EngineKind::Base58 => {
+                let len = self.base58_length();
+                if code_space(58, len) < MIN_CODE_SPACE {
+                    return Err(format!(
+                        "engine.base58.length {} yields fewer than {} codes; use at least 6",
+                        len, MIN_CODE_SPACE
+                    ));
+                }
+            }
+            EngineKind::Ulid => {
+                let len = self.ulid_length();
+                if len > ULID_BASE62_LEN {
+                    return Err(format!(
+                        "engine.ulid.truncate_to must be <= {}",
+                        ULID_BASE62_LEN
+                    ));
+                }
+                if code_space(62, len) < MIN_CODE_SPACE {
+                    return Err(format!(
+                        "engine.ulid.truncate_to {} yields fewer than {} codes",
+                        len, MIN_CODE_SPACE
+                    ));
+                }
+                if self
+                    .alphabet
+                    .as_ref()
+                    .is_some_and(|a| a.chars().count() != 62)
+                {
+                    return Err("engine kind ulid requires a 62 character alphabet".into());
+                }
+            }
+            EngineKind::Pronounceable => {
+                let len = self.pronounceable_length();
+                if PronounceableEngine::code_space(len) < MIN_CODE_SPACE {
+                    return Err(format!(
+                        "engine.pronounceable.length {} yields fewer than {} codes; use at least 10",
+                        len, MIN_CODE_SPACE
+                    ));
+                }
+            }
             EngineKind::Sequence => {
                 let seq = self
                     .engine
@@ -86,4 +209,144 @@ impl ShortenerConfig {
 
         Ok(())
     }
+
+    /// Checks that `alias_prefix` is at most [`MAX_ALIAS_PREFIX_LENGTH`]
+    /// characters, all of them from the alphabet, so prefixed aliases can be
+    /// followed like any other code.
+    pub fn validate_alias_prefix(&self) -> Result<(), String> {
+        let Some(prefix) = &self.alias_prefix else {
+            return Ok(());
+        };
+        if prefix.chars().count() > MAX_ALIAS_PREFIX_LENGTH {
+            return Err(format!(
+                "shortener.alias_prefix must be at most {} characters",
+                MAX_ALIAS_PREFIX_LENGTH
+            ));
+        }
+        let allowed = |c: char| match &self.alphabet {
+            Some(alpha) => alpha.contains(c),
+            None => DEFAULT_ALPHABET.contains(&c),
+        };
+        if !prefix.chars().all(allowed) {
+            return Err("shortener.alias_prefix must only contain alphabet characters".into());
+        }
+        Ok(())
+    }
+
+    /// Length used by the base58 engine: `engine.base58.length`, falling back
+    /// to the top-level `length`.
+    pub fn base58_length(&self) -> usize {
+        self.engine
+            .base58
+            .as_ref()
+            .map(|b| b.length)
+            .unwrap_or(self.length)
+    }
+
+    /// Length used by the pronounceable engine: `engine.pronounceable.length`,
+    /// falling back to the top-level `length`.
+    pub fn pronounceable_length(&self) -> usize {
+        self.engine
+            .pronounceable
+            .as_ref()
+            .map(|p| p.length)
+            .unwrap_or(self.length)
+    }
+
+    /// Length used by the ULID engine: `engine.ulid.truncate_to`, falling back
+    /// to the top-level `length`.
+    pub fn ulid_length(&self) -> usize {
+        self.engine
+            .ulid
+            .as_ref()
+            .and_then(|u| u.truncate_to)
+            .unwrap_or(self.length)
+    }
+
+    /// Length of the codes the configured engine generates; `None` for the
+    /// sequence engine, whose codes grow with the counter.
+    pub fn code_length(&self) -> Option<usize> {
+        match self.engine.kind {
+            EngineKind::Nanoid => Some(self.length),
+            EngineKind::Base58 => Some(self.base58_length()),
+            EngineKind::Ulid => Some(self.ulid_length()),
+            EngineKind::Pronounceable => Some(self.pronounceable_length()),
+            EngineKind::Sequence => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn base58_config(length: usize) -> ShortenerConfig {
+        ShortenerConfig {
+            length: 7,
+            alphabet: None,
+            engine: EngineConfig {
+                kind: EngineKind::Base58,
+                nanoid: None,
+                sequence: None,
+                base58: Some(Base58Config { length }),
+                pronounceable: None,
+                ulid: None,
+            },
+            bit_layout: None,
+            retry_base_delay_ms: 10,
+            alias_prefix: None,
+        }
+    }
+
+    #[test]
+    fn base58_requires_at_least_a_billion_codes() {
+        assert!(base58_config(5).validate().is_err());
+        assert!(base58_config(6).validate().is_ok());
+        assert!(base58_config(10).validate().is_ok());
+    }
+
+    #[test]
+    fn pronounceable_needs_a_long_enough_code() {
+        let mut cfg = base58_config(7);
+        cfg.engine.kind = EngineKind::Pronounceable;
+        cfg.engine.pronounceable = Some(PronounceableConfig { length: 6 });
+        assert!(cfg.validate().is_err());
+        cfg.engine.pronounceable = Some(PronounceableConfig { length: 10 });
+        assert!(cfg.validate().is_ok());
+    }
+
+    #[test]
+    fn ulid_length_defaults_to_global_length_and_is_bounded() {
+        let mut cfg = base58_config(7);
+        cfg.engine.kind = EngineKind::Ulid;
+        cfg.engine.ulid = Some(UlidConfig::default());
+        assert_eq!(cfg.ulid_length(), 7);
+        assert!(cfg.validate().is_ok());
+
+        cfg.engine.ulid = Some(UlidConfig {
+            truncate_to: Some(23),
+        });
+        assert!(cfg.validate().is_err());
+    }
+
+    #[test]
+    fn alias_prefix_must_be_short_and_use_the_alphabet() {
+        let mut cfg = base58_config(7);
+        cfg.alias_prefix = Some("t1".to_string());
+        assert!(cfg.validate().is_ok());
+
+        cfg.alias_prefix = Some("t1-".to_string());
+        assert!(cfg.validate().is_err());
+        cfg.alphabet = Some(format!("{}-", DEFAULT_ALPHABET.iter().collect::<String>()));
+        assert!(cfg.validate().is_ok());
+
+        cfg.alias_prefix = Some("tenant12345".to_string());
+        assert!(cfg.validate().is_err());
+    }
+
+    #[test]
+    fn code_space_saturates_instead_of_overflowing() {
+        assert_eq!(code_space(58, 7), 2_207_984_167_552);
+        assert_eq!(code_space(62, 100), u128::MAX);
+    }
 }
@@ -0,0 +1,192 @@
+//! Fixed-length positional encoding of integers over an alphabet, shared by
+//! the counter-based engines. The first alphabet character is the zero
+//! digit, so shorter values are left-padded with it.
+
+/// Fixed-length base62 encoding (left-pad with `alphabet[0]`).
+///
+/// - `v`: value to encode (u128)
+/// - `len`: desired output length
+/// - `alphabet`: mapping table (must have length 62)
+///
+/// Returns `None` when `alphabet.len() != 62` or `v` cannot fit in `len` digits.
+pub fn encode_base62_fixed(v: u128, len: usize, alphabet: &[char]) -> Option<String> {
+    if alphabet.len() != 62 {
+        return None;
+    }
+    encode_base_n_fixed(v, len, alphabet)
+}
+
+/// Fixed-length encoding in base `alphabet.len()` (left-pad with `alphabet[0]`).
+///
+/// Returns `None` when the alphabet has fewer than 2 characters or `v` cannot
+/// fit in `len` digits, i.e. `v >= alphabet.len() ^ len`.
+///
+/// # Examples
+///
+/// ```rust
+/// use url_shortener_ztm_lib::generator::{decode_base_n, encode_base_n_fixed};
+///
+/// let alphabet = ['0', '1'];
+/// assert_eq!(encode_base_n_fixed(5, 4, &alphabet).as_deref(), Some("0101"));
+/// assert_eq!(decode_base_n("0101", &alphabet), Some(5));
+/// assert_eq!(encode_base_n_fixed(16, 4, &alphabet), None);
+/// ```
+pub fn encode_base_n_fixed(mut v: u128, len: usize, alphabet: &[char]) -> Option<String> {
+    if alphabet.len() < 2 {
+        return None;
+    }
+    let base = alphabet.len() as u128;
+    let mut buf = vec![alphabet[0]; len];
+    let mut i = len;
+    while i > 0 {
+        i -= 1;
+        let rem = (v % base) as usize;
+        v /= base;
+        buf[i] = alphabet[rem];
+    }
+    // If v still > 0, `len` is too small to hold the value.
+    if v != 0 {
+        return None;
+    }
+    Some(buf.into_iter().collect())
+}
+
+/// Inverse of [`encode_base_n_fixed`]: the value `s` spells in base
+/// `alphabet.len()`.
+///
+/// Returns `None` when the alphabet has fewer than 2 characters, `s` holds a
+/// character outside it, or the value does not fit in a `u128`. The alphabet
+/// must not repeat characters, or decoding is ambiguous.
+pub fn decode_base_n(s: &str, alphabet: &[char]) -> Option<u128> {
+    if alphabet.len() < 2 {
+        return None;
+    }
+    let base = alphabet.len() as u128;
+    s.chars().try_fold(0u128, |value, c| {
+        let digit = alphabet.iter().position(|&a| a == c)? as u128;
+        value.checked_mul(base)?.checked_add(digit)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::DEFAULT_ALPHABET;
+    use crate::generator::config::code_space;
+    use proptest::prelude::*;
+    use std::collections::HashSet;
+
+    // Test alphabet: '0'-'9', 'A'-'Z', 'a'-'z' (62 chars)
+    fn test_alphabet_vec() -> Vec<char> {
+        "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz"
+            .chars()
+            .collect()
+    }
+
+    #[test]
+    fn test_encode_base62_fixed_basic() {
+        let alpha = test_alphabet_vec();
+
+        // v = 0 -> all digits are alphabet[0] ('0'), length 4 => "0000"
+        assert_eq!(
+            encode_base62_fixed(0u128, 4, &alpha).unwrap(),
+            "0000".to_string()
+        );
+
+        // v = 1 -> last digit becomes '1'
+        assert_eq!(
+            encode_base62_fixed(1u128, 4, &alpha).unwrap(),
+            "0001".to_string()
+        );
+
+        // Value that fits exactly: largest value for len=2 is 62^2 - 1 = 3843
+        let max_len2 = 62u128.pow(2) - 1;
+        assert!(encode_base62_fixed(max_len2, 2, &alpha).is_some());
+
+        // Overflow: 62^2 cannot fit in len=2
+        let overflow = 62u128.pow(2);
+        assert!(encode_base62_fixed(overflow, 2, &alpha).is_none());
+    }
+
+    #[test]
+    fn test_encode_exhausted_space_case() {
+        let alpha = test_alphabet_vec();
+
+        // For len = 3, capacity = 62^3 = 238328, so value == 238328 should be out of range
+        let cap = 62u128.pow(3);
+        assert!(encode_base62_fixed(cap, 3, &alpha).is_none());
+    }
+
+    #[test]
+    fn decode_rejects_foreign_characters_and_overflow() {
+        let alpha = test_alphabet_vec();
+
+        assert_eq!(decode_base_n("ab-c", &alpha), None);
+        assert_eq!(decode_base_n(&"z".repeat(22), &alpha), None);
+        assert_eq!(decode_base_n("", &alpha), Some(0));
+    }
+
+    // An alphabet of 2 to 62 distinct characters and a length whose code
+    // space still fits in a u128
+    fn alphabet_and_len() -> impl Strategy<Value = (Vec<char>, usize)> {
+        proptest::sample::subsequence(DEFAULT_ALPHABET.to_vec(), 2..=62)
+            .prop_flat_map(|alphabet| (Just(alphabet), 0usize..=21))
+    }
+
+    proptest! {
+        #[test]
+        fn encode_fixed_is_distinct_over_the_whole_space(
+            alphabet in proptest::sample::subsequence(DEFAULT_ALPHABET.to_vec(), 2..=8),
+            len in 1usize..=4,
+        ) {
+            let space = code_space(alphabet.len() as u128, len);
+            let mut seen = HashSet::new();
+            for n in 0..space {
+                let code = encode_base_n_fixed(n, len, &alphabet).unwrap();
+                prop_assert_eq!(code.chars().count(), len);
+                prop_assert!(code.chars().all(|c| alphabet.contains(&c)));
+                prop_assert!(seen.insert(code), "{} encoded twice", n);
+            }
+            prop_assert!(encode_base_n_fixed(space, len, &alphabet).is_none());
+        }
+
+        #[test]
+        fn decode_inverts_encode((alphabet, len) in alphabet_and_len(), v in any::<u128>()) {
+            let space = code_space(alphabet.len() as u128, len);
+            let v = if space == u128::MAX { v } else { v % space };
+
+            let code = encode_base_n_fixed(v, len, &alphabet).unwrap();
+
+            prop_assert_eq!(decode_base_n(&code, &alphabet), Some(v));
+        }
+
+        #[test]
+        fn encode_inverts_decode(
+            (alphabet, codes) in alphabet_and_len().prop_flat_map(|(alphabet, len)| {
+                let chars = proptest::sample::select(alphabet.clone());
+                (Just(alphabet), proptest::collection::vec(chars, len))
+            }),
+        ) {
+            let code: String = codes.into_iter().collect();
+
+            let v = decode_base_n(&code, &alphabet).unwrap();
+
+            prop_assert_eq!(
+                encode_base_n_fixed(v, code.chars().count(), &alphabet),
+                Some(code)
+            );
+        }
+
+        #[test]
+        fn encode_fails_exactly_past_the_code_space(
+            (alphabet, len) in alphabet_and_len(),
+            v in prop_oneof![0u128..100_000, any::<u128>()],
+        ) {
+            let fits = (alphabet.len() as u128)
+                .checked_pow(len as u32)
+                .is_none_or(|space| v < space);
+
+            prop_assert_eq!(encode_base_n_fixed(v, len, &alphabet).is_some(), fits);
+        }
+    }
+}
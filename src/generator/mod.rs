@@ -1,4 +1,6 @@
+use serde::Serialize;
 use std::sync::Arc;
+use utoipa::ToSchema;
 
 /// Default base62 alphabet: 0-9, A-Z, a-z (62 characters).
 pub const DEFAULT_ALPHABET: &[char] = &[
@@ -8,7 +10,24 @@ pub const DEFAULT_ALPHABET: &[char] = &[
     'v', 'w', 'x', 'y', 'z',
 ];
 
+/// Errors raised while producing short codes.
+///
+/// This enum is `#[non_exhaustive]`: new variants may be added in minor
+/// releases, so a `match` outside this crate needs a wildcard arm.
+///
+/// ```rust,compile_fail
+/// use url_shortener_ztm_lib::generator::GeneratorError;
+///
+/// fn describe(error: &GeneratorError) -> &'static str {
+///     match error {
+///         GeneratorError::ExhaustedSpace => "exhausted",
+///         GeneratorError::Io(_) => "io",
+///         GeneratorError::Internal(_) => "internal",
+///     }
+/// }
+/// ```
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum GeneratorError {
     /// The numeric value exceeded the available encoding space (increase `length`).
     ExhaustedSpace,
@@ -18,6 +37,19 @@ pub enum GeneratorError {
     Internal(&'static str),
 }
 
+/// Names every variant, in step with the `compile_fail` example on
+/// [`GeneratorError`]: a new variant breaks this match, which is the cue to
+/// add it to that example too.
+#[allow(dead_code)]
+#[deny(unreachable_patterns)]
+fn generator_error_variant(error: &GeneratorError) -> &'static str {
+    match error {
+        GeneratorError::ExhaustedSpace => "exhausted",
+        GeneratorError::Io(_) => "io",
+        GeneratorError::Internal(_) => "internal",
+    }
+}
+
 impl From<std::io::Error> for GeneratorError {
     fn from(e: std::io::Error) -> Self {
         GeneratorError::Io(e)
@@ -30,39 +62,107 @@ pub trait ShortCodeGenerator: Send + Sync {
     /// Generate a new short code.
     fn generate(&self) -> Result<String, GeneratorError>;
 
+    /// Generate `n` short codes at once, e.g. to pre-fill retry candidates.
+    ///
+    /// The default calls [`generate`](Self::generate) in a loop; engines with
+    /// internal locking can override it to take the lock only once.
+    fn generate_n(&self, n: usize) -> Result<Vec<String>, GeneratorError> {
+        (0..n).map(|_| self.generate()).collect()
+    }
+
     /// Engine name, used for logging/identification.
     fn name(&self) -> &'static str;
 }
 
+/// Engine-agnostic view of a generator's counters.
+///
+/// Fields an engine does not track are left as `None` and omitted from JSON.
+#[derive(Clone, Debug, Serialize, ToSchema)]
+pub struct GeneratorMetricsSnapshot {
+    #[schema(value_type = String)]
+    pub engine: &'static str,
+    pub codes_issued: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capacity_used_pct: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_refills: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub persist_writes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_value: Option<u64>,
+}
+
+/// Implemented by engines that expose usage counters, e.g. for the health endpoint.
+pub trait GeneratorMetrics: Send + Sync {
+    fn snapshot(&self) -> GeneratorMetricsSnapshot;
+
+    /// Codes the engine can still issue, for engines with a finite counter.
+    fn remaining_capacity(&self) -> Option<u64> {
+        None
+    }
+}
+
+mod base58;
 pub mod config;
+mod encoding;
 mod nanoid;
+mod pronounceable;
 mod sequence;
+mod ulid;
 
+pub use base58::{BASE58_ALPHABET, Base58Engine};
+pub use encoding::{decode_base_n, encode_base_n_fixed, encode_base62_fixed};
 pub use nanoid::NanoIdEngine;
-pub use sequence::SequenceEngine;
+pub use pronounceable::PronounceableEngine;
+pub use sequence::{SequenceEngine, SequenceMetrics};
+pub use ulid::{ULID_BASE62_LEN, UlidEngine};
 
 use crate::generator::config::{EngineKind, ShortenerConfig};
 
 /// Factory: builds the appropriate generator engine based on common
 /// configuration fields and the engine-specific settings.
 pub fn build_generator(cfg: &ShortenerConfig) -> Arc<dyn ShortCodeGenerator> {
+    build_generator_with_metrics(cfg).0
+}
+
+/// Like [`build_generator`], but also returns the engine's metrics handle when
+/// the engine tracks any.
+pub fn build_generator_with_metrics(
+    cfg: &ShortenerConfig,
+) -> (
+    Arc<dyn ShortCodeGenerator>,
+    Option<Arc<dyn GeneratorMetrics>>,
+) {
     cfg.validate().expect("invalid shortener config");
 
     match cfg.engine.kind {
-        EngineKind::Nanoid => Arc::new(NanoIdEngine::new(cfg.length, cfg.alphabet.clone())),
+        EngineKind::Nanoid => (
+            Arc::new(NanoIdEngine::new(cfg.length, cfg.alphabet.clone())),
+            None,
+        ),
+        EngineKind::Base58 => (Arc::new(Base58Engine::new(cfg.base58_length())), None),
+        EngineKind::Ulid => (
+            Arc::new(UlidEngine::new(cfg.ulid_length(), cfg.alphabet.clone())),
+            None,
+        ),
+        EngineKind::Pronounceable => (
+            Arc::new(PronounceableEngine::new(cfg.pronounceable_length(), None)),
+            None,
+        ),
         EngineKind::Sequence => {
             let seq: &config::SequenceConfig = cfg
                 .engine
                 .sequence
                 .as_ref()
                 .expect("sequence config must exist when kind=Sequence");
-            Arc::new(SequenceEngine::new(
+            let engine = Arc::new(SequenceEngine::new(
                 cfg.length,
                 cfg.alphabet.clone(),
                 seq.block_size.max(1),
                 seq.persist_interval.max(1),
                 seq.state_path.clone(),
-            ))
+            ));
+            (engine.clone(), Some(engine))
         }
     }
 }
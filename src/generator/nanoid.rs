@@ -27,3 +27,51 @@ impl ShortCodeGenerator for NanoIdEngine {
         "nanoid"
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::config::code_space;
+    use proptest::prelude::*;
+    use std::collections::HashSet;
+
+    // Distinct ASCII characters, the only ones `shortener.alphabet` accepts
+    fn alphabet(sizes: std::ops::Range<usize>) -> impl Strategy<Value = Vec<char>> {
+        let ascii: Vec<char> = (0u8..128).map(char::from).collect();
+        proptest::sample::subsequence(ascii, sizes)
+    }
+
+    #[test]
+    fn default_alphabet_is_used_when_none_is_given() {
+        let code = NanoIdEngine::new(12, None).generate().unwrap();
+        assert_eq!(code.chars().count(), 12);
+        assert!(code.chars().all(|c| DEFAULT_ALPHABET.contains(&c)));
+    }
+
+    proptest! {
+        #[test]
+        fn codes_have_the_length_and_alphabet_asked_for(
+            len in 1usize..20,
+            alphabet in alphabet(2..128),
+        ) {
+            let engine = NanoIdEngine::new(len, Some(alphabet.iter().collect()));
+            for code in engine.generate_n(20).unwrap() {
+                prop_assert_eq!(code.chars().count(), len);
+                prop_assert!(code.chars().all(|c| alphabet.contains(&c)), "{:?}", code);
+            }
+        }
+
+        // Only spaces of at least 32^10 codes, where a collision among 1000
+        // codes has a chance of under one in two billion
+        #[test]
+        fn thousand_codes_do_not_collide(
+            len in 10usize..20,
+            alphabet in alphabet(32..128),
+        ) {
+            prop_assert!(code_space(alphabet.len() as u128, len) >= 32u128.pow(10));
+            let engine = NanoIdEngine::new(len, Some(alphabet.iter().collect()));
+            let codes = engine.generate_n(1000).unwrap();
+            prop_assert_eq!(codes.iter().collect::<HashSet<_>>().len(), 1000);
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use std::{
     fs,
     io::{Read, Write},
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{
         Mutex,
         atomic::{AtomicU64, Ordering},
@@ -9,34 +9,10 @@ use std::{
 };
 
 use crate::generator::DEFAULT_ALPHABET;
+use crate::generator::config::code_space;
+use crate::generator::encoding::encode_base_n_fixed;
 
-use super::{GeneratorError, ShortCodeGenerator};
-
-/// Fixed-length base62 encoding (left-pad with '0').
-///
-/// - `v`: value to encode (u128)
-/// - `len`: desired output length
-/// - `alphabet`: mapping table (must have length 62)
-///
-/// Returns `None` when `alphabet.len() != 62` or `v` cannot fit in `len` digits.
-fn encode_base62_fixed(mut v: u128, len: usize, alphabet: &[char]) -> Option<String> {
-    if alphabet.len() != 62 {
-        return None;
-    }
-    let mut buf = vec![alphabet[0]; len];
-    let mut i = len;
-    while i > 0 {
-        i -= 1;
-        let rem = (v % 62) as usize;
-        v /= 62;
-        buf[i] = alphabet[rem];
-    }
-    // If v still > 0, `len` is too small to hold the value.
-    if v != 0 {
-        return None;
-    }
-    Some(buf.into_iter().collect())
-}
+use super::{GeneratorError, GeneratorMetrics, GeneratorMetricsSnapshot, ShortCodeGenerator};
 
 /// Simple local block allocator:
 /// - `next_global`: global cursor; refill obtains `block_size` ids starting from it.
@@ -52,6 +28,25 @@ pub struct SequenceEngine {
 
     next_global: AtomicU64,    // next refill start
     inner: Mutex<BlockWindow>, // current window
+
+    codes_issued: AtomicU64,
+    block_refills: AtomicU64,
+    persist_writes: AtomicU64,
+}
+
+/// Point-in-time counters for a [`SequenceEngine`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct SequenceMetrics {
+    /// Codes handed out since the engine was created
+    pub codes_issued: u64,
+    /// Blocks reserved from the global cursor
+    pub block_refills: u64,
+    /// Times the cursor was written to `state_path`
+    pub persist_writes: u64,
+    /// Current global cursor (start of the next block)
+    pub next_value: u64,
+    /// Share of the `alphabet.len() ^ len` code space already reserved
+    pub capacity_used_pct: f64,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -89,20 +84,54 @@ impl SequenceEngine {
                 end: 0,
                 issued_since_persist: 0,
             }),
+            codes_issued: AtomicU64::new(0),
+            block_refills: AtomicU64::new(0),
+            persist_writes: AtomicU64::new(0),
         }
     }
 
+    /// Returns the engine's counters and how much of its code space is used.
+    pub fn metrics(&self) -> SequenceMetrics {
+        let next_value = self.next_global.load(Ordering::Relaxed);
+        let capacity = code_space(self.alphabet.len() as u128, self.len) as f64;
+        SequenceMetrics {
+            codes_issued: self.codes_issued.load(Ordering::Relaxed),
+            block_refills: self.block_refills.load(Ordering::Relaxed),
+            persist_writes: self.persist_writes.load(Ordering::Relaxed),
+            next_value,
+            capacity_used_pct: (next_value as f64 / capacity * 100.0).min(100.0),
+        }
+    }
+
+    /// Values left before the global cursor reaches `u64::MAX`.
+    pub fn remaining_capacity(&self) -> u64 {
+        u64::MAX - self.next_global.load(Ordering::SeqCst)
+    }
+
     /// Allocate a new block [start, end).
     ///
     /// Caller must hold the `inner` lock. This atomically increments `next_global`
     /// by `block_size` and sets the window to [start, start + block_size).
+    ///
+    /// The cursor is only advanced when the whole block fits below `u64::MAX`;
+    /// letting it wrap would hand out codes that were already issued.
     fn refill_locked(&self, w: &mut BlockWindow) -> Result<(), GeneratorError> {
+        let block_size = self.block_size;
         let start = self
             .next_global
-            .fetch_add(self.block_size, Ordering::Relaxed);
-        let end = start
-            .checked_add(self.block_size)
-            .ok_or(GeneratorError::Internal("counter overflow"))?;
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |start| {
+                start.checked_add(block_size)
+            })
+            .map_err(|start| {
+                tracing::error!(
+                    next_value = start,
+                    block_size,
+                    "sequence counter exhausted; no short codes can be issued"
+                );
+                GeneratorError::ExhaustedSpace
+            })?;
+        let end = start + block_size;
+        self.block_refills.fetch_add(1, Ordering::Relaxed);
         w.current = start; // \u2705 \u5fc5\u987b\u8bbe\u7f6e\u5f53\u524d\u7a97\u53e3\u8d77\u70b9
         w.end = end; // \u7ed3\u675f\u8fb9\u754c
         Ok(())
@@ -122,6 +151,7 @@ impl SequenceEngine {
             // Persist the "future next_global" (current global cursor).
             let next = self.next_global.load(Ordering::SeqCst);
             store_state(self.state_path.as_ref().unwrap(), next)?;
+            self.persist_writes.fetch_add(1, Ordering::Relaxed);
         }
         Ok(())
     }
@@ -140,18 +170,62 @@ impl ShortCodeGenerator for SequenceEngine {
             self.maybe_persist(&mut win)?;
             v
         };
+        self.codes_issued.fetch_add(1, Ordering::Relaxed);
 
-        // Encode to fixed-length base62. If not enough space, return ExhaustedSpace.
-        let s = encode_base62_fixed(n as u128, self.len, &self.alphabet)
+        // Encode to fixed-length base-N. If not enough space, return ExhaustedSpace.
+        let s = encode_base_n_fixed(n as u128, self.len, &self.alphabet)
             .ok_or(GeneratorError::ExhaustedSpace)?;
         Ok(s)
     }
 
+    fn generate_n(&self, n: usize) -> Result<Vec<String>, GeneratorError> {
+        // Reserve all ids under a single lock acquisition, refilling as needed.
+        let ids = {
+            let mut win = self.inner.lock().expect("lock poisoned");
+            let mut ids = Vec::with_capacity(n);
+            for _ in 0..n {
+                if win.current >= win.end {
+                    self.refill_locked(&mut win)?;
+                }
+                ids.push(win.current);
+                win.current += 1;
+                self.maybe_persist(&mut win)?;
+            }
+            ids
+        };
+        self.codes_issued.fetch_add(n as u64, Ordering::Relaxed);
+
+        ids.into_iter()
+            .map(|v| {
+                encode_base_n_fixed(v as u128, self.len, &self.alphabet)
+                    .ok_or(GeneratorError::ExhaustedSpace)
+            })
+            .collect()
+    }
+
     fn name(&self) -> &'static str {
         "sequence"
     }
 }
 
+impl GeneratorMetrics for SequenceEngine {
+    fn snapshot(&self) -> GeneratorMetricsSnapshot {
+        let m = self.metrics();
+        GeneratorMetricsSnapshot {
+            engine: self.name(),
+            codes_issued: m.codes_issued,
+            capacity_used_pct: Some(m.capacity_used_pct),
+            block_refills: Some(m.block_refills),
+            persist_writes: Some(m.persist_writes),
+            next_value: Some(m.next_value),
+        }
+    }
+
+    fn remaining_capacity(&self) -> Option<u64> {
+        Some(SequenceEngine::remaining_capacity(self))
+    }
+}
+
 /// State file stores `next_global` as little-endian u64.
 fn load_state(path: &Option<PathBuf>) -> Result<u64, std::io::Error> {
     if let Some(p) = path
@@ -165,56 +239,50 @@ fn load_state(path: &Option<PathBuf>) -> Result<u64, std::io::Error> {
     Ok(0)
 }
 
+/// Writes the state to a temp file next to `path` and renames it over `path`,
+/// so a crash mid-write leaves the previous state file intact instead of a
+/// partial one.
+///
+/// `fs::rename` replaces an existing file on Windows too, so the old state
+/// is never removed before the new one is in place.
 fn store_state(path: &PathBuf, next: u64) -> Result<(), std::io::Error> {
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)?;
     }
-    let mut f = fs::File::create(path)?;
+    let tmp = temp_state_path(path);
+    let mut f = fs::File::create(&tmp)?;
     f.write_all(&next.to_le_bytes())?;
     f.sync_all()?;
-    Ok(())
+    fs::rename(&tmp, path)
+}
+
+/// `{path}.{pid}.{n}.tmp`, where the state is written before it replaces `path`.
+///
+/// Every write gets its own name: engines sharing a state path would otherwise
+/// truncate each other's temp file and rename a half-written one into place.
+fn temp_state_path(path: &Path) -> PathBuf {
+    static NEXT_TEMP: AtomicU64 = AtomicU64::new(0);
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(format!(
+        ".{}.{}.tmp",
+        std::process::id(),
+        NEXT_TEMP.fetch_add(1, Ordering::Relaxed)
+    ));
+    PathBuf::from(tmp)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
+    use std::collections::HashSet;
     use std::time::{SystemTime, UNIX_EPOCH};
 
     // Test alphabet: '0'-'9', 'A'-'Z', 'a'-'z' (62 chars)
-    fn test_alphabet_vec() -> Vec<char> {
-        "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz"
-            .chars()
-            .collect()
-    }
     fn test_alphabet_string() -> String {
         "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz".to_string()
     }
 
-    #[test]
-    fn test_encode_base62_fixed_basic() {
-        let alpha = test_alphabet_vec();
-
-        // v = 0 -> all digits are alphabet[0] ('0'), length 4 => "0000"
-        assert_eq!(
-            encode_base62_fixed(0u128, 4, &alpha).unwrap(),
-            "0000".to_string()
-        );
-
-        // v = 1 -> last digit becomes '1'
-        assert_eq!(
-            encode_base62_fixed(1u128, 4, &alpha).unwrap(),
-            "0001".to_string()
-        );
-
-        // Value that fits exactly: largest value for len=2 is 62^2 - 1 = 3843
-        let max_len2 = 62u128.pow(2) - 1;
-        assert!(encode_base62_fixed(max_len2, 2, &alpha).is_some());
-
-        // Overflow: 62^2 cannot fit in len=2
-        let overflow = 62u128.pow(2);
-        assert!(encode_base62_fixed(overflow, 2, &alpha).is_none());
-    }
-
     #[test]
     fn test_sequence_engine_generate_and_persistence() {
         // Build a unique temp path for state file
@@ -251,11 +319,147 @@ mod tests {
     }
 
     #[test]
-    fn test_encode_exhausted_space_case() {
-        let alpha = test_alphabet_vec();
+    fn test_generate_n_matches_repeated_generate() {
+        let batch = SequenceEngine::new(6, Some(test_alphabet_string()), 3, 1, None);
+        let single = SequenceEngine::new(6, Some(test_alphabet_string()), 3, 1, None);
+
+        // n spans several block refills
+        let codes = batch.generate_n(10).unwrap();
+        let expected: Vec<String> = (0..10).map(|_| single.generate().unwrap()).collect();
+        assert_eq!(codes, expected);
+        assert_eq!(batch.generate().unwrap(), single.generate().unwrap());
+    }
+
+    #[test]
+    fn test_metrics_count_issued_codes_and_refills() {
+        let engine = SequenceEngine::new(6, Some(test_alphabet_string()), 4, 1, None);
+
+        for _ in 0..10 {
+            engine.generate().unwrap();
+        }
+        let m = engine.metrics();
+        assert_eq!(m.codes_issued, 10);
+        assert_eq!(m.block_refills, 3);
+        assert_eq!(m.persist_writes, 0);
+        assert_eq!(m.next_value, 12);
+
+        engine.generate_n(5).unwrap();
+        assert_eq!(engine.metrics().codes_issued, 15);
+
+        let expected_pct = 16.0 / 62f64.powi(6) * 100.0;
+        assert!((engine.metrics().capacity_used_pct - expected_pct).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_counter_overflow_is_exhausted_space() {
+        // 62^11 exceeds u64::MAX, so only the counter can run out
+        let engine = SequenceEngine::new(11, Some(test_alphabet_string()), 5, 1, None);
+        engine.next_global.store(u64::MAX - 2, Ordering::SeqCst);
+
+        assert!(matches!(
+            engine.generate(),
+            Err(GeneratorError::ExhaustedSpace)
+        ));
+        assert!(matches!(
+            engine.generate_n(2),
+            Err(GeneratorError::ExhaustedSpace)
+        ));
+        // The cursor did not wrap back to already issued values
+        assert_eq!(engine.remaining_capacity(), 2);
+    }
+
+    // A length of 1..=3 with an alphabet of fewer than 62 characters, small
+    // enough that the whole code space (at most 4096 codes) can be issued
+    fn small_code_space() -> impl Strategy<Value = (usize, Vec<char>)> {
+        (1usize..=3).prop_flat_map(|len| {
+            let max_chars = if len == 3 { 16 } else { 61 };
+            (
+                Just(len),
+                proptest::sample::subsequence(DEFAULT_ALPHABET.to_vec(), 2..=max_chars),
+            )
+        })
+    }
+
+    #[test]
+    fn test_sequence_engine_accepts_non_base62_alphabet() {
+        let engine = SequenceEngine::new(2, Some("ab".to_string()), 10, 1, None);
+        let codes = engine.generate_n(4).unwrap();
+        assert_eq!(codes, vec!["aa", "ab", "ba", "bb"]);
+        assert!(matches!(
+            engine.generate(),
+            Err(GeneratorError::ExhaustedSpace)
+        ));
+    }
+
+    fn state_path(name: &str) -> PathBuf {
+        let t = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        std::env::temp_dir().join(format!("seq_state_{}_{}.bin", name, t))
+    }
+
+    #[test]
+    fn store_state_leaves_no_temp_file_behind() {
+        let path = state_path("atomic");
+
+        store_state(&path, 42).unwrap();
+
+        assert_eq!(load_state(&Some(path.clone())).unwrap(), 42);
+        let prefix = format!("{}.", path.file_name().unwrap().to_string_lossy());
+        let leftovers = fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().starts_with(&prefix))
+            .count();
+        assert_eq!(leftovers, 0);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn partial_temp_file_is_ignored_before_the_first_rename() {
+        let path = state_path("partial");
+        // A crash while writing the temp file: only part of the value landed
+        let tmp = temp_state_path(&path);
+        fs::write(&tmp, &7u64.to_le_bytes()[..3]).unwrap();
+
+        assert_eq!(load_state(&Some(path.clone())).unwrap(), 0);
+        let _ = fs::remove_file(&tmp);
+    }
 
-        // For len = 3, capacity = 62^3 = 238328, so value == 238328 should be out of range
-        let cap = 62u128.pow(3);
-        assert!(encode_base62_fixed(cap, 3, &alpha).is_none());
+    #[test]
+    fn crash_before_rename_keeps_the_previous_state() {
+        let path = state_path("previous");
+        store_state(&path, 5).unwrap();
+        // A crash after the new temp file was written but before the rename
+        let tmp = temp_state_path(&path);
+        fs::write(&tmp, 10u64.to_le_bytes()).unwrap();
+
+        assert_eq!(load_state(&Some(path.clone())).unwrap(), 5);
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_file(&tmp);
+    }
+
+    proptest! {
+        // On failure proptest shrinks towards the shortest length and smallest
+        // alphabet at which the engine runs out of codes too early or too late.
+        #[test]
+        fn sequence_engine_exhausts_exactly_at_its_code_space(
+            (len, alphabet) in small_code_space(),
+            block_size in 1u64..64,
+        ) {
+            let space = code_space(alphabet.len() as u128, len);
+            let engine = SequenceEngine::new(
+                len,
+                Some(alphabet.iter().collect()),
+                block_size,
+                1,
+                None,
+            );
+
+            let codes = engine.generate_n(space as usize).unwrap();
+            prop_assert_eq!(codes.iter().collect::<HashSet<_>>().len(), codes.len());
+            prop_assert!(matches!(engine.generate(), Err(GeneratorError::ExhaustedSpace)));
+        }
     }
 }
@@ -0,0 +1,90 @@
+use std::sync::Mutex;
+
+use ulid::Generator;
+
+use crate::generator::DEFAULT_ALPHABET;
+use crate::generator::config::code_space;
+use crate::generator::encoding::encode_base62_fixed;
+
+use super::{GeneratorError, ShortCodeGenerator};
+
+/// Base62 digits needed to hold a full 128-bit ULID.
+pub const ULID_BASE62_LEN: usize = 22;
+
+/// Codes derived from ULIDs (48-bit millisecond timestamp + 80 random bits).
+///
+/// ULIDs come from a monotonic generator: two ULIDs minted in the same
+/// millisecond differ by incrementing the random part, so a single instance never
+/// repeats a value and full-length (22 character) codes sort by creation time.
+///
+/// Shorter codes keep only the trailing `len` base62 digits of the ULID. They
+/// stay unique within a millisecond on one instance, but they no longer sort and
+/// their collision resistance is that of a random code of the same length.
+/// At high throughput across several instances, collisions are caught by the
+/// database like any other engine's.
+pub struct UlidEngine {
+    len: usize,
+    alphabet: Vec<char>,
+    ulids: Mutex<Generator>,
+}
+
+impl UlidEngine {
+    pub fn new(len: usize, alphabet: Option<String>) -> Self {
+        let alpha = alphabet.unwrap_or_else(|| DEFAULT_ALPHABET.iter().collect());
+        UlidEngine {
+            len,
+            alphabet: alpha.chars().collect(),
+            ulids: Mutex::new(Generator::new()),
+        }
+    }
+}
+
+impl ShortCodeGenerator for UlidEngine {
+    fn generate(&self) -> Result<String, GeneratorError> {
+        let ulid = self
+            .ulids
+            .lock()
+            .map_err(|_| GeneratorError::Internal("ulid generator poisoned"))?
+            .generate()
+            .map_err(|_| GeneratorError::ExhaustedSpace)?;
+
+        let mut value = u128::from(ulid);
+        if self.len < ULID_BASE62_LEN {
+            value %= code_space(62, self.len);
+        }
+        encode_base62_fixed(value, self.len, &self.alphabet).ok_or(GeneratorError::Internal(
+            "ulid alphabet must have 62 characters",
+        ))
+    }
+
+    fn name(&self) -> &'static str {
+        "ulid"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn full_length_codes_are_sorted_and_unique() {
+        let engine = UlidEngine::new(ULID_BASE62_LEN, None);
+        let codes: Vec<String> = (0..1000).map(|_| engine.generate().unwrap()).collect();
+
+        let mut sorted = codes.clone();
+        sorted.sort();
+        assert_eq!(codes, sorted);
+
+        let unique: HashSet<_> = codes.iter().collect();
+        assert_eq!(unique.len(), codes.len());
+    }
+
+    #[test]
+    fn truncated_codes_are_unique_and_fixed_length() {
+        let engine = UlidEngine::new(7, None);
+        let codes: HashSet<String> = (0..1000).map(|_| engine.generate().unwrap()).collect();
+        assert_eq!(codes.len(), 1000);
+        assert!(codes.iter().all(|c| c.chars().count() == 7));
+    }
+}
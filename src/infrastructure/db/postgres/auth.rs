@@ -1,7 +1,8 @@
 use std::net::IpAddr;
 
 use crate::features::auth::repositories::{
-    AuthRepoError, AuthRepository, AuthenticationAction, AuthenticationChallenge, RefreshDevice,
+    AuthAuditEntry, AuthEvent, AuthEventMeta, AuthRepoError, AuthRepository, AuthenticationAction,
+    AuthenticationChallenge, DeviceSummary, RefreshDevice,
 };
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
@@ -182,6 +183,36 @@ impl AuthRepository for PgAuthRepository {
         Ok(())
     }
 
+    async fn list_devices_for_user(&self, user_id: Uuid) -> anyhow::Result<Vec<DeviceSummary>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, device_id, user_agent, ip, created_at,
+                    COALESCE(last_rotated_at, created_at) AS last_used
+            FROM refresh_token_devices
+            WHERE user_id = $1 AND revoked_at IS NULL AND absolute_expires > NOW()
+            ORDER BY last_used DESC, id DESC
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| DeviceSummary {
+                id: r.get("id"),
+                device_id: r.get("device_id"),
+                user_agent: r.get("user_agent"),
+                ip: r
+                    .get::<Option<IpNetwork>, _>("ip")
+                    .map(|ipn| ipn.ip().to_string()),
+                created_at: r.get("created_at"),
+                last_used: r.get("last_used"),
+                is_current: false,
+            })
+            .collect())
+    }
+
     async fn create_or_refresh_auth_challenge(
         &self,
         user_id: Uuid,
@@ -399,6 +430,71 @@ impl AuthRepository for PgAuthRepository {
 
         Ok(should_lock)
     }
+
+    async fn log_auth_event(
+        &self,
+        user_id: Uuid,
+        event: AuthEvent,
+        meta: AuthEventMeta,
+    ) -> Result<(), AuthRepoError> {
+        sqlx::query(
+            r#"
+            INSERT INTO auth_audit_log (user_id, event_type, ip, user_agent, metadata)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+        )
+        .bind(user_id)
+        .bind(event.as_str())
+        .bind(meta.ip)
+        .bind(meta.user_agent)
+        .bind(meta.metadata)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("Error logging auth event: {:#?}", e);
+            e
+        })?;
+
+        Ok(())
+    }
+
+    async fn get_auth_events(
+        &self,
+        user_id: Uuid,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<AuthAuditEntry>, AuthRepoError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, event_type, ip, user_agent, metadata, created_at
+            FROM auth_audit_log
+            WHERE user_id = $1
+            ORDER BY created_at DESC, id DESC
+            OFFSET $2
+            LIMIT $3
+            "#,
+        )
+        .bind(user_id)
+        .bind(i64::try_from(offset).unwrap_or(i64::MAX))
+        .bind(i64::try_from(limit).unwrap_or(i64::MAX))
+        .fetch_all(&self.pool)
+        .await?;
+
+        // Rows with an event type this build does not know are skipped
+        Ok(rows
+            .into_iter()
+            .filter_map(|r| {
+                Some(AuthAuditEntry {
+                    id: r.get("id"),
+                    event_type: AuthEvent::from_db(r.get("event_type"))?,
+                    ip: r.get::<Option<IpNetwork>, _>("ip").map(|ipn| ipn.ip()),
+                    user_agent: r.get("user_agent"),
+                    metadata: r.get("metadata"),
+                    created_at: r.get("created_at"),
+                })
+            })
+            .collect())
+    }
 }
 
 impl From<sqlx::Error> for AuthRepoError {
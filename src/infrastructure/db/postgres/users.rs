@@ -3,7 +3,7 @@ use chrono::{DateTime, Utc};
 use sqlx::{PgPool, Row};
 use uuid::Uuid;
 
-use crate::features::users::repositories::{User, UserRepository};
+use crate::features::users::repositories::{TotpState, User, UserRepository};
 
 #[derive(Clone)]
 pub struct PgUserRepository {
@@ -43,6 +43,7 @@ impl UserRepository for PgUserRepository {
             jwt_token_version: row.get::<i32, _>("jwt_token_version") as u32,
             locked_until: None,
             fail_count_since: None,
+            vanity_domain: None,
         })
     }
 
@@ -50,7 +51,8 @@ impl UserRepository for PgUserRepository {
         let row = sqlx::query(
             r#"
             SELECT id, email, password_hash, display_name, is_email_verified,
-                created_at, last_login_at, jwt_token_version, locked_until, fail_count_since
+                created_at, last_login_at, jwt_token_version, locked_until, fail_count_since,
+                vanity_domain
             FROM users WHERE email = $1
             "#,
         )
@@ -69,6 +71,7 @@ impl UserRepository for PgUserRepository {
             jwt_token_version: r.get::<i32, _>("jwt_token_version") as u32,
             locked_until: r.get("locked_until"),
             fail_count_since: r.get("fail_count_since"),
+            vanity_domain: r.get("vanity_domain"),
         }))
     }
 
@@ -93,7 +96,8 @@ impl UserRepository for PgUserRepository {
         let row = sqlx::query(
             r#"
             SELECT id, email, password_hash, display_name, is_email_verified,
-                created_at, last_login_at, jwt_token_version, locked_until, fail_count_since
+                created_at, last_login_at, jwt_token_version, locked_until, fail_count_since,
+                vanity_domain
             FROM users WHERE id = $1
             "#,
         )
@@ -112,6 +116,7 @@ impl UserRepository for PgUserRepository {
             jwt_token_version: r.get::<i32, _>("jwt_token_version") as u32,
             locked_until: r.get("locked_until"),
             fail_count_since: r.get("fail_count_since"),
+            vanity_domain: r.get("vanity_domain"),
         }))
     }
 
@@ -158,6 +163,15 @@ impl UserRepository for PgUserRepository {
         Ok(())
     }
 
+    async fn update_display_name(&self, id: Uuid, name: &str) -> anyhow::Result<()> {
+        sqlx::query("UPDATE users SET display_name = $1 WHERE id = $2")
+            .bind(name)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
     async fn lock_user_until(&self, id: Uuid, until: DateTime<Utc>) -> anyhow::Result<()> {
         sqlx::query("UPDATE users SET locked_until = $1 WHERE id = $2")
             .bind(until)
@@ -175,4 +189,175 @@ impl UserRepository for PgUserRepository {
             .await?;
         Ok(())
     }
+
+    async fn find_user_by_oauth_identity(
+        &self,
+        provider: &str,
+        subject_id: &str,
+    ) -> anyhow::Result<Option<User>> {
+        let row = sqlx::query(
+            r#"
+            SELECT u.id, u.email, u.password_hash, u.display_name, u.is_email_verified,
+                u.created_at, u.last_login_at, u.jwt_token_version, u.locked_until,
+                u.fail_count_since, u.vanity_domain
+            FROM oauth_identities o
+            JOIN users u ON u.id = o.user_id
+            WHERE o.provider = $1 AND o.subject_id = $2
+            "#,
+        )
+        .bind(provider)
+        .bind(subject_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|r| User {
+            id: r.get("id"),
+            email: r.get("email"),
+            password_hash: Some(r.get("password_hash")),
+            display_name: r.get("display_name"),
+            is_email_verified: r.get("is_email_verified"),
+            created_at: r.get("created_at"),
+            last_login_at: r.get("last_login_at"),
+            jwt_token_version: r.get::<i32, _>("jwt_token_version") as u32,
+            locked_until: r.get("locked_until"),
+            fail_count_since: r.get("fail_count_since"),
+            vanity_domain: r.get("vanity_domain"),
+        }))
+    }
+
+    async fn link_oauth_identity(
+        &self,
+        user_id: Uuid,
+        provider: &str,
+        subject_id: &str,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO oauth_identities (user_id, provider, subject_id)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (provider, subject_id) DO NOTHING
+            "#,
+        )
+        .bind(user_id)
+        .bind(provider)
+        .bind(subject_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn get_totp(&self, id: Uuid) -> anyhow::Result<Option<TotpState>> {
+        let row = sqlx::query(
+            r#"
+            SELECT totp_secret_encrypted, totp_enabled
+            FROM users WHERE id = $1 AND totp_secret_encrypted IS NOT NULL
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|r| TotpState {
+            secret_encrypted: r.get("totp_secret_encrypted"),
+            enabled: r.get("totp_enabled"),
+        }))
+    }
+
+    async fn set_totp_secret(&self, id: Uuid, secret_encrypted: &[u8]) -> anyhow::Result<()> {
+        sqlx::query(
+            "UPDATE users SET totp_secret_encrypted = $1, totp_enabled = FALSE WHERE id = $2",
+        )
+        .bind(secret_encrypted)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn enable_totp(&self, id: Uuid) -> anyhow::Result<()> {
+        sqlx::query(
+            "UPDATE users SET totp_enabled = TRUE WHERE id = $1 AND totp_secret_encrypted IS NOT NULL",
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn clear_totp(&self, id: Uuid) -> anyhow::Result<()> {
+        sqlx::query(
+            "UPDATE users SET totp_secret_encrypted = NULL, totp_enabled = FALSE WHERE id = $1",
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn find_user_by_vanity_domain(&self, domain: &str) -> anyhow::Result<Option<User>> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, email, password_hash, display_name, is_email_verified,
+                created_at, last_login_at, jwt_token_version, locked_until, fail_count_since,
+                vanity_domain
+            FROM users WHERE vanity_domain = $1
+            "#,
+        )
+        .bind(domain)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|r| User {
+            id: r.get("id"),
+            email: r.get("email"),
+            password_hash: Some(r.get("password_hash")),
+            display_name: r.get("display_name"),
+            is_email_verified: r.get("is_email_verified"),
+            created_at: r.get("created_at"),
+            last_login_at: r.get("last_login_at"),
+            jwt_token_version: r.get::<i32, _>("jwt_token_version") as u32,
+            locked_until: r.get("locked_until"),
+            fail_count_since: r.get("fail_count_since"),
+            vanity_domain: r.get("vanity_domain"),
+        }))
+    }
+
+    async fn set_vanity_domain(&self, id: Uuid, domain: &str) -> anyhow::Result<()> {
+        sqlx::query("UPDATE users SET vanity_domain = $1 WHERE id = $2")
+            .bind(domain)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_user(&self, id: Uuid) -> anyhow::Result<()> {
+        // Short links only have their owner set to NULL by the foreign key,
+        // so they are deleted here; the rest would cascade, but are deleted
+        // explicitly so the cleanup does not depend on every ON DELETE clause
+        const DEPENDENTS: [&str; 8] = [
+            "DELETE FROM aliases WHERE created_by = $1",
+            "DELETE FROM urls WHERE owner_id = $1",
+            "DELETE FROM api_keys WHERE user_id = $1",
+            "DELETE FROM refresh_token_devices WHERE user_id = $1",
+            "DELETE FROM authentication_challenges WHERE user_id = $1",
+            "DELETE FROM sign_in_attempts WHERE user_id = $1",
+            "DELETE FROM oauth_identities WHERE user_id = $1",
+            "DELETE FROM auth_audit_log WHERE user_id = $1",
+        ];
+
+        let mut tx = self.pool.begin().await?;
+        for statement in DEPENDENTS {
+            sqlx::query(statement).bind(id).execute(&mut *tx).await?;
+        }
+        let deleted = sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        if deleted.rows_affected() == 0 {
+            anyhow::bail!("User not found");
+        }
+        tx.commit().await?;
+        Ok(())
+    }
 }
@@ -1,6 +1,15 @@
 use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use resend_rs::{Resend, types::CreateEmailBaseOptions};
 
+/// Sends one HTML email; implemented by [`EmailService`], and by stand-ins
+/// in tests so nothing is delivered.
+#[async_trait]
+pub trait EmailSender: Send + Sync {
+    async fn send_email(&self, to: &str, subject: &str, html_content: &str) -> Result<()>;
+}
+
 pub struct EmailService {
     client: Resend,
     from_address: String,
@@ -14,13 +23,14 @@ impl EmailService {
         }
     }
 
-    pub async fn send_verification_code(&self, to: &str, code: &str) -> Result<()> {
+    pub async fn send_verification_code(&self, to: &str, code: &str, ttl_hours: u32) -> Result<()> {
         let subject = "Email Verification Code";
         let html = format!(
             r#"<h2>Verify Your Email</h2>
             <p>Your verification code is: <strong>{}</strong></p>
-            <p>This code will expire in 1 hour.</p>"#,
-            code
+            <p>This code will expire in {}.</p>"#,
+            code,
+            hours(ttl_hours)
         );
         tracing::debug!(
             "Sending verification code email from {} to {}",
@@ -36,13 +46,40 @@ impl EmailService {
         Ok(())
     }
 
-    pub async fn send_password_reset_code(&self, to: &str, code: &str) -> Result<()> {
+    pub async fn send_password_reset_code(
+        &self,
+        to: &str,
+        code: &str,
+        ttl_hours: u32,
+    ) -> Result<()> {
         let subject = "Password Reset Code";
         let html = format!(
             r#"<h2>Reset Your Password</h2>
             <p>Your password reset code is: <strong>{}</strong></p>
-            <p>This code will expire in 1 hour.</p>"#,
-            code
+            <p>This code will expire in {}.</p>"#,
+            code,
+            hours(ttl_hours)
+        );
+
+        let email = CreateEmailBaseOptions::new(&self.from_address, [to], subject).with_html(&html);
+
+        self.client.emails.send(email).await?;
+        Ok(())
+    }
+
+    pub async fn send_account_locked(
+        &self,
+        to: &str,
+        failed_attempts: u8,
+        until: DateTime<Utc>,
+    ) -> Result<()> {
+        let subject = "Account Locked";
+        let html = format!(
+            r#"<h2>Your Account Was Locked</h2>
+            <p>Sign-in was locked after {} failed attempts.</p>
+            <p>You can sign in again after {} UTC. If this was not you, reset your password.</p>"#,
+            failed_attempts,
+            until.format("%Y-%m-%d %H:%M")
         );
 
         let email = CreateEmailBaseOptions::new(&self.from_address, [to], subject).with_html(&html);
@@ -59,3 +96,19 @@ impl EmailService {
         Ok(())
     }
 }
+
+#[async_trait]
+impl EmailSender for EmailService {
+    async fn send_email(&self, to: &str, subject: &str, html_content: &str) -> Result<()> {
+        EmailService::send_email(self, to, subject, html_content).await
+    }
+}
+
+// "1 hour", "2 hours"
+fn hours(n: u32) -> String {
+    if n == 1 {
+        "1 hour".to_string()
+    } else {
+        format!("{} hours", n)
+    }
+}
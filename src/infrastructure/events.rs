@@ -0,0 +1,234 @@
+//! # Click Events
+//!
+//! Publishes every recorded click to a stream, chosen by `events.backend`, so
+//! analytics pipelines (Flink, Spark Streaming) can follow redirects in real
+//! time without querying the database:
+//!
+//! - `none` (default): events are dropped
+//! - `kafka`: JSON messages on a Kafka topic, keyed by short code (`kafka`
+//!   feature)
+//! - `nats`: JSON messages on a NATS subject (`nats` feature)
+//!
+//! Publishing runs in the background after the click is stored and never
+//! delays the redirect; a failed publish is logged and dropped.
+
+use crate::configuration::{EventBackend, EventSettings};
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::Arc;
+
+/// One followed short link, with everything stored by
+/// [`UrlDatabase::record_click`](crate::database::UrlDatabase::record_click).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ClickEvent {
+    /// Short code or alias that was followed
+    pub code: String,
+    /// When the redirect was served
+    pub clicked_at: DateTime<Utc>,
+    /// Client IP
+    pub ip: Option<String>,
+    /// `Referer` sent by the client
+    pub referrer: Option<String>,
+    /// A/B variant served: 0 for the main URL, 1 for the alternate
+    pub variant: Option<u8>,
+    /// `mobile`, `desktop` or `unknown`
+    pub device_type: Option<String>,
+    /// ISO 3166-1 alpha-2 country of the client IP, when geolocation is on
+    pub country_code: Option<String>,
+}
+
+/// Delivers [`ClickEvent`]s to a stream.
+#[async_trait]
+pub trait EventPublisher: Send + Sync {
+    /// Publishes `event`, returning once the backend has accepted it.
+    async fn publish(&self, event: ClickEvent) -> Result<()>;
+}
+
+/// Drops every event; used when `events.backend` is `none`.
+pub struct NoopEventPublisher;
+
+#[async_trait]
+impl EventPublisher for NoopEventPublisher {
+    async fn publish(&self, _event: ClickEvent) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Produces events to a Kafka topic, keyed by short code so the clicks of
+/// one link stay in order within a partition.
+#[cfg(feature = "kafka")]
+pub struct KafkaEventPublisher {
+    producer: rdkafka::producer::FutureProducer,
+    topic: String,
+}
+
+#[cfg(feature = "kafka")]
+impl KafkaEventPublisher {
+    /// Connects to the comma-separated `brokers`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the producer cannot be created.
+    pub fn new(brokers: &str, topic: String) -> Result<Self> {
+        let producer = rdkafka::ClientConfig::new()
+            .set("bootstrap.servers", brokers)
+            .set("message.timeout.ms", "5000")
+            .create()?;
+        Ok(Self { producer, topic })
+    }
+}
+
+#[cfg(feature = "kafka")]
+#[async_trait]
+impl EventPublisher for KafkaEventPublisher {
+    async fn publish(&self, event: ClickEvent) -> Result<()> {
+        let payload = serde_json::to_vec(&event)?;
+        let record = rdkafka::producer::FutureRecord::to(&self.topic)
+            .key(&event.code)
+            .payload(&payload);
+        self.producer
+            .send(record, std::time::Duration::from_secs(5))
+            .await
+            .map_err(|(e, _)| anyhow::anyhow!(e))?;
+        Ok(())
+    }
+}
+
+/// Publishes events to a NATS subject.
+#[cfg(feature = "nats")]
+pub struct NatsEventPublisher {
+    client: async_nats::Client,
+    subject: String,
+}
+
+#[cfg(feature = "nats")]
+impl NatsEventPublisher {
+    /// Connects to the NATS server at `url`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the server cannot be reached.
+    pub async fn connect(url: &str, subject: String) -> Result<Self> {
+        let client = async_nats::connect(url).await?;
+        Ok(Self { client, subject })
+    }
+}
+
+#[cfg(feature = "nats")]
+#[async_trait]
+impl EventPublisher for NatsEventPublisher {
+    async fn publish(&self, event: ClickEvent) -> Result<()> {
+        let payload = serde_json::to_vec(&event)?;
+        self.client
+            .publish(self.subject.clone(), payload.into())
+            .await?;
+        Ok(())
+    }
+}
+
+/// Builds the publisher selected by `events.backend`.
+///
+/// # Errors
+///
+/// Returns an error if the backend has no `url`, cannot be reached, or was
+/// not compiled in.
+pub async fn build_event_publisher(settings: &EventSettings) -> Result<Arc<dyn EventPublisher>> {
+    match settings.backend {
+        EventBackend::None => Ok(Arc::new(NoopEventPublisher)),
+        #[cfg(feature = "kafka")]
+        EventBackend::Kafka => {
+            let brokers = settings
+                .url
+                .as_deref()
+                .ok_or_else(|| anyhow::anyhow!("events.url must be set for kafka"))?;
+            Ok(Arc::new(KafkaEventPublisher::new(
+                brokers,
+                settings.topic.clone(),
+            )?))
+        }
+        #[cfg(feature = "nats")]
+        EventBackend::Nats => {
+            let url = settings
+                .url
+                .as_deref()
+                .ok_or_else(|| anyhow::anyhow!("events.url must be set for nats"))?;
+            Ok(Arc::new(
+                NatsEventPublisher::connect(url, settings.topic.clone()).await?,
+            ))
+        }
+        #[cfg(not(feature = "kafka"))]
+        EventBackend::Kafka => Err(anyhow::anyhow!(
+            "events.backend kafka needs the `kafka` feature"
+        )),
+        #[cfg(not(feature = "nats"))]
+        EventBackend::Nats => Err(anyhow::anyhow!(
+            "events.backend nats needs the `nats` feature"
+        )),
+    }
+}
+
+/// Publishes `event` on its own task, logging a failure.
+pub fn spawn_publish(publisher: &Arc<dyn EventPublisher>, event: ClickEvent) {
+    let publisher = publisher.clone();
+    tokio::spawn(async move {
+        if let Err(e) = publisher.publish(event).await {
+            tracing::warn!("failed to publish click event: {:#}", e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event() -> ClickEvent {
+        ClickEvent {
+            code: "abc123".to_string(),
+            clicked_at: DateTime::parse_from_rfc3339("2025-12-01T10:00:00Z")
+                .unwrap()
+                .with_timezone(&Utc),
+            ip: Some("203.0.113.7".to_string()),
+            referrer: None,
+            variant: Some(0),
+            device_type: Some("desktop".to_string()),
+            country_code: Some("DE".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn noop_publisher_accepts_every_event() {
+        let publisher = NoopEventPublisher;
+
+        assert!(publisher.publish(event()).await.is_ok());
+        assert!(publisher.publish(event()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn none_backend_builds_the_noop_publisher() {
+        let publisher = build_event_publisher(&EventSettings::default())
+            .await
+            .unwrap();
+
+        assert!(publisher.publish(event()).await.is_ok());
+    }
+
+    #[test]
+    fn click_event_serializes_every_field() {
+        let json = serde_json::to_value(event()).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "code": "abc123",
+                "clicked_at": "2025-12-01T10:00:00Z",
+                "ip": "203.0.113.7",
+                "referrer": null,
+                "variant": 0,
+                "device_type": "desktop",
+                "country_code": "DE",
+            })
+        );
+    }
+}
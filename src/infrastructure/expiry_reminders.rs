@@ -0,0 +1,94 @@
+//! # Expiry Reminders
+//!
+//! Warns the owner of a short URL by email a few days before it expires, so
+//! time-limited campaign links do not stop working by surprise.
+//!
+//! With `expiry_reminders.enabled`, [`spawn_expiry_reminders`] checks once a
+//! day for owned URLs expiring within `expiry_reminders.days_before` days.
+//! Each URL is reminded about once; `urls.expiry_reminder_sent_at` records
+//! the reminder. A reminder that cannot be sent is tried again the next day.
+
+use crate::database::DatabaseError;
+use crate::infrastructure::email::EmailSender;
+use crate::models::ExpiringUrl;
+use crate::state::AppState;
+
+use chrono::{DateTime, Duration, Utc};
+use std::sync::Arc;
+
+/// How often the background task looks for URLs expiring soon.
+pub const REMINDER_INTERVAL: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60);
+
+/// Runs [`send_expiry_reminders`] now and then every [`REMINDER_INTERVAL`]
+/// on a background task.
+pub fn spawn_expiry_reminders(state: AppState, mailer: Arc<dyn EmailSender>) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(REMINDER_INTERVAL);
+        loop {
+            ticker.tick().await;
+            match send_expiry_reminders(&state, mailer.as_ref(), Utc::now()).await {
+                Ok(sent) => tracing::info!(sent, "expiry reminders sent"),
+                Err(e) => tracing::warn!("failed to look up expiring URLs: {}", e),
+            }
+        }
+    });
+}
+
+/// Emails the owner of every URL expiring within
+/// `expiry_reminders.days_before` days of `now` that has not been reminded
+/// yet, and returns how many reminders went out.
+///
+/// An owner whose account cannot be found, or whose email fails, is skipped
+/// and left for the next run.
+///
+/// # Errors
+///
+/// Returns an error if the expiring URLs cannot be listed.
+pub async fn send_expiry_reminders(
+    state: &AppState,
+    mailer: &dyn EmailSender,
+    now: DateTime<Utc>,
+) -> Result<usize, DatabaseError> {
+    let days_before = state.config().expiry_reminders.days_before;
+    let until = now + Duration::days(i64::from(days_before));
+    let expiring = state.database().list_expiring_urls(now, until).await?;
+
+    let mut sent = 0;
+    for url in expiring {
+        let owner = match state.user_service().me(url.owner_id).await {
+            Ok(owner) => owner,
+            Err(e) => {
+                tracing::warn!(code = %url.code, "no owner to remind of expiry: {}", e);
+                continue;
+            }
+        };
+        let html = reminder_html(&state.config().application.base_url, &url);
+        if let Err(e) = mailer
+            .send_email(&owner.email, "Your short link expires soon", &html)
+            .await
+        {
+            tracing::warn!(code = %url.code, "failed to send expiry reminder: {}", e);
+            continue;
+        }
+        if let Err(e) = state
+            .database()
+            .mark_expiry_reminder_sent(&url.code, now)
+            .await
+        {
+            tracing::warn!(code = %url.code, "failed to record expiry reminder: {}", e);
+        }
+        sent += 1;
+    }
+    Ok(sent)
+}
+
+fn reminder_html(base_url: &str, url: &ExpiringUrl) -> String {
+    let short_url = format!("{}/{}", base_url.trim_end_matches('/'), url.code);
+    format!(
+        r#"<h2>Your Short Link Expires Soon</h2>
+        <p><a href="{short_url}">{short_url}</a>, which leads to {}, stops working on {} UTC.</p>
+        <p>Shorten the URL again with a new expiry if you still need it.</p>"#,
+        url.url,
+        url.expires_at.format("%Y-%m-%d %H:%M")
+    )
+}
@@ -0,0 +1,49 @@
+//! # Click Geolocation
+//!
+//! Resolves the client IP of a click to the ISO 3166-1 alpha-2 code of its
+//! country, stored with the click when `geolocation.enabled` is set.
+//!
+//! Lookups go through [`CountryLookup`], so tests can stand in a fixed
+//! answer for [`MaxMindCountryLookup`], which reads a GeoLite2 Country
+//! database into memory once at startup.
+
+use anyhow::{Context, Result};
+use maxminddb::{Reader, geoip2};
+use std::net::IpAddr;
+use std::path::Path;
+
+/// Finds the country an IP address is registered in.
+pub trait CountryLookup: Send + Sync {
+    /// Two-letter country code of `ip`, e.g. `"US"`; `None` when the
+    /// database does not know it (private ranges, unassigned blocks).
+    fn country_code(&self, ip: IpAddr) -> Option<String>;
+}
+
+/// [`CountryLookup`] backed by a MaxMind GeoLite2 or GeoIP2 Country database.
+pub struct MaxMindCountryLookup {
+    reader: Reader<Vec<u8>>,
+}
+
+impl MaxMindCountryLookup {
+    /// Reads the `.mmdb` database at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not a MaxMind database.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let reader = Reader::open_readfile(path)
+            .with_context(|| format!("Failed to open GeoIP database {}", path.display()))?;
+        Ok(Self { reader })
+    }
+}
+
+impl CountryLookup for MaxMindCountryLookup {
+    fn country_code(&self, ip: IpAddr) -> Option<String> {
+        let record: geoip2::Country = self.reader.lookup(ip).ok()?;
+        record
+            .country
+            .and_then(|country| country.iso_code)
+            .map(str::to_string)
+    }
+}
@@ -0,0 +1,54 @@
+//! # Outbound HTTP Client
+//!
+//! Builds the one [`reqwest::Client`] held by
+//! [`AppState::http_client`](crate::state::AppState::http_client), so
+//! reachability checks, link previews, webhooks, Safe Browsing lookups and
+//! OAuth sign-ins share a connection pool and the `http_client` timeout,
+//! `User-Agent` and proxy.
+//!
+//! The client never follows redirects itself. Callers that do follow them
+//! (the reachability check and link previews) vet every hop before requesting
+//! it and stop after `http_client.max_redirects`.
+
+use crate::configuration::HttpClientSettings;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+/// Builds the shared client from the `http_client` settings.
+///
+/// # Errors
+///
+/// Returns an error if `proxy` is not a valid proxy URL or the TLS backend
+/// cannot be initialized.
+pub fn build_http_client(settings: &HttpClientSettings) -> Result<reqwest::Client, reqwest::Error> {
+    client_builder(settings)?.build()
+}
+
+/// Builds a client like [`build_http_client`] that connects to `domain` only
+/// at `addresses`, which the caller has already vetted, instead of resolving
+/// it again when the request is sent.
+///
+/// # Errors
+///
+/// Returns an error if `proxy` is not a valid proxy URL or the TLS backend
+/// cannot be initialized.
+pub fn build_pinned_http_client(
+    settings: &HttpClientSettings,
+    domain: &str,
+    addresses: &[SocketAddr],
+) -> Result<reqwest::Client, reqwest::Error> {
+    client_builder(settings)?
+        .resolve_to_addrs(domain, addresses)
+        .build()
+}
+
+fn client_builder(settings: &HttpClientSettings) -> Result<reqwest::ClientBuilder, reqwest::Error> {
+    let mut builder = reqwest::Client::builder()
+        .user_agent(settings.user_agent.as_str())
+        .timeout(Duration::from_millis(settings.timeout_ms))
+        .redirect(reqwest::redirect::Policy::none());
+    if let Some(proxy) = &settings.proxy {
+        builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+    }
+    Ok(builder)
+}
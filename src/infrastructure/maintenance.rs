@@ -0,0 +1,146 @@
+//! # Database Maintenance
+//!
+//! Keeps the database compact and its query plans current on long-running
+//! deployments, where deleted URLs and pruned clicks leave free pages behind.
+//!
+//! With `maintenance.enabled`, [`spawn_maintenance`] runs
+//! [`UrlDatabase::run_maintenance`] at startup and then every
+//! `maintenance.interval_hours` hours: `VACUUM` followed by `ANALYZE` on
+//! SQLite, `VACUUM ANALYZE urls` on PostgreSQL. Each statement is logged with
+//! its duration and outcome. After a successful `VACUUM`, the
+//! `maintenance.post_vacuum_hook` shell command is run, if one is set.
+
+use crate::configuration::MaintenanceSettings;
+use crate::database::UrlDatabase;
+
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::process::Command;
+use tokio::task::JoinHandle;
+use tracing::Instrument;
+
+/// Runs [`run_maintenance`] now and then every `interval_hours` hours on a
+/// background task.
+///
+/// With `interval_hours` set to `0` the maintenance runs once, and the
+/// returned task finishes afterwards.
+pub fn spawn_maintenance(
+    database: Arc<dyn UrlDatabase>,
+    settings: MaintenanceSettings,
+) -> JoinHandle<()> {
+    let hook = settings.post_vacuum_hook;
+    let task = async move {
+        if settings.interval_hours == 0 {
+            run_maintenance(database.as_ref(), hook.as_deref()).await;
+            return;
+        }
+        let period = Duration::from_secs(u64::from(settings.interval_hours) * 60 * 60);
+        let mut ticker = tokio::time::interval(period);
+        loop {
+            ticker.tick().await;
+            run_maintenance(database.as_ref(), hook.as_deref()).await;
+        }
+    };
+    tokio::spawn(task.in_current_span())
+}
+
+/// Runs the maintenance statements of `database` once, logging each one,
+/// and runs `post_vacuum_hook` after a successful `VACUUM`.
+///
+/// Failures are logged rather than returned; the next run tries again.
+pub async fn run_maintenance(database: &dyn UrlDatabase, post_vacuum_hook: Option<&str>) {
+    for step in database.run_maintenance().await {
+        let duration_ms = step.duration.as_millis() as u64;
+        match &step.error {
+            None => tracing::info!(
+                operation = step.operation,
+                duration_ms,
+                status = "ok",
+                "database maintenance"
+            ),
+            Some(error) => tracing::info!(
+                operation = step.operation,
+                duration_ms,
+                status = "error",
+                error = %error,
+                "database maintenance"
+            ),
+        }
+        let vacuumed = step.error.is_none() && step.operation.starts_with("VACUUM");
+        if let Some(command) = post_vacuum_hook.filter(|_| vacuumed) {
+            run_hook(command).await;
+        }
+    }
+}
+
+/// Runs `command` through the platform shell and logs its exit code.
+async fn run_hook(command: &str) {
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.args(["/C", command]);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.args(["-c", command]);
+        shell
+    };
+
+    let started = std::time::Instant::now();
+    match shell.status().await {
+        Ok(status) => tracing::info!(
+            operation = "post_vacuum_hook",
+            duration_ms = started.elapsed().as_millis() as u64,
+            exit_code = status.code(),
+            status = if status.success() { "ok" } else { "error" },
+            "database maintenance"
+        ),
+        Err(e) => tracing::warn!(
+            operation = "post_vacuum_hook",
+            "failed to run post-vacuum hook: {}",
+            e
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::SqliteUrlDatabase;
+    use tracing_test::traced_test;
+
+    async fn database() -> Arc<dyn UrlDatabase> {
+        Arc::new(
+            SqliteUrlDatabase::in_memory_with_migrations()
+                .await
+                .unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn zero_interval_runs_vacuum_and_analyze_once() {
+        let settings = MaintenanceSettings {
+            enabled: true,
+            interval_hours: 0,
+            post_vacuum_hook: None,
+        };
+
+        spawn_maintenance(database().await, settings).await.unwrap();
+
+        assert!(logs_contain("operation=\"VACUUM\""));
+        assert!(logs_contain("operation=\"ANALYZE\""));
+        assert!(logs_contain("duration_ms="));
+        assert!(logs_contain("status=\"ok\""));
+        assert!(!logs_contain("status=\"error\""));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    #[traced_test]
+    async fn post_vacuum_hook_exit_code_is_logged() {
+        run_maintenance(database().await.as_ref(), Some("exit 3")).await;
+
+        assert!(logs_contain("operation=\"post_vacuum_hook\""));
+        assert!(logs_contain("exit_code=3"));
+    }
+}
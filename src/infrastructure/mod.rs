@@ -1,2 +1,9 @@
 pub mod db;
 pub mod email;
+pub mod events;
+pub mod expiry_reminders;
+pub mod geo;
+pub mod http_client;
+pub mod maintenance;
+pub mod safe_browsing;
+pub mod webhooks;
@@ -0,0 +1,267 @@
+//! # Safe Browsing
+//!
+//! Looks up URLs in the Google Safe Browsing v4 Lookup API before they are
+//! shortened, when `safe_browsing.enabled` is set, so the service does not
+//! hand out short links to known malware or phishing pages.
+//!
+//! Answers are cached per URL for `safe_browsing.cache_ttl_seconds`, clean
+//! and flagged alike. When the API cannot be reached the URL is let through
+//! and nothing is cached: an outage of the lookup should not stop shortening.
+
+use crate::configuration::SafeBrowsingSettings;
+use crate::errors::ApiError;
+use crate::telemetry::trace_context_headers;
+use moka::sync::Cache;
+use secrecy::{ExposeSecret, SecretString};
+use serde::Deserialize;
+use serde_json::json;
+use std::time::Duration;
+
+/// Lookup API endpoint URLs are checked against.
+pub const SAFE_BROWSING_ENDPOINT: &str =
+    "https://safebrowsing.googleapis.com/v4/threatMatches:find";
+
+/// How long one lookup may take.
+const LOOKUP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Threat types a URL is checked for.
+const THREAT_TYPES: [&str; 4] = [
+    "MALWARE",
+    "SOCIAL_ENGINEERING",
+    "UNWANTED_SOFTWARE",
+    "POTENTIALLY_HARMFUL_APPLICATION",
+];
+
+/// Verdict of the Safe Browsing API on one URL.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SafeBrowsingResult {
+    /// No known threat matches the URL
+    Safe,
+    /// The URL is listed as a threat
+    Flagged,
+}
+
+/// Why a Safe Browsing lookup gave no verdict.
+#[derive(Debug, thiserror::Error)]
+pub enum SafeBrowsingError {
+    /// The API could not be reached or its answer could not be read
+    #[error("Safe Browsing request failed: {0}")]
+    Request(#[from] reqwest::Error),
+    /// The API answered with a non-success status
+    #[error("Safe Browsing answered with status {0}")]
+    Status(reqwest::StatusCode),
+}
+
+#[derive(Deserialize)]
+struct ThreatMatches {
+    #[serde(default)]
+    matches: Vec<serde_json::Value>,
+}
+
+/// Looks `url` up in the Safe Browsing API.
+///
+/// # Errors
+///
+/// Returns a [`SafeBrowsingError`] if the API gave no verdict.
+pub async fn check_safe_browsing(
+    url: &str,
+    client: &reqwest::Client,
+    api_key: &str,
+) -> Result<SafeBrowsingResult, SafeBrowsingError> {
+    check_safe_browsing_at(SAFE_BROWSING_ENDPOINT, url, client, api_key).await
+}
+
+/// Looks `url` up in the Safe Browsing API served at `endpoint`.
+///
+/// # Errors
+///
+/// Returns a [`SafeBrowsingError`] if the API gave no verdict.
+pub async fn check_safe_browsing_at(
+    endpoint: &str,
+    url: &str,
+    client: &reqwest::Client,
+    api_key: &str,
+) -> Result<SafeBrowsingResult, SafeBrowsingError> {
+    let body = json!({
+        "client": {
+            "clientId": "url-shortener-ztm",
+            "clientVersion": env!("CARGO_PKG_VERSION"),
+        },
+        "threatInfo": {
+            "threatTypes": THREAT_TYPES,
+            "platformTypes": ["ANY_PLATFORM"],
+            "threatEntryTypes": ["URL"],
+            "threatEntries": [{ "url": url }],
+        },
+    });
+    let response = client
+        .post(endpoint)
+        .headers(trace_context_headers())
+        .query(&[("key", api_key)])
+        .timeout(LOOKUP_TIMEOUT)
+        .json(&body)
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(SafeBrowsingError::Status(response.status()));
+    }
+
+    let found: ThreatMatches = response.json().await?;
+    Ok(if found.matches.is_empty() {
+        SafeBrowsingResult::Safe
+    } else {
+        SafeBrowsingResult::Flagged
+    })
+}
+
+/// Screens URLs with the Safe Browsing API, caching its verdicts.
+///
+/// Held by [`AppState::safe_browsing`](crate::state::AppState::safe_browsing)
+/// when `safe_browsing.enabled` is set.
+pub struct SafeBrowsing {
+    client: reqwest::Client,
+    endpoint: String,
+    api_key: SecretString,
+    cache: Cache<String, SafeBrowsingResult>,
+}
+
+impl SafeBrowsing {
+    /// Builds a screener for the settings, querying [`SAFE_BROWSING_ENDPOINT`]
+    /// with `client`.
+    pub fn new(settings: &SafeBrowsingSettings, client: reqwest::Client) -> Self {
+        Self {
+            client,
+            endpoint: SAFE_BROWSING_ENDPOINT.to_string(),
+            api_key: settings
+                .api_key
+                .clone()
+                .unwrap_or_else(|| SecretString::from("")),
+            cache: Cache::builder()
+                .time_to_live(Duration::from_secs(settings.cache_ttl_seconds))
+                .build(),
+        }
+    }
+
+    /// Queries `endpoint` instead of [`SAFE_BROWSING_ENDPOINT`].
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = endpoint.into();
+        self
+    }
+
+    /// Rejects `url` if Safe Browsing lists it as a threat.
+    ///
+    /// A URL the API gave no verdict on is let through, with a warning.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ApiError::Unprocessable`] if the URL is flagged.
+    pub async fn reject_flagged(&self, url: &str) -> Result<(), ApiError> {
+        let verdict = match self.cache.get(url) {
+            Some(verdict) => verdict,
+            None => {
+                let checked = check_safe_browsing_at(
+                    &self.endpoint,
+                    url,
+                    &self.client,
+                    self.api_key.expose_secret(),
+                )
+                .await;
+                match checked {
+                    Ok(verdict) => {
+                        self.cache.insert(url.to_string(), verdict);
+                        verdict
+                    }
+                    Err(e) => {
+                        tracing::warn!("Safe Browsing lookup failed, allowing URL: {}", e);
+                        return Ok(());
+                    }
+                }
+            }
+        };
+
+        match verdict {
+            SafeBrowsingResult::Safe => Ok(()),
+            SafeBrowsingResult::Flagged => {
+                tracing::warn!(url, "rejecting URL flagged by Safe Browsing");
+                Err(ApiError::Unprocessable(
+                    "URL flagged by Safe Browsing".to_string(),
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const URL: &str = "https://www.example.com/download";
+
+    fn screener(server: &MockServer) -> SafeBrowsing {
+        let settings = SafeBrowsingSettings {
+            enabled: true,
+            api_key: Some(SecretString::from("test-key")),
+            cache_ttl_seconds: 60,
+        };
+        SafeBrowsing::new(&settings, reqwest::Client::new())
+            .with_endpoint(format!("{}/v4/threatMatches:find", server.uri()))
+    }
+
+    async fn answer(server: &MockServer, response: ResponseTemplate) {
+        Mock::given(method("POST"))
+            .and(path("/v4/threatMatches:find"))
+            .and(query_param("key", "test-key"))
+            .respond_with(response)
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn clean_url_proceeds() {
+        let server = MockServer::start().await;
+        answer(&server, ResponseTemplate::new(200).set_body_json(json!({}))).await;
+
+        assert!(screener(&server).reject_flagged(URL).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn flagged_url_is_rejected() {
+        let server = MockServer::start().await;
+        let flagged = json!({
+            "matches": [{
+                "threatType": "MALWARE",
+                "platformType": "ANY_PLATFORM",
+                "threatEntryType": "URL",
+                "threat": { "url": URL },
+            }]
+        });
+        answer(&server, ResponseTemplate::new(200).set_body_json(flagged)).await;
+
+        let result = screener(&server).reject_flagged(URL).await;
+        assert!(matches!(result, Err(ApiError::Unprocessable(_))));
+    }
+
+    #[tokio::test]
+    async fn unavailable_api_fails_open() {
+        let server = MockServer::start().await;
+        answer(&server, ResponseTemplate::new(503)).await;
+
+        assert!(screener(&server).reject_flagged(URL).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn verdicts_are_cached() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let screener = screener(&server);
+
+        assert!(screener.reject_flagged(URL).await.is_ok());
+        assert!(screener.reject_flagged(URL).await.is_ok());
+    }
+}
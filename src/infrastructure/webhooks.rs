@@ -0,0 +1,198 @@
+//! # Webhooks
+//!
+//! Delivers link events to the endpoints configured under `webhooks`, so
+//! external pipelines learn when a link is followed without polling the API.
+//!
+//! Deliveries run in the background and never delay the request that caused
+//! them. A delivery that fails, or gets a non-2xx answer, is retried up to
+//! [`MAX_ATTEMPTS`] times in total with exponential backoff, then dropped
+//! with a warning. Redirects answered by an endpoint are not followed.
+
+use crate::configuration::WebhookConfig;
+use crate::core::security::HmacSha256;
+use crate::telemetry::trace_context_headers;
+
+use chrono::{DateTime, Utc};
+use hmac::Mac;
+use secrecy::ExposeSecret;
+use serde::Serialize;
+use std::time::Duration;
+
+/// Attempts made per delivery before giving up.
+pub const MAX_ATTEMPTS: u32 = 3;
+/// Wait before the first retry; doubled before each further one.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(200);
+
+/// Body POSTed to a webhook endpoint.
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookEvent {
+    /// Event name, e.g. `"redirect"`
+    pub event: &'static str,
+    /// Short code the event is about
+    pub code: String,
+    /// Destination of the short code
+    pub original_url: String,
+    /// Client IP that caused the event
+    pub ip: String,
+    /// `User-Agent` of the client, when it sent one
+    pub user_agent: Option<String>,
+    /// When the event happened
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Sends `event` with `client` to every webhook in `webhooks` that wants it.
+///
+/// Returns immediately; each delivery runs on its own task and carries the
+/// trace context of the caller's span.
+pub fn fire_webhooks(client: &reqwest::Client, webhooks: &[WebhookConfig], event: &WebhookEvent) {
+    let targets: Vec<_> = webhooks
+        .iter()
+        .filter(|w| w.wants(event.event, &event.code))
+        .collect();
+    if targets.is_empty() {
+        return;
+    }
+
+    let body = match serde_json::to_vec(event) {
+        Ok(body) => body,
+        Err(e) => {
+            tracing::warn!("failed to serialize webhook event: {}", e);
+            return;
+        }
+    };
+    let trace = trace_context_headers();
+    for webhook in targets {
+        let url = webhook.url.clone();
+        let signature = sign(webhook.secret.expose_secret().as_bytes(), &body);
+        let body = body.clone();
+        let client = client.clone();
+        let trace = trace.clone();
+        tokio::spawn(async move { deliver(&client, &url, &signature, trace, body).await });
+    }
+}
+
+/// The `X-Signature-256` value of `body`: `sha256=` and the hex HMAC-SHA256
+/// keyed with `secret`.
+pub fn sign(secret: &[u8], body: &[u8]) -> String {
+    let mut mac =
+        <HmacSha256 as Mac>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// POSTs `body` to `url`, retrying failed attempts with exponential backoff.
+async fn deliver(
+    client: &reqwest::Client,
+    url: &str,
+    signature: &str,
+    trace: reqwest::header::HeaderMap,
+    body: Vec<u8>,
+) {
+    let mut backoff = INITIAL_BACKOFF;
+    for attempt in 1..=MAX_ATTEMPTS {
+        let result = client
+            .post(url)
+            .headers(trace.clone())
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header("X-Signature-256", signature)
+            .body(body.clone())
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+
+        match result {
+            Ok(_) => return,
+            Err(e) if attempt < MAX_ATTEMPTS => {
+                tracing::debug!(attempt, "webhook delivery to {} failed: {}", url, e);
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "webhook delivery to {} failed after {} attempts: {}",
+                    url,
+                    MAX_ATTEMPTS,
+                    e
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature_is_the_hex_hmac_of_the_body() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign(b"Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[cfg(feature = "opentelemetry")]
+    #[tokio::test]
+    async fn delivery_continues_the_trace_of_the_incoming_request() {
+        use crate::telemetry::make_http_span;
+        use axum::http::Request;
+        use opentelemetry::trace::TracerProvider;
+        use opentelemetry_sdk::{propagation::TraceContextPropagator, trace::SdkTracerProvider};
+        use secrecy::SecretString;
+        use tracing_subscriber::{Registry, prelude::*};
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+
+        opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+        let provider = SdkTracerProvider::builder().build();
+        let subscriber = Registry::default()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+        let _guard = tracing::subscriber::set_default(subscriber);
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/hook"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+        let webhook = WebhookConfig {
+            url: format!("{}/hook", server.uri()),
+            secret: SecretString::from("webhook-secret"),
+            events: vec!["redirect".to_string()],
+            filter_code: None,
+        };
+        let event = WebhookEvent {
+            event: "redirect",
+            code: "abc123".to_string(),
+            original_url: "https://www.example.com/".to_string(),
+            ip: "203.0.113.7".to_string(),
+            user_agent: None,
+            timestamp: Utc::now(),
+        };
+        let request = Request::get("/abc123")
+            .header("traceparent", format!("00-{TRACE_ID}-00f067aa0ba902b7-01"))
+            .body(())
+            .unwrap();
+
+        make_http_span(&request)
+            .in_scope(|| fire_webhooks(&reqwest::Client::new(), &[webhook], &event));
+
+        let mut received = Vec::new();
+        for _ in 0..50 {
+            received = server.received_requests().await.unwrap_or_default();
+            if !received.is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        let traceparent = received
+            .first()
+            .and_then(|request| request.headers.get("traceparent"))
+            .expect("the delivery carried no traceparent")
+            .to_str()
+            .unwrap();
+        assert!(traceparent.starts_with(&format!("00-{TRACE_ID}-")));
+    }
+}
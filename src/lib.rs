@@ -33,7 +33,7 @@
 //!     let configuration = get_configuration().expect("Failed to read configuration files.");
 //!
 //!     // Build and run the application
-//!     let application = Application::build(configuration).await?;
+//!     let application = Application::build(configuration.try_into()?).await?;
 //!     application.run_until_stopped().await?;
 //!
 //!     Ok(())
@@ -48,12 +48,14 @@
 //! - [`database`] - Database abstraction layer with SQLite implementation
 //! - [`errors`] - Comprehensive error handling with custom API error types
 //! - [`middleware`] - API key authentication middleware
+//! - [`preflight`] - Checks run before the server accepts connections
 //! - [`response`] - Standardized JSON response envelope
 //! - [`routes`] - HTTP route handlers for all endpoints
 //! - [`startup`] - Application startup and router configuration
 //! - [`state`] - Application state management
 //! - [`telemetry`] - Logging and tracing setup
 //! - [`templates`] - Tera template rendering for web interface
+//! - [`validation`] - Shared input validation helpers
 //!
 //! ## API Endpoints
 //!
@@ -67,6 +69,7 @@
 //! The service supports environment-based configuration with YAML files:
 //! - `configuration/base.yml` - Base configuration
 //! - `configuration/local.yml` - Local development overrides
+//! - `configuration/staging.yml` - Staging settings
 //! - `configuration/production.yml` - Production settings
 //!
 //! Environment variables can override any setting using the `APP_` prefix.
@@ -82,6 +85,7 @@ pub mod generator;
 pub mod infrastructure;
 pub mod middleware;
 pub mod models;
+pub mod preflight;
 pub mod response;
 pub mod routes;
 pub mod shortcode;
@@ -89,6 +93,7 @@ pub mod startup;
 pub mod state;
 pub mod telemetry;
 pub mod templates;
+pub mod validation;
 
 // Re-exports for convenience
 pub use configuration::*;
@@ -99,3 +104,4 @@ pub use startup::*;
 pub use state::*;
 pub use telemetry::*;
 pub use templates::*;
+pub use validation::{normalize_url, validate_alias};
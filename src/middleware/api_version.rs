@@ -0,0 +1,41 @@
+//! # API Version Negotiation
+//!
+//! Clients may pin the API version they were written against with an
+//! `Accept-Version` header. A request asking for any version other than
+//! [`API_VERSION`] is rejected with a 400 before it reaches a handler, so a
+//! client never silently talks to an API it does not understand. Requests
+//! without the header are always served.
+
+use crate::errors::ApiError;
+use crate::response::API_VERSION;
+
+use axum::{
+    extract::Request,
+    http::HeaderName,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+/// Name of the request header naming the API version a client expects.
+pub const ACCEPT_VERSION: HeaderName = HeaderName::from_static("accept-version");
+
+/// Middleware function that rejects requests for an unsupported API version.
+///
+/// Answers with [`ApiError::BadRequest`] when `Accept-Version` is present and
+/// is not [`API_VERSION`]; surrounding whitespace is ignored.
+pub async fn check_accept_version(request: Request, next: Next) -> Response {
+    let supported = request
+        .headers()
+        .get(&ACCEPT_VERSION)
+        .is_none_or(|version| {
+            version
+                .to_str()
+                .is_ok_and(|version| version.trim() == API_VERSION)
+        });
+    if !supported {
+        return ApiError::BadRequest("This API version is not supported".to_string())
+            .into_response();
+    }
+
+    next.run(request).await
+}
@@ -0,0 +1,26 @@
+//! # Panic Recovery
+//!
+//! A panicking handler, such as an `unwrap()` meeting unexpected database
+//! state, would otherwise drop the connection without a response.
+//! `CatchPanicLayer::custom(handle_panic)` wraps the whole router and turns
+//! such a panic into an [`ApiError::Internal`] response, which is also
+//! reported to Sentry when it is enabled.
+
+use crate::errors::ApiError;
+
+use axum::response::{IntoResponse, Response};
+use std::any::Any;
+
+/// Answers a request whose handler panicked with a 500 JSON envelope.
+///
+/// The panic message is logged; clients only see a generic message.
+pub fn handle_panic(panic_info: Box<dyn Any + Send + 'static>) -> Response {
+    let message = panic_info
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic_info.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("non-string panic payload");
+    tracing::error!("Handler panicked: {}", message);
+
+    ApiError::Internal("An unexpected error occurred".to_string()).into_response()
+}
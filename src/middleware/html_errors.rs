@@ -0,0 +1,113 @@
+//! # HTML Error Pages
+//!
+//! Browsers following a dead short link or mistyping a path should land on a
+//! page, not on a JSON envelope. Error responses to requests whose `Accept`
+//! header prefers `text/html` are re-rendered with the `errors/404.html` and
+//! `errors/500.html` templates; every other client keeps the JSON body.
+
+use crate::configuration::Environment;
+use crate::errors::ErrorProblem;
+use crate::state::AppState;
+
+use axum::{
+    extract::{Request, State},
+    http::{StatusCode, header},
+    middleware::Next,
+    response::{Html, IntoResponse, Response},
+};
+use tera::Context;
+use tower_http::request_id::RequestId;
+
+/// Message shown on 5xx pages in production instead of the internal error.
+const GENERIC_SERVER_ERROR: &str = "Something went wrong on our end. Please try again later.";
+
+/// Middleware function that renders [`ApiError`](crate::errors::ApiError)
+/// responses as HTML pages for browsers.
+///
+/// Must run inside `SetRequestIdLayer`. Only `404` and `5xx` responses
+/// carrying an [`ErrorProblem`] extension are rewritten; other errors keep
+/// their JSON body. In production, 5xx pages never show the internal message.
+pub async fn render_html_errors(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let wants_html = request
+        .headers()
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(prefers_html);
+    let request_id = request
+        .extensions()
+        .get::<RequestId>()
+        .and_then(|id| id.header_value().to_str().ok())
+        .map(str::to_owned);
+
+    let response = next.run(request).await;
+    if !wants_html {
+        return response;
+    }
+
+    let Some(problem) = response.extensions().get::<ErrorProblem>() else {
+        return response;
+    };
+    let template = match problem.status {
+        StatusCode::NOT_FOUND => "errors/404.html",
+        status if status.is_server_error() => "errors/500.html",
+        _ => return response,
+    };
+    let message = if problem.status.is_server_error()
+        && state.config().environment == Environment::Production
+    {
+        GENERIC_SERVER_ERROR
+    } else {
+        problem.detail.as_str()
+    };
+
+    let mut context = Context::new();
+    context.insert("title", "URL Shortener");
+    context.insert("page", problem.status.canonical_reason().unwrap_or("Error"));
+    context.insert("status_code", &problem.status.as_u16());
+    context.insert("message", message);
+    context.insert("request_id", &request_id);
+    let body = match state.templates().render(template, &context) {
+        Ok(body) => body,
+        Err(e) => {
+            tracing::error!("failed to render {}: {:?}", template, e);
+            return response;
+        }
+    };
+
+    let (mut parts, _) = response.into_parts();
+    let mut rendered = (parts.status, Html(body)).into_response();
+    // Keep headers set by inner layers, such as security or rate limit headers
+    parts.headers.remove(header::CONTENT_TYPE);
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts.headers.remove(header::CONTENT_ENCODING);
+    rendered.headers_mut().extend(parts.headers);
+    rendered
+}
+
+/// Whether an `Accept` header ranks `text/html` at least as high as
+/// `application/json`, as browsers navigating to a page do.
+pub(crate) fn prefers_html(accept: &str) -> bool {
+    let mut html_q = 0.0_f32;
+    let mut json_q = 0.0_f32;
+
+    for range in accept.split(',') {
+        let mut params = range.split(';');
+        let media_type = params.next().unwrap_or("").trim().to_ascii_lowercase();
+        let q = params
+            .filter_map(|p| p.trim().strip_prefix("q="))
+            .find_map(|q| q.parse::<f32>().ok())
+            .unwrap_or(1.0);
+
+        if media_type == "text/html" {
+            html_q = html_q.max(q);
+        } else if media_type == "application/json" {
+            json_q = json_q.max(q);
+        }
+    }
+
+    html_q > 0.0 && html_q >= json_q
+}
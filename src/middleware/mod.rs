@@ -0,0 +1,309 @@
+//! # Middleware
+//!
+//! This module provides middleware functions for the URL shortener service.
+//! Middleware functions are used to process requests before they reach the
+//! main handler, enabling cross-cutting concerns like authentication.
+//!
+//! ## Available Middleware
+//!
+//! - [`handle_panic`] - Answers a panicking handler with a 500 (`CatchPanicLayer`)
+//! - [`check_accept_version`] - Rejects requests for an unsupported `Accept-Version`
+//! - [`check_api_key`] - Validates API key authentication for protected endpoints
+//! - [`check_api_key_or_user`] - Accepts an API key or a signed-in user's access token
+//! - [`require_admin`] - Limits [`check_api_key_or_user`] to API keys and admin accounts
+//! - [`enforce_request_timeout`] - Answers requests that run too long with a 504
+//! - [`track_in_flight`] - Counts the requests being handled, for graceful shutdown
+//! - [`extract_real_ip`] - Resolves the client IP behind trusted reverse proxies
+//! - `RedisRateLimiter` - Shares the rate limit between instances through Redis
+//!   (`redis` feature)
+//! - [`render_html_errors`] - Serves 404 and 5xx errors to browsers as HTML pages
+//! - [`negotiate_problem_details`] - Serves errors as RFC 7807 Problem Details on request
+//! - [`attach_request_id`] - Adds the `x-request-id` to JSON error bodies
+//! - [`set_security_headers`] - Adds clickjacking, sniffing and CSP protection headers
+//! - [`verify_webhook_auth`] - Rejects webhook callbacks without a valid HMAC signature
+//!
+//! ## Usage
+//!
+//! Middleware is applied to routes using Axum's middleware system:
+//!
+//! ```rust,ignore
+//! use axum::{Router, middleware::from_fn_with_state};
+//! use url_shortener_ztm_lib::middleware::check_api_key;
+//!
+//! // Apply middleware to specific routes
+//! let protected_routes = Router::new()
+//!     .route("/api/shorten", post(shorten_handler))
+//!     .route_layer(from_fn_with_state(state, check_api_key));
+//! ```
+pub mod api_version;
+pub mod catch_panic;
+pub mod html_errors;
+pub mod problem_details;
+pub mod real_ip;
+#[cfg(feature = "redis")]
+pub mod redis_rate_limit;
+pub mod request_id;
+pub mod security_headers;
+pub mod webhook_auth;
+
+pub use api_version::check_accept_version;
+pub use catch_panic::handle_panic;
+pub use html_errors::render_html_errors;
+pub use problem_details::negotiate_problem_details;
+pub use real_ip::{RealIp, RealIpKeyExtractor, extract_real_ip};
+#[cfg(feature = "redis")]
+pub use redis_rate_limit::RedisRateLimiter;
+pub use request_id::attach_request_id;
+pub use security_headers::set_security_headers;
+pub use webhook_auth::{verify_webhook_auth, verify_webhook_signature};
+
+use crate::core::extractors::auth_user::AuthenticatedUser;
+use crate::errors::ApiError;
+use crate::state::AppState;
+
+use axum::{
+    extract::{FromRequestParts, Request, State},
+    http::HeaderMap,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::{net::IpAddr, time::Duration};
+use uuid::Uuid;
+
+/// Middleware function that validates API key authentication.
+///
+/// This middleware checks for a valid API key in the `x-api-key` header of incoming requests.
+/// If a valid API key is provided, the request is allowed to proceed to the handler.
+/// If no key or an invalid key is provided, an unauthorized error response is returned.
+///
+/// # Authentication Process
+///
+/// 1. Extracts the `x-api-key` header from the request
+/// 2. Parses the header value as a UUID
+/// 3. Checks the provided key against the set of configured API keys
+/// 4. Allows the request to proceed if keys match, otherwise returns 401 Unauthorized
+///
+/// # Arguments
+///
+/// * `State(state)` - Application state containing the configured API keys
+/// * `request` - The incoming HTTP request
+/// * `next` - The next middleware or handler in the chain
+///
+/// # Returns
+///
+/// Returns either:
+/// - The response from the next handler (if authentication succeeds)
+/// - An unauthorized error response (if authentication fails)
+///
+/// # Error Response
+///
+/// When authentication fails, returns a JSON error response:
+///
+/// ```json
+/// {
+///   "success": false,
+///   "message": "Unauthorized",
+///   "status": 401,
+///   "time": "2025-01-18T12:00:00Z",
+///   "data": null
+/// }
+/// ```
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use axum::{Router, middleware::from_fn_with_state, routing::post};
+/// use url_shortener_ztm_lib::middleware::check_api_key;
+/// use url_shortener_ztm_lib::state::AppState;
+///
+/// // Apply to protected routes
+/// let app = Router::new()
+///     .route("/api/shorten", post(post_shorten))
+///     .route_layer(from_fn_with_state(app_state, check_api_key));
+/// ```
+///
+/// # Security Notes
+///
+/// - API keys should be kept secure and not logged
+/// - Keys are compared in constant time, so response timing does not reveal
+///   how close a guessed key is to a real one
+/// - Keys configured as bcrypt hashes are checked with `bcrypt::verify`; see
+///   [`ApiKeyStore`](crate::core::security::api_key::ApiKeyStore)
+/// - Consider using HTTPS in production to prevent key interception
+/// - Several keys may be configured so clients can be given separate
+///   credentials and keys can be rotated without downtime
+pub async fn check_api_key(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if has_api_key(&state, request.headers()).await {
+        next.run(request).await
+    } else {
+        ApiError::Unauthorized("Unauthorized".to_string()).into_response()
+    }
+}
+
+/// Who a request let through by [`check_api_key_or_user`] is acting as.
+///
+/// Inserted as a request extension for the handler to read.
+#[derive(Clone, Debug)]
+pub enum Caller {
+    /// A configured API key, which may act on every URL
+    ApiKey,
+    /// A signed-in account, which may only act on the URLs it owns
+    User(AuthenticatedUser),
+}
+
+/// Middleware function that accepts an API key or a signed-in user.
+///
+/// Works like [`check_api_key`], except that a request without a valid key
+/// is still let through when it carries a valid access token, as a cookie or
+/// a bearer token. The handler learns which one was used from the [`Caller`]
+/// extension; an API key wins when both are sent.
+///
+/// # Error Response
+///
+/// Returns `401 Unauthorized` when neither a known API key nor a valid
+/// access token is present.
+pub async fn check_api_key_or_user(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if has_api_key(&state, request.headers()).await {
+        let mut request = request;
+        request.extensions_mut().insert(Caller::ApiKey);
+        return next.run(request).await;
+    }
+
+    let (mut parts, body) = request.into_parts();
+    match AuthenticatedUser::from_request_parts(&mut parts, &state).await {
+        Ok(user) => {
+            let mut request = Request::from_parts(parts, body);
+            request.extensions_mut().insert(Caller::User(user));
+            next.run(request).await
+        }
+        Err(_) => ApiError::Unauthorized("Unauthorized".to_string()).into_response(),
+    }
+}
+
+/// Middleware function that lets API keys and admin accounts through.
+///
+/// Must run inside [`check_api_key_or_user`], whose [`Caller`] extension it
+/// reads. An API key always passes; a signed-in user only passes when their
+/// id is listed in `application.admin_user_ids`.
+///
+/// # Error Response
+///
+/// Returns `403 Forbidden` for a signed-in user who is not an admin, and
+/// `401 Unauthorized` when no [`Caller`] was recorded.
+pub async fn require_admin(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let allowed = match request.extensions().get::<Caller>() {
+        Some(Caller::ApiKey) => true,
+        Some(Caller::User(user)) => state
+            .config()
+            .application
+            .admin_user_ids
+            .contains(&user.user_id),
+        None => {
+            return ApiError::Unauthorized("Unauthorized".to_string()).into_response();
+        }
+    };
+
+    if allowed {
+        next.run(request).await
+    } else {
+        ApiError::Forbidden("Admin access required".to_string()).into_response()
+    }
+}
+
+/// Whether the request's `x-api-key` header holds a configured API key.
+async fn has_api_key(state: &AppState, headers: &HeaderMap) -> bool {
+    let provided_api_key = headers
+        .get("x-api-key")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|s| Uuid::parse_str(s.trim()).ok());
+
+    match provided_api_key {
+        Some(key) => state.api_keys().contains(&key).await,
+        None => false,
+    }
+}
+
+/// Middleware function that bounds how long a request may take.
+///
+/// The rest of the stack is raced against `application.request_timeout_ms`.
+/// If the deadline passes first, the in-flight handler future is dropped and
+/// a `504 Gateway Timeout` error response is returned instead, so a stalled
+/// database cannot hold connections open indefinitely.
+///
+/// # Error Response
+///
+/// ```json
+/// {
+///   "success": false,
+///   "message": "Request timed out",
+///   "status": 504,
+///   "time": "2025-01-18T12:00:00Z",
+///   "data": null
+/// }
+/// ```
+pub async fn enforce_request_timeout(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let timeout = Duration::from_millis(state.config().application.request_timeout_ms);
+
+    match tokio::time::timeout(timeout, next.run(request)).await {
+        Ok(response) => response,
+        Err(_) => {
+            tracing::warn!("Request exceeded timeout of {:?}", timeout);
+            ApiError::Timeout("Request timed out".to_string()).into_response()
+        }
+    }
+}
+
+/// Middleware function that counts the requests being handled.
+///
+/// Holds a permit of [`AppState::in_flight`] until the response is ready, so
+/// [`AppState::in_flight_requests`] can report how many requests a shutdown
+/// is still waiting for. The semaphore has more permits than there can be
+/// requests, so acquiring one never waits.
+pub async fn track_in_flight(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let _permit = state.in_flight().clone().acquire_owned().await;
+    next.run(request).await
+}
+
+// src/middleware/client_meta.rs
+
+#[derive(Clone, Debug)]
+pub struct ClientMeta {
+    pub ip: Option<IpAddr>,
+    pub user_agent: Option<String>,
+}
+
+pub async fn capture_client_meta(mut req: Request, next: Next) -> Response {
+    // Resolved by `extract_real_ip`, which only believes forwarding headers
+    // sent by a trusted proxy
+    let ip = req.extensions().get::<RealIp>().map(|ip| ip.0);
+
+    let ua = req
+        .headers()
+        .get("user-agent")
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string());
+
+    req.extensions_mut()
+        .insert(ClientMeta { ip, user_agent: ua });
+    next.run(req).await
+}
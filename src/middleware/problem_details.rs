@@ -0,0 +1,83 @@
+//! # Problem Details Negotiation
+//!
+//! Error responses are rendered with the [`ApiResponse`](crate::response::ApiResponse)
+//! envelope by default. Clients whose `Accept` header prefers
+//! `application/problem+json` get an RFC 7807 [`ProblemDetail`] body instead,
+//! with a `type` URI built from `application.problem_type_base`.
+//!
+//! [`ProblemDetail`]: crate::response::ProblemDetail
+
+use crate::errors::ErrorProblem;
+use crate::response::PROBLEM_JSON;
+use crate::state::AppState;
+
+use axum::{
+    extract::{Request, State},
+    http::header,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+/// Middleware function that re-renders [`ApiError`](crate::errors::ApiError)
+/// responses as Problem Details when the client asks for them.
+///
+/// Only responses carrying an [`ErrorProblem`] extension are rewritten, so
+/// successful responses and errors from other layers pass through unchanged.
+pub async fn negotiate_problem_details(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let wants_problem = request
+        .headers()
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(prefers_problem_json);
+    let path = request.uri().path().to_string();
+
+    let response = next.run(request).await;
+    if !wants_problem {
+        return response;
+    }
+
+    match response.extensions().get::<ErrorProblem>() {
+        Some(problem) => {
+            let detail = problem
+                .clone()
+                .into_problem_detail(&state.config().application.problem_type_base(), &path);
+            let (mut parts, _) = response.into_parts();
+            let mut rendered = detail.into_response();
+            // Keep headers set by inner layers, such as rate limit or CORS headers
+            parts.headers.remove(header::CONTENT_TYPE);
+            parts.headers.remove(header::CONTENT_LENGTH);
+            parts.headers.remove(header::CONTENT_ENCODING);
+            rendered.headers_mut().extend(parts.headers);
+            rendered
+        }
+        None => response,
+    }
+}
+
+/// Whether an `Accept` header ranks `application/problem+json` at least as
+/// high as `application/json`.
+fn prefers_problem_json(accept: &str) -> bool {
+    let mut problem_q = 0.0_f32;
+    let mut json_q = 0.0_f32;
+
+    for range in accept.split(',') {
+        let mut params = range.split(';');
+        let media_type = params.next().unwrap_or("").trim().to_ascii_lowercase();
+        let q = params
+            .filter_map(|p| p.trim().strip_prefix("q="))
+            .find_map(|q| q.parse::<f32>().ok())
+            .unwrap_or(1.0);
+
+        if media_type == PROBLEM_JSON {
+            problem_q = problem_q.max(q);
+        } else if media_type == "application/json" {
+            json_q = json_q.max(q);
+        }
+    }
+
+    problem_q > 0.0 && problem_q >= json_q
+}
@@ -0,0 +1,174 @@
+//! # Real Client IP
+//!
+//! Behind a reverse proxy the TCP peer is always the proxy, so the client's
+//! address has to be taken from a forwarding header instead. Those headers
+//! are only believed when the peer is listed in `application.trusted_proxies`;
+//! otherwise any client could pick its own address and dodge the rate limiter.
+//!
+//! Headers are checked in priority order:
+//!
+//! 1. `CF-Connecting-IP`
+//! 2. `X-Real-IP`
+//! 3. First entry of `X-Forwarded-For`
+//! 4. The TCP peer address
+
+use crate::state::AppState;
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::HeaderMap,
+    middleware::Next,
+    response::Response,
+};
+use ipnet::IpNet;
+use std::net::{IpAddr, SocketAddr};
+use tower_governor::{GovernorError, key_extractor::KeyExtractor};
+
+/// The resolved client IP address, stored as a request extension by
+/// [`extract_real_ip`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RealIp(pub IpAddr);
+
+impl std::fmt::Display for RealIp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Resolves the client IP from the forwarding headers and the TCP peer.
+///
+/// The headers are ignored unless `peer` falls within one of `trusted_proxies`.
+pub fn resolve_client_ip(headers: &HeaderMap, peer: IpAddr, trusted_proxies: &[IpNet]) -> IpAddr {
+    if !trusted_proxies.iter().any(|net| net.contains(&peer)) {
+        return peer;
+    }
+
+    let header_ip = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|s| s.split(',').next())
+            .and_then(|s| s.trim().parse::<IpAddr>().ok())
+    };
+
+    header_ip("cf-connecting-ip")
+        .or_else(|| header_ip("x-real-ip"))
+        .or_else(|| header_ip("x-forwarded-for"))
+        .unwrap_or(peer)
+}
+
+/// Middleware function that stores the client's [`RealIp`] on the request.
+///
+/// Must wrap every layer that reads the extension, including the rate
+/// limiter keyed by [`RealIpKeyExtractor`].
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use axum::{Extension, Router, middleware::from_fn_with_state};
+/// use url_shortener_ztm_lib::middleware::{RealIp, extract_real_ip};
+///
+/// async fn handler(Extension(ip): Extension<RealIp>) -> String {
+///     ip.to_string()
+/// }
+///
+/// let app = Router::new()
+///     .route("/ip", get(handler))
+///     .layer(from_fn_with_state(app_state, extract_real_ip));
+/// ```
+pub async fn extract_real_ip(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    // A dual-stack listener reports IPv4 peers as `::ffff:a.b.c.d`
+    let ip = resolve_client_ip(
+        request.headers(),
+        addr.ip().to_canonical(),
+        &state.config().application.trusted_proxies,
+    );
+    request.extensions_mut().insert(RealIp(ip));
+    next.run(request).await
+}
+
+/// Rate limiter key extractor that keys on the [`RealIp`] extension rather
+/// than the TCP peer, so clients behind a trusted proxy get their own budget.
+#[derive(Clone, Copy, Debug)]
+pub struct RealIpKeyExtractor;
+
+impl KeyExtractor for RealIpKeyExtractor {
+    type Key = IpAddr;
+
+    fn extract<T>(&self, req: &axum::http::Request<T>) -> Result<Self::Key, GovernorError> {
+        req.extensions()
+            .get::<RealIp>()
+            .map(|ip| ip.0)
+            .ok_or(GovernorError::UnableToExtractKey)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    const PROXY: IpAddr = IpAddr::V4(std::net::Ipv4Addr::new(10, 0, 0, 1));
+
+    fn trusted() -> Vec<IpNet> {
+        vec!["10.0.0.0/8".parse().unwrap()]
+    }
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in pairs {
+            map.insert(*name, HeaderValue::from_static(value));
+        }
+        map
+    }
+
+    #[test]
+    fn first_forwarded_for_entry_is_used_from_trusted_proxy() {
+        let h = headers(&[("x-forwarded-for", "203.0.113.7, 10.0.0.2")]);
+        assert_eq!(
+            resolve_client_ip(&h, PROXY, &trusted()),
+            "203.0.113.7".parse::<IpAddr>().unwrap()
+        );
+    }
+
+    #[test]
+    fn headers_are_checked_in_priority_order() {
+        let h = headers(&[
+            ("x-forwarded-for", "203.0.113.1"),
+            ("x-real-ip", "203.0.113.2"),
+            ("cf-connecting-ip", "203.0.113.3"),
+        ]);
+        assert_eq!(
+            resolve_client_ip(&h, PROXY, &trusted()),
+            "203.0.113.3".parse::<IpAddr>().unwrap()
+        );
+
+        let h = headers(&[
+            ("x-forwarded-for", "203.0.113.1"),
+            ("x-real-ip", "203.0.113.2"),
+        ]);
+        assert_eq!(
+            resolve_client_ip(&h, PROXY, &trusted()),
+            "203.0.113.2".parse::<IpAddr>().unwrap()
+        );
+    }
+
+    #[test]
+    fn headers_from_untrusted_peer_are_ignored() {
+        let peer: IpAddr = "198.51.100.9".parse().unwrap();
+        let h = headers(&[("x-forwarded-for", "203.0.113.7")]);
+        assert_eq!(resolve_client_ip(&h, peer, &trusted()), peer);
+        assert_eq!(resolve_client_ip(&h, PROXY, &[]), PROXY);
+    }
+
+    #[test]
+    fn unparseable_header_falls_back_to_peer() {
+        let h = headers(&[("x-forwarded-for", "not-an-ip")]);
+        assert_eq!(resolve_client_ip(&h, PROXY, &trusted()), PROXY);
+    }
+}
@@ -0,0 +1,170 @@
+//! # Redis Rate Limiting
+//!
+//! The in-memory governor keeps its counters inside one process, so a
+//! client spreading requests over several instances gets a budget from
+//! each of them. [`RedisRateLimiter`] keeps the counters in Redis instead,
+//! so every instance pointed at the same Redis enforces one shared limit.
+//!
+//! Each client IP gets a fixed one-second window, counted under the key
+//! `rate:{ip}:{window}` with `INCR` and expired shortly after the window
+//! closes. Requests beyond `burst_size` in a window are rejected with
+//! `429 Too Many Requests`.
+//!
+//! When Redis cannot be reached the request is let through and a warning
+//! is logged, so an outage of the limiter does not take the API down.
+
+use crate::configuration::RateLimitingSettings;
+use crate::errors::ApiError;
+use crate::middleware::RealIp;
+
+use anyhow::{Context, Result};
+use axum::{
+    body::Body,
+    http::{HeaderValue, Request},
+    response::{IntoResponse, Response},
+};
+use futures_util::future::BoxFuture;
+use redis::aio::ConnectionManager;
+use std::{
+    convert::Infallible,
+    net::IpAddr,
+    task::{Context as TaskContext, Poll},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tower::{Layer, Service};
+
+/// How long a window's counter outlives the window, in seconds, so a
+/// request arriving on the boundary still finds it.
+const KEY_TTL_SECONDS: i64 = 2;
+
+/// Tower layer rate limiting requests per client IP through Redis.
+///
+/// Keys on the [`RealIp`] extension, so it must sit inside
+/// [`extract_real_ip`](crate::middleware::extract_real_ip). Requests
+/// without the extension are let through.
+#[derive(Clone)]
+pub struct RedisRateLimiter {
+    connection: ConnectionManager,
+    limit: u64,
+}
+
+impl RedisRateLimiter {
+    /// Connects to `rate_limiting.redis_url` and builds the layer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no URL is configured or Redis cannot be reached.
+    pub async fn connect(settings: &RateLimitingSettings) -> Result<Self> {
+        let url = settings
+            .redis_url
+            .as_deref()
+            .context("rate_limiting.redis_url is not set")?;
+        let client = redis::Client::open(url).context("Invalid rate_limiting.redis_url")?;
+        let connection = ConnectionManager::new(client)
+            .await
+            .context("Failed to connect to the rate limiting Redis")?;
+
+        Ok(Self {
+            connection,
+            limit: u64::from(settings.burst_size),
+        })
+    }
+}
+
+impl<S> Layer<S> for RedisRateLimiter {
+    type Service = RedisRateLimit<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RedisRateLimit {
+            inner,
+            limiter: self.clone(),
+        }
+    }
+}
+
+/// Service produced by [`RedisRateLimiter`].
+#[derive(Clone)]
+pub struct RedisRateLimit<S> {
+    inner: S,
+    limiter: RedisRateLimiter,
+}
+
+impl<S> Service<Request<Body>> for RedisRateLimit<S>
+where
+    S: Service<Request<Body>, Response = Response, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = BoxFuture<'static, Result<Response, Infallible>>;
+
+    fn poll_ready(&mut self, cx: &mut TaskContext<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        // The clone may not be ready, so keep the polled service and hand
+        // the clone back in its place
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let mut connection = self.limiter.connection.clone();
+        let limit = self.limiter.limit;
+        let ip = request.extensions().get::<RealIp>().map(|ip| ip.0);
+
+        Box::pin(async move {
+            let Some(ip) = ip else {
+                return inner.call(request).await;
+            };
+
+            match count_request(&mut connection, ip).await {
+                Ok(count) if count > limit => Ok(too_many_requests(limit)),
+                Ok(count) => {
+                    let mut response = inner.call(request).await?;
+                    set_limit_headers(&mut response, limit, limit - count);
+                    Ok(response)
+                }
+                Err(e) => {
+                    tracing::warn!("Redis rate limiter unavailable, letting request through: {e}");
+                    inner.call(request).await
+                }
+            }
+        })
+    }
+}
+
+/// Counts a request from `ip` in the current window, returning the
+/// window's count so far.
+async fn count_request(connection: &mut ConnectionManager, ip: IpAddr) -> redis::RedisResult<u64> {
+    let window = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let key = format!("rate:{}:{}", ip, window);
+
+    let (count,): (u64,) = redis::pipe()
+        .atomic()
+        .incr(&key, 1)
+        .expire(&key, KEY_TTL_SECONDS)
+        .ignore()
+        .query_async(connection)
+        .await?;
+    Ok(count)
+}
+
+/// Renders a rejection as [`ApiError::RateLimited`] with the same headers
+/// the in-memory limiter sends.
+fn too_many_requests(limit: u64) -> Response {
+    let mut response =
+        ApiError::RateLimited("Too many requests, retry in 1s".to_string()).into_response();
+    let headers = response.headers_mut();
+    headers.insert("retry-after", HeaderValue::from_static("1"));
+    headers.insert("x-ratelimit-after", HeaderValue::from_static("1"));
+    set_limit_headers(&mut response, limit, 0);
+    response
+}
+
+fn set_limit_headers(response: &mut Response, limit: u64, remaining: u64) {
+    let headers = response.headers_mut();
+    headers.insert("x-ratelimit-limit", HeaderValue::from(limit));
+    headers.insert("x-ratelimit-remaining", HeaderValue::from(remaining));
+}
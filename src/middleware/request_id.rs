@@ -0,0 +1,53 @@
+//! # Request ID in Error Bodies
+//!
+//! `SetRequestIdLayer` gives every request an `x-request-id`, which is echoed
+//! as a response header and recorded in the trace span. This middleware also
+//! writes it into the body of [`ApiError`](crate::errors::ApiError) responses
+//! as `request_id`, so an error body can be matched to its log lines even
+//! when the headers were not kept, and records it on the request's Sentry
+//! scope for the events [`ApiError::Internal`](crate::errors::ApiError::Internal)
+//! reports.
+
+use crate::errors::ErrorProblem;
+use crate::telemetry::error_reporting::record_request_id;
+
+use axum::{body::Body, extract::Request, http::header, middleware::Next, response::Response};
+use tower_http::request_id::RequestId;
+
+/// Middleware function that adds the request ID to error envelopes.
+///
+/// Must run inside `SetRequestIdLayer`, and inside Sentry's `NewSentryLayer`
+/// so the request ID lands on the request's own scope. Only responses carrying an
+/// [`ErrorProblem`] extension are rewritten; their extensions are kept so
+/// outer middleware can still re-render them.
+pub async fn attach_request_id(request: Request, next: Next) -> Response {
+    let request_id = request
+        .extensions()
+        .get::<RequestId>()
+        .and_then(|id| id.header_value().to_str().ok())
+        .map(str::to_owned);
+    if let Some(request_id) = &request_id {
+        record_request_id(request_id);
+    }
+
+    let response = next.run(request).await;
+
+    let (Some(request_id), Some(problem)) = (
+        request_id,
+        response.extensions().get::<ErrorProblem>().cloned(),
+    ) else {
+        return response;
+    };
+
+    let mut envelope = problem.envelope();
+    envelope.request_id = Some(request_id);
+    let Ok(body) = serde_json::to_vec(&envelope) else {
+        return response;
+    };
+
+    let (mut parts, _) = response.into_parts();
+    // The body is replaced uncompressed, so drop the old length and encoding
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts.headers.remove(header::CONTENT_ENCODING);
+    Response::from_parts(parts, Body::from(body))
+}
@@ -0,0 +1,89 @@
+//! # Security Headers
+//!
+//! Adds browser hardening headers to every response:
+//!
+//! - `X-Frame-Options: DENY` - the admin panel cannot be framed (clickjacking)
+//! - `X-Content-Type-Options: nosniff` - responses are not MIME-sniffed
+//! - `Referrer-Policy: strict-origin-when-cross-origin`
+//! - `Content-Security-Policy` - [`DEFAULT_CONTENT_SECURITY_POLICY`], or
+//!   `application.csp_header` when configured
+//! - `X-API-Version` - [`API_VERSION`], the version of the API answering
+//!
+//! Headers already set by a handler are left untouched, so routes with
+//! special needs can opt out: short-code redirects send
+//! `Referrer-Policy: no-referrer` and the Swagger UI page sends a CSP that
+//! allows its CDN assets.
+
+use crate::response::API_VERSION;
+use crate::state::AppState;
+
+use axum::{
+    extract::{Request, State},
+    http::{HeaderName, HeaderValue, header},
+    middleware::Next,
+    response::Response,
+};
+
+/// Content Security Policy used when `application.csp_header` is not set.
+///
+/// Scripts are limited to this origin plus the CDN serving Chart.js for the
+/// admin dashboard; styles to this origin plus Google Fonts.
+pub const DEFAULT_CONTENT_SECURITY_POLICY: &str = "default-src 'self'; \
+     script-src 'self' https://cdn.jsdelivr.net; \
+     style-src 'self' 'unsafe-inline' https://fonts.googleapis.com; \
+     font-src 'self' https://fonts.gstatic.com; \
+     img-src 'self' data:; \
+     frame-ancestors 'none'";
+
+/// Name of the response header carrying [`API_VERSION`].
+pub const X_API_VERSION: HeaderName = HeaderName::from_static("x-api-version");
+
+/// Middleware function that adds security headers to every response.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use axum::{Router, middleware::from_fn_with_state};
+/// use url_shortener_ztm_lib::middleware::set_security_headers;
+///
+/// let app = Router::new()
+///     .route("/admin", get(get_admin_dashboard))
+///     .layer(from_fn_with_state(app_state, set_security_headers));
+/// ```
+pub async fn set_security_headers(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let mut response = next.run(request).await;
+
+    // Validated at startup, so a configured policy always parses
+    let csp = state
+        .config()
+        .application
+        .csp_header
+        .as_deref()
+        .and_then(|csp| HeaderValue::from_str(csp).ok())
+        .unwrap_or(HeaderValue::from_static(DEFAULT_CONTENT_SECURITY_POLICY));
+
+    let defaults: [(HeaderName, HeaderValue); 5] = [
+        (header::X_FRAME_OPTIONS, HeaderValue::from_static("DENY")),
+        (
+            header::X_CONTENT_TYPE_OPTIONS,
+            HeaderValue::from_static("nosniff"),
+        ),
+        (
+            header::REFERRER_POLICY,
+            HeaderValue::from_static("strict-origin-when-cross-origin"),
+        ),
+        (header::CONTENT_SECURITY_POLICY, csp),
+        (X_API_VERSION, HeaderValue::from_static(API_VERSION)),
+    ];
+
+    let headers = response.headers_mut();
+    for (name, value) in defaults {
+        headers.entry(name).or_insert(value);
+    }
+
+    response
+}
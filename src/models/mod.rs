@@ -1,19 +1,388 @@
-use sqlx::FromRow;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::error::BoxDynError;
+use sqlx::{Database, Decode, Encode, FromRow, encode::IsNull};
+use std::fmt;
+use std::str::FromStr;
+use utoipa::ToSchema;
+use uuid::Uuid;
 
-#[derive(Debug, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct UrlRecord {
+    #[schema(example = "AbC123")]
     pub code: String,
+    #[schema(example = "https://www.example.com/")]
     pub url: String,
+    /// The account that shortened the URL, if it was shortened while signed in
+    pub owner_id: Option<Uuid>,
 }
 
-#[derive(sqlx::FromRow)]
+/// One row of a `GET /api/urls/export` backup.
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct ExportRecord {
+    pub code: String,
+    pub url: String,
+    pub created_at: DateTime<Utc>,
+    #[sqlx(try_from = "i64")]
+    pub click_count: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct UpsertResult {
     pub id: i64,
     pub created: bool,
 }
 
-#[derive(sqlx::FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Urls {
     pub id: i64,
     pub code: String,
 }
+
+/// Outcome of one row of [`crate::database::UrlDatabase::insert_urls_batch`].
+///
+/// `code` is the code the URL is stored under, which is an existing code when
+/// the URL was already present (`created == false`).
+#[derive(Debug, Clone)]
+pub struct BatchInsertResult {
+    pub code: String,
+    pub url: String,
+    pub created: bool,
+}
+
+/// Everything known about a short code without following it.
+///
+/// `code` is the code that was looked up (primary code or alias); the remaining
+/// fields describe the target URL row it resolves to.
+///
+/// `owner_id` and `password_hash` are never serialized, so they come back as
+/// `None` when a serialized value is deserialized again.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct UrlMetadata {
+    pub code: String,
+    pub url: String,
+    pub created_at: DateTime<Utc>,
+    #[sqlx(try_from = "i64")]
+    pub click_count: u64,
+    #[sqlx(try_from = "i64")]
+    pub preview_views: u64,
+    pub redirect_type: String,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub is_active: bool,
+    /// The account that shortened the URL; kept off the public info endpoint
+    #[serde(skip_serializing)]
+    pub owner_id: Option<Uuid>,
+    /// Argon2id hash of the passphrase guarding the URL, if it has one
+    #[serde(skip_serializing)]
+    pub password_hash: Option<Vec<u8>>,
+    /// Destination for visitors on mobile devices, if it differs from `url`
+    pub mobile_url: Option<String>,
+    /// Clicks after which the URL is deactivated, if it has a limit
+    pub max_clicks: Option<i64>,
+    /// When the URL reached `max_clicks` and stopped redirecting
+    pub deactivated_at: Option<DateTime<Utc>>,
+    /// When the destination was last updated or the URL last clicked, or
+    /// `created_at` if neither has happened
+    pub updated_at: DateTime<Utc>,
+    /// Where the URL is in its lifecycle; `expired` as soon as `expires_at`
+    /// has passed, even before anything stored it
+    pub status: UrlStatus,
+}
+
+/// Lifecycle of a stored URL, kept in the `status` column of `urls`.
+///
+/// `Active` and `PasswordProtected` URLs redirect, the latter only after the
+/// password is entered. `Expired` and `MaxClicksReached` URLs answer
+/// `410 Gone`. `Deleted` URLs keep their row and clicks but are invisible to
+/// every lookup, and their codes are never issued again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum UrlStatus {
+    Active,
+    Expired,
+    Deleted,
+    PasswordProtected,
+    MaxClicksReached,
+}
+
+impl UrlStatus {
+    /// The value stored in the `status` column.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            UrlStatus::Active => "active",
+            UrlStatus::Expired => "expired",
+            UrlStatus::Deleted => "deleted",
+            UrlStatus::PasswordProtected => "password_protected",
+            UrlStatus::MaxClicksReached => "max_clicks_reached",
+        }
+    }
+
+    /// Whether a URL in this status still redirects.
+    pub fn resolves(self) -> bool {
+        matches!(self, UrlStatus::Active | UrlStatus::PasswordProtected)
+    }
+}
+
+impl fmt::Display for UrlStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for UrlStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "active" => Ok(UrlStatus::Active),
+            "expired" => Ok(UrlStatus::Expired),
+            "deleted" => Ok(UrlStatus::Deleted),
+            "password_protected" => Ok(UrlStatus::PasswordProtected),
+            "max_clicks_reached" => Ok(UrlStatus::MaxClicksReached),
+            other => Err(format!("unknown URL status {:?}", other)),
+        }
+    }
+}
+
+// Stored as TEXT on both backends, so the encoding is borrowed from `str`
+impl<DB: Database> sqlx::Type<DB> for UrlStatus
+where
+    str: sqlx::Type<DB>,
+{
+    fn type_info() -> DB::TypeInfo {
+        <str as sqlx::Type<DB>>::type_info()
+    }
+
+    fn compatible(ty: &DB::TypeInfo) -> bool {
+        <str as sqlx::Type<DB>>::compatible(ty)
+    }
+}
+
+impl<'r, DB: Database> Decode<'r, DB> for UrlStatus
+where
+    &'r str: Decode<'r, DB>,
+{
+    fn decode(value: <DB as Database>::ValueRef<'r>) -> Result<Self, BoxDynError> {
+        Ok(<&str as Decode<DB>>::decode(value)?.parse()?)
+    }
+}
+
+impl<'q, DB: Database> Encode<'q, DB> for UrlStatus
+where
+    &'q str: Encode<'q, DB>,
+{
+    fn encode_by_ref(
+        &self,
+        buf: &mut <DB as Database>::ArgumentBuffer<'q>,
+    ) -> Result<IsNull, BoxDynError> {
+        <&str as Encode<DB>>::encode_by_ref(&self.as_str(), buf)
+    }
+}
+
+/// A URL whose owner is due a reminder that it expires soon.
+#[derive(Debug, Clone, FromRow)]
+pub struct ExpiringUrl {
+    /// Primary short code of the URL
+    pub code: String,
+    pub url: String,
+    pub expires_at: DateTime<Utc>,
+    pub owner_id: Uuid,
+}
+
+/// Clicks on a URL within one hour or day, for the analytics chart.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ClickBucket {
+    /// Start of the bucket
+    pub timestamp: DateTime<Utc>,
+    #[sqlx(try_from = "i64")]
+    pub count: u64,
+}
+
+/// Number of clicks that arrived from one referrer origin.
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct ReferrerCount {
+    /// `scheme://host[:port]` of the referring pages
+    pub referrer_origin: String,
+    #[sqlx(try_from = "i64")]
+    pub count: u64,
+}
+
+/// Number of clicks from one country.
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct CountryCount {
+    /// ISO 3166-1 alpha-2 code, e.g. `"US"`
+    pub country_code: String,
+    #[sqlx(try_from = "i64")]
+    pub count: u64,
+}
+
+/// One recorded redirect, for the click history of a short code.
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct ClickRecord {
+    pub id: i64,
+    /// Primary code of the URL, whichever of its codes was followed
+    #[schema(example = "AbC123")]
+    pub code: String,
+    /// Client IP, if known
+    #[schema(example = "203.0.113.7")]
+    pub ip: Option<String>,
+    /// `User-Agent` header sent with the click, if any
+    pub user_agent: Option<String>,
+    /// `scheme://host[:port]` of the referring page, if any
+    pub referrer_origin: Option<String>,
+    /// ISO 3166-1 alpha-2 country of `ip`, with geolocation enabled
+    pub country_code: Option<String>,
+    /// `"mobile"`, `"desktop"` or `"unknown"`
+    #[schema(example = "desktop")]
+    pub device_type: Option<String>,
+    pub clicked_at: DateTime<Utc>,
+}
+
+/// Totals over the whole database, for `GET /admin/stats`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct DatabaseStats {
+    /// Stored URLs, not counting aliases
+    pub total_urls: u64,
+    /// Recorded redirects across every URL
+    pub total_clicks: u64,
+    /// Size of the database on disk
+    pub size_bytes: u64,
+}
+
+/// One statement of a database maintenance run, see
+/// [`UrlDatabase::run_maintenance`](crate::database::UrlDatabase::run_maintenance).
+#[derive(Debug, Clone)]
+pub struct MaintenanceStep {
+    /// The statement that was run, e.g. `"VACUUM"`
+    pub operation: &'static str,
+    /// How long the statement took
+    pub duration: std::time::Duration,
+    /// Why the statement failed, if it did
+    pub error: Option<String>,
+}
+
+/// One destination of an A/B tested URL.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct AbTarget {
+    pub url: String,
+    /// Percentage of redirects sent to this destination
+    #[sqlx(try_from = "i16")]
+    pub weight: u8,
+}
+
+/// Clicks sent to one A/B variant of a URL.
+#[derive(Debug, Clone, Serialize, FromRow, ToSchema)]
+pub struct VariantCount {
+    /// Position of the variant among the URL's A/B targets
+    #[sqlx(try_from = "i16")]
+    pub variant: u8,
+    pub url: String,
+    #[sqlx(try_from = "i64")]
+    pub count: u64,
+}
+
+/// Totals shown next to the click chart of a URL.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ClickSummary {
+    pub total_clicks: u64,
+    pub unique_ips: u64,
+    /// Clicks from a `User-Agent` classified as desktop
+    pub desktop_clicks: u64,
+    /// Clicks from a `User-Agent` classified as mobile
+    pub mobile_clicks: u64,
+    /// Most frequent referrer origins, most clicks first
+    pub top_referrers: Vec<ReferrerCount>,
+    /// Clicks per A/B variant, in variant order; empty unless A/B tested
+    pub variants: Vec<VariantCount>,
+    /// Clicks per country, most clicks first; left out unless `geolocation`
+    /// is enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_countries: Option<Vec<CountryCount>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::de::DeserializeOwned;
+
+    // serializing, deserializing and serializing again gives the same JSON
+    fn assert_roundtrips<T: Serialize + DeserializeOwned>(value: &T) {
+        let json = serde_json::to_string(value).unwrap();
+        let back: T = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&back).unwrap(), json);
+    }
+
+    #[test]
+    fn url_record_roundtrips() {
+        assert_roundtrips(&UrlRecord {
+            code: "abc123".to_string(),
+            url: "https://example.com/".to_string(),
+            owner_id: Some(Uuid::new_v4()),
+        });
+        assert_roundtrips(&UrlRecord {
+            code: "abc123".to_string(),
+            url: "https://example.com/".to_string(),
+            owner_id: None,
+        });
+    }
+
+    #[test]
+    fn upsert_result_and_urls_roundtrip() {
+        assert_roundtrips(&UpsertResult {
+            id: 42,
+            created: true,
+        });
+        assert_roundtrips(&Urls {
+            id: 42,
+            code: "abc123".to_string(),
+        });
+    }
+
+    #[test]
+    fn url_metadata_roundtrips_without_its_private_fields() {
+        let created_at = "2025-01-02T03:04:05Z".parse::<DateTime<Utc>>().unwrap();
+        let metadata = UrlMetadata {
+            code: "abc123".to_string(),
+            url: "https://example.com/".to_string(),
+            created_at,
+            click_count: 7,
+            preview_views: 2,
+            redirect_type: "permanent".to_string(),
+            expires_at: Some(created_at + chrono::Duration::days(30)),
+            is_active: true,
+            owner_id: Some(Uuid::new_v4()),
+            password_hash: Some(vec![1, 2, 3]),
+            mobile_url: Some("https://m.example.com/".to_string()),
+            max_clicks: Some(100),
+            deactivated_at: None,
+            updated_at: created_at,
+            status: UrlStatus::PasswordProtected,
+        };
+
+        assert_roundtrips(&metadata);
+        let back: UrlMetadata =
+            serde_json::from_str(&serde_json::to_string(&metadata).unwrap()).unwrap();
+        assert_eq!(back.created_at, created_at);
+        assert_eq!(back.owner_id, None);
+        assert_eq!(back.password_hash, None);
+        assert_eq!(back.status, UrlStatus::PasswordProtected);
+    }
+
+    #[test]
+    fn url_status_parses_what_it_stores() {
+        for status in [
+            UrlStatus::Active,
+            UrlStatus::Expired,
+            UrlStatus::Deleted,
+            UrlStatus::PasswordProtected,
+            UrlStatus::MaxClicksReached,
+        ] {
+            assert_eq!(status.as_str().parse::<UrlStatus>().unwrap(), status);
+            assert_eq!(
+                serde_json::to_string(&status).unwrap(),
+                format!("\"{}\"", status)
+            );
+        }
+        assert!("inactive".parse::<UrlStatus>().is_err());
+    }
+}
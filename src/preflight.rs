@@ -0,0 +1,124 @@
+//! # Pre-flight Checks
+//!
+//! Verifies, before the server accepts its first connection, that what
+//! requests depend on is in place, so a broken deployment fails at startup
+//! instead of answering every request with an error.
+//!
+//! [`run_checks`] is called by [`Application::build`](crate::startup::Application::build)
+//! once the database is connected and migrated. Every check logs
+//! `Pre-flight: <check> OK` or `Pre-flight: <check> FAILED: <reason>`; the
+//! first failure stops the startup.
+
+use crate::configuration::{BloomStorageBackend, Settings};
+use crate::database::UrlDatabase;
+use crate::infrastructure::geo::MaxMindCountryLookup;
+use crate::shortcode::snapshot_store::build_snapshot_store;
+use crate::templates::TemplateState;
+
+use anyhow::{Context, Result};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Directory the `/static` assets are served from.
+pub const STATIC_DIR: &str = "static";
+
+/// Runs every pre-flight check that applies to `settings`:
+///
+/// - `database`: the database answers a health check
+/// - `templates`: every Tera template under `templates.dir` compiles
+/// - `static files`: the [`STATIC_DIR`] directory exists
+/// - `geolocation`: with `geolocation.enabled`, the GeoLite2 database at
+///   `geolocation.db_path` can be read
+/// - `bloom storage`: with `bloom.storage.backend: s3`, the bucket can be
+///   reached
+///
+/// # Errors
+///
+/// Returns the first failed check, naming it.
+pub async fn run_checks(settings: &Settings, database: &Arc<dyn UrlDatabase>) -> Result<()> {
+    report("database", database.health_check().await)?;
+    report("templates", TemplateState::load(&settings.templates))?;
+    report("static files", check_directory(Path::new(STATIC_DIR)))?;
+    if settings.geolocation.enabled {
+        report(
+            "geolocation",
+            MaxMindCountryLookup::open(&settings.geolocation.db_path),
+        )?;
+    }
+    if settings.bloom.storage.backend == BloomStorageBackend::S3 {
+        let store = report(
+            "bloom storage",
+            build_snapshot_store(&settings.bloom.storage, database).await,
+        )?;
+        report("bloom storage", store.check().await)?;
+    }
+    Ok(())
+}
+
+/// Logs the outcome of the pre-flight check `check` and returns its value,
+/// or its error with the check named.
+///
+/// # Errors
+///
+/// Returns `result`'s error, with context.
+pub fn report<T, E>(check: &str, result: Result<T, E>) -> Result<T>
+where
+    E: Into<anyhow::Error>,
+{
+    match result {
+        Ok(value) => {
+            tracing::info!("Pre-flight: {} OK", check);
+            Ok(value)
+        }
+        Err(e) => {
+            let e = e.into();
+            tracing::error!("Pre-flight: {} FAILED: {:#}", check, e);
+            Err(e).with_context(|| format!("Pre-flight check {} failed", check))
+        }
+    }
+}
+
+fn check_directory(path: &Path) -> Result<()> {
+    anyhow::ensure!(
+        path.is_dir(),
+        "directory {} not found (relative to {})",
+        path.display(),
+        std::env::current_dir()
+            .map(|dir| dir.display().to_string())
+            .unwrap_or_else(|_| "the working directory".to_string())
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_test::traced_test;
+
+    #[test]
+    #[traced_test]
+    fn passing_check_is_logged_as_ok() {
+        report("static files", check_directory(Path::new(STATIC_DIR))).unwrap();
+
+        assert!(logs_contain("Pre-flight: static files OK"));
+    }
+
+    #[test]
+    #[traced_test]
+    fn failing_check_is_logged_and_named_in_the_error() {
+        let error = report(
+            "geolocation",
+            MaxMindCountryLookup::open("missing.mmdb").map(|_| ()),
+        )
+        .unwrap_err();
+
+        assert!(logs_contain(
+            "Pre-flight: geolocation FAILED: Failed to open GeoIP database"
+        ));
+        assert!(
+            error
+                .to_string()
+                .contains("Pre-flight check geolocation failed")
+        );
+    }
+}
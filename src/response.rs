@@ -13,6 +13,7 @@
 //!   "success": true,
 //!   "message": "Optional message",
 //!   "status": 200,
+//!   "version": "0.26",
 //!   "time": "2025-01-18T12:00:00Z",
 //!   "data": { /* Response data */ }
 //! }
@@ -27,6 +28,7 @@
 //!   "success": true,
 //!   "message": "ok",
 //!   "status": 200,
+//!   "version": "0.26",
 //!   "time": "2025-01-18T12:00:00Z",
 //!   "data": "https://localhost:8000/AbC123"
 //! }
@@ -41,11 +43,18 @@
 //!   "success": false,
 //!   "message": "URL not found",
 //!   "status": 404,
+//!   "version": "0.26",
 //!   "time": "2025-01-18T12:00:00Z",
 //!   "data": null
 //! }
 //! ```
 //!
+//! ## Versioning
+//!
+//! `version` is [`API_VERSION`], the major and minor part of the crate
+//! version. It is also sent as the `X-API-Version` header; see `VERSIONING.md`
+//! for what a version change means to clients.
+//!
 //! ## Usage
 //!
 //! ```rust,no_run
@@ -56,7 +65,7 @@
 //! let response = ApiResponse::success("Hello, world!");
 //!
 //! // Create error response
-//! let error = ApiResponse::<()>::error("Not found", StatusCode::NOT_FOUND);
+//! let error = ApiResponse::<()>::error("Not found", StatusCode::NOT_FOUND, Some("NOT_FOUND"));
 //!
 //! // Use in handlers
 //! fn handler() -> ApiResult<String> {
@@ -70,6 +79,30 @@ use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use chrono::{DateTime, Utc};
 use serde::Serialize;
+use utoipa::ToSchema;
+
+/// Version of the API, the `major.minor` part of the crate version.
+///
+/// Sent as `version` in every [`ApiResponse`] and as the `X-API-Version`
+/// response header, and the only value accepted in `Accept-Version`.
+pub const API_VERSION: &str = major_minor(env!("CARGO_PKG_VERSION"));
+
+/// Cuts a `major.minor.patch` version down to `major.minor`.
+const fn major_minor(version: &str) -> &str {
+    let bytes = version.as_bytes();
+    let mut dots = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'.' {
+            dots += 1;
+            if dots == 2 {
+                return version.split_at(i).0;
+            }
+        }
+        i += 1;
+    }
+    version
+}
 
 /// Convenience type alias for API handler results.
 ///
@@ -105,6 +138,7 @@ pub type ApiResult<T> = Result<ApiResponse<T>, ApiError>;
 ///   "success": true,
 ///   "message": "Optional message",
 ///   "status": 200,
+///   "version": "0.26",
 ///   "time": "2025-01-18T12:00:00Z",
 ///   "data": { /* Response data */ }
 /// }
@@ -120,12 +154,12 @@ pub type ApiResult<T> = Result<ApiResponse<T>, ApiError>;
 /// let success = ApiResponse::success("Hello, world!");
 ///
 /// // Error response
-/// let error = ApiResponse::<()>::error("Not found", StatusCode::NOT_FOUND);
+/// let error = ApiResponse::<()>::error("Not found", StatusCode::NOT_FOUND, Some("NOT_FOUND"));
 ///
 /// // Custom status response
 /// let created = ApiResponse::success_with_status(StatusCode::CREATED, "Created");
 /// ```
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ApiResponse<T> {
     /// Indicates whether the request was successful
@@ -135,11 +169,22 @@ pub struct ApiResponse<T> {
     pub message: Option<String>,
     /// HTTP status code
     pub status: u16,
+    /// API version that produced the response, see [`API_VERSION`]
+    #[serde(rename = "version")]
+    #[schema(value_type = String, example = "0.26")]
+    pub api_version: &'static str,
     /// Timestamp when the response was generated
     pub time: DateTime<Utc>,
     /// Optional response data (omitted for error responses)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub data: Option<T>,
+    /// Stable machine-readable error code (error responses only)
+    #[serde(rename = "error_code", skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>)]
+    pub error_code: Option<&'static str>,
+    /// `x-request-id` of the request, for matching an error to its log lines
+    #[serde(rename = "request_id", skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
 }
 
 impl<T> ApiResponse<T> {
@@ -198,8 +243,11 @@ impl<T> ApiResponse<T> {
             success: true,
             message: Some("ok".into()),
             status: status.as_u16(),
+            api_version: API_VERSION,
             time: Utc::now(),
             data: Some(data),
+            error_code: None,
+            request_id: None,
         }
     }
 
@@ -212,6 +260,7 @@ impl<T> ApiResponse<T> {
     ///
     /// * `message` - The error message to include in the response
     /// * `status` - The HTTP status code to use
+    /// * `error_code` - Optional stable code serialized as `error_code`
     ///
     /// # Returns
     ///
@@ -223,18 +272,22 @@ impl<T> ApiResponse<T> {
     /// use url_shortener_ztm_lib::response::ApiResponse;
     /// use axum::http::StatusCode;
     ///
-    /// let response = ApiResponse::<()>::error("Not found", StatusCode::NOT_FOUND);
+    /// let response = ApiResponse::<()>::error("Not found", StatusCode::NOT_FOUND, Some("NOT_FOUND"));
     /// assert!(!response.success);
     /// assert_eq!(response.status, 404);
     /// assert_eq!(response.message, Some("Not found".to_string()));
+    /// assert_eq!(response.error_code, Some("NOT_FOUND"));
     /// ```
-    pub fn error(message: &str, status: StatusCode) -> Self {
+    pub fn error(message: &str, status: StatusCode, error_code: Option<&'static str>) -> Self {
         Self {
             success: false,
             message: Some(message.to_string()),
             status: status.as_u16(),
+            api_version: API_VERSION,
             time: Utc::now(),
             data: None,
+            error_code,
+            request_id: None,
         }
     }
 }
@@ -269,3 +322,100 @@ impl<T: Serialize> IntoResponse for ApiResponse<T> {
         (status, Json(self)).into_response()
     }
 }
+
+/// One page of a listing, returned as the `data` of an [`ApiResponse`].
+///
+/// # JSON Structure
+///
+/// ```json
+/// {
+///   "items": [ /* Page items */ ],
+///   "page": 1,
+///   "per_page": 20,
+///   "has_more": false
+/// }
+/// ```
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PaginatedResponse<T> {
+    /// Items on this page
+    pub items: Vec<T>,
+    /// 1-based page number
+    pub page: u64,
+    /// Maximum number of items per page
+    pub per_page: u64,
+    /// Whether another page follows this one
+    pub has_more: bool,
+}
+
+impl<T> PaginatedResponse<T> {
+    /// Builds a page from up to `per_page + 1` fetched items.
+    ///
+    /// Fetching one extra row tells whether a next page exists without a
+    /// separate count query; the extra row is dropped here.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use url_shortener_ztm_lib::response::PaginatedResponse;
+    ///
+    /// let page = PaginatedResponse::from_overfetch(vec![1, 2, 3], 1, 2);
+    /// assert_eq!(page.items, vec![1, 2]);
+    /// assert!(page.has_more);
+    /// ```
+    pub fn from_overfetch(mut items: Vec<T>, page: u64, per_page: u64) -> Self {
+        let has_more = items.len() as u64 > per_page;
+        items.truncate(per_page as usize);
+        Self {
+            items,
+            page,
+            per_page,
+            has_more,
+        }
+    }
+}
+
+/// Media type of [`ProblemDetail`] responses.
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// RFC 7807 Problem Details error body, served instead of the [`ApiResponse`]
+/// error envelope when a client's `Accept` header prefers `application/problem+json`.
+///
+/// # JSON Structure
+///
+/// ```json
+/// {
+///   "type": "https://s.example.com/problems/not-found",
+///   "title": "Not Found",
+///   "status": 404,
+///   "detail": "URL not found",
+///   "instance": "/AbC123"
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize)]
+pub struct ProblemDetail {
+    /// URI identifying the problem type
+    #[serde(rename = "type")]
+    pub r#type: String,
+    /// Short summary of the problem type, the status code's reason phrase
+    pub title: String,
+    /// HTTP status code
+    pub status: u16,
+    /// Explanation specific to this occurrence
+    pub detail: String,
+    /// Request path the problem occurred on
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+}
+
+impl IntoResponse for ProblemDetail {
+    fn into_response(self) -> Response {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+
+        (
+            status,
+            [(axum::http::header::CONTENT_TYPE, PROBLEM_JSON)],
+            Json(self),
+        )
+            .into_response()
+    }
+}
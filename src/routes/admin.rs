@@ -3,72 +3,523 @@
 // Admin panel routes for user management
 
 // dependencies
+use crate::configuration::RedactedSettings;
+use crate::database::{CLICK_BUCKETS, DatabaseError};
 use crate::errors::ApiError;
+use crate::middleware::html_errors::prefers_html;
+use crate::response::ApiResponse;
+use crate::routes::redirect::forget_cached_redirects;
+use crate::shortcode::bloom_filter::rebuild_bloom_from_db;
 use crate::state::AppState;
-use crate::templates::get_templates;
-use axum::{extract::State, response::Html};
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, header},
+    response::{Html, IntoResponse, Redirect, Response},
+};
+use axum_extra::extract::cookie::{Cookie, CookieJar};
 use axum_macros::debug_handler;
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
 use tera::Context;
+use utoipa::{IntoParams, ToSchema};
 
 // handler for the admin dashboard
+#[utoipa::path(
+    get,
+    path = "/admin",
+    tag = "Admin",
+    summary = "Admin dashboard page",
+    responses(
+        (status = 200, description = "Rendered page", body = String, content_type = "text/html"),
+        (status = 500, description = "Template rendering failed", body = ApiError),
+    )
+)]
 #[debug_handler]
-pub async fn get_admin_dashboard(_state: State<AppState>) -> Result<Html<String>, ApiError> {
+pub async fn get_admin_dashboard(State(state): State<AppState>) -> Result<Html<String>, ApiError> {
     let mut context = Context::new();
     context.insert("title", "Dashboard");
-    let body = get_templates().render("admin.html", &context)?;
+    let body = state.templates().render("admin.html", &context)?;
     Ok(Html(body))
 }
 
 // handler for user profile management
+#[utoipa::path(
+    get,
+    path = "/admin/profile",
+    tag = "Admin",
+    summary = "User profile page",
+    responses(
+        (status = 200, description = "Rendered page", body = String, content_type = "text/html"),
+        (status = 500, description = "Template rendering failed", body = ApiError),
+    )
+)]
 #[debug_handler]
-pub async fn get_user_profile(_state: State<AppState>) -> Result<Html<String>, ApiError> {
+pub async fn get_user_profile(State(state): State<AppState>) -> Result<Html<String>, ApiError> {
     let mut context = Context::new();
     context.insert("title", "User Profile");
-    let body = get_templates().render("profile.html", &context)?;
+    let body = state.templates().render("profile.html", &context)?;
     Ok(Html(body))
 }
 
 // handler for user login
+#[utoipa::path(
+    get,
+    path = "/admin/login",
+    tag = "Admin",
+    summary = "Login page",
+    responses(
+        (status = 200, description = "Rendered page", body = String, content_type = "text/html"),
+        (status = 500, description = "Template rendering failed", body = ApiError),
+    )
+)]
 #[debug_handler]
-pub async fn get_login(_state: State<AppState>) -> Result<Html<String>, ApiError> {
+pub async fn get_login(State(state): State<AppState>) -> Result<Html<String>, ApiError> {
     let mut context = Context::new();
     context.insert("title", "Login");
-    let body = get_templates().render("login.html", &context)?;
+    let body = state.templates().render("login.html", &context)?;
     Ok(Html(body))
 }
 
 // handler for user registration
+#[utoipa::path(
+    get,
+    path = "/admin/register",
+    tag = "Admin",
+    summary = "Registration page",
+    responses(
+        (status = 200, description = "Rendered page", body = String, content_type = "text/html"),
+        (status = 500, description = "Template rendering failed", body = ApiError),
+    )
+)]
 #[debug_handler]
-pub async fn get_register(_state: State<AppState>) -> Result<Html<String>, ApiError> {
+pub async fn get_register(State(state): State<AppState>) -> Result<Html<String>, ApiError> {
     let mut context = Context::new();
     context.insert("title", "Register");
-    let body = get_templates().render("register.html", &context)?;
+    let body = state.templates().render("register.html", &context)?;
     Ok(Html(body))
 }
 
 // handler for manage users
+#[utoipa::path(
+    get,
+    path = "/admin/users",
+    tag = "Admin",
+    summary = "User management page",
+    responses(
+        (status = 200, description = "Rendered page", body = String, content_type = "text/html"),
+        (status = 500, description = "Template rendering failed", body = ApiError),
+    )
+)]
 #[debug_handler]
-pub async fn get_users(_state: State<AppState>) -> Result<Html<String>, ApiError> {
+pub async fn get_users(State(state): State<AppState>) -> Result<Html<String>, ApiError> {
     let mut context = Context::new();
     context.insert("title", "Manage Users");
-    let body = get_templates().render("users.html", &context)?;
+    let body = state.templates().render("users.html", &context)?;
     Ok(Html(body))
 }
 
-// handler for manage urls
+/// Cookie carrying a one-time message for `GET /admin/shorten`: the outcome
+/// of the shorten form.
+pub const FLASH_COOKIE: &str = "flash";
+
+// handler for the shorten form, which posts to /api/shorten and is sent back here
+#[utoipa::path(
+    get,
+    path = "/admin/shorten",
+    tag = "Admin",
+    summary = "Shorten form page",
+    description = "A form that shortens a URL without JavaScript, showing the outcome of the last submission once.",
+    security(("ApiKeyAuth" = []), ("BearerAuth" = [])),
+    responses(
+        (status = 200, description = "Rendered page", body = String, content_type = "text/html"),
+        (status = 401, description = "Missing or invalid API key and access token", body = ApiError),
+    )
+)]
+#[debug_handler]
+#[tracing::instrument(name = "admin_shorten", skip(state, jar))]
+pub async fn get_admin_shorten(
+    State(state): State<AppState>,
+    jar: CookieJar,
+) -> Result<(CookieJar, Html<String>), ApiError> {
+    let mut context = Context::new();
+    context.insert("title", "Shorten a URL");
+    // Shown once: the cookie is cleared by this response. The jar has
+    // already percent-decoded the value
+    let flash = jar.get(FLASH_COOKIE).map(|c| c.value().to_string());
+    context.insert("flash", &flash);
+    let body = state.templates().render("admin/shorten.html", &context)?;
+    let jar = match flash {
+        Some(_) => jar.remove(Cookie::build(FLASH_COOKIE).path("/admin")),
+        None => jar,
+    };
+    Ok((jar, Html(body)))
+}
+
+/// Rows shown per page of `GET /admin/urls`.
+pub const ADMIN_URLS_PAGE_SIZE: u64 = 25;
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AdminUrlsParams {
+    /// Only show URLs whose destination, code or alias contains this text
+    pub q: Option<String>,
+    /// 1-based page number
+    pub page: Option<u64>,
+}
+
+// handler for manage urls: one page of stored URLs with their metadata
+#[utoipa::path(
+    get,
+    path = "/admin/urls",
+    tag = "Admin",
+    summary = "URL management page",
+    description = "One page of stored URLs with their metadata, optionally filtered by `q`.",
+    params(AdminUrlsParams),
+    security(("ApiKeyAuth" = []), ("BearerAuth" = [])),
+    responses(
+        (status = 200, description = "Rendered page", body = String, content_type = "text/html"),
+        (status = 401, description = "Missing or invalid API key and access token", body = ApiError),
+        (status = 403, description = "The signed-in user is not listed in `admin_user_ids`", body = ApiError),
+        (status = 500, description = "Database error", body = ApiError),
+    )
+)]
 #[debug_handler]
-pub async fn get_urls(_state: State<AppState>) -> Result<Html<String>, ApiError> {
+#[tracing::instrument(name = "admin_urls", skip(state))]
+pub async fn get_admin_urls(
+    State(state): State<AppState>,
+    Query(params): Query<AdminUrlsParams>,
+) -> Result<Html<String>, ApiError> {
+    let q = params.q.unwrap_or_default().trim().to_string();
+    let page = params.page.unwrap_or(1).max(1);
+    let offset = (page - 1) * ADMIN_URLS_PAGE_SIZE;
+
+    // One extra row tells whether there is a next page
+    let mut records = if q.is_empty() {
+        state
+            .database()
+            .list_urls(offset, ADMIN_URLS_PAGE_SIZE + 1, None)
+            .await
+    } else {
+        state
+            .database()
+            .search_urls(&q, offset, ADMIN_URLS_PAGE_SIZE + 1)
+            .await
+    }
+    .map_err(internal_error)?;
+    let has_next = records.len() as u64 > ADMIN_URLS_PAGE_SIZE;
+    records.truncate(ADMIN_URLS_PAGE_SIZE as usize);
+
+    let mut urls = Vec::with_capacity(records.len());
+    for record in records {
+        match state.database().get_url_with_metadata(&record.code).await {
+            Ok(metadata) => urls.push(metadata),
+            // Deleted since the page was listed
+            Err(DatabaseError::NotFound) => {}
+            Err(e) => return Err(internal_error(e)),
+        }
+    }
+
     let mut context = Context::new();
     context.insert("title", "Manage URLs");
-    let body = get_templates().render("urls.html", &context)?;
+    context.insert("urls", &urls);
+    context.insert("q", &q);
+    context.insert("page", &page);
+    context.insert("has_next", &has_next);
+    let body = state.templates().render("urls.html", &context)?;
+    Ok(Html(body))
+}
+
+// handler for the delete buttons of the url list; returns to the list afterwards
+#[utoipa::path(
+    post,
+    path = "/admin/urls/{id}/delete",
+    tag = "Admin",
+    summary = "Delete a URL from the admin panel",
+    params(("id" = String, Path, description = "Short code or alias to delete")),
+    security(("ApiKeyAuth" = []), ("BearerAuth" = [])),
+    responses(
+        (status = 303, description = "Deleted; redirects back to `/admin/urls`"),
+        (status = 401, description = "Missing or invalid API key and access token", body = ApiError),
+        (status = 403, description = "The signed-in user is not listed in `admin_user_ids`", body = ApiError),
+        (status = 404, description = "URL not found", body = ApiError),
+        (status = 500, description = "Database error", body = ApiError),
+    )
+)]
+#[debug_handler]
+#[tracing::instrument(name = "admin_delete_url", skip(state))]
+pub async fn post_admin_delete_url(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Redirect, ApiError> {
+    // Read before deleting, to drop the cached aliases of the code; metadata
+    // also comes back for URLs that expired or reached their click limit
+    let url = state
+        .database()
+        .get_url_with_metadata(&id)
+        .await
+        .ok()
+        .map(|meta| meta.url);
+    match state.database().delete_url(&id).await {
+        Ok(()) => {
+            forget_cached_redirects(state.redirect_cache(), &id, url.as_deref());
+            tracing::warn!(
+                code = %id,
+                "short code deleted; the Bloom filter may report it as present until rebuilt"
+            );
+            Ok(Redirect::to("/admin/urls"))
+        }
+        Err(e) => Err(not_found_or_internal(e)),
+    }
+}
+
+/// Referrers listed on the per-URL analytics page.
+pub const ANALYTICS_TOP_REFERRERS: u8 = 10;
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct UrlAnalyticsParams {
+    /// Chart bucket size, one of [`CLICK_BUCKETS`] (default `hour`)
+    pub bucket: Option<String>,
+}
+
+// handler for the analytics of a single short code: click timeline, unique IPs and referrers
+#[utoipa::path(
+    get,
+    path = "/admin/urls/{id}/analytics",
+    tag = "Admin",
+    summary = "Analytics page for a URL",
+    params(
+        ("id" = String, Path, description = "Short code or alias"),
+        UrlAnalyticsParams,
+    ),
+    security(("ApiKeyAuth" = []), ("BearerAuth" = [])),
+    responses(
+        (status = 200, description = "Rendered page", body = String, content_type = "text/html"),
+        (status = 401, description = "Missing or invalid API key and access token", body = ApiError),
+        (status = 403, description = "The signed-in user is not listed in `admin_user_ids`", body = ApiError),
+        (status = 404, description = "URL not found", body = ApiError),
+        (status = 422, description = "Unknown bucket size", body = ApiError),
+    )
+)]
+#[debug_handler]
+#[tracing::instrument(name = "admin_url_analytics", skip(state))]
+pub async fn get_admin_url_analytics(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(params): Query<UrlAnalyticsParams>,
+) -> Result<Html<String>, ApiError> {
+    let bucket = params.bucket.unwrap_or_else(|| "hour".to_string());
+    if !CLICK_BUCKETS.contains(&bucket.as_str()) {
+        return Err(ApiError::Unprocessable(format!(
+            "bucket must be one of {}",
+            CLICK_BUCKETS.join(", ")
+        )));
+    }
+
+    let url = state
+        .database()
+        .get_url_with_metadata(&id)
+        .await
+        .map_err(not_found_or_internal)?;
+    let buckets = state
+        .database()
+        .get_clicks_over_time(&id, &bucket)
+        .await
+        .map_err(not_found_or_internal)?;
+    let summary = state
+        .database()
+        .get_click_summary(&id, ANALYTICS_TOP_REFERRERS)
+        .await
+        .map_err(not_found_or_internal)?;
+
+    let mut context = Context::new();
+    context.insert("title", &format!("Analytics for {}", id));
+    context.insert("url", &url);
+    context.insert("bucket", &bucket);
+    context.insert("buckets", &buckets);
+    context.insert("summary", &summary);
+    let body = state.templates().render("url_analytics.html", &context)?;
     Ok(Html(body))
 }
 
+/// System totals reported by `GET /admin/stats`.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct AdminStats {
+    /// Stored URLs, not counting aliases
+    pub total_urls: u64,
+    /// Recorded redirects across every URL
+    pub total_clicks: u64,
+    /// Size of the database on disk
+    pub db_size_bytes: u64,
+    /// Estimated fill level of the short code Bloom filter, in percent
+    pub bloom_utilization_pct: f64,
+    /// Share of redirects answered from the redirect cache, from `0.0` to
+    /// `1.0`; `null` before the first redirect
+    pub cache_hit_rate: Option<f64>,
+    pub uptime_seconds: u64,
+}
+
+// handler for the operator statistics: a JSON envelope for API clients, a
+// page that reloads itself every 30 seconds for browsers
+#[utoipa::path(
+    get,
+    path = "/admin/stats",
+    tag = "Admin",
+    summary = "System statistics",
+    description = "System totals in the JSON envelope; browsers that prefer `text/html` get a page that reloads itself every 30 seconds instead.",
+    security(("ApiKeyAuth" = []), ("BearerAuth" = [])),
+    responses(
+        (status = 200, description = "Statistics", body = ApiResponse<AdminStats>),
+        (status = 401, description = "Missing or invalid API key and access token", body = ApiError),
+        (status = 403, description = "The signed-in user is not listed in `admin_user_ids`", body = ApiError),
+        (status = 500, description = "Database error", body = ApiError),
+    )
+)]
+#[debug_handler]
+#[tracing::instrument(name = "admin_stats", skip(state, headers))]
+pub async fn get_admin_stats(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let database = state.database().get_stats().await.map_err(internal_error)?;
+    let stats = AdminStats {
+        total_urls: database.total_urls,
+        total_clicks: database.total_clicks,
+        db_size_bytes: database.size_bytes,
+        bloom_utilization_pct: (state.blooms().utilization() * 1000.0).round() / 10.0,
+        cache_hit_rate: state.redirect_cache_counters().hit_rate(),
+        uptime_seconds: state.uptime().elapsed().as_secs(),
+    };
+
+    let wants_html = headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(prefers_html);
+    if !wants_html {
+        return Ok(ApiResponse::success(stats).into_response());
+    }
+
+    let mut context = Context::new();
+    context.insert("title", "System Stats");
+    context.insert(
+        "cache_hit_pct",
+        &stats
+            .cache_hit_rate
+            .map(|rate| (rate * 1000.0).round() / 10.0),
+    );
+    context.insert("stats", &stats);
+    let body = state.templates().render("admin/stats.html", &context)?;
+    Ok(Html(body).into_response())
+}
+
+/// Outcome of `POST /admin/bloom/rebuild`.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BloomRebuildResult {
+    pub rebuilt: bool,
+    /// Short codes and aliases in the rebuilt filter
+    pub items_added: usize,
+    /// Time taken, including waiting for a rebuild already running
+    pub duration_ms: u64,
+}
+
+// handler for rebuilding the Bloom filter from every stored code, e.g. after
+// a database restore left it missing codes
+#[utoipa::path(
+    post,
+    path = "/admin/bloom/rebuild",
+    tag = "Admin",
+    summary = "Rebuild the Bloom filter",
+    description = "Rebuilds the short code Bloom filter from every stored code, e.g. after a database restore.",
+    security(("ApiKeyAuth" = []), ("BearerAuth" = [])),
+    responses(
+        (status = 200, description = "Filter rebuilt", body = ApiResponse<BloomRebuildResult>),
+        (status = 401, description = "Missing or invalid API key and access token", body = ApiError),
+        (status = 403, description = "The signed-in user is not listed in `admin_user_ids`", body = ApiError),
+        (status = 500, description = "Rebuild failed", body = ApiError),
+    )
+)]
+#[debug_handler]
+#[tracing::instrument(name = "admin_bloom_rebuild", skip(state))]
+pub async fn post_rebuild_bloom(
+    State(state): State<AppState>,
+) -> Result<ApiResponse<BloomRebuildResult>, ApiError> {
+    let started = Instant::now();
+    let items_added = rebuild_bloom_from_db(
+        state.database(),
+        state.bloom_snapshot_store(),
+        state.blooms(),
+        &state.config().bloom,
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!("Bloom filter rebuild failed: {:#}", e);
+        ApiError::Internal(e.to_string())
+    })?;
+
+    Ok(ApiResponse::success(BloomRebuildResult {
+        rebuilt: true,
+        items_added,
+        duration_ms: started.elapsed().as_millis() as u64,
+    }))
+}
+
+// handler for the effective configuration, secrets redacted: YAML for
+// clients that ask for it, otherwise a string in the JSON envelope
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/config",
+    tag = "Admin",
+    summary = "Effective configuration",
+    description = "The effective configuration as YAML with secrets redacted, as a string in the JSON envelope. Clients whose `Accept` header mentions `yaml` get the YAML document itself as `text/yaml`.",
+    security(("ApiKeyAuth" = []), ("BearerAuth" = [])),
+    responses(
+        (status = 200, description = "Redacted configuration", body = ApiResponse<String>),
+        (status = 401, description = "Missing or invalid API key and access token", body = ApiError),
+    )
+)]
+#[debug_handler]
+#[tracing::instrument(name = "admin_config", skip(state, headers))]
+pub async fn get_admin_config(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    let yaml = RedactedSettings(state.config()).to_yaml();
+
+    let wants_yaml = headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|accept| accept.contains("yaml"));
+    if wants_yaml {
+        ([(header::CONTENT_TYPE, "text/yaml; charset=utf-8")], yaml).into_response()
+    } else {
+        ApiResponse::success(yaml).into_response()
+    }
+}
+
+fn not_found_or_internal(e: DatabaseError) -> ApiError {
+    match e {
+        DatabaseError::NotFound => ApiError::NotFound("URL not found".to_string()),
+        e => internal_error(e),
+    }
+}
+
+fn internal_error(e: DatabaseError) -> ApiError {
+    tracing::error!("Database error: {}", e);
+    ApiError::Internal(e.to_string())
+}
+
 // handler for analytics
+#[utoipa::path(
+    get,
+    path = "/admin/analytics",
+    tag = "Admin",
+    summary = "Analytics page",
+    responses(
+        (status = 200, description = "Rendered page", body = String, content_type = "text/html"),
+        (status = 500, description = "Template rendering failed", body = ApiError),
+    )
+)]
 #[debug_handler]
-pub async fn get_analytics(_state: State<AppState>) -> Result<Html<String>, ApiError> {
+pub async fn get_analytics(State(state): State<AppState>) -> Result<Html<String>, ApiError> {
     let mut context = Context::new(); // <-- Make it mutable
     context.insert("title", "Analytics"); // <-- ADD THIS LINE
-    let body = get_templates().render("analytics.html", &context)?;
+    let body = state.templates().render("analytics.html", &context)?;
     Ok(Html(body))
 }
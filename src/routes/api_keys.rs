@@ -0,0 +1,80 @@
+//! # API Key Handlers
+//!
+//! This module provides the protected endpoint that lists the API keys the
+//! service accepts, so operators can see which clients hold credentials
+//! without the keys themselves being exposed.
+
+use crate::errors::ApiError;
+use crate::response::ApiResponse;
+use crate::state::AppState;
+use axum::extract::State;
+use axum_macros::debug_handler;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// A configured API key as shown to operators.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiKeyInfo {
+    /// The key with everything but its first group masked out
+    #[schema(example = "e4125dd1-****-****-****-************")]
+    pub key: String,
+    /// Name of the client the key belongs to, if one was configured
+    #[schema(example = "development")]
+    pub label: Option<String>,
+}
+
+/// Lists the configured API keys with their labels.
+///
+/// Keys are masked (e.g. `e4125dd1-****-****-****-************`) so the listing
+/// can identify a credential without disclosing it.
+///
+/// # Endpoint
+///
+/// `GET /api/v1/auth/api-keys` (protected - requires API key)
+///
+/// # Response Format
+///
+/// ```json
+/// {
+///   "success": true,
+///   "message": "ok",
+///   "status": 200,
+///   "time": "2025-01-18T12:00:00Z",
+///   "data": [
+///     { "key": "e4125dd1-****-****-****-************", "label": "development" }
+///   ]
+/// }
+/// ```
+///
+/// # Status Codes
+///
+/// - `200 OK` - Keys listed
+/// - `401 Unauthorized` - Missing or invalid API key
+#[utoipa::path(
+    get,
+    path = "/api/v1/auth/api-keys",
+    tag = "Authentication",
+    summary = "List API keys",
+    description = "List the configured API keys, masked, with the label of the client each belongs to",
+    security(("ApiKeyAuth" = [])),
+    responses(
+        (status = 200, description = "API keys listed", body = ApiResponse<Vec<ApiKeyInfo>>),
+        (status = 401, description = "Missing or invalid API key", body = ApiError),
+    )
+)]
+#[debug_handler]
+#[tracing::instrument(name = "list_api_keys", skip(state))]
+pub async fn list_api_keys(State(state): State<AppState>) -> ApiResponse<Vec<ApiKeyInfo>> {
+    let keys = state
+        .config()
+        .application
+        .api_keys
+        .iter()
+        .map(|k| ApiKeyInfo {
+            key: k.masked(),
+            label: k.label.clone(),
+        })
+        .collect();
+
+    ApiResponse::success(keys)
+}
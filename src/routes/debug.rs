@@ -0,0 +1,15 @@
+//! # Debug Handlers
+//!
+//! Routes that exist only to exercise the server's failure handling. They are
+//! compiled for the crate's own tests and with the `testing` feature, and
+//! never into a release build.
+
+/// Handler that always panics, to check that a panicking handler is answered
+/// with a 500 instead of taking the connection, or the server, down.
+///
+/// # Endpoint
+///
+/// `GET /api/debug/panic`
+pub async fn get_debug_panic() -> &'static str {
+    panic!("deliberate panic from GET /api/debug/panic")
+}
@@ -0,0 +1,104 @@
+//! # URL Deletion Handler
+//!
+//! This module provides the protected endpoint used to remove a short link.
+
+use crate::database::DatabaseError;
+use crate::errors::ApiError;
+use crate::middleware::Caller;
+use crate::routes::redirect::forget_cached_redirects;
+use crate::routes::urls::authorize_url_owner;
+use crate::state::AppState;
+use axum::Extension;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum_macros::debug_handler;
+
+/// Deletes a short code or alias.
+///
+/// Deleting a primary code also removes its aliases, tags and click history;
+/// deleting an alias leaves the URL and its other codes in place.
+///
+/// An API key may delete any code; a signed-in user only codes of URLs they own.
+///
+/// # Endpoint
+///
+/// `DELETE /api/urls/{id}` (protected - requires API key or access token)
+///
+/// # Arguments
+///
+/// * `State(state)` - Application state containing database connection
+/// * `Extension(caller)` - The API key or user the request is made as
+/// * `Path(id)` - Short code or alias extracted from the URL path
+///
+/// # Bloom Filter
+///
+/// The short-to-long Bloom filter cannot remove entries, so a deleted code
+/// keeps passing the filter until it is rebuilt. Lookups for it then fall
+/// through to the database, which answers `404 Not Found`.
+///
+/// # Redirect Cache
+///
+/// The code, and every alias deleted with it, is dropped from the redirect
+/// cache so it stops redirecting at once.
+///
+/// # Status Codes
+///
+/// - `204 No Content` - Code deleted
+/// - `401 Unauthorized` - Missing or invalid API key and access token
+/// - `403 Forbidden` - The signed-in user does not own the URL
+/// - `404 Not Found` - Unknown short code (including one already deleted)
+/// - `500 Internal Server Error` - Database error occurred
+///
+/// # Examples
+///
+/// ```bash
+/// curl -X DELETE -H "x-api-key: your-api-key" \
+///   http://localhost:8000/api/urls/AbC123
+/// ```
+#[utoipa::path(
+    delete,
+    path = "/api/urls/{id}",
+    tag = "URL Management",
+    summary = "Delete Short URL",
+    description = "Delete a short code (with its aliases, tags and clicks) or a single alias. A signed-in user may only delete codes of URLs they own.",
+    params(("id" = String, Path, description = "Short code or alias")),
+    security(("ApiKeyAuth" = []), ("BearerAuth" = [])),
+    responses(
+        (status = 204, description = "Code deleted"),
+        (status = 401, description = "Missing or invalid API key and access token", body = ApiError),
+        (status = 403, description = "The signed-in user does not own the URL", body = ApiError),
+        (status = 404, description = "Unknown short code", body = ApiError),
+    )
+)]
+#[debug_handler]
+#[tracing::instrument(name = "delete_url", skip(state))]
+pub async fn delete_url(
+    State(state): State<AppState>,
+    Extension(caller): Extension<Caller>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    authorize_url_owner(&state, &caller, &id).await?;
+    // Read before deleting, to drop the cached aliases of the code; metadata
+    // also comes back for URLs that expired or reached their click limit
+    let url = state
+        .database()
+        .get_url_with_metadata(&id)
+        .await
+        .ok()
+        .map(|meta| meta.url);
+    match state.database().delete_url(&id).await {
+        Ok(()) => {
+            forget_cached_redirects(state.redirect_cache(), &id, url.as_deref());
+            tracing::warn!(
+                code = %id,
+                "short code deleted; the Bloom filter may report it as present until rebuilt"
+            );
+            Ok(StatusCode::NO_CONTENT)
+        }
+        Err(DatabaseError::NotFound) => Err(ApiError::NotFound("URL not found".to_string())),
+        Err(e) => {
+            tracing::error!("Database error: {}", e);
+            Err(ApiError::Internal(e.to_string()))
+        }
+    }
+}
@@ -3,14 +3,161 @@
 //! This module provides routes for serving API documentation including
 //! the OpenAPI specification and Swagger UI interface.
 
-use axum::response::Html;
+use crate::errors::ApiError;
+use crate::models::{ClickRecord, UrlRecord};
+use crate::response::ApiResponse;
+use crate::routes::shorten::{ShortenParams, ShortenRequest, ShortenResponse};
+use axum::Json;
+use axum::http::header;
+use axum::response::{Html, IntoResponse};
 use axum_macros::debug_handler;
+use std::sync::LazyLock;
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+
+/// The OpenAPI document, collected from the `#[utoipa::path]` annotations
+/// on the route handlers and the `ToSchema` derives on their types.
+///
+/// Feature-gated endpoints are added by [`openapi_spec`]; use that rather
+/// than `ApiDoc::openapi()` directly.
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "URL Shortener API",
+        description = "A high-performance URL shortener service built with Rust and Axum",
+        license(name = "MIT", url = "https://opensource.org/licenses/MIT"),
+    ),
+    servers((url = "http://localhost:8000", description = "Local development server")),
+    paths(
+        crate::routes::health_check::health_check,
+        crate::routes::health_check::readiness_check,
+        crate::routes::redirect::get_redirect,
+        crate::routes::redirect::get_redirect_head,
+        crate::routes::redirect::options_redirect,
+        crate::routes::redirect::get_redirect_preview,
+        crate::routes::unlock::post_unlock,
+        crate::routes::qr::get_qr_code,
+        crate::routes::qr::get_qr_image,
+        crate::routes::inspect::get_url_info,
+        crate::routes::inspect::head_url_info,
+        crate::routes::delete::delete_url,
+        crate::routes::update::patch_url,
+        crate::routes::urls::list_urls,
+        crate::routes::urls::post_url_tag,
+        crate::routes::urls::delete_url_tag,
+        crate::routes::search::search_urls,
+        crate::routes::export::export_urls,
+        crate::routes::import::import_urls,
+        crate::routes::preview::get_url_preview,
+        crate::routes::stats::get_url_stats,
+        crate::routes::stats::get_url_clicks,
+        crate::routes::api_keys::list_api_keys,
+        crate::routes::shorten::post_shorten,
+        crate::routes::shorten::post_shorten_batch,
+        public_shorten,
+        crate::routes::webhooks::post_webhook_receive,
+        crate::routes::index::get_index,
+        crate::routes::admin::get_admin_dashboard,
+        crate::routes::admin::get_user_profile,
+        crate::routes::admin::get_login,
+        crate::routes::admin::get_register,
+        crate::routes::admin::get_users,
+        crate::routes::admin::get_admin_shorten,
+        crate::routes::admin::get_admin_urls,
+        crate::routes::admin::post_admin_delete_url,
+        crate::routes::admin::get_admin_url_analytics,
+        crate::routes::admin::get_analytics,
+        crate::routes::admin::get_admin_stats,
+        crate::routes::admin::post_rebuild_bloom,
+        crate::routes::admin::get_admin_config,
+    ),
+    components(schemas(ApiError, UrlRecord, ClickRecord)),
+    modifiers(&SecuritySchemes),
+)]
+pub struct ApiDoc;
+
+/// Endpoints that only exist with optional cargo features.
+#[cfg(feature = "metrics")]
+#[derive(OpenApi)]
+#[openapi(paths(crate::routes::metrics::get_metrics))]
+struct MetricsDoc;
+
+/// Registers the authentication schemes the path annotations refer to.
+struct SecuritySchemes;
+
+impl Modify for SecuritySchemes {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "ApiKeyAuth",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::with_description(
+                "x-api-key",
+                "One of the configured API keys, required on protected endpoints",
+            ))),
+        );
+        components.add_security_scheme(
+            "BearerAuth",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .description(Some(
+                        "A signed-in user's access token, also accepted as the access_token cookie",
+                    ))
+                    .build(),
+            ),
+        );
+        components.add_security_scheme(
+            "WebhookSignature",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::with_description(
+                "X-Signature-256",
+                "sha256= followed by the hex HMAC-SHA256 of the body, keyed with incoming_webhooks.secret",
+            ))),
+        );
+    }
+}
+
+/// `POST /api/public/shorten` is served by
+/// [`post_shorten`](crate::routes::shorten::post_shorten), whose annotation
+/// documents `/api/shorten`; this function only carries the second path.
+#[utoipa::path(
+    post,
+    path = "/api/public/shorten",
+    tag = "URL Shortening",
+    summary = "Shorten URL (Public)",
+    description = "Create a short URL from a long URL (no authentication required). The body may also be the URL as plain text.",
+    params(ShortenParams),
+    request_body(content = ShortenRequest, content_type = "application/json"),
+    responses(
+        (status = 200, description = "URL shortened successfully", body = ApiResponse<ShortenResponse>),
+        (status = 400, description = "Malformed JSON body", body = ApiError),
+        (status = 409, description = "Alias already taken", body = ApiError),
+        (status = 413, description = "Body exceeds `application.max_body_bytes`", body = ApiError),
+        (status = 422, description = "Invalid URL format, URL too long, or invalid alias or `ttl_seconds`", body = ApiError),
+        (status = 429, description = "Rate limit exceeded", body = ApiError),
+    )
+)]
+#[allow(dead_code)]
+fn public_shorten() {}
+
+/// The full OpenAPI document, including the endpoints of enabled cargo
+/// features.
+pub fn openapi_spec() -> utoipa::openapi::OpenApi {
+    #[allow(unused_mut)]
+    let mut spec = ApiDoc::openapi();
+    #[cfg(feature = "metrics")]
+    spec.merge(MetricsDoc::openapi());
+    spec
+}
+
+/// The document is fixed for the life of the process, so it is built once.
+static OPENAPI_SPEC: LazyLock<utoipa::openapi::OpenApi> = LazyLock::new(openapi_spec);
 
 /// Serve the OpenAPI specification as YAML.
 ///
-/// This handler serves the OpenAPI 3.0 specification file that describes
-/// all the API endpoints, request/response formats, and authentication
-/// requirements for the URL shortener service.
+/// This handler serves the OpenAPI 3.1 specification generated from the
+/// handler annotations, describing all the API endpoints, request/response
+/// formats, and authentication requirements for the URL shortener service.
 ///
 /// # Endpoint
 ///
@@ -26,15 +173,35 @@ use axum_macros::debug_handler;
 /// `application/yaml`
 ///
 #[debug_handler]
-pub async fn serve_openapi_spec() -> impl axum::response::IntoResponse {
-    let yaml_content = include_str!("../../openapi.yaml");
+pub async fn serve_openapi_spec() -> Result<impl IntoResponse, ApiError> {
+    let yaml = OPENAPI_SPEC
+        .to_yaml()
+        .map_err(|e| ApiError::Internal(format!("Failed to render OpenAPI spec: {}", e)))?;
 
-    axum::response::Response::builder()
-        .header("content-type", "application/yaml")
-        .body(axum::body::Body::from(yaml_content))
-        .unwrap()
+    Ok(([(header::CONTENT_TYPE, "application/yaml")], yaml))
 }
 
+/// Serve the OpenAPI specification as JSON.
+///
+/// The same document as [`serve_openapi_spec`], for clients and code
+/// generators that expect JSON.
+///
+/// # Endpoint
+///
+/// `GET /api/docs/openapi.json`
+///
+#[debug_handler]
+pub async fn serve_openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(OPENAPI_SPEC.clone())
+}
+
+/// Content Security Policy for the Swagger UI page.
+pub const SWAGGER_UI_CSP: &str = "default-src 'self'; \
+     script-src 'self' 'unsafe-inline' https://unpkg.com; \
+     style-src 'self' 'unsafe-inline' https://unpkg.com; \
+     img-src 'self' data: https://unpkg.com; \
+     frame-ancestors 'none'";
+
 /// Serve the Swagger UI interface.
 ///
 /// This handler serves an HTML page containing the Swagger UI interface
@@ -60,8 +227,13 @@ pub async fn serve_openapi_spec() -> impl axum::response::IntoResponse {
 /// - **Authentication**: Support for API key authentication
 /// - **Schema Validation**: Automatic validation of request/response formats
 ///
+/// # Content Security Policy
+///
+/// The page carries its own [`SWAGGER_UI_CSP`] allowing the inline bootstrap
+/// script and the unpkg CDN, which the default policy would block.
+///
 #[debug_handler]
-pub async fn serve_swagger_ui() -> Html<String> {
+pub async fn serve_swagger_ui() -> impl IntoResponse {
     let html = r#"<!DOCTYPE html>
 <html lang="en">
 <head>
@@ -114,5 +286,8 @@ pub async fn serve_swagger_ui() -> Html<String> {
 </body>
 </html>"#;
 
-    Html(html.to_string())
+    (
+        [(header::CONTENT_SECURITY_POLICY, SWAGGER_UI_CSP)],
+        Html(html.to_string()),
+    )
 }
@@ -0,0 +1,196 @@
+//! # URL Export Handler
+//!
+//! This module provides the protected endpoint used to download every stored
+//! URL mapping as JSON or CSV, for backups and migrations. The output is
+//! streamed page by page so the table is never loaded into memory at once.
+
+use crate::database::UrlDatabase;
+use crate::errors::ApiError;
+use crate::models::ExportRecord;
+use crate::state::AppState;
+use axum::body::{Body, Bytes};
+use axum::extract::State;
+use axum::http::{HeaderMap, header};
+use axum::response::IntoResponse;
+use axum_macros::debug_handler;
+use chrono::Utc;
+use futures_util::stream;
+use std::sync::Arc;
+
+/// Number of rows read from the database per streamed chunk.
+pub const EXPORT_CHUNK_ROWS: u64 = 500;
+
+/// UTF-8 byte order mark, prepended to CSV exports so Excel detects the encoding.
+const UTF8_BOM: &str = "\u{feff}";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExportFormat {
+    Json,
+    Csv,
+}
+
+impl ExportFormat {
+    fn from_accept(headers: &HeaderMap) -> Self {
+        let accept = headers
+            .get(header::ACCEPT)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default();
+        if accept.contains("text/csv") {
+            ExportFormat::Csv
+        } else {
+            ExportFormat::Json
+        }
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "application/json",
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Csv => "csv",
+        }
+    }
+}
+
+/// Streams every stored URL mapping as a JSON or CSV attachment.
+///
+/// The format follows the `Accept` header: `text/csv` selects CSV, anything
+/// else (or no header) selects JSON. The JSON output can be fed straight back
+/// into `POST /api/urls/import`.
+///
+/// # Endpoint
+///
+/// `GET /api/urls/export` (protected - requires API key)
+///
+/// # Response Format
+///
+/// JSON:
+///
+/// ```json
+/// [
+///   { "code": "AbC123", "url": "https://www.example.com/", "created_at": "2025-01-18T11:59:00Z", "click_count": 0 }
+/// ]
+/// ```
+///
+/// CSV (with a UTF-8 BOM):
+///
+/// ```text
+/// code,url,created_at,click_count
+/// AbC123,https://www.example.com/,2025-01-18T11:59:00Z,0
+/// ```
+///
+/// Both are sent with `Content-Disposition: attachment; filename="urls-export-YYYY-MM-DD.<ext>"`.
+///
+/// # Status Codes
+///
+/// - `200 OK` - Export started; a database error mid-stream aborts the body
+/// - `401 Unauthorized` - Missing or invalid API key
+#[utoipa::path(
+    get,
+    path = "/api/urls/export",
+    tag = "URL Management",
+    summary = "Export URLs",
+    description = "Stream every stored URL mapping as a JSON or CSV attachment. Send `Accept: text/csv` for CSV (with a UTF-8 BOM and a `code,url,created_at,click_count` header row); JSON is the default and can be re-imported with `POST /api/urls/import`.",
+    security(("ApiKeyAuth" = [])),
+    responses(
+        (status = 200, description = "Export started; a database error mid-stream aborts the body", body = Vec<ExportRecord>, headers(
+            ("content-disposition" = String, description = "`attachment; filename=\"urls-export-YYYY-MM-DD.<ext>\"`"),
+        )),
+        (status = 401, description = "Missing or invalid API key", body = ApiError),
+    )
+)]
+#[debug_handler]
+#[tracing::instrument(name = "export_urls", skip(state, headers))]
+pub async fn export_urls(State(state): State<AppState>, headers: HeaderMap) -> impl IntoResponse {
+    let format = ExportFormat::from_accept(&headers);
+    let filename = format!(
+        "urls-export-{}.{}",
+        Utc::now().format("%Y-%m-%d"),
+        format.extension()
+    );
+
+    let body = Body::from_stream(export_stream(state.database().clone(), format));
+
+    (
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+        ],
+        body,
+    )
+}
+
+/// Pages through the table [`EXPORT_CHUNK_ROWS`] rows at a time, yielding one
+/// encoded chunk per page.
+fn export_stream(
+    database: Arc<dyn UrlDatabase>,
+    format: ExportFormat,
+) -> impl futures_util::Stream<Item = Result<Bytes, std::io::Error>> {
+    stream::unfold(Some(0u64), move |next| {
+        let database = database.clone();
+        async move {
+            let offset = next?;
+            let rows = match database.export_urls(offset, EXPORT_CHUNK_ROWS).await {
+                Ok(rows) => rows,
+                Err(e) => {
+                    tracing::error!("Database error during export: {}", e);
+                    return Some((Err(std::io::Error::other(e.to_string())), None));
+                }
+            };
+
+            let last = (rows.len() as u64) < EXPORT_CHUNK_ROWS;
+            let chunk = encode_chunk(&rows, format, offset == 0, last);
+            let next = (!last).then_some(offset + rows.len() as u64);
+            Some((chunk.map(Bytes::from), next))
+        }
+    })
+}
+
+/// Encodes one page of rows, adding the document prefix on the first page and
+/// the suffix on the last one.
+fn encode_chunk(
+    rows: &[ExportRecord],
+    format: ExportFormat,
+    first: bool,
+    last: bool,
+) -> Result<Vec<u8>, std::io::Error> {
+    let mut out = Vec::new();
+    match format {
+        ExportFormat::Json => {
+            if first {
+                out.push(b'[');
+            }
+            for (i, row) in rows.iter().enumerate() {
+                if !first || i > 0 {
+                    out.push(b',');
+                }
+                serde_json::to_writer(&mut out, row)?;
+            }
+            if last {
+                out.push(b']');
+            }
+        }
+        ExportFormat::Csv => {
+            if first {
+                out.extend_from_slice(UTF8_BOM.as_bytes());
+                out.extend_from_slice(b"code,url,created_at,click_count\n");
+            }
+            let mut writer = csv::WriterBuilder::new()
+                .has_headers(false)
+                .from_writer(&mut out);
+            for row in rows {
+                writer.serialize(row).map_err(std::io::Error::other)?;
+            }
+            writer.flush()?;
+        }
+    }
+    Ok(out)
+}
@@ -4,13 +4,62 @@
 //! The health check endpoint is used by load balancers, monitoring systems, and
 //! other services to verify that the URL shortener service is running and healthy.
 
+use crate::errors::ApiError;
+use crate::generator::GeneratorMetricsSnapshot;
 use crate::response::ApiResponse;
+use crate::shortcode::bloom_filter::BloomMetrics;
+use crate::state::AppState;
+use axum::extract::State;
+use axum::http::StatusCode;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// Payload returned by the health check endpoint.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct HealthStatus {
+    /// Whether the database answered a trivial query
+    pub db_ok: bool,
+    /// Configured database backend (`sqlite` or `postgres`)
+    #[schema(value_type = String, example = "sqlite")]
+    pub db_type: &'static str,
+    /// Name of the short-code generator engine in use
+    #[schema(value_type = String, example = "sequence")]
+    pub generator_engine: &'static str,
+    /// Generator usage counters, for engines that track them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generator: Option<GeneratorMetricsSnapshot>,
+    /// Values the generator counter can still reach, for engines with one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generator_remaining_capacity: Option<u64>,
+    /// Seconds elapsed since the application state was built
+    pub uptime_seconds: u64,
+    /// Crate version of the running binary
+    #[schema(value_type = String, example = "0.26.0")]
+    pub version: &'static str,
+    /// Approximate number of short codes in the short-to-long Bloom filter
+    pub bloom_items: u64,
+    /// Estimated Bloom filter fill as a percentage of `bloom.expected_items`
+    pub bloom_utilization_pct: f64,
+    /// Short-to-long Bloom filter lookup outcomes since startup
+    pub bloom_lookups: BloomMetrics,
+}
+
+/// Payload returned by the readiness endpoint.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReadyStatus {
+    /// Whether the database answered a trivial query
+    pub db_ok: bool,
+    /// Number of embedded migrations not yet applied (always 0 when ready)
+    pub migrations_pending: usize,
+}
 
 /// Health check endpoint handler.
 ///
-/// This handler provides a simple health check endpoint that returns a success
-/// response if the service is running. It's commonly used by load balancers,
-/// monitoring systems, and orchestration platforms to verify service health.
+/// This handler verifies that the database is reachable and reports basic
+/// runtime information. The `generator` object is only present for engines
+/// that track usage counters (currently the sequence engine). It's commonly
+/// used by load balancers, monitoring systems, and orchestration platforms to
+/// verify service health.
 ///
 /// # Endpoint
 ///
@@ -26,13 +75,36 @@ use crate::response::ApiResponse;
 ///   "message": "ok",
 ///   "status": 200,
 ///   "time": "2025-01-18T12:00:00Z",
-///   "data": null
+///   "data": {
+///     "db_ok": true,
+///     "db_type": "sqlite",
+///     "generator_engine": "sequence",
+///     "generator": {
+///       "engine": "sequence",
+///       "codes_issued": 1024,
+///       "capacity_used_pct": 0.0001,
+///       "block_refills": 1,
+///       "persist_writes": 1,
+///       "next_value": 65536
+///     },
+///     "generator_remaining_capacity": 18446744073709486079,
+///     "uptime_seconds": 3600,
+///     "version": "0.26.0",
+///     "bloom_items": 1024,
+///     "bloom_utilization_pct": 0.0,
+///     "bloom_lookups": {
+///       "s2l_hits": 512,
+///       "s2l_misses": 37,
+///       "s2l_false_positives": 2
+///     }
+///   }
 /// }
 /// ```
 ///
 /// # Status Codes
 ///
-/// - `200 OK` - Service is healthy and running
+/// - `200 OK` - Service is healthy and the database is reachable
+/// - `503 Service Unavailable` - The database could not be reached
 ///
 /// # Tracing
 ///
@@ -45,11 +117,11 @@ use crate::response::ApiResponse;
 /// # Check service health
 /// curl http://localhost:8000/api/health_check
 ///
-/// # Expected response
+/// # Response when the database is down
 /// {
-///   "success": true,
-///   "message": "ok",
-///   "status": 200,
+///   "success": false,
+///   "message": "database unavailable",
+///   "status": 503,
 ///   "time": "2025-01-18T12:00:00Z",
 ///   "data": null
 /// }
@@ -59,10 +131,119 @@ use crate::response::ApiResponse;
 ///
 /// This endpoint can be used with monitoring tools like:
 /// - Prometheus health checks
-/// - Kubernetes liveness/readiness probes
+/// - Kubernetes liveness probes (use [`readiness_check`] for readiness)
 /// - Load balancer health checks
 /// - Application monitoring dashboards
-#[tracing::instrument(name = "health check")]
-pub async fn health_check() -> ApiResponse<()> {
-    ApiResponse::success(())
+#[utoipa::path(
+    get,
+    path = "/api/health_check",
+    tag = "Health",
+    summary = "Health Check",
+    description = "Check if the service is running and its database is reachable",
+    responses(
+        (status = 200, description = "Service is healthy", body = ApiResponse<HealthStatus>),
+        (status = 503, description = "The database could not be reached", body = ApiError),
+    )
+)]
+#[tracing::instrument(name = "health check", skip(state))]
+pub async fn health_check(State(state): State<AppState>) -> ApiResponse<HealthStatus> {
+    if let Err(e) = state.database().health_check().await {
+        tracing::error!("Database health check failed: {}", e);
+        return ApiResponse::error(
+            "database unavailable",
+            StatusCode::SERVICE_UNAVAILABLE,
+            Some("DATABASE_UNAVAILABLE"),
+        );
+    }
+
+    ApiResponse::success(HealthStatus {
+        db_ok: true,
+        db_type: state.config().database.r#type.as_str(),
+        generator_engine: state.code_generator().name(),
+        generator: state.generator_metrics().map(|m| m.snapshot()),
+        generator_remaining_capacity: state
+            .generator_metrics()
+            .and_then(|m| m.remaining_capacity()),
+        uptime_seconds: state.uptime().elapsed().as_secs(),
+        version: env!("CARGO_PKG_VERSION"),
+        bloom_items: state.blooms().s2l.estimated_count(),
+        bloom_utilization_pct: (state.blooms().utilization() * 1000.0).round() / 10.0,
+        bloom_lookups: state.blooms().metrics_snapshot(),
+    })
+}
+
+/// Readiness endpoint handler.
+///
+/// Unlike [`health_check`], which only proves the process and database are
+/// alive, this also requires every embedded migration to have been applied,
+/// so traffic is not routed to an instance running against an old schema.
+///
+/// # Endpoint
+///
+/// `GET /api/ready`
+///
+/// # Response
+///
+/// ```json
+/// {
+///   "success": true,
+///   "message": "ok",
+///   "status": 200,
+///   "time": "2025-01-18T12:00:00Z",
+///   "data": {
+///     "db_ok": true,
+///     "migrations_pending": 0
+///   }
+/// }
+/// ```
+///
+/// # Status Codes
+///
+/// - `200 OK` - Database reachable and schema up to date
+/// - `503 Service Unavailable` - Database unreachable, migrations table
+///   unreadable, or migrations pending
+#[utoipa::path(
+    get,
+    path = "/api/ready",
+    tag = "Health",
+    summary = "Readiness Check",
+    description = "Check that the database is reachable and every migration has been applied",
+    responses(
+        (status = 200, description = "Service is ready to receive traffic", body = ApiResponse<ReadyStatus>),
+        (status = 503, description = "Database unreachable or migrations pending", body = ApiError),
+    )
+)]
+#[tracing::instrument(name = "readiness check", skip(state))]
+pub async fn readiness_check(State(state): State<AppState>) -> ApiResponse<ReadyStatus> {
+    if let Err(e) = state.database().health_check().await {
+        tracing::error!("Database health check failed: {}", e);
+        return ApiResponse::error(
+            "database unavailable",
+            StatusCode::SERVICE_UNAVAILABLE,
+            Some("DATABASE_UNAVAILABLE"),
+        );
+    }
+
+    match state.database().pending_migrations().await {
+        Ok(0) => ApiResponse::success(ReadyStatus {
+            db_ok: true,
+            migrations_pending: 0,
+        }),
+        Ok(pending) => {
+            tracing::warn!(pending, "Database migrations are not up to date");
+            ApiResponse::error(
+                "migrations pending",
+                StatusCode::SERVICE_UNAVAILABLE,
+                Some("MIGRATIONS_PENDING"),
+            )
+        }
+        Err(e) => {
+            tracing::error!("Unable to read applied migrations: {}", e);
+            ApiResponse::error(
+                "migrations pending",
+                StatusCode::SERVICE_UNAVAILABLE,
+                Some("MIGRATIONS_PENDING"),
+            )
+        }
+    }
 }
@@ -0,0 +1,225 @@
+//! # URL Import Handler
+//!
+//! This module provides the protected endpoint used to bulk import existing
+//! `code -> url` mappings, e.g. when migrating from another URL shortener.
+//! Imported codes are kept exactly as given.
+
+use crate::errors::ApiError;
+use crate::response::ApiResponse;
+use crate::routes::shorten::MAX_URL_LENGTH;
+use crate::state::AppState;
+use crate::validation::{check_destination, normalize_url, validate_code};
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{HeaderMap, header};
+use axum_macros::debug_handler;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Maximum number of rows accepted by `POST /api/urls/import`.
+pub const MAX_IMPORT_ROWS: usize = 10_000;
+/// Request body limit for `POST /api/urls/import`, sized for a full import.
+pub const MAX_IMPORT_BODY_BYTES: usize = 16 * 1024 * 1024;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ImportRow {
+    /// The short code to keep
+    pub code: String,
+    /// The destination URL
+    pub url: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ImportError {
+    /// 1-based position of the row in the payload (header row not counted)
+    pub row: usize,
+    /// The code given on that row
+    pub code: String,
+    /// Why the row was rejected
+    pub error: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ImportResult {
+    /// Number of rows in the payload
+    pub total: usize,
+    /// Rows stored under their given code
+    pub imported: usize,
+    /// Valid rows not stored because the code or URL already exists
+    pub skipped: usize,
+    /// Rows rejected by validation
+    pub errors: Vec<ImportError>,
+}
+
+/// Bulk imports `code -> url` mappings from JSON or CSV.
+///
+/// Every row is validated on its own; invalid rows are listed in `errors` and
+/// the rest are stored with a single
+/// [`UrlDatabase::insert_urls_batch`](crate::database::UrlDatabase::insert_urls_batch)
+/// call. Rows whose code is already taken, or whose URL is already shortened,
+/// are counted as `skipped` rather than reported as errors.
+///
+/// # Endpoint
+///
+/// `POST /api/urls/import` (protected - requires API key)
+///
+/// # Request Format
+///
+/// With `Content-Type: application/json`:
+///
+/// ```json
+/// [{ "code": "abc123", "url": "https://www.example.com/" }]
+/// ```
+///
+/// With `Content-Type: text/csv` (header row required, quoted fields and a
+/// UTF-8 BOM are accepted):
+///
+/// ```text
+/// code,url
+/// abc123,https://www.example.com/
+/// ```
+///
+/// # Response Format
+///
+/// ```json
+/// {
+///   "success": true,
+///   "message": "ok",
+///   "status": 200,
+///   "time": "2025-01-18T12:00:00Z",
+///   "data": {
+///     "total": 2,
+///     "imported": 1,
+///     "skipped": 0,
+///     "errors": [
+///       { "row": 2, "code": "def456", "error": "Unsupported or invalid URL" }
+///     ]
+///   }
+/// }
+/// ```
+///
+/// # Status Codes
+///
+/// - `200 OK` - Import processed (check `errors` for rejected rows)
+/// - `400 Bad Request` - Unsupported content type or unparsable payload
+/// - `401 Unauthorized` - Missing or invalid API key
+/// - `422 Unprocessable Entity` - More than [`MAX_IMPORT_ROWS`] rows
+/// - `500 Internal Server Error` - Database error (no row is stored)
+#[utoipa::path(
+    post,
+    path = "/api/urls/import",
+    tag = "URL Management",
+    summary = "Import URLs",
+    description = "Bulk import up to 10,000 code-to-URL mappings, keeping the given codes. Rows whose code or URL already exists are skipped; invalid rows are reported in `errors` while the rest are imported. Also accepts `Content-Type: text/csv` with a `code,url` header row.",
+    request_body(content = Vec<ImportRow>, content_type = "application/json"),
+    security(("ApiKeyAuth" = [])),
+    responses(
+        (status = 200, description = "Import processed (check `errors` for rejected rows)", body = ApiResponse<ImportResult>),
+        (status = 400, description = "Unsupported content type or unparsable payload", body = ApiError),
+        (status = 401, description = "Missing or invalid API key", body = ApiError),
+        (status = 422, description = "More than 10,000 rows", body = ApiError),
+    )
+)]
+#[debug_handler]
+#[tracing::instrument(name = "import_urls", skip(state, headers, body))]
+pub async fn import_urls(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<ApiResponse<ImportResult>, ApiError> {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+
+    let rows = if content_type.starts_with("application/json") {
+        serde_json::from_slice::<Vec<ImportRow>>(&body)
+            .map_err(|e| ApiError::BadRequest(format!("Invalid JSON payload: {}", e)))?
+    } else if content_type.starts_with("text/csv") {
+        parse_csv(&body)?
+    } else {
+        return Err(ApiError::BadRequest(
+            "Content-Type must be application/json or text/csv".to_string(),
+        ));
+    };
+
+    if rows.len() > MAX_IMPORT_ROWS {
+        return Err(ApiError::Unprocessable(format!(
+            "Import exceeds maximum of {} rows",
+            MAX_IMPORT_ROWS
+        )));
+    }
+
+    // 1) Validate every row up front; invalid ones are reported, not fatal
+    let mut errors = Vec::new();
+    let mut valid: Vec<(&str, String)> = Vec::with_capacity(rows.len());
+    for (index, row) in rows.iter().enumerate() {
+        let checked = if row.url.len() > MAX_URL_LENGTH {
+            Err(ApiError::UrlTooLong(format!(
+                "URL exceeds maximum allowed length of {} characters",
+                MAX_URL_LENGTH
+            )))
+        } else {
+            match validate_code(&row.code, &state).and_then(|_| normalize_url(&row.url)) {
+                Ok(norm) => check_destination(&norm, &state).await.map(|()| norm),
+                Err(e) => Err(e),
+            }
+        };
+
+        match checked {
+            Ok(norm) => valid.push((row.code.as_str(), norm)),
+            Err(e) => errors.push(ImportError {
+                row: index + 1,
+                code: row.code.clone(),
+                error: e.message(),
+            }),
+        }
+    }
+
+    // 2) Store the valid rows in one transaction
+    let pairs: Vec<(&str, &str)> = valid
+        .iter()
+        .map(|(code, url)| (*code, url.as_str()))
+        .collect();
+    let stored = state
+        .database()
+        .insert_urls_batch(&pairs)
+        .await
+        .map_err(|e| {
+            tracing::error!("Database error on import: {}", e);
+            ApiError::Internal(e.to_string())
+        })?;
+
+    let mut imported = 0;
+    for row in stored.iter().filter(|row| row.created) {
+        state.blooms().insert(&row.code).await;
+        imported += 1;
+    }
+
+    tracing::info!(
+        total = rows.len(),
+        imported,
+        rejected = errors.len(),
+        "URL import finished"
+    );
+    Ok(ApiResponse::success(ImportResult {
+        total: rows.len(),
+        imported,
+        skipped: pairs.len() - imported,
+        errors,
+    }))
+}
+
+/// Parses a `code,url` CSV document with a header row.
+fn parse_csv(body: &[u8]) -> Result<Vec<ImportRow>, ApiError> {
+    let body = body.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(body);
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .trim(csv::Trim::All)
+        .from_reader(body);
+
+    reader
+        .deserialize::<ImportRow>()
+        .map(|row| row.map_err(|e| ApiError::BadRequest(format!("Invalid CSV payload: {}", e))))
+        .collect()
+}
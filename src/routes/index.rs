@@ -5,7 +5,6 @@
 
 use crate::errors::ApiError;
 use crate::state::AppState;
-use crate::templates::get_templates;
 use axum::{extract::State, response::Html};
 use axum_macros::debug_handler;
 use tera::Context;
@@ -22,7 +21,7 @@ use tera::Context;
 ///
 /// # Arguments
 ///
-/// * `State(state)` - Application state containing template directory and other dependencies
+/// * `State(state)` - Application state containing the compiled templates and other dependencies
 ///
 /// # Returns
 ///
@@ -62,14 +61,24 @@ use tera::Context;
 /// - Template files cannot be loaded
 /// - Template rendering fails
 /// - Context data is invalid
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "Admin",
+    summary = "Home page",
+    responses(
+        (status = 200, description = "Rendered page", body = String, content_type = "text/html"),
+        (status = 500, description = "Template rendering failed", body = ApiError),
+    )
+)]
 #[debug_handler]
-pub async fn get_index(_state: State<AppState>) -> Result<Html<String>, ApiError> {
+pub async fn get_index(State(state): State<AppState>) -> Result<Html<String>, ApiError> {
     let mut context = Context::new();
     context.insert("title", "URL Shortener");
     context.insert("page", "Home");
     context.insert("message", "Hello, world!");
 
-    let body = get_templates().render("index.html", &context)?;
+    let body = state.templates().render("index.html", &context)?;
 
     Ok(Html(body))
 }
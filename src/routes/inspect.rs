@@ -0,0 +1,202 @@
+//! # URL Inspection Handler
+//!
+//! This module provides a read-only view of a short code's metadata. Unlike the
+//! redirect handler it never sends the caller to the destination URL, so it can
+//! be used by tooling to check link health without touching analytics.
+//!
+//! Metadata responses carry an `ETag`, so clients polling for new clicks can
+//! revalidate with `If-None-Match` and get an empty `304 Not Modified` until
+//! something changes.
+
+use crate::database::{DatabaseError, MAX_ALIAS_LENGTH};
+use crate::errors::ApiError;
+use crate::models::UrlMetadata;
+use crate::response::ApiResponse;
+use crate::state::AppState;
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use axum_macros::debug_handler;
+use sha2::{Digest, Sha256};
+
+/// Lets clients keep a metadata response but makes them revalidate it first.
+const INSPECT_CACHE_CONTROL: &str = "private, no-cache";
+
+/// Returns the metadata stored for a short code or alias.
+///
+/// # Endpoint
+///
+/// `GET /api/urls/{id}`
+///
+/// # Arguments
+///
+/// * `State(state)` - Application state containing database connection
+/// * `Path(id)` - Short code or alias extracted from the URL path
+/// * `headers` - Request headers, read for `If-None-Match`
+///
+/// # Response Format
+///
+/// ```json
+/// {
+///   "success": true,
+///   "message": "ok",
+///   "status": 200,
+///   "time": "2025-01-18T12:00:00Z",
+///   "data": {
+///     "code": "AbC123",
+///     "url": "https://www.example.com/",
+///     "created_at": "2025-01-18T11:59:00Z",
+///     "click_count": 0,
+///     "preview_views": 0,
+///     "redirect_type": "permanent",
+///     "expires_at": null,
+///     "is_active": true,
+///     "updated_at": "2025-01-18T11:59:00Z"
+///   }
+/// }
+/// ```
+///
+/// # Caching
+///
+/// The response is sent with `Cache-Control: private, no-cache` and an `ETag`,
+/// the quoted SHA-256 hex of the `data` JSON. A request whose `If-None-Match`
+/// names the current `ETag` gets `304 Not Modified` without a body. A click or
+/// an update of the destination changes the `ETag`.
+///
+/// # Status Codes
+///
+/// - `200 OK` - Metadata returned
+/// - `304 Not Modified` - `If-None-Match` names the current `ETag`
+/// - `401 Unauthorized` - The code is password protected; its destination is not shown
+/// - `404 Not Found` - Unknown short code
+/// - `500 Internal Server Error` - Database error occurred
+#[utoipa::path(
+    get,
+    path = "/api/urls/{id}",
+    tag = "Redirect",
+    summary = "Inspect Short URL",
+    description = "Return the metadata stored for a short code or alias without redirecting",
+    params(("id" = String, Path, description = "Short code or alias", example = "AbC123")),
+    responses(
+        (status = 200, description = "Metadata of the short URL", body = ApiResponse<UrlMetadata>, headers(
+            ("etag" = String, description = "Quoted SHA-256 of the `data` JSON"),
+            ("cache-control" = String, description = "`private, no-cache`"),
+        )),
+        (status = 304, description = "`If-None-Match` names the current `ETag`"),
+        (status = 401, description = "The code is password protected; its destination is not shown", body = ApiError),
+        (status = 404, description = "Unknown short code", body = ApiError),
+    )
+)]
+#[debug_handler]
+#[tracing::instrument(name = "inspect", skip(state, headers))]
+pub async fn get_url_info(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    reject_unknown_id(&state, &id)?;
+
+    let meta = match state.database().get_url_with_metadata(&id).await {
+        Ok(meta) if meta.password_hash.is_some() => {
+            return Err(ApiError::Unauthorized(
+                "URL is password protected".to_string(),
+            ));
+        }
+        Ok(meta) => meta,
+        Err(DatabaseError::NotFound) => {
+            return Err(ApiError::NotFound("URL not found".to_string()));
+        }
+        Err(e) => {
+            tracing::error!("Database error: {}", e);
+            return Err(ApiError::Internal(e.to_string()));
+        }
+    };
+
+    let etag = metadata_etag(&meta)?;
+    let revalidated = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| etag_matches(v, &etag));
+    let mut response = if revalidated {
+        StatusCode::NOT_MODIFIED.into_response()
+    } else {
+        ApiResponse::success(meta).into_response()
+    };
+
+    let response_headers = response.headers_mut();
+    response_headers.insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static(INSPECT_CACHE_CONTROL),
+    );
+    if let Ok(value) = HeaderValue::from_str(&etag) {
+        response_headers.insert(header::ETAG, value);
+    }
+    Ok(response)
+}
+
+/// Answers whether a short code or alias exists, without a body.
+///
+/// Reads only the destination rather than the full metadata, so tooling can
+/// check many links cheaply. Like [`get_url_info`] it records no click.
+///
+/// # Endpoint
+///
+/// `HEAD /api/urls/{id}`
+///
+/// # Status Codes
+///
+/// - `200 OK` - The code exists, even if its URL expired or was deactivated
+/// - `404 Not Found` - Unknown or deleted short code
+/// - `500 Internal Server Error` - Database error occurred
+#[utoipa::path(
+    head,
+    path = "/api/urls/{id}",
+    tag = "Redirect",
+    summary = "Check Short URL Exists",
+    description = "Answer whether a short code or alias exists, without a body and without recording a click",
+    params(("id" = String, Path, description = "Short code or alias")),
+    responses(
+        (status = 200, description = "The code exists, even if its URL expired or was deactivated"),
+        (status = 404, description = "Unknown or deleted short code"),
+    )
+)]
+#[tracing::instrument(name = "inspect_head", skip(state))]
+pub async fn head_url_info(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    reject_unknown_id(&state, &id)?;
+
+    match state.database().get_url(&id).await {
+        Ok(_) | Err(DatabaseError::Gone(_)) => Ok(StatusCode::OK),
+        Err(DatabaseError::NotFound) => Err(ApiError::NotFound("URL not found".to_string())),
+        Err(e) => {
+            tracing::error!("Database error: {}", e);
+            Err(ApiError::Internal(e.to_string()))
+        }
+    }
+}
+
+/// Quoted SHA-256 hex of the JSON of `meta`, sent as the `ETag`.
+fn metadata_etag(meta: &UrlMetadata) -> Result<String, ApiError> {
+    let json = serde_json::to_vec(meta).map_err(|e| ApiError::Internal(e.to_string()))?;
+    Ok(format!("\"{}\"", hex::encode(Sha256::digest(&json))))
+}
+
+/// Whether an `If-None-Match` header value names `etag`.
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    if_none_match
+        .split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == etag)
+}
+
+/// Answers codes that cannot exist with a 404 before touching the database.
+fn reject_unknown_id(state: &AppState, id: &str) -> Result<(), ApiError> {
+    if id.chars().count() > MAX_ALIAS_LENGTH
+        || id.chars().any(|c| !state.allowed_chars().contains(&c))
+    {
+        return Err(ApiError::NotFound("URL not found".to_string()));
+    }
+    Ok(())
+}
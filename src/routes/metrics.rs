@@ -0,0 +1,48 @@
+//! # Metrics Handler
+//!
+//! This module provides the Prometheus scrape endpoint, available with the
+//! `metrics` cargo feature. See [`crate::telemetry::metrics`] for the list of
+//! recorded metrics.
+
+use crate::state::AppState;
+use crate::telemetry::metrics::record_bloom_metrics;
+use axum::extract::State;
+use axum::http::header;
+use axum::response::IntoResponse;
+
+/// Content type of the Prometheus text exposition format.
+const PROMETHEUS_TEXT: &str = "text/plain; version=0.0.4";
+
+/// Prometheus scrape endpoint handler.
+///
+/// # Endpoint
+///
+/// `GET /metrics`
+///
+/// The endpoint is public, like the health check; restrict it at the reverse
+/// proxy if the per-code `redirects_total` series should not be exposed.
+///
+/// # Examples
+///
+/// ```bash
+/// curl http://localhost:8000/metrics
+/// ```
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "Health",
+    summary = "Prometheus metrics",
+    description = "Metrics in the Prometheus text exposition format (only with the `metrics` cargo feature).",
+    responses(
+        (status = 200, description = "Prometheus scrape output", body = String, content_type = "text/plain"),
+    )
+)]
+#[tracing::instrument(name = "metrics", skip(state))]
+pub async fn get_metrics(State(state): State<AppState>) -> impl IntoResponse {
+    // Bloom counters live in the app state, so they are published on scrape
+    record_bloom_metrics(&state.blooms().metrics_snapshot());
+    (
+        [(header::CONTENT_TYPE, PROMETHEUS_TEXT)],
+        state.metrics().render(),
+    )
+}
@@ -8,14 +8,40 @@
 //!
 //! ### Public API (No Authentication Required)
 //! - `GET /api/health_check` - Health check endpoint
+//! - `GET /api/ready` - Readiness check (database reachable, migrations applied)
 //! - `GET /api/redirect/{id}` - Redirect to original URL
+//! - `GET /api/urls/{id}` - Short code metadata (no redirect)
+//! - `GET /{id}/preview` - Preview page showing where a short code leads
+//! - `POST /{id}/unlock` - Enter the password of a protected short code
+//! - `GET /api/qr/{id}` - PNG QR code for a short URL
+//! - `GET /api/urls/{id}/qr.{format}` - PNG or SVG QR code download
 //! - `POST /api/public/shorten` - Shorten URL (public endpoint)
 //!
+//! ### Webhook Callbacks (Requires HMAC Signature)
+//! - `POST /api/webhooks/receive` - Signed event callback from an external service
+//!
 //! ### Protected API (Requires API Key)
 //! - `POST /api/shorten` - Shorten URL (protected endpoint)
+//! - `POST /api/shorten/batch` - Shorten up to 50 URLs in one request
+//! - `GET /api/urls` - List stored URLs, optionally filtered with `?tag=`
+//! - `GET /api/urls/search?q=` - Search URLs by destination, code or alias
+//! - `POST /api/urls/import` - Bulk import `code,url` mappings from JSON or CSV
+//! - `GET /api/urls/export` - Download every URL mapping as JSON or CSV
+//! - `DELETE /api/urls/{id}` - Delete a short code or alias
+//! - `PATCH /api/urls/{id}` - Change the target URL of a short code
+//! - `GET /api/urls/{id}/stats` - Click totals of a short code, per A/B variant
+//! - `GET /api/urls/{id}/clicks` - Most recent clicks of a short code, newest first
+//! - `POST /api/urls/{id}/tags` - Attach a tag to a short code
+//! - `DELETE /api/urls/{id}/tags/{tag}` - Detach a tag from a short code
+//! - `GET /api/urls/{id}/preview` - Open Graph title, description and image of the destination
+//! - `GET /api/v1/auth/api-keys` - List configured API keys (masked) with labels
+//! - `GET /api/v1/admin/config` - Effective configuration as YAML, secrets redacted
 //!
 //! ### Admin Panel
 //! - `GET /admin` - Web interface for management
+//! - `GET /admin/urls?q=&page=` - Paginated, searchable list of stored URLs
+//! - `POST /admin/urls/{id}/delete` - Delete a short code from the URL list
+//! - `GET /admin/urls/{id}/analytics?bucket=hour|day` - Click timeline and referrers of one short code
 //!
 //! ## Handler Design
 //!
@@ -45,18 +71,52 @@
 
 // module declarations
 pub mod admin;
+pub mod api_keys;
+#[cfg(any(test, feature = "testing"))]
+pub mod debug;
+pub mod delete;
 // Module declarations
 pub mod docs;
+pub mod export;
 pub mod health_check;
+pub mod import;
 pub mod index;
+pub mod inspect;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod preview;
+pub mod qr;
 pub mod redirect;
+pub mod search;
 pub mod shorten;
+pub mod stats;
+pub mod unlock;
+pub mod update;
+pub mod urls;
+pub mod webhooks;
 
 // re-exports
 pub use admin::*;
+pub use api_keys::*;
+#[cfg(any(test, feature = "testing"))]
+pub use debug::*;
+pub use delete::*;
 pub use docs::*;
 // Re-exports for convenience
+pub use export::*;
 pub use health_check::*;
+pub use import::*;
 pub use index::*;
+pub use inspect::*;
+#[cfg(feature = "metrics")]
+pub use metrics::*;
+pub use preview::*;
+pub use qr::*;
 pub use redirect::*;
+pub use search::*;
 pub use shorten::*;
+pub use stats::*;
+pub use unlock::*;
+pub use update::*;
+pub use urls::*;
+pub use webhooks::*;
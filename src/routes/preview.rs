@@ -0,0 +1,346 @@
+//! # Link Preview Handler
+//!
+//! This module fetches the destination of a short code and reports the Open
+//! Graph title, description and image it advertises, so a link can be checked
+//! before it is shared. Only the start of the page is read, and previews are
+//! cached per destination for ten minutes.
+
+use crate::database::{DatabaseError, MAX_ALIAS_LENGTH};
+use crate::errors::ApiError;
+use crate::infrastructure::http_client::build_pinned_http_client;
+use crate::response::ApiResponse;
+use crate::state::AppState;
+use crate::telemetry::trace_context_headers;
+use crate::validation::resolve_unblocked;
+use axum::extract::{Path, State};
+use axum::http::header;
+use axum_macros::debug_handler;
+use chrono::{DateTime, Utc};
+use moka::sync::Cache;
+use regex::Regex;
+use serde::Serialize;
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+use url::{Host, Url};
+use utoipa::ToSchema;
+
+/// How much of the destination page is read looking for meta tags
+pub const PREVIEW_MAX_BYTES: usize = 64 * 1024;
+
+/// How long the destination has to answer, redirects included
+pub const PREVIEW_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// How long a fetched preview is served from the cache
+pub const PREVIEW_CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Sent as `User-Agent`; many sites serve bots a page without meta tags
+const BROWSER_USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 \
+     (KHTML, like Gecko) Chrome/130.0.0.0 Safari/537.36";
+
+static META_TAG: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?is)<meta\b[^>]*>").unwrap());
+
+static ATTRIBUTE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?is)([a-z:_-]+)\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'>]+))"#).unwrap()
+});
+
+static TITLE_TAG: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)<title\b[^>]*>(.*?)</title>").unwrap());
+
+/// Open Graph metadata of a short code's destination.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct UrlPreview {
+    /// Destination URL of the short code
+    pub url: String,
+    /// `og:title`, or the page `<title>` when there is none
+    pub title: Option<String>,
+    /// `og:description`
+    pub description: Option<String>,
+    /// `og:image`, made absolute against the page URL
+    pub image_url: Option<String>,
+    /// When the destination was fetched
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// Previews keyed by destination URL, shared through
+/// [`AppState::preview_cache`](crate::state::AppState::preview_cache).
+pub type PreviewCache = Cache<String, UrlPreview>;
+
+/// Builds the preview cache, expiring entries after [`PREVIEW_CACHE_TTL`].
+pub fn build_preview_cache() -> Arc<PreviewCache> {
+    Arc::new(
+        Cache::builder()
+            .max_capacity(10_000)
+            .time_to_live(PREVIEW_CACHE_TTL)
+            .build(),
+    )
+}
+
+/// Returns the Open Graph title, description and image of the page a short
+/// code leads to.
+///
+/// # Endpoint
+///
+/// `GET /api/urls/{id}/preview`
+///
+/// # Arguments
+///
+/// * `State(state)` - Application state containing database connection and preview cache
+/// * `Path(id)` - Short code or alias extracted from the URL path
+///
+/// # Response Format
+///
+/// ```json
+/// {
+///   "success": true,
+///   "message": "ok",
+///   "status": 200,
+///   "time": "2025-01-18T12:00:00Z",
+///   "data": {
+///     "url": "https://www.example.com/",
+///     "title": "Example Domain",
+///     "description": "An illustrative page",
+///     "image_url": "https://www.example.com/cover.png",
+///     "fetched_at": "2025-01-18T12:00:00Z"
+///   }
+/// }
+/// ```
+///
+/// A destination that cannot be reached, or does not answer within three
+/// seconds, gets a preview with no title, description or image. Those are not
+/// cached, so the next request tries again.
+///
+/// # Status Codes
+///
+/// - `200 OK` - Preview returned
+/// - `401 Unauthorized` - Missing or invalid API key
+/// - `404 Not Found` - Unknown short code
+/// - `410 Gone` - The code's URL expired or reached its click limit
+/// - `422 Unprocessable Entity` - The destination resolves to a private address
+/// - `500 Internal Server Error` - Database error occurred
+#[utoipa::path(
+    get,
+    path = "/api/urls/{id}/preview",
+    tag = "URL Management",
+    summary = "Preview destination",
+    description = "Fetches the first 64 KB of the destination page and returns its Open Graph title, description and image. The page title stands in for a missing `og:title`. A destination that fails or takes longer than three seconds gets an empty preview. Previews are cached per destination for ten minutes.",
+    params(("id" = String, Path, description = "Short code or alias")),
+    security(("ApiKeyAuth" = [])),
+    responses(
+        (status = 200, description = "Preview returned", body = ApiResponse<UrlPreview>),
+        (status = 401, description = "Missing or invalid API key", body = ApiError),
+        (status = 404, description = "Unknown short code", body = ApiError),
+        (status = 410, description = "The code's URL expired or reached its click limit", body = ApiError),
+        (status = 422, description = "The destination resolves to a private address", body = ApiError),
+    )
+)]
+#[debug_handler]
+#[tracing::instrument(name = "preview", skip(state))]
+pub async fn get_url_preview(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<ApiResponse<UrlPreview>, ApiError> {
+    if id.chars().count() > MAX_ALIAS_LENGTH
+        || id.chars().any(|c| !state.allowed_chars().contains(&c))
+    {
+        return Err(ApiError::NotFound("URL not found".to_string()));
+    }
+
+    let url = match state.database().get_url(&id).await {
+        Ok(url) => url,
+        Err(DatabaseError::NotFound) => {
+            return Err(ApiError::NotFound("URL not found".to_string()));
+        }
+        Err(DatabaseError::Gone(status)) => {
+            return Err(ApiError::Gone(format!("URL is no longer active: {status}")));
+        }
+        Err(e) => {
+            tracing::error!("Database error: {}", e);
+            return Err(ApiError::Internal(e.to_string()));
+        }
+    };
+
+    if let Some(preview) = state.preview_cache().get(&url) {
+        return Ok(ApiResponse::success(preview));
+    }
+
+    let fetched = fetch_page(&url, &state).await?;
+    let fetched_at = Utc::now();
+    let preview = match fetched {
+        Some((page_url, html)) => {
+            let preview = parse_preview(&url, &page_url, &html, fetched_at);
+            state.preview_cache().insert(url, preview.clone());
+            preview
+        }
+        None => UrlPreview {
+            url,
+            title: None,
+            description: None,
+            image_url: None,
+            fetched_at,
+        },
+    };
+    Ok(ApiResponse::success(preview))
+}
+
+/// Fetches up to [`PREVIEW_MAX_BYTES`] of `url`, following redirects.
+///
+/// Returns the final page URL with the text read, or `None` when the page
+/// could not be fetched in time. Only a URL or redirect that is, or resolves
+/// to, a private address is an error.
+async fn fetch_page(url: &str, state: &AppState) -> Result<Option<(Url, String)>, ApiError> {
+    match tokio::time::timeout(PREVIEW_TIMEOUT, follow_redirects(url, state)).await {
+        Ok(Ok(page)) => Ok(page),
+        Ok(Err(e)) => Err(e),
+        Err(_) => {
+            tracing::debug!(url, "preview fetch timed out");
+            Ok(None)
+        }
+    }
+}
+
+async fn follow_redirects(url: &str, state: &AppState) -> Result<Option<(Url, String)>, ApiError> {
+    let Ok(mut current) = Url::parse(url) else {
+        return Ok(None);
+    };
+
+    // Redirects are followed by hand, so every hop passes the private address
+    // check, whether or not SSRF protection is on; the shared client does not
+    // follow them itself
+    let extra_blocked = &state
+        .config()
+        .application
+        .ssrf_protection
+        .extra_blocked_cidrs;
+    for _ in 0..=state.config().http_client.max_redirects {
+        let addresses = resolve_unblocked(&current, extra_blocked).await?;
+
+        // A resolved host name is connected to at the addresses just checked,
+        // so it cannot be rebound to a private one in between
+        let pinned;
+        let client = match current.host() {
+            Some(Host::Domain(domain)) if !addresses.is_empty() => {
+                match build_pinned_http_client(&state.config().http_client, domain, &addresses) {
+                    Ok(client) => {
+                        pinned = client;
+                        &pinned
+                    }
+                    Err(e) => {
+                        tracing::debug!(url = %current, "preview client failed to build: {}", e);
+                        return Ok(None);
+                    }
+                }
+            }
+            _ => state.http_client(),
+        };
+        let request = client
+            .get(current.clone())
+            .headers(trace_context_headers())
+            .header(header::USER_AGENT, BROWSER_USER_AGENT);
+        let mut response = match request.send().await {
+            Ok(response) => response,
+            Err(e) => {
+                tracing::debug!(url = %current, "preview fetch failed: {}", e);
+                return Ok(None);
+            }
+        };
+
+        if response.status().is_redirection() {
+            let next = response
+                .headers()
+                .get(header::LOCATION)
+                .and_then(|location| location.to_str().ok())
+                .and_then(|location| current.join(location).ok());
+            match next {
+                Some(next) => {
+                    current = next;
+                    continue;
+                }
+                None => return Ok(None),
+            }
+        }
+        if !response.status().is_success() {
+            tracing::debug!(url = %current, status = %response.status(), "preview fetch failed");
+            return Ok(None);
+        }
+
+        let mut body = Vec::new();
+        while body.len() < PREVIEW_MAX_BYTES {
+            match response.chunk().await {
+                Ok(Some(chunk)) => body.extend_from_slice(&chunk),
+                Ok(None) => break,
+                Err(e) => {
+                    tracing::debug!(url = %current, "preview body read failed: {}", e);
+                    break;
+                }
+            }
+        }
+        body.truncate(PREVIEW_MAX_BYTES);
+        return Ok(Some((current, String::from_utf8_lossy(&body).into_owned())));
+    }
+
+    tracing::debug!(url, "preview fetch gave up after too many redirects");
+    Ok(None)
+}
+
+/// Picks the Open Graph tags out of `html`, fetched from `page_url`.
+fn parse_preview(url: &str, page_url: &Url, html: &str, fetched_at: DateTime<Utc>) -> UrlPreview {
+    let mut title = None;
+    let mut description = None;
+    let mut image_url = None;
+
+    for tag in META_TAG.find_iter(html) {
+        let mut property = None;
+        let mut content = None;
+        for attr in ATTRIBUTE.captures_iter(tag.as_str()) {
+            let value = attr
+                .get(2)
+                .or_else(|| attr.get(3))
+                .or_else(|| attr.get(4))
+                .map_or("", |m| m.as_str());
+            match attr[1].to_ascii_lowercase().as_str() {
+                "property" | "name" => property = Some(value.to_ascii_lowercase()),
+                "content" => content = Some(decode_entities(value.trim())),
+                _ => {}
+            }
+        }
+        let (Some(property), Some(content)) = (property, content) else {
+            continue;
+        };
+        if content.is_empty() {
+            continue;
+        }
+        let slot = match property.as_str() {
+            "og:title" => &mut title,
+            "og:description" => &mut description,
+            "og:image" => &mut image_url,
+            _ => continue,
+        };
+        slot.get_or_insert(content);
+    }
+
+    if title.is_none() {
+        title = TITLE_TAG
+            .captures(html)
+            .map(|c| decode_entities(c[1].trim()))
+            .filter(|t| !t.is_empty());
+    }
+    let image_url = image_url.and_then(|image| page_url.join(&image).ok().map(String::from));
+
+    UrlPreview {
+        url: url.to_string(),
+        title,
+        description,
+        image_url,
+        fetched_at,
+    }
+}
+
+/// Decodes the character references that commonly appear in attribute values.
+fn decode_entities(text: &str) -> String {
+    text.replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
@@ -0,0 +1,311 @@
+//! # QR Code Handlers
+//!
+//! This module renders QR codes pointing at the short URL for a code, so
+//! links can be printed or scanned without relying on a third-party service.
+//! `GET /api/qr/{id}` returns a PNG, while `GET /api/urls/{id}/qr.{format}`
+//! offers PNG and SVG downloads. Rendered images are cached briefly in
+//! [`AppState::qr_cache`].
+
+use crate::database::DatabaseError;
+use crate::errors::ApiError;
+use crate::state::AppState;
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::header,
+    response::IntoResponse,
+};
+use axum_macros::debug_handler;
+use image::{DynamicImage, ImageFormat, Luma};
+use moka::sync::Cache;
+use qrcode::QrCode;
+use qrcode::render::svg;
+use serde::Deserialize;
+use std::io::Cursor;
+use std::sync::Arc;
+use std::time::Duration;
+use utoipa::IntoParams;
+
+/// Image size in pixels used when no `size` is given.
+pub const DEFAULT_QR_SIZE: u32 = 200;
+/// Smallest image size accepted by the QR code endpoints.
+pub const MIN_QR_SIZE: u32 = 64;
+/// Largest image size accepted by the QR code endpoints.
+pub const MAX_QR_SIZE: u32 = 1024;
+/// How long a rendered QR code is served from the cache.
+pub const QR_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Image formats a QR code can be rendered in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QrFormat {
+    Png,
+    Svg,
+}
+
+impl QrFormat {
+    /// Parses the file extension of a download path, `png` or `svg`.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "png" => Some(Self::Png),
+            "svg" => Some(Self::Svg),
+            _ => None,
+        }
+    }
+
+    /// File extension of the format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Svg => "svg",
+        }
+    }
+
+    /// `Content-Type` the image is served with.
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Png => "image/png",
+            Self::Svg => "image/svg+xml",
+        }
+    }
+}
+
+/// Rendered QR codes keyed by code, format and size.
+pub type QrCache = Cache<(String, QrFormat, u32), Bytes>;
+
+/// Builds the QR code cache, expiring entries after [`QR_CACHE_TTL`].
+pub fn build_qr_cache() -> Arc<QrCache> {
+    Arc::new(
+        Cache::builder()
+            .max_capacity(1_000)
+            .time_to_live(QR_CACHE_TTL)
+            .build(),
+    )
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct QrParams {
+    /// Width and height of the image in pixels (clamped to 64–1024)
+    pub size: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct QrImageParams {
+    /// Width and height of the image in pixels (clamped to 64–1024)
+    pub size: Option<u32>,
+    /// Whether the image is sent as an attachment (the default) or inline
+    pub download: Option<bool>,
+}
+
+/// Returns a PNG QR code encoding the short URL for a code or alias.
+///
+/// # Endpoint
+///
+/// `GET /api/qr/{id}?size=200`
+///
+/// # Arguments
+///
+/// * `State(state)` - Application state containing database connection
+/// * `Path(id)` - Short code or alias extracted from the URL path
+/// * `Query(params)` - Optional `size` in pixels, clamped to 64–1024
+///
+/// # Response Format
+///
+/// The raw PNG bytes with `Content-Type: image/png`. The encoded text is
+/// `{base_url}/{id}`, i.e. the same short URL returned by `POST /api/shorten`.
+///
+/// # Status Codes
+///
+/// - `200 OK` - QR code rendered
+/// - `404 Not Found` - Unknown short code
+/// - `410 Gone` - The code's URL expired or reached its click limit
+/// - `500 Internal Server Error` - Database or encoding error occurred
+///
+/// # Examples
+///
+/// ```bash
+/// curl -o AbC123.png "http://localhost:8000/api/qr/AbC123?size=300"
+/// ```
+#[utoipa::path(
+    get,
+    path = "/api/qr/{id}",
+    tag = "Redirect",
+    summary = "QR Code",
+    description = "Return a PNG QR code encoding the short URL for a code or alias",
+    params(
+        ("id" = String, Path, description = "Short code or alias"),
+        QrParams,
+    ),
+    responses(
+        (status = 200, description = "The QR code as `image/png`"),
+        (status = 404, description = "Unknown short code", body = ApiError),
+        (status = 410, description = "The code's URL expired or reached its click limit", body = ApiError),
+    )
+)]
+#[debug_handler]
+#[tracing::instrument(name = "qr_code", skip(state))]
+pub async fn get_qr_code(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(params): Query<QrParams>,
+) -> Result<impl IntoResponse, ApiError> {
+    let png = cached_qr(&state, &id, QrFormat::Png, params.size).await?;
+    Ok(([(header::CONTENT_TYPE, QrFormat::Png.content_type())], png))
+}
+
+/// Returns a QR code for the short URL of a code or alias as a PNG or SVG
+/// download.
+///
+/// # Endpoint
+///
+/// `GET /api/urls/{id}/qr.{format}?size=200&download=true`
+///
+/// # Arguments
+///
+/// * `State(state)` - Application state containing database connection
+/// * `Path((id, format))` - Short code or alias, and `png` or `svg`
+/// * `Query(params)` - Optional `size` in pixels, clamped to 64–1024, and
+///   `download=false` to show the image inline instead of saving it
+///
+/// # Response Format
+///
+/// The image with `Content-Type: image/png` or `image/svg+xml` and
+/// `Content-Disposition: attachment; filename="{id}.{format}"`, or `inline`
+/// with `download=false`. The encoded text is the same as for
+/// `GET /api/qr/{id}`.
+///
+/// # Status Codes
+///
+/// - `200 OK` - QR code rendered
+/// - `404 Not Found` - Unknown short code or unsupported format
+/// - `410 Gone` - The code's URL expired or reached its click limit
+/// - `500 Internal Server Error` - Database or encoding error occurred
+///
+/// # Examples
+///
+/// ```bash
+/// curl -OJ "http://localhost:8000/api/urls/AbC123/qr.svg?size=300"
+/// ```
+#[utoipa::path(
+    get,
+    path = "/api/urls/{id}/qr.{format}",
+    tag = "Redirect",
+    summary = "Download QR Code",
+    description = "Return a PNG or SVG QR code encoding the short URL for a code or alias, as an attachment named `{id}.{format}` unless `download=false`",
+    params(
+        ("id" = String, Path, description = "Short code or alias"),
+        ("format" = String, Path, description = "`png` or `svg`"),
+        QrImageParams,
+    ),
+    responses(
+        (status = 200, description = "The QR code as `image/png` or `image/svg+xml`", headers(
+            ("content-disposition" = String, description = "`attachment; filename=\"{id}.{format}\"`, or `inline` with `download=false`"),
+        )),
+        (status = 404, description = "Unknown short code or unsupported format", body = ApiError),
+        (status = 410, description = "The code's URL expired or reached its click limit", body = ApiError),
+    )
+)]
+#[debug_handler]
+#[tracing::instrument(name = "qr_image", skip(state))]
+pub async fn get_qr_image(
+    State(state): State<AppState>,
+    Path((id, format)): Path<(String, String)>,
+    Query(params): Query<QrImageParams>,
+) -> Result<impl IntoResponse, ApiError> {
+    let Some(format) = QrFormat::from_extension(&format) else {
+        return Err(ApiError::NotFound(format!(
+            "Unsupported QR code format {:?}",
+            format
+        )));
+    };
+    let image = cached_qr(&state, &id, format, params.size).await?;
+
+    let disposition = if params.download.unwrap_or(true) {
+        format!("attachment; filename=\"{}.{}\"", id, format.extension())
+    } else {
+        "inline".to_string()
+    };
+    Ok((
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        image,
+    ))
+}
+
+/// Renders the QR code of `id`, or takes it from the cache.
+///
+/// Fails with `404 Not Found` if the code is unknown, so nothing is cached
+/// for codes that do not exist.
+async fn cached_qr(
+    state: &AppState,
+    id: &str,
+    format: QrFormat,
+    size: Option<u32>,
+) -> Result<Bytes, ApiError> {
+    let size = size
+        .unwrap_or(DEFAULT_QR_SIZE)
+        .clamp(MIN_QR_SIZE, MAX_QR_SIZE);
+
+    match state.database().get_url(id).await {
+        Ok(_) => {}
+        Err(DatabaseError::NotFound) => {
+            return Err(ApiError::NotFound("URL not found".to_string()));
+        }
+        Err(DatabaseError::Gone(status)) => {
+            return Err(ApiError::Gone(format!("URL is no longer active: {status}")));
+        }
+        Err(e) => {
+            tracing::error!("Database error: {}", e);
+            return Err(ApiError::Internal(e.to_string()));
+        }
+    }
+
+    let key = (id.to_string(), format, size);
+    if let Some(image) = state.qr_cache().get(&key) {
+        return Ok(image);
+    }
+
+    let short_url = format!(
+        "{}/{}",
+        state.config().application.base_url.trim_end_matches('/'),
+        id
+    );
+    let image = match format {
+        QrFormat::Png => render_qr_png(&short_url, size)?,
+        QrFormat::Svg => render_qr_svg(&short_url, size)?,
+    };
+    state.qr_cache().insert(key, image.clone());
+    Ok(image)
+}
+
+/// Encodes `text` as a QR code and renders it as a `size` x `size` PNG.
+pub(crate) fn render_qr_png(text: &str, size: u32) -> Result<Bytes, ApiError> {
+    let code = QrCode::new(text.as_bytes()).map_err(|e| ApiError::Internal(e.to_string()))?;
+    let image = code
+        .render::<Luma<u8>>()
+        .min_dimensions(size, size)
+        .max_dimensions(size, size)
+        .build();
+
+    let mut buf = Cursor::new(Vec::new());
+    DynamicImage::ImageLuma8(image)
+        .write_to(&mut buf, ImageFormat::Png)
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+
+    Ok(Bytes::from(buf.into_inner()))
+}
+
+/// Encodes `text` as a QR code and renders it as a `size` x `size` SVG.
+pub(crate) fn render_qr_svg(text: &str, size: u32) -> Result<Bytes, ApiError> {
+    let code = QrCode::new(text.as_bytes()).map_err(|e| ApiError::Internal(e.to_string()))?;
+    let image = code
+        .render::<svg::Color>()
+        .min_dimensions(size, size)
+        .max_dimensions(size, size)
+        .build();
+
+    Ok(Bytes::from(image))
+}
@@ -3,20 +3,288 @@
 //! This module provides the URL redirect handler for the URL shortener service.
 //! It handles requests to shortened URLs and redirects users to the original URLs.
 
-use crate::database::{DatabaseError, MAX_ALIAS_LENGTH};
+use crate::configuration::CacheSettings;
+use crate::database::{DatabaseError, MAX_ALIAS_LENGTH, retry_transient};
 use crate::errors::ApiError;
+use crate::infrastructure::events::{ClickEvent, spawn_publish};
+use crate::infrastructure::webhooks::{WebhookEvent, fire_webhooks};
+use crate::middleware::RealIp;
+use crate::models::{AbTarget, UrlMetadata};
+use crate::response::ApiResponse;
+use crate::routes::get_url_info;
+use crate::routes::unlock::{is_unlocked, password_challenge};
+use crate::shortcode::bloom_filter::BloomOutcome;
 use crate::state::AppState;
 use axum::{
-    extract::{Path, State},
-    response::{IntoResponse, Redirect},
+    Extension,
+    extract::{Path, Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::{Html, IntoResponse, Redirect, Response},
 };
+use axum_extra::headers::{HeaderMapExt, Host};
 use axum_macros::debug_handler;
+use chrono::{DateTime, Utc};
+use moka::sync::Cache;
+use rand::Rng;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+use tera::Context;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+/// `Cache-Control` sent with permanent redirects.
+///
+/// Kept short and revalidated, so a retargeted code is picked up within the
+/// hour; the `ETag` makes revalidating cheap.
+const PERMANENT_CACHE_CONTROL: &str = "public, max-age=3600, must-revalidate";
+/// `Cache-Control` sent with temporary redirects.
+const TEMPORARY_CACHE_CONTROL: &str = "no-cache";
+
+/// `User-Agent` fragments that mark a mobile device.
+static MOBILE_USER_AGENT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new("iPhone|Android|Mobile").unwrap());
+
+/// How a short code redirects to its destination.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RedirectKind {
+    /// `308 Permanent Redirect`, cached for an hour and then revalidated
+    #[default]
+    Permanent,
+    /// `307 Temporary Redirect`, revalidated on every visit
+    Temporary,
+}
+
+impl RedirectKind {
+    /// Returns the value stored in the `redirect_type` column.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RedirectKind::Permanent => "permanent",
+            RedirectKind::Temporary => "temporary",
+        }
+    }
+
+    /// Parses a stored `redirect_type`, falling back to [`RedirectKind::Permanent`].
+    pub fn from_stored(value: &str) -> Self {
+        match value {
+            "temporary" => RedirectKind::Temporary,
+            _ => RedirectKind::Permanent,
+        }
+    }
+}
+
+/// Device class of a visitor, recorded with each click.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceType {
+    /// `User-Agent` mentions `iPhone`, `Android` or `Mobile`
+    Mobile,
+    /// Any other `User-Agent`
+    Desktop,
+    /// No readable `User-Agent`
+    Unknown,
+}
+
+impl DeviceType {
+    /// Classifies the `User-Agent` of a request.
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        match headers
+            .get(header::USER_AGENT)
+            .and_then(|v| v.to_str().ok())
+        {
+            Some(ua) if MOBILE_USER_AGENT.is_match(ua) => DeviceType::Mobile,
+            Some(_) => DeviceType::Desktop,
+            None => DeviceType::Unknown,
+        }
+    }
+
+    /// Returns the value stored in the `device_type` column.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DeviceType::Mobile => "mobile",
+            DeviceType::Desktop => "desktop",
+            DeviceType::Unknown => "unknown",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RedirectParams {
+    /// Overrides the stored redirect type for this request
+    pub redirect: Option<RedirectKind>,
+}
+
+/// Query parameters asking [`get_redirect`] not to redirect.
+///
+/// `?r=0`, or its readable alias `?no_redirect=true`, answers with the
+/// metadata of the code, as `GET /api/urls/{id}` would, instead of
+/// redirecting. `?r=1` or no parameter redirects as usual.
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RedirectQueryParams {
+    /// `0` returns metadata instead of redirecting
+    pub r: Option<u8>,
+    /// `true` is the same as `r=0`
+    pub no_redirect: Option<bool>,
+}
+
+impl RedirectQueryParams {
+    /// Whether the caller asked for metadata rather than a redirect.
+    pub fn wants_metadata(&self) -> bool {
+        self.r == Some(0) || self.no_redirect == Some(true)
+    }
+}
+
+/// What [`get_redirect`] needs to answer for a code without the database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedRedirect {
+    /// Destination URL
+    pub url: String,
+    /// Redirect kind stored with the code
+    pub redirect_type: RedirectKind,
+    /// Quoted SHA-256 of `url`, sent as the `ETag`
+    pub etag: String,
+    /// Stored hash of the passphrase the code asks for before redirecting,
+    /// if any; unlock tokens are bound to it
+    pub password_hash: Option<Vec<u8>>,
+    /// Destinations drawn by weight instead of `url`, when A/B tested
+    pub ab_targets: Vec<AbTarget>,
+    /// Destination for visitors on mobile devices, if it differs from `url`
+    pub mobile_url: Option<String>,
+    /// Clicks after which the code's URL is deactivated, if it has a limit
+    pub max_clicks: Option<u64>,
+    /// Whether the URL reached its click limit and no longer redirects
+    pub deactivated: bool,
+    /// When the URL stops redirecting, if it expires
+    pub expires_at: Option<DateTime<Utc>>,
+    /// User who shortened the URL, if it was shortened while signed in
+    pub owner_id: Option<Uuid>,
+}
+
+impl CachedRedirect {
+    /// Builds the cached target of a code, computing its `ETag`.
+    pub fn new(url: String, redirect_type: RedirectKind) -> Self {
+        let etag = format!("\"{}\"", hex::encode(Sha256::digest(url.as_bytes())));
+        Self {
+            url,
+            redirect_type,
+            etag,
+            password_hash: None,
+            ab_targets: Vec::new(),
+            mobile_url: None,
+            max_clicks: None,
+            deactivated: false,
+            expires_at: None,
+            owner_id: None,
+        }
+    }
+
+    /// Picks the destination of one redirect for a visitor on `device`.
+    ///
+    /// Mobile visitors of a code with a `mobile_url` are sent there; everyone
+    /// else gets a [`draw`](Self::draw) among the A/B targets, if any.
+    fn choose(self, device: DeviceType) -> (Option<u8>, CachedRedirect) {
+        match &self.mobile_url {
+            Some(mobile_url) if device == DeviceType::Mobile => (
+                None,
+                CachedRedirect::new(mobile_url.clone(), self.redirect_type),
+            ),
+            _ => self.draw(rand::rng().random_range(0..100)),
+        }
+    }
+
+    /// Draws the destination of one redirect from a `roll` between 0 and 99.
+    ///
+    /// A/B tested codes send the roll to the target whose share of the
+    /// weights covers it and return that variant's number with a temporary
+    /// redirect to it, so browsers draw again on their next visit; other codes
+    /// always redirect to `url`.
+    fn draw(self, roll: u8) -> (Option<u8>, CachedRedirect) {
+        let mut covered = 0u16;
+        for (variant, target) in self.ab_targets.iter().enumerate() {
+            covered += u16::from(target.weight);
+            if u16::from(roll) < covered {
+                let drawn = CachedRedirect::new(target.url.clone(), RedirectKind::Temporary);
+                return (Some(variant as u8), drawn);
+            }
+        }
+        (None, self)
+    }
+
+    /// Whether an `If-None-Match` header value names this target's `ETag`.
+    fn matches(&self, if_none_match: &str) -> bool {
+        if_none_match
+            .split(',')
+            .map(str::trim)
+            .any(|tag| tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == self.etag)
+    }
+}
+
+/// Redirect targets keyed by short code, shared through
+/// [`AppState::redirect_cache`](crate::state::AppState::redirect_cache).
+pub type RedirectCache = Cache<String, CachedRedirect>;
+
+/// Builds the redirect cache sized and timed by `settings`.
+pub fn build_redirect_cache(settings: &CacheSettings) -> Arc<RedirectCache> {
+    Arc::new(
+        Cache::builder()
+            .max_capacity(settings.max_capacity)
+            .time_to_live(Duration::from_secs(settings.ttl_seconds))
+            .build(),
+    )
+}
+
+/// Redirects answered from the redirect cache and ones that had to look the
+/// code up, shared through
+/// [`AppState::redirect_cache_counters`](crate::state::AppState::redirect_cache_counters).
+#[derive(Debug, Default)]
+pub struct RedirectCacheCounters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl RedirectCacheCounters {
+    /// Counts a redirect lookup, `hit` when the cache had the code.
+    pub fn record(&self, hit: bool) {
+        let counter = if hit { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Share of lookups answered from the cache, or `None` before the first.
+    pub fn hit_rate(&self) -> Option<f64> {
+        let hits = self.hits.load(Ordering::Relaxed);
+        let total = hits + self.misses.load(Ordering::Relaxed);
+        (total > 0).then(|| hits as f64 / total as f64)
+    }
+}
+
+/// Drops `code` from the redirect cache, along with every other code cached
+/// for `url`.
+///
+/// Aliases share the target of their primary code, so retargeting or deleting
+/// a code can change where its aliases lead too.
+pub fn forget_cached_redirects(cache: &RedirectCache, code: &str, url: Option<&str>) {
+    cache.invalidate(code);
+    let Some(url) = url else {
+        return;
+    };
+    for (cached_code, target) in cache.iter() {
+        if target.url == url {
+            cache.invalidate(cached_code.as_str());
+        }
+    }
+}
 
 /// URL redirect handler that redirects users to the original URL.
 ///
 /// This handler processes requests to shortened URLs and redirects users to
 /// the original URLs stored in the database. It uses HTTP 308 Permanent Redirect
-/// to ensure proper SEO handling and browser caching.
+/// by default, or HTTP 307 Temporary Redirect when the URL was shortened with
+/// `?redirect=temporary` or the request itself passes `?redirect=temporary`.
 ///
 /// # Endpoint
 ///
@@ -25,11 +293,15 @@ use axum_macros::debug_handler;
 /// # Arguments
 ///
 /// * `State(state)` - Application state containing database connection
+/// * `Extension(real_ip)` - Client IP resolved by the real-IP middleware, logged with the click
 /// * `Path(id)` - Short URL identifier extracted from the URL path
+/// * `Query(params)` - Optional `redirect=permanent|temporary` override
+/// * `Query(mode)` - `r=0` or `no_redirect=true` returns metadata instead
+/// * `headers` - Request headers; the origin of `Referer` is stored with the click
 ///
 /// # Returns
 ///
-/// Returns `Ok(Redirect)` with a permanent redirect to the original URL, or
+/// Returns `Ok(Redirect)` with a permanent or temporary redirect to the original URL, or
 /// `Err(ApiError)` if the URL is not found or there's a database error.
 ///
 /// # Redirect Behavior
@@ -38,12 +310,84 @@ use axum_macros::debug_handler;
 ///   moved to the new location
 /// - **SEO Friendly** - Search engines understand that the short URL is an alias
 ///   for the original URL
-/// - **Browser Caching** - Browsers may cache the redirect for performance
+/// - **Browser Caching** - Sent with `Cache-Control: public, max-age=3600, must-revalidate`
+///   and an `ETag` of the destination; a request whose `If-None-Match` names
+///   the current `ETag` is answered `304 Not Modified`
+/// - **HTTP 307 Temporary Redirect** - For links whose target will change (A/B
+///   tests, campaigns); sent with `Cache-Control: no-cache`
+/// - **No Referrer** - Both kinds send `Referrer-Policy: no-referrer`, so the
+///   destination is not told which page the short link was followed from
+/// - **Link Headers** - `rel="describedby"` points at `GET /api/urls/{id}`
+///   and `rel="canonical"` at the destination
+///
+/// # Metadata Instead of a Redirect
+///
+/// With `?r=0` or `?no_redirect=true` the handler answers `200 OK` with the
+/// same JSON as [`get_url_info`](crate::routes::get_url_info) and does not
+/// redirect. Nobody followed the link, so no click is recorded and no webhook
+/// is fired.
+///
+/// # A/B Tests
+///
+/// A URL shortened with `ab_targets` redirects each visit to one of them,
+/// drawn by weight, and stores the variant number with the click. These
+/// redirects are always `307` with `Cache-Control: no-cache`, so a browser
+/// does not stick to its first draw. The `ETag` is that of the drawn target.
+///
+/// # Mobile Destinations
+///
+/// A URL shortened with a `mobile_url` sends visitors whose `User-Agent`
+/// mentions `iPhone`, `Android` or `Mobile` there instead, ahead of any A/B
+/// draw. Its redirects carry `Vary: user-agent`. Every click is stored with
+/// its device type, `mobile`, `desktop` or `unknown` without a `User-Agent`.
+///
+/// # Password Protection
+///
+/// A URL shortened with a `password` answers `401 Unauthorized` with a
+/// password form until the request carries an `unlock_token` cookie for the
+/// code, issued by [`post_unlock`](crate::routes::post_unlock). No click is
+/// recorded for the form.
+///
+/// # Caching
+///
+/// Targets are kept in [`AppState::redirect_cache`](crate::state::AppState::redirect_cache)
+/// after the first lookup, so hot codes skip the database until the entry
+/// expires or the code is retargeted or deleted through the API. Other
+/// instances sharing the database only see those changes after `cache.ttl_seconds`.
+///
+/// # Click Tracking
+///
+/// Every redirect is stored as a click with the client IP and the origin
+/// (`scheme://host`) of its `Referer`, for the admin analytics page and
+/// `GET /api/urls/{id}/stats`. Browsers that cached a permanent redirect do not
+/// come back, so repeat visits through them are not counted.
+///
+/// Once the click is stored, a `redirect` event is sent to the configured
+/// [`webhooks`](crate::infrastructure::webhooks).
+///
+/// # Click Limits
+///
+/// A URL shortened with `max_clicks` is deactivated by the click that
+/// reaches the limit; from then on its codes answer `410 Gone`. Nothing
+/// reactivates it, not even shortening the same URL again.
+///
+/// # Vanity Domains
+///
+/// A request whose `Host` is a user's vanity domain only reaches the codes of
+/// URLs that user owns; any other code answers `404 Not Found` there. Requests
+/// on the host of `application.base_url` reach every code.
 ///
 /// # Status Codes
 ///
+/// - `200 OK` - Metadata returned for `?r=0` / `?no_redirect=true`
+/// - `304 Not Modified` - `If-None-Match` names the current `ETag`
+/// - `307 Temporary Redirect` - URL found, temporary redirect requested
 /// - `308 Permanent Redirect` - URL found and redirect successful
-/// - `404 Not Found` - Short URL not found in database
+/// - `400 Bad Request` - Unknown `redirect` value
+/// - `401 Unauthorized` - Password-protected URL not unlocked; the password form is shown
+/// - `404 Not Found` - Short URL not found in database, or owned by someone
+///   other than the user whose vanity domain was requested
+/// - `410 Gone` - The URL expired, or reached its `max_clicks` and was deactivated
 /// - `500 Internal Server Error` - Database error occurred
 ///
 /// # Tracing
@@ -53,6 +397,10 @@ use axum_macros::debug_handler;
 /// - Not found errors are logged at error level
 /// - Database errors are logged at error level
 ///
+/// The `redirect` span carries `code`, `original_url`, `click_count` (only
+/// known when the code was not served from the redirect cache) and, with
+/// geolocation on, `country_code`, so every log line of the request has them.
+///
 /// # Examples
 ///
 /// ```bash
@@ -60,6 +408,9 @@ use axum_macros::debug_handler;
 /// curl -L http://localhost:8000/api/redirect/AbC123
 ///
 /// # Expected behavior: HTTP 308 redirect to original URL
+///
+/// # Show where it leads instead
+/// curl "http://localhost:8000/AbC123?r=0"
 /// ```
 ///
 /// # Error Handling
@@ -74,37 +425,462 @@ use axum_macros::debug_handler;
 /// - Database queries are optimized for fast lookups
 /// - Redirects are processed asynchronously
 /// - Error responses are minimal to reduce bandwidth
+#[utoipa::path(
+    get,
+    path = "/api/redirect/{id}",
+    tag = "Redirect",
+    summary = "Redirect to Original URL",
+    description = "Redirect to the original URL associated with the given short ID. Also served at /{id}.",
+    params(
+        ("id" = String, Path, description = "The short URL identifier", example = "AbC123"),
+        RedirectParams,
+        RedirectQueryParams,
+    ),
+    responses(
+        (status = 200, description = "Metadata of the short URL, returned for `r=0` or `no_redirect=true`", body = ApiResponse<UrlMetadata>),
+        (status = 304, description = "The If-None-Match header names the current ETag; the cached redirect is still valid and no click is recorded"),
+        (status = 307, description = "Temporary redirect to original URL", headers(
+            ("location" = String, description = "The original URL to redirect to"),
+        )),
+        (status = 308, description = "Permanent redirect to original URL", headers(
+            ("location" = String, description = "The original URL to redirect to"),
+            ("etag" = String, description = "Quoted SHA-256 of the original URL"),
+        )),
+        (status = 400, description = "Unknown `redirect` value", body = ApiError),
+        (status = 401, description = "The short URL is password protected and the request has no valid `unlock_token` cookie; an HTML password form posting to `/{id}/unlock` is returned", content_type = "text/html", body = String),
+        (status = 404, description = "Short URL not found", body = ApiError),
+        (status = 410, description = "The short URL expired, or reached its `max_clicks` and was deactivated", body = ApiError),
+    )
+)]
 #[debug_handler]
-#[tracing::instrument(name = "redirect" skip(state))]
+#[tracing::instrument(
+    name = "redirect",
+    skip(state, headers),
+    fields(
+        code = %id,
+        original_url = tracing::field::Empty,
+        click_count = tracing::field::Empty,
+        country_code = tracing::field::Empty,
+    )
+)]
 pub async fn get_redirect(
     State(state): State<AppState>,
+    Extension(real_ip): Extension<RealIp>,
     Path(id): Path<String>,
-) -> Result<impl IntoResponse, ApiError> {
-    // Validate against configured length and alphabet before DB lookup
-    // check length (use char count to be safe)
-    if id.chars().count() > MAX_ALIAS_LENGTH {
-        tracing::info!("rejecting redirect: invalid id length");
-        return Err(ApiError::NotFound("URL not found".to_string()));
+    Query(params): Query<RedirectParams>,
+    Query(mode): Query<RedirectQueryParams>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    if mode.wants_metadata() {
+        return get_url_info(State(state), Path(id), headers).await;
     }
+    let bloom_checked = reject_unknown_id(&state, &id)?;
 
-    // Use precomputed allowed_chars from AppState
-    if id.chars().any(|c| !state.allowed_chars.contains(&c)) {
-        tracing::info!("rejecting redirect: id contains invalid characters");
-        return Err(ApiError::NotFound("URL not found".to_string()));
+    let target = resolve_target(&state, &id, bloom_checked, &real_ip, &headers).await?;
+    if let Some(password_hash) = &target.password_hash
+        && !is_unlocked(&state, &id, password_hash, &headers)
+    {
+        return password_challenge(&state, &id, StatusCode::UNAUTHORIZED, None);
     }
+    let device = DeviceType::from_headers(&headers);
+    let varies_by_device = target.mobile_url.is_some();
+    let click_limit = target.max_clicks.map(|max| (max, target.url.clone()));
+    let (variant, target) = target.choose(device);
+    let kind = params.redirect.unwrap_or(target.redirect_type);
 
-    if !state.blooms.s2l.may_contain(&id) {
-        tracing::info!("rejecting redirect: id is not in the short to long filter");
+    // A revalidation is the client reusing a redirect it already followed, so
+    // it is not counted as another click
+    let revalidated = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| target.matches(v));
+    if revalidated {
+        let mut response = not_modified(kind, &target);
+        if varies_by_device {
+            vary_by_user_agent(&mut response);
+        }
+        return Ok(response);
+    }
+    #[cfg(feature = "metrics")]
+    crate::telemetry::metrics::record_redirect(&id);
+
+    // A failed click record should not stop the redirect
+    let referrer = headers.get(header::REFERER).and_then(|v| v.to_str().ok());
+    let user_agent = headers
+        .get(header::USER_AGENT)
+        .and_then(|v| v.to_str().ok());
+    let clicked_at = Utc::now();
+    let country = state
+        .country_lookup()
+        .and_then(|lookup| lookup.country_code(real_ip.0));
+    if let Some(country) = &country {
+        tracing::Span::current().record("country_code", country.as_str());
+    }
+    match state
+        .database()
+        .record_click(
+            &id,
+            clicked_at,
+            Some(&real_ip.to_string()),
+            referrer,
+            variant,
+            Some(device.as_str()),
+            country.as_deref(),
+            user_agent,
+        )
+        .await
+    {
+        Ok(()) => {
+            spawn_publish(
+                state.event_publisher(),
+                ClickEvent {
+                    code: id.clone(),
+                    clicked_at,
+                    ip: Some(real_ip.to_string()),
+                    referrer: referrer.map(str::to_string),
+                    variant,
+                    device_type: Some(device.as_str().to_string()),
+                    country_code: country.clone(),
+                },
+            );
+            fire_webhooks(
+                state.http_client(),
+                &state.config().webhooks,
+                &WebhookEvent {
+                    event: "redirect",
+                    code: id.clone(),
+                    original_url: target.url.clone(),
+                    ip: real_ip.to_string(),
+                    user_agent: user_agent.map(str::to_string),
+                    timestamp: clicked_at,
+                },
+            );
+            if let Some((max_clicks, url)) = click_limit {
+                enforce_click_limit(&state, &id, max_clicks, &url).await;
+            }
+        }
+        Err(e) => tracing::warn!("failed to record click: {}", e),
+    }
+
+    let mut response = redirect_response(kind, &target, &id, &state.config().application.base_url);
+    if varies_by_device {
+        vary_by_user_agent(&mut response);
+    }
+    Ok(response)
+}
+
+/// Answers `HEAD /{id}` for link checkers.
+///
+/// Sends the same status and headers as [`get_redirect`], including
+/// `Location`, with an empty body. A `HEAD` request is not someone following
+/// the link, so no click is recorded and no webhook is fired.
+///
+/// # Endpoint
+///
+/// `HEAD /{id}` and `HEAD /api/redirect/{id}`
+///
+/// # Status Codes
+///
+/// - `307 Temporary Redirect` / `308 Permanent Redirect` - URL found
+/// - `400 Bad Request` - Unknown `redirect` value
+/// - `401 Unauthorized` - Password-protected URL not unlocked
+/// - `404 Not Found` - Short URL not found in database
+/// - `410 Gone` - The URL expired, or reached its `max_clicks` and was deactivated
+/// - `500 Internal Server Error` - Database error occurred
+#[utoipa::path(
+    head,
+    path = "/api/redirect/{id}",
+    tag = "Redirect",
+    summary = "Check Redirect",
+    description = "Same status and headers as GET, with an empty body; no click is recorded. Also served at /{id}.",
+    params(
+        ("id" = String, Path, description = "The short URL identifier"),
+        RedirectParams,
+    ),
+    responses(
+        (status = 307, description = "Temporary redirect to original URL"),
+        (status = 308, description = "Permanent redirect to original URL"),
+        (status = 401, description = "Password-protected URL not unlocked"),
+        (status = 404, description = "Short URL not found"),
+        (status = 410, description = "The short URL expired, or reached its `max_clicks` and was deactivated"),
+    )
+)]
+#[tracing::instrument(name = "redirect_head", skip(state, headers))]
+pub async fn get_redirect_head(
+    State(state): State<AppState>,
+    Extension(real_ip): Extension<RealIp>,
+    Path(id): Path<String>,
+    Query(params): Query<RedirectParams>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let bloom_checked = reject_unknown_id(&state, &id)?;
+
+    let target = resolve_target(&state, &id, bloom_checked, &real_ip, &headers).await?;
+    if let Some(password_hash) = &target.password_hash
+        && !is_unlocked(&state, &id, password_hash, &headers)
+    {
+        return password_challenge(&state, &id, StatusCode::UNAUTHORIZED, None);
+    }
+    let varies_by_device = target.mobile_url.is_some();
+    let (_, target) = target.choose(DeviceType::from_headers(&headers));
+    let kind = params.redirect.unwrap_or(target.redirect_type);
+    let mut response = redirect_response(kind, &target, &id, &state.config().application.base_url);
+    if varies_by_device {
+        vary_by_user_agent(&mut response);
+    }
+    Ok(response)
+}
+
+/// Answers `OPTIONS /{id}` with the methods the redirect routes accept.
+#[utoipa::path(
+    options,
+    path = "/api/redirect/{id}",
+    tag = "Redirect",
+    summary = "Allowed Methods",
+    description = "Lists the methods accepted by the redirect routes. Also served at /{id}.",
+    params(("id" = String, Path, description = "The short URL identifier")),
+    responses(
+        (status = 204, description = "Allowed methods", headers(
+            ("allow" = String, description = "`GET, HEAD, OPTIONS`"),
+        )),
+    )
+)]
+pub async fn options_redirect() -> impl IntoResponse {
+    (
+        StatusCode::NO_CONTENT,
+        [(header::ALLOW, "GET, HEAD, OPTIONS")],
+    )
+}
+
+/// Finds the redirect target of `id`, from the redirect cache when it holds
+/// the code.
+///
+/// `bloom_checked` tells whether [`reject_unknown_id`] passed `id` through the
+/// Bloom filter; only then does a database lookup count as a true or false
+/// positive. Cache hits are not counted.
+///
+/// Fails with `404 Not Found` when the request came in on a user's vanity
+/// domain and someone else owns the code, and with `410 Gone` once the code's
+/// URL has been deactivated or has expired. Cached targets are checked against
+/// `expires_at` on every hit, so an expiry never waits for the cache to evict.
+async fn resolve_target(
+    state: &AppState,
+    id: &str,
+    bloom_checked: bool,
+    real_ip: &RealIp,
+    headers: &HeaderMap,
+) -> Result<CachedRedirect, ApiError> {
+    let target = match state.redirect_cache().get(id) {
+        Some(target) => {
+            tracing::Span::current().record("original_url", target.url.as_str());
+            state.redirect_cache_counters().record(true);
+            #[cfg(feature = "metrics")]
+            crate::telemetry::metrics::record_cache_hit();
+            tracing::info!(client_ip = %real_ip, "cached URL retrieved, redirecting...");
+            target
+        }
+        None => {
+            state.redirect_cache_counters().record(false);
+            let target = lookup_target(state, id, bloom_checked, real_ip).await?;
+            state
+                .redirect_cache()
+                .insert(id.to_string(), target.clone());
+            target
+        }
+    };
+    if let Some(owner) = vanity_domain_owner(state, headers).await?
+        && target.owner_id != Some(owner)
+    {
         return Err(ApiError::NotFound("URL not found".to_string()));
     }
+    if target.deactivated {
+        return Err(ApiError::Gone(
+            "URL reached its click limit and is no longer active".to_string(),
+        ));
+    }
+    if target
+        .expires_at
+        .is_some_and(|expires_at| expires_at <= Utc::now())
+    {
+        return Err(ApiError::Gone("URL has expired".to_string()));
+    }
+    Ok(target)
+}
 
-    // Proceed with DB lookup
-    match state.database.get_url(&id).await {
-        Ok(url) => {
-            tracing::info!("shortened URL retrieved, redirecting...");
-            Ok(Redirect::permanent(&url))
+/// The user whose vanity domain the request came in on, if any.
+///
+/// Requests on the host of `application.base_url` are never looked up, so no
+/// one can claim the shortener's own domain for their codes.
+async fn vanity_domain_owner(
+    state: &AppState,
+    headers: &HeaderMap,
+) -> Result<Option<Uuid>, ApiError> {
+    let Some(host) = headers.typed_get::<Host>() else {
+        return Ok(None);
+    };
+    let host = host.hostname().trim_end_matches('.').to_ascii_lowercase();
+    let base_host = url::Url::parse(&state.config().application.base_url)
+        .ok()
+        .and_then(|base| base.host_str().map(str::to_ascii_lowercase));
+    if base_host.as_deref() == Some(host.as_str()) {
+        return Ok(None);
+    }
+    state
+        .user_service()
+        .vanity_domain_owner(&host)
+        .await
+        .map_err(|e| {
+            tracing::error!("Database error on looking up vanity domain: {}", e);
+            ApiError::Internal(e.to_string())
+        })
+}
+
+/// Deactivates the URL of `code` once its clicks reach `max_clicks`, and
+/// drops the cached redirects of `url` so its codes answer `410 Gone`.
+///
+/// Counting and deactivating are separate statements, so redirects racing
+/// each other can all count below the limit before any of them deactivates
+/// the URL; under load a link may be followed a few times past `max_clicks`.
+async fn enforce_click_limit(state: &AppState, code: &str, max_clicks: u64, url: &str) {
+    let clicks = match state.database().get_click_count(code).await {
+        Ok(clicks) => clicks,
+        Err(e) => {
+            tracing::warn!("failed to count clicks: {}", e);
+            return;
+        }
+    };
+    if clicks < max_clicks {
+        return;
+    }
+    match state.database().deactivate_url(code).await {
+        Ok(()) => {
+            tracing::info!(clicks, max_clicks, "click limit reached, URL deactivated");
+            forget_cached_redirects(state.redirect_cache(), code, Some(url));
+        }
+        Err(e) => tracing::warn!("failed to deactivate URL: {}", e),
+    }
+}
+
+/// Redirects to `target` with the caching headers of `kind`.
+///
+/// `Link` headers point at the metadata of `code` on `base_url`
+/// (`rel="describedby"`) and at the destination (`rel="canonical"`).
+fn redirect_response(
+    kind: RedirectKind,
+    target: &CachedRedirect,
+    code: &str,
+    base_url: &str,
+) -> Response {
+    let url = target.url.as_str();
+    let mut response = match kind {
+        RedirectKind::Permanent => (
+            [
+                (header::CACHE_CONTROL, PERMANENT_CACHE_CONTROL),
+                (header::REFERRER_POLICY, "no-referrer"),
+            ],
+            Redirect::permanent(url),
+        )
+            .into_response(),
+        RedirectKind::Temporary => (
+            [
+                (header::CACHE_CONTROL, TEMPORARY_CACHE_CONTROL),
+                (header::REFERRER_POLICY, "no-referrer"),
+            ],
+            Redirect::temporary(url),
+        )
+            .into_response(),
+    };
+
+    let links = [
+        format!("<{}/api/urls/{}>; rel=\"describedby\"", base_url, code),
+        format!("<{}>; rel=\"canonical\"", url),
+    ];
+    for link in links {
+        if let Ok(value) = HeaderValue::from_str(&link) {
+            response.headers_mut().append(header::LINK, value);
+        }
+    }
+    set_etag(&mut response, target);
+    response
+}
+
+/// Tells a client whose cached redirect is still current to keep using it.
+fn not_modified(kind: RedirectKind, target: &CachedRedirect) -> Response {
+    let cache_control = match kind {
+        RedirectKind::Permanent => PERMANENT_CACHE_CONTROL,
+        RedirectKind::Temporary => TEMPORARY_CACHE_CONTROL,
+    };
+    let mut response = (
+        StatusCode::NOT_MODIFIED,
+        [(header::CACHE_CONTROL, cache_control)],
+    )
+        .into_response();
+    set_etag(&mut response, target);
+    response
+}
+
+/// Marks a redirect of a code with a `mobile_url` as depending on the
+/// `User-Agent`, so shared caches keep mobile and desktop answers apart.
+fn vary_by_user_agent(response: &mut Response) {
+    response
+        .headers_mut()
+        .append(header::VARY, HeaderValue::from_static("user-agent"));
+}
+
+fn set_etag(response: &mut Response, target: &CachedRedirect) {
+    if let Ok(value) = HeaderValue::from_str(&target.etag) {
+        response.headers_mut().insert(header::ETAG, value);
+    }
+}
+
+/// Reads the redirect target of `id` from the database, retrying transient
+/// database errors, and records the Bloom filter outcome when `bloom_checked`.
+async fn lookup_target(
+    state: &AppState,
+    id: &str,
+    bloom_checked: bool,
+    real_ip: &RealIp,
+) -> Result<CachedRedirect, ApiError> {
+    let meta = retry_transient("get_url_with_metadata", || {
+        state.database().get_url_with_metadata(id)
+    })
+    .await;
+    match meta {
+        Ok(meta) => {
+            if bloom_checked {
+                state.blooms().record_lookup(BloomOutcome::TruePositive);
+            }
+            tracing::Span::current()
+                .record("original_url", meta.url.as_str())
+                .record("click_count", meta.click_count);
+            tracing::info!(
+                client_ip = %real_ip,
+                click_count = meta.click_count,
+                expires_at = ?meta.expires_at,
+                is_active = meta.is_active,
+                created_at = %meta.created_at,
+                redirect_type = %meta.redirect_type,
+                "shortened URL retrieved, redirecting..."
+            );
+            let ab_targets = state.database().get_ab_targets(id).await.map_err(|e| {
+                tracing::error!("Database error: {}", e);
+                ApiError::Internal(e.to_string())
+            })?;
+            Ok(CachedRedirect {
+                password_hash: meta.password_hash,
+                ab_targets,
+                mobile_url: meta.mobile_url,
+                max_clicks: meta.max_clicks.map(|max| max as u64),
+                deactivated: meta.deactivated_at.is_some(),
+                expires_at: meta.expires_at,
+                owner_id: meta.owner_id,
+                ..CachedRedirect::new(meta.url, RedirectKind::from_stored(&meta.redirect_type))
+            })
         }
         Err(DatabaseError::NotFound) => {
+            if bloom_checked {
+                state.blooms().record_lookup(BloomOutcome::FalsePositive);
+            }
             tracing::error!("shortened URL not found in the database...");
             Err(ApiError::NotFound("URL not found".to_string()))
         }
@@ -114,3 +890,180 @@ pub async fn get_redirect(
         }
     }
 }
+
+/// Link preview handler that shows where a short code leads without redirecting.
+///
+/// Renders `preview.html` with the destination domain, an HTTPS indicator, the
+/// full destination URL and a "Continue" button linking to `/{id}`. Each view
+/// increments the code's `preview_views` counter, which is kept separate from
+/// the redirect click count. The target is resolved like a redirect, so
+/// expired and deactivated codes, and codes requested on someone else's
+/// vanity domain, reveal nothing.
+///
+/// # Endpoint
+///
+/// `GET /{id}/preview`
+///
+/// # Arguments
+///
+/// * `State(state)` - Application state containing database connection
+/// * `Extension(real_ip)` - Client IP, for the lookup logs
+/// * `Path(id)` - Short URL identifier extracted from the URL path
+///
+/// # Caching
+///
+/// The response carries `Cache-Control: no-store` so that a retargeted or
+/// deleted code is never shown from a stale copy.
+///
+/// # Status Codes
+///
+/// - `200 OK` - Preview page rendered
+/// - `401 Unauthorized` - Password-protected URL not unlocked; the password form is shown
+/// - `404 Not Found` - Short URL not found in database
+/// - `410 Gone` - The URL expired, or reached its `max_clicks` and was deactivated
+/// - `500 Internal Server Error` - Database or template error occurred
+///
+/// # Examples
+///
+/// ```bash
+/// curl http://localhost:8000/AbC123/preview
+/// ```
+#[utoipa::path(
+    get,
+    path = "/{id}/preview",
+    tag = "Redirect",
+    summary = "Preview Short URL",
+    description = "HTML page showing where a short code leads, with a button to continue there",
+    params(("id" = String, Path, description = "The short URL identifier")),
+    responses(
+        (status = 200, description = "Preview page", content_type = "text/html", body = String),
+        (status = 401, description = "Password-protected URL not unlocked; the password form is shown", content_type = "text/html", body = String),
+        (status = 404, description = "Short URL not found", body = ApiError),
+        (status = 410, description = "The short URL expired, or reached its `max_clicks` and was deactivated", body = ApiError),
+    )
+)]
+#[debug_handler]
+#[tracing::instrument(name = "redirect_preview", skip(state, headers))]
+pub async fn get_redirect_preview(
+    State(state): State<AppState>,
+    Extension(real_ip): Extension<RealIp>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let bloom_checked = reject_unknown_id(&state, &id)?;
+
+    let target = resolve_target(&state, &id, bloom_checked, &real_ip, &headers).await?;
+    if let Some(password_hash) = &target.password_hash
+        && !is_unlocked(&state, &id, password_hash, &headers)
+    {
+        return password_challenge(&state, &id, StatusCode::UNAUTHORIZED, None);
+    }
+    let url = target.url;
+
+    // A failed counter update should not stop the user from seeing the page
+    if let Err(e) = state.database().record_preview_view(&id).await {
+        tracing::warn!("failed to record preview view: {}", e);
+    }
+
+    let parsed = url::Url::parse(&url).ok();
+    let domain = parsed
+        .as_ref()
+        .and_then(|u| u.host_str())
+        .unwrap_or(&url)
+        .to_string();
+    let is_https = parsed.as_ref().is_some_and(|u| u.scheme() == "https");
+
+    let mut context = Context::new();
+    context.insert("title", "URL Shortener");
+    context.insert("page", "Link Preview");
+    context.insert("code", &id);
+    context.insert("url", &url);
+    context.insert("domain", &domain);
+    context.insert("is_https", &is_https);
+
+    let body = state.templates().render("preview.html", &context)?;
+
+    Ok(([(header::CACHE_CONTROL, "no-store")], Html(body)).into_response())
+}
+
+/// Rejects ids that cannot be a stored short code before touching the database.
+///
+/// Checks the configured length and alphabet, then the short-to-long Bloom
+/// filter. Every rejection is reported as `404 Not Found`. Bloom filter
+/// rejections are counted as misses; callers record whether a passed id was
+/// then found in the database. While the filter is still being rebuilt at
+/// startup it is skipped, and the database decides.
+///
+/// Returns whether the Bloom filter was consulted, and so passed `id`.
+fn reject_unknown_id(state: &AppState, id: &str) -> Result<bool, ApiError> {
+    // check length (use char count to be safe)
+    if id.chars().count() > MAX_ALIAS_LENGTH {
+        tracing::info!("rejecting redirect: invalid id length");
+        return Err(ApiError::NotFound("URL not found".to_string()));
+    }
+
+    // Use precomputed allowed_chars from AppState
+    if id.chars().any(|c| !state.allowed_chars().contains(&c)) {
+        tracing::info!("rejecting redirect: id contains invalid characters");
+        return Err(ApiError::NotFound("URL not found".to_string()));
+    }
+
+    if !state.blooms().is_ready() {
+        tracing::warn!("Bloom filter is still being rebuilt; looking the id up in the database");
+        return Ok(false);
+    }
+    if !state.blooms().s2l.may_contain(id) {
+        state.blooms().record_lookup(BloomOutcome::Miss);
+        tracing::info!("rejecting redirect: id is not in the short to long filter");
+        return Err(ApiError::NotFound("URL not found".to_string()));
+    }
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::configuration::get_configuration;
+    use crate::database::{SqliteUrlDatabase, UrlDatabase};
+    use crate::state::AppStateBuilder;
+    use tracing_test::traced_test;
+
+    #[tokio::test]
+    #[traced_test]
+    async fn redirect_span_records_the_original_url() {
+        let database = Arc::new(
+            SqliteUrlDatabase::in_memory_with_migrations()
+                .await
+                .unwrap(),
+        );
+        database
+            .insert_url("traced1", "https://www.example.com/traced")
+            .await
+            .unwrap();
+        let state = AppStateBuilder::new()
+            .config(get_configuration().unwrap())
+            .database(database as Arc<dyn UrlDatabase>)
+            .build()
+            .await
+            .unwrap();
+
+        let response = get_redirect(
+            State(state),
+            Extension(RealIp("203.0.113.7".parse().unwrap())),
+            Path("traced1".to_string()),
+            Query(RedirectParams { redirect: None }),
+            Query(RedirectQueryParams::default()),
+            HeaderMap::new(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+        assert!(logs_contain(
+            "original_url=\"https://www.example.com/traced\""
+        ));
+        assert!(logs_contain("click_count=0"));
+        assert!(logs_contain("code=traced1"));
+    }
+}
@@ -0,0 +1,121 @@
+//! # URL Search Handler
+//!
+//! This module provides the protected endpoint used to find stored URLs by a
+//! substring of their destination, primary code or alias.
+
+use crate::errors::ApiError;
+use crate::models::UrlRecord;
+use crate::response::{ApiResponse, PaginatedResponse};
+use crate::routes::urls::MAX_LIST_LIMIT;
+use crate::state::AppState;
+use axum::extract::{Query, State};
+use axum_macros::debug_handler;
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+/// Number of results per page when no `per_page` is given.
+pub const DEFAULT_SEARCH_PER_PAGE: u64 = 20;
+/// Maximum accepted length of the search term in characters.
+pub const MAX_SEARCH_QUERY_LENGTH: usize = 256;
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SearchParams {
+    /// Substring to look for in the URL, code or alias
+    pub q: String,
+    /// 1-based page number
+    pub page: Option<u64>,
+    /// Results per page (capped at [`MAX_LIST_LIMIT`])
+    pub per_page: Option<u64>,
+}
+
+/// Searches stored URLs by destination, primary code or alias.
+///
+/// Matching is a case-insensitive substring match; `%` and `_` in the term are
+/// matched literally. No matches is an empty page, not a 404.
+///
+/// # Endpoint
+///
+/// `GET /api/urls/search?q=example&page=1&per_page=20` (protected - requires API key)
+///
+/// # Response Format
+///
+/// ```json
+/// {
+///   "success": true,
+///   "message": "ok",
+///   "status": 200,
+///   "time": "2025-01-18T12:00:00Z",
+///   "data": {
+///     "items": [
+///       { "code": "AbC123", "url": "https://www.example.com/" }
+///     ],
+///     "page": 1,
+///     "per_page": 20,
+///     "has_more": false
+///   }
+/// }
+/// ```
+///
+/// # Status Codes
+///
+/// - `200 OK` - Search completed (possibly with no results)
+/// - `400 Bad Request` - Missing `q` parameter
+/// - `401 Unauthorized` - Missing or invalid API key
+/// - `422 Unprocessable Entity` - Empty or overlong search term
+/// - `500 Internal Server Error` - Database error occurred
+#[utoipa::path(
+    get,
+    path = "/api/urls/search",
+    tag = "URL Management",
+    summary = "Search URLs",
+    description = "Case-insensitive substring search over destination URLs, codes and aliases",
+    params(SearchParams),
+    security(("ApiKeyAuth" = [])),
+    responses(
+        (status = 200, description = "Search completed (possibly with no results)", body = ApiResponse<PaginatedResponse<UrlRecord>>),
+        (status = 400, description = "Missing `q` parameter", body = ApiError),
+        (status = 401, description = "Missing or invalid API key", body = ApiError),
+        (status = 422, description = "Empty or overlong search term", body = ApiError),
+    )
+)]
+#[debug_handler]
+#[tracing::instrument(name = "search_urls", skip(state))]
+pub async fn search_urls(
+    State(state): State<AppState>,
+    Query(params): Query<SearchParams>,
+) -> Result<ApiResponse<PaginatedResponse<UrlRecord>>, ApiError> {
+    let q = params.q.trim();
+    if q.is_empty() {
+        return Err(ApiError::Unprocessable(
+            "Search query cannot be empty".to_string(),
+        ));
+    }
+    if q.chars().count() > MAX_SEARCH_QUERY_LENGTH {
+        return Err(ApiError::Unprocessable(format!(
+            "Search query exceeds maximum length of {} characters",
+            MAX_SEARCH_QUERY_LENGTH
+        )));
+    }
+
+    let page = params.page.unwrap_or(1).max(1);
+    let per_page = params
+        .per_page
+        .unwrap_or(DEFAULT_SEARCH_PER_PAGE)
+        .clamp(1, MAX_LIST_LIMIT);
+    let offset = (page - 1).saturating_mul(per_page);
+
+    // Fetch one extra row to learn whether another page follows
+    let records = state
+        .database()
+        .search_urls(q, offset, per_page + 1)
+        .await
+        .map_err(|e| {
+            tracing::error!("Database error: {}", e);
+            ApiError::Internal(e.to_string())
+        })?;
+
+    Ok(ApiResponse::success(PaginatedResponse::from_overfetch(
+        records, page, per_page,
+    )))
+}
@@ -4,38 +4,257 @@
 //! It processes requests to shorten URLs and stores them in the database with
 //! unique identifiers.
 
-use crate::database::MAX_ALIAS_LENGTH;
+use crate::configuration::{UtmSettings, ValidationMode};
+use crate::core::extractors::auth_user::AuthenticatedUser;
+use crate::core::security::password::{NormalizedPassword, hash_password};
 use crate::errors::ApiError;
+use crate::models::AbTarget;
 use crate::response::ApiResponse;
+use crate::routes::admin::FLASH_COOKIE;
+use crate::routes::redirect::RedirectKind;
 use crate::state::AppState;
-use crate::{database::DatabaseError, models::UpsertResult};
-use axum::extract::{Query, State};
+use crate::validation::{
+    alias_prefix, apply_validation_mode, check_destination, check_reachability, normalize_url,
+    validate_alias, validate_tag,
+};
+use crate::{
+    database::{DatabaseError, retry_transient},
+    models::UpsertResult,
+};
+use axum::extract::{FromRequest, Query, Request, State};
+use axum::http::{StatusCode, header};
+use axum::response::{IntoResponse, Redirect, Response};
+use axum::{Form, Json};
+use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
 use axum_extra::{TypedHeader, headers::Host};
 use axum_macros::debug_handler;
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use secrecy::{ExposeSecret, SecretString};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
 use tracing::instrument;
+use utoipa::{IntoParams, ToSchema};
 
 /// Maximum allowed URL length in characters.
 ///
 /// RFC 2616 doesn't specify a limit, but most browsers support 2000+ characters.
 /// We use 2048 as a reasonable limit to prevent abuse while supporting legitimate URLs.
-const MAX_URL_LENGTH: usize = 2048;
+pub(crate) const MAX_URL_LENGTH: usize = 2048;
 const MAX_ID_RETRIES: usize = 8;
+/// Longest pause between two short code collision retries.
+const MAX_RETRY_DELAY: Duration = Duration::from_millis(200);
+/// Maximum number of entries accepted by `POST /api/shorten/batch`.
+pub const MAX_BATCH_ITEMS: usize = 50;
+/// Maximum number of A/B targets one short URL may split traffic between.
+pub const MAX_AB_TARGETS: usize = 10;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct ShortenParams {
+    /// Optional custom alias to use instead of generating a random ID.
+    ///
+    /// Deprecated in favour of the `alias` field of a JSON body; a body alias
+    /// takes precedence when both are given.
+    pub alias: Option<String>,
+    /// Optional tags to attach, given as a comma-separated list (`?tags=a,b`)
+    #[serde(default, deserialize_with = "comma_separated")]
+    #[param(value_type = Option<String>, example = "campaign-q3,newsletter")]
+    pub tags: Option<Vec<String>>,
+    /// Optional redirect type to store for the URL (`permanent` or `temporary`)
+    pub redirect: Option<RedirectKind>,
+}
+
+/// Deserializes `"a,b"` into `Some(vec!["a", "b"])`, skipping empty entries.
+fn comma_separated<'de, D>(deserializer: D) -> Result<Option<Vec<String>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let raw: Option<String> = Option::deserialize(deserializer)?;
+    Ok(raw.map(|s| {
+        s.split(',')
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(str::to_string)
+            .collect()
+    }))
+}
+
+/// JSON body accepted by `POST /api/shorten`.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ShortenRequest {
+    /// The URL to shorten
+    #[schema(example = "https://www.example.com/page", max_length = 2048)]
+    pub url: String,
     /// Optional custom alias to use instead of generating a random ID
+    #[schema(example = "mypage")]
     pub alias: Option<String>,
+    /// Optional lifetime of the short URL in seconds (must be positive)
+    #[schema(example = 3600, minimum = 1)]
+    pub ttl_seconds: Option<u64>,
+    /// Optional passphrase visitors must enter before being redirected
+    #[schema(value_type = Option<String>, format = Password)]
+    pub password: Option<SecretString>,
+    /// Optional destinations to split redirects between, weights summing to 100
+    pub ab_targets: Option<Vec<AbTarget>>,
+    /// Optional destination for visitors on mobile devices
+    pub mobile_url: Option<String>,
+    /// Optional number of clicks after which the short URL stops redirecting
+    pub max_clicks: Option<u64>,
+}
+
+/// Form fields accepted by `POST /api/shorten` from an HTML form
+/// (`Content-Type: application/x-www-form-urlencoded`).
+#[derive(Debug, Deserialize)]
+pub struct ShortenFormData {
+    /// The URL to shorten
+    pub url: String,
+    /// Optional custom alias; an empty field means none
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub alias: Option<String>,
+    /// Optional lifetime of the short URL in seconds; an empty field means none
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub ttl_seconds: Option<u64>,
+}
+
+/// Deserializes an empty form field as `None`, since browsers submit inputs
+/// left blank as `name=`.
+fn empty_as_none<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    let raw: Option<String> = Option::deserialize(deserializer)?;
+    match raw.as_deref().map(str::trim) {
+        None | Some("") => Ok(None),
+        Some(value) => value.parse().map(Some).map_err(serde::de::Error::custom),
+    }
+}
+
+/// Body extractor for `POST /api/shorten`.
+///
+/// The body is read according to its `Content-Type`:
+/// - `application/json` is parsed as a [`ShortenRequest`]
+/// - `application/x-www-form-urlencoded` is parsed as [`ShortenFormData`]
+/// - anything else is taken as the plain-text URL, as before
+#[derive(Debug)]
+pub enum ShortenInput {
+    Text(String),
+    Json(ShortenRequest),
+    Form(ShortenFormData),
+}
+
+impl ShortenInput {
+    /// The URL, alias and TTL to shorten with, whichever format they came in.
+    pub fn into_request(self) -> ShortenRequest {
+        match self {
+            ShortenInput::Text(url) => ShortenRequest {
+                url,
+                alias: None,
+                ttl_seconds: None,
+                password: None,
+                ab_targets: None,
+                mobile_url: None,
+                max_clicks: None,
+            },
+            ShortenInput::Json(body) => body,
+            ShortenInput::Form(form) => ShortenRequest {
+                url: form.url,
+                alias: form.alias,
+                ttl_seconds: form.ttl_seconds,
+                password: None,
+                ab_targets: None,
+                mobile_url: None,
+                max_clicks: None,
+            },
+        }
+    }
+}
+
+impl<S> FromRequest<S> for ShortenInput
+where
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let content_type = req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default();
+
+        if content_type.starts_with("application/json") {
+            let Json(body) = Json::<ShortenRequest>::from_request(req, state)
+                .await
+                .map_err(|r| body_rejection(r.status(), r.body_text()))?;
+            Ok(Self::Json(body))
+        } else if content_type.starts_with("application/x-www-form-urlencoded") {
+            let Form(form) = Form::<ShortenFormData>::from_request(req, state)
+                .await
+                .map_err(|r| body_rejection(r.status(), r.body_text()))?;
+            Ok(Self::Form(form))
+        } else {
+            let url = String::from_request(req, state)
+                .await
+                .map_err(|r| body_rejection(r.status(), r.body_text()))?;
+            Ok(Self::Text(url))
+        }
+    }
+}
+
+/// Maps a body extractor rejection onto the matching [`ApiError`].
+///
+/// Bodies larger than `application.max_body_bytes` fail with a length limit
+/// error before they are fully read, which surfaces here as a 413.
+fn body_rejection(status: StatusCode, text: String) -> ApiError {
+    match status {
+        StatusCode::PAYLOAD_TOO_LARGE => {
+            ApiError::PayloadTooLarge("Request body too large".to_string())
+        }
+        StatusCode::UNPROCESSABLE_ENTITY => ApiError::Unprocessable(text),
+        _ => ApiError::BadRequest(text),
+    }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ShortenResponse {
     /// The shortened URL
+    #[schema(example = "https://localhost:8000/AbC123")]
     pub shortened_url: String,
     /// The original URL that was shortened
+    #[schema(example = "https://www.example.com/page")]
     pub original_url: String,
     /// The unique identifier used in the shortened URL
+    #[schema(example = "AbC123")]
     pub id: String,
+    /// When the short URL stops resolving, if it has an expiry
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BatchShortenItem {
+    /// The URL to shorten
+    pub url: String,
+    /// Optional custom alias to use instead of the generated ID
+    pub alias: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BatchShortenResult {
+    /// Whether this entry was shortened
+    pub success: bool,
+    /// The URL exactly as submitted
+    pub url: String,
+    /// Why the entry was rejected, when `success` is false
+    pub error: Option<String>,
+    /// The shortened URL details, when `success` is true
+    #[serde(flatten)]
+    pub result: Option<ShortenResponse>,
 }
 
 /// URL shortening handler that creates short URLs from long URLs.
@@ -46,23 +265,45 @@ pub struct ShortenResponse {
 ///
 /// # Endpoint
 ///
-/// `POST /api/shorten` (protected - requires API key)
+/// `POST /api/shorten` (protected - requires API key or access token)
 /// `POST /api/public/shorten` (public - no authentication required)
 ///
 /// # Arguments
 ///
 /// * `State(state)` - Application state containing database connection
-/// * `TypedHeader(header)` - Host header for constructing the response URL
-/// * `url` - The URL to shorten (provided in request body as plain text)
+/// * `TypedHeader(header)` - Host header (the response URL is built from
+///   `application.base_url`, never from this header)
+/// * `user` - The signed-in user, when an access token is sent; they become
+///   the owner of a newly shortened URL, whose `shortened_url` is on their
+///   vanity domain if they set one
+/// * `Query(params)` - Optional `alias` (deprecated), comma-separated `tags` to
+///   attach and `redirect` type (`permanent` or `temporary`)
+/// * `input` - The URL to shorten, with an optional alias and TTL
 ///
 /// # Request Format
 ///
-/// The request body should contain the URL to shorten as plain text:
+/// The request body may be a JSON object when sent with
+/// `Content-Type: application/json`:
+///
+/// ```json
+/// { "url": "https://www.example.com/page", "alias": "mypage", "ttl_seconds": 3600 }
+/// ```
+///
+/// Any other body is read as the URL to shorten in plain text:
 ///
 /// ```text
 /// https://www.example.com/very/long/url/with/many/parameters
 /// ```
 ///
+/// An HTML form may post the same fields as
+/// `Content-Type: application/x-www-form-urlencoded`, leaving optional ones blank:
+///
+/// ```text
+/// url=https%3A%2F%2Fwww.example.com%2Fpage&alias=&ttl_seconds=
+/// ```
+///
+/// When both the query string and the body carry an alias, the body wins.
+///
 /// # Response Format
 ///
 /// Returns a JSON response with the shortened URL information:
@@ -76,11 +317,16 @@ pub struct ShortenResponse {
 ///   "data": {
 ///     "shortened_url": "https://localhost:8000/AbC123",
 ///     "original_url": "https://www.example.com/very/long/url",
-///     "id": "AbC123"
+///     "id": "AbC123",
+///     "expires_at": null
 ///   }
 /// }
 /// ```
 ///
+/// A form submission is answered with `303 See Other` to `/admin/shorten`
+/// instead, whether it succeeded or not; the outcome is left in the `flash`
+/// cookie for that page to show.
+///
 /// # URL Generation
 ///
 /// Short URLs are generated using the `nanoid` library with the following characteristics:
@@ -91,7 +337,12 @@ pub struct ShortenResponse {
 /// # Status Codes
 ///
 /// - `200 OK` - URL shortened successfully
-/// - `422 Unprocessable Entity` - Invalid URL format or URL exceeds maximum length
+/// - `303 See Other` - Form submission handled (see the `flash` cookie)
+/// - `400 Bad Request` - Malformed JSON or form body
+/// - `401 Unauthorized` - An access token was sent but is invalid or revoked
+/// - `413 Payload Too Large` - Body exceeds `application.max_body_bytes`
+/// - `422 Unprocessable Entity` - Invalid URL format, URL exceeds maximum length,
+///   or invalid alias or `ttl_seconds`
 /// - `500 Internal Server Error` - Database error or ID collision
 ///
 /// # URL Validation
@@ -102,6 +353,22 @@ pub struct ShortenResponse {
 /// - Must have a valid hostname
 /// - Must not exceed MAX_URL_LENGTH (2048 characters)
 ///
+/// With `application.utm.auto_append` set, the configured UTM parameters are
+/// then added by [`append_utm_params`], and `original_url` in the response is
+/// the URL with them.
+///
+/// With `url_validation.check_reachability` set, the destination must then
+/// answer a `HEAD` request with a non-error status, see [`check_reachability`].
+///
+/// Failed domain filter, private address and reachability checks are
+/// handled as `url_validation.mode` says: rejected with 422 in `strict`
+/// mode, logged and accepted in `warn` mode, and skipped in `permissive`
+/// mode.
+///
+/// With `safe_browsing.enabled` set, a destination, A/B target or mobile URL
+/// Google Safe Browsing lists as a threat is rejected with 422; lookups that
+/// fail let the URL through.
+///
 /// # Tracing
 ///
 /// This handler is instrumented with tracing for request monitoring:
@@ -159,46 +426,204 @@ pub struct ShortenResponse {
 /// - Database inserts are performed asynchronously
 /// - ID generation is fast and collision-resistant
 /// - Response format follows consistent JSON schema for better frontend integration
+#[utoipa::path(
+    post,
+    path = "/api/shorten",
+    tag = "URL Shortening",
+    summary = "Shorten URL (Protected)",
+    description = "Create a short URL from a long URL (requires an API key or a signed-in user's access token). The body may also be the URL as plain text, or an HTML form (`application/x-www-form-urlencoded` with `url`, `alias` and `ttl_seconds`), which is answered with `303 See Other` to `/admin/shorten`.",
+    params(ShortenParams),
+    request_body(content = ShortenRequest, content_type = "application/json"),
+    security(("ApiKeyAuth" = []), ("BearerAuth" = [])),
+    responses(
+        (status = 200, description = "URL shortened successfully", body = ApiResponse<ShortenResponse>),
+        (status = 303, description = "Form submission handled (see the `flash` cookie)"),
+        (status = 400, description = "Malformed JSON or form body", body = ApiError),
+        (status = 401, description = "Missing or invalid API key and access token", body = ApiError),
+        (status = 409, description = "Alias already taken, or a password, A/B targets or a mobile URL were given for a URL that is already shortened", body = ApiError),
+        (status = 413, description = "Body exceeds `application.max_body_bytes`", body = ApiError),
+        (status = 422, description = "Invalid URL format, URL too long, or invalid alias or `ttl_seconds`", body = ApiError),
+    )
+)]
 #[debug_handler]
 #[instrument(name = "shorten", skip(state))]
 pub async fn post_shorten(
     State(state): State<AppState>,
     TypedHeader(header): TypedHeader<Host>,
+    user: Option<AuthenticatedUser>,
     Query(params): Query<ShortenParams>,
-    url: String,
+    input: ShortenInput,
+) -> Result<Response, ApiError> {
+    let from_form = matches!(input, ShortenInput::Form(_));
+    let result = shorten(&state, user, params, input.into_request()).await;
+    if from_form {
+        Ok(flash_redirect(result))
+    } else {
+        result.map(IntoResponse::into_response)
+    }
+}
+
+/// Shortens one URL for [`post_shorten`], whatever format it was submitted in.
+async fn shorten(
+    state: &AppState,
+    user: Option<AuthenticatedUser>,
+    params: ShortenParams,
+    body: ShortenRequest,
 ) -> Result<ApiResponse<ShortenResponse>, ApiError> {
+    let url = body.url;
+
     // 1) Early length validation to prevent resource exhaustion
     if url.len() > MAX_URL_LENGTH {
         tracing::warn!("URL length {} exceeds max {}", url.len(), MAX_URL_LENGTH);
-        return Err(ApiError::Unprocessable(format!(
+        return Err(ApiError::UrlTooLong(format!(
             "URL exceeds maximum allowed length of {} characters",
             MAX_URL_LENGTH
         )));
     }
 
     // 2) Parse and normalize the URL (lowercase host, remove fragments, etc.)
-    let norm = normalize_url(&url).map_err(|e| {
+    let norm = normalize_url(&url).inspect_err(|e| {
         tracing::error!("Unable to parse URL: {}", e);
-        ApiError::Unprocessable(e.to_string())
     })?;
+    let norm = append_utm_params(&norm, &state.config().application.utm)?;
+    check_destination(&norm, state).await?;
+    let validation = &state.config().url_validation;
+    if validation.check_reachability && validation.mode != ValidationMode::Permissive {
+        let reachable = check_reachability(
+            &norm,
+            state.http_client(),
+            &state.config().http_client,
+            Duration::from_millis(validation.timeout_ms),
+            &state
+                .config()
+                .application
+                .ssrf_protection
+                .extra_blocked_cidrs,
+        )
+        .await;
+        apply_validation_mode(validation.mode, &norm, reachable)?;
+    }
 
-    // let hostname = header.hostname();
+    // Short URLs are built from `application.base_url`, or the user's vanity
+    // domain, not `header.hostname()`, since reverse proxies may rewrite the
+    // Host header.
 
-    let (upset, code) = insert_with_retry(&state, &norm).await?;
-    if upset.created {
-        state.blooms.s2l.insert(&code);
+    let tags = params.tags.unwrap_or_default();
+    for tag in &tags {
+        validate_tag(tag)?;
     }
 
+    let ttl_expiry = body.ttl_seconds.map(expiry_from_ttl).transpose()?;
+    let password_hash = body
+        .password
+        .as_ref()
+        .map(|password| hash_url_password(state, password))
+        .transpose()?;
+    let ab_targets = match body.ab_targets {
+        Some(targets) => Some(validate_ab_targets(state, targets).await?),
+        None => None,
+    };
+    let mobile_url = match body.mobile_url {
+        Some(url) => Some(validate_destination(state, &url).await?),
+        None => None,
+    };
+    if body.max_clicks == Some(0) {
+        return Err(ApiError::Unprocessable(
+            "max_clicks must be greater than zero".to_string(),
+        ));
+    }
+
+    let (upset, code) = insert_with_retry(state, &norm, ttl_expiry).await?;
+    // A password, A/B targets, a mobile URL or a click limit can only be set
+    // on a fresh row; an existing one is already shared through its codes.
+    let has_extras = password_hash.is_some()
+        || ab_targets.is_some()
+        || mobile_url.is_some()
+        || body.max_clicks.is_some();
+    if !upset.created && has_extras {
+        return Err(ApiError::Conflict(
+            "URL is already shortened; a password, A/B targets, a mobile URL or a click limit can only be set on a new short URL"
+                .to_string(),
+        ));
+    }
+    if let Some(password_hash) = &password_hash {
+        state
+            .database()
+            .set_url_password(&code, password_hash)
+            .await
+            .map_err(|e| {
+                tracing::error!("Database error on setting password: {}", e);
+                ApiError::Internal(e.to_string())
+            })?;
+    }
+    if let Some(ab_targets) = &ab_targets {
+        state
+            .database()
+            .set_ab_targets(&code, ab_targets)
+            .await
+            .map_err(|e| {
+                tracing::error!("Database error on setting A/B targets: {}", e);
+                ApiError::Internal(e.to_string())
+            })?;
+    }
+    if let Some(mobile_url) = &mobile_url {
+        state
+            .database()
+            .set_mobile_url(&code, mobile_url)
+            .await
+            .map_err(|e| {
+                tracing::error!("Database error on setting mobile URL: {}", e);
+                ApiError::Internal(e.to_string())
+            })?;
+    }
+    if let Some(max_clicks) = body.max_clicks {
+        state
+            .database()
+            .set_max_clicks(&code, max_clicks)
+            .await
+            .map_err(|e| {
+                tracing::error!("Database error on setting click limit: {}", e);
+                ApiError::Internal(e.to_string())
+            })?;
+    }
+    // The TTL and owner only apply to fresh rows; an existing row keeps its
+    // expiry and stays with whoever shortened it first.
+    let expires_at = if upset.created {
+        state.blooms().insert(&code).await;
+        if let Some(user) = &user {
+            state
+                .database()
+                .set_url_owner(&code, user.user_id)
+                .await
+                .map_err(|e| {
+                    tracing::error!("Database error on setting owner: {}", e);
+                    ApiError::Internal(e.to_string())
+                })?;
+        }
+        ttl_expiry
+    } else {
+        state
+            .database()
+            .get_url_with_metadata(&code)
+            .await
+            .ok()
+            .and_then(|meta| meta.expires_at)
+    };
+
     // 3) Insert path: use custom alias if provided, otherwise generate with retries
-    let final_code = if let Some(alias) = params.alias {
-        validate_alias(alias.as_str(), &state)?;
-        match state.database.insert_alias(alias.as_str(), upset.id).await {
+    let final_code = if let Some(alias) = body.alias.or(params.alias) {
+        let alias = validate_alias(alias.as_str(), state)?;
+        match state
+            .database()
+            .insert_alias(alias.as_str(), upset.id)
+            .await
+        {
             Ok(()) => {
-                state.blooms.s2l.insert(&alias);
+                state.blooms().insert(&alias).await;
                 alias
             }
             Err(DatabaseError::Duplicate) => {
-                return Err(ApiError::Conflict("Alias is already taken".to_string()));
+                return Err(ApiError::AliasTaken);
             }
             Err(e) => {
                 tracing::error!("Database error on insert with alias: {}", e);
@@ -209,82 +634,246 @@ pub async fn post_shorten(
         code
     };
 
+    for tag in &tags {
+        state
+            .database()
+            .add_tag(&final_code, tag)
+            .await
+            .map_err(|e| {
+                tracing::error!("Database error on tagging: {}", e);
+                ApiError::Internal(e.to_string())
+            })?;
+    }
+
+    if let Some(kind) = params.redirect {
+        state
+            .database()
+            .set_redirect_type(&final_code, kind.as_str())
+            .await
+            .map_err(|e| {
+                tracing::error!("Database error on setting redirect type: {}", e);
+                ApiError::Internal(e.to_string())
+            })?;
+    }
+
     tracing::info!("URL shortened and saved successfully");
-    Ok(make_response(
-        &state.config.application.base_url,
-        &final_code,
-        &norm,
-    ))
+    let base_url = short_url_base(state, user.as_ref()).await;
+    Ok(make_response(&base_url, &final_code, &norm, expires_at))
 }
 
-/// Parses and normalizes a URL:
-/// - Enforces http/https schemes
-/// - Removes fragments
-/// - Lowercases host
-/// - Validates proper slashes after scheme using manual parsing
-pub fn normalize_url(raw: &str) -> Result<String, ApiError> {
-    let is_http = raw.starts_with("http://");
-    let is_https = raw.starts_with("https://");
-
-    if is_http || is_https {
-        let scheme_len = if is_http { 7 } else { 8 };
-        if raw[scheme_len..].starts_with('/') {
-            return Err(ApiError::Unprocessable(
-                "Wrong number of slashes (separators) in scheme".to_string(),
-            ));
+/// The base of the short URLs made for `user`: their vanity domain, with the
+/// scheme of `application.base_url`, or else `application.base_url` itself.
+///
+/// The base URL is also used when the vanity domain cannot be looked up, as
+/// links on it lead to the same codes.
+async fn short_url_base(state: &AppState, user: Option<&AuthenticatedUser>) -> String {
+    let base_url = &state.config().application.base_url;
+    let Some(user) = user else {
+        return base_url.clone();
+    };
+    match state.user_service().vanity_domain(user.user_id).await {
+        Ok(Some(domain)) => {
+            let scheme = base_url
+                .split_once("://")
+                .map_or("https", |(scheme, _)| scheme);
+            format!("{}://{}", scheme, domain)
         }
+        Ok(None) => base_url.clone(),
+        Err(e) => {
+            tracing::warn!("failed to look up vanity domain: {}", e);
+            base_url.clone()
+        }
+    }
+}
 
-        let mut u = url::Url::parse(raw).map_err(|e| ApiError::Unprocessable(e.to_string()))?;
-        u.set_fragment(None);
+/// Answers an HTML form submission with a `303 See Other` back to the
+/// shorten form page, leaving the outcome in a short-lived flash cookie for
+/// the page to show once.
+fn flash_redirect(result: Result<ApiResponse<ShortenResponse>, ApiError>) -> Response {
+    let message = match result {
+        Ok(response) => match response.data {
+            Some(data) => format!("Shortened to {}", data.shortened_url),
+            None => "URL shortened".to_string(),
+        },
+        Err(e) => e.message(),
+    };
+    // The jar percent-encodes the value, so error messages holding `;` or `"`
+    // cannot break the header
+    let flash = Cookie::build((FLASH_COOKIE, message))
+        .path("/admin")
+        .max_age(time::Duration::seconds(60))
+        .http_only(true)
+        .same_site(SameSite::Lax)
+        .build();
+    (CookieJar::new().add(flash), Redirect::to("/admin/shorten")).into_response()
+}
 
-        if let Some(h) = u.host_str() {
-            let lower = h.to_ascii_lowercase();
-            if lower != h {
-                let _ = u.set_host(Some(&lower));
-            }
-        }
-        return Ok(u.to_string());
+/// Appends the configured UTM parameters to a normalized URL.
+///
+/// Does nothing unless `utm.auto_append` is set. Each of `utm_source`,
+/// `utm_medium` and `utm_campaign` that is configured and not already in the
+/// query is added, so a URL keeps the values it was shortened with.
+///
+/// # Errors
+///
+/// Returns `ApiError::UrlTooLong` if the parameters push the URL past
+/// [`MAX_URL_LENGTH`].
+pub fn append_utm_params(norm: &str, utm: &UtmSettings) -> Result<String, ApiError> {
+    if !utm.auto_append {
+        return Ok(norm.to_string());
+    }
+    let mut u = url::Url::parse(norm).map_err(|e| ApiError::Unprocessable(e.to_string()))?;
+    let present: Vec<String> = u.query_pairs().map(|(k, _)| k.into_owned()).collect();
+    let missing: Vec<(&str, &str)> = [
+        ("utm_source", &utm.source),
+        ("utm_medium", &utm.medium),
+        ("utm_campaign", &utm.campaign),
+    ]
+    .into_iter()
+    .filter(|(key, _)| !present.iter().any(|p| p == key))
+    .filter_map(|(key, value)| value.as_deref().map(|v| (key, v)))
+    .collect();
+    if missing.is_empty() {
+        return Ok(norm.to_string());
     }
 
-    // If it looks like a URL with a scheme but not http(s), call it what it is: unsupported scheme.
-    if let Some(pos) = raw.find("://") {
-        let scheme = &raw[..pos];
-        return Err(ApiError::Unprocessable(format!(
-            "Unsupported scheme: {}",
-            scheme
+    u.query_pairs_mut().extend_pairs(missing);
+    let appended = u.to_string();
+    if appended.len() > MAX_URL_LENGTH {
+        return Err(ApiError::UrlTooLong(format!(
+            "URL with UTM parameters exceeds maximum allowed length of {} characters",
+            MAX_URL_LENGTH
         )));
     }
+    Ok(appended)
+}
 
-    // Explicitly catch http/https missing slashes like "http:example.com".
-    if raw.starts_with("http:") || raw.starts_with("https:") {
+/// Hashes the passphrase of a protected URL with Argon2id, like account
+/// passwords but without the strength policy.
+fn hash_url_password(state: &AppState, password: &SecretString) -> Result<Vec<u8>, ApiError> {
+    let norm = NormalizedPassword::try_from(password)
+        .map_err(|e| ApiError::Unprocessable(format!("Invalid password: {}", e)))?;
+    if norm.is_empty() {
         return Err(ApiError::Unprocessable(
-            "Wrong number of slashes (separators) in scheme".to_string(),
+            "password must not be empty".to_string(),
         ));
     }
+    let config = state.config();
+    hash_password(
+        &norm,
+        config.application.pwd_pepper_b64.expose_secret(),
+        &config.argon2,
+    )
+    .map_err(|e| {
+        tracing::error!("Failed to hash URL password: {}", e);
+        ApiError::Internal(e.to_string())
+    })
+}
 
-    // Everything else is just not a URL we handle.
-    Err(ApiError::Unprocessable(
-        "Unsupported or invalid URL".to_string(),
-    ))
+/// Checks the A/B targets of a shorten request and normalizes their URLs.
+///
+/// There must be between two and [`MAX_AB_TARGETS`] targets, each with a
+/// positive weight, the weights summing to 100. Every URL is normalized and
+/// checked like the URL being shortened.
+async fn validate_ab_targets(
+    state: &AppState,
+    targets: Vec<AbTarget>,
+) -> Result<Vec<AbTarget>, ApiError> {
+    if !(2..=MAX_AB_TARGETS).contains(&targets.len()) {
+        return Err(ApiError::Unprocessable(format!(
+            "ab_targets must list between 2 and {} targets",
+            MAX_AB_TARGETS
+        )));
+    }
+    if targets.iter().any(|t| t.weight == 0) {
+        return Err(ApiError::Unprocessable(
+            "ab_targets weights must be greater than zero".to_string(),
+        ));
+    }
+    let total: u32 = targets.iter().map(|t| u32::from(t.weight)).sum();
+    if total != 100 {
+        return Err(ApiError::Unprocessable(format!(
+            "ab_targets weights must sum to 100, not {}",
+            total
+        )));
+    }
+
+    let mut normalized = Vec::with_capacity(targets.len());
+    for target in targets {
+        normalized.push(AbTarget {
+            url: validate_destination(state, &target.url).await?,
+            weight: target.weight,
+        });
+    }
+    Ok(normalized)
+}
+
+/// Checks an extra destination of a shorten request, an A/B target or the
+/// mobile URL, with [`check_destination`] and returns it normalized. Unlike
+/// the URL being shortened, it is not sent a reachability check.
+async fn validate_destination(state: &AppState, url: &str) -> Result<String, ApiError> {
+    if url.len() > MAX_URL_LENGTH {
+        return Err(ApiError::UrlTooLong(format!(
+            "URL exceeds maximum allowed length of {} characters",
+            MAX_URL_LENGTH
+        )));
+    }
+    let url = normalize_url(url)?;
+    check_destination(&url, state).await?;
+    Ok(url)
+}
+
+/// Turns a `ttl_seconds` value into an absolute expiry time.
+fn expiry_from_ttl(ttl_seconds: u64) -> Result<DateTime<Utc>, ApiError> {
+    if ttl_seconds == 0 {
+        return Err(ApiError::Unprocessable(
+            "ttl_seconds must be greater than zero".to_string(),
+        ));
+    }
+    i64::try_from(ttl_seconds)
+        .ok()
+        .and_then(chrono::Duration::try_seconds)
+        .and_then(|ttl| Utc::now().checked_add_signed(ttl))
+        .ok_or_else(|| ApiError::Unprocessable("ttl_seconds is too large".to_string()))
 }
 
 /// Inserts a new URL, retrying ID generation if duplicates occur.
 /// Relies on the database's Duplicate error to ensure atomicity and avoid TOCTOU issues.
+/// Transient database errors are retried with the same code (see [`retry_transient`]).
+///
+/// Retries back off exponentially with jitter (see [`retry_delay`]), so requests
+/// that collided on the same code do not all retry at the same moment.
 async fn insert_with_retry(
     state: &AppState,
     norm_url: &str,
+    expires_at: Option<DateTime<Utc>>,
 ) -> Result<(UpsertResult, String), ApiError> {
-    for attempt in 0..MAX_ID_RETRIES {
-        let code = state.code_generator.generate().map_err(|e| {
+    // Draw every retry candidate up front so the generator is hit only once.
+    let candidates = state
+        .code_generator()
+        .generate_n(MAX_ID_RETRIES)
+        .map_err(|e| {
             tracing::error!("Code generation error: {:?}", e);
             ApiError::Internal("Code generation failed".to_string())
         })?;
 
-        match state.database.insert_url(code.as_str(), norm_url).await {
+    for (attempt, code) in candidates.into_iter().enumerate() {
+        let insert = retry_transient("insert_url_with_expiry", || {
+            state
+                .database()
+                .insert_url_with_expiry(code.as_str(), norm_url, expires_at)
+        })
+        .await;
+        match insert {
             Ok((upsert, urls)) => return Ok((upsert, urls.code)),
             Err(DatabaseError::Duplicate) => {
-                tracing::warn!("ID collision on attempt {} — retrying", attempt + 1);
-                continue;
+                if attempt + 1 == MAX_ID_RETRIES {
+                    break;
+                }
+                let delay = retry_delay(state.config().shortener.retry_base_delay_ms, attempt);
+                let delay_ms = delay.as_millis() as u64;
+                tracing::warn!(attempt = attempt + 1, delay_ms, "ID collision retry");
+                tokio::time::sleep(delay).await;
             }
             Err(e) => {
                 tracing::error!("Database error on insert: {}", e);
@@ -297,8 +886,24 @@ async fn insert_with_retry(
     Err(ApiError::Internal("ID collision occurred".into()))
 }
 
+/// Pause before retry `attempt + 1`: `base_ms * 2^attempt` plus up to `base_ms`
+/// of jitter, capped at [`MAX_RETRY_DELAY`].
+fn retry_delay(base_ms: u64, attempt: usize) -> Duration {
+    if base_ms == 0 {
+        return Duration::ZERO;
+    }
+    let backoff = base_ms.saturating_mul(1 << attempt.min(16));
+    let jitter = rand::rng().random_range(0..base_ms);
+    Duration::from_millis(backoff.saturating_add(jitter)).min(MAX_RETRY_DELAY)
+}
+
 /// Builds a unified response structure for shortened URLs.
-fn make_response(base_url: &str, id: &str, original_url: &str) -> ApiResponse<ShortenResponse> {
+pub(crate) fn make_response(
+    base_url: &str,
+    id: &str,
+    original_url: &str,
+    expires_at: Option<DateTime<Utc>>,
+) -> ApiResponse<ShortenResponse> {
     // Trim any trailing slash from the base_url to prevent double slashes (e.g., "http://localhost:8000//ID")
     let base = base_url.trim_end_matches('/');
     let shortened_url = format!("{}/{}", base, id);
@@ -307,31 +912,229 @@ fn make_response(base_url: &str, id: &str, original_url: &str) -> ApiResponse<Sh
         shortened_url,
         original_url: original_url.to_string(),
         id: id.to_string(),
+        expires_at,
     };
     ApiResponse::success(response_data)
 }
 
-/// Validates a user-provided alias.
-/// Rules:
-/// - Non-empty
-/// - Max length = MAX_ALIAS_LENGTH
-/// - Allowed characters: based on configuration (state.allowed_chars)
-fn validate_alias(alias: &str, state: &AppState) -> Result<(), ApiError> {
-    if alias.is_empty() {
-        return Err(ApiError::Unprocessable("Alias cannot be empty".to_string()));
-    }
-    if alias.len() > MAX_ALIAS_LENGTH {
+/// Batch URL shortening handler.
+///
+/// Every entry is validated with [`normalize_url`] (and [`validate_alias`] when it
+/// carries an alias); valid entries are stored with a single
+/// [`UrlDatabase::insert_urls_batch`](crate::database::UrlDatabase::insert_urls_batch)
+/// call and invalid ones are reported individually, so a request with some bad
+/// entries still succeeds for the rest.
+///
+/// # Endpoint
+///
+/// `POST /api/shorten/batch` (protected - requires API key)
+///
+/// # Request Format
+///
+/// ```json
+/// [
+///   {"url": "https://www.example.com/a"},
+///   {"url": "https://www.example.com/b", "alias": "my-b"}
+/// ]
+/// ```
+///
+/// # Response Format
+///
+/// `data` holds one entry per submitted URL, in request order. Failed entries
+/// carry an `error` string and no short URL:
+///
+/// ```json
+/// [
+///   {
+///     "success": true,
+///     "url": "https://www.example.com/a",
+///     "error": null,
+///     "shortened_url": "https://localhost:8000/AbC123",
+///     "original_url": "https://www.example.com/a",
+///     "id": "AbC123",
+///     "expires_at": null
+///   },
+///   { "success": false, "url": "notaurl", "error": "Unsupported or invalid URL" }
+/// ]
+/// ```
+///
+/// An entry whose alias is already taken still stores its URL, but is reported
+/// as failed because the requested short URL could not be created.
+///
+/// The entries are stored in one transaction. An entry whose generated code
+/// was taken is then stored on its own; if that fails, the entry is reported
+/// as failed while the others stay stored.
+///
+/// # Status Codes
+///
+/// - `207 Multi-Status` - Batch processed (check each entry's `success` flag)
+/// - `413 Payload Too Large` - Body exceeds `application.max_body_bytes`
+/// - `422 Unprocessable Entity` - More than [`MAX_BATCH_ITEMS`] entries
+/// - `500 Internal Server Error` - Code generation or the batch insert failed
+///   (no entry is stored)
+#[utoipa::path(
+    post,
+    path = "/api/shorten/batch",
+    tag = "URL Shortening",
+    summary = "Shorten URLs in bulk",
+    description = "Shorten up to 50 URLs in one request; each entry reports its own outcome.",
+    request_body = Vec<BatchShortenItem>,
+    security(("ApiKeyAuth" = []), ("BearerAuth" = [])),
+    responses(
+        (status = 207, description = "Batch processed (check each entry's `success` flag)", body = ApiResponse<Vec<BatchShortenResult>>),
+        (status = 401, description = "Missing or invalid API key and access token", body = ApiError),
+        (status = 413, description = "Body exceeds `application.max_body_bytes`", body = ApiError),
+        (status = 422, description = "Too many entries in the batch", body = ApiError),
+        (status = 500, description = "Code generation or the batch insert failed (no entry is stored)", body = ApiError),
+    )
+)]
+#[debug_handler]
+#[instrument(name = "shorten_batch", skip(state, items), fields(count = items.len()))]
+pub async fn post_shorten_batch(
+    State(state): State<AppState>,
+    Json(items): Json<Vec<BatchShortenItem>>,
+) -> Result<ApiResponse<Vec<BatchShortenResult>>, ApiError> {
+    if items.len() > MAX_BATCH_ITEMS {
         return Err(ApiError::Unprocessable(format!(
-            "Alias exceeds maximum length of {} characters",
-            MAX_ALIAS_LENGTH
+            "Batch exceeds maximum of {} URLs",
+            MAX_BATCH_ITEMS
         )));
     }
 
-    if alias.chars().any(|c| !state.allowed_chars.contains(&c)) {
-        return Err(ApiError::Unprocessable(
-            "Alias contains characters not allowed by configuration".to_string(),
-        ));
+    // 1) Validate every entry up front; invalid ones are reported, not fatal
+    let mut normalized: Vec<Result<String, String>> = Vec::with_capacity(items.len());
+    for item in &items {
+        let checked = if item.url.len() > MAX_URL_LENGTH {
+            Err(format!(
+                "URL exceeds maximum allowed length of {} characters",
+                MAX_URL_LENGTH
+            ))
+        } else {
+            match item
+                .alias
+                .as_deref()
+                .map_or(Ok(()), |alias| validate_alias(alias, &state).map(drop))
+                .and_then(|_| normalize_url(&item.url))
+            {
+                Ok(norm) => check_destination(&norm, &state).await.map(|()| norm),
+                Err(e) => Err(e),
+            }
+            .map_err(|e| e.message())
+        };
+        normalized.push(checked);
     }
 
-    Ok(())
+    // 2) Generate a code per valid entry and store them in one transaction
+    let valid: Vec<&str> = normalized.iter().flatten().map(String::as_str).collect();
+    let generated = state
+        .code_generator()
+        .generate_n(valid.len())
+        .map_err(|e| {
+            tracing::error!("Code generation error: {:?}", e);
+            ApiError::Internal("Code generation failed".to_string())
+        })?;
+    let pending: Vec<(String, &str)> = generated.into_iter().zip(valid).collect();
+    let pairs: Vec<(&str, &str)> = pending
+        .iter()
+        .map(|(code, url)| (code.as_str(), *url))
+        .collect();
+
+    let stored = state
+        .database()
+        .insert_urls_batch(&pairs)
+        .await
+        .map_err(|e| {
+            tracing::error!("Database error on batch insert: {}", e);
+            ApiError::Internal(e.to_string())
+        })?;
+
+    let mut codes: HashMap<String, String> = HashMap::with_capacity(stored.len());
+    for row in stored {
+        if row.created {
+            state.blooms().insert(&row.code).await;
+        }
+        codes.insert(row.url, row.code);
+    }
+
+    // 3) Entries whose generated code collided are retried one by one. The
+    //    rows above are already committed, so a failure here is reported on
+    //    its entry rather than failing the whole request
+    let mut failed: HashMap<String, String> = HashMap::new();
+    for (_, url) in &pending {
+        if codes.contains_key(*url) || failed.contains_key(*url) {
+            continue;
+        }
+        match insert_with_retry(&state, url, None).await {
+            Ok((upsert, code)) => {
+                if upsert.created {
+                    state.blooms().insert(&code).await;
+                }
+                codes.insert(url.to_string(), code);
+            }
+            Err(e) => {
+                failed.insert(url.to_string(), e.message());
+            }
+        }
+    }
+
+    // 4) Attach aliases to the stored rows and build the per-entry report
+    let base = &state.config().application.base_url;
+    let mut results = Vec::with_capacity(items.len());
+    for (item, checked) in items.into_iter().zip(normalized) {
+        let outcome = match checked {
+            Ok(norm) => match (failed.get(&norm), item.alias) {
+                (Some(error), _) => Err(error.clone()),
+                (None, Some(alias)) => {
+                    let alias = format!("{}{}", alias_prefix(&state), alias);
+                    attach_alias(&state, &norm, &alias)
+                        .await
+                        .map(|()| (alias, norm))
+                }
+                (None, None) => Ok((codes[&norm].clone(), norm)),
+            },
+            Err(error) => Err(error),
+        };
+
+        results.push(match outcome {
+            Ok((code, norm)) => BatchShortenResult {
+                success: true,
+                url: item.url,
+                error: None,
+                result: make_response(base, &code, &norm, None).data,
+            },
+            Err(error) => BatchShortenResult {
+                success: false,
+                url: item.url,
+                error: Some(error),
+                result: None,
+            },
+        });
+    }
+
+    tracing::info!("URL batch shortened and saved successfully");
+    Ok(ApiResponse::success_with_status(
+        StatusCode::MULTI_STATUS,
+        results,
+    ))
+}
+
+/// Points `alias` at the stored row for `norm_url`, reporting failures as a
+/// per-entry error message.
+async fn attach_alias(state: &AppState, norm_url: &str, alias: &str) -> Result<(), String> {
+    let row = state
+        .database()
+        .get_id_by_url(norm_url)
+        .await
+        .map_err(|e| e.to_string())?;
+    match state.database().insert_alias(alias, row.id).await {
+        Ok(()) => {
+            state.blooms().insert(alias).await;
+            Ok(())
+        }
+        Err(DatabaseError::Duplicate) => Err("Alias is already taken".to_string()),
+        Err(e) => {
+            tracing::error!("Database error on insert with alias: {}", e);
+            Err(e.to_string())
+        }
+    }
 }
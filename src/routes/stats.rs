@@ -0,0 +1,231 @@
+//! # URL Statistics Handler
+//!
+//! This module provides the protected endpoints reporting the click totals of
+//! one short code, including the clicks of each A/B variant and, with
+//! `geolocation` enabled, of each country, and its most recent clicks.
+
+use crate::database::DatabaseError;
+use crate::errors::ApiError;
+use crate::middleware::Caller;
+use crate::models::{ClickRecord, ClickSummary};
+use crate::response::ApiResponse;
+use crate::routes::urls::authorize_url_owner;
+use crate::state::AppState;
+use axum::Extension;
+use axum::extract::{Path, Query, State};
+use axum_macros::debug_handler;
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+/// Referrers listed in the statistics of a short code.
+pub const STATS_TOP_REFERRERS: u8 = 10;
+/// Clicks returned by `GET /api/urls/{id}/clicks` when no `limit` is given.
+pub const DEFAULT_CLICK_HISTORY_LIMIT: u8 = 50;
+/// Upper bound on the `limit` accepted by `GET /api/urls/{id}/clicks`.
+pub const MAX_CLICK_HISTORY_LIMIT: u8 = 200;
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ClickHistoryParams {
+    /// Maximum number of clicks to return (capped at [`MAX_CLICK_HISTORY_LIMIT`])
+    pub limit: Option<u64>,
+}
+
+/// Returns the click totals of a short code or alias.
+///
+/// An API key may read the statistics of any code; a signed-in user only of
+/// URLs they own.
+///
+/// # Endpoint
+///
+/// `GET /api/urls/{id}/stats` (protected - requires API key or access token)
+///
+/// # Arguments
+///
+/// * `State(state)` - Application state containing database connection
+/// * `Extension(caller)` - The API key or user the request is made as
+/// * `Path(id)` - Short code or alias extracted from the URL path
+///
+/// # Response Format
+///
+/// ```json
+/// {
+///   "success": true,
+///   "message": "ok",
+///   "status": 200,
+///   "time": "2025-01-18T12:00:00Z",
+///   "data": {
+///     "total_clicks": 1000,
+///     "unique_ips": 1,
+///     "desktop_clicks": 640,
+///     "mobile_clicks": 360,
+///     "top_referrers": [
+///       { "referrer_origin": "https://news.example.org", "count": 412 }
+///     ],
+///     "variants": [
+///       { "variant": 0, "url": "https://www.example.com/a", "count": 604 },
+///       { "variant": 1, "url": "https://www.example.com/b", "count": 396 }
+///     ],
+///     "top_countries": [
+///       { "country_code": "US", "count": 530 },
+///       { "country_code": "DE", "count": 212 }
+///     ]
+///   }
+/// }
+/// ```
+///
+/// `variants` is empty unless the URL was shortened with `ab_targets`. Clicks
+/// without a `User-Agent` count towards neither `desktop_clicks` nor
+/// `mobile_clicks`. `top_countries` is only present with `geolocation`
+/// enabled, and leaves out clicks whose IP was not found in the database.
+///
+/// # Status Codes
+///
+/// - `200 OK` - Statistics returned
+/// - `401 Unauthorized` - Missing or invalid API key and access token
+/// - `403 Forbidden` - The signed-in user does not own the URL
+/// - `404 Not Found` - Unknown short code
+/// - `500 Internal Server Error` - Database error occurred
+///
+/// # Examples
+///
+/// ```bash
+/// curl -H "x-api-key: your-api-key" \
+///   http://localhost:8000/api/urls/AbC123/stats
+/// ```
+#[utoipa::path(
+    get,
+    path = "/api/urls/{id}/stats",
+    tag = "URL Management",
+    summary = "URL statistics",
+    description = "Click totals of a short code or alias: total clicks, unique client IPs, clicks from desktop and mobile devices, the ten referrer origins (`scheme://host`) sending the most clicks and, for A/B tested URLs, the clicks of each variant. With `geolocation` enabled it also lists the clicks per country (`top_countries`); the field is left out otherwise. A signed-in user may only read the statistics of URLs they own.",
+    params(("id" = String, Path, description = "Short code or alias")),
+    security(("ApiKeyAuth" = []), ("BearerAuth" = [])),
+    responses(
+        (status = 200, description = "Statistics returned", body = ApiResponse<ClickSummary>),
+        (status = 401, description = "Missing or invalid API key and access token", body = ApiError),
+        (status = 403, description = "The signed-in user does not own the URL", body = ApiError),
+        (status = 404, description = "Unknown short code", body = ApiError),
+    )
+)]
+#[debug_handler]
+#[tracing::instrument(name = "url_stats", skip(state))]
+pub async fn get_url_stats(
+    State(state): State<AppState>,
+    Extension(caller): Extension<Caller>,
+    Path(id): Path<String>,
+) -> Result<ApiResponse<ClickSummary>, ApiError> {
+    authorize_url_owner(&state, &caller, &id).await?;
+    let database = state.database();
+    let result = async {
+        let mut summary = database.get_click_summary(&id, STATS_TOP_REFERRERS).await?;
+        if state.country_lookup().is_some() {
+            summary.top_countries = Some(database.get_clicks_by_country(&id).await?);
+        }
+        Ok(summary)
+    }
+    .await;
+    match result {
+        Ok(summary) => Ok(ApiResponse::success(summary)),
+        Err(DatabaseError::NotFound) => Err(ApiError::NotFound("URL not found".to_string())),
+        Err(e) => {
+            tracing::error!("Database error: {}", e);
+            Err(ApiError::Internal(e.to_string()))
+        }
+    }
+}
+
+/// Returns the most recent clicks on a short code or alias, newest first.
+///
+/// Clicks through any of the URL's codes are listed. An API key may read the
+/// clicks of any code; a signed-in user only of URLs they own.
+///
+/// # Endpoint
+///
+/// `GET /api/urls/{id}/clicks?limit=50` (protected - requires API key or
+/// access token)
+///
+/// # Arguments
+///
+/// * `State(state)` - Application state containing database connection
+/// * `Extension(caller)` - The API key or user the request is made as
+/// * `Path(id)` - Short code or alias extracted from the URL path
+/// * `Query(params)` - `limit`, 50 by default and capped at 200
+///
+/// # Response Format
+///
+/// ```json
+/// {
+///   "success": true,
+///   "message": "ok",
+///   "status": 200,
+///   "time": "2025-01-18T12:00:00Z",
+///   "data": [
+///     {
+///       "id": 1042,
+///       "code": "AbC123",
+///       "ip": "203.0.113.7",
+///       "user_agent": "Mozilla/5.0 (iPhone; CPU iPhone OS 17_0 like Mac OS X)",
+///       "referrer_origin": "https://news.example.org",
+///       "country_code": "US",
+///       "device_type": "mobile",
+///       "clicked_at": "2025-01-18T11:59:30Z"
+///     }
+///   ]
+/// }
+/// ```
+///
+/// # Status Codes
+///
+/// - `200 OK` - Clicks returned
+/// - `401 Unauthorized` - Missing or invalid API key and access token
+/// - `403 Forbidden` - The signed-in user does not own the URL
+/// - `404 Not Found` - Unknown short code
+/// - `500 Internal Server Error` - Database error occurred
+///
+/// # Examples
+///
+/// ```bash
+/// curl -H "x-api-key: your-api-key" \
+///   "http://localhost:8000/api/urls/AbC123/clicks?limit=20"
+/// ```
+#[utoipa::path(
+    get,
+    path = "/api/urls/{id}/clicks",
+    tag = "URL Management",
+    summary = "URL click history",
+    description = "The most recent clicks on a short code or alias, newest first. Clicks through any of the URL's codes are listed under its primary code. A signed-in user may only read the clicks of URLs they own.",
+    params(
+        ("id" = String, Path, description = "Short code or alias"),
+        ClickHistoryParams,
+    ),
+    security(("ApiKeyAuth" = []), ("BearerAuth" = [])),
+    responses(
+        (status = 200, description = "Clicks returned", body = ApiResponse<Vec<ClickRecord>>),
+        (status = 401, description = "Missing or invalid API key and access token", body = ApiError),
+        (status = 403, description = "The signed-in user does not own the URL", body = ApiError),
+        (status = 404, description = "Unknown short code", body = ApiError),
+    )
+)]
+#[debug_handler]
+#[tracing::instrument(name = "url_clicks", skip(state))]
+pub async fn get_url_clicks(
+    State(state): State<AppState>,
+    Extension(caller): Extension<Caller>,
+    Path(id): Path<String>,
+    Query(params): Query<ClickHistoryParams>,
+) -> Result<ApiResponse<Vec<ClickRecord>>, ApiError> {
+    authorize_url_owner(&state, &caller, &id).await?;
+    let limit = params
+        .limit
+        .unwrap_or(u64::from(DEFAULT_CLICK_HISTORY_LIMIT))
+        .clamp(1, u64::from(MAX_CLICK_HISTORY_LIMIT)) as u8;
+    match state.database().get_recent_clicks(&id, limit).await {
+        Ok(clicks) => Ok(ApiResponse::success(clicks)),
+        Err(DatabaseError::NotFound) => Err(ApiError::NotFound("URL not found".to_string())),
+        Err(e) => {
+            tracing::error!("Database error: {}", e);
+            Err(ApiError::Internal(e.to_string()))
+        }
+    }
+}
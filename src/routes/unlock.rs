@@ -0,0 +1,197 @@
+//! # Password-Protected Short URLs
+//!
+//! A URL shortened with a `password` does not redirect straight away. Its
+//! redirect routes answer `401 Unauthorized` with a passphrase form, which
+//! posts to `POST /{id}/unlock`. The right passphrase earns a short-lived
+//! signed `unlock_token` cookie scoped to `/{id}` and a redirect back to the
+//! short URL, which then redirects as usual.
+
+use crate::configuration::Environment;
+use crate::core::security::password::{PasswordVerification, verify_password};
+use crate::core::security::unlock::{issue_unlock_token, verify_unlock_token};
+use crate::database::DatabaseError;
+use crate::errors::ApiError;
+use crate::state::AppState;
+use axum::{
+    Form,
+    extract::{Path, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::{Html, IntoResponse, Redirect, Response},
+};
+use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
+use chrono::{Duration, Utc};
+use secrecy::{ExposeSecret, SecretString};
+use serde::Deserialize;
+use tera::Context;
+use utoipa::ToSchema;
+
+/// Cookie holding the token that unlocks one short code.
+pub const UNLOCK_COOKIE: &str = "unlock_token";
+/// How long an entered passphrase keeps a short code unlocked.
+const UNLOCK_TTL_MINUTES: i64 = 10;
+
+/// Form fields accepted by `POST /{id}/unlock`.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UnlockForm {
+    /// The passphrase of the short URL
+    #[schema(value_type = String, format = Password)]
+    pub password: SecretString,
+}
+
+/// Checks the passphrase of a protected short URL and unlocks it.
+///
+/// # Endpoint
+///
+/// `POST /{id}/unlock` with a `password` form field
+///
+/// # Status Codes
+///
+/// - `303 See Other` - Passphrase accepted (or none needed); back to `/{id}`
+///   with the `unlock_token` cookie set
+/// - `403 Forbidden` - Wrong passphrase; the form is shown again
+/// - `404 Not Found` - Unknown short code
+/// - `500 Internal Server Error` - Database or template error occurred
+#[utoipa::path(
+    post,
+    path = "/{id}/unlock",
+    tag = "Redirect",
+    summary = "Unlock a Protected Short URL",
+    description = "Checks the passphrase of a password-protected short URL. The right passphrase sets a signed `unlock_token` cookie, scoped to `/{id}` and valid for 10 minutes, and redirects back to `/{id}`.",
+    params(("id" = String, Path, description = "The short URL identifier")),
+    request_body(content = UnlockForm, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 303, description = "Passphrase accepted; redirects to `/{id}` with the `unlock_token` cookie set", headers(
+            ("set-cookie" = String, description = "The signed `unlock_token` cookie"),
+        )),
+        (status = 403, description = "Wrong passphrase; the form is shown again", content_type = "text/html", body = String),
+        (status = 404, description = "Unknown short code", body = ApiError),
+    )
+)]
+#[tracing::instrument(name = "unlock", skip(state, form))]
+pub async fn post_unlock(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Form(form): Form<UnlockForm>,
+) -> Result<Response, ApiError> {
+    let meta = match state.database().get_url_with_metadata(&id).await {
+        Ok(meta) => meta,
+        Err(DatabaseError::NotFound) => {
+            return Err(ApiError::NotFound("URL not found".to_string()));
+        }
+        Err(e) => {
+            tracing::error!("Database error: {}", e);
+            return Err(ApiError::Internal(e.to_string()));
+        }
+    };
+    let back = Redirect::to(&format!("/{}", id));
+    let Some(password_hash) = meta.password_hash else {
+        return Ok(back.into_response());
+    };
+
+    let config = state.config();
+    let verification = verify_password(
+        &form.password,
+        &password_hash,
+        config.application.pwd_pepper_b64.expose_secret(),
+        &config.argon2,
+    )
+    .unwrap_or(PasswordVerification::Invalid);
+    let password_hash = match verification {
+        PasswordVerification::Invalid => {
+            tracing::info!("wrong passphrase for a protected URL");
+            return password_challenge(
+                &state,
+                &id,
+                StatusCode::FORBIDDEN,
+                Some("Wrong password, please try again."),
+            );
+        }
+        PasswordVerification::Valid => password_hash,
+        PasswordVerification::Rehashed(phc) => {
+            // The token is bound to whichever hash ends up stored
+            match state.database().set_url_password(&id, &phc).await {
+                Ok(()) => phc,
+                Err(e) => {
+                    tracing::warn!("failed to store rehashed URL password: {}", e);
+                    password_hash
+                }
+            }
+        }
+    };
+
+    let token = issue_unlock_token(
+        unlock_secret(&state),
+        &id,
+        &password_hash,
+        Utc::now(),
+        Duration::minutes(UNLOCK_TTL_MINUTES),
+    );
+    let cookie = Cookie::build((UNLOCK_COOKIE, token))
+        .path(format!("/{}", id))
+        .max_age(time::Duration::minutes(UNLOCK_TTL_MINUTES))
+        .http_only(true)
+        .secure(config.environment == Environment::Production)
+        .same_site(SameSite::Lax)
+        .build();
+    Ok((CookieJar::new().add(cookie), back).into_response())
+}
+
+/// Whether the request carries a valid `unlock_token` for `code`, issued
+/// while `password_hash` was its stored passphrase hash.
+pub fn is_unlocked(
+    state: &AppState,
+    code: &str,
+    password_hash: &[u8],
+    headers: &HeaderMap,
+) -> bool {
+    CookieJar::from_headers(headers)
+        .get(UNLOCK_COOKIE)
+        .is_some_and(|cookie| {
+            verify_unlock_token(
+                unlock_secret(state),
+                code,
+                password_hash,
+                cookie.value(),
+                Utc::now(),
+            )
+        })
+}
+
+/// Renders the passphrase form of `code` with `status`.
+///
+/// A `401` carries `WWW-Authenticate: URL-Password realm="{code}"`. The page
+/// is never cached, so unlocking takes effect on the next visit.
+pub fn password_challenge(
+    state: &AppState,
+    code: &str,
+    status: StatusCode,
+    error: Option<&str>,
+) -> Result<Response, ApiError> {
+    let mut context = Context::new();
+    context.insert("title", "URL Shortener");
+    context.insert("page", "Password Required");
+    context.insert("code", code);
+    context.insert("error", &error);
+    let body = state.templates().render("unlock.html", &context)?;
+
+    let mut response = (status, [(header::CACHE_CONTROL, "no-store")], Html(body)).into_response();
+    if status == StatusCode::UNAUTHORIZED
+        && let Ok(value) = HeaderValue::from_str(&format!("URL-Password realm=\"{}\"", code))
+    {
+        response
+            .headers_mut()
+            .insert(header::WWW_AUTHENTICATE, value);
+    }
+    Ok(response)
+}
+
+/// Key the unlock tokens are signed with; the JWT secret, so rotating it
+/// also locks every unlocked link again.
+fn unlock_secret(state: &AppState) -> &[u8] {
+    state
+        .config()
+        .application
+        .jwt_secret_b64
+        .expose_secret()
+        .as_bytes()
+}
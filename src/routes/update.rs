@@ -0,0 +1,148 @@
+//! # URL Update Handler
+//!
+//! This module provides the protected endpoint used to change where an existing
+//! short code points, without losing the code itself or its click history.
+
+use crate::database::DatabaseError;
+use crate::errors::ApiError;
+use crate::middleware::Caller;
+use crate::response::ApiResponse;
+use crate::routes::redirect::forget_cached_redirects;
+use crate::routes::shorten::{MAX_URL_LENGTH, ShortenResponse, make_response};
+use crate::routes::urls::authorize_url_owner;
+use crate::state::AppState;
+use crate::validation::{check_destination, normalize_url};
+use axum::Extension;
+use axum::extract::{Path, State};
+use axum_macros::debug_handler;
+
+/// Updates the target URL of an existing short code or alias.
+///
+/// An API key may retarget any code; a signed-in user only codes of URLs they own.
+///
+/// # Endpoint
+///
+/// `PATCH /api/urls/{id}` (protected - requires API key or access token)
+///
+/// # Arguments
+///
+/// * `State(state)` - Application state containing database connection
+/// * `Extension(caller)` - The API key or user the request is made as
+/// * `Path(id)` - Short code or alias extracted from the URL path
+/// * `url` - The new target URL (plain text body, same as `POST /api/shorten`)
+///
+/// # Response Format
+///
+/// ```json
+/// {
+///   "success": true,
+///   "message": "ok",
+///   "status": 200,
+///   "time": "2025-01-18T12:00:00Z",
+///   "data": {
+///     "shortened_url": "https://localhost:8000/AbC123",
+///     "original_url": "https://www.example.com/fixed",
+///     "id": "AbC123",
+///     "expires_at": null
+///   }
+/// }
+/// ```
+///
+/// # Bloom Filter
+///
+/// The Bloom filter only tracks short codes, which do not change here, so it
+/// needs no update. The previous target is logged at warn level for auditing.
+/// The code and its aliases are dropped from the redirect cache, so the next
+/// redirect reads the new target.
+///
+/// # Status Codes
+///
+/// - `200 OK` - Target URL updated
+/// - `401 Unauthorized` - Missing or invalid API key and access token
+/// - `403 Forbidden` - The signed-in user does not own the URL
+/// - `404 Not Found` - Unknown short code
+/// - `409 Conflict` - The new URL is already shortened under another code
+/// - `422 Unprocessable Entity` - Invalid URL format or URL exceeds maximum length
+/// - `500 Internal Server Error` - Database error occurred
+///
+/// # Examples
+///
+/// ```bash
+/// curl -X PATCH -d 'https://www.example.com/fixed' \
+///   -H "x-api-key: your-api-key" \
+///   http://localhost:8000/api/urls/AbC123
+/// ```
+#[utoipa::path(
+    patch,
+    path = "/api/urls/{id}",
+    tag = "URL Management",
+    summary = "Retarget Short URL",
+    description = "Point an existing short code or alias at a new destination URL. A signed-in user may only retarget codes of URLs they own.",
+    params(("id" = String, Path, description = "Short code or alias")),
+    request_body(content = String, content_type = "text/plain", description = "The new destination URL", example = "https://www.example.com/fixed"),
+    security(("ApiKeyAuth" = []), ("BearerAuth" = [])),
+    responses(
+        (status = 200, description = "Target URL updated", body = ApiResponse<ShortenResponse>),
+        (status = 401, description = "Missing or invalid API key and access token", body = ApiError),
+        (status = 403, description = "The signed-in user does not own the URL", body = ApiError),
+        (status = 404, description = "Unknown short code", body = ApiError),
+        (status = 409, description = "The new URL is already shortened under another code", body = ApiError),
+        (status = 422, description = "Invalid URL format or URL exceeds maximum length", body = ApiError),
+    )
+)]
+#[debug_handler]
+#[tracing::instrument(name = "update_url", skip(state))]
+pub async fn patch_url(
+    State(state): State<AppState>,
+    Extension(caller): Extension<Caller>,
+    Path(id): Path<String>,
+    url: String,
+) -> Result<ApiResponse<ShortenResponse>, ApiError> {
+    authorize_url_owner(&state, &caller, &id).await?;
+    if url.len() > MAX_URL_LENGTH {
+        return Err(ApiError::UrlTooLong(format!(
+            "URL exceeds maximum allowed length of {} characters",
+            MAX_URL_LENGTH
+        )));
+    }
+    let norm = normalize_url(&url)?;
+    check_destination(&norm, &state).await?;
+
+    let meta = state
+        .database()
+        .get_url_with_metadata(&id)
+        .await
+        .map_err(map_update_error)?;
+    state
+        .database()
+        .update_url(&id, &norm)
+        .await
+        .map_err(map_update_error)?;
+    forget_cached_redirects(state.redirect_cache(), &id, Some(&meta.url));
+
+    tracing::warn!(
+        code = %id,
+        old_url = %meta.url,
+        new_url = %norm,
+        "short code retargeted"
+    );
+    Ok(make_response(
+        &state.config().application.base_url,
+        &id,
+        &norm,
+        meta.expires_at,
+    ))
+}
+
+fn map_update_error(e: DatabaseError) -> ApiError {
+    match e {
+        DatabaseError::NotFound => ApiError::NotFound("URL not found".to_string()),
+        DatabaseError::Duplicate => {
+            ApiError::Conflict("URL is already shortened under another code".to_string())
+        }
+        e => {
+            tracing::error!("Database error: {}", e);
+            ApiError::Internal(e.to_string())
+        }
+    }
+}
@@ -0,0 +1,253 @@
+//! # URL Management Handlers
+//!
+//! This module provides the protected endpoints used to list stored URLs and to
+//! manage the tags attached to them. Tags let callers group links by campaign,
+//! project or client and filter listings by that grouping.
+
+use crate::database::DatabaseError;
+use crate::errors::ApiError;
+use crate::middleware::Caller;
+use crate::models::UrlRecord;
+use crate::response::ApiResponse;
+use crate::state::AppState;
+use crate::validation::validate_tag;
+use axum::extract::{Path, Query, State};
+use axum::{Extension, Json};
+use axum_macros::debug_handler;
+use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
+
+/// Number of rows returned by `GET /api/urls` when no `limit` is given.
+pub const DEFAULT_LIST_LIMIT: u64 = 50;
+/// Upper bound on the `limit` accepted by `GET /api/urls`.
+pub const MAX_LIST_LIMIT: u64 = 100;
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListUrlsParams {
+    /// Only return URLs carrying this tag
+    pub tag: Option<String>,
+    /// Number of rows to skip
+    pub offset: Option<u64>,
+    /// Maximum number of rows to return (capped at [`MAX_LIST_LIMIT`])
+    pub limit: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TagRequest {
+    /// The tag to attach
+    #[schema(example = "campaign-q3")]
+    pub tag: String,
+}
+
+/// Lists stored URLs, optionally filtered by tag.
+///
+/// An API key lists every URL; a signed-in user only sees the URLs they own.
+///
+/// # Endpoint
+///
+/// `GET /api/urls?tag=campaign-q3&offset=0&limit=50` (protected - requires API
+/// key or access token)
+///
+/// # Response Format
+///
+/// ```json
+/// {
+///   "success": true,
+///   "message": "ok",
+///   "status": 200,
+///   "time": "2025-01-18T12:00:00Z",
+///   "data": [
+///     { "code": "AbC123", "url": "https://www.example.com/", "owner_id": null }
+///   ]
+/// }
+/// ```
+///
+/// # Status Codes
+///
+/// - `200 OK` - URLs listed
+/// - `401 Unauthorized` - Missing or invalid API key and access token
+/// - `422 Unprocessable Entity` - Invalid tag
+/// - `500 Internal Server Error` - Database error occurred
+#[utoipa::path(
+    get,
+    path = "/api/urls",
+    tag = "URL Management",
+    summary = "List URLs",
+    description = "List stored URLs, optionally filtered by tag. An API key lists every URL; a signed-in user only the URLs they own.",
+    params(ListUrlsParams),
+    security(("ApiKeyAuth" = []), ("BearerAuth" = [])),
+    responses(
+        (status = 200, description = "URLs listed", body = ApiResponse<Vec<UrlRecord>>),
+        (status = 401, description = "Missing or invalid API key and access token", body = ApiError),
+        (status = 422, description = "Invalid tag", body = ApiError),
+    )
+)]
+#[debug_handler]
+#[tracing::instrument(name = "list_urls", skip(state))]
+pub async fn list_urls(
+    State(state): State<AppState>,
+    Extension(caller): Extension<Caller>,
+    Query(params): Query<ListUrlsParams>,
+) -> Result<ApiResponse<Vec<UrlRecord>>, ApiError> {
+    let offset = params.offset.unwrap_or(0);
+    let limit = params
+        .limit
+        .unwrap_or(DEFAULT_LIST_LIMIT)
+        .min(MAX_LIST_LIMIT);
+    let owner_id = match caller {
+        Caller::ApiKey => None,
+        Caller::User(user) => Some(user.user_id),
+    };
+
+    let records = match params.tag {
+        Some(tag) => {
+            validate_tag(&tag)?;
+            state
+                .database()
+                .get_urls_by_tag(&tag, offset, limit, owner_id)
+                .await
+        }
+        None => state.database().list_urls(offset, limit, owner_id).await,
+    }
+    .map_err(|e| {
+        tracing::error!("Database error: {}", e);
+        ApiError::Internal(e.to_string())
+    })?;
+
+    Ok(ApiResponse::success(records))
+}
+
+/// Attaches a tag to a short code or alias.
+///
+/// # Endpoint
+///
+/// `POST /api/urls/{id}/tags` (protected - requires API key)
+///
+/// # Request Format
+///
+/// ```json
+/// { "tag": "campaign-q3" }
+/// ```
+///
+/// # Status Codes
+///
+/// - `200 OK` - Tag attached (or already present)
+/// - `404 Not Found` - Unknown short code
+/// - `422 Unprocessable Entity` - Invalid tag
+/// - `500 Internal Server Error` - Database error occurred
+#[utoipa::path(
+    post,
+    path = "/api/urls/{id}/tags",
+    tag = "URL Management",
+    summary = "Tag URL",
+    description = "Attach a tag to a short code or alias",
+    params(("id" = String, Path, description = "Short code or alias")),
+    request_body = TagRequest,
+    security(("ApiKeyAuth" = [])),
+    responses(
+        (status = 200, description = "Tag attached (or already present)"),
+        (status = 401, description = "Missing or invalid API key", body = ApiError),
+        (status = 404, description = "Unknown short code", body = ApiError),
+        (status = 422, description = "Invalid tag", body = ApiError),
+    )
+)]
+#[debug_handler]
+#[tracing::instrument(name = "add_tag", skip(state))]
+pub async fn post_url_tag(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(body): Json<TagRequest>,
+) -> Result<ApiResponse<()>, ApiError> {
+    validate_tag(&body.tag)?;
+    state
+        .database()
+        .add_tag(&id, &body.tag)
+        .await
+        .map_err(map_tag_error)?;
+    Ok(ApiResponse::success(()))
+}
+
+/// Detaches a tag from a short code or alias.
+///
+/// # Endpoint
+///
+/// `DELETE /api/urls/{id}/tags/{tag}` (protected - requires API key)
+///
+/// # Status Codes
+///
+/// - `200 OK` - Tag removed (or was not present)
+/// - `404 Not Found` - Unknown short code
+/// - `422 Unprocessable Entity` - Invalid tag
+/// - `500 Internal Server Error` - Database error occurred
+#[utoipa::path(
+    delete,
+    path = "/api/urls/{id}/tags/{tag}",
+    tag = "URL Management",
+    summary = "Untag URL",
+    description = "Detach a tag from a short code or alias",
+    params(
+        ("id" = String, Path, description = "Short code or alias"),
+        ("tag" = String, Path, description = "The tag to detach"),
+    ),
+    security(("ApiKeyAuth" = [])),
+    responses(
+        (status = 200, description = "Tag removed (or was not present)"),
+        (status = 401, description = "Missing or invalid API key", body = ApiError),
+        (status = 404, description = "Unknown short code", body = ApiError),
+        (status = 422, description = "Invalid tag", body = ApiError),
+    )
+)]
+#[debug_handler]
+#[tracing::instrument(name = "remove_tag", skip(state))]
+pub async fn delete_url_tag(
+    State(state): State<AppState>,
+    Path((id, tag)): Path<(String, String)>,
+) -> Result<ApiResponse<()>, ApiError> {
+    validate_tag(&tag)?;
+    state
+        .database()
+        .remove_tag(&id, &tag)
+        .await
+        .map_err(map_tag_error)?;
+    Ok(ApiResponse::success(()))
+}
+
+fn map_tag_error(e: DatabaseError) -> ApiError {
+    match e {
+        DatabaseError::NotFound => ApiError::NotFound("URL not found".to_string()),
+        e => {
+            tracing::error!("Database error: {}", e);
+            ApiError::Internal(e.to_string())
+        }
+    }
+}
+
+/// Checks that `caller` may change or delete the URL behind `code`.
+///
+/// An API key may manage every URL; a signed-in user only the URLs they own,
+/// which for an alias means owning the URL it points at.
+///
+/// # Errors
+///
+/// - `ApiError::NotFound` - Unknown short code
+/// - `ApiError::Forbidden` - The user does not own the URL
+pub(crate) async fn authorize_url_owner(
+    state: &AppState,
+    caller: &Caller,
+    code: &str,
+) -> Result<(), ApiError> {
+    let Caller::User(user) = caller else {
+        return Ok(());
+    };
+    let meta = state
+        .database()
+        .get_url_with_metadata(code)
+        .await
+        .map_err(map_tag_error)?;
+    if meta.owner_id == Some(user.user_id) {
+        Ok(())
+    } else {
+        Err(ApiError::Forbidden("You do not own this URL".to_string()))
+    }
+}
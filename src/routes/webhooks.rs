@@ -0,0 +1,59 @@
+//! # Webhook Receiver
+//!
+//! This module provides the endpoint external services POST event callbacks
+//! to, such as Safe Browsing malware reports. Callbacks are authenticated by
+//! [`verify_webhook_auth`](crate::middleware::verify_webhook_auth) before
+//! they reach the handler.
+
+use axum::body::Bytes;
+use axum::http::StatusCode;
+use axum_macros::debug_handler;
+
+/// Accepts a signed webhook callback.
+///
+/// The callback is logged with its `event` field, when the body is a JSON
+/// object that has one; acting on specific events is left to later
+/// handlers.
+///
+/// # Endpoint
+///
+/// `POST /api/webhooks/receive` (requires an `X-Signature-256` header)
+///
+/// # Status Codes
+///
+/// - `204 No Content` - Callback accepted
+/// - `401 Unauthorized` - Missing or wrong signature
+/// - `413 Payload Too Large` - Body over 64 KiB
+///
+/// # Examples
+///
+/// ```bash
+/// body='{"event":"malware","url":"https://bad.example"}'
+/// signature=$(printf '%s' "$body" | openssl dgst -sha256 -hmac "$SECRET" -hex | cut -d' ' -f2)
+/// curl -X POST -H "X-Signature-256: sha256=$signature" -d "$body" \
+///   http://localhost:8000/api/webhooks/receive
+/// ```
+#[utoipa::path(
+    post,
+    path = "/api/webhooks/receive",
+    tag = "Webhooks",
+    summary = "Receive a signed webhook callback",
+    description = "Accepts event callbacks from other services. The body must be signed with HMAC-SHA256 using `incoming_webhooks.secret`, sent as `X-Signature-256: sha256=<hex>`.",
+    request_body(content = String, content_type = "application/json"),
+    security(("WebhookSignature" = [])),
+    responses(
+        (status = 204, description = "Callback accepted"),
+        (status = 401, description = "Missing or wrong signature"),
+        (status = 413, description = "Body over 64 KiB"),
+    )
+)]
+#[debug_handler]
+#[tracing::instrument(name = "post_webhook_receive", skip(body), fields(bytes = body.len()))]
+pub async fn post_webhook_receive(body: Bytes) -> StatusCode {
+    let event = serde_json::from_slice::<serde_json::Value>(&body)
+        .ok()
+        .and_then(|v| v.get("event").and_then(|e| e.as_str()).map(str::to_string));
+    tracing::info!(event = event.as_deref(), "webhook callback received");
+
+    StatusCode::NO_CONTENT
+}
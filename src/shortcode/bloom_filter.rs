@@ -1,19 +1,58 @@
 // shortcode/mod.rs
+use crate::configuration::BloomConfig;
 use crate::database::UrlDatabase;
+use crate::shortcode::snapshot_store::BloomSnapshotStore;
 use anyhow::{Context, Result, anyhow};
 use fastbloom_rs::{BloomFilter, FilterBuilder, Hashes, Membership};
 use parking_lot::RwLock;
-use std::{env, sync::Arc};
+use serde::Serialize;
+use std::{
+    env,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+use tokio::sync::{Mutex, watch};
+use tokio::task::JoinHandle;
+use tracing::Instrument;
+use utoipa::ToSchema;
 
 pub const S2L_SNAPSHOT_KEY: &str = "short_to_long";
-const EXPECTED: u64 = 10_000_000;
-const FPP: f64 = 0.01;
+/// Snapshot key used when `bloom.scalable` is on; the layered format differs.
+pub const S2L_SCALABLE_SNAPSHOT_KEY: &str = "short_to_long_scalable";
 const PAGE: u64 = 50_000;
+/// Codes read per query by [`BloomState::rebuild_async`].
+const REBUILD_CHUNK: u64 = 10_000;
+/// How many codes [`BloomState::rebuild_async`] adds between progress logs.
+const REBUILD_PROGRESS_EVERY: u64 = 100_000;
+
+/// Capacity of each new scalable layer relative to the previous one.
+const LAYER_GROWTH: u64 = 2;
+/// False-positive rate of each new scalable layer relative to the previous one.
+const LAYER_TIGHTENING: f64 = 0.5;
+/// Fill level of the newest scalable layer at which another is added.
+const LAYER_FILL_THRESHOLD: f64 = 0.9;
+
+/// Version byte of an uncompressed snapshot blob.
+const SNAPSHOT_RAW: u8 = 0x01;
+/// Version byte of an LZ4-compressed snapshot blob.
+const SNAPSHOT_LZ4: u8 = 0x02;
 
 pub trait ProbSet: Send + Sync {
     fn may_contain(&self, key: &str) -> bool;
     fn insert(&self, key: &str);
     fn snapshot(&self) -> Result<Vec<u8>>;
+    /// Approximate number of distinct keys inserted so far.
+    fn estimated_count(&self) -> u64;
+    /// Memory held by the filter's bit arrays, in bytes.
+    fn size_bytes(&self) -> usize;
+    /// Replaces the contents with a fresh filter sized for `expected` items at
+    /// `fpp`, holding only `keys`.
+    ///
+    /// The new filter is built before it is swapped in, so lookups never see
+    /// it half filled.
+    fn reset(&self, keys: &[String], expected: u64, fpp: f64);
 
     fn extend<'a, I>(&self, items: I)
     where
@@ -29,6 +68,175 @@ pub trait ProbSet: Send + Sync {
 #[derive(Clone)]
 pub struct BloomState {
     pub s2l: Arc<dyn ProbSet>,
+    /// Number of codes the filter was sized for (`bloom.expected_items`)
+    pub expected_items: u64,
+    /// Key the filter's snapshot is stored under
+    pub snapshot_key: &'static str,
+    /// Lookup outcomes, shared by every clone of the state
+    pub counters: Arc<BloomCounters>,
+    /// Held by [`rebuild_bloom_from_db`] and by [`BloomState::insert`], so
+    /// codes stored during a rebuild are not lost when the filter is swapped
+    pub rebuild_lock: Arc<Mutex<()>>,
+    /// Whether the filter holds every stored code, see [`BloomState::is_ready`]
+    ready: Arc<watch::Sender<bool>>,
+}
+
+/// Outcome of a short-to-long filter lookup, once the database has answered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BloomOutcome {
+    /// The filter ruled the code out; the database was not queried
+    Miss,
+    /// The filter passed the code and the database found it
+    TruePositive,
+    /// The filter passed the code but the database did not have it
+    FalsePositive,
+}
+
+/// Running totals of [`BloomOutcome`]s.
+#[derive(Debug, Default)]
+pub struct BloomCounters {
+    s2l_hits: AtomicU64,
+    s2l_misses: AtomicU64,
+    s2l_false_positives: AtomicU64,
+}
+
+/// Point-in-time copy of [`BloomCounters`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, ToSchema)]
+pub struct BloomMetrics {
+    /// Lookups passed by the filter and confirmed by the database
+    pub s2l_hits: u64,
+    /// Lookups the filter rejected without a database query
+    pub s2l_misses: u64,
+    /// Lookups passed by the filter that the database could not find
+    pub s2l_false_positives: u64,
+}
+
+impl BloomState {
+    /// Estimated fill level: estimated items divided by expected items.
+    ///
+    /// Above `1.0` the filter holds more codes than it was sized for and its
+    /// false-positive rate exceeds the configured one.
+    pub fn utilization(&self) -> f64 {
+        if self.expected_items == 0 {
+            return 0.0;
+        }
+        self.s2l.estimated_count() as f64 / self.expected_items as f64
+    }
+
+    /// Adds a newly stored code, waiting for a running rebuild to finish.
+    pub async fn insert(&self, code: &str) {
+        let _rebuild = self.rebuild_lock.lock().await;
+        self.s2l.insert(code);
+    }
+
+    /// Whether the filter holds every stored code.
+    ///
+    /// A filter loaded from its snapshot is ready at once; one filled by
+    /// [`rebuild_async`](Self::rebuild_async) once the rebuild finishes.
+    /// Until then it would rule out codes it has not been given yet, so
+    /// lookups skip it and ask the database.
+    pub fn is_ready(&self) -> bool {
+        *self.ready.borrow()
+    }
+
+    /// Waits until the filter [`is_ready`](Self::is_ready).
+    pub async fn wait_until_ready(&self) {
+        let mut ready = self.ready.subscribe();
+        // The sender lives as long as `self`, so this only returns once ready
+        let _ = ready.wait_for(|ready| *ready).await;
+    }
+
+    /// Fills the filter from every short code and alias in `db` on a
+    /// background task, then stores a snapshot in `store` and marks the
+    /// filter ready.
+    ///
+    /// Codes are read [`REBUILD_CHUNK`] at a time and added to the live
+    /// filter, logging progress every [`REBUILD_PROGRESS_EVERY`] codes, so
+    /// requests are served meanwhile. A failed rebuild is logged and leaves
+    /// the filter not ready, so lookups keep going to the database.
+    pub fn rebuild_async(
+        &self,
+        db: Arc<dyn UrlDatabase>,
+        store: Arc<dyn BloomSnapshotStore>,
+    ) -> JoinHandle<()> {
+        let blooms = self.clone();
+        let task = async move {
+            #[cfg(feature = "metrics")]
+            crate::telemetry::metrics::record_bloom_rebuilding(true);
+            let started = std::time::Instant::now();
+            let filled = blooms.fill_from(db.as_ref()).await;
+            #[cfg(feature = "metrics")]
+            crate::telemetry::metrics::record_bloom_rebuilding(false);
+
+            match filled {
+                Ok(items) => {
+                    if blooms.snapshot_key == S2L_SNAPSHOT_KEY {
+                        warn_if_over_capacity(items, blooms.expected_items);
+                    }
+                    if not_disable_bf_snapshots() {
+                        save_snapshot(&store, blooms.s2l.as_ref(), blooms.snapshot_key).await;
+                    }
+                    blooms.ready.send_replace(true);
+                    tracing::info!(
+                        items,
+                        duration_ms = started.elapsed().as_millis() as u64,
+                        "Bloom filter rebuilt from the database"
+                    );
+                }
+                Err(e) => tracing::error!(
+                    error = %e,
+                    "Bloom filter rebuild failed; lookups keep going to the database"
+                ),
+            }
+        };
+        tokio::spawn(task.in_current_span())
+    }
+
+    /// Adds every short code and alias in `db` to the filter.
+    ///
+    /// Returns the number of codes read.
+    async fn fill_from(&self, db: &dyn UrlDatabase) -> Result<u64> {
+        let mut items: u64 = 0;
+        loop {
+            let chunk = db.list_short_codes(items, REBUILD_CHUNK).await?;
+            for code in &chunk {
+                self.s2l.insert(code);
+            }
+            let len = chunk.len() as u64;
+            if (items + len) / REBUILD_PROGRESS_EVERY > items / REBUILD_PROGRESS_EVERY {
+                tracing::info!(items = items + len, "Bloom filter rebuild in progress");
+            }
+            items += len;
+            if len < REBUILD_CHUNK {
+                return Ok(items);
+            }
+        }
+    }
+
+    /// Counts the outcome of a short-to-long lookup.
+    ///
+    /// Lookups made before the filter [`is_ready`](Self::is_ready) did not
+    /// go through it and are not counted.
+    pub fn record_lookup(&self, outcome: BloomOutcome) {
+        if !self.is_ready() {
+            return;
+        }
+        let counter = match outcome {
+            BloomOutcome::Miss => &self.counters.s2l_misses,
+            BloomOutcome::TruePositive => &self.counters.s2l_hits,
+            BloomOutcome::FalsePositive => &self.counters.s2l_false_positives,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Current lookup counters.
+    pub fn metrics_snapshot(&self) -> BloomMetrics {
+        BloomMetrics {
+            s2l_hits: self.counters.s2l_hits.load(Ordering::Relaxed),
+            s2l_misses: self.counters.s2l_misses.load(Ordering::Relaxed),
+            s2l_false_positives: self.counters.s2l_false_positives.load(Ordering::Relaxed),
+        }
+    }
 }
 
 pub struct LocalBloom {
@@ -57,19 +265,7 @@ impl LocalBloom {
     }
 
     pub fn from_snapshot(bytes: &[u8]) -> Result<Self> {
-        if bytes.len() < 4 {
-            return Err(anyhow!("Bloom snapshot payload too small"));
-        }
-        let hashes = u32::from_be_bytes(bytes[..4].try_into()?);
-        let body = &bytes[4..];
-        assert_eq!(body.len() % 8, 0);
-
-        let mut words = Vec::<u64>::with_capacity(body.len() / 8);
-        for chunk in body.chunks_exact(8) {
-            words.push(u64::from_ne_bytes(chunk.try_into()?));
-        }
-        let filter = fastbloom_rs::BloomFilter::from_u64_array(&words, hashes);
-
+        let filter = filter_from_payload(&decode_snapshot(bytes)?)?;
         Ok(Self {
             inner: RwLock::new(filter),
         })
@@ -85,29 +281,366 @@ impl ProbSet for LocalBloom {
     }
 
     fn snapshot(&self) -> Result<Vec<u8>> {
-        let bf = self.inner.read();
-        let mut payload = Vec::with_capacity(4 + bf.get_u8_array().len());
-        payload.extend_from_slice(&bf.hashes().to_be_bytes());
-        payload.extend_from_slice(bf.get_u8_array());
-        Ok(payload)
+        Ok(encode_snapshot(&filter_payload(&self.inner.read())))
+    }
+
+    fn estimated_count(&self) -> u64 {
+        estimated_items(&self.inner.read())
+    }
+
+    fn size_bytes(&self) -> usize {
+        self.inner.read().get_u8_array().len()
+    }
+
+    fn reset(&self, keys: &[String], expected: u64, fpp: f64) {
+        let fresh = Self::from_items(keys, expected, fpp);
+        *self.inner.write() = fresh.inner.into_inner();
+    }
+}
+
+/// One fixed-size filter within a [`ScalableBloomFilter`].
+struct BloomLayer {
+    filter: BloomFilter,
+    capacity: u64,
+    fpp: f64,
+    /// Keys added to this layer that were not already present
+    items: u64,
+}
+
+impl BloomLayer {
+    fn new(capacity: u64, fpp: f64) -> Self {
+        Self {
+            filter: FilterBuilder::new(capacity, fpp).build_bloom_filter(),
+            capacity,
+            fpp,
+            items: 0,
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        self.items as f64 > self.capacity as f64 * LAYER_FILL_THRESHOLD
+    }
+
+    fn next(&self) -> Self {
+        Self::new(
+            self.capacity.saturating_mul(LAYER_GROWTH),
+            self.fpp * LAYER_TIGHTENING,
+        )
+    }
+}
+
+/// Bloom filter that adds layers as it fills, instead of letting its
+/// false-positive rate climb past the configured one.
+///
+/// The first layer uses the configured capacity and rate. Once the newest
+/// layer is 90% full, a layer with twice the capacity and half the
+/// false-positive rate is added. Lookups check every layer; inserts only
+/// write to the newest one.
+pub struct ScalableBloomFilter {
+    layers: RwLock<Vec<BloomLayer>>,
+}
+
+impl ScalableBloomFilter {
+    pub fn new(expected: u64, fpp: f64) -> Self {
+        Self {
+            layers: RwLock::new(vec![BloomLayer::new(expected, fpp)]),
+        }
+    }
+
+    /// Number of layers allocated so far.
+    pub fn layer_count(&self) -> usize {
+        self.layers.read().len()
+    }
+
+    /// Sum of the estimated item counts of every layer.
+    pub fn total_estimated_items(&self) -> u64 {
+        self.layers.read().iter().fold(0u64, |total, layer| {
+            total.saturating_add(estimated_items(&layer.filter))
+        })
+    }
+
+    /// Size of the first layer's bit array, which is fixed by the configuration.
+    fn base_size_bytes(&self) -> usize {
+        self.layers.read()[0].filter.get_u8_array().len()
+    }
+
+    /// Restores a filter from [`ProbSet::snapshot`] output.
+    ///
+    /// The payload is a big-endian `u32` layer count followed by each layer:
+    /// capacity (`u64`), false-positive rate (`f64`), item count (`u64`),
+    /// filter payload length (`u32`) and the filter payload itself.
+    pub fn from_snapshot(bytes: &[u8]) -> Result<Self> {
+        let payload = decode_snapshot(bytes)?;
+        let mut reader = &payload[..];
+        let mut take = |n: usize| -> Result<&[u8]> {
+            if reader.len() < n {
+                return Err(anyhow!("scalable Bloom snapshot payload truncated"));
+            }
+            let (head, rest) = reader.split_at(n);
+            reader = rest;
+            Ok(head)
+        };
+
+        let count = u32::from_be_bytes(take(4)?.try_into()?);
+        if count == 0 {
+            return Err(anyhow!("scalable Bloom snapshot has no layers"));
+        }
+        let mut layers = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let capacity = u64::from_be_bytes(take(8)?.try_into()?);
+            let fpp = f64::from_be_bytes(take(8)?.try_into()?);
+            let items = u64::from_be_bytes(take(8)?.try_into()?);
+            let len = u32::from_be_bytes(take(4)?.try_into()?) as usize;
+            layers.push(BloomLayer {
+                filter: filter_from_payload(take(len)?)?,
+                capacity,
+                fpp,
+                items,
+            });
+        }
+
+        Ok(Self {
+            layers: RwLock::new(layers),
+        })
+    }
+}
+
+impl ProbSet for ScalableBloomFilter {
+    fn may_contain(&self, key: &str) -> bool {
+        self.layers
+            .read()
+            .iter()
+            .any(|layer| layer.filter.contains(key.as_bytes()))
+    }
+
+    fn insert(&self, key: &str) {
+        let mut layers = self.layers.write();
+        if layers
+            .iter()
+            .any(|layer| layer.filter.contains(key.as_bytes()))
+        {
+            return;
+        }
+
+        let newest = layers
+            .last_mut()
+            .expect("scalable Bloom filter has a layer");
+        newest.filter.add(key.as_bytes());
+        newest.items += 1;
+        if newest.is_full() {
+            let next = newest.next();
+            tracing::info!(
+                layers = layers.len() + 1,
+                capacity = next.capacity,
+                false_positive_rate = next.fpp,
+                "Scalable Bloom filter added a layer"
+            );
+            layers.push(next);
+        }
+    }
+
+    fn snapshot(&self) -> Result<Vec<u8>> {
+        let layers = self.layers.read();
+        let mut payload = Vec::new();
+        payload.extend_from_slice(&(layers.len() as u32).to_be_bytes());
+        for layer in layers.iter() {
+            let filter = filter_payload(&layer.filter);
+            payload.extend_from_slice(&layer.capacity.to_be_bytes());
+            payload.extend_from_slice(&layer.fpp.to_be_bytes());
+            payload.extend_from_slice(&layer.items.to_be_bytes());
+            payload.extend_from_slice(&(filter.len() as u32).to_be_bytes());
+            payload.extend_from_slice(&filter);
+        }
+        Ok(encode_snapshot(&payload))
     }
+
+    fn estimated_count(&self) -> u64 {
+        self.total_estimated_items()
+    }
+
+    fn size_bytes(&self) -> usize {
+        self.layers
+            .read()
+            .iter()
+            .map(|layer| layer.filter.get_u8_array().len())
+            .sum()
+    }
+
+    fn reset(&self, keys: &[String], expected: u64, fpp: f64) {
+        let fresh = Self::new(expected, fpp);
+        fresh.extend(keys.iter().map(String::as_str));
+        *self.layers.write() = fresh.layers.into_inner();
+    }
+}
+
+/// Serializes a single filter as its big-endian hash count followed by its bits.
+fn filter_payload(bf: &BloomFilter) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(4 + bf.get_u8_array().len());
+    payload.extend_from_slice(&bf.hashes().to_be_bytes());
+    payload.extend_from_slice(bf.get_u8_array());
+    payload
+}
+
+fn filter_from_payload(bytes: &[u8]) -> Result<BloomFilter> {
+    if bytes.len() < 4 {
+        return Err(anyhow!("Bloom snapshot payload too small"));
+    }
+    let hashes = u32::from_be_bytes(bytes[..4].try_into()?);
+    let body = &bytes[4..];
+    assert_eq!(body.len() % 8, 0);
+
+    let mut words = Vec::<u64>::with_capacity(body.len() / 8);
+    for chunk in body.chunks_exact(8) {
+        words.push(u64::from_ne_bytes(chunk.try_into()?));
+    }
+    Ok(BloomFilter::from_u64_array(&words, hashes))
+}
+
+/// Swamidass & Baldi estimate: `n = -(m / k) * ln(1 - X / m)`, where `m` is
+/// the filter size in bits, `k` the hash count and `X` the bits set.
+fn estimated_items(bf: &BloomFilter) -> u64 {
+    let bits = bf.get_u8_array();
+    let m = (bits.len() * 8) as f64;
+    let k = f64::from(bf.hashes());
+    if m == 0.0 || k == 0.0 {
+        return 0;
+    }
+    let set: u64 = bits.iter().map(|b| u64::from(b.count_ones())).sum();
+    if set as f64 >= m {
+        return u64::MAX;
+    }
+    (-(m / k) * (1.0 - set as f64 / m).ln()).round() as u64
 }
 
-pub async fn build_bloom_state(db: &Arc<dyn UrlDatabase>) -> Result<BloomState> {
-    if let Some(bytes) = db
-        .load_bloom_snapshot(S2L_SNAPSHOT_KEY)
+/// Loads the filter from its snapshot in `store`, or starts an empty one and
+/// fills it from every code in `db` with [`BloomState::rebuild_async`] when
+/// there is no snapshot or it was sized for other settings.
+///
+/// Returns without waiting for that rebuild, so startup does not block on
+/// large databases; see [`BloomState::is_ready`].
+pub async fn build_bloom_state(
+    db: &Arc<dyn UrlDatabase>,
+    store: &Arc<dyn BloomSnapshotStore>,
+    config: &BloomConfig,
+) -> Result<BloomState> {
+    let expected = config.expected_items;
+    let fpp = config.false_positive_rate;
+    let snapshot_key = if config.scalable {
+        S2L_SCALABLE_SNAPSHOT_KEY
+    } else {
+        S2L_SNAPSHOT_KEY
+    };
+
+    if let Some(bytes) = store
+        .load(snapshot_key)
         .await
-        .context("failed to load s2l bloom snapshot from database")?
+        .context("failed to load s2l bloom snapshot")?
     {
-        let s2l = LocalBloom::from_snapshot(&bytes)
-            .context("failed to decode s2l bloom snapshot payload")?;
-        tracing::info!("Loaded Bloom snapshot from database.");
-        return Ok(BloomState { s2l: Arc::new(s2l) });
+        let configured_bytes = LocalBloom::_new(expected, fpp).size_bytes();
+        let (s2l, snapshot_bytes): (Arc<dyn ProbSet>, usize) = if config.scalable {
+            let s2l = ScalableBloomFilter::from_snapshot(&bytes)
+                .context("failed to decode scalable s2l bloom snapshot payload")?;
+            let base = s2l.base_size_bytes();
+            (Arc::new(s2l), base)
+        } else {
+            let s2l = LocalBloom::from_snapshot(&bytes)
+                .context("failed to decode s2l bloom snapshot payload")?;
+            let size = s2l.size_bytes();
+            (Arc::new(s2l), size)
+        };
+
+        // A snapshot sized for other settings is rebuilt so new sizing takes effect
+        if snapshot_bytes == configured_bytes {
+            tracing::info!("Loaded Bloom snapshot.");
+            log_bloom_sizing(s2l.as_ref(), config);
+            if !config.scalable {
+                warn_if_over_capacity(s2l.estimated_count(), expected);
+            }
+            return Ok(BloomState {
+                s2l,
+                expected_items: expected,
+                snapshot_key,
+                counters: Arc::default(),
+                rebuild_lock: Arc::default(),
+                ready: Arc::new(watch::Sender::new(true)),
+            });
+        }
+        tracing::info!(
+            snapshot_bytes,
+            configured_bytes,
+            "Bloom snapshot was sized for different settings; rebuilding."
+        );
     }
 
-    // First-time build: pull data from DB in pages
-    let mut shorts: Vec<Vec<u8>> = Vec::new();
+    // First-time build: start empty and fill from the database in the background
+    let s2l: Arc<dyn ProbSet> = if config.scalable {
+        Arc::new(ScalableBloomFilter::new(expected, fpp))
+    } else {
+        Arc::new(LocalBloom::_new(expected, fpp))
+    };
+    log_bloom_sizing(s2l.as_ref(), config);
+
+    let blooms = BloomState {
+        s2l,
+        expected_items: expected,
+        snapshot_key,
+        counters: Arc::default(),
+        rebuild_lock: Arc::default(),
+        ready: Arc::new(watch::Sender::new(false)),
+    };
+    blooms.rebuild_async(Arc::clone(db), Arc::clone(store));
+    Ok(blooms)
+}
+
+/// Refills `blooms` from every short code and alias in the database and
+/// stores a fresh snapshot, e.g. after a restore or bulk import left the
+/// filter missing codes.
+///
+/// The filter is rebuilt to the configured size, so codes deleted since it
+/// was built are dropped too. Codes stored while the rebuild runs wait for it
+/// in [`BloomState::insert`]. A filter whose startup rebuild has not
+/// finished is [ready](BloomState::is_ready) afterwards.
+///
+/// # Returns
+///
+/// The number of codes in the rebuilt filter.
+pub async fn rebuild_bloom_from_db(
+    db: &Arc<dyn UrlDatabase>,
+    store: &Arc<dyn BloomSnapshotStore>,
+    blooms: &BloomState,
+    config: &BloomConfig,
+) -> Result<usize> {
+    let _rebuild = blooms.rebuild_lock.lock().await;
+    #[cfg(feature = "metrics")]
+    crate::telemetry::metrics::record_bloom_rebuilding(true);
 
+    let rebuilt = async {
+        let shorts = list_all_short_codes(db).await?;
+        blooms
+            .s2l
+            .reset(&shorts, config.expected_items, config.false_positive_rate);
+        if !config.scalable {
+            warn_if_over_capacity(shorts.len() as u64, config.expected_items);
+        }
+        if not_disable_bf_snapshots() {
+            save_snapshot(store, blooms.s2l.as_ref(), blooms.snapshot_key).await;
+        }
+        Ok(shorts.len())
+    }
+    .await;
+
+    #[cfg(feature = "metrics")]
+    crate::telemetry::metrics::record_bloom_rebuilding(false);
+    if let Ok(items) = rebuilt {
+        blooms.ready.send_replace(true);
+        tracing::info!(items, "Bloom filter rebuilt from the database");
+    }
+    rebuilt
+}
+
+/// Reads every short code and alias, a page at a time.
+async fn list_all_short_codes(db: &Arc<dyn UrlDatabase>) -> Result<Vec<String>> {
+    let mut shorts: Vec<String> = Vec::new();
     let mut offset: u64 = 0;
 
     loop {
@@ -115,35 +648,89 @@ pub async fn build_bloom_state(db: &Arc<dyn UrlDatabase>) -> Result<BloomState>
         if batch.is_empty() {
             break;
         }
-        for rec in &batch {
-            shorts.push(rec.as_bytes().to_vec());
-        }
-        offset += batch.len() as u64;
-        if batch.len() < PAGE as usize {
+        let len = batch.len();
+        shorts.extend(batch);
+        offset += len as u64;
+        if len < PAGE as usize {
             break;
         }
     }
+    Ok(shorts)
+}
 
-    let s2l = LocalBloom::from_items(shorts.iter().map(|v| &v[..]), EXPECTED, FPP);
-
-    if not_disable_bf_snapshots() {
-        match s2l.snapshot() {
-            Ok(bytes) => {
-                if let Err(err) = db
-                    .save_bloom_snapshot(S2L_SNAPSHOT_KEY, &bytes)
-                    .await
-                    .context("failed to persist s2l bloom snapshot to database")
-                {
-                    tracing::warn!(error = %err, "failed to persist s2l Bloom snapshot");
-                }
-            }
-            Err(err) => {
-                tracing::warn!(error = %err, "unable to serialize s2l Bloom snapshot");
+/// Stores a snapshot of `s2l`; a failure is only logged.
+async fn save_snapshot(store: &Arc<dyn BloomSnapshotStore>, s2l: &dyn ProbSet, snapshot_key: &str) {
+    match s2l.snapshot() {
+        Ok(bytes) => {
+            if let Err(err) = store
+                .save(snapshot_key, &bytes)
+                .await
+                .context("failed to persist s2l bloom snapshot")
+            {
+                tracing::warn!(error = %err, "failed to persist s2l Bloom snapshot");
             }
         }
+        Err(err) => {
+            tracing::warn!(error = %err, "unable to serialize s2l Bloom snapshot");
+        }
     }
+}
+
+fn log_bloom_sizing(s2l: &dyn ProbSet, config: &BloomConfig) {
+    tracing::info!(
+        size_bytes = s2l.size_bytes(),
+        expected_items = config.expected_items,
+        false_positive_rate = config.false_positive_rate,
+        scalable = config.scalable,
+        "Bloom filter ready"
+    );
+}
 
-    Ok(BloomState { s2l: Arc::new(s2l) })
+/// Warns when more codes are stored than the filter was sized for.
+///
+/// Returns whether the filter is over capacity.
+fn warn_if_over_capacity(items: u64, expected_items: u64) -> bool {
+    let over = items > expected_items;
+    if over {
+        tracing::warn!(
+            items,
+            expected_items,
+            "Bloom filter holds more codes than bloom.expected_items; \
+             false positives will exceed the configured rate. Raise bloom.expected_items."
+        );
+    }
+    over
+}
+
+/// Compresses a snapshot payload with LZ4 and prefixes the format version.
+///
+/// Stored blobs start with a version byte: [`SNAPSHOT_RAW`] or
+/// [`SNAPSHOT_LZ4`]. Snapshots written before versioning have no prefix; they
+/// start with the big-endian hash count, whose first byte is always `0x00`.
+pub fn encode_snapshot(payload: &[u8]) -> Vec<u8> {
+    let compressed = lz4_flex::compress_prepend_size(payload);
+    tracing::debug!(
+        original_bytes = payload.len(),
+        compressed_bytes = compressed.len(),
+        ratio = payload.len() as f64 / compressed.len().max(1) as f64,
+        "Bloom snapshot compressed"
+    );
+
+    let mut blob = Vec::with_capacity(1 + compressed.len());
+    blob.push(SNAPSHOT_LZ4);
+    blob.extend_from_slice(&compressed);
+    blob
+}
+
+/// Reverses [`encode_snapshot`], also accepting raw and legacy unprefixed blobs.
+pub fn decode_snapshot(blob: &[u8]) -> Result<Vec<u8>> {
+    match blob.first() {
+        Some(&SNAPSHOT_LZ4) => lz4_flex::decompress_size_prepended(&blob[1..])
+            .context("failed to decompress LZ4 Bloom snapshot"),
+        Some(&SNAPSHOT_RAW) => Ok(blob[1..].to_vec()),
+        Some(0x00) | None => Ok(blob.to_vec()),
+        Some(version) => Err(anyhow!("unknown Bloom snapshot format 0x{version:02x}")),
+    }
 }
 
 pub(crate) fn not_disable_bf_snapshots() -> bool {
@@ -152,3 +739,198 @@ pub(crate) fn not_disable_bf_snapshots() -> bool {
         Ok("1") | Ok("true") | Ok("TRUE")
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tracing_subscriber::{Layer, Registry, layer::Context as LayerContext, prelude::*};
+
+    struct WarnCounter(Arc<AtomicUsize>);
+
+    impl<S: tracing::Subscriber> Layer<S> for WarnCounter {
+        fn on_event(&self, event: &tracing::Event<'_>, _ctx: LayerContext<'_, S>) {
+            if *event.metadata().level() == tracing::Level::WARN {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+    }
+
+    #[test]
+    fn over_capacity_filter_emits_warning() {
+        let warnings = Arc::new(AtomicUsize::new(0));
+        let subscriber = Registry::default().with(WarnCounter(warnings.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            assert!(!warn_if_over_capacity(10, 10));
+            assert!(warn_if_over_capacity(11, 10));
+        });
+
+        assert_eq!(warnings.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn overfilled_filter_degrades_without_panicking() {
+        let keys: Vec<String> = (0..1_000).map(|i| format!("code{i}")).collect();
+        let bloom = LocalBloom::from_items(&keys, 10, 0.01);
+
+        // No false negatives, however full the filter is
+        assert!(keys.iter().all(|k| bloom.may_contain(k)));
+
+        let state = BloomState {
+            s2l: Arc::new(bloom),
+            expected_items: 10,
+            snapshot_key: S2L_SNAPSHOT_KEY,
+            counters: Arc::default(),
+            rebuild_lock: Arc::default(),
+            ready: Arc::new(watch::Sender::new(true)),
+        };
+        assert!(state.utilization() > 1.0);
+    }
+
+    #[test]
+    fn compressed_snapshot_round_trips_bit_identical() {
+        let bloom = LocalBloom::from_items(["abc", "def", "ghi"], 1_000, 0.01);
+        let stored = bloom.snapshot().unwrap();
+        assert_eq!(stored[0], SNAPSHOT_LZ4);
+
+        let restored = LocalBloom::from_snapshot(&stored).unwrap();
+        assert_eq!(
+            decode_snapshot(&restored.snapshot().unwrap()).unwrap(),
+            decode_snapshot(&stored).unwrap()
+        );
+        assert!(restored.may_contain("def"));
+    }
+
+    #[test]
+    fn legacy_and_raw_snapshots_are_loaded() {
+        let bloom = LocalBloom::from_items(["abc", "def"], 1_000, 0.01);
+        let payload = decode_snapshot(&bloom.snapshot().unwrap()).unwrap();
+
+        let legacy = LocalBloom::from_snapshot(&payload).unwrap();
+        assert!(legacy.may_contain("abc"));
+
+        let mut raw = vec![SNAPSHOT_RAW];
+        raw.extend_from_slice(&payload);
+        let raw = LocalBloom::from_snapshot(&raw).unwrap();
+        assert!(raw.may_contain("def"));
+
+        assert!(LocalBloom::from_snapshot(&[0x7f, 0, 0, 0, 0]).is_err());
+    }
+
+    #[test]
+    fn scalable_filter_grows_and_keeps_earlier_keys() {
+        let bloom = ScalableBloomFilter::new(100, 0.01);
+        let keys: Vec<String> = (0..1_000).map(|i| format!("code{i}")).collect();
+        bloom.extend(keys.iter().map(String::as_str));
+
+        // 100 -> 200 -> 400 -> 800 capacity covers 1000 keys at 90% fill
+        assert!(bloom.layer_count() >= 4, "{} layers", bloom.layer_count());
+        assert!(keys.iter().all(|k| bloom.may_contain(k)));
+        assert!(bloom.total_estimated_items() >= 900);
+    }
+
+    #[test]
+    fn scalable_snapshot_round_trips_all_layers() {
+        let bloom = ScalableBloomFilter::new(10, 0.01);
+        let keys: Vec<String> = (0..100).map(|i| format!("code{i}")).collect();
+        bloom.extend(keys.iter().map(String::as_str));
+
+        let restored = ScalableBloomFilter::from_snapshot(&bloom.snapshot().unwrap()).unwrap();
+        assert_eq!(restored.layer_count(), bloom.layer_count());
+        assert_eq!(restored.size_bytes(), bloom.size_bytes());
+        assert!(keys.iter().all(|k| restored.may_contain(k)));
+
+        // Restored layers keep their fill counts, so growth resumes correctly
+        restored.extend(["more1", "more2", "more3"]);
+        assert!(restored.layer_count() >= bloom.layer_count());
+    }
+
+    #[test]
+    fn reset_replaces_the_keys_of_both_filters() {
+        let keys: Vec<String> = (0..100).map(|i| format!("new{i}")).collect();
+        let filters: [Box<dyn ProbSet>; 2] = [
+            Box::new(LocalBloom::from_items(["old"], 1_000, 0.001)),
+            Box::new(ScalableBloomFilter::new(1_000, 0.001)),
+        ];
+
+        for filter in filters {
+            filter.insert("old");
+            filter.reset(&keys, 1_000, 0.001);
+
+            assert!(keys.iter().all(|k| filter.may_contain(k)));
+            assert!(!filter.may_contain("old"));
+        }
+    }
+
+    #[test]
+    fn lookups_are_counted_by_outcome() {
+        let state = BloomState {
+            s2l: Arc::new(LocalBloom::_new(1_000, 0.01)),
+            expected_items: 1_000,
+            snapshot_key: S2L_SNAPSHOT_KEY,
+            counters: Arc::default(),
+            rebuild_lock: Arc::default(),
+            ready: Arc::new(watch::Sender::new(true)),
+        };
+        state.s2l.insert("abc123");
+
+        // Clones share the counters, as handlers each get a cloned AppState
+        let handler_view = state.clone();
+        assert!(handler_view.s2l.may_contain("abc123"));
+        handler_view.record_lookup(BloomOutcome::TruePositive);
+        handler_view.record_lookup(BloomOutcome::TruePositive);
+        handler_view.record_lookup(BloomOutcome::FalsePositive);
+        handler_view.record_lookup(BloomOutcome::Miss);
+
+        assert_eq!(
+            state.metrics_snapshot(),
+            BloomMetrics {
+                s2l_hits: 2,
+                s2l_misses: 1,
+                s2l_false_positives: 1,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn build_without_snapshot_fills_the_filter_in_the_background() {
+        let db: Arc<dyn UrlDatabase> = Arc::new(
+            crate::database::SqliteUrlDatabase::in_memory_with_migrations()
+                .await
+                .unwrap(),
+        );
+        db.insert_urls_batch(&[
+            ("bg0001", "https://example.com/1"),
+            ("bg0002", "https://example.com/2"),
+        ])
+        .await
+        .unwrap();
+        let store: Arc<dyn BloomSnapshotStore> =
+            Arc::new(crate::shortcode::snapshot_store::DatabaseBloomSnapshotStore::new(db.clone()));
+        let config = BloomConfig {
+            expected_items: 1_000,
+            ..BloomConfig::default()
+        };
+
+        let blooms = build_bloom_state(&db, &store, &config).await.unwrap();
+        blooms.wait_until_ready().await;
+
+        assert!(blooms.is_ready());
+        assert!(blooms.s2l.may_contain("bg0001"));
+        assert!(blooms.s2l.may_contain("bg0002"));
+    }
+
+    #[test]
+    fn utilization_is_zero_for_empty_filter() {
+        let state = BloomState {
+            s2l: Arc::new(LocalBloom::_new(1_000, 0.01)),
+            expected_items: 1_000,
+            snapshot_key: S2L_SNAPSHOT_KEY,
+            counters: Arc::default(),
+            rebuild_lock: Arc::default(),
+            ready: Arc::new(watch::Sender::new(true)),
+        };
+        assert_eq!(state.utilization(), 0.0);
+    }
+}
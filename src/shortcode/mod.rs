@@ -1 +1,2 @@
 pub mod bloom_filter;
+pub mod snapshot_store;
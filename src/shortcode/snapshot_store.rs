@@ -0,0 +1,182 @@
+//! # Bloom Snapshot Storage
+//!
+//! Where serialized Bloom filters are kept between restarts, chosen by
+//! `bloom.storage.backend`:
+//!
+//! - `database` (default): the `bloom_snapshots` table, through [`UrlDatabase`]
+//! - `s3`: an object per snapshot in an S3 or R2 bucket, for deployments whose
+//!   database is a read replica or has a tight storage quota (`s3` feature)
+
+use crate::configuration::{BloomStorageBackend, BloomStorageConfig};
+use crate::database::{DatabaseError, UrlDatabase};
+use anyhow::Result;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// Why a snapshot could not be loaded or saved.
+#[derive(Debug, thiserror::Error)]
+pub enum StorageError {
+    #[error(transparent)]
+    Database(#[from] DatabaseError),
+    /// The object store refused the request or could not be reached
+    #[error("object storage request failed: {0}")]
+    ObjectStore(#[source] Box<dyn std::error::Error + Send + Sync>),
+}
+
+/// Loads and saves serialized Bloom filters by name.
+#[async_trait]
+pub trait BloomSnapshotStore: Send + Sync {
+    /// Returns the snapshot stored under `name`, or `None` if there is none.
+    async fn load(&self, name: &str) -> Result<Option<Vec<u8>>, StorageError>;
+    /// Stores `data` under `name`, replacing any earlier snapshot.
+    async fn save(&self, name: &str, data: &[u8]) -> Result<(), StorageError>;
+    /// Verifies that the store can be reached, for the pre-flight checks.
+    async fn check(&self) -> Result<(), StorageError>;
+}
+
+/// Keeps snapshots in the database's `bloom_snapshots` table.
+pub struct DatabaseBloomSnapshotStore {
+    db: Arc<dyn UrlDatabase>,
+}
+
+impl DatabaseBloomSnapshotStore {
+    pub fn new(db: Arc<dyn UrlDatabase>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl BloomSnapshotStore for DatabaseBloomSnapshotStore {
+    async fn load(&self, name: &str) -> Result<Option<Vec<u8>>, StorageError> {
+        Ok(self.db.load_bloom_snapshot(name).await?)
+    }
+
+    async fn save(&self, name: &str, data: &[u8]) -> Result<(), StorageError> {
+        Ok(self.db.save_bloom_snapshot(name, data).await?)
+    }
+
+    async fn check(&self) -> Result<(), StorageError> {
+        Ok(self.db.health_check().await?)
+    }
+}
+
+/// Keeps snapshots as objects named `<key_prefix><name>` in an S3 bucket.
+///
+/// Works with any S3-compatible store; point the SDK at it with
+/// `AWS_ENDPOINT_URL`, e.g. `https://<account>.r2.cloudflarestorage.com`.
+#[cfg(feature = "s3")]
+pub struct S3BloomSnapshotStore {
+    client: aws_sdk_s3::Client,
+    bucket: String,
+    key_prefix: String,
+}
+
+#[cfg(feature = "s3")]
+impl S3BloomSnapshotStore {
+    /// Builds a client from the AWS default provider chain, in `region` if
+    /// given.
+    pub async fn new(bucket: String, key_prefix: Option<String>, region: Option<String>) -> Self {
+        let mut loader = aws_config::defaults(aws_config::BehaviorVersion::latest());
+        if let Some(region) = region {
+            loader = loader.region(aws_config::Region::new(region));
+        }
+        let sdk_config = loader.load().await;
+        Self::from_client(aws_sdk_s3::Client::new(&sdk_config), bucket, key_prefix)
+    }
+
+    /// Uses an already configured `client`.
+    pub fn from_client(
+        client: aws_sdk_s3::Client,
+        bucket: String,
+        key_prefix: Option<String>,
+    ) -> Self {
+        Self {
+            client,
+            bucket,
+            key_prefix: key_prefix.unwrap_or_default(),
+        }
+    }
+
+    fn object_key(&self, name: &str) -> String {
+        format!("{}{}", self.key_prefix, name)
+    }
+}
+
+#[cfg(feature = "s3")]
+#[async_trait]
+impl BloomSnapshotStore for S3BloomSnapshotStore {
+    async fn load(&self, name: &str) -> Result<Option<Vec<u8>>, StorageError> {
+        let object = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(self.object_key(name))
+            .send()
+            .await;
+        let object = match object {
+            Ok(object) => object,
+            Err(e) if e.as_service_error().is_some_and(|e| e.is_no_such_key()) => {
+                return Ok(None);
+            }
+            Err(e) => return Err(StorageError::ObjectStore(e.into())),
+        };
+        let body = object
+            .body
+            .collect()
+            .await
+            .map_err(|e| StorageError::ObjectStore(e.into()))?;
+        Ok(Some(body.into_bytes().to_vec()))
+    }
+
+    async fn save(&self, name: &str, data: &[u8]) -> Result<(), StorageError> {
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(self.object_key(name))
+            .body(aws_sdk_s3::primitives::ByteStream::from(data.to_vec()))
+            .send()
+            .await
+            .map_err(|e| StorageError::ObjectStore(e.into()))?;
+        Ok(())
+    }
+
+    async fn check(&self) -> Result<(), StorageError> {
+        self.client
+            .head_bucket()
+            .bucket(&self.bucket)
+            .send()
+            .await
+            .map_err(|e| StorageError::ObjectStore(e.into()))?;
+        Ok(())
+    }
+}
+
+/// Builds the store selected by `bloom.storage.backend`.
+///
+/// # Errors
+///
+/// Returns an error for the `s3` backend without a bucket, or in a binary
+/// built without the `s3` feature.
+pub async fn build_snapshot_store(
+    config: &BloomStorageConfig,
+    db: &Arc<dyn UrlDatabase>,
+) -> Result<Arc<dyn BloomSnapshotStore>> {
+    match config.backend {
+        BloomStorageBackend::Database => Ok(Arc::new(DatabaseBloomSnapshotStore::new(db.clone()))),
+        #[cfg(feature = "s3")]
+        BloomStorageBackend::S3 => {
+            let bucket = config
+                .bucket
+                .clone()
+                .ok_or_else(|| anyhow::anyhow!("bloom.storage.bucket must be set for s3"))?;
+            Ok(Arc::new(
+                S3BloomSnapshotStore::new(bucket, config.key_prefix.clone(), config.region.clone())
+                    .await,
+            ))
+        }
+        #[cfg(not(feature = "s3"))]
+        BloomStorageBackend::S3 => Err(anyhow::anyhow!(
+            "bloom.storage.backend s3 needs the `s3` feature"
+        )),
+    }
+}
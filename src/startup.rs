@@ -23,8 +23,13 @@
 //! ## Middleware Stack
 //!
 //! The application uses several middleware layers:
+//! - **Real IP** - Client IP from trusted proxy headers
 //! - **Request ID** - Unique identifier for each request
 //! - **Tracing** - Request/response logging and tracing
+//! - **Access Log** - Optional one-line-per-request log
+//! - **Security Headers** - Clickjacking, MIME-sniffing and CSP protection
+//! - **HTML Error Pages** - Error pages instead of JSON for browsers
+//! - **Timeout** - 504 for requests exceeding `application.request_timeout_ms`
 //! - **API Key Authentication** - For protected endpoints
 //!
 //! ## Graceful Shutdown
@@ -41,57 +46,100 @@
 //!
 //! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
 //! let config = get_configuration()?;
-//! let app = Application::build(config).await?;
+//! let app = Application::build(config.try_into()?).await?;
 //! app.run_until_stopped().await?;
 //! # Ok(())
 //! # }
 //! ```
 
-use crate::configuration::Settings;
+use crate::configuration::{
+    ApplicationSettings, CompressionSettings, CorsSettings, DatabaseSettings, RateLimitBackend,
+    Settings, ValidatedSettings,
+};
 use crate::core::security::jwt::JwtKeys;
 use crate::database::postgres_sql::PostgresUrlDatabase;
 use crate::database::{SqliteUrlDatabase, UrlDatabase};
+use crate::errors::ApiError;
 use crate::features::auth::repositories::NoopAuthRepo;
 use crate::features::auth::routes as auth;
 use crate::features::auth::services::AuthService;
 use crate::features::users;
 use crate::features::users::repositories::NoopUserRepo;
 use crate::features::users::services::UserService;
-use crate::generator::{DEFAULT_ALPHABET, build_generator};
+use crate::generator::DEFAULT_ALPHABET;
 use crate::infrastructure::db::{self};
 use crate::infrastructure::email::EmailService;
-use crate::middleware::check_api_key;
+use crate::infrastructure::expiry_reminders::spawn_expiry_reminders;
+use crate::infrastructure::maintenance::spawn_maintenance;
+use crate::middleware::{
+    RealIpKeyExtractor, attach_request_id, check_accept_version, check_api_key,
+    check_api_key_or_user, enforce_request_timeout, extract_real_ip, handle_panic,
+    negotiate_problem_details, render_html_errors, require_admin, set_security_headers,
+    track_in_flight, verify_webhook_auth,
+};
+use crate::preflight::{self, STATIC_DIR};
 use crate::routes::{
-    get_admin_dashboard, get_analytics, get_index, get_login, get_redirect, get_register, get_urls,
-    get_user_profile, get_users, health_check, post_shorten, serve_openapi_spec, serve_swagger_ui,
+    MAX_IMPORT_BODY_BYTES, delete_url, delete_url_tag, export_urls, get_admin_config,
+    get_admin_dashboard, get_admin_shorten, get_admin_stats, get_admin_url_analytics,
+    get_admin_urls, get_analytics, get_index, get_login, get_qr_code, get_qr_image, get_redirect,
+    get_redirect_head, get_redirect_preview, get_register, get_url_clicks, get_url_info,
+    get_url_preview, get_url_stats, get_user_profile, get_users, head_url_info, health_check,
+    import_urls, list_api_keys, list_urls, options_redirect, patch_url, post_admin_delete_url,
+    post_rebuild_bloom, post_shorten, post_shorten_batch, post_unlock, post_url_tag,
+    post_webhook_receive, readiness_check, search_urls, serve_openapi_json, serve_openapi_spec,
+    serve_swagger_ui,
 };
 use axum::middleware::from_fn;
 use secrecy::ExposeSecret;
 use tokio::time::Duration as TokioDuration;
 
-use crate::shortcode::bloom_filter::{
-    S2L_SNAPSHOT_KEY, build_bloom_state, not_disable_bf_snapshots,
-};
-use crate::state::AppState;
-use crate::telemetry::MakeRequestUuid;
+use crate::shortcode::bloom_filter::not_disable_bf_snapshots;
+use crate::state::{AppState, AppStateBuilder};
+use crate::telemetry::access_log::log_access;
+use crate::telemetry::error_reporting::init_error_reporting;
+use crate::telemetry::{MakeRequestUuid, make_http_span};
+use crate::templates::watch_templates;
 use crate::{DatabaseType, capture_client_meta};
 use anyhow::{Context, Result};
-use axum::http::{Request, Response};
+use axum::http::{
+    Extensions, HeaderMap, HeaderValue, Method, Request, Response, StatusCode, Version, header,
+};
+#[cfg(unix)]
+use axum::{Extension, extract::ConnectInfo};
 use axum::{
     Router,
+    body::Body,
+    extract::DefaultBodyLimit,
     http::HeaderName,
     middleware::from_fn_with_state,
-    routing::{get, post},
+    response::IntoResponse,
+    routing::{delete, get, post},
 };
 use std::collections::HashSet;
 
 use chrono::Duration;
+use futures_util::FutureExt;
+use futures_util::future::{BoxFuture, try_join_all};
+use sentry_tower::{NewSentryLayer, SentryHttpLayer};
+use socket2::{Domain, Protocol, Socket, Type};
+use std::net::{Ipv6Addr, SocketAddr};
+#[cfg(unix)]
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::net::TcpListener;
+#[cfg(unix)]
+use tokio::net::UnixListener;
 use tokio::signal;
+use tokio::sync::{Notify, watch};
 use tower::ServiceBuilder;
-use tower_governor::{GovernorLayer, governor::GovernorConfigBuilder};
+use tower_governor::{GovernorError, GovernorLayer, governor::GovernorConfigBuilder};
 use tower_http::{
+    catch_panic::CatchPanicLayer,
+    compression::{
+        CompressionLayer, Predicate,
+        predicate::{NotForContentType, SizeAbove},
+    },
+    cors::{AllowOrigin, Any, CorsLayer},
     request_id::{PropagateRequestIdLayer, SetRequestIdLayer},
     services::ServeDir,
     trace::TraceLayer,
@@ -129,27 +177,30 @@ use tracing::Span;
 /// # Ok(())
 /// # }
 /// ```
-async fn shutdown_signal() {
+fn shutdown_signal() -> impl Future<Output = ()> + Send + 'static {
     let ctrl_c = async {
         signal::ctrl_c()
             .await
             .expect("failed to install Ctrl+C handler");
     };
 
+    // Installed now rather than on first poll, see `Application::shutdown`
+    #[cfg(unix)]
+    let mut sigterm = signal::unix::signal(signal::unix::SignalKind::terminate())
+        .expect("failed to install signal handler");
     #[cfg(unix)]
-    let terminate = async {
-        signal::unix::signal(signal::unix::SignalKind::terminate())
-            .expect("failed to install signal handler")
-            .recv()
-            .await;
+    let terminate = async move {
+        sigterm.recv().await;
     };
 
     #[cfg(not(unix))]
     let terminate = std::future::pending::<()>();
 
-    tokio::select! {
-        _ = ctrl_c => {},
-        _ = terminate => {},
+    async {
+        tokio::select! {
+            _ = ctrl_c => {},
+            _ = terminate => {},
+        }
     }
 }
 
@@ -161,7 +212,7 @@ async fn shutdown_signal() {
 /// # Fields
 ///
 /// * `port` - The port number the server is listening on
-/// * `listener` - TCP listener for incoming connections
+/// * `listeners` - TCP listeners for incoming connections, all on `port`
 /// * `router` - Axum router with all configured routes and middleware
 /// * `state` - Application state shared across all handlers
 ///
@@ -178,7 +229,7 @@ async fn shutdown_signal() {
 ///
 /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
 /// let config = get_configuration()?;
-/// let app = Application::build(config).await?;
+/// let app = Application::build(config.try_into()?).await?;
 /// println!("Server running on port {}", app.port());
 /// app.run_until_stopped().await?;
 /// # Ok(())
@@ -188,12 +239,22 @@ async fn shutdown_signal() {
 pub struct Application {
     /// Port number the server is listening on
     port: u16,
-    /// TCP listener for incoming connections
-    listener: TcpListener,
+    /// TCP listeners for incoming connections, all on `port`
+    listeners: Vec<TcpListener>,
+    /// Unix domain socket listener and its path, from
+    /// `application.unix_socket_path`
+    #[cfg(unix)]
+    unix_listener: Option<(UnixListener, PathBuf)>,
     /// Axum router with all configured routes and middleware
     router: Router<AppState>,
     /// Application state shared across all handlers
     state: AppState,
+    /// Keeps Sentry reporting on while the server runs, with `sentry.enabled`
+    _error_reporting: Option<sentry::ClientInitGuard>,
+    /// Resolves on SIGINT/SIGTERM; the handlers are installed before the
+    /// listeners are bound, so a signal sent once connections are accepted
+    /// always shuts down gracefully instead of killing the process
+    shutdown: BoxFuture<'static, ()>,
 }
 
 impl Application {
@@ -201,13 +262,15 @@ impl Application {
     ///
     /// This method performs the complete application initialization process:
     /// 1. Sets up the database connection and runs migrations
-    /// 2. Creates the TCP listener on the configured address
-    /// 3. Initializes the application state
-    /// 4. Builds the router with all routes and middleware
+    /// 2. Runs the [pre-flight checks](crate::preflight::run_checks)
+    /// 3. Creates the TCP listener on the configured address
+    /// 4. Initializes the application state
+    /// 5. Builds the router with all routes and middleware
     ///
     /// # Arguments
     ///
-    /// * `config` - Application configuration settings
+    /// * `config` - Application configuration settings that passed
+    ///   [`Settings::validate`](crate::configuration::Settings::validate)
     ///
     /// # Returns
     ///
@@ -219,6 +282,7 @@ impl Application {
     /// This method will return an error if:
     /// - Database connection fails
     /// - Database migrations fail
+    /// - A pre-flight check fails
     /// - TCP listener cannot be bound to the configured address
     /// - Router creation fails
     ///
@@ -230,63 +294,51 @@ impl Application {
     ///
     /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
     /// let config = get_configuration()?;
-    /// let app = Application::build(config).await?;
+    /// let app = Application::build(config.try_into()?).await?;
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn build(cfg: Settings) -> Result<Self, anyhow::Error> {
-        let url_db: Arc<dyn UrlDatabase> = match cfg.database.r#type {
-            DatabaseType::Sqlite => {
-                let db = SqliteUrlDatabase::from_config(&cfg.database).await?;
-                db.migrate().await?;
-                Arc::new(db) as Arc<dyn UrlDatabase>
-            }
-            DatabaseType::Postgres => {
-                let db = PostgresUrlDatabase::from_config(&cfg.database).await?;
-                db.migrate().await?;
-                Arc::new(db) as Arc<dyn UrlDatabase>
-            }
-        };
-
-        let code_gen = build_generator(&cfg.shortener);
-        let allowed_chars = build_allowed_chars(cfg.shortener.alphabet.as_deref());
+    pub async fn build(cfg: ValidatedSettings) -> Result<Self, anyhow::Error> {
+        let cfg = cfg.into_inner();
+        let error_reporting = init_error_reporting(&cfg.sentry, &cfg.environment);
 
-        let blooms: crate::shortcode::bloom_filter::BloomState = build_bloom_state(&url_db).await?;
-        let jwt = JwtKeys::new(cfg.application.jwt_secret_b64.expose_secret().as_bytes());
+        let url_db =
+            preflight::report("database migrations", connect_database(&cfg.database).await)?;
+        preflight::run_checks(&cfg, &url_db).await?;
 
-        let (auth_svc, user_svc) = build_services(&cfg, &jwt).await?;
+        #[cfg(feature = "metrics")]
+        let url_db = crate::telemetry::metrics::MeteredDatabase::wrap(url_db);
 
-        // Set up the TCP listener and application state
-        let address = format!("{}:{}", cfg.application.host, cfg.application.port);
-        let listener = TcpListener::bind(address)
-            .await
-            .context("Unable to obtain a TCP listener...")?;
-        let port = listener.local_addr()?.port();
-
-        let state = AppState {
-            // db_pool: Arc::new(db_pool),
-            code_generator: code_gen,
-            blooms,
-            allowed_chars,
-            api_key: cfg.application.api_key,
-            template_dir: cfg.application.templates.clone(),
-            config: cfg.clone(),
-            auth_service: auth_svc,
-            user_service: user_svc,
-            jwt,
-            database: url_db,
+        // Set up the TCP listeners and application state
+        let shutdown = shutdown_signal().boxed();
+        let listeners = bind_listeners(&cfg.application).await?;
+        let port = listeners[0].local_addr()?.port();
+        #[cfg(unix)]
+        let unix_listener = match &cfg.application.unix_socket_path {
+            Some(path) => {
+                let path = PathBuf::from(path);
+                Some((bind_unix_socket(&path)?, path))
+            }
+            None => None,
         };
 
-        // Template initialization
-        crate::templates::build_templates(state.clone()).expect("Failed to build templates");
+        let state = AppStateBuilder::new()
+            .config(cfg)
+            .database(url_db)
+            .build()
+            .await?;
+
+        if let Err(e) = watch_templates(state.templates()) {
+            tracing::warn!("template hot reload unavailable: {}", e);
+        }
 
         // Build the application router, passing in the application state
         let router = build_router(state.clone())
             .await
             .context("Failed to create the application router.")?;
 
-        let blooms = state.blooms.clone();
-        let bloom_db = state.database.clone();
+        let blooms = state.blooms().clone();
+        let bloom_store = state.bloom_snapshot_store().clone();
 
         if not_disable_bf_snapshots() {
             tokio::spawn(async move {
@@ -300,23 +352,33 @@ impl Application {
                             continue;
                         }
                     };
-                    if let Err(err) = bloom_db
-                        .save_bloom_snapshot(S2L_SNAPSHOT_KEY, &snapshot)
-                        .await
-                    {
+                    if let Err(err) = bloom_store.save(blooms.snapshot_key, &snapshot).await {
                         tracing::warn!(error = %err, "failed to persist s2l Bloom snapshot");
                         continue;
                     }
-                    tracing::info!("Bloom snapshot saved to database.");
+                    tracing::info!("Bloom snapshot saved.");
                 }
             });
         }
 
+        if state.config().expiry_reminders.enabled {
+            let mailer = Arc::new(build_email_service(state.config()));
+            spawn_expiry_reminders(state.clone(), mailer);
+        }
+
+        if state.config().maintenance.enabled {
+            spawn_maintenance(state.database().clone(), state.config().maintenance.clone());
+        }
+
         Ok(Self {
             port,
-            listener,
+            listeners,
+            #[cfg(unix)]
+            unix_listener,
             router,
             state,
+            _error_reporting: error_reporting,
+            shutdown,
         })
     }
 
@@ -337,7 +399,7 @@ impl Application {
     ///
     /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
     /// let config = get_configuration()?;
-    /// let app = Application::build(config).await?;
+    /// let app = Application::build(config.try_into()?).await?;
     /// println!("Server running on port {}", app.port());
     /// # Ok(())
     /// # }
@@ -346,6 +408,15 @@ impl Application {
         self.port
     }
 
+    /// Returns the address of every socket the server is listening on; two
+    /// with `application.bind_ipv6` and `application.ipv6_only`, else one.
+    pub fn local_addrs(&self) -> Vec<SocketAddr> {
+        self.listeners
+            .iter()
+            .filter_map(|listener| listener.local_addr().ok())
+            .collect()
+    }
+
     /// Runs the application server until stopped.
     ///
     /// This method starts the HTTP server and runs it until a shutdown signal
@@ -358,6 +429,12 @@ impl Application {
     /// - `SIGINT` (Ctrl+C)
     /// - `SIGTERM` (Unix only)
     ///
+    /// In-flight requests get `application.graceful_shutdown_timeout_seconds`
+    /// to finish. If any are still running after that, a warning is logged
+    /// and the process exits with status 1, so a stuck handler cannot keep
+    /// it alive. The `application.unix_socket_path` socket, if any, is
+    /// removed on the way out.
+    ///
     /// # Returns
     ///
     /// Returns `Ok(())` if the server shuts down cleanly, or `Err(anyhow::Error)`
@@ -371,30 +448,39 @@ impl Application {
     ///
     /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
     /// let config = get_configuration()?;
-    /// let app = Application::build(config).await?;
+    /// let app = Application::build(config.try_into()?).await?;
     /// app.run_until_stopped().await?;
     /// # Ok(())
     /// # }
     /// ```
     pub async fn run_until_stopped(self) -> Result<(), anyhow::Error> {
-        let blooms = self.state.blooms.clone();
-        let bloom_db = self.state.database.clone();
-
-        axum::serve(
-            self.listener,
-            self.router
-                .with_state(self.state.clone())
-                .into_make_service_with_connect_info::<std::net::SocketAddr>(),
-        )
-        .with_graceful_shutdown(async move {
-            shutdown_signal().await;
+        let blooms = self.state.blooms().clone();
+        let bloom_store = self.state.bloom_snapshot_store().clone();
+        let drain_timeout = std::time::Duration::from_secs(
+            self.state
+                .config()
+                .application
+                .graceful_shutdown_timeout_seconds,
+        );
+        let draining = Arc::new(Notify::new());
+        let shutdown_started = draining.clone();
+        let shutdown_state = self.state.clone();
+
+        // Every listener stops accepting once the signal has been handled
+        let (stopping, stopped) = watch::channel(false);
+        let signalled = self.shutdown;
+        tokio::spawn(async move {
+            signalled.await;
+            tracing::info!(
+                in_flight = shutdown_state.in_flight_requests(),
+                "Shutting down; waiting for in-flight requests"
+            );
+            shutdown_started.notify_one();
 
             if not_disable_bf_snapshots() {
                 match blooms.s2l.snapshot() {
                     Ok(bytes) => {
-                        if let Err(err) =
-                            bloom_db.save_bloom_snapshot(S2L_SNAPSHOT_KEY, &bytes).await
-                        {
+                        if let Err(err) = bloom_store.save(blooms.snapshot_key, &bytes).await {
                             tracing::warn!(
                                 %err,
                                 "failed to persist s2l Bloom snapshot on shutdown"
@@ -411,14 +497,189 @@ impl Application {
                     }
                 }
             }
-        })
-        .await
-        .context("Unable to start the app server...")?;
+            let _ = stopping.send(true);
+        });
+
+        let router = self.router.with_state(self.state.clone());
+        let service = router
+            .clone()
+            .into_make_service_with_connect_info::<SocketAddr>();
+        let mut servers: Vec<BoxFuture<'static, std::io::Result<()>>> = Vec::new();
+        for listener in self.listeners {
+            let mut stopped = stopped.clone();
+            servers.push(
+                axum::serve(listener, service.clone())
+                    .with_graceful_shutdown(async move {
+                        let _ = stopped.wait_for(|stopped| *stopped).await;
+                    })
+                    .into_future()
+                    .boxed(),
+            );
+        }
+        #[cfg(unix)]
+        let unix_socket_path = match self.unix_listener {
+            Some((listener, path)) => {
+                // A Unix socket has no peer IP; report the peer as local, so
+                // `extract_real_ip` treats it like a proxy on this machine
+                let local = ConnectInfo(SocketAddr::from((std::net::Ipv4Addr::LOCALHOST, 0)));
+                let mut stopped = stopped.clone();
+                servers.push(
+                    axum::serve(listener, router.layer(Extension(local)).into_make_service())
+                        .with_graceful_shutdown(async move {
+                            let _ = stopped.wait_for(|stopped| *stopped).await;
+                        })
+                        .into_future()
+                        .boxed(),
+                );
+                Some(path)
+            }
+            None => None,
+        };
+        let server = try_join_all(servers);
+
+        let served = drain_within(server, &draining, drain_timeout).await;
+        #[cfg(unix)]
+        if let Some((path, Err(err))) = unix_socket_path
+            .as_deref()
+            .map(|path| (path, std::fs::remove_file(path)))
+        {
+            tracing::warn!(%err, path = %path.display(), "failed to remove the Unix socket");
+        }
+        match served {
+            Some(result) => {
+                result.context("Unable to start the app server...")?;
+            }
+            None => {
+                tracing::warn!(
+                    in_flight = self.state.in_flight_requests(),
+                    "Graceful shutdown timed out; forcing exit"
+                );
+                std::process::exit(1);
+            }
+        }
 
         Ok(())
     }
 }
 
+/// Runs `server` to completion, but once `draining` is notified gives it only
+/// `drain_timeout` more; `None` means it did not finish in time.
+async fn drain_within<F: Future>(
+    server: F,
+    draining: &Notify,
+    drain_timeout: std::time::Duration,
+) -> Option<F::Output> {
+    tokio::pin!(server);
+    tokio::select! {
+        output = &mut server => Some(output),
+        _ = draining.notified() => tokio::time::timeout(drain_timeout, server).await.ok(),
+    }
+}
+
+/// Binds the sockets the server accepts connections on.
+///
+/// Without `bind_ipv6` there is one listener on `host:port`. With it, one
+/// dual-stack socket on `[::]:port` takes both IPv4 and IPv6 connections and
+/// `host` is not used. Add `ipv6_only` where dual-stack sockets are not
+/// wanted or not supported: then `host:port` serves IPv4 and an IPv6-only
+/// socket on `[::]` serves IPv6, at the same port even when `port` is `0`.
+///
+/// # Errors
+///
+/// Returns an error if a socket cannot be bound, e.g. when the port is in
+/// use or the host has no IPv6 support.
+pub async fn bind_listeners(settings: &ApplicationSettings) -> Result<Vec<TcpListener>> {
+    if settings.bind_ipv6 && !settings.ipv6_only {
+        return Ok(vec![bind_ipv6_any(settings.port, false)?]);
+    }
+
+    let address = format!("{}:{}", settings.host, settings.port);
+    let listener = TcpListener::bind(address)
+        .await
+        .context("Unable to obtain a TCP listener...")?;
+    if !settings.bind_ipv6 {
+        return Ok(vec![listener]);
+    }
+    let ipv6 = bind_ipv6_any(listener.local_addr()?.port(), true)?;
+    Ok(vec![listener, ipv6])
+}
+
+/// Binds `[::]:port`, accepting IPv4 connections too unless `only_v6`.
+///
+/// The operating system default for `IPV6_V6ONLY` differs (off on Linux, on
+/// on Windows and the BSDs), so it is always set explicitly.
+fn bind_ipv6_any(port: u16, only_v6: bool) -> Result<TcpListener> {
+    let address = SocketAddr::from((Ipv6Addr::UNSPECIFIED, port));
+    let socket = Socket::new(Domain::IPV6, Type::STREAM, Some(Protocol::TCP))
+        .context("Unable to create an IPv6 socket...")?;
+    socket.set_only_v6(only_v6)?;
+    #[cfg(unix)]
+    socket.set_reuse_address(true)?;
+    socket.set_nonblocking(true)?;
+    socket
+        .bind(&address.into())
+        .with_context(|| format!("Unable to obtain a TCP listener on {}...", address))?;
+    socket.listen(1024)?;
+    TcpListener::from_std(socket.into()).context("Unable to obtain a TCP listener...")
+}
+
+/// Binds a Unix domain socket at `path` that its owner and group may
+/// connect to (mode `0o660`).
+///
+/// The parent directory is created if missing. A socket file left behind by
+/// a process that did not shut down cleanly is replaced; any other file at
+/// `path` is an error.
+///
+/// # Errors
+///
+/// Returns an error if the directory cannot be created, `path` holds a file
+/// that is not a socket, or the socket cannot be bound.
+#[cfg(unix)]
+pub fn bind_unix_socket(path: &Path) -> Result<UnixListener> {
+    use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Unable to create {}...", parent.display()))?;
+    }
+    if let Ok(metadata) = std::fs::symlink_metadata(path) {
+        anyhow::ensure!(
+            metadata.file_type().is_socket(),
+            "{} exists and is not a socket",
+            path.display()
+        );
+        std::fs::remove_file(path)?;
+    }
+    let listener = UnixListener::bind(path)
+        .with_context(|| format!("Unable to obtain a Unix socket at {}...", path.display()))?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o660))?;
+    Ok(listener)
+}
+
+/// Connects to the configured database and runs its migrations.
+///
+/// Used by [`Application::build`] and by the offline `migrate` and
+/// `rebuild-bloom` commands of the binary.
+///
+/// # Errors
+///
+/// Returns an error if the database cannot be reached or a migration fails.
+pub async fn connect_database(settings: &DatabaseSettings) -> Result<Arc<dyn UrlDatabase>> {
+    let url_db: Arc<dyn UrlDatabase> = match settings.r#type {
+        DatabaseType::Sqlite => {
+            let db = SqliteUrlDatabase::from_config(settings).await?;
+            db.migrate().await?;
+            Arc::new(db)
+        }
+        DatabaseType::Postgres => {
+            let db = PostgresUrlDatabase::from_config(settings).await?;
+            db.migrate().await?;
+            Arc::new(db)
+        }
+    };
+    Ok(url_db)
+}
+
 /// Builds and configures the application router with all routes and middleware.
 ///
 /// This function creates the complete Axum router with all configured routes,
@@ -436,9 +697,20 @@ impl Application {
 /// # Middleware Stack
 ///
 /// The following middleware layers are applied in order:
-/// 1. **Request ID** - Generates unique identifiers for each request
-/// 2. **Tracing** - Logs request/response information
-/// 3. **API Key Authentication** - For protected endpoints only
+/// 1. **Real IP** - Resolves the client IP, trusting forwarding headers only from `application.trusted_proxies`
+/// 2. **Metrics** - Request counts and latency histograms (`metrics` feature only)
+/// 3. **Request ID** - Generates unique identifiers for each request
+/// 4. **Sentry** - A Sentry hub per request, tracing it as a transaction when `sentry.enabled` is set
+/// 5. **Tracing** - Logs request/response information
+/// 6. **Access Log** - One event per request when `access_log.enabled` is set
+/// 7. **Security Headers** - `X-Frame-Options`, `X-Content-Type-Options`, `Referrer-Policy` and CSP
+/// 8. **HTML Error Pages** - 404 and 5xx errors as Tera-rendered pages for browsers
+/// 9. **Problem Details** - Errors as `application/problem+json` for clients that ask for it
+/// 10. **Request ID in Errors** - Copies `x-request-id` into JSON error bodies
+/// 11. **Timeout** - Answers requests still running after `application.request_timeout_ms` with a 504
+/// 12. **Compression** - Brotli/gzip for responses above `compression.min_size_bytes`
+/// 13. **CORS** - Configured origins for the API, same-origin only for the admin panel
+/// 14. **API Key Authentication** - For protected endpoints only
 ///
 /// # Arguments
 ///
@@ -467,6 +739,7 @@ impl Application {
 ///     create_if_missing: true,
 ///     max_connections: Some(16),
 ///     min_connections: Some(4),
+///     busy_timeout_ms: None,
 /// };
 /// let database = Arc::new(SqliteUrlDatabase::from_config(&config).await?);
 /// let api_key = Uuid::new_v4();
@@ -479,20 +752,8 @@ impl Application {
 /// ```
 pub async fn build_router(state: AppState) -> Result<Router<AppState>, anyhow::Error> {
     // Define the tracing layer for request/response logging
-    crate::templates::build_templates(state.clone()).context("Failed to build templates")?;
     let trace_layer = TraceLayer::new_for_http()
-        .make_span_with(|req: &Request<_>| {
-            let ua = req
-                .headers()
-                .get("user-agent")
-                .and_then(|v| v.to_str().ok())
-                .unwrap_or("-");
-            tracing::info_span!("http",
-                method = %req.method(),
-                uri = %req.uri(),
-                user_agent = %ua,
-            )
-        })
+        .make_span_with(make_http_span)
         .on_request(|req: &Request<_>, _span: &Span| {
             tracing::info!(
                 "\nrequest:\n  method: {}\n  uri: {}\n  headers:\n{:#?}",
@@ -513,10 +774,22 @@ pub async fn build_router(state: AppState) -> Result<Router<AppState>, anyhow::E
     let x_request_id = HeaderName::from_static("x-request-id");
 
     // Create rate limiting configuration if enabled
-    let rate_limit_layer = if state.config.rate_limiting.enabled {
+    let rate_limiting = &state.config().rate_limiting;
+    let limit_in_memory =
+        rate_limiting.enabled && rate_limiting.backend == RateLimitBackend::Memory;
+    // Each route group gets its own limiter, so exhausting one leaves the
+    // others untouched
+    let rate_limit_layer = |endpoint: &str| -> Result<_> {
+        if !limit_in_memory {
+            return Ok(None);
+        }
+        let limit = rate_limiting.limit_for(endpoint);
+
+        // Keyed on the resolved client IP, not the proxy's address
         let governor_conf = GovernorConfigBuilder::default()
-            .per_second(state.config.rate_limiting.requests_per_second)
-            .burst_size(state.config.rate_limiting.burst_size)
+            .key_extractor(RealIpKeyExtractor)
+            .per_second(limit.requests_per_second)
+            .burst_size(limit.burst_size)
             .use_headers()
             .finish()
             .context("Failed to create rate limiting configuration")?;
@@ -533,36 +806,152 @@ pub async fn build_router(state: AppState) -> Result<Router<AppState>, anyhow::E
             }
         });
 
-        Some(GovernorLayer::new(governor_conf))
+        Ok(Some(
+            GovernorLayer::new(governor_conf).error_handler(rate_limit_error),
+        ))
+    };
+
+    // Counters shared with the other instances through Redis
+    #[cfg(feature = "redis")]
+    let redis_rate_limit_layer = if rate_limiting.enabled && !limit_in_memory {
+        Some(crate::middleware::RedisRateLimiter::connect(rate_limiting).await?)
     } else {
         None
     };
 
+    let cors_layer = build_cors_layer(&state.config().cors)?;
+
+    // Redirects get their own, typically far higher, limit. CORS only wraps
+    // GET and HEAD, as it would answer every OPTIONS request itself
+    let redirect_methods = || {
+        get(get_redirect)
+            .head(get_redirect_head)
+            .layer(cors_layer.clone())
+            .options(options_redirect)
+    };
+    let mut redirects = Router::new()
+        .route("/{id}", redirect_methods())
+        .route("/api/redirect/{id}", redirect_methods());
+    if let Some(rate_layer) = rate_limit_layer("redirect")? {
+        redirects = redirects.layer(rate_layer);
+    }
+
+    // Every passphrase guess costs an Argon2 verification, so unlocking gets
+    // its own limiter rather than none at all
+    let mut unlock = Router::new().route("/{id}/unlock", post(post_unlock));
+    if let Some(rate_layer) = rate_limit_layer("unlock")? {
+        unlock = unlock.layer(rate_layer);
+    }
+    #[cfg(feature = "redis")]
+    if let Some(rate_layer) = redis_rate_limit_layer.clone() {
+        unlock = unlock.layer(rate_layer);
+    }
+    unlock = unlock.layer(cors_layer.clone());
+
     // Build public routes (no authentication required)
     let public_routes = Router::new()
         .route("/", get(get_index))
-        .nest_service("/static", ServeDir::new("static"))
+        .nest_service("/static", ServeDir::new(STATIC_DIR))
         .route("/api/docs/openapi.yaml", get(serve_openapi_spec))
+        .route("/api/docs/openapi.json", get(serve_openapi_json))
         .route("/api/docs", get(serve_swagger_ui))
-        .route("/{id}", get(get_redirect))
+        .route("/{id}/preview", get(get_redirect_preview))
         .route("/api/health_check", get(health_check))
-        .route("/api/redirect/{id}", get(get_redirect));
+        .route("/api/ready", get(readiness_check))
+        .route("/api/urls/{id}", get(get_url_info).head(head_url_info))
+        .route("/api/qr/{id}", get(get_qr_code))
+        .route("/api/urls/{id}/qr.{format}", get(get_qr_image))
+        .layer(cors_layer.clone())
+        .merge(redirects)
+        .merge(unlock);
+
+    // Namespace for endpoints whose changes would break v1 clients; the
+    // existing routes stay where they are
+    let api_v2 = Router::new()
+        .route("/health_check", get(health_check))
+        .layer(cors_layer.clone());
+
+    #[cfg(feature = "metrics")]
+    let public_routes = public_routes.route("/metrics", get(crate::routes::get_metrics));
+
+    #[cfg(any(test, feature = "testing"))]
+    let public_routes =
+        public_routes.route("/api/debug/panic", get(crate::routes::get_debug_panic));
+
+    // Shorten bodies are capped well below axum's 2 MB default; oversized ones are
+    // rejected with 413 before being read in full
+    let shorten_body_limit = DefaultBodyLimit::max(state.config().application.max_body_bytes);
 
     // Build public rate-limited shorten endpoint
-    let mut public_shorten = Router::new().route("/api/public/shorten", post(post_shorten));
+    let mut public_shorten = Router::new().route(
+        "/api/public/shorten",
+        post(post_shorten).layer(shorten_body_limit),
+    );
 
-    if let Some(rate_layer) = rate_limit_layer.clone() {
+    if let Some(rate_layer) = rate_limit_layer("public_shorten")? {
         public_shorten = public_shorten.layer(rate_layer);
     }
+    #[cfg(feature = "redis")]
+    if let Some(rate_layer) = redis_rate_limit_layer.clone() {
+        public_shorten = public_shorten.layer(rate_layer);
+    }
+    // Outermost, so preflight requests are answered without using up the rate limit
+    public_shorten = public_shorten.layer(cors_layer.clone());
+
+    // Shortening, and managing the URLs they own, is open to signed-in users too
+    let owned_urls = Router::new()
+        .route("/api/shorten", post(post_shorten).layer(shorten_body_limit))
+        .route("/api/urls", get(list_urls))
+        .route("/api/urls/{id}", delete(delete_url).patch(patch_url))
+        .route("/api/urls/{id}/stats", get(get_url_stats))
+        .route("/api/urls/{id}/clicks", get(get_url_clicks))
+        .route_layer(from_fn_with_state(state.clone(), check_api_key_or_user));
 
     // Build protected API routes (requires API key)
     let mut protected_api = Router::new()
-        .route("/api/shorten", post(post_shorten))
-        .route_layer(from_fn_with_state(state.clone(), check_api_key));
+        .route(
+            "/api/shorten/batch",
+            post(post_shorten_batch).layer(shorten_body_limit),
+        )
+        .route("/api/urls/search", get(search_urls))
+        .route("/api/urls/export", get(export_urls))
+        .route(
+            "/api/urls/import",
+            post(import_urls).layer(DefaultBodyLimit::max(MAX_IMPORT_BODY_BYTES)),
+        )
+        .route("/api/urls/{id}/preview", get(get_url_preview))
+        .route("/api/urls/{id}/tags", post(post_url_tag))
+        .route("/api/urls/{id}/tags/{tag}", delete(delete_url_tag))
+        .route("/api/v1/auth/api-keys", get(list_api_keys))
+        .route("/api/v1/admin/config", get(get_admin_config))
+        .route_layer(from_fn_with_state(state.clone(), check_api_key))
+        .merge(owned_urls);
 
-    if let Some(rate_layer) = rate_limit_layer {
+    if let Some(rate_layer) = rate_limit_layer("shorten")? {
+        protected_api = protected_api.layer(rate_layer);
+    }
+    #[cfg(feature = "redis")]
+    if let Some(rate_layer) = redis_rate_limit_layer {
         protected_api = protected_api.layer(rate_layer);
     }
+    protected_api = protected_api.layer(cors_layer);
+
+    // Operator endpoints and URL management, open to API keys and to the
+    // browser sessions of admin accounts. `require_admin` is added first so it
+    // runs after `check_api_key_or_user` has recorded the caller
+    let admin_ops = Router::new()
+        .route("/admin/stats", get(get_admin_stats))
+        .route("/admin/bloom/rebuild", post(post_rebuild_bloom))
+        .route("/admin/urls", get(get_admin_urls))
+        .route("/admin/urls/{id}/delete", post(post_admin_delete_url))
+        .route("/admin/urls/{id}/analytics", get(get_admin_url_analytics))
+        .route_layer(from_fn_with_state(state.clone(), require_admin))
+        .route_layer(from_fn_with_state(state.clone(), check_api_key_or_user));
+
+    // The shorten form page, for every caller who may shorten URLs
+    let shorten_page = Router::new()
+        .route("/admin/shorten", get(get_admin_shorten))
+        .route_layer(from_fn_with_state(state.clone(), check_api_key_or_user));
 
     // Build protected admin routes (requires API key)
     let protected_admin = Router::new()
@@ -571,36 +960,168 @@ pub async fn build_router(state: AppState) -> Result<Router<AppState>, anyhow::E
         .route("/admin/login", get(get_login))
         .route("/admin/register", get(get_register))
         .route("/admin/users", get(get_users))
-        .route("/admin/urls", get(get_urls))
-        .route("/admin/analytics", get(get_analytics));
+        .route("/admin/analytics", get(get_analytics))
+        .merge(admin_ops)
+        .merge(shorten_page)
+        // No allowed origins: the admin panel is only usable same-origin
+        .layer(CorsLayer::new());
     // TODO: Add session-based auth middleware once implemented
 
+    // Callbacks from other services, authenticated by their HMAC signature
+    let webhook_callbacks = Router::new()
+        .route("/api/webhooks/receive", post(post_webhook_receive))
+        .route_layer(from_fn_with_state(state.clone(), verify_webhook_auth));
+
     // Merge all routes together
     let mut router = Router::new()
         .merge(public_routes)
         .merge(public_shorten)
         .merge(protected_api)
         .merge(protected_admin)
-        .layer(
-            ServiceBuilder::new()
-                .layer(SetRequestIdLayer::new(
-                    x_request_id.clone(),
-                    MakeRequestUuid,
-                ))
-                .layer(trace_layer)
-                .layer(PropagateRequestIdLayer::new(x_request_id)),
-        );
+        .merge(webhook_callbacks)
+        .nest("/api/v2", api_v2)
+        .fallback(page_not_found);
 
-    if matches!(state.config.database.r#type, DatabaseType::Postgres) {
-        router = router
+    // Merged before the layers below, which only wrap the routes that exist
+    // when they are added
+    if matches!(state.config().database.r#type, DatabaseType::Postgres) {
+        let accounts = Router::new()
             .nest("/api/v1/auth", auth::router())
             .nest("/api/v1/user", users::router())
             .layer(from_fn(capture_client_meta));
+        router = router.merge(accounts);
+    }
+
+    // Inside the trace layer, so the logged response sizes are the compressed ones
+    if state.config().compression.enabled {
+        router = router.layer(build_compression_layer(&state.config().compression));
     }
 
+    router = router.layer(
+        ServiceBuilder::new()
+            .layer(SetRequestIdLayer::new(
+                x_request_id.clone(),
+                MakeRequestUuid,
+            ))
+            // A Sentry hub per request, so scopes do not leak between them
+            .layer(NewSentryLayer::<Request<Body>>::new_from_top())
+            .layer(SentryHttpLayer::new().enable_transaction())
+            .layer(trace_layer)
+            .option_layer(
+                state
+                    .config()
+                    .access_log
+                    .enabled
+                    .then(|| from_fn_with_state(state.config().access_log.format, log_access)),
+            )
+            .layer(from_fn_with_state(state.clone(), set_security_headers))
+            .layer(from_fn_with_state(state.clone(), render_html_errors))
+            .layer(from_fn_with_state(state.clone(), negotiate_problem_details))
+            .layer(from_fn(attach_request_id))
+            .layer(from_fn(check_accept_version))
+            // Inside the trace layer, so timed-out requests are still logged
+            .layer(from_fn_with_state(state.clone(), enforce_request_timeout))
+            .layer(PropagateRequestIdLayer::new(x_request_id)),
+    );
+
+    // Around every route, including the nested auth routes, so all requests are counted
+    #[cfg(feature = "metrics")]
+    {
+        router = router.layer(from_fn(crate::telemetry::metrics::track_http_metrics));
+    }
+
+    // Outermost, so the rate limiter and every handler see the resolved client IP
+    router = router.layer(from_fn_with_state(state.clone(), extract_real_ip));
+    // Around everything, so a shutdown knows about every request it waits for
+    router = router.layer(from_fn_with_state(state.clone(), track_in_flight));
+    // Outermost of all, so a panic anywhere below is still answered with a 500
+    router = router.layer(CatchPanicLayer::custom(handle_panic));
+
     Ok(router)
 }
 
+/// Answers requests that match no route with [`ApiError::NotFound`], so they
+/// get the same JSON body, or error page, as any other 404.
+async fn page_not_found() -> ApiError {
+    ApiError::NotFound("Page not found".to_string())
+}
+
+/// Renders rate limiter rejections as [`ApiError::RateLimited`], keeping the
+/// `retry-after` and `x-ratelimit-*` headers added by the governor.
+fn rate_limit_error(error: GovernorError) -> Response<Body> {
+    match error {
+        GovernorError::TooManyRequests { wait_time, headers } => {
+            let mut response =
+                ApiError::RateLimited(format!("Too many requests, retry in {}s", wait_time))
+                    .into_response();
+            if let Some(headers) = headers {
+                response.headers_mut().extend(headers);
+            }
+            response
+        }
+        other => other.into_response().map(Body::from),
+    }
+}
+
+/// Builds the CORS layer for the API routes from the `cors` settings.
+///
+/// `"*"` in `allowed_origins` allows any origin; otherwise only the listed
+/// origins receive CORS headers.
+fn build_cors_layer(cors: &CorsSettings) -> Result<CorsLayer, anyhow::Error> {
+    let origins = if cors.allows_any_origin() {
+        AllowOrigin::from(Any)
+    } else {
+        let list = cors
+            .allowed_origins
+            .iter()
+            .map(|o| HeaderValue::from_str(o))
+            .collect::<Result<Vec<_>, _>>()
+            .context("Invalid CORS origin")?;
+        AllowOrigin::list(list)
+    };
+
+    Ok(CorsLayer::new()
+        .allow_origin(origins)
+        .allow_methods([
+            Method::GET,
+            Method::POST,
+            Method::PATCH,
+            Method::DELETE,
+            Method::OPTIONS,
+        ])
+        .allow_headers([
+            header::CONTENT_TYPE,
+            header::AUTHORIZATION,
+            HeaderName::from_static("x-api-key"),
+        ])
+        .allow_credentials(cors.allow_credentials)
+        .max_age(TokioDuration::from_secs(cors.max_age_seconds.into())))
+}
+
+/// Builds the brotli/gzip compression layer from the `compression` settings.
+///
+/// Responses below `min_size_bytes`, redirects (which have no body) and images,
+/// gRPC and server-sent events are left uncompressed.
+fn build_compression_layer(
+    compression: &CompressionSettings,
+) -> CompressionLayer<impl Predicate + use<>> {
+    let min_size = u16::try_from(compression.min_size_bytes).unwrap_or(u16::MAX);
+    let predicate = SizeAbove::new(min_size)
+        .and(NotForContentType::GRPC)
+        .and(NotForContentType::IMAGES)
+        .and(NotForContentType::SSE)
+        .and(
+            |status: StatusCode, _: Version, _: &HeaderMap, _: &Extensions| {
+                !status.is_redirection()
+            },
+        );
+
+    CompressionLayer::new()
+        .br(true)
+        .gzip(true)
+        .compress_when(predicate)
+}
+
 pub fn build_allowed_chars(alphabet: Option<&str>) -> HashSet<char> {
     let mut set = HashSet::new();
     if let Some(alpha) = alphabet {
@@ -612,11 +1133,10 @@ pub fn build_allowed_chars(alphabet: Option<&str>) -> HashSet<char> {
     set
 }
 
-pub async fn build_services(
-    cfg: &Settings,
-    jwt: &JwtKeys,
-) -> Result<(Arc<AuthService>, Arc<UserService>), anyhow::Error> {
-    let email_service = EmailService::new(
+/// Builds the email service from `application.email_svc_api_key` and
+/// `application.email_svc_address`.
+pub fn build_email_service(cfg: &Settings) -> EmailService {
+    EmailService::new(
         cfg.application
             .email_svc_api_key
             .as_ref()
@@ -626,32 +1146,54 @@ pub async fn build_services(
             .email_svc_address
             .as_deref()
             .unwrap_or_default(),
-    );
+    )
+}
+
+pub async fn build_services(
+    cfg: &Settings,
+    jwt: &JwtKeys,
+    http: &reqwest::Client,
+) -> Result<(Arc<AuthService>, Arc<UserService>), anyhow::Error> {
+    let email_service = build_email_service(cfg);
 
     let (auth_svc, user_svc) = if matches!(cfg.database.r#type, DatabaseType::Postgres) {
         let db_pool = db::make_pools(&cfg.database).await?;
         let repos = db::make_repos(&db_pool).await;
         (
-            Arc::new(AuthService::new(
-                repos.users.clone(),
-                repos.auth.clone(),
-                jwt.clone(),
-                chrono::Duration::minutes(15),
-                cfg.application.pwd_pepper_b64.clone(),
-                email_service,
-            )),
+            Arc::new(
+                AuthService::new(
+                    repos.users.clone(),
+                    repos.auth.clone(),
+                    jwt.clone(),
+                    chrono::Duration::minutes(15),
+                    cfg.application.pwd_pepper_b64.clone(),
+                    cfg.argon2.clone(),
+                    email_service,
+                )
+                .with_oauth(cfg.oauth.clone())
+                .with_http_client(http.clone())
+                .with_lockout(cfg.lockout.clone())
+                .with_verification(cfg.auth.clone()),
+            ),
             Arc::new(UserService::new(repos.users.clone())),
         )
     } else {
         (
-            Arc::new(AuthService::new(
-                Arc::new(NoopUserRepo),
-                Arc::new(NoopAuthRepo),
-                jwt.clone(),
-                chrono::Duration::minutes(15),
-                cfg.application.pwd_pepper_b64.clone(),
-                email_service,
-            )),
+            Arc::new(
+                AuthService::new(
+                    Arc::new(NoopUserRepo),
+                    Arc::new(NoopAuthRepo),
+                    jwt.clone(),
+                    chrono::Duration::minutes(15),
+                    cfg.application.pwd_pepper_b64.clone(),
+                    cfg.argon2.clone(),
+                    email_service,
+                )
+                .with_oauth(cfg.oauth.clone())
+                .with_http_client(http.clone())
+                .with_lockout(cfg.lockout.clone())
+                .with_verification(cfg.auth.clone()),
+            ),
             Arc::new(UserService::new(Arc::new(NoopUserRepo))),
         )
     };
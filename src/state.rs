@@ -8,7 +8,7 @@
 //!
 //! The application state includes:
 //! - Database connection for URL storage and retrieval
-//! - API key for authentication
+//! - API keys for authentication
 //! - Template directory path for web interface rendering
 //!
 //! ## Thread Safety
@@ -25,23 +25,39 @@
 //! use url_shortener_ztm_lib::state::AppState;
 //!
 //! async fn handler(State(state): State<AppState>) -> String {
-//!     // Access database, API key, and template directory
-//!     format!("API key: {}", state.api_key)
+//!     // Access database, API keys, and template directory
+//!     format!("API keys: {}", state.api_keys().len())
 //! }
 //! ```
 
 use crate::configuration::Settings;
+use crate::core::security::api_key::ApiKeyStore;
 use crate::core::security::jwt::JwtKeys;
 
 use crate::database::UrlDatabase;
 use crate::features::{auth::AuthService, users::UserService};
 
-use crate::generator::ShortCodeGenerator;
-use crate::shortcode::bloom_filter::BloomState;
+use crate::generator::{GeneratorMetrics, ShortCodeGenerator, build_generator_with_metrics};
+use crate::infrastructure::events::{EventPublisher, build_event_publisher};
+use crate::infrastructure::geo::{CountryLookup, MaxMindCountryLookup};
+use crate::infrastructure::http_client::build_http_client;
+use crate::infrastructure::safe_browsing::SafeBrowsing;
+use crate::routes::preview::{PreviewCache, build_preview_cache};
+use crate::routes::qr::{QrCache, build_qr_cache};
+use crate::routes::redirect::{RedirectCache, RedirectCacheCounters, build_redirect_cache};
+use crate::shortcode::bloom_filter::{BloomState, build_bloom_state};
+use crate::shortcode::snapshot_store::{BloomSnapshotStore, build_snapshot_store};
+use crate::startup::{build_allowed_chars, build_services};
+use crate::templates::TemplateState;
+use crate::validation::DomainFilter;
+use anyhow::Context;
 use axum_macros::FromRef;
+use secrecy::ExposeSecret;
 use std::collections::HashSet;
+use std::fmt;
 use std::sync::Arc;
-use uuid::Uuid;
+use std::time::Instant;
+use tokio::sync::Semaphore;
 
 /// Application state shared across all request handlers.
 ///
@@ -57,56 +73,472 @@ use uuid::Uuid;
 /// # Fields
 ///
 /// * `database` - Database connection for URL storage operations
-/// * `api_key` - UUID-based API key for authentication
-/// * `template_dir` - Directory path containing Tera template files
+/// * `api_keys` - UUID-based API keys accepted for authentication
+/// * `templates` - Compiled Tera templates for the web interface
+///
+/// # Construction
+///
+/// The fields are private; build the state with [`AppStateBuilder`] and read
+/// it through the accessor methods.
+///
+/// # Debug Output
+///
+/// `Debug` prints `"[REDACTED]"` in place of the API keys and JWT signing
+/// keys, and leaves out the services and caches.
+#[derive(Clone, FromRef)]
+pub struct AppState {
+    /// Database connection for URL storage and retrieval operations
+    database: Arc<dyn UrlDatabase>,
+    /// Short code generator for creating unique short URLs
+    code_generator: Arc<dyn ShortCodeGenerator>,
+    /// Usage counters of the code generator, when its engine tracks any
+    generator_metrics: Option<Arc<dyn GeneratorMetrics>>,
+    blooms: BloomState,
+    /// Where Bloom filter snapshots are kept, from `bloom.storage`
+    bloom_snapshot_store: Arc<dyn BloomSnapshotStore>,
+    /// The set of characters that can be used when generating short codes. \
+    /// Typically includes alphanumeric characters (e.g., `a-z`, `A-Z`, `0-9`).
+    allowed_chars: HashSet<char>,
+    /// Bytes of the UUID API keys accepted on protected endpoints, compared
+    /// in constant time by [`check_api_key`](crate::middleware::check_api_key)
+    api_keys: Arc<ApiKeyStore>,
+    /// Compiled Tera templates for the web interface
+    templates: TemplateState,
+    jwt: JwtKeys,
+    config: Settings,
+
+    auth_service: Arc<AuthService>,
+    user_service: Arc<UserService>,
+    /// Moment the state was built, used to report process uptime
+    uptime: Arc<Instant>,
+    /// One permit is held by every request being handled, see
+    /// [`track_in_flight`](crate::middleware::track_in_flight)
+    in_flight: Arc<Semaphore>,
+    /// Rendered QR codes keyed by code, format and size
+    qr_cache: Arc<QrCache>,
+    /// Redirect targets of recently followed short codes, keyed by code
+    redirect_cache: Arc<RedirectCache>,
+    /// Hits and misses of the redirect cache
+    redirect_cache_counters: Arc<RedirectCacheCounters>,
+    /// Open Graph previews of recently previewed destinations, keyed by URL
+    preview_cache: Arc<PreviewCache>,
+    /// Country lookup of click IPs, when `geolocation` is enabled
+    country_lookup: Option<Arc<dyn CountryLookup>>,
+    /// Domains that may be shortened, from `application.url_filter`
+    domain_filter: Arc<DomainFilter>,
+    /// Sends every outbound HTTP request, from `http_client`; clones share
+    /// one connection pool
+    http_client: reqwest::Client,
+    /// Safe Browsing lookups of destinations, when `safe_browsing` is enabled
+    safe_browsing: Option<Arc<SafeBrowsing>>,
+    /// Where click events go, from `events`
+    event_publisher: Arc<dyn EventPublisher>,
+    /// Renders the process-wide Prometheus metrics for `GET /metrics`
+    #[cfg(feature = "metrics")]
+    metrics: metrics_exporter_prometheus::PrometheusHandle,
+}
+
+impl fmt::Debug for AppState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AppState")
+            .field("api_keys", &"[REDACTED]")
+            .field("jwt", &"[REDACTED]")
+            .field("allowed_chars", &self.allowed_chars.len())
+            .field("safe_browsing", &self.safe_browsing.is_some())
+            .field("country_lookup", &self.country_lookup.is_some())
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AppState {
+    /// Database connection for URL storage and retrieval operations
+    pub fn database(&self) -> &Arc<dyn UrlDatabase> {
+        &self.database
+    }
+
+    /// Short code generator for creating unique short URLs
+    pub fn code_generator(&self) -> &Arc<dyn ShortCodeGenerator> {
+        &self.code_generator
+    }
+
+    /// Usage counters of the code generator, when its engine tracks any
+    pub fn generator_metrics(&self) -> Option<&Arc<dyn GeneratorMetrics>> {
+        self.generator_metrics.as_ref()
+    }
+
+    /// Bloom filters of the stored short codes
+    pub fn blooms(&self) -> &BloomState {
+        &self.blooms
+    }
+
+    /// Where Bloom filter snapshots are kept, from `bloom.storage`
+    pub fn bloom_snapshot_store(&self) -> &Arc<dyn BloomSnapshotStore> {
+        &self.bloom_snapshot_store
+    }
+
+    /// Characters a short code or alias may contain
+    pub fn allowed_chars(&self) -> &HashSet<char> {
+        &self.allowed_chars
+    }
+
+    /// API keys accepted on protected endpoints
+    pub fn api_keys(&self) -> &Arc<ApiKeyStore> {
+        &self.api_keys
+    }
+
+    /// Compiled Tera templates for the web interface
+    pub fn templates(&self) -> &TemplateState {
+        &self.templates
+    }
+
+    /// Keys signing and verifying access tokens
+    pub fn jwt(&self) -> &JwtKeys {
+        &self.jwt
+    }
+
+    /// Settings the state was built from
+    pub fn config(&self) -> &Settings {
+        &self.config
+    }
+
+    pub fn auth_service(&self) -> &Arc<AuthService> {
+        &self.auth_service
+    }
+
+    pub fn user_service(&self) -> &Arc<UserService> {
+        &self.user_service
+    }
+
+    /// Moment the state was built, used to report process uptime
+    pub fn uptime(&self) -> Instant {
+        *self.uptime
+    }
+
+    /// Permits held by the requests being handled, one each
+    pub fn in_flight(&self) -> &Arc<Semaphore> {
+        &self.in_flight
+    }
+
+    /// How many requests are being handled right now
+    pub fn in_flight_requests(&self) -> usize {
+        Semaphore::MAX_PERMITS - self.in_flight.available_permits()
+    }
+
+    /// Rendered QR codes keyed by code, format and size
+    pub fn qr_cache(&self) -> &Arc<QrCache> {
+        &self.qr_cache
+    }
+
+    /// Redirect targets of recently followed short codes, keyed by code
+    pub fn redirect_cache(&self) -> &Arc<RedirectCache> {
+        &self.redirect_cache
+    }
+
+    /// Hits and misses of the redirect cache
+    pub fn redirect_cache_counters(&self) -> &Arc<RedirectCacheCounters> {
+        &self.redirect_cache_counters
+    }
+
+    /// Open Graph previews of recently previewed destinations, keyed by URL
+    pub fn preview_cache(&self) -> &Arc<PreviewCache> {
+        &self.preview_cache
+    }
+
+    /// Country lookup of click IPs, when `geolocation` is enabled
+    pub fn country_lookup(&self) -> Option<&Arc<dyn CountryLookup>> {
+        self.country_lookup.as_ref()
+    }
+
+    /// Domains that may be shortened, from `application.url_filter`
+    pub fn domain_filter(&self) -> &DomainFilter {
+        &self.domain_filter
+    }
+
+    /// Sends every outbound HTTP request, from `http_client`; clones share
+    /// one connection pool
+    pub fn http_client(&self) -> &reqwest::Client {
+        &self.http_client
+    }
+
+    /// Safe Browsing lookups of destinations, when `safe_browsing` is enabled
+    pub fn safe_browsing(&self) -> Option<&SafeBrowsing> {
+        self.safe_browsing.as_deref()
+    }
+
+    /// Where click events go, from `events`
+    pub fn event_publisher(&self) -> &Arc<dyn EventPublisher> {
+        &self.event_publisher
+    }
+
+    /// Renders the process-wide Prometheus metrics for `GET /metrics`
+    #[cfg(feature = "metrics")]
+    pub fn metrics(&self) -> &metrics_exporter_prometheus::PrometheusHandle {
+        &self.metrics
+    }
+}
+
+/// Builds an [`AppState`], deriving from the configuration whatever is not
+/// given explicitly.
+///
+/// Only [`config`](Self::config) and [`database`](Self::database) are
+/// required. Everything else defaults to what
+/// [`Application::build`](crate::startup::Application::build) would use:
+///
+/// * the code generator and its metrics come from the `shortener` settings
+/// * `allowed_chars` is the configured alphabet, or
+///   [`DEFAULT_ALPHABET`](crate::generator::DEFAULT_ALPHABET)
+/// * the Bloom filters are loaded from the database
+/// * API keys, templates and JWT keys come from the application settings
+/// * the auth and user services come from
+///   [`build_services`](crate::startup::build_services)
 ///
 /// # Examples
 ///
 /// ```rust,no_run
-/// use url_shortener_ztm_lib::state::AppState;
-/// use url_shortener_ztm_lib::DatabaseType;
 /// use url_shortener_ztm_lib::database::SqliteUrlDatabase;
-/// use url_shortener_ztm_lib::configuration::DatabaseSettings;
+/// use url_shortener_ztm_lib::get_configuration;
+/// use url_shortener_ztm_lib::state::AppStateBuilder;
 /// use std::sync::Arc;
-/// use uuid::Uuid;
 ///
 /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
-/// let config = DatabaseSettings {
-///     r#type: DatabaseType::Sqlite,
-///     url: "database.db".to_string(),
-///     create_if_missing: true,
-///     max_connections: Some(16),
-///     min_connections: Some(4),
-/// };
-/// let database = Arc::new(SqliteUrlDatabase::from_config(&config).await?);
-/// let api_key = Uuid::new_v4();
-/// let template_dir = "templates".to_string();
-/// // let settings = get_configuration().expect("Failed to read configuration");
-///
-/// // let state = AppState::new(database, api_key, template_dir, settings);
+/// let config = get_configuration()?;
+/// let database = SqliteUrlDatabase::in_memory_with_migrations().await?;
+/// let state = AppStateBuilder::new()
+///     .config(config)
+///     .database(Arc::new(database))
+///     .build()
+///     .await?;
+/// println!("API keys: {}", state.api_keys().len());
 /// # Ok(())
 /// # }
 /// ```
-#[derive(Clone, FromRef)]
-pub struct AppState {
-    /// Database connection for URL storage and retrieval operations
-    pub database: Arc<dyn UrlDatabase>,
-    /// Short code generator for creating unique short URLs
-    pub code_generator: Arc<dyn ShortCodeGenerator>,
-    pub blooms: BloomState,
-    /// The set of characters that can be used when generating short codes. \
-    /// Typically includes alphanumeric characters (e.g., `a-z`, `A-Z`, `0-9`).
-    pub allowed_chars: HashSet<char>,
-    /// UUID-based API key for authenticating protected endpoints
-    pub api_key: Uuid,
-    /// Directory path containing Tera template files for web interface
-    pub template_dir: String,
-    pub jwt: JwtKeys,
-    pub config: Settings,
-
-    // pub db_pool: Arc<db::DbPool>,
-    pub auth_service: Arc<AuthService>,
-    pub user_service: Arc<UserService>,
+#[derive(Default)]
+pub struct AppStateBuilder {
+    config: Option<Settings>,
+    database: Option<Arc<dyn UrlDatabase>>,
+    code_generator: Option<Arc<dyn ShortCodeGenerator>>,
+    generator_metrics: Option<Arc<dyn GeneratorMetrics>>,
+    blooms: Option<BloomState>,
+    bloom_snapshot_store: Option<Arc<dyn BloomSnapshotStore>>,
+    allowed_chars: Option<HashSet<char>>,
+    api_keys: Option<Arc<ApiKeyStore>>,
+    templates: Option<TemplateState>,
+    jwt: Option<JwtKeys>,
+    auth_service: Option<Arc<AuthService>>,
+    user_service: Option<Arc<UserService>>,
+    country_lookup: Option<Arc<dyn CountryLookup>>,
+    http_client: Option<reqwest::Client>,
+    safe_browsing: Option<Arc<SafeBrowsing>>,
+    event_publisher: Option<Arc<dyn EventPublisher>>,
 }
 
-impl AppState {}
+impl AppStateBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Settings the defaults are derived from; required
+    pub fn config(mut self, config: Settings) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Database backing the state; required
+    pub fn database(mut self, database: Arc<dyn UrlDatabase>) -> Self {
+        self.database = Some(database);
+        self
+    }
+
+    /// Uses `generator` instead of the configured one. Its metrics are not
+    /// reported unless given with [`generator_metrics`](Self::generator_metrics).
+    pub fn code_generator(mut self, generator: Arc<dyn ShortCodeGenerator>) -> Self {
+        self.code_generator = Some(generator);
+        self
+    }
+
+    pub fn generator_metrics(mut self, metrics: Arc<dyn GeneratorMetrics>) -> Self {
+        self.generator_metrics = Some(metrics);
+        self
+    }
+
+    pub fn blooms(mut self, blooms: BloomState) -> Self {
+        self.blooms = Some(blooms);
+        self
+    }
+
+    /// Uses `store` for Bloom snapshots instead of the `bloom.storage` backend
+    pub fn bloom_snapshot_store(mut self, store: Arc<dyn BloomSnapshotStore>) -> Self {
+        self.bloom_snapshot_store = Some(store);
+        self
+    }
+
+    pub fn allowed_chars(mut self, allowed_chars: HashSet<char>) -> Self {
+        self.allowed_chars = Some(allowed_chars);
+        self
+    }
+
+    pub fn api_keys(mut self, api_keys: Arc<ApiKeyStore>) -> Self {
+        self.api_keys = Some(api_keys);
+        self
+    }
+
+    pub fn templates(mut self, templates: TemplateState) -> Self {
+        self.templates = Some(templates);
+        self
+    }
+
+    pub fn jwt(mut self, jwt: JwtKeys) -> Self {
+        self.jwt = Some(jwt);
+        self
+    }
+
+    pub fn auth_service(mut self, auth_service: Arc<AuthService>) -> Self {
+        self.auth_service = Some(auth_service);
+        self
+    }
+
+    pub fn user_service(mut self, user_service: Arc<UserService>) -> Self {
+        self.user_service = Some(user_service);
+        self
+    }
+
+    /// Uses `lookup` instead of opening `geolocation.db_path`; only consulted
+    /// when `geolocation.enabled` is set
+    pub fn country_lookup(mut self, lookup: Arc<dyn CountryLookup>) -> Self {
+        self.country_lookup = Some(lookup);
+        self
+    }
+
+    /// Uses `client` for outbound HTTP requests instead of one built from
+    /// the `http_client` settings
+    pub fn http_client(mut self, client: reqwest::Client) -> Self {
+        self.http_client = Some(client);
+        self
+    }
+
+    /// Uses `safe_browsing` instead of one built from the `safe_browsing`
+    /// settings; only consulted when `safe_browsing.enabled` is set
+    pub fn safe_browsing(mut self, safe_browsing: Arc<SafeBrowsing>) -> Self {
+        self.safe_browsing = Some(safe_browsing);
+        self
+    }
+
+    /// Publishes click events with `publisher` instead of the `events` backend
+    pub fn event_publisher(mut self, publisher: Arc<dyn EventPublisher>) -> Self {
+        self.event_publisher = Some(publisher);
+        self
+    }
+
+    /// Builds the state, filling in the defaults.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the configuration or database was not given, or
+    /// if the Bloom filters, templates, services, GeoIP database, URL
+    /// domain filter, outbound HTTP client or click event publisher cannot
+    /// be built.
+    #[must_use = "the built state should be handed to the router"]
+    pub async fn build(self) -> Result<AppState, anyhow::Error> {
+        let config = self
+            .config
+            .context("AppStateBuilder needs the configuration")?;
+        let database = self.database.context("AppStateBuilder needs a database")?;
+
+        let (code_generator, generator_metrics) = match self.code_generator {
+            Some(generator) => (generator, self.generator_metrics),
+            None => {
+                let (generator, metrics) = build_generator_with_metrics(&config.shortener);
+                (generator, self.generator_metrics.or(metrics))
+            }
+        };
+        let allowed_chars = self
+            .allowed_chars
+            .unwrap_or_else(|| build_allowed_chars(config.shortener.alphabet.as_deref()));
+        let bloom_snapshot_store = match self.bloom_snapshot_store {
+            Some(store) => store,
+            None => build_snapshot_store(&config.bloom.storage, &database).await?,
+        };
+        let blooms = match self.blooms {
+            Some(blooms) => blooms,
+            None => build_bloom_state(&database, &bloom_snapshot_store, &config.bloom).await?,
+        };
+        let api_keys = self
+            .api_keys
+            .unwrap_or_else(|| Arc::new(ApiKeyStore::new(&config.application.api_keys)));
+        let templates = match self.templates {
+            Some(templates) => templates,
+            None => TemplateState::load(&config.templates).context("Failed to build templates")?,
+        };
+        let http_client = match self.http_client {
+            Some(client) => client,
+            None => build_http_client(&config.http_client)
+                .context("Failed to build the outbound HTTP client")?,
+        };
+        let jwt = self.jwt.unwrap_or_else(|| {
+            JwtKeys::new(config.application.jwt_secret_b64.expose_secret().as_bytes())
+        });
+        let (auth_service, user_service) = match (self.auth_service, self.user_service) {
+            (Some(auth), Some(user)) => (auth, user),
+            (auth, user) => {
+                let (default_auth, default_user) =
+                    build_services(&config, &jwt, &http_client).await?;
+                (auth.unwrap_or(default_auth), user.unwrap_or(default_user))
+            }
+        };
+
+        let country_lookup = match self.country_lookup {
+            _ if !config.geolocation.enabled => None,
+            Some(lookup) => Some(lookup),
+            None => Some(
+                Arc::new(MaxMindCountryLookup::open(&config.geolocation.db_path)?)
+                    as Arc<dyn CountryLookup>,
+            ),
+        };
+
+        let domain_filter = DomainFilter::new(&config.application.url_filter)
+            .context("Invalid application.url_filter.domains")?;
+        let safe_browsing = match self.safe_browsing {
+            _ if !config.safe_browsing.enabled => None,
+            Some(safe_browsing) => Some(safe_browsing),
+            None => Some(Arc::new(SafeBrowsing::new(
+                &config.safe_browsing,
+                http_client.clone(),
+            ))),
+        };
+        let event_publisher = match self.event_publisher {
+            Some(publisher) => publisher,
+            None => build_event_publisher(&config.events)
+                .await
+                .context("Failed to connect the click event publisher")?,
+        };
+
+        Ok(AppState {
+            redirect_cache: build_redirect_cache(&config.cache),
+            redirect_cache_counters: Arc::new(RedirectCacheCounters::default()),
+            database,
+            code_generator,
+            generator_metrics,
+            blooms,
+            bloom_snapshot_store,
+            allowed_chars,
+            api_keys,
+            templates,
+            jwt,
+            config,
+            auth_service,
+            user_service,
+            uptime: Arc::new(Instant::now()),
+            in_flight: Arc::new(Semaphore::new(Semaphore::MAX_PERMITS)),
+            qr_cache: build_qr_cache(),
+            preview_cache: build_preview_cache(),
+            country_lookup,
+            domain_filter: Arc::new(domain_filter),
+            http_client,
+            safe_browsing,
+            event_publisher,
+            #[cfg(feature = "metrics")]
+            metrics: crate::telemetry::metrics::handle(),
+        })
+    }
+}
@@ -0,0 +1,157 @@
+//! # Access Log
+//!
+//! One event per completed request, separate from the `http` spans of the
+//! trace layer, for log pipelines (ELK, Splunk) that expect an access log.
+//! Enabled with `access_log.enabled`; `access_log.format` picks the shape:
+//!
+//! - `json` - structured fields `method`, `path`, `status`, `duration_ms`,
+//!   `bytes_sent`, `remote_addr`, `user_agent` and `request_id`
+//! - `common` - an Apache Common Log Format line as the event message
+//!
+//! `bytes_sent` comes from `Content-Length`, so it is missing for streamed
+//! (e.g. compressed) responses.
+
+use crate::configuration::AccessLogFormat;
+use crate::middleware::RealIp;
+
+use axum::{
+    body::HttpBody,
+    extract::{Request, State},
+    http::header,
+    middleware::Next,
+    response::Response,
+};
+use std::time::Instant;
+use tower_http::request_id::RequestId;
+
+/// Middleware function that logs each request once its response is ready.
+///
+/// Must run inside `SetRequestIdLayer` and the real IP middleware to report
+/// `request_id` and `remote_addr`; either is logged as `-` otherwise.
+pub async fn log_access(
+    State(format): State<AccessLogFormat>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let started = Instant::now();
+    let method = request.method().clone();
+    let path = request.uri().path().to_owned();
+    let version = request.version();
+    let user_agent = request
+        .headers()
+        .get(header::USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("-")
+        .to_owned();
+    let remote_addr = request
+        .extensions()
+        .get::<RealIp>()
+        .map_or_else(|| "-".to_owned(), ToString::to_string);
+    let request_id = request
+        .extensions()
+        .get::<RequestId>()
+        .and_then(|id| id.header_value().to_str().ok())
+        .unwrap_or("-")
+        .to_owned();
+
+    let response = next.run(request).await;
+
+    let status = response.status().as_u16();
+    let duration_ms = started.elapsed().as_millis() as u64;
+    let bytes_sent = response
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok())
+        .or_else(|| response.body().size_hint().exact());
+
+    match format {
+        AccessLogFormat::Json => tracing::info!(
+            method = %method,
+            path,
+            status,
+            duration_ms,
+            bytes_sent,
+            remote_addr,
+            user_agent,
+            request_id,
+            "access"
+        ),
+        AccessLogFormat::Common => tracing::info!(
+            "{} - - [{}] \"{} {} {:?}\" {} {}",
+            remote_addr,
+            chrono::Utc::now().format("%d/%b/%Y:%H:%M:%S %z"),
+            method,
+            path,
+            version,
+            status,
+            bytes_sent.map_or_else(|| "-".to_owned(), |b| b.to_string())
+        ),
+    }
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, body::Body, middleware::from_fn_with_state, routing::get};
+    use tower::ServiceExt;
+    use tracing_test::traced_test;
+
+    fn app(format: AccessLogFormat) -> Router {
+        Router::new()
+            .route("/hello", get(|| async { "hello" }))
+            .layer(from_fn_with_state(format, log_access))
+    }
+
+    fn request() -> Request {
+        let mut request = Request::get("/hello")
+            .header(header::USER_AGENT, "curl/8.0")
+            .body(Body::empty())
+            .unwrap();
+        request
+            .extensions_mut()
+            .insert(RealIp("203.0.113.7".parse().unwrap()));
+        request
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn json_format_logs_one_event_with_every_field() {
+        app(AccessLogFormat::Json).oneshot(request()).await.unwrap();
+
+        logs_assert(|lines: &[&str]| {
+            let events: Vec<_> = lines.iter().filter(|l| l.contains("access")).collect();
+            if events.len() != 1 {
+                return Err(format!("expected one access event, got {:?}", events));
+            }
+            let fields = [
+                "method=GET",
+                "path=\"/hello\"",
+                "status=200",
+                "duration_ms=",
+                "bytes_sent=5",
+                "remote_addr=\"203.0.113.7\"",
+                "user_agent=\"curl/8.0\"",
+                "request_id=\"-\"",
+            ];
+            match fields.iter().find(|f| !events[0].contains(*f)) {
+                Some(missing) => Err(format!("{missing} missing from {}", events[0])),
+                None => Ok(()),
+            }
+        });
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn common_format_logs_a_common_log_line() {
+        app(AccessLogFormat::Common)
+            .oneshot(request())
+            .await
+            .unwrap();
+
+        assert!(logs_contain("203.0.113.7 - - ["));
+        assert!(logs_contain("\"GET /hello HTTP/1.1\" 200 5"));
+    }
+}
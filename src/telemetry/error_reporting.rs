@@ -0,0 +1,62 @@
+//! # Error Reporting
+//!
+//! Sends internal server errors to Sentry when `sentry.enabled` is set.
+//!
+//! [`init_error_reporting`] installs the Sentry client; the router wraps
+//! every request in its own Sentry hub, which
+//! [`attach_request_id`](crate::middleware::attach_request_id) tags with the
+//! request ID. [`ApiError::Internal`](crate::errors::ApiError::Internal)
+//! responses are then captured by [`report_internal_error`]. Without a
+//! client, as when reporting is disabled, every Sentry call does nothing.
+
+use crate::configuration::{Environment, SentrySettings};
+
+use secrecy::ExposeSecret;
+
+/// Installs the Sentry client described by `settings`, or does nothing when
+/// `sentry.enabled` is off.
+///
+/// Events are reported for as long as the returned guard is alive; dropping
+/// it flushes the ones still queued.
+pub fn init_error_reporting(
+    settings: &SentrySettings,
+    environment: &Environment,
+) -> Option<sentry::ClientInitGuard> {
+    if !settings.enabled {
+        return None;
+    }
+
+    let environment = match settings.environment.trim() {
+        "" => environment.as_str().to_string(),
+        name => name.to_string(),
+    };
+    let guard = sentry::init(sentry::ClientOptions {
+        // Checked by `Settings::validate`
+        dsn: settings.dsn.expose_secret().parse().ok(),
+        release: sentry::release_name!(),
+        environment: Some(environment.into()),
+        traces_sample_rate: settings.traces_sample_rate as f32,
+        ..Default::default()
+    });
+    tracing::info!("Sentry error reporting enabled");
+    Some(guard)
+}
+
+/// Reports the message of an internal server error to Sentry as an error
+/// event.
+pub fn report_internal_error(message: &str) {
+    sentry::capture_message(message, sentry::Level::Error);
+}
+
+/// Records the request ID on the current Sentry scope, as a tag and a
+/// breadcrumb, so events captured while handling the request carry it.
+pub fn record_request_id(request_id: &str) {
+    sentry::configure_scope(|scope| {
+        scope.set_tag("request_id", request_id);
+    });
+    sentry::add_breadcrumb(sentry::Breadcrumb {
+        category: Some("request".into()),
+        message: Some(format!("request_id {}", request_id)),
+        ..Default::default()
+    });
+}
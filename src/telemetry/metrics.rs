@@ -0,0 +1,498 @@
+//! # Prometheus Metrics
+//!
+//! Available with the `metrics` cargo feature. A Prometheus recorder is
+//! installed once per process and rendered in text format by `GET /metrics`.
+//!
+//! ## Recorded Metrics
+//!
+//! - `http_requests_total{method,route,status}` - Requests by matched route
+//! - `http_request_duration_seconds{method,route}` - Request latency histogram
+//! - `db_query_duration_seconds{operation}` - Latency histogram per [`UrlDatabase`] method
+//! - `short_codes_created_total` - Codes and aliases newly stored
+//! - `redirects_total{code}` - Successful redirects per short code
+//! - `bloom_filter_hits_total{filter,result}` - Bloom filter lookup outcomes
+//! - `bloom_filter_rebuilding` - `1` while `POST /admin/bloom/rebuild` runs
+
+use crate::database::{DatabaseError, UrlDatabase};
+use crate::models::{
+    AbTarget, BatchInsertResult, ClickBucket, ClickRecord, ClickSummary, CountryCount,
+    DatabaseStats, ExpiringUrl, ExportRecord, MaintenanceStep, ReferrerCount, UpsertResult,
+    UrlMetadata, UrlRecord, Urls,
+};
+use crate::shortcode::bloom_filter::BloomMetrics;
+
+use async_trait::async_trait;
+use axum::{
+    extract::{MatchedPath, Request},
+    middleware::Next,
+    response::Response,
+};
+use chrono::{DateTime, Utc};
+use metrics::{counter, gauge, histogram};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use std::sync::{Arc, OnceLock};
+use std::time::Instant;
+use uuid::Uuid;
+
+/// Histogram buckets in seconds, fine enough around typical latencies to
+/// estimate p50, p95 and p99.
+const DURATION_BUCKETS: &[f64] = &[
+    0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+static HANDLE: OnceLock<PrometheusHandle> = OnceLock::new();
+
+/// Returns the process-wide Prometheus handle, installing the recorder on
+/// first use.
+///
+/// # Panics
+///
+/// Panics if another `metrics` recorder was installed first.
+pub fn handle() -> PrometheusHandle {
+    HANDLE
+        .get_or_init(|| {
+            PrometheusBuilder::new()
+                .set_buckets_for_metric(
+                    Matcher::Suffix("_duration_seconds".to_string()),
+                    DURATION_BUCKETS,
+                )
+                .expect("duration buckets are not empty")
+                .install_recorder()
+                .expect("Failed to install the Prometheus recorder")
+        })
+        .clone()
+}
+
+/// Middleware function that counts requests and records their latency.
+///
+/// Requests are labelled with the matched route template (e.g. `/{id}`)
+/// rather than the raw path, so every short code shares one series.
+pub async fn track_http_metrics(request: Request, next: Next) -> Response {
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_owned())
+        .unwrap_or_else(|| "unmatched".to_owned());
+    let started = Instant::now();
+
+    let response = next.run(request).await;
+
+    histogram!(
+        "http_request_duration_seconds",
+        "method" => method.clone(),
+        "route" => route.clone()
+    )
+    .record(started.elapsed().as_secs_f64());
+    counter!(
+        "http_requests_total",
+        "method" => method,
+        "route" => route,
+        "status" => response.status().as_u16().to_string()
+    )
+    .increment(1);
+
+    response
+}
+
+/// Counts a successful redirect of `code`.
+pub fn record_redirect(code: &str) {
+    counter!("redirects_total", "code" => code.to_owned()).increment(1);
+}
+
+/// Counts a redirect answered from the redirect cache.
+pub fn record_cache_hit() {
+    counter!("cache_hits_total", "cache" => "redirect").increment(1);
+}
+
+/// Publishes the Bloom filter lookup counters kept in
+/// [`BloomState`](crate::shortcode::bloom_filter::BloomState).
+pub fn record_bloom_metrics(metrics: &BloomMetrics) {
+    let outcomes = [
+        ("true_positive", metrics.s2l_hits),
+        ("false_positive", metrics.s2l_false_positives),
+        ("miss", metrics.s2l_misses),
+    ];
+    for (result, value) in outcomes {
+        counter!("bloom_filter_hits_total", "filter" => "s2l", "result" => result).absolute(value);
+    }
+}
+
+/// Sets the `bloom_filter_rebuilding` gauge while the Bloom filter is rebuilt.
+pub fn record_bloom_rebuilding(rebuilding: bool) {
+    gauge!("bloom_filter_rebuilding").set(if rebuilding { 1.0 } else { 0.0 });
+}
+
+/// [`UrlDatabase`] wrapper that records how long each query takes, and counts
+/// short codes as they are created.
+pub struct MeteredDatabase {
+    inner: Arc<dyn UrlDatabase>,
+}
+
+impl MeteredDatabase {
+    pub fn wrap(inner: Arc<dyn UrlDatabase>) -> Arc<dyn UrlDatabase> {
+        Arc::new(Self { inner })
+    }
+}
+
+async fn timed<T>(operation: &'static str, query: impl Future<Output = T>) -> T {
+    let started = Instant::now();
+    let result = query.await;
+    histogram!("db_query_duration_seconds", "operation" => operation)
+        .record(started.elapsed().as_secs_f64());
+    result
+}
+
+fn count_created(created: usize) {
+    counter!("short_codes_created_total").increment(created as u64);
+}
+
+#[async_trait]
+impl UrlDatabase for MeteredDatabase {
+    async fn insert_url(
+        &self,
+        code: &str,
+        url: &str,
+    ) -> Result<(UpsertResult, Urls), DatabaseError> {
+        let result = timed("insert_url", self.inner.insert_url(code, url)).await;
+        if let Ok((upsert, _)) = &result {
+            count_created(usize::from(upsert.created));
+        }
+        result
+    }
+
+    async fn insert_url_with_expiry(
+        &self,
+        code: &str,
+        url: &str,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<(UpsertResult, Urls), DatabaseError> {
+        let result = timed(
+            "insert_url_with_expiry",
+            self.inner.insert_url_with_expiry(code, url, expires_at),
+        )
+        .await;
+        if let Ok((upsert, _)) = &result {
+            count_created(usize::from(upsert.created));
+        }
+        result
+    }
+
+    async fn insert_urls_batch(
+        &self,
+        items: &[(&str, &str)],
+    ) -> Result<Vec<BatchInsertResult>, DatabaseError> {
+        let result = timed("insert_urls_batch", self.inner.insert_urls_batch(items)).await;
+        if let Ok(rows) = &result {
+            count_created(rows.iter().filter(|row| row.created).count());
+        }
+        result
+    }
+
+    async fn insert_alias(&self, alias_code: &str, code_id: i64) -> Result<(), DatabaseError> {
+        let result = timed("insert_alias", self.inner.insert_alias(alias_code, code_id)).await;
+        if result.is_ok() {
+            count_created(1);
+        }
+        result
+    }
+
+    async fn get_id_by_url(&self, url: &str) -> Result<Urls, DatabaseError> {
+        timed("get_id_by_url", self.inner.get_id_by_url(url)).await
+    }
+
+    async fn get_url(&self, id: &str) -> Result<String, DatabaseError> {
+        timed("get_url", self.inner.get_url(id)).await
+    }
+
+    async fn get_url_with_metadata(&self, code: &str) -> Result<UrlMetadata, DatabaseError> {
+        timed(
+            "get_url_with_metadata",
+            self.inner.get_url_with_metadata(code),
+        )
+        .await
+    }
+
+    async fn delete_url(&self, code: &str) -> Result<(), DatabaseError> {
+        timed("delete_url", self.inner.delete_url(code)).await
+    }
+
+    async fn update_url(&self, code: &str, new_url: &str) -> Result<(), DatabaseError> {
+        timed("update_url", self.inner.update_url(code, new_url)).await
+    }
+
+    async fn record_preview_view(&self, code: &str) -> Result<(), DatabaseError> {
+        timed("record_preview_view", self.inner.record_preview_view(code)).await
+    }
+
+    async fn record_click(
+        &self,
+        code: &str,
+        clicked_at: DateTime<Utc>,
+        ip: Option<&str>,
+        referrer: Option<&str>,
+        variant: Option<u8>,
+        device_type: Option<&str>,
+        country_code: Option<&str>,
+        user_agent: Option<&str>,
+    ) -> Result<(), DatabaseError> {
+        timed(
+            "record_click",
+            self.inner.record_click(
+                code,
+                clicked_at,
+                ip,
+                referrer,
+                variant,
+                device_type,
+                country_code,
+                user_agent,
+            ),
+        )
+        .await
+    }
+
+    async fn get_recent_clicks(
+        &self,
+        code: &str,
+        limit: u8,
+    ) -> Result<Vec<ClickRecord>, DatabaseError> {
+        timed(
+            "get_recent_clicks",
+            self.inner.get_recent_clicks(code, limit),
+        )
+        .await
+    }
+
+    async fn get_clicks_over_time(
+        &self,
+        code: &str,
+        bucket: &str,
+    ) -> Result<Vec<ClickBucket>, DatabaseError> {
+        timed(
+            "get_clicks_over_time",
+            self.inner.get_clicks_over_time(code, bucket),
+        )
+        .await
+    }
+
+    async fn get_top_referrers(
+        &self,
+        code: &str,
+        limit: u8,
+    ) -> Result<Vec<ReferrerCount>, DatabaseError> {
+        timed(
+            "get_top_referrers",
+            self.inner.get_top_referrers(code, limit),
+        )
+        .await
+    }
+
+    async fn get_clicks_by_country(&self, code: &str) -> Result<Vec<CountryCount>, DatabaseError> {
+        timed(
+            "get_clicks_by_country",
+            self.inner.get_clicks_by_country(code),
+        )
+        .await
+    }
+
+    async fn get_click_summary(
+        &self,
+        code: &str,
+        top_referrers: u8,
+    ) -> Result<ClickSummary, DatabaseError> {
+        timed(
+            "get_click_summary",
+            self.inner.get_click_summary(code, top_referrers),
+        )
+        .await
+    }
+
+    async fn set_redirect_type(
+        &self,
+        code: &str,
+        redirect_type: &str,
+    ) -> Result<(), DatabaseError> {
+        timed(
+            "set_redirect_type",
+            self.inner.set_redirect_type(code, redirect_type),
+        )
+        .await
+    }
+
+    async fn list_short_codes(
+        &self,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<String>, DatabaseError> {
+        timed(
+            "list_short_codes",
+            self.inner.list_short_codes(offset, limit),
+        )
+        .await
+    }
+
+    async fn set_url_owner(&self, code: &str, owner_id: Uuid) -> Result<(), DatabaseError> {
+        timed("set_url_owner", self.inner.set_url_owner(code, owner_id)).await
+    }
+
+    async fn set_url_password(
+        &self,
+        code: &str,
+        password_hash: &[u8],
+    ) -> Result<(), DatabaseError> {
+        timed(
+            "set_url_password",
+            self.inner.set_url_password(code, password_hash),
+        )
+        .await
+    }
+
+    async fn set_ab_targets(&self, code: &str, targets: &[AbTarget]) -> Result<(), DatabaseError> {
+        timed("set_ab_targets", self.inner.set_ab_targets(code, targets)).await
+    }
+
+    async fn get_ab_targets(&self, code: &str) -> Result<Vec<AbTarget>, DatabaseError> {
+        timed("get_ab_targets", self.inner.get_ab_targets(code)).await
+    }
+
+    async fn set_mobile_url(&self, code: &str, mobile_url: &str) -> Result<(), DatabaseError> {
+        timed(
+            "set_mobile_url",
+            self.inner.set_mobile_url(code, mobile_url),
+        )
+        .await
+    }
+
+    async fn set_max_clicks(&self, code: &str, max_clicks: u64) -> Result<(), DatabaseError> {
+        timed(
+            "set_max_clicks",
+            self.inner.set_max_clicks(code, max_clicks),
+        )
+        .await
+    }
+
+    async fn get_click_count(&self, code: &str) -> Result<u64, DatabaseError> {
+        timed("get_click_count", self.inner.get_click_count(code)).await
+    }
+
+    async fn deactivate_url(&self, code: &str) -> Result<(), DatabaseError> {
+        timed("deactivate_url", self.inner.deactivate_url(code)).await
+    }
+
+    async fn list_expiring_urls(
+        &self,
+        from: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<Vec<ExpiringUrl>, DatabaseError> {
+        timed(
+            "list_expiring_urls",
+            self.inner.list_expiring_urls(from, until),
+        )
+        .await
+    }
+
+    async fn mark_expiry_reminder_sent(
+        &self,
+        code: &str,
+        sent_at: DateTime<Utc>,
+    ) -> Result<(), DatabaseError> {
+        timed(
+            "mark_expiry_reminder_sent",
+            self.inner.mark_expiry_reminder_sent(code, sent_at),
+        )
+        .await
+    }
+
+    async fn list_urls(
+        &self,
+        offset: u64,
+        limit: u64,
+        owner_id: Option<Uuid>,
+    ) -> Result<Vec<UrlRecord>, DatabaseError> {
+        timed("list_urls", self.inner.list_urls(offset, limit, owner_id)).await
+    }
+
+    async fn count_urls_by_owner(&self, owner_id: Uuid) -> Result<u64, DatabaseError> {
+        timed(
+            "count_urls_by_owner",
+            self.inner.count_urls_by_owner(owner_id),
+        )
+        .await
+    }
+
+    async fn sum_clicks_by_owner(&self, owner_id: Uuid) -> Result<u64, DatabaseError> {
+        timed(
+            "sum_clicks_by_owner",
+            self.inner.sum_clicks_by_owner(owner_id),
+        )
+        .await
+    }
+
+    async fn export_urls(
+        &self,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<ExportRecord>, DatabaseError> {
+        timed("export_urls", self.inner.export_urls(offset, limit)).await
+    }
+
+    async fn add_tag(&self, code: &str, tag: &str) -> Result<(), DatabaseError> {
+        timed("add_tag", self.inner.add_tag(code, tag)).await
+    }
+
+    async fn remove_tag(&self, code: &str, tag: &str) -> Result<(), DatabaseError> {
+        timed("remove_tag", self.inner.remove_tag(code, tag)).await
+    }
+
+    async fn search_urls(
+        &self,
+        query: &str,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<UrlRecord>, DatabaseError> {
+        timed("search_urls", self.inner.search_urls(query, offset, limit)).await
+    }
+
+    async fn get_urls_by_tag(
+        &self,
+        tag: &str,
+        offset: u64,
+        limit: u64,
+        owner_id: Option<Uuid>,
+    ) -> Result<Vec<UrlRecord>, DatabaseError> {
+        timed(
+            "get_urls_by_tag",
+            self.inner.get_urls_by_tag(tag, offset, limit, owner_id),
+        )
+        .await
+    }
+
+    async fn load_bloom_snapshot(&self, name: &str) -> Result<Option<Vec<u8>>, DatabaseError> {
+        timed("load_bloom_snapshot", self.inner.load_bloom_snapshot(name)).await
+    }
+
+    async fn save_bloom_snapshot(&self, name: &str, data: &[u8]) -> Result<(), DatabaseError> {
+        timed(
+            "save_bloom_snapshot",
+            self.inner.save_bloom_snapshot(name, data),
+        )
+        .await
+    }
+
+    async fn health_check(&self) -> Result<(), DatabaseError> {
+        timed("health_check", self.inner.health_check()).await
+    }
+
+    async fn get_stats(&self) -> Result<DatabaseStats, DatabaseError> {
+        timed("get_stats", self.inner.get_stats()).await
+    }
+
+    async fn pending_migrations(&self) -> Result<usize, DatabaseError> {
+        timed("pending_migrations", self.inner.pending_migrations()).await
+    }
+
+    async fn run_maintenance(&self) -> Vec<MaintenanceStep> {
+        timed("run_maintenance", self.inner.run_maintenance()).await
+    }
+}
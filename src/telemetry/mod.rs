@@ -0,0 +1,559 @@
+//! # Telemetry and Logging
+//!
+//! This module provides structured logging and request tracing capabilities for the
+//! URL shortener service. It sets up comprehensive observability using the `tracing`
+//! ecosystem with JSON formatting and request correlation.
+//!
+//! ## Features
+//!
+//! - **Structured Logging** - JSON-formatted logs with consistent fields
+//! - **Request Tracing** - Full request lifecycle tracking with correlation IDs
+//! - **Environment-based Filtering** - Configurable log levels via environment variables
+//! - **Bunyan Formatting** - Human-readable and machine-parseable log output
+//! - **Request ID Generation** - Unique identifiers for request correlation
+//! - **Prometheus Metrics** - Request, database and redirect counters (`metrics` feature)
+//! - **OTLP Export** - Spans sent to an OpenTelemetry collector, continuing
+//!   incoming `traceparent` traces and passing them on to webhooks, Safe
+//!   Browsing lookups, reachability checks and link previews
+//!   (`opentelemetry` feature)
+//! - **Error Reporting** - Internal server errors sent to Sentry, tagged with
+//!   their request ID
+//!
+//! ## Log Format
+//!
+//! Logs are formatted as JSON with the following structure:
+//!
+//! ```json
+//! {
+//!   "v": 0,
+//!   "name": "url-shortener-ztm",
+//!   "msg": "Request completed",
+//!   "level": 30,
+//!   "time": "2025-01-18T12:00:00Z",
+//!   "request_id": "550e8400-e29b-41d4-a716-446655440000",
+//!   "method": "POST",
+//!   "path": "/api/shorten",
+//!   "status": 200
+//! }
+//! ```
+//!
+//! ## Usage
+//!
+//! ```rust,no_run
+//! use url_shortener_ztm_lib::telemetry::{get_subscriber, init_subscriber};
+//!
+//! // Initialize logging
+//! let subscriber = get_subscriber("my-app".into(), "info".into(), std::io::stdout);
+//! init_subscriber(subscriber);
+//!
+//! // Use tracing macros
+//! tracing::info!("Application started");
+//! tracing::error!("Something went wrong");
+//! ```
+
+pub mod access_log;
+pub mod error_reporting;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+
+use crate::configuration::Settings;
+
+use axum::http::Request;
+use tower_http::request_id::{MakeRequestId, RequestId};
+use tracing::subscriber::set_global_default;
+use tracing::{Span, Subscriber};
+use tracing_log::LogTracer;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::registry::LookupSpan;
+use uuid::Uuid;
+
+/// Request ID generator that creates UUID-based request identifiers.
+///
+/// This struct implements the `MakeRequestId` trait to generate unique request IDs
+/// for each incoming HTTP request. The IDs are used for request correlation and
+/// tracing across the application.
+///
+/// # ID Format
+///
+/// Request IDs are generated as UUID v4 strings, providing globally unique
+/// identifiers that are suitable for distributed systems.
+///
+/// # Examples
+///
+/// ```rust
+/// use url_shortener_ztm_lib::telemetry::MakeRequestUuid;
+/// use tower_http::request_id::MakeRequestId;
+/// use axum::http::Request;
+///
+/// let mut generator = MakeRequestUuid;
+/// let request = Request::new(());
+/// let request_id = generator.make_request_id(&request);
+/// assert!(request_id.is_some());
+/// ```
+#[derive(Clone)]
+pub struct MakeRequestUuid;
+
+impl MakeRequestId for MakeRequestUuid {
+    /// Generates a new UUID-based request ID for the given request.
+    ///
+    /// This method creates a unique identifier for each request, which is used
+    /// for tracing and correlation across the application's logging and monitoring.
+    ///
+    /// # Arguments
+    ///
+    /// * `_` - The HTTP request (unused, but required by the trait)
+    ///
+    /// # Returns
+    ///
+    /// Returns `Some(RequestId)` containing a UUID v4 string, or `None` if
+    /// ID generation fails (which should not happen in normal operation).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use url_shortener_ztm_lib::telemetry::MakeRequestUuid;
+    /// use tower_http::request_id::MakeRequestId;
+    /// use axum::http::Request;
+    ///
+    /// let mut generator = MakeRequestUuid;
+    /// let request = Request::new(());
+    /// let request_id = generator.make_request_id(&request);
+    /// assert!(request_id.is_some());
+    /// ```
+    fn make_request_id<B>(&mut self, _: &Request<B>) -> Option<RequestId> {
+        let request_id = Uuid::new_v4().to_string();
+
+        Some(RequestId::new(request_id.parse().unwrap()))
+    }
+}
+
+/// Creates a configured tracing subscriber for structured logging.
+///
+/// This function sets up a comprehensive logging subscriber with JSON formatting,
+/// environment-based filtering, and request correlation capabilities.
+///
+/// # Arguments
+///
+/// * `name` - Application name used in log output
+/// * `env_filter` - Default log level filter (overridden by `RUST_LOG` environment variable)
+/// * `sink` - Output destination for log messages (e.g., `std::io::stdout`, `std::io::stderr`)
+///
+/// # Returns
+///
+/// Returns a configured `Subscriber` that can be used with `init_subscriber`.
+///
+/// # Environment Variables
+///
+/// The log level can be controlled using the `RUST_LOG` environment variable:
+///
+/// ```bash
+/// # Set log level for the entire application
+/// RUST_LOG=info cargo run
+///
+/// # Set different levels for different modules
+/// RUST_LOG=url_shortener_ztm_lib=debug,axum=info cargo run
+///
+/// # Enable all logs
+/// RUST_LOG=trace cargo run
+/// ```
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use url_shortener_ztm_lib::telemetry::{get_subscriber, init_subscriber};
+///
+/// // Basic setup
+/// let subscriber = get_subscriber("my-app".into(), "info".into(), std::io::stdout);
+/// init_subscriber(subscriber);
+///
+/// // With custom log level
+/// let subscriber = get_subscriber("my-app".into(), "debug".into(), std::io::stderr);
+/// init_subscriber(subscriber);
+/// ```
+#[cfg(debug_assertions)]
+pub fn get_subscriber<Sink>(
+    _name: String,
+    env_filter: String,
+    sink: Sink,
+) -> impl Subscriber + for<'a> LookupSpan<'a> + Sync + Send
+where
+    Sink: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+{
+    use tracing_subscriber::EnvFilter;
+
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(env_filter)),
+        )
+        .with_writer(sink)
+        .pretty()
+        .with_ansi(true)
+        .with_file(true)
+        .with_line_number(true)
+        .finish()
+}
+
+#[cfg(not(debug_assertions))]
+pub fn get_subscriber<Sink>(
+    name: String,
+    env_filter: String,
+    sink: Sink,
+) -> impl Subscriber + for<'a> LookupSpan<'a> + Sync + Send
+where
+    Sink: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+{
+    use tracing_bunyan_formatter::{BunyanFormattingLayer, JsonStorageLayer};
+    use tracing_subscriber::{EnvFilter, Registry, layer::SubscriberExt};
+
+    let env_filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(env_filter));
+    let formatting_layer = BunyanFormattingLayer::new(name, sink);
+
+    Registry::default()
+        .with(env_filter)
+        .with(JsonStorageLayer)
+        .with(formatting_layer)
+}
+
+/// Initializes the global tracing subscriber.
+///
+/// This function sets up the global logging infrastructure by:
+/// 1. Redirecting standard log macros to the tracing subscriber
+/// 2. Setting the provided subscriber as the global default
+/// 3. Installing the Prometheus recorder, with the `metrics` feature
+///
+/// # Arguments
+///
+/// * `subscriber` - The tracing subscriber to use for logging
+///
+/// # Panics
+///
+/// This function will panic if:
+/// - The logger has already been initialized
+/// - The subscriber cannot be set as the global default
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use url_shortener_ztm_lib::telemetry::{get_subscriber, init_subscriber};
+///
+/// // Initialize logging
+/// let subscriber = get_subscriber("my-app".into(), "info".into(), std::io::stdout);
+/// init_subscriber(subscriber);
+///
+/// // Now you can use tracing macros
+/// tracing::info!("Application started");
+/// tracing::error!("Something went wrong");
+/// ```
+pub fn init_subscriber(subscriber: impl Subscriber + Sync + Send) {
+    // Redirect logs to subscriber
+    LogTracer::init().expect("Failed to set logger");
+    set_global_default(subscriber).expect("Failed to set subscriber");
+    #[cfg(feature = "metrics")]
+    metrics::handle();
+}
+
+/// Logs which build of the service is running and how it is set up, so the
+/// instances of a cluster can be told apart in mixed logs.
+///
+/// `git_sha` is the commit the binary was built from, set by `build.rs`;
+/// `port` is the port actually bound, which differs from the configured one
+/// when that is `0`.
+pub fn log_startup_banner(settings: &Settings, port: u16) {
+    tracing::info!(
+        version = env!("CARGO_PKG_VERSION"),
+        git_sha = option_env!("GIT_SHA").unwrap_or("unknown"),
+        db_type = settings.database.r#type.as_str(),
+        generator_engine = settings.shortener.engine.kind.as_str(),
+        host = %settings.application.host,
+        port,
+        rate_limiting_enabled = settings.rate_limiting.enabled,
+        bloom_expected_items = settings.bloom.expected_items,
+        "Application started"
+    );
+}
+
+/// Creates the `http` span that [`TraceLayer`](tower_http::trace::TraceLayer)
+/// opens for every request.
+///
+/// `otel.name` names the span in OpenTelemetry backends. With the
+/// `opentelemetry` feature, a W3C `traceparent`/`tracestate` pair on the
+/// request makes the span a child of the caller's trace.
+pub fn make_http_span<B>(req: &Request<B>) -> Span {
+    let ua = req
+        .headers()
+        .get("user-agent")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("-");
+    let span = tracing::info_span!("http",
+        otel.name = "http",
+        otel.kind = "server",
+        method = %req.method(),
+        uri = %req.uri(),
+        user_agent = %ua,
+    );
+
+    #[cfg(feature = "opentelemetry")]
+    {
+        use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+        let parent = opentelemetry::global::get_text_map_propagator(|propagator| {
+            propagator.extract(&otlp::HeaderExtractor(req.headers()))
+        });
+        // Only fails when no OpenTelemetry layer is installed
+        let _ = span.set_parent(parent);
+    }
+
+    span
+}
+
+/// Adds the W3C `traceparent`/`tracestate` headers of the current span to
+/// `headers`, so the service receiving an outbound request continues this
+/// trace.
+///
+/// Does nothing without the `opentelemetry` feature, or outside a span
+/// exported to OpenTelemetry.
+pub fn inject_trace_context(headers: &mut reqwest::header::HeaderMap) {
+    #[cfg(feature = "opentelemetry")]
+    {
+        use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+        let cx = Span::current().context();
+        opentelemetry::global::get_text_map_propagator(|propagator| {
+            propagator.inject_context(&cx, &mut otlp::HeaderInjector(headers))
+        });
+    }
+    #[cfg(not(feature = "opentelemetry"))]
+    let _ = headers;
+}
+
+/// The trace context headers of the current span, for
+/// [`RequestBuilder::headers`](reqwest::RequestBuilder::headers).
+pub fn trace_context_headers() -> reqwest::header::HeaderMap {
+    let mut headers = reqwest::header::HeaderMap::new();
+    inject_trace_context(&mut headers);
+    headers
+}
+
+#[cfg(feature = "opentelemetry")]
+pub use otlp::{init_otlp_subscriber, shutdown_otlp};
+
+#[cfg(feature = "opentelemetry")]
+mod otlp {
+    use crate::configuration::OtlpSettings;
+
+    use anyhow::Context;
+    use axum::http::{HeaderMap, HeaderName, HeaderValue};
+    use opentelemetry::propagation::{Extractor, Injector};
+    use opentelemetry::trace::TracerProvider;
+    use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+    use opentelemetry_sdk::{
+        Resource, propagation::TraceContextPropagator, trace::SdkTracerProvider,
+    };
+    use std::sync::OnceLock;
+    use tracing::Subscriber;
+    use tracing_subscriber::{layer::SubscriberExt, registry::LookupSpan};
+
+    static PROVIDER: OnceLock<SdkTracerProvider> = OnceLock::new();
+
+    /// Reads W3C trace context from request headers.
+    pub(super) struct HeaderExtractor<'a>(pub &'a HeaderMap);
+
+    impl Extractor for HeaderExtractor<'_> {
+        fn get(&self, key: &str) -> Option<&str> {
+            self.0.get(key).and_then(|v| v.to_str().ok())
+        }
+
+        fn keys(&self) -> Vec<&str> {
+            self.0.keys().map(|k| k.as_str()).collect()
+        }
+    }
+
+    /// Writes W3C trace context into outbound request headers.
+    pub(super) struct HeaderInjector<'a>(pub &'a mut HeaderMap);
+
+    impl Injector for HeaderInjector<'_> {
+        fn set(&mut self, key: &str, value: String) {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(key.as_bytes()),
+                HeaderValue::from_str(&value),
+            ) {
+                self.0.insert(name, value);
+            }
+        }
+    }
+
+    /// Adds OTLP span export to `subscriber` when `settings.enabled` is set.
+    ///
+    /// Spans are batched and sent over OTLP/HTTP to `settings.endpoint`, and
+    /// the W3C trace context propagator is registered globally. When export
+    /// is disabled the subscriber is returned with a no-op layer.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use url_shortener_ztm_lib::configuration::OtlpSettings;
+    /// use url_shortener_ztm_lib::telemetry::{get_subscriber, init_otlp_subscriber, init_subscriber};
+    ///
+    /// # fn main() -> anyhow::Result<()> {
+    /// let subscriber = get_subscriber("my-app".into(), "info".into(), std::io::stdout);
+    /// let subscriber = init_otlp_subscriber(subscriber, &OtlpSettings::default())?;
+    /// init_subscriber(subscriber);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn init_otlp_subscriber<S>(
+        subscriber: S,
+        settings: &OtlpSettings,
+    ) -> anyhow::Result<impl Subscriber + Send + Sync>
+    where
+        S: Subscriber + for<'a> LookupSpan<'a> + Send + Sync,
+    {
+        let layer = if settings.enabled {
+            let exporter = SpanExporter::builder()
+                .with_http()
+                .with_endpoint(&settings.endpoint)
+                .build()
+                .context("Failed to build the OTLP span exporter")?;
+            let provider = SdkTracerProvider::builder()
+                .with_batch_exporter(exporter)
+                .with_resource(
+                    Resource::builder()
+                        .with_service_name(settings.service_name.clone())
+                        .build(),
+                )
+                .build();
+            let tracer = provider.tracer("url-shortener-ztm");
+
+            opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+            opentelemetry::global::set_tracer_provider(provider.clone());
+            let _ = PROVIDER.set(provider);
+            Some(tracing_opentelemetry::layer().with_tracer(tracer))
+        } else {
+            None
+        };
+
+        Ok(subscriber.with(layer))
+    }
+
+    /// Flushes spans still buffered for export. Call once on shutdown.
+    pub fn shutdown_otlp() {
+        if let Some(provider) = PROVIDER.get()
+            && let Err(err) = provider.shutdown()
+        {
+            tracing::warn!(%err, "failed to flush OTLP spans on shutdown");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::configuration::get_configuration_from_str;
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing_subscriber::{Layer, Registry, layer::Context, prelude::*};
+    use tracing_test::traced_test;
+
+    // Records the fields of every new span as strings
+    #[derive(Clone, Default)]
+    struct SpanFields(Arc<Mutex<Vec<(String, String)>>>);
+
+    impl Visit for SpanFields {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0
+                .lock()
+                .unwrap()
+                .push((field.name().to_string(), format!("{:?}", value)));
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0
+                .lock()
+                .unwrap()
+                .push((field.name().to_string(), value.to_string()));
+        }
+    }
+
+    impl<S: Subscriber> Layer<S> for SpanFields {
+        fn on_new_span(
+            &self,
+            attrs: &tracing::span::Attributes<'_>,
+            _id: &tracing::span::Id,
+            _ctx: Context<'_, S>,
+        ) {
+            attrs.record(&mut self.clone());
+        }
+    }
+
+    #[test]
+    fn http_span_is_named_for_opentelemetry() {
+        let fields = SpanFields::default();
+        let subscriber = Registry::default().with(fields.clone());
+        let request = Request::get("/abc123").body(()).unwrap();
+
+        tracing::subscriber::with_default(subscriber, || {
+            make_http_span(&request);
+        });
+
+        let fields = fields.0.lock().unwrap();
+        assert!(fields.contains(&("otel.name".to_string(), "http".to_string())));
+        assert!(fields.contains(&("uri".to_string(), "/abc123".to_string())));
+    }
+
+    #[test]
+    #[traced_test]
+    fn startup_banner_names_the_version_and_database() {
+        let settings = get_configuration_from_str(
+            "application:\n  port: 0\n  host: 127.0.0.1\n  base_url: http://localhost\n  \
+             api_keys: []\n  jwt_secret_b64: c2VjcmV0\n  pwd_pepper_b64: cGVwcGVy\n\
+             database:\n  type: sqlite\n  url: \"sqlite::memory:\"\n",
+        )
+        .unwrap();
+
+        log_startup_banner(&settings, 8123);
+
+        assert!(logs_contain("Application started"));
+        assert!(logs_contain(&format!(
+            "version=\"{}\"",
+            env!("CARGO_PKG_VERSION")
+        )));
+        assert!(logs_contain("db_type=\"sqlite\""));
+        assert!(logs_contain("generator_engine=\"nanoid\""));
+        assert!(logs_contain("port=8123"));
+    }
+
+    #[cfg(feature = "opentelemetry")]
+    #[test]
+    fn incoming_traceparent_is_continued_and_forwarded() {
+        use opentelemetry::trace::{TraceContextExt, TracerProvider};
+        use opentelemetry_sdk::{propagation::TraceContextPropagator, trace::SdkTracerProvider};
+        use std::collections::HashMap;
+        use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+        const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+
+        opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+        let provider = SdkTracerProvider::builder().build();
+        let subscriber = Registry::default()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+        let request = Request::get("/abc123")
+            .header("traceparent", format!("00-{TRACE_ID}-00f067aa0ba902b7-01"))
+            .body(())
+            .unwrap();
+
+        let outgoing = tracing::subscriber::with_default(subscriber, || {
+            let span = make_http_span(&request);
+            let cx = span.context();
+            assert_eq!(cx.span().span_context().trace_id().to_string(), TRACE_ID);
+
+            let mut headers = HashMap::new();
+            opentelemetry::global::get_text_map_propagator(|propagator| {
+                propagator.inject_context(&cx, &mut headers)
+            });
+            headers
+        });
+
+        assert!(outgoing["traceparent"].starts_with(&format!("00-{TRACE_ID}-")));
+    }
+}
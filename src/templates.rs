@@ -6,161 +6,320 @@
 //! ## Features
 //!
 //! - **Tera Integration** - Uses the Tera template engine for HTML rendering
-//! - **Template Caching** - Templates are compiled once and cached for performance
+//! - **Template Caching** - Templates are compiled once and shared through [`AppState`](crate::state::AppState)
+//! - **Hot Reload** - With `templates.hot_reload`, edited templates are recompiled without a restart
 //! - **Error Handling** - Comprehensive error handling for template operations
-//! - **Static Compilation** - Templates are loaded at startup for optimal performance
+//! - **Custom Filters** - `format_date` renders timestamps in a readable form
 //!
 //! ## Template Directory Structure
 //!
-//! Templates should be placed in the configured template directory:
+//! Templates should be placed under the directory matched by `templates.dir`:
 //!
 //! ```text
 //! templates/
 //! ├── base.html          # Base template with common layout
-//! └── index.html         # Home page template
+//! ├── index.html         # Home page template
+//! └── errors/
+//!     ├── 404.html       # Not found page for browsers
+//!     └── 500.html       # Server error page for browsers
 //! ```
 //!
 //! ## Usage
 //!
 //! ```rust,no_run
-//! use url_shortener_ztm_lib::templates::build_templates;
-//! use url_shortener_ztm_lib::DatabaseType;
-//! use url_shortener_ztm_lib::state::AppState;
+//! use url_shortener_ztm_lib::configuration::TemplateSettings;
+//! use url_shortener_ztm_lib::templates::TemplateState;
 //! use tera::Context;
 //!
-//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
-//! use url_shortener_ztm_lib::database::SqliteUrlDatabase;
-//! use url_shortener_ztm_lib::configuration::DatabaseSettings;
-//! use std::sync::Arc;
-//! use uuid::Uuid;
-//!
-//! let config = DatabaseSettings {
-//!     r#type: DatabaseType::Sqlite,
-//!     url: "database.db".to_string(),
-//!     create_if_missing: true,
-//!     max_connections: Some(16),
-//!     min_connections: Some(4),
-//! };
-//! let database = Arc::new(SqliteUrlDatabase::from_config(&config).await?);
-//! let api_key = Uuid::new_v4();
-//! // let settings = get_configuration().expect("Failed to read configuration");
-//! // let state = AppState::new(database, api_key, "templates".to_string(), settings);
-//! // let templates = build_templates(state)?;
+//! # fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let templates = TemplateState::load(&TemplateSettings::default())?;
 //!
 //! let mut context = Context::new();
 //! context.insert("title", "My Page");
 //! context.insert("message", "Hello, World!");
 //!
-//! // let html = templates.render("index.html", &context)?;
+//! let html = templates.render("index.html", &context)?;
 //! # Ok(())
 //! # }
 //! ```
 
-use crate::AppState;
-use std::sync::OnceLock;
-use tera::{Error, Tera};
+use crate::configuration::TemplateSettings;
+use chrono::{DateTime, Utc};
+use notify::{Event, RecursiveMode, Watcher};
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tera::{Context, Error, Tera, Value};
 
-/// Global template cache for compiled Tera templates.
-///
-/// This static variable holds the compiled templates to avoid recompiling
-/// them on every request. Templates are loaded once at startup and cached
-/// for the lifetime of the application.
-static COMPILED_TEMPLATES: OnceLock<Tera> = OnceLock::new();
+/// Format used by the `format_date` filter when no `format` is given.
+pub const DEFAULT_DATE_FORMAT: &str = "%b %d, %Y";
 
-/// Loads and compiles Tera templates from the specified directory.
-///
-/// This function scans the template directory for HTML files and compiles
-/// them into a Tera instance. The templates are then ready for rendering.
-///
-/// # Arguments
-///
-/// * `template_dir` - Path to the directory containing template files
-///
-/// # Returns
-///
-/// Returns `Ok(Tera)` if templates are successfully loaded and compiled,
-/// or `Err(Error)` if there's an error reading or parsing templates.
+/// How long the watcher waits for an editor to finish writing before reloading.
+const RELOAD_DEBOUNCE: Duration = Duration::from_millis(50);
+
+/// Compiled Tera templates, shared by every handler through the app state.
 ///
-/// # Template Discovery
+/// `Static` templates are compiled once at startup. `HotReload` templates sit
+/// behind a lock so [`TemplateState::reload`] can swap in a fresh compile;
+/// [`watch_templates`] calls it whenever a template file changes.
+#[derive(Clone)]
+pub enum TemplateState {
+    Static(Arc<Tera>),
+    HotReload {
+        tera: Arc<RwLock<Tera>>,
+        dir: String,
+    },
+}
+
+impl TemplateState {
+    /// Compiles the templates matched by `settings.dir`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a template cannot be read or fails to parse.
+    pub fn load(settings: &TemplateSettings) -> Result<Self, Error> {
+        let tera = load_templates(&settings.dir)?;
+        Ok(if settings.hot_reload {
+            Self::HotReload {
+                tera: Arc::new(RwLock::new(tera)),
+                dir: settings.dir.clone(),
+            }
+        } else {
+            Self::Static(Arc::new(tera))
+        })
+    }
+
+    /// Renders the template `name` with `context`.
+    pub fn render(&self, name: &str, context: &Context) -> Result<String, Error> {
+        match self {
+            Self::Static(tera) => tera.render(name, context),
+            Self::HotReload { tera, .. } => tera.read().render(name, context),
+        }
+    }
+
+    /// Recompiles every template from disk. Does nothing for static templates.
+    ///
+    /// The new set replaces the old one only if all of it compiles, so a
+    /// template saved half-way through an edit leaves the last good set in use.
+    ///
+    /// # Errors
+    ///
+    /// Returns the compile error; the previous templates stay active.
+    pub fn reload(&self) -> Result<(), Error> {
+        if let Self::HotReload { tera, dir } = self {
+            let fresh = load_templates(dir)?;
+            *tera.write() = fresh;
+        }
+        Ok(())
+    }
+}
+
+/// Loads and compiles Tera templates matching the `template_dir` glob, with
+/// the custom filters of this module registered.
 ///
 /// Tera automatically discovers template files with the following extensions:
 /// - `.html`
 /// - `.tera`
 /// - `.jinja`
 /// - `.jinja2`
-///
-/// # Internal Usage
-///
-/// This function is used internally by [`build_templates`] and is not
-/// intended for direct use by library consumers.
-fn load_templates(template_dir: String) -> Result<Tera, Error> {
-    let templates = Tera::new(&template_dir)?;
+fn load_templates(template_dir: &str) -> Result<Tera, Error> {
+    let mut templates = Tera::new(template_dir)?;
+    templates.register_filter("format_date", format_date);
     Ok(templates)
 }
 
-/// Builds and returns the compiled template engine.
-///
-/// This function loads templates from the application state's template directory
-/// and returns a reference to the compiled Tera instance. Templates are compiled
-/// once and cached for the lifetime of the application.
-///
-/// # Arguments
-///
-/// * `state` - Application state containing the template directory path
+/// Spawns a task that reloads hot-reload templates whenever an `.html` file
+/// under their directory is created, changed or removed.
 ///
-/// # Returns
+/// Static templates are left alone. The watcher lives as long as the task,
+/// which runs until the Tokio runtime shuts down.
 ///
-/// Returns `Ok(&'static Tera)` if templates are successfully loaded and compiled,
-/// or `Err(Error)` if there's an error reading or parsing templates.
+/// # Errors
 ///
-/// # Caching
-///
-/// Templates are compiled once and cached in a static variable. Subsequent
-/// calls to this function will return the same compiled instance.
-///
-/// # Examples
-///
-/// ```rust,no_run
-/// use url_shortener_ztm_lib::templates::build_templates;
-/// use url_shortener_ztm_lib::DatabaseType;
-/// use url_shortener_ztm_lib::state::AppState;
-/// use tera::Context;
-///
-/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
-/// use url_shortener_ztm_lib::database::SqliteUrlDatabase;
-/// use url_shortener_ztm_lib::configuration::DatabaseSettings;
-/// use std::sync::Arc;
-/// use uuid::Uuid;
-///
-/// let config = DatabaseSettings {
-///     r#type: DatabaseType::Sqlite,
-///     url: "database.db".to_string(),
-///     create_if_missing: true,
-///     max_connections: Some(16),
-///     min_connections: Some(4),
-/// };
-/// let database = Arc::new(SqliteUrlDatabase::from_config(&config).await?);
-/// let api_key = Uuid::new_v4();
-/// // let settings = get_configuration().expect("Failed to read configuration");
-/// // let state = AppState::new(database, api_key, "templates".to_string(), settings);
-/// // let templates = build_templates(state)?;
+/// Returns an error if the filesystem watcher cannot be started.
+pub fn watch_templates(templates: &TemplateState) -> Result<(), notify::Error> {
+    let TemplateState::HotReload { dir, .. } = templates else {
+        return Ok(());
+    };
+    let root = watch_root(dir);
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+        if let Ok(event) = event
+            && is_template_change(&event)
+        {
+            let _ = tx.send(());
+        }
+    })?;
+    watcher.watch(&root, RecursiveMode::Recursive)?;
+    tracing::info!(dir = %root.display(), "watching templates for changes");
+
+    let templates = templates.clone();
+    tokio::spawn(async move {
+        let _watcher = watcher;
+        while rx.recv().await.is_some() {
+            // Editors often write a file in several steps; reload once they settle
+            tokio::time::sleep(RELOAD_DEBOUNCE).await;
+            while rx.try_recv().is_ok() {}
+
+            match templates.reload() {
+                Ok(()) => tracing::info!("templates reloaded"),
+                Err(e) => tracing::error!(
+                    "template reload failed, keeping the previous templates: {:?}",
+                    e
+                ),
+            }
+        }
+    });
+    Ok(())
+}
+
+/// The directory to watch for a template glob: its components before the
+/// first one containing a glob character (`templates/**/*` -> `templates`).
+fn watch_root(dir: &str) -> PathBuf {
+    let root: PathBuf = Path::new(dir)
+        .components()
+        .take_while(|c| !matches!(c, Component::Normal(part) if part.to_string_lossy().contains(['*', '?', '['])))
+        .collect();
+    if root.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        root
+    }
+}
+
+fn is_template_change(event: &Event) -> bool {
+    !event.kind.is_access()
+        && event
+            .paths
+            .iter()
+            .any(|path| path.extension().is_some_and(|ext| ext == "html"))
+}
+
+/// Tera filter that formats a timestamp, e.g. `{{ url.created_at | format_date }}`.
 ///
-/// let mut context = Context::new();
-/// context.insert("title", "My Page");
-/// // let html = templates.render("index.html", &context)?;
-/// # Ok(())
-/// # }
-/// ```
-pub fn build_templates(state: AppState) -> Result<&'static Tera, Error> {
-    let dir = state.template_dir;
-    let templates = load_templates(dir)?;
-    Ok(COMPILED_TEMPLATES.get_or_init(|| templates))
+/// Accepts an optional chrono `format` argument, [`DEFAULT_DATE_FORMAT`] by
+/// default: `{{ bucket.timestamp | format_date(format="%d %b %H:00") }}`.
+fn format_date(value: &Value, args: &HashMap<String, Value>) -> Result<Value, Error> {
+    let timestamp: DateTime<Utc> = tera::from_value(value.clone())
+        .map_err(|_| Error::msg(format!("format_date expects a timestamp, got {}", value)))?;
+    let format = match args.get("format") {
+        Some(format) => tera::from_value::<String>(format.clone())?,
+        None => DEFAULT_DATE_FORMAT.to_string(),
+    };
+
+    // An invalid format fails while writing instead of panicking in to_string
+    let mut formatted = String::new();
+    write!(formatted, "{}", timestamp.format(&format))
+        .map_err(|_| Error::msg(format!("format_date got an invalid format {:?}", format)))?;
+    Ok(Value::String(formatted))
 }
 
-/// Returns a reference to the globally cached Tera instance.
-/// Panics if templates have not been initialized by calling `build_templates` first.
-pub fn get_templates() -> &'static Tera {
-    COMPILED_TEMPLATES
-        .get()
-        .expect("Templates have not been initialized")
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(format: &str) -> HashMap<String, Value> {
+        HashMap::from([("format".to_string(), Value::String(format.to_string()))])
+    }
+
+    // A template directory of its own, so tests can rewrite files freely
+    fn template_dir(body: &str) -> (PathBuf, TemplateSettings) {
+        let root = std::env::temp_dir().join(format!("templates-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("page.html"), body).unwrap();
+        let settings = TemplateSettings {
+            hot_reload: true,
+            dir: format!("{}/**/*", root.display()),
+        };
+        (root, settings)
+    }
+
+    #[test]
+    fn format_date_uses_the_default_format() {
+        let value = Value::String("2025-10-23T14:05:00Z".to_string());
+        let formatted = format_date(&value, &HashMap::new()).unwrap();
+        assert_eq!(formatted, Value::String("Oct 23, 2025".to_string()));
+    }
+
+    #[test]
+    fn format_date_accepts_a_format_argument() {
+        let value = Value::String("2025-10-23T14:05:00Z".to_string());
+        let formatted = format_date(&value, &args("%Y-%m-%d %H:%M")).unwrap();
+        assert_eq!(formatted, Value::String("2025-10-23 14:05".to_string()));
+    }
+
+    #[test]
+    fn format_date_rejects_bad_input() {
+        let value = Value::String("2025-10-23T14:05:00Z".to_string());
+        assert!(format_date(&Value::String("yesterday".to_string()), &HashMap::new()).is_err());
+        assert!(format_date(&value, &args("%Q")).is_err());
+    }
+
+    #[test]
+    fn watch_root_stops_at_the_first_glob() {
+        assert_eq!(watch_root("templates/**/*"), PathBuf::from("templates"));
+        assert_eq!(
+            watch_root("/srv/app/templates/*.html"),
+            PathBuf::from("/srv/app/templates")
+        );
+        assert_eq!(watch_root("*.html"), PathBuf::from("."));
+    }
+
+    #[test]
+    fn static_templates_ignore_reloads() {
+        let (root, mut settings) = template_dir("v1");
+        settings.hot_reload = false;
+        let templates = TemplateState::load(&settings).unwrap();
+
+        std::fs::write(root.join("page.html"), "v2").unwrap();
+        templates.reload().unwrap();
+
+        assert_eq!(
+            templates.render("page.html", &Context::new()).unwrap(),
+            "v1"
+        );
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn failed_reload_keeps_the_previous_templates() {
+        let (root, settings) = template_dir("v1");
+        let templates = TemplateState::load(&settings).unwrap();
+
+        std::fs::write(root.join("page.html"), "{% if %}").unwrap();
+        assert!(templates.reload().is_err());
+
+        assert_eq!(
+            templates.render("page.html", &Context::new()).unwrap(),
+            "v1"
+        );
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn edited_template_is_rendered_after_the_watcher_reloads() {
+        let (root, settings) = template_dir("v1");
+        let templates = TemplateState::load(&settings).unwrap();
+        watch_templates(&templates).unwrap();
+        assert_eq!(
+            templates.render("page.html", &Context::new()).unwrap(),
+            "v1"
+        );
+
+        std::fs::write(root.join("page.html"), "v2").unwrap();
+
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+        while templates.render("page.html", &Context::new()).unwrap() != "v2" {
+            assert!(
+                tokio::time::Instant::now() < deadline,
+                "template was not reloaded"
+            );
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        std::fs::remove_dir_all(root).unwrap();
+    }
 }
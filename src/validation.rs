@@ -0,0 +1,835 @@
+//! # Input Validation
+//!
+//! Validation helpers shared by the HTTP handlers. Each helper returns an
+//! [`ApiError::Unprocessable`] describing the first rule that was broken, so
+//! handlers can propagate it with `?`.
+//!
+//! [`normalize_url`] turns a submitted destination into the form it is stored
+//! and deduplicated in, and [`validate_alias`] checks a custom alias against
+//! the configured alphabet and prefix.
+//!
+//! [`reject_private_destination`] keeps shortened URLs from pointing into
+//! private networks, so the service cannot be used to reach internal hosts.
+//! [`DomainFilter`] limits which domains may be shortened at all, and
+//! [`check_reachability`] that the destination answers. What a failure of
+//! these checks does is set by `url_validation.mode`, see
+//! [`apply_validation_mode`].
+
+use crate::configuration::{
+    HttpClientSettings, SsrfSettings, UrlFilterMode, UrlFilterSettings, ValidationMode,
+};
+use crate::database::MAX_ALIAS_LENGTH;
+use crate::errors::ApiError;
+use crate::infrastructure::http_client::build_pinned_http_client;
+use crate::state::AppState;
+use crate::telemetry::trace_context_headers;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use ipnet::IpNet;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::LazyLock;
+use std::time::Duration;
+use url::{Host, Url};
+
+/// Maximum allowed tag length in characters.
+pub const MAX_TAG_LENGTH: usize = 32;
+
+/// Networks a shortened URL may not point into when SSRF protection is on:
+/// loopback, "this network" (`0.0.0.0` reaches the local host on Linux),
+/// RFC 1918 private ranges, carrier-grade NAT, link-local (home of cloud
+/// metadata endpoints), the IPv6 unspecified address and IPv6 unique local
+/// addresses.
+pub const BLOCKED_NETWORKS: [&str; 11] = [
+    "127.0.0.0/8",
+    "0.0.0.0/8",
+    "10.0.0.0/8",
+    "172.16.0.0/12",
+    "192.168.0.0/16",
+    "100.64.0.0/10",
+    "169.254.0.0/16",
+    "::1/128",
+    "::/128",
+    "fc00::/7",
+    "fe80::/10",
+];
+
+static BLOCKED: LazyLock<Vec<IpNet>> = LazyLock::new(|| {
+    BLOCKED_NETWORKS
+        .iter()
+        .map(|net| net.parse().expect("BLOCKED_NETWORKS holds valid CIDRs"))
+        .collect()
+});
+
+/// Validates a user-provided tag name.
+/// Rules:
+/// - Non-empty
+/// - Max length = MAX_TAG_LENGTH
+/// - Allowed characters: ASCII letters, digits and `-`
+///
+/// # Examples
+///
+/// ```rust
+/// use url_shortener_ztm_lib::validation::validate_tag;
+///
+/// assert!(validate_tag("campaign-q3").is_ok());
+/// assert!(validate_tag("not a tag").is_err());
+/// ```
+pub fn validate_tag(tag: &str) -> Result<(), ApiError> {
+    if tag.is_empty() {
+        return Err(ApiError::Unprocessable("Tag cannot be empty".to_string()));
+    }
+    if tag.len() > MAX_TAG_LENGTH {
+        return Err(ApiError::Unprocessable(format!(
+            "Tag exceeds maximum length of {} characters",
+            MAX_TAG_LENGTH
+        )));
+    }
+    if !tag.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(ApiError::Unprocessable(
+            "Tag may only contain letters, digits and hyphens".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Parses and normalizes a URL:
+/// - Enforces http/https schemes
+/// - Removes fragments, keeping the query string as given
+/// - Lowercases host, and converts an internationalized host to its
+///   ASCII (punycode) form, so `münchen.de` and `xn--mnchen-3ya.de` dedupe
+/// - Percent-encodes non-ASCII path characters, with uppercase hex digits
+/// - Validates proper slashes after scheme using manual parsing
+///
+/// Existing escapes such as `%20` are kept as they are, so normalizing an
+/// already normalized URL returns it unchanged.
+///
+/// # Examples
+///
+/// ```rust
+/// use url_shortener_ztm_lib::validation::normalize_url;
+///
+/// assert_eq!(
+///     normalize_url("https://Example.COM/path#top").unwrap(),
+///     "https://example.com/path"
+/// );
+/// assert!(normalize_url("ftp://example.com").is_err());
+/// ```
+pub fn normalize_url(raw: &str) -> Result<String, ApiError> {
+    let is_http = raw.starts_with("http://");
+    let is_https = raw.starts_with("https://");
+
+    if is_http || is_https {
+        let scheme_len = if is_http { 7 } else { 8 };
+        if raw[scheme_len..].starts_with('/') {
+            return Err(ApiError::Unprocessable(
+                "Wrong number of slashes (separators) in scheme".to_string(),
+            ));
+        }
+
+        let mut u = url::Url::parse(raw).map_err(|e| ApiError::Unprocessable(e.to_string()))?;
+        u.set_fragment(None);
+
+        if let Some(url::Host::Domain(h)) = u.host() {
+            let ascii = idna::domain_to_ascii(h).map_err(|_| {
+                ApiError::Unprocessable("Invalid internationalized domain name".to_string())
+            })?;
+            if ascii != h {
+                let _ = u.set_host(Some(&ascii));
+            }
+        }
+
+        // Parsing already percent-encodes non-ASCII characters, but keeps the
+        // case of escapes it was given; `%e6` and `%E6` are the same byte and
+        // must dedupe to the same stored URL
+        let path = uppercase_percent_escapes(u.path());
+        if path != u.path() {
+            u.set_path(&path);
+        }
+        return Ok(u.to_string());
+    }
+
+    // If it looks like a URL with a scheme but not http(s), call it what it is: unsupported scheme.
+    if let Some(pos) = raw.find("://") {
+        let scheme = &raw[..pos];
+        return Err(ApiError::InvalidScheme(format!(
+            "Unsupported scheme: {}",
+            scheme
+        )));
+    }
+
+    // Explicitly catch http/https missing slashes like "http:example.com".
+    if raw.starts_with("http:") || raw.starts_with("https:") {
+        return Err(ApiError::Unprocessable(
+            "Wrong number of slashes (separators) in scheme".to_string(),
+        ));
+    }
+
+    // Everything else is just not a URL we handle.
+    Err(ApiError::Unprocessable(
+        "Unsupported or invalid URL".to_string(),
+    ))
+}
+
+/// Rewrites the hex digits of every `%XX` escape in `path` in uppercase.
+fn uppercase_percent_escapes(path: &str) -> String {
+    let mut parts = path.split('%');
+    let mut out = parts.next().unwrap_or_default().to_string();
+    for part in parts {
+        out.push('%');
+        match part.get(..2) {
+            Some(hex) if hex.bytes().all(|b| b.is_ascii_hexdigit()) => {
+                out.push_str(&hex.to_ascii_uppercase());
+                out.push_str(&part[2..]);
+            }
+            _ => out.push_str(part),
+        }
+    }
+    out
+}
+
+/// Validates a user-provided alias and returns it as it is stored, with
+/// `shortener.alias_prefix` prepended.
+///
+/// The alias must leave room for the prefix within [`MAX_ALIAS_LENGTH`].
+pub fn validate_alias(alias: &str, state: &AppState) -> Result<String, ApiError> {
+    let prefix = alias_prefix(state);
+    check_code(alias, MAX_ALIAS_LENGTH - prefix.len(), state)?;
+    Ok(format!("{}{}", prefix, alias))
+}
+
+/// The configured `shortener.alias_prefix`, or `""` when there is none.
+pub(crate) fn alias_prefix(state: &AppState) -> &str {
+    state
+        .config()
+        .shortener
+        .alias_prefix
+        .as_deref()
+        .unwrap_or_default()
+}
+
+/// Validates a code that is stored as given, such as an imported one.
+/// Rules:
+/// - Non-empty
+/// - Max length = [`MAX_ALIAS_LENGTH`]
+/// - Allowed characters: based on configuration (state.allowed_chars())
+pub(crate) fn validate_code(code: &str, state: &AppState) -> Result<(), ApiError> {
+    check_code(code, MAX_ALIAS_LENGTH, state)
+}
+
+fn check_code(alias: &str, max_length: usize, state: &AppState) -> Result<(), ApiError> {
+    if alias.is_empty() {
+        return Err(ApiError::Unprocessable("Alias cannot be empty".to_string()));
+    }
+    if alias.len() > max_length {
+        return Err(ApiError::Unprocessable(format!(
+            "Alias exceeds maximum length of {} characters",
+            max_length
+        )));
+    }
+
+    if alias.chars().any(|c| !state.allowed_chars().contains(&c)) {
+        return Err(ApiError::Unprocessable(
+            "Alias contains characters not allowed by configuration".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Whether `ip` lies in [`BLOCKED_NETWORKS`] or in one of `extra_blocked`.
+///
+/// IPv4-mapped IPv6 addresses (`::ffff:10.0.0.1`) are checked as the IPv4
+/// address they reach.
+pub fn is_blocked_address(ip: IpAddr, extra_blocked: &[IpNet]) -> bool {
+    let ip = ip.to_canonical();
+    BLOCKED
+        .iter()
+        .chain(extra_blocked)
+        .any(|net| net.contains(&ip))
+}
+
+/// Rejects a normalized URL whose host is, or resolves to, a blocked address.
+///
+/// Does nothing unless `settings.enabled` is set. IP literals are checked as
+/// they are; host names are resolved and rejected if any of their addresses
+/// is blocked. A host that does not resolve is let through, since it cannot
+/// reach anything either.
+///
+/// # Errors
+///
+/// Returns [`ApiError::Unprocessable`] if the URL points into a blocked network.
+pub async fn reject_private_destination(
+    url: &str,
+    settings: &SsrfSettings,
+) -> Result<(), ApiError> {
+    if !settings.enabled {
+        return Ok(());
+    }
+    let Ok(url) = Url::parse(url) else {
+        return Ok(());
+    };
+
+    if resolves_to_blocked(&url, &settings.extra_blocked_cidrs).await {
+        tracing::warn!(url = %url, "rejecting URL that resolves to a private address");
+        return Err(ApiError::Unprocessable(
+            "URL resolves to a private address".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Whether the host of `url` is, or resolves to, an address in
+/// [`BLOCKED_NETWORKS`] or `extra_blocked`. A host name that does not resolve
+/// is not blocked.
+async fn resolves_to_blocked(url: &Url, extra_blocked: &[IpNet]) -> bool {
+    resolve_host(url)
+        .await
+        .into_iter()
+        .any(|addr| is_blocked_address(addr.ip(), extra_blocked))
+}
+
+/// Resolves the host of `url` once, so a request to it can be pinned to the
+/// addresses checked here rather than resolving the name again.
+///
+/// IP literals resolve to themselves; a host name that does not resolve has
+/// no addresses.
+///
+/// # Errors
+///
+/// Returns [`ApiError::Unprocessable`] if any address lies in
+/// [`BLOCKED_NETWORKS`] or `extra_blocked`, whether or not SSRF protection is
+/// on.
+pub async fn resolve_unblocked(
+    url: &Url,
+    extra_blocked: &[IpNet],
+) -> Result<Vec<SocketAddr>, ApiError> {
+    let addresses = resolve_host(url).await;
+    if addresses
+        .iter()
+        .any(|addr| is_blocked_address(addr.ip(), extra_blocked))
+    {
+        tracing::warn!(url = %url, "not requesting a URL that resolves to a private address");
+        return Err(ApiError::Unprocessable(
+            "URL resolves to a private address".to_string(),
+        ));
+    }
+    Ok(addresses)
+}
+
+/// The addresses the host of `url` resolves to, on its port.
+async fn resolve_host(url: &Url) -> Vec<SocketAddr> {
+    let port = url.port_or_known_default().unwrap_or(80);
+    match url.host() {
+        Some(Host::Ipv4(ip)) => vec![SocketAddr::new(ip.into(), port)],
+        Some(Host::Ipv6(ip)) => vec![SocketAddr::new(ip.into(), port)],
+        Some(Host::Domain(domain)) => match tokio::net::lookup_host((domain, port)).await {
+            Ok(resolved) => resolved.collect(),
+            Err(e) => {
+                tracing::debug!(host = domain, "URL host did not resolve: {}", e);
+                Vec::new()
+            }
+        },
+        None => Vec::new(),
+    }
+}
+
+/// Runs the [`DomainFilter`] and [`reject_private_destination`] checks on a
+/// normalized URL under the configured `url_validation.mode`, then, with
+/// `safe_browsing.enabled` set, rejects a URL Safe Browsing lists as a threat
+/// in every mode.
+///
+/// Every path that stores a destination calls this, so a URL refused by one
+/// of them cannot be stored through another.
+///
+/// # Errors
+///
+/// Returns the error of the first failed check in `strict` mode, and
+/// [`ApiError::Unprocessable`] for a URL flagged by Safe Browsing.
+pub async fn check_destination(url: &str, state: &AppState) -> Result<(), ApiError> {
+    let mode = state.config().url_validation.mode;
+    if mode != ValidationMode::Permissive {
+        let result = match state.domain_filter().check(url) {
+            Ok(()) => {
+                reject_private_destination(url, &state.config().application.ssrf_protection).await
+            }
+            Err(e) => Err(e),
+        };
+        apply_validation_mode(mode, url, result)?;
+    }
+    if let Some(safe_browsing) = state.safe_browsing() {
+        safe_browsing.reject_flagged(url).await?;
+    }
+    Ok(())
+}
+
+/// Turns the `result` of a destination check on `url` into the outcome
+/// `mode` asks for.
+///
+/// `strict` keeps the error, `warn` logs it and accepts the URL, and
+/// `permissive` accepts the URL silently; callers skip the check itself in
+/// that mode.
+///
+/// # Errors
+///
+/// Returns the error of `result` in `strict` mode.
+pub fn apply_validation_mode(
+    mode: ValidationMode,
+    url: &str,
+    result: Result<(), ApiError>,
+) -> Result<(), ApiError> {
+    match (mode, result) {
+        (ValidationMode::Warn, Err(e)) => {
+            tracing::warn!(url, "accepting URL that failed validation: {}", e);
+            Ok(())
+        }
+        (ValidationMode::Permissive, _) => Ok(()),
+        (ValidationMode::Strict | ValidationMode::Warn, result) => result,
+    }
+}
+
+/// Rejects a normalized URL whose destination does not answer a `HEAD`
+/// request, or answers it with a 4xx or 5xx status.
+///
+/// Up to `settings.max_redirects` redirects are followed, all within
+/// `timeout`. The host of the URL and of every redirect is resolved once, and
+/// one that is or resolves to an address in [`BLOCKED_NETWORKS`] or
+/// `extra_blocked` is not requested, whether or not SSRF protection is on, so
+/// the check cannot be used to probe internal services; a URL or redirect
+/// pointing at one is let through. A host name is requested at the addresses
+/// that were checked, so it cannot be rebound to a private one in between.
+///
+/// # Errors
+///
+/// Returns [`ApiError::Unprocessable`] if the destination answered with an
+/// error status, redirected too often, or could not be reached in time.
+pub async fn check_reachability(
+    url: &str,
+    client: &reqwest::Client,
+    settings: &HttpClientSettings,
+    timeout: Duration,
+    extra_blocked: &[IpNet],
+) -> Result<(), ApiError> {
+    let Ok(url) = Url::parse(url) else {
+        return Ok(());
+    };
+
+    let followed = follow_head(url.clone(), client, settings, extra_blocked);
+    match tokio::time::timeout(timeout, followed).await {
+        Ok(Ok(Some(status))) if status.is_client_error() || status.is_server_error() => {
+            Err(ApiError::Unprocessable(format!(
+                "Destination URL is not reachable (status: {})",
+                status.as_u16()
+            )))
+        }
+        Ok(Ok(_)) => Ok(()),
+        Ok(Err(e)) => {
+            tracing::debug!(url = %url, "reachability check failed: {}", e);
+            Err(ApiError::Unprocessable(
+                "Destination URL is not reachable".to_string(),
+            ))
+        }
+        Err(_) => {
+            tracing::debug!(url = %url, "reachability check timed out");
+            Err(ApiError::Unprocessable(
+                "Destination URL is not reachable".to_string(),
+            ))
+        }
+    }
+}
+
+/// Sends `HEAD` requests from `url` along its redirects.
+///
+/// Returns the status of the last answer, or `None` once a blocked address
+/// is reached, which is not requested.
+async fn follow_head(
+    mut url: Url,
+    client: &reqwest::Client,
+    settings: &HttpClientSettings,
+    extra_blocked: &[IpNet],
+) -> Result<Option<reqwest::StatusCode>, String> {
+    for _ in 0..=settings.max_redirects {
+        let addresses = match url.host() {
+            Some(_) => resolve_unblocked(&url, extra_blocked).await.ok(),
+            None => None,
+        };
+        let Some(addresses) = addresses else {
+            tracing::debug!(url = %url, "not requesting a blocked address");
+            return Ok(None);
+        };
+
+        // A resolved host name is connected to at the addresses just checked,
+        // so it cannot be rebound to a private one in between
+        let pinned;
+        let hop_client = match url.host() {
+            Some(Host::Domain(domain)) if !addresses.is_empty() => {
+                pinned = build_pinned_http_client(settings, domain, &addresses)
+                    .map_err(|e| e.to_string())?;
+                &pinned
+            }
+            _ => client,
+        };
+        let response = hop_client
+            .head(url.clone())
+            .headers(trace_context_headers())
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        if !response.status().is_redirection() {
+            return Ok(Some(response.status()));
+        }
+        let next = response
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|location| location.to_str().ok())
+            .and_then(|location| url.join(location).ok());
+        match next {
+            Some(next) => url = next,
+            None => return Ok(Some(response.status())),
+        }
+    }
+    Err("too many redirects".to_string())
+}
+
+/// The `application.url_filter` allowlist or blocklist, compiled once.
+///
+/// Patterns are globs matched against the whole host, ignoring case, so
+/// `*.example.com` matches `www.example.com` and `a.b.example.com` but not
+/// `example.com` itself.
+#[derive(Clone, Debug)]
+pub struct DomainFilter {
+    mode: UrlFilterMode,
+    domains: GlobSet,
+}
+
+impl DomainFilter {
+    /// Compiles the patterns of `settings`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the [`globset::Error`] of the first invalid pattern.
+    pub fn new(settings: &UrlFilterSettings) -> Result<Self, globset::Error> {
+        let mut domains = GlobSetBuilder::new();
+        for pattern in &settings.domains {
+            domains.add(GlobBuilder::new(pattern).case_insensitive(true).build()?);
+        }
+        Ok(Self {
+            mode: settings.mode,
+            domains: domains.build()?,
+        })
+    }
+
+    /// Rejects a normalized URL whose host the filter does not let through.
+    ///
+    /// In `allowlist` mode a URL without a host is rejected too.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ApiError::Unprocessable`] if the domain is not allowed.
+    pub fn check(&self, url: &str) -> Result<(), ApiError> {
+        if self.mode == UrlFilterMode::None {
+            return Ok(());
+        }
+        let host = Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string));
+        let listed = host
+            .as_deref()
+            .is_some_and(|host| self.domains.is_match(host));
+        let allowed = match self.mode {
+            UrlFilterMode::None => true,
+            UrlFilterMode::Allowlist => listed,
+            UrlFilterMode::Blocklist => !listed,
+        };
+        if !allowed {
+            tracing::info!(host = host.as_deref(), "rejecting URL of a filtered domain");
+            return Err(ApiError::Unprocessable("Domain not allowed".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use tracing_test::traced_test;
+
+    fn enabled(extra: &[&str]) -> SsrfSettings {
+        SsrfSettings {
+            enabled: true,
+            extra_blocked_cidrs: extra.iter().map(|net| net.parse().unwrap()).collect(),
+        }
+    }
+
+    fn blocked(ip: &str) -> bool {
+        is_blocked_address(ip.parse().unwrap(), &[])
+    }
+
+    #[test]
+    fn loopback_is_blocked() {
+        assert!(blocked("127.0.0.1"));
+        assert!(blocked("127.255.255.254"));
+    }
+
+    #[test]
+    fn ten_slash_eight_is_blocked() {
+        assert!(blocked("10.0.0.1"));
+        assert!(blocked("10.255.255.255"));
+    }
+
+    #[test]
+    fn one_seventy_two_sixteen_slash_twelve_is_blocked() {
+        assert!(blocked("172.16.0.1"));
+        assert!(blocked("172.31.255.255"));
+        assert!(!blocked("172.32.0.1"));
+    }
+
+    #[test]
+    fn one_ninety_two_one_sixty_eight_slash_sixteen_is_blocked() {
+        assert!(blocked("192.168.1.1"));
+        assert!(!blocked("192.169.0.1"));
+    }
+
+    #[test]
+    fn this_network_is_blocked() {
+        assert!(blocked("0.0.0.0"));
+        assert!(blocked("0.255.255.255"));
+        assert!(!blocked("1.0.0.1"));
+    }
+
+    #[test]
+    fn carrier_grade_nat_is_blocked() {
+        assert!(blocked("100.64.0.1"));
+        assert!(blocked("100.127.255.254"));
+        assert!(!blocked("100.63.255.255"));
+        assert!(!blocked("100.128.0.1"));
+    }
+
+    #[test]
+    fn ipv6_loopback_is_blocked() {
+        assert!(blocked("::1"));
+    }
+
+    #[test]
+    fn ipv6_unspecified_is_blocked() {
+        assert!(blocked("::"));
+        assert!(blocked("::ffff:0.0.0.0"));
+    }
+
+    #[test]
+    fn ipv6_unique_local_and_mapped_ipv4_are_blocked() {
+        assert!(blocked("fd12:3456::1"));
+        assert!(blocked("::ffff:192.168.1.1"));
+    }
+
+    #[test]
+    fn public_addresses_are_allowed() {
+        assert!(!blocked("93.184.216.34"));
+        assert!(!blocked("2606:2800:220:1:248:1893:25c8:1946"));
+    }
+
+    #[test]
+    fn extra_cidrs_extend_the_blocklist() {
+        let extra: Vec<IpNet> = vec!["203.0.113.0/24".parse().unwrap()];
+        assert!(is_blocked_address("203.0.113.9".parse().unwrap(), &extra));
+        assert!(!is_blocked_address("198.51.100.9".parse().unwrap(), &extra));
+    }
+
+    #[tokio::test]
+    async fn private_ip_literal_is_rejected() {
+        let result = reject_private_destination("http://192.168.1.1/admin", &enabled(&[])).await;
+        assert!(matches!(result, Err(ApiError::Unprocessable(_))));
+        let result = reject_private_destination("http://[::1]:8080/", &enabled(&[])).await;
+        assert!(matches!(result, Err(ApiError::Unprocessable(_))));
+    }
+
+    #[tokio::test]
+    async fn disabled_protection_lets_private_urls_through() {
+        let settings = SsrfSettings::default();
+        assert!(
+            reject_private_destination("http://10.0.0.1/", &settings)
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn strict_mode_keeps_private_address_rejection() {
+        let url = "http://192.168.1.1/admin";
+        let result = reject_private_destination(url, &enabled(&[])).await;
+        let result = apply_validation_mode(ValidationMode::Strict, url, result);
+        assert!(matches!(result, Err(ApiError::Unprocessable(_))));
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn warn_mode_logs_private_address_and_accepts_it() {
+        let url = "http://10.0.0.1/";
+        let result = reject_private_destination(url, &enabled(&[])).await;
+        assert!(apply_validation_mode(ValidationMode::Warn, url, result).is_ok());
+        assert!(logs_contain("accepting URL that failed validation"));
+        assert!(logs_contain("private address"));
+    }
+
+    #[test]
+    fn permissive_mode_accepts_failed_checks() {
+        let result = Err(ApiError::Unprocessable("Domain not allowed".to_string()));
+        assert!(
+            apply_validation_mode(ValidationMode::Permissive, "http://spam.test/", result).is_ok()
+        );
+    }
+
+    fn domain_filter(mode: UrlFilterMode, domains: &[&str]) -> DomainFilter {
+        DomainFilter::new(&UrlFilterSettings {
+            mode,
+            domains: domains.iter().map(|d| d.to_string()).collect(),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn allowlist_accepts_only_listed_domains() {
+        let filter = domain_filter(UrlFilterMode::Allowlist, &["partner.org", "*.company.com"]);
+        assert!(filter.check("https://partner.org/offer").is_ok());
+        assert!(filter.check("https://wiki.company.com/page").is_ok());
+        assert!(filter.check("https://WIKI.Company.com/page").is_ok());
+        assert!(matches!(
+            filter.check("https://www.example.com/"),
+            Err(ApiError::Unprocessable(_))
+        ));
+    }
+
+    #[test]
+    fn wildcard_matches_subdomains_but_not_the_bare_domain() {
+        let filter = domain_filter(UrlFilterMode::Allowlist, &["*.company.com"]);
+        assert!(filter.check("https://a.b.company.com/").is_ok());
+        assert!(filter.check("https://company.com/").is_err());
+        assert!(filter.check("https://evilcompany.com/").is_err());
+    }
+
+    #[test]
+    fn blocklist_rejects_only_listed_domains() {
+        let filter = domain_filter(UrlFilterMode::Blocklist, &["*.spam.test"]);
+        assert!(filter.check("https://www.spam.test/win").is_err());
+        assert!(filter.check("https://www.example.com/").is_ok());
+    }
+
+    #[test]
+    fn no_filter_lets_everything_through() {
+        let filter = domain_filter(UrlFilterMode::None, &["example.com"]);
+        assert!(filter.check("https://www.example.com/").is_ok());
+    }
+
+    #[test]
+    fn invalid_pattern_is_an_error() {
+        let settings = UrlFilterSettings {
+            mode: UrlFilterMode::Blocklist,
+            domains: vec!["[example.com".to_string()],
+        };
+        assert!(DomainFilter::new(&settings).is_err());
+    }
+
+    #[test]
+    fn normalize_url_converts_internationalized_hosts_to_punycode() {
+        assert_eq!(
+            normalize_url("https://münchen.de/").unwrap(),
+            "https://xn--mnchen-3ya.de/"
+        );
+    }
+
+    #[test]
+    fn normalize_url_uppercases_percent_escapes() {
+        assert_eq!(
+            normalize_url("https://example.com/caf%c3%a9?q=%2f").unwrap(),
+            "https://example.com/caf%C3%A9?q=%2f"
+        );
+    }
+
+    #[test]
+    fn normalize_url_is_idempotent() {
+        let once = normalize_url("https://Example.com/a b/ü#frag").unwrap();
+        assert_eq!(normalize_url(&once).unwrap(), once);
+    }
+
+    #[test]
+    fn normalize_url_rejects_unsupported_schemes() {
+        assert!(matches!(
+            normalize_url("ftp://example.com"),
+            Err(ApiError::InvalidScheme(_))
+        ));
+        assert!(matches!(
+            normalize_url("http:example.com"),
+            Err(ApiError::Unprocessable(_))
+        ));
+    }
+
+    // Unoptimized builds are roughly ten times slower, so debug test runs get
+    // a looser budget; `cargo test --release` checks the real one.
+    const NORMALIZE_BUDGET: Duration = if cfg!(debug_assertions) {
+        Duration::from_millis(1)
+    } else {
+        Duration::from_micros(100)
+    };
+
+    // The fastest of a few runs, so a descheduled thread does not fail the test
+    fn fastest_normalize(url: &str) -> Duration {
+        (0..5)
+            .map(|_| {
+                let start = std::time::Instant::now();
+                let normalized = normalize_url(std::hint::black_box(url));
+                let elapsed = start.elapsed();
+                assert!(normalized.is_ok(), "{url} should normalize");
+                elapsed
+            })
+            .min()
+            .unwrap()
+    }
+
+    #[test]
+    fn normalize_url_stays_within_budget_for_pathological_urls() {
+        let longest = format!("https://example.com/{}", "a".repeat(2048 - 20));
+        let query = (0..50)
+            .map(|i| format!("key{i}=value{i}"))
+            .collect::<Vec<_>>()
+            .join("&");
+        let nested = ('a'..='z').map(String::from).collect::<Vec<_>>().join("/");
+        let urls = [
+            longest,
+            format!("https://example.com/search?{query}"),
+            "https://bücher.example/straße?q=ä".to_string(),
+            format!("https://example.com/{nested}"),
+            "https://example.com:8443/path".to_string(),
+        ];
+
+        for url in &urls {
+            assert!(url.len() <= 2048);
+            let elapsed = fastest_normalize(url);
+            assert!(
+                elapsed < NORMALIZE_BUDGET,
+                "normalizing a {}-byte URL took {:?}",
+                url.len(),
+                elapsed
+            );
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+        #[test]
+        fn normalize_url_stays_within_budget_for_any_valid_url(
+            segments in prop::collection::vec("[a-zA-Z0-9._~-]{1,16}", 0..60),
+            params in prop::collection::vec(("[a-z]{1,8}", "[a-zA-Z0-9]{0,24}"), 0..30),
+        ) {
+            let query = params
+                .iter()
+                .map(|(k, v)| format!("{k}={v}"))
+                .collect::<Vec<_>>()
+                .join("&");
+            let url = format!("https://example.com/{}?{}", segments.join("/"), query);
+            prop_assume!(url.len() <= 2048);
+
+            let elapsed = fastest_normalize(&url);
+            prop_assert!(elapsed < NORMALIZE_BUDGET, "took {:?}", elapsed);
+        }
+    }
+}
@@ -3,6 +3,7 @@ document.addEventListener("DOMContentLoaded", () => {
     setActiveLink();
     initDashboardChart();
     initAnalyticsCharts();
+    initUrlAnalyticsChart();
     handleAuthForms();
     initUserSearch();
     initUrlActions();
@@ -137,6 +138,32 @@ function initAnalyticsCharts() {
     });
 }
 
+/**
+ * Draws the click timeline on the per-URL analytics page from the
+ * buckets embedded by the server.
+ */
+function initUrlAnalyticsChart() {
+    const chartCtx = document.getElementById('urlClicksChart');
+    const bucketsEl = document.getElementById('click-buckets');
+
+    if (!chartCtx || !bucketsEl) {
+        return; // Only run on the per-URL analytics page
+    }
+
+    const buckets = JSON.parse(bucketsEl.textContent);
+    new Chart(chartCtx, {
+        type: 'bar',
+        data: {
+            labels: buckets.map(b => new Date(b.timestamp).toLocaleString()),
+            datasets: [{
+                label: 'Clicks',
+                data: buckets.map(b => b.count),
+                backgroundColor: 'rgb(76, 161, 163)',
+            }]
+        },
+    });
+}
+
 /**
  * Handles front-end simulation of login and registration forms.
  * IMPORTANT: This is for UI demonstration only and is NOT secure.
@@ -405,8 +432,10 @@ function initUrlActions() {
             // Copy button clicked
             handleCopyUrl(originalUrl, shortCode);
         } else if (button.classList.contains('btn-sm') && button.classList.contains('btn-danger')) {
-            // Delete button clicked
-            handleDeleteUrl(shortCode, row);
+            // Delete button clicked; the surrounding form posts the deletion
+            if (!confirmDeleteUrl(shortCode)) {
+                e.preventDefault();
+            }
         }
     });
 }
@@ -443,39 +472,10 @@ function handleCopyUrl(originalUrl, shortCode) {
 }
 
 /**
- * Handles the delete URL functionality
+ * Asks for confirmation before a URL deletion form is submitted
  */
-function handleDeleteUrl(shortCode, row) {
-    if (confirm(`Are you sure you want to delete the short URL: ${shortCode}?`)) {
-        // In a real application, you would make an API call here to delete the URL
-        // fetch(`/api/urls/${shortCode}`, { method: 'DELETE' })
-        //   .then(response => response.json())
-        //   .then(data => {
-        //     if (data.success) {
-        //       row.remove(); // Remove the row from the table
-        //     } else {
-        //       alert('Failed to delete URL');
-        //     }
-        //   })
-        //   .catch(error => {
-        //     console.error('Error deleting URL:', error);
-        //     alert('Error deleting URL');
-        //   });
-
-        // For demo purposes, just remove the row
-        row.style.opacity = '0';
-        setTimeout(() => {
-            row.remove();
-            // Show a message or update UI if table becomes empty
-            const table = document.querySelector('.data-table');
-            if (table && table.querySelectorAll('tbody tr').length === 0) {
-                const emptyState = document.querySelector('.table-empty-state');
-                if (emptyState) {
-                    emptyState.classList.add('visible');
-                }
-            }
-        }, 300);
-    }
+function confirmDeleteUrl(shortCode) {
+    return confirm(`Are you sure you want to delete the short URL: ${shortCode}?`);
 }
 
 /**
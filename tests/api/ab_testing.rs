@@ -0,0 +1,100 @@
+// tests/api/ab_testing.rs
+
+// weighted A/B targets: redirects split across the variants by weight and
+// the per-variant click counts of GET /api/urls/{id}/stats
+
+// dependencies
+use crate::helpers::{assert_json_ok, spawn_app, spawn_app_with};
+use axum::http::StatusCode;
+use serde_json::json;
+use std::collections::HashMap;
+
+#[tokio::test]
+async fn redirects_are_split_by_weight_and_counted_per_variant() {
+    // Arrange
+    let app = spawn_app_with(|c| c.rate_limiting.enabled = false).await;
+    let response = app
+        .post_json_with_key(
+            "/api/shorten",
+            &json!({
+                "url": "https://www.example.com/landing",
+                "ab_targets": [
+                    { "url": "https://www.example.com/a", "weight": 60 },
+                    { "url": "https://www.example.com/b", "weight": 40 }
+                ]
+            }),
+        )
+        .await;
+    let body = assert_json_ok(response).await;
+    let id = body["data"]["id"].as_str().expect("no id").to_string();
+
+    // Act
+    let mut seen: HashMap<String, u64> = HashMap::new();
+    for _ in 0..1000 {
+        let response = app.get(&format!("/{}", id)).await;
+        assert_eq!(response.status(), StatusCode::TEMPORARY_REDIRECT);
+        let location = response
+            .headers()
+            .get("location")
+            .and_then(|v| v.to_str().ok())
+            .expect("A/B tested code did not redirect");
+        *seen.entry(location.to_string()).or_default() += 1;
+    }
+    let stats = assert_json_ok(
+        app.get_api_with_key(&format!("/api/urls/{}/stats", id))
+            .await,
+    )
+    .await;
+
+    // Assert
+    let a = seen["https://www.example.com/a"];
+    let b = seen["https://www.example.com/b"];
+    assert_eq!(a + b, 1000);
+    assert!((520..=680).contains(&a), "variant a got {} of 1000", a);
+
+    assert_eq!(stats["data"]["total_clicks"], 1000);
+    let variants = stats["data"]["variants"]
+        .as_array()
+        .expect("stats did not include variants");
+    assert_eq!(variants.len(), 2);
+    assert_eq!(variants[0]["variant"], 0);
+    assert_eq!(variants[0]["url"], "https://www.example.com/a");
+    assert_eq!(variants[0]["count"], a);
+    assert_eq!(variants[1]["url"], "https://www.example.com/b");
+    assert_eq!(variants[1]["count"], b);
+}
+
+#[tokio::test]
+async fn weights_not_summing_to_100_are_rejected() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .post_json_with_key(
+            "/api/shorten",
+            &json!({
+                "url": "https://www.example.com/landing",
+                "ab_targets": [
+                    { "url": "https://www.example.com/a", "weight": 60 },
+                    { "url": "https://www.example.com/b", "weight": 30 }
+                ]
+            }),
+        )
+        .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn stats_of_an_unknown_code_are_not_found() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app.get_api_with_key("/api/urls/nope404/stats").await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
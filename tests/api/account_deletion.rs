@@ -0,0 +1,184 @@
+// tests/api/account_deletion.rs
+
+// deleting an account through DELETE /api/v1/user; the user routes are only
+// mounted on Postgres, so the controller is called directly with in-memory repos
+
+// dependencies
+use crate::mock_repos::{MockUserRepo, TestAuth, auth_service_with_user};
+use axum::Json;
+use axum::extract::State;
+use axum::http::{StatusCode, header};
+use axum::response::IntoResponse;
+use axum_extra::extract::CookieJar;
+use secrecy::SecretString;
+use std::sync::Arc;
+use url_shortener_ztm_lib::configuration::CacheSettings;
+use url_shortener_ztm_lib::core::extractors::auth_user::AuthenticatedUser;
+use url_shortener_ztm_lib::database::SqliteUrlDatabase;
+use url_shortener_ztm_lib::features::auth::dto::SignInReq;
+use url_shortener_ztm_lib::features::users::UserService;
+use url_shortener_ztm_lib::features::users::controllers::{UserController, delete_account};
+use url_shortener_ztm_lib::features::users::dto::DeleteAccountReq;
+use url_shortener_ztm_lib::features::users::repositories::UserRepository;
+use url_shortener_ztm_lib::middleware::ClientMeta;
+use url_shortener_ztm_lib::routes::redirect::{CachedRedirect, RedirectKind, build_redirect_cache};
+
+const EMAIL: &str = "someone@example.com";
+const PASSWORD: &str = "Correct-Horse-42";
+
+async fn controller_with_user() -> (UserController, Arc<MockUserRepo>, AuthenticatedUser) {
+    let TestAuth {
+        service,
+        users,
+        user_id,
+        ..
+    } = auth_service_with_user(EMAIL, PASSWORD).await;
+    users
+        .link_oauth_identity(user_id, "github", "12345")
+        .await
+        .unwrap();
+    users.set_totp_secret(user_id, b"sealed").await.unwrap();
+
+    let ctrl = UserController {
+        svc: Arc::new(UserService::new(users.clone())),
+        auth_svc: Arc::new(service),
+        database: Arc::new(
+            SqliteUrlDatabase::in_memory_with_migrations()
+                .await
+                .expect("Failed to create database"),
+        ),
+        redirect_cache: build_redirect_cache(&CacheSettings::default()),
+    };
+    let user = AuthenticatedUser {
+        user_id,
+        token_version: 0,
+    };
+    (ctrl, users, user)
+}
+
+async fn delete(
+    ctrl: &UserController,
+    user: AuthenticatedUser,
+    password: &str,
+) -> axum::response::Response {
+    delete_account(
+        State(ctrl.clone()),
+        user,
+        CookieJar::new(),
+        Json(DeleteAccountReq {
+            password: SecretString::from(password),
+        }),
+    )
+    .await
+    .into_response()
+}
+
+#[tokio::test]
+async fn deleting_the_account_returns_204_and_clears_the_cookies() {
+    // Arrange
+    let (ctrl, _, user) = controller_with_user().await;
+
+    // Act
+    let response = delete(&ctrl, user, PASSWORD).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let cleared: Vec<String> = response
+        .headers()
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .map(|v| v.to_str().unwrap().to_string())
+        .collect();
+    for name in ["access_token=", "refresh_token="] {
+        assert!(
+            cleared
+                .iter()
+                .any(|c| c.starts_with(name) && c.contains("Max-Age=0")),
+            "{} was not cleared: {:?}",
+            name,
+            cleared
+        );
+    }
+}
+
+#[tokio::test]
+async fn the_deleted_account_and_its_data_are_gone() {
+    // Arrange
+    let (ctrl, users, user) = controller_with_user().await;
+    let user_id = user.user_id;
+
+    // Act
+    delete(&ctrl, user, PASSWORD).await;
+
+    // Assert
+    assert!(users.users().is_empty());
+    assert!(users.identities().is_empty());
+    assert!(users.totp(user_id).is_none());
+    let lookup = ctrl.svc.get_user_by_email(EMAIL).await;
+    assert!(lookup.is_err_and(|e| e.to_string().contains("User not found")));
+    let sign_in = ctrl
+        .auth_svc
+        .sign_in(
+            SignInReq {
+                email: EMAIL.to_string(),
+                password: SecretString::from(PASSWORD),
+                device_id: None,
+            },
+            ClientMeta {
+                ip: Some("203.0.113.7".parse().unwrap()),
+                user_agent: None,
+            },
+        )
+        .await;
+    assert!(sign_in.is_err());
+}
+
+#[tokio::test]
+async fn a_wrong_password_keeps_the_account() {
+    // Arrange
+    let (ctrl, users, user) = controller_with_user().await;
+
+    // Act
+    let response = delete(&ctrl, user, "wrong-password").await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert_eq!(users.users().len(), 1);
+    assert_eq!(users.identities().len(), 1);
+}
+
+#[tokio::test]
+async fn a_failure_other_than_the_password_is_a_server_error() {
+    // Arrange: the account is already gone, so its password cannot be read
+    let (ctrl, _, user) = controller_with_user().await;
+    delete(&ctrl, user.clone(), PASSWORD).await;
+
+    // Act
+    let response = delete(&ctrl, user, PASSWORD).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+}
+
+#[tokio::test]
+async fn cached_redirects_of_the_deleted_links_are_dropped() {
+    // Arrange
+    let (ctrl, _, user) = controller_with_user().await;
+    let url = "https://www.example.com/owned";
+    ctrl.database.insert_url("owned01", url).await.unwrap();
+    ctrl.database
+        .set_url_owner("owned01", user.user_id)
+        .await
+        .unwrap();
+    ctrl.redirect_cache.insert(
+        "owned01".to_string(),
+        CachedRedirect::new(url.to_string(), RedirectKind::Permanent),
+    );
+
+    // Act
+    let response = delete(&ctrl, user, PASSWORD).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert!(ctrl.redirect_cache.get("owned01").is_none());
+}
@@ -0,0 +1,68 @@
+// tests/api/admin_config.rs
+
+// the effective configuration at GET /api/v1/admin/config, behind the API key
+// and with every secret redacted
+
+// dependencies
+use crate::helpers::{TestApp, assert_json_ok, spawn_app};
+use axum::http::StatusCode;
+use secrecy::ExposeSecret;
+
+async fn get_config(app: &TestApp, accept: &str) -> reqwest::Response {
+    app.client
+        .get(app.url("/api/v1/admin/config"))
+        .header("x-api-key", app.api_key.to_string())
+        .header("accept", accept)
+        .send()
+        .await
+        .expect("Failed to execute GET request")
+}
+
+#[tokio::test]
+async fn config_is_returned_as_yaml_without_the_api_key() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = get_config(&app, "text/yaml").await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()["content-type"],
+        "text/yaml; charset=utf-8"
+    );
+    let body = response.text().await.unwrap();
+    assert!(body.contains("application:"));
+    assert!(body.contains("[REDACTED]"));
+    assert!(!body.contains(&app.api_key.to_string()));
+    let configuration = url_shortener_ztm_lib::get_configuration().unwrap();
+    assert!(!body.contains(configuration.application.jwt_secret_b64.expose_secret()));
+}
+
+#[tokio::test]
+async fn config_is_a_string_in_the_json_envelope_by_default() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = get_config(&app, "application/json").await;
+
+    // Assert
+    let body = assert_json_ok(response).await;
+    let yaml = body["data"].as_str().expect("data should be a string");
+    assert!(yaml.contains("rate_limiting:"));
+    assert!(!yaml.contains(&app.api_key.to_string()));
+}
+
+#[tokio::test]
+async fn config_requires_the_api_key() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app.get("/api/v1/admin/config").await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
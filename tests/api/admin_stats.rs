@@ -0,0 +1,98 @@
+// tests/api/admin_stats.rs
+
+// operator statistics at GET /admin/stats: JSON for API clients, a
+// self-refreshing page for browsers, both behind the API key
+
+// dependencies
+use crate::helpers::{TestApp, assert_json_ok, spawn_app_with};
+
+async fn get_stats(app: &TestApp, accept: &str) -> reqwest::Response {
+    app.client
+        .get(app.url("/admin/stats"))
+        .header("x-api-key", app.api_key.to_string())
+        .header("accept", accept)
+        .send()
+        .await
+        .expect("Failed to execute GET request")
+}
+
+#[tokio::test]
+async fn stats_are_returned_as_json() {
+    // Arrange
+    let app = spawn_app_with(|c| c.rate_limiting.enabled = false).await;
+
+    // Act
+    let response = get_stats(&app, "application/json").await;
+
+    // Assert
+    let body = assert_json_ok(response).await;
+    let data = &body["data"];
+    assert_eq!(data["total_urls"].as_u64(), Some(0));
+    assert_eq!(data["total_clicks"].as_u64(), Some(0));
+    assert!(data["db_size_bytes"].as_u64().is_some_and(|size| size > 0));
+    assert!(data["bloom_utilization_pct"].as_f64().is_some());
+    assert!(data["cache_hit_rate"].is_null());
+    assert!(data["uptime_seconds"].as_u64().is_some());
+}
+
+#[tokio::test]
+async fn total_urls_counts_a_shortened_url() {
+    // Arrange
+    let app = spawn_app_with(|c| c.rate_limiting.enabled = false).await;
+    let before = assert_json_ok(get_stats(&app, "application/json").await).await;
+
+    // Act
+    app.shorten_url("https://www.example.com/counted").await;
+
+    // Assert
+    let after = assert_json_ok(get_stats(&app, "application/json").await).await;
+    assert_eq!(
+        after["data"]["total_urls"].as_u64(),
+        before["data"]["total_urls"].as_u64().map(|n| n + 1)
+    );
+}
+
+#[tokio::test]
+async fn redirects_are_counted_as_clicks_and_cache_hits() {
+    // Arrange
+    let app = spawn_app_with(|c| c.rate_limiting.enabled = false).await;
+    let id = app.shorten_url("https://www.example.com/clicked").await;
+
+    // Act: the first redirect fills the cache, the second is served from it
+    for _ in 0..2 {
+        assert!(app.get_redirect_location(&id).await.is_some());
+    }
+
+    // Assert
+    let body = assert_json_ok(get_stats(&app, "application/json").await).await;
+    assert_eq!(body["data"]["total_clicks"].as_u64(), Some(2));
+    assert_eq!(body["data"]["cache_hit_rate"].as_f64(), Some(0.5));
+}
+
+#[tokio::test]
+async fn browsers_get_a_page_that_refreshes_itself() {
+    // Arrange
+    let app = spawn_app_with(|c| c.rate_limiting.enabled = false).await;
+    app.shorten_url("https://www.example.com/page").await;
+
+    // Act
+    let response = get_stats(&app, "text/html,application/xhtml+xml,*/*;q=0.8").await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let html = response.text().await.unwrap();
+    assert!(html.contains(r#"<meta http-equiv="refresh" content="30">"#));
+    assert!(html.contains(r#"<p class="stat-number" id="total-urls">1</p>"#));
+}
+
+#[tokio::test]
+async fn stats_require_an_api_key() {
+    // Arrange
+    let app = spawn_app_with(|c| c.rate_limiting.enabled = false).await;
+
+    // Act
+    let response = app.get("/admin/stats").await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 401);
+}
@@ -0,0 +1,259 @@
+// tests/api/admin_urls.rs
+
+// integration tests for the admin URL list (GET /admin/urls, POST /admin/urls/{id}/delete)
+
+// dependencies
+use crate::helpers::{TestApp, assert_redirect_to, spawn_app, spawn_app_with_session};
+use axum::http::StatusCode;
+
+async fn seed_urls(app: &TestApp) {
+    for (code, url) in [
+        ("adm0001", "https://www.example.com/spring-sale"),
+        ("adm0002", "https://shop.example.org/checkout"),
+        ("adm0003", "https://www.example.com/autumn-sale"),
+    ] {
+        app._database.insert_url(code, url).await.unwrap();
+    }
+}
+
+// Serves the app with one signed-up user, listed in `admin_user_ids` when
+// `admin` is set, and returns the `Cookie` header of their browser session
+async fn app_with_signed_in_user(admin: bool) -> (TestApp, String) {
+    spawn_app_with_session(|c, user_id| {
+        if admin {
+            c.application.admin_user_ids = vec![user_id];
+        }
+    })
+    .await
+}
+
+#[tokio::test]
+async fn url_list_renders_stored_short_codes() {
+    // Arrange
+    let app = spawn_app().await;
+    seed_urls(&app).await;
+
+    // Act
+    let response = app.get_with_key("/admin/urls").await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let html = response.text().await.unwrap();
+    for code in ["adm0001", "adm0002", "adm0003"] {
+        assert!(
+            html.contains(&format!("/admin/urls/{code}/delete")),
+            "{code}"
+        );
+    }
+    assert!(html.contains("Never"));
+    assert!(!html.contains("table-empty-state visible"));
+}
+
+#[tokio::test]
+async fn url_list_search_filters_rows() {
+    let app = spawn_app().await;
+    seed_urls(&app).await;
+
+    let html = app
+        .get_with_key("/admin/urls?q=sale")
+        .await
+        .text()
+        .await
+        .unwrap();
+
+    assert!(html.contains(">adm0001<"));
+    assert!(html.contains(">adm0003<"));
+    assert!(!html.contains(">adm0002<"));
+    assert!(html.contains("value=\"sale\""));
+}
+
+#[tokio::test]
+async fn url_list_truncates_long_destinations() {
+    let app = spawn_app().await;
+    let long_url = format!("https://www.example.com/{}", "a".repeat(100));
+    app._database
+        .insert_url("adm0004", &long_url)
+        .await
+        .unwrap();
+
+    let html = app.get_with_key("/admin/urls").await.text().await.unwrap();
+
+    // Full URL in the link and tooltip, shortened text in between; Tera escapes slashes
+    let escaped = |s: &str| s.replace('/', "&#x2F;");
+    assert!(html.contains(&format!("title=\"{}\"", escaped(&long_url))));
+    assert!(html.contains(&format!("{}…</a>", escaped(&long_url[..60]))));
+}
+
+#[tokio::test]
+async fn url_list_pages_past_the_first_25_rows() {
+    let app = spawn_app().await;
+    for i in 0..30 {
+        app._database
+            .insert_url(&format!("page{i:03}"), &format!("https://example.com/{i}"))
+            .await
+            .unwrap();
+    }
+
+    let first = app.get_with_key("/admin/urls").await.text().await.unwrap();
+    let second = app
+        .get_with_key("/admin/urls?page=2")
+        .await
+        .text()
+        .await
+        .unwrap();
+
+    assert!(first.contains(">page024<"));
+    assert!(!first.contains(">page025<"));
+    assert!(first.contains("page=2"));
+    assert!(second.contains(">page025<"));
+    assert!(second.contains(">page029<"));
+    assert!(second.contains("page=1"));
+    assert!(!second.contains("page=3"));
+}
+
+#[tokio::test]
+async fn delete_button_removes_the_code_and_returns_to_the_list() {
+    let app = spawn_app().await;
+    seed_urls(&app).await;
+
+    let response = app
+        .client
+        .post(app.url("/admin/urls/adm0002/delete"))
+        .header("x-api-key", app.api_key.to_string())
+        .send()
+        .await
+        .expect("Failed to execute POST request");
+
+    assert_redirect_to(response, "/admin/urls", StatusCode::SEE_OTHER).await;
+    let html = app.get_with_key("/admin/urls").await.text().await.unwrap();
+    assert!(!html.contains(">adm0002<"));
+    assert!(html.contains(">adm0001<"));
+}
+
+#[tokio::test]
+async fn deleting_an_unknown_code_is_not_found() {
+    let app = spawn_app().await;
+
+    let response = app
+        .client
+        .post(app.url("/admin/urls/missing1/delete"))
+        .header("x-api-key", app.api_key.to_string())
+        .send()
+        .await
+        .expect("Failed to execute POST request");
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn url_management_requires_the_api_key() {
+    // Arrange
+    let app = spawn_app().await;
+    seed_urls(&app).await;
+
+    // Act
+    let list = app.get("/admin/urls").await;
+    let analytics = app.get("/admin/urls/adm0001/analytics").await;
+    let delete = app
+        .client
+        .post(app.url("/admin/urls/adm0001/delete"))
+        .send()
+        .await
+        .expect("Failed to execute POST request");
+
+    // Assert
+    assert_eq!(list.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(analytics.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(delete.status(), StatusCode::UNAUTHORIZED);
+    let html = app.get_with_key("/admin/urls").await.text().await.unwrap();
+    assert!(html.contains(">adm0001<"), "the code is still stored");
+}
+
+#[tokio::test]
+async fn an_admin_session_can_list_and_delete_urls() {
+    // Arrange
+    let (app, cookie) = app_with_signed_in_user(true).await;
+    seed_urls(&app).await;
+
+    // Act
+    let list = app
+        .client
+        .get(app.url("/admin/urls"))
+        .header("Cookie", &cookie)
+        .send()
+        .await
+        .expect("Failed to execute GET request");
+    let delete = app
+        .client
+        .post(app.url("/admin/urls/adm0002/delete"))
+        .header("Cookie", &cookie)
+        .send()
+        .await
+        .expect("Failed to execute POST request");
+
+    // Assert
+    assert_eq!(list.status(), StatusCode::OK);
+    assert!(list.text().await.unwrap().contains(">adm0002<"));
+    assert_redirect_to(delete, "/admin/urls", StatusCode::SEE_OTHER).await;
+    let html = app.get_with_key("/admin/urls").await.text().await.unwrap();
+    assert!(!html.contains(">adm0002<"));
+}
+
+#[tokio::test]
+async fn a_session_without_admin_rights_is_forbidden() {
+    // Arrange
+    let (app, cookie) = app_with_signed_in_user(false).await;
+    seed_urls(&app).await;
+
+    // Act
+    let list = app
+        .client
+        .get(app.url("/admin/urls"))
+        .header("Cookie", &cookie)
+        .send()
+        .await
+        .expect("Failed to execute GET request");
+    let delete = app
+        .client
+        .post(app.url("/admin/urls/adm0001/delete"))
+        .header("Cookie", &cookie)
+        .send()
+        .await
+        .expect("Failed to execute POST request");
+
+    // Assert
+    assert_eq!(list.status(), StatusCode::FORBIDDEN);
+    assert_eq!(delete.status(), StatusCode::FORBIDDEN);
+    let html = app.get_with_key("/admin/urls").await.text().await.unwrap();
+    assert!(html.contains(">adm0001<"), "the code is still stored");
+}
+
+#[tokio::test]
+async fn the_analytics_page_is_open_to_admin_sessions_only() {
+    // Arrange
+    let (admin_app, admin_cookie) = app_with_signed_in_user(true).await;
+    let (user_app, user_cookie) = app_with_signed_in_user(false).await;
+    seed_urls(&admin_app).await;
+    seed_urls(&user_app).await;
+
+    // Act
+    let as_admin = admin_app
+        .client
+        .get(admin_app.url("/admin/urls/adm0001/analytics"))
+        .header("Cookie", &admin_cookie)
+        .send()
+        .await
+        .expect("Failed to execute GET request");
+    let as_user = user_app
+        .client
+        .get(user_app.url("/admin/urls/adm0001/analytics"))
+        .header("Cookie", &user_cookie)
+        .send()
+        .await
+        .expect("Failed to execute GET request");
+
+    // Assert
+    assert_eq!(as_admin.status(), StatusCode::OK);
+    assert!(as_admin.text().await.unwrap().contains("adm0001"));
+    assert_eq!(as_user.status(), StatusCode::FORBIDDEN);
+}
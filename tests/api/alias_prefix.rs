@@ -0,0 +1,86 @@
+// tests/api/alias_prefix.rs
+
+// shortener.alias_prefix is prepended to every custom alias before it is stored
+
+// dependencies
+use crate::helpers::{TestApp, assert_json_ok, spawn_app_with};
+use serde_json::json;
+use url_shortener_ztm_lib::generator::DEFAULT_ALPHABET;
+
+async fn spawn_app_with_prefix(prefix: &str) -> TestApp {
+    let prefix = prefix.to_string();
+    spawn_app_with(move |c| {
+        let alphabet: String = DEFAULT_ALPHABET.iter().collect();
+        c.shortener.alphabet = Some(format!("{}-", alphabet));
+        c.shortener.alias_prefix = Some(prefix);
+    })
+    .await
+}
+
+#[tokio::test]
+async fn alias_is_stored_with_the_prefix_and_redirects() {
+    // Arrange
+    let app = spawn_app_with_prefix("t1-").await;
+
+    // Act
+    let response = app
+        .post_json_with_key(
+            "/api/shorten",
+            &json!({ "url": "https://www.example.com/tenant", "alias": "mylink" }),
+        )
+        .await;
+
+    // Assert
+    let body = assert_json_ok(response).await;
+    assert_eq!(body["data"]["id"].as_str(), Some("t1-mylink"));
+    let shortened = body["data"]["shortened_url"]
+        .as_str()
+        .expect("missing shortened_url");
+    assert!(shortened.ends_with("/t1-mylink"), "{}", shortened);
+    assert_eq!(
+        app.get_redirect_location("t1-mylink").await.as_deref(),
+        Some("https://www.example.com/tenant")
+    );
+    assert!(app.get_redirect_location("mylink").await.is_none());
+}
+
+#[tokio::test]
+async fn batch_aliases_are_prefixed_too() {
+    // Arrange
+    let app = spawn_app_with_prefix("t2-").await;
+
+    // Act
+    let response = app
+        .post_json_with_key(
+            "/api/shorten/batch",
+            &json!([{ "url": "https://www.example.com/batch-tenant", "alias": "mine" }]),
+        )
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 207);
+    let body: serde_json::Value = response.json().await.expect("invalid JSON");
+    assert_eq!(body["data"][0]["id"].as_str(), Some("t2-mine"));
+    assert_eq!(
+        app.get_redirect_location("t2-mine").await.as_deref(),
+        Some("https://www.example.com/batch-tenant")
+    );
+}
+
+#[tokio::test]
+async fn alias_must_leave_room_for_the_prefix() {
+    // Arrange
+    let app = spawn_app_with_prefix("t1-").await;
+    let alias = "a".repeat(62);
+
+    // Act
+    let response = app
+        .post_json_with_key(
+            "/api/shorten",
+            &json!({ "url": "https://www.example.com/long-alias", "alias": alias }),
+        )
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 422);
+}
@@ -0,0 +1,190 @@
+// tests/api/api_keys.rs
+
+use crate::helpers::{
+    assert_json_ok, build_test_state, spawn_app, spawn_app_with, test_configuration,
+};
+use crate::mock_db::MockUrlDatabase;
+use axum::http::StatusCode;
+use std::sync::Arc;
+use url_shortener_ztm_lib::configuration::{ApiKey, ApiKeySecret};
+use uuid::Uuid;
+
+#[tokio::test]
+async fn single_configured_key_is_accepted() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app.get_api_with_key("/api/v1/auth/api-keys").await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn every_configured_key_is_accepted() {
+    // Arrange
+    let second = Uuid::new_v4();
+    let app = spawn_app_with(|c| {
+        c.application.api_keys.push(ApiKey {
+            key: second.into(),
+            label: Some("mobile-app".to_string()),
+        })
+    })
+    .await;
+
+    // Act
+    let with_first = app.get_api_with_key("/api/v1/auth/api-keys").await;
+    let with_second = app
+        .client
+        .get(app.api("/api/v1/auth/api-keys"))
+        .header("x-api-key", second.to_string())
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(with_first.status(), StatusCode::OK);
+    assert_eq!(with_second.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn keys_differing_only_in_the_last_byte_are_told_apart() {
+    // Arrange
+    let mut bytes = [0x5a; 16];
+    let configured = Uuid::from_bytes(bytes);
+    bytes[15] ^= 0x01;
+    let neighbour = Uuid::from_bytes(bytes);
+    let app = spawn_app_with(|c| {
+        c.application.api_keys.push(ApiKey {
+            key: configured.into(),
+            label: None,
+        })
+    })
+    .await;
+    let get_with = |key: Uuid| {
+        app.client
+            .get(app.api("/api/v1/auth/api-keys"))
+            .header("x-api-key", key.to_string())
+            .send()
+    };
+
+    // Act
+    let with_configured = get_with(configured).await.unwrap();
+    let with_neighbour = get_with(neighbour).await.unwrap();
+
+    // Assert
+    assert_eq!(with_configured.status(), StatusCode::OK);
+    assert_eq!(with_neighbour.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn key_configured_as_a_bcrypt_hash_is_accepted() {
+    // Arrange
+    let hashed = Uuid::new_v4();
+    let hash = bcrypt::hash(hashed.hyphenated().to_string(), 4).unwrap();
+    let app = spawn_app_with(|c| {
+        c.rate_limiting.enabled = false;
+        c.application.api_keys.push(ApiKey {
+            key: ApiKeySecret::Bcrypt(hash),
+            label: Some("billing".to_string()),
+        })
+    })
+    .await;
+    let get_with = |key: Uuid| {
+        app.client
+            .get(app.api("/api/v1/auth/api-keys"))
+            .header("x-api-key", key.to_string())
+            .send()
+    };
+
+    // Act
+    let with_plain = app.get_api_with_key("/api/v1/auth/api-keys").await;
+    let with_hashed = get_with(hashed).await.unwrap();
+    let with_unknown = get_with(Uuid::new_v4()).await.unwrap();
+
+    // Assert
+    assert_eq!(with_plain.status(), StatusCode::OK);
+    assert_eq!(with_hashed.status(), StatusCode::OK);
+    assert_eq!(with_unknown.status(), StatusCode::UNAUTHORIZED);
+    let body = assert_json_ok(with_hashed).await;
+    assert!(
+        body["data"][1]["key"]
+            .as_str()
+            .unwrap()
+            .starts_with("$2b$04$***")
+    );
+}
+
+#[tokio::test]
+async fn unknown_key_is_rejected_with_several_configured() {
+    // Arrange
+    let app = spawn_app_with(|c| {
+        c.application.api_keys.push(ApiKey {
+            key: Uuid::new_v4().into(),
+            label: None,
+        })
+    })
+    .await;
+
+    // Act
+    let response = app
+        .client
+        .get(app.api("/api/v1/auth/api-keys"))
+        .header("x-api-key", Uuid::new_v4().to_string())
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn api_key_listing_masks_keys_and_shows_labels() {
+    // Arrange
+    let second = Uuid::new_v4();
+    let app = spawn_app_with(|c| {
+        c.application.api_keys.push(ApiKey {
+            key: second.into(),
+            label: Some("mobile-app".to_string()),
+        })
+    })
+    .await;
+
+    // Act
+    let response = app.get_api_with_key("/api/v1/auth/api-keys").await;
+
+    // Assert
+    let body = assert_json_ok(response).await;
+    let keys = body["data"].as_array().expect("data should be an array");
+    assert_eq!(keys.len(), 2);
+    assert_eq!(keys[0]["label"], "development");
+    assert_eq!(keys[1]["label"], "mobile-app");
+    assert!(!body.to_string().contains(&app.api_key.to_string()));
+    assert!(!body.to_string().contains(&second.to_string()));
+    let masked = keys[1]["key"].as_str().unwrap();
+    assert!(masked.starts_with(&second.to_string()[..8]));
+    assert!(masked.ends_with("-************"));
+}
+
+#[tokio::test]
+async fn debug_output_of_the_state_redacts_keys() {
+    // Arrange
+    let secret = Uuid::new_v4();
+    let configuration = test_configuration(|c| {
+        c.application.api_keys = vec![ApiKey {
+            key: secret.into(),
+            label: None,
+        }]
+    });
+    let state = build_test_state(&configuration, Arc::new(MockUrlDatabase::new())).await;
+
+    // Act
+    let debug = format!("{:?}", state);
+
+    // Assert
+    assert!(debug.contains("api_keys: \"[REDACTED]\""));
+    assert!(debug.contains("jwt: \"[REDACTED]\""));
+    assert!(!debug.contains(&secret.to_string()));
+}
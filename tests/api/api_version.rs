@@ -0,0 +1,96 @@
+// tests/api/api_version.rs
+
+// dependencies
+use crate::helpers::{assert_json_ok, spawn_app};
+use url_shortener_ztm_lib::response::API_VERSION;
+
+#[tokio::test]
+async fn health_check_reports_api_version() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app.get_api("/api/health_check").await;
+
+    // Assert
+    assert_eq!(response.headers()["x-api-version"], API_VERSION);
+    let body = assert_json_ok(response).await;
+    assert_eq!(body["version"], API_VERSION);
+}
+
+#[test]
+fn api_version_is_major_minor_of_crate_version() {
+    let crate_version = env!("CARGO_PKG_VERSION");
+
+    assert_eq!(API_VERSION.split('.').count(), 2);
+    assert!(crate_version.starts_with(&format!("{}.", API_VERSION)));
+}
+
+#[tokio::test]
+async fn shorten_response_reports_api_version() {
+    let app = spawn_app().await;
+
+    let response = app
+        .post_api_with_key("/api/shorten", "https://www.example.com/versioned")
+        .await;
+
+    assert_eq!(response.headers()["x-api-version"], API_VERSION);
+    let body = assert_json_ok(response).await;
+    assert_eq!(body["version"], API_VERSION);
+}
+
+#[tokio::test]
+async fn error_response_reports_api_version() {
+    let app = spawn_app().await;
+
+    let response = app.get_api("/api/urls/doesnotexist").await;
+
+    assert_eq!(response.status().as_u16(), 404);
+    assert_eq!(response.headers()["x-api-version"], API_VERSION);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["success"], false);
+    assert_eq!(body["version"], API_VERSION);
+}
+
+#[tokio::test]
+async fn matching_accept_version_is_served() {
+    let app = spawn_app().await;
+
+    let response = app
+        .client
+        .get(app.api("/api/health_check"))
+        .header("accept-version", API_VERSION)
+        .send()
+        .await
+        .unwrap();
+
+    assert_json_ok(response).await;
+}
+
+#[tokio::test]
+async fn unsupported_accept_version_returns_400() {
+    let app = spawn_app().await;
+
+    let response = app
+        .client
+        .get(app.api("/api/health_check"))
+        .header("accept-version", "99.0")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(response.status().as_u16(), 400);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["message"], "This API version is not supported");
+    assert_eq!(body["version"], API_VERSION);
+}
+
+#[tokio::test]
+async fn v2_namespace_serves_health_check() {
+    let app = spawn_app().await;
+
+    let response = app.get_api("/api/v2/health_check").await;
+
+    let body = assert_json_ok(response).await;
+    assert_eq!(body["data"]["db_ok"], true);
+}
@@ -0,0 +1,164 @@
+// tests/api/auth_audit.rs
+
+// the authentication audit log behind GET /api/v1/user/security-events; the
+// auth routes are only mounted on Postgres, so the service and controller are
+// called directly with in-memory repos
+
+// dependencies
+use crate::mock_repos::{TestAuth, auth_service_with_user};
+use axum::extract::{Query, State};
+use secrecy::SecretString;
+use std::sync::Arc;
+use url_shortener_ztm_lib::core::extractors::auth_user::AuthenticatedUser;
+use url_shortener_ztm_lib::features::auth::AuthService;
+use url_shortener_ztm_lib::features::auth::controllers::{AuthController, security_events};
+use url_shortener_ztm_lib::features::auth::dto::{SecurityEventsQuery, SignInReq};
+use url_shortener_ztm_lib::features::auth::repositories::AuthEvent;
+use url_shortener_ztm_lib::features::users::UserService;
+use url_shortener_ztm_lib::middleware::ClientMeta;
+
+const EMAIL: &str = "someone@example.com";
+const PASSWORD: &str = "Correct-Horse-42";
+
+async fn service_with_user() -> TestAuth {
+    auth_service_with_user(EMAIL, PASSWORD).await
+}
+
+fn meta() -> ClientMeta {
+    ClientMeta {
+        ip: Some("203.0.113.7".parse().unwrap()),
+        user_agent: Some("audit-test".to_string()),
+    }
+}
+
+async fn sign_in(service: &AuthService) {
+    service
+        .sign_in(
+            SignInReq {
+                email: EMAIL.to_string(),
+                password: SecretString::from(PASSWORD),
+                device_id: None,
+            },
+            meta(),
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn sign_in_records_a_sign_in_event() {
+    // Arrange
+    let t = service_with_user().await;
+
+    // Act
+    sign_in(&t.service).await;
+
+    // Assert
+    assert_eq!(t.auth.events(t.user_id), vec![AuthEvent::SignIn]);
+    let entries = t.service.auth_events(t.user_id, 0, 10).await.unwrap();
+    assert_eq!(entries[0].ip, Some("203.0.113.7".parse().unwrap()));
+    assert_eq!(entries[0].user_agent.as_deref(), Some("audit-test"));
+    assert_eq!(
+        entries[0].metadata,
+        Some(serde_json::json!({ "method": "password" }))
+    );
+}
+
+#[tokio::test]
+async fn a_failed_sign_in_is_not_recorded_as_a_sign_in() {
+    // Arrange
+    let t = service_with_user().await;
+
+    // Act
+    let result = t
+        .service
+        .sign_in(
+            SignInReq {
+                email: EMAIL.to_string(),
+                password: SecretString::from("wrong-password"),
+                device_id: None,
+            },
+            meta(),
+        )
+        .await;
+
+    // Assert
+    assert!(result.is_err());
+    assert!(t.auth.events(t.user_id).is_empty());
+}
+
+#[tokio::test]
+async fn change_password_records_the_change_and_the_revoked_tokens() {
+    // Arrange
+    let t = service_with_user().await;
+
+    // Act
+    t.service
+        .change_password(
+            t.user_id,
+            &SecretString::from(PASSWORD),
+            &SecretString::from("Another-Horse-43"),
+            meta(),
+        )
+        .await
+        .unwrap();
+
+    // Assert
+    assert_eq!(
+        t.auth.events(t.user_id),
+        vec![AuthEvent::PasswordChanged, AuthEvent::TokenRevoked]
+    );
+    assert_eq!(t.users.users()[0].jwt_token_version, 1);
+}
+
+#[tokio::test]
+async fn security_events_are_paginated_newest_first() {
+    // Arrange
+    let t = service_with_user().await;
+    sign_in(&t.service).await;
+    t.service
+        .sign_out(t.user_id, "phone", meta())
+        .await
+        .unwrap();
+    t.service.sign_out_all(t.user_id, meta()).await.unwrap();
+    let ctrl = AuthController {
+        auth_svc: Arc::new(t.service),
+        user_svc: Arc::new(UserService::new(t.users.clone())),
+    };
+    let user = AuthenticatedUser {
+        user_id: t.user_id,
+        token_version: 1,
+    };
+
+    // Act
+    let first = security_events(
+        State(ctrl.clone()),
+        user.clone(),
+        Query(SecurityEventsQuery {
+            page: Some(1),
+            per_page: Some(2),
+        }),
+    )
+    .await
+    .unwrap();
+    let second = security_events(
+        State(ctrl),
+        user,
+        Query(SecurityEventsQuery {
+            page: Some(2),
+            per_page: Some(2),
+        }),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    let first = first.data.unwrap();
+    let events: Vec<AuthEvent> = first.items.iter().map(|e| e.event_type).collect();
+    assert_eq!(events, vec![AuthEvent::TokenRevoked, AuthEvent::SignOut]);
+    assert!(first.has_more);
+    let second = second.data.unwrap();
+    let events: Vec<AuthEvent> = second.items.iter().map(|e| e.event_type).collect();
+    assert_eq!(events, vec![AuthEvent::SignIn]);
+    assert!(!second.has_more);
+}
@@ -0,0 +1,90 @@
+// tests/api/bloom_rebuild.rs
+
+// rebuilding the Bloom filter from the database with POST /admin/bloom/rebuild,
+// e.g. after codes were stored without going through the filter
+
+// dependencies
+use crate::helpers::{assert_json_ok, build_test_state, spawn_app_with, test_configuration};
+use axum::extract::State;
+use std::sync::Arc;
+use url_shortener_ztm_lib::database::{SqliteUrlDatabase, UrlDatabase};
+use url_shortener_ztm_lib::routes::post_rebuild_bloom;
+
+#[tokio::test]
+async fn rebuild_adds_every_stored_code_to_the_filter() {
+    // Arrange: codes written straight to the database, as a restore would
+    let database = Arc::new(
+        SqliteUrlDatabase::in_memory_with_migrations()
+            .await
+            .expect("Failed to create database"),
+    );
+    let state = build_test_state(&test_configuration(|_| {}), database.clone()).await;
+    let rows: Vec<(String, String)> = (0..1000)
+        .map(|i| {
+            (
+                format!("rb{:05}", i),
+                format!("https://www.example.com/{}", i),
+            )
+        })
+        .collect();
+    let pairs: Vec<(&str, &str)> = rows.iter().map(|(c, u)| (c.as_str(), u.as_str())).collect();
+    database.insert_urls_batch(&pairs).await.unwrap();
+    assert!(
+        rows.iter()
+            .any(|(code, _)| !state.blooms().s2l.may_contain(code))
+    );
+
+    // Act
+    let response = post_rebuild_bloom(State(state.clone())).await.unwrap();
+
+    // Assert
+    let result = response.data.unwrap();
+    assert!(result.rebuilt);
+    assert_eq!(result.items_added, 1000);
+    assert!(
+        rows.iter()
+            .all(|(code, _)| state.blooms().s2l.may_contain(code))
+    );
+}
+
+#[tokio::test]
+async fn rebuild_returns_the_item_count() {
+    // Arrange
+    let app = spawn_app_with(|c| c.rate_limiting.enabled = false).await;
+    app.shorten_url("https://www.example.com/one").await;
+    app.shorten_url_with_alias("https://www.example.com/two", "second")
+        .await;
+
+    // Act
+    let response = app
+        .client
+        .post(app.url("/admin/bloom/rebuild"))
+        .header("x-api-key", app.api_key.to_string())
+        .send()
+        .await
+        .expect("Failed to execute POST request");
+
+    // Assert
+    let body = assert_json_ok(response).await;
+    assert_eq!(body["data"]["rebuilt"].as_bool(), Some(true));
+    // the generated code, and the second URL's code and alias
+    assert_eq!(body["data"]["items_added"].as_u64(), Some(3));
+    assert!(body["data"]["duration_ms"].as_u64().is_some());
+}
+
+#[tokio::test]
+async fn rebuild_requires_an_api_key() {
+    // Arrange
+    let app = spawn_app_with(|c| c.rate_limiting.enabled = false).await;
+
+    // Act
+    let response = app
+        .client
+        .post(app.url("/admin/bloom/rebuild"))
+        .send()
+        .await
+        .expect("Failed to execute POST request");
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 401);
+}
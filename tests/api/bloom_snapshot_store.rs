@@ -0,0 +1,112 @@
+// tests/api/bloom_snapshot_store.rs
+
+// storing Bloom filter snapshots in the database or an S3 bucket (bloom.storage)
+
+// dependencies
+use std::sync::Arc;
+use url_shortener_ztm_lib::configuration::BloomConfig;
+use url_shortener_ztm_lib::database::{SqliteUrlDatabase, UrlDatabase};
+use url_shortener_ztm_lib::shortcode::bloom_filter::{
+    LocalBloom, ProbSet, S2L_SNAPSHOT_KEY, build_bloom_state,
+};
+use url_shortener_ztm_lib::shortcode::snapshot_store::{
+    BloomSnapshotStore, DatabaseBloomSnapshotStore,
+};
+
+async fn in_memory_database() -> Arc<dyn UrlDatabase> {
+    Arc::new(
+        SqliteUrlDatabase::in_memory_with_migrations()
+            .await
+            .expect("Failed to create database"),
+    )
+}
+
+async fn assert_round_trips(store: &dyn BloomSnapshotStore) {
+    assert!(store.load("missing").await.unwrap().is_none());
+
+    store.save("snapshot", b"first").await.unwrap();
+    store.save("snapshot", b"second").await.unwrap();
+
+    assert_eq!(
+        store.load("snapshot").await.unwrap().as_deref(),
+        Some(&b"second"[..])
+    );
+}
+
+#[tokio::test]
+async fn database_store_round_trips_snapshots() {
+    let store = DatabaseBloomSnapshotStore::new(in_memory_database().await);
+
+    assert_round_trips(&store).await;
+}
+
+#[tokio::test]
+async fn bloom_state_is_loaded_from_the_store() {
+    // Arrange: a snapshot holding a code the database does not have
+    let database = in_memory_database().await;
+    let store: Arc<dyn BloomSnapshotStore> =
+        Arc::new(DatabaseBloomSnapshotStore::new(database.clone()));
+    let config = BloomConfig {
+        expected_items: 1_000,
+        false_positive_rate: 0.01,
+        ..BloomConfig::default()
+    };
+    let bloom = LocalBloom::from_items(["snap01"], 1_000, 0.01);
+    store
+        .save(S2L_SNAPSHOT_KEY, &bloom.snapshot().unwrap())
+        .await
+        .unwrap();
+
+    // Act
+    let blooms = build_bloom_state(&database, &store, &config).await.unwrap();
+
+    // Assert
+    assert!(blooms.s2l.may_contain("snap01"));
+}
+
+#[cfg(feature = "s3")]
+mod s3 {
+    use super::assert_round_trips;
+    use aws_sdk_s3::config::{BehaviorVersion, Credentials, Region};
+    use testcontainers_modules::{localstack::LocalStack, testcontainers::runners::AsyncRunner};
+    use url_shortener_ztm_lib::shortcode::snapshot_store::S3BloomSnapshotStore;
+
+    /// Starts LocalStack in a container, so it needs a Docker daemon.
+    ///
+    /// This test is ignored by default; run it explicitly when Docker is available.
+    #[tokio::test]
+    #[ignore]
+    async fn s3_store_round_trips_snapshots() {
+        // Arrange
+        let localstack = LocalStack::default()
+            .start()
+            .await
+            .expect("Failed to start LocalStack");
+        let port = localstack
+            .get_host_port_ipv4(4566)
+            .await
+            .expect("Failed to get the LocalStack port");
+        let config = aws_sdk_s3::Config::builder()
+            .behavior_version(BehaviorVersion::latest())
+            .region(Region::new("us-east-1"))
+            .credentials_provider(Credentials::new("test", "test", None, None, "test"))
+            .endpoint_url(format!("http://127.0.0.1:{}", port))
+            .force_path_style(true)
+            .build();
+        let client = aws_sdk_s3::Client::from_conf(config);
+        client
+            .create_bucket()
+            .bucket("bloom")
+            .send()
+            .await
+            .expect("Failed to create the bucket");
+        let store = S3BloomSnapshotStore::from_client(
+            client,
+            "bloom".to_string(),
+            Some("snapshots/".to_string()),
+        );
+
+        // Act & Assert
+        assert_round_trips(&store).await;
+    }
+}
@@ -0,0 +1,30 @@
+// tests/api/catch_panic.rs
+
+// dependencies
+use crate::helpers::{assert_json_ok, spawn_app};
+
+#[tokio::test]
+async fn panicking_handler_returns_500_instead_of_dropping_connection() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app.get_api("/api/debug/panic").await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 500);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["success"], false);
+    assert_eq!(body["message"], "An unexpected error occurred");
+    assert_eq!(body["error_code"], "INTERNAL_ERROR");
+}
+
+#[tokio::test]
+async fn server_keeps_serving_after_a_handler_panics() {
+    let app = spawn_app().await;
+    app.get_api("/api/debug/panic").await;
+
+    let response = app.get_api("/api/health_check").await;
+
+    assert_json_ok(response).await;
+}
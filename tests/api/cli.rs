@@ -0,0 +1,41 @@
+// tests/api/cli.rs
+
+// tests for the command line interface of the binary
+
+// dependencies
+use std::process::Command;
+
+#[test]
+fn help_lists_every_subcommand() {
+    // Act
+    let output = Command::new(env!("CARGO_BIN_EXE_url-shortener-ztm"))
+        .arg("--help")
+        .output()
+        .expect("Failed to run the binary");
+
+    // Assert
+    assert!(output.status.success());
+    let help = String::from_utf8_lossy(&output.stdout);
+    for subcommand in ["serve", "rebuild-bloom", "migrate", "create-api-key"] {
+        assert!(
+            help.contains(subcommand),
+            "{} missing from:\n{}",
+            subcommand,
+            help
+        );
+    }
+}
+
+#[test]
+fn create_api_key_prints_a_uuid() {
+    // Act
+    let output = Command::new(env!("CARGO_BIN_EXE_url-shortener-ztm"))
+        .arg("create-api-key")
+        .output()
+        .expect("Failed to run the binary");
+
+    // Assert
+    assert!(output.status.success());
+    let key = String::from_utf8_lossy(&output.stdout);
+    assert!(uuid::Uuid::parse_str(key.trim()).is_ok(), "{}", key);
+}
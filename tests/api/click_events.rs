@@ -0,0 +1,127 @@
+// tests/api/click_events.rs
+
+// click events published after a redirect is recorded (events.backend)
+
+// dependencies
+use crate::helpers::spawn_app_with_state;
+use async_trait::async_trait;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use url_shortener_ztm_lib::infrastructure::events::{ClickEvent, EventPublisher};
+
+#[derive(Default)]
+struct RecordingPublisher {
+    events: Mutex<Vec<ClickEvent>>,
+}
+
+#[async_trait]
+impl EventPublisher for RecordingPublisher {
+    async fn publish(&self, event: ClickEvent) -> anyhow::Result<()> {
+        self.events.lock().unwrap().push(event);
+        Ok(())
+    }
+}
+
+// Events are published in the background, so wait for one to arrive
+async fn wait_for_event(publisher: &RecordingPublisher) -> Option<ClickEvent> {
+    for _ in 0..50 {
+        if let Some(event) = publisher.events.lock().unwrap().first() {
+            return Some(event.clone());
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    None
+}
+
+#[tokio::test]
+async fn redirect_publishes_a_click_event() {
+    // Arrange
+    let publisher = Arc::new(RecordingPublisher::default());
+    let app = spawn_app_with_state(
+        |c| c.rate_limiting.enabled = false,
+        |builder| builder.event_publisher(publisher.clone()),
+    )
+    .await;
+    let code = app.shorten_url("https://www.example.com/streamed").await;
+
+    // Act
+    app.client
+        .get(app.url(&format!("/{}", code)))
+        .header("referer", "https://news.example.org/")
+        .header("user-agent", "Mozilla/5.0 (X11; Linux x86_64)")
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    let event = wait_for_event(&publisher)
+        .await
+        .expect("no click event was published");
+    assert_eq!(event.code, code);
+    assert_eq!(event.ip.as_deref(), Some("127.0.0.1"));
+    assert_eq!(event.referrer.as_deref(), Some("https://news.example.org/"));
+    assert_eq!(event.device_type.as_deref(), Some("desktop"));
+    assert_eq!(event.country_code, None);
+}
+
+#[cfg(feature = "kafka")]
+mod kafka {
+    use super::*;
+    use chrono::Utc;
+    use rdkafka::consumer::{Consumer, StreamConsumer};
+    use rdkafka::{ClientConfig, Message};
+    use testcontainers_modules::{kafka::Kafka, testcontainers::runners::AsyncRunner};
+    use url_shortener_ztm_lib::infrastructure::events::KafkaEventPublisher;
+
+    /// Starts Kafka in a container, so it needs a Docker daemon.
+    ///
+    /// This test is ignored by default; run it explicitly when Docker is available.
+    #[tokio::test]
+    #[ignore]
+    async fn kafka_publisher_produces_json_keyed_by_code() {
+        // Arrange
+        let kafka = Kafka::default()
+            .start()
+            .await
+            .expect("Failed to start Kafka");
+        let port = kafka
+            .get_host_port_ipv4(testcontainers_modules::kafka::KAFKA_PORT)
+            .await
+            .expect("Failed to get the Kafka port");
+        let brokers = format!("127.0.0.1:{}", port);
+        let publisher = KafkaEventPublisher::new(&brokers, "clicks".to_string())
+            .expect("Failed to create the producer");
+        let consumer: StreamConsumer = ClientConfig::new()
+            .set("bootstrap.servers", &brokers)
+            .set("group.id", "click-events-test")
+            .set("auto.offset.reset", "earliest")
+            .create()
+            .expect("Failed to create the consumer");
+        let event = ClickEvent {
+            code: "kafka1".to_string(),
+            clicked_at: Utc::now(),
+            ip: Some("203.0.113.7".to_string()),
+            referrer: None,
+            variant: None,
+            device_type: Some("mobile".to_string()),
+            country_code: Some("NL".to_string()),
+        };
+
+        // Act
+        publisher
+            .publish(event.clone())
+            .await
+            .expect("Failed to publish");
+
+        // Assert
+        consumer.subscribe(&["clicks"]).unwrap();
+        let message = tokio::time::timeout(Duration::from_secs(30), consumer.recv())
+            .await
+            .expect("no message within 30s")
+            .expect("Failed to consume");
+        assert_eq!(message.key(), Some(&b"kafka1"[..]));
+        let payload: serde_json::Value =
+            serde_json::from_slice(message.payload().unwrap()).expect("invalid JSON");
+        assert_eq!(payload, serde_json::to_value(&event).unwrap());
+    }
+}
@@ -0,0 +1,91 @@
+// tests/api/click_limits.rs
+
+// click-limited short URLs: max_clicks redirects work, the next one is
+// 410 Gone, and nothing brings the link back
+
+// dependencies
+use crate::helpers::{TestApp, assert_json_ok, spawn_app};
+use axum::http::StatusCode;
+use serde_json::json;
+
+const URL: &str = "https://www.example.com/coupon";
+
+async fn shorten_with_max_clicks(app: &TestApp, max_clicks: u64) -> String {
+    let response = app
+        .post_json_with_key(
+            "/api/shorten",
+            &json!({ "url": URL, "max_clicks": max_clicks }),
+        )
+        .await;
+    let body = assert_json_ok(response).await;
+    body["data"]["id"]
+        .as_str()
+        .expect("shorten response did not include an id")
+        .to_string()
+}
+
+#[tokio::test]
+async fn link_redirects_for_its_first_max_clicks() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = shorten_with_max_clicks(&app, 3).await;
+
+    // Act
+    let mut statuses = Vec::new();
+    for _ in 0..3 {
+        statuses.push(app.get(&format!("/{}", id)).await.status());
+    }
+
+    // Assert
+    assert_eq!(statuses, [StatusCode::PERMANENT_REDIRECT; 3]);
+}
+
+#[tokio::test]
+async fn click_past_the_limit_is_gone() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = shorten_with_max_clicks(&app, 2).await;
+    for _ in 0..2 {
+        app.get(&format!("/{}", id)).await;
+    }
+
+    // Act
+    let response = app.get(&format!("/{}", id)).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::GONE);
+    assert!(response.headers().get("location").is_none());
+    let info = assert_json_ok(app.get_api(&format!("/api/urls/{}", id)).await).await;
+    assert_eq!(info["data"]["is_active"], false);
+    assert_eq!(info["data"]["click_count"], 2);
+}
+
+#[tokio::test]
+async fn deactivated_link_is_not_reactivated_by_shortening_it_again() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = shorten_with_max_clicks(&app, 1).await;
+    app.get(&format!("/{}", id)).await;
+
+    // Act
+    let again = app.shorten_url(URL).await;
+    let response = app.get(&format!("/{}", id)).await;
+
+    // Assert
+    assert_eq!(again, id);
+    assert_eq!(response.status(), StatusCode::GONE);
+}
+
+#[tokio::test]
+async fn zero_max_clicks_is_rejected() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .post_json_with_key("/api/shorten", &json!({ "url": URL, "max_clicks": 0 }))
+        .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
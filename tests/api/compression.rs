@@ -0,0 +1,69 @@
+// tests/api/compression.rs
+
+use crate::helpers::spawn_app;
+use axum::http::StatusCode;
+use flate2::read::GzDecoder;
+use reqwest::header::{ACCEPT_ENCODING, CONTENT_ENCODING};
+use std::io::Read;
+
+#[tokio::test]
+async fn large_listing_is_gzip_compressed_when_accepted() {
+    // Arrange
+    let app = spawn_app().await;
+    for i in 0..40 {
+        app._database
+            .insert_url(
+                &format!("code{:03}", i),
+                &format!("https://www.example.com/a/fairly/long/path/{}", i),
+            )
+            .await
+            .expect("Failed to insert URL");
+    }
+
+    // Act
+    let compressed = app
+        .client
+        .get(app.api("/api/urls"))
+        .header("x-api-key", app.api_key.to_string())
+        .header(ACCEPT_ENCODING, "gzip")
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let plain = app.get_api_with_key("/api/urls").await;
+
+    // Assert
+    assert_eq!(compressed.status(), StatusCode::OK);
+    assert_eq!(compressed.headers()[CONTENT_ENCODING], "gzip");
+    assert!(plain.headers().get(CONTENT_ENCODING).is_none());
+
+    let bytes = compressed.bytes().await.expect("Failed to read body");
+    let mut decompressed = String::new();
+    GzDecoder::new(&bytes[..])
+        .read_to_string(&mut decompressed)
+        .expect("Body should be valid gzip");
+    let decompressed: serde_json::Value =
+        serde_json::from_str(&decompressed).expect("Decompressed body should be JSON");
+    let plain: serde_json::Value = plain.json().await.expect("Body should be JSON");
+
+    assert_eq!(decompressed["data"].as_array().unwrap().len(), 40);
+    assert_eq!(decompressed["data"], plain["data"]);
+}
+
+#[tokio::test]
+async fn small_health_check_is_not_compressed() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .client
+        .get(app.api("/api/health_check"))
+        .header(ACCEPT_ENCODING, "gzip")
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().get(CONTENT_ENCODING).is_none());
+}
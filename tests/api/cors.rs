@@ -0,0 +1,107 @@
+// tests/api/cors.rs
+
+use crate::helpers::{TestApp, spawn_app, spawn_app_with};
+use axum::http::StatusCode;
+use reqwest::header::{
+    ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_MAX_AGE, ACCESS_CONTROL_REQUEST_HEADERS,
+    ACCESS_CONTROL_REQUEST_METHOD, ORIGIN,
+};
+
+/// The origin allowed by `configuration/base.yml`
+const ALLOWED_ORIGIN: &str = "http://localhost:3000";
+
+async fn preflight(app: &TestApp, path: &str, origin: &str) -> reqwest::Response {
+    app.client
+        .request(reqwest::Method::OPTIONS, app.url(path))
+        .header(ORIGIN, origin)
+        .header(ACCESS_CONTROL_REQUEST_METHOD, "POST")
+        .header(ACCESS_CONTROL_REQUEST_HEADERS, "content-type")
+        .send()
+        .await
+        .expect("Failed to execute request.")
+}
+
+#[tokio::test]
+async fn preflight_from_allowed_origin_gets_cors_headers() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = preflight(&app, "/api/public/shorten", ALLOWED_ORIGIN).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()[ACCESS_CONTROL_ALLOW_ORIGIN],
+        ALLOWED_ORIGIN
+    );
+    assert_eq!(response.headers()[ACCESS_CONTROL_MAX_AGE], "3600");
+}
+
+#[tokio::test]
+async fn preflight_from_disallowed_origin_gets_no_cors_headers() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = preflight(&app, "/api/public/shorten", "https://evil.example.com").await;
+
+    // Assert
+    assert!(
+        response
+            .headers()
+            .get(ACCESS_CONTROL_ALLOW_ORIGIN)
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn wildcard_origin_allows_any_origin() {
+    // Arrange
+    let app = spawn_app_with(|c| c.cors.allowed_origins = vec!["*".to_string()]).await;
+
+    // Act
+    let response = preflight(&app, "/api/public/shorten", "https://anywhere.example.com").await;
+
+    // Assert
+    assert_eq!(response.headers()[ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+}
+
+#[tokio::test]
+async fn protected_api_preflight_skips_api_key_check() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = preflight(&app, "/api/shorten", ALLOWED_ORIGIN).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()[ACCESS_CONTROL_ALLOW_ORIGIN],
+        ALLOWED_ORIGIN
+    );
+}
+
+#[tokio::test]
+async fn admin_routes_are_same_origin_only() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .client
+        .get(app.url("/admin"))
+        .header(ORIGIN, ALLOWED_ORIGIN)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert!(
+        response
+            .headers()
+            .get(ACCESS_CONTROL_ALLOW_ORIGIN)
+            .is_none()
+    );
+}
@@ -0,0 +1,106 @@
+// tests/api/delete.rs
+
+// integration tests for DELETE /api/urls/{id}
+
+// dependencies
+use crate::helpers::{assert_json_ok, spawn_app, spawn_app_with};
+use axum::http::StatusCode;
+
+#[tokio::test]
+async fn delete_removes_the_code_and_redirect_returns_404() {
+    // Arrange
+    let app = spawn_app().await;
+    let response = app
+        .post_api_with_key("/api/shorten", "https://www.example.com/delete-me")
+        .await;
+    let body = assert_json_ok(response).await;
+    let id = body["data"]["id"].as_str().unwrap().to_string();
+
+    // Act
+    let response = app.delete_api_with_key(&format!("/api/urls/{}", id)).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    let redirect = app.get_api(&format!("/api/redirect/{}", id)).await;
+    assert_eq!(redirect.status(), StatusCode::NOT_FOUND);
+    let info = app.get_api(&format!("/api/urls/{}", id)).await;
+    assert_eq!(info.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn delete_stops_a_cached_code_and_its_aliases_from_redirecting() {
+    // Arrange - redirect once so both codes are cached
+    let app = spawn_app_with(|c| c.rate_limiting.enabled = false).await;
+    let code = app.shorten_url("https://www.example.com/cached").await;
+    let alias = app
+        .shorten_url_with_alias("https://www.example.com/cached", "cachedAlias")
+        .await;
+    for id in [&code, &alias] {
+        assert!(app.get_redirect_location(id).await.is_some());
+    }
+
+    // Act
+    let response = app
+        .delete_api_with_key(&format!("/api/urls/{}", code))
+        .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    for id in [&code, &alias] {
+        assert_eq!(app.get_redirect_location(id).await, None);
+    }
+}
+
+#[tokio::test]
+async fn delete_without_api_key_returns_401() {
+    let app = spawn_app().await;
+    app._database
+        .insert_url("keepMe1", "https://www.example.com/keep")
+        .await
+        .unwrap();
+
+    let response = app
+        .client
+        .delete(app.api("/api/urls/keepMe1"))
+        .send()
+        .await
+        .expect("Failed to execute DELETE request");
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert!(app._database.get_url("keepMe1").await.is_ok());
+}
+
+#[tokio::test]
+async fn deleting_an_unknown_code_returns_404_every_time() {
+    let app = spawn_app().await;
+    app._database
+        .insert_url("gone123", "https://www.example.com/gone")
+        .await
+        .unwrap();
+
+    let first = app.delete_api_with_key("/api/urls/gone123").await;
+    let second = app.delete_api_with_key("/api/urls/gone123").await;
+
+    assert_eq!(first.status(), StatusCode::NO_CONTENT);
+    assert_eq!(second.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn deleting_an_alias_keeps_the_primary_code() {
+    let app = spawn_app().await;
+    let (upsert, urls) = app
+        ._database
+        .insert_url("primary", "https://www.example.com/aliased")
+        .await
+        .unwrap();
+    app._database
+        .insert_alias("shortcut", upsert.id)
+        .await
+        .unwrap();
+
+    let response = app.delete_api_with_key("/api/urls/shortcut").await;
+
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert!(app._database.get_url("shortcut").await.is_err());
+    assert!(app._database.get_url(&urls.code).await.is_ok());
+}
@@ -0,0 +1,163 @@
+// tests/api/devices.rs
+
+// listing and revoking signed-in devices through GET and DELETE
+// /api/v1/auth/devices; the auth routes are only mounted on Postgres, so the
+// controllers are called directly with in-memory repos
+
+// dependencies
+use crate::mock_repos::{TestAuth, auth_service_with_user};
+use axum::Extension;
+use axum::extract::{Path, Query, State};
+use secrecy::SecretString;
+use std::sync::Arc;
+use url_shortener_ztm_lib::ApiError;
+use url_shortener_ztm_lib::core::extractors::auth_user::AuthenticatedUser;
+use url_shortener_ztm_lib::features::auth::controllers::{
+    AuthController, list_devices, revoke_device,
+};
+use url_shortener_ztm_lib::features::auth::dto::{DevicesQuery, SignInReq};
+use url_shortener_ztm_lib::features::auth::repositories::DeviceSummary;
+use url_shortener_ztm_lib::features::users::UserService;
+use url_shortener_ztm_lib::middleware::ClientMeta;
+
+const EMAIL: &str = "someone@example.com";
+const PASSWORD: &str = "Correct-Horse-42";
+
+// Signs the user in on a laptop and a phone, returning the controller and the
+// signed-in user
+async fn signed_in_on_two_devices() -> (AuthController, AuthenticatedUser) {
+    let TestAuth {
+        service,
+        users,
+        user_id,
+        ..
+    } = auth_service_with_user(EMAIL, PASSWORD).await;
+
+    for (device_id, user_agent) in [("laptop", "Firefox"), ("phone", "Safari")] {
+        service
+            .sign_in(
+                SignInReq {
+                    email: EMAIL.to_string(),
+                    password: SecretString::from(PASSWORD),
+                    device_id: Some(device_id.to_string()),
+                },
+                ClientMeta {
+                    ip: Some("203.0.113.7".parse().unwrap()),
+                    user_agent: Some(user_agent.to_string()),
+                },
+            )
+            .await
+            .unwrap();
+    }
+
+    let ctrl = AuthController {
+        auth_svc: Arc::new(service),
+        user_svc: Arc::new(UserService::new(users)),
+    };
+    let user = AuthenticatedUser {
+        user_id,
+        token_version: 0,
+    };
+    (ctrl, user)
+}
+
+fn meta() -> ClientMeta {
+    ClientMeta {
+        ip: Some("203.0.113.7".parse().unwrap()),
+        user_agent: None,
+    }
+}
+
+async fn devices(
+    ctrl: &AuthController,
+    user: &AuthenticatedUser,
+    current: &str,
+) -> Vec<DeviceSummary> {
+    list_devices(
+        State(ctrl.clone()),
+        user.clone(),
+        Query(DevicesQuery {
+            device_id: Some(current.to_string()),
+        }),
+    )
+    .await
+    .unwrap()
+    .data
+    .unwrap()
+}
+
+#[tokio::test]
+async fn both_signed_in_devices_are_listed() {
+    // Arrange
+    let (ctrl, user) = signed_in_on_two_devices().await;
+
+    // Act
+    let listed = devices(&ctrl, &user, "phone").await;
+
+    // Assert
+    let mut ids: Vec<&str> = listed.iter().map(|d| d.device_id.as_str()).collect();
+    ids.sort();
+    assert_eq!(ids, vec!["laptop", "phone"]);
+    for dev in &listed {
+        assert_eq!(dev.is_current, dev.device_id == "phone");
+        assert_eq!(dev.ip.as_deref(), Some("203.0.113.7"));
+    }
+    let laptop = listed.iter().find(|d| d.device_id == "laptop").unwrap();
+    assert_eq!(laptop.user_agent.as_deref(), Some("Firefox"));
+}
+
+#[tokio::test]
+async fn a_revoked_device_is_no_longer_listed() {
+    // Arrange
+    let (ctrl, user) = signed_in_on_two_devices().await;
+
+    // Act
+    revoke_device(
+        State(ctrl.clone()),
+        Extension(meta()),
+        user.clone(),
+        Path("laptop".to_string()),
+    )
+    .await
+    .unwrap();
+
+    // Assert
+    let listed = devices(&ctrl, &user, "phone").await;
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].device_id, "phone");
+    assert!(listed[0].is_current);
+}
+
+#[tokio::test]
+async fn revoking_an_unknown_device_is_not_found() {
+    // Arrange
+    let (ctrl, user) = signed_in_on_two_devices().await;
+    revoke_device(
+        State(ctrl.clone()),
+        Extension(meta()),
+        user.clone(),
+        Path("laptop".to_string()),
+    )
+    .await
+    .unwrap();
+
+    // Act
+    let unknown = revoke_device(
+        State(ctrl.clone()),
+        Extension(meta()),
+        user.clone(),
+        Path("tablet".to_string()),
+    )
+    .await;
+    let again = revoke_device(
+        State(ctrl),
+        Extension(meta()),
+        user,
+        Path("laptop".to_string()),
+    )
+    .await;
+
+    // Assert
+    assert!(matches!(unknown, Err(ApiError::NotFound(_))));
+    assert!(matches!(again, Err(ApiError::NotFound(_))));
+}
@@ -0,0 +1,53 @@
+// tests/api/docs.rs
+
+// dependencies
+use crate::helpers::spawn_app;
+use reqwest::StatusCode;
+
+#[tokio::test]
+async fn openapi_json_is_a_valid_document() {
+    let app = spawn_app().await;
+
+    let response = app
+        .client
+        .get(app.url("/api/docs/openapi.json"))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: serde_json::Value = response.json().await.unwrap();
+    serde_json::from_value::<utoipa::openapi::OpenApi>(body.clone())
+        .expect("response is not an OpenAPI document");
+    assert!(body["openapi"].as_str().unwrap().starts_with("3."));
+
+    let shorten = &body["paths"]["/api/shorten"]["post"];
+    assert_eq!(
+        shorten["requestBody"]["content"]["application/json"]["schema"]["$ref"],
+        "#/components/schemas/ShortenRequest"
+    );
+    let required = body["components"]["schemas"]["ShortenRequest"]["required"]
+        .as_array()
+        .unwrap();
+    assert!(required.contains(&serde_json::json!("url")));
+    assert!(body["components"]["securitySchemes"]["ApiKeyAuth"].is_object());
+}
+
+#[tokio::test]
+async fn openapi_yaml_is_generated_from_the_annotations() {
+    let app = spawn_app().await;
+
+    let yaml = app
+        .client
+        .get(app.url("/api/docs/openapi.yaml"))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(yaml.status(), StatusCode::OK);
+    assert_eq!(yaml.headers()["content-type"], "application/yaml");
+    let yaml = yaml.text().await.unwrap();
+
+    assert!(yaml.contains("/api/shorten:"));
+    assert!(yaml.contains("/api/public/shorten:"));
+    assert!(yaml.contains("/api/webhooks/receive:"));
+}
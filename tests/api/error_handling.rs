@@ -8,8 +8,9 @@
 // - Malformed ID validation in redirect endpoint
 // - Authentication errors
 // - Edge cases and boundary conditions
+// - Request IDs in error bodies
 
-use crate::helpers::{assert_json_ok, spawn_app};
+use crate::helpers::{assert_json_ok, spawn_app, spawn_app_with_accounts};
 use axum::http::StatusCode;
 use serde_json::Value;
 
@@ -485,9 +486,9 @@ async fn redirect_handles_numeric_ids() {
 // CONTENT TYPE TESTS
 // ================================
 
-/// Test that JSON content type for POST is rejected
+/// Test that JSON content type for POST is parsed as a JSON body
 #[tokio::test]
-async fn shorten_rejects_json_content_type() {
+async fn shorten_accepts_json_content_type() {
     let app = spawn_app().await;
     let response = app
         .client
@@ -500,8 +501,8 @@ async fn shorten_rejects_json_content_type() {
         .await
         .expect("Failed to execute POST request");
 
-    // The endpoint expects plain text, not JSON
-    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    // JSON bodies are parsed as `{"url": ...}` rather than as a plain-text URL
+    assert_eq!(response.status(), StatusCode::OK);
 }
 
 /// Test that XML content type for POST is rejected
@@ -521,3 +522,73 @@ async fn shorten_rejects_xml_content_type() {
 
     assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
 }
+
+// ================================
+// REQUEST CORRELATION TESTS
+// ================================
+
+/// Test that error bodies carry the same request ID as the `x-request-id` header
+#[tokio::test]
+async fn error_body_request_id_matches_header() {
+    let app = spawn_app().await;
+    let response = app
+        .post_api_with_key("/api/shorten", "ftp://example.com")
+        .await;
+
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let header_id = response
+        .headers()
+        .get("x-request-id")
+        .expect("Missing x-request-id header")
+        .to_str()
+        .unwrap()
+        .to_string();
+    let body = response
+        .json::<Value>()
+        .await
+        .expect("Failed to parse JSON");
+    assert_eq!(body["request_id"], header_id);
+}
+
+/// Test that a caller-supplied request ID is echoed in the error body
+#[tokio::test]
+async fn error_body_echoes_incoming_request_id() {
+    let app = spawn_app().await;
+    let response = app
+        .client
+        .get(app.url("/zzzzzzz"))
+        .header("x-request-id", "trace-me-123")
+        .send()
+        .await
+        .expect("Failed to execute GET request");
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let body = response
+        .json::<Value>()
+        .await
+        .expect("Failed to parse JSON");
+    assert_eq!(body["request_id"], "trace-me-123");
+}
+
+/// Test that errors from the auth routes carry the request ID too
+#[tokio::test]
+async fn auth_route_error_body_echoes_incoming_request_id() {
+    // the auth routes are only mounted on Postgres
+    let app = spawn_app_with_accounts(|_| {}).await;
+    let response = app
+        .client
+        .post(app.url("/api/v1/auth/sign-in"))
+        .header("x-request-id", "trace-sign-in-456")
+        .json(&serde_json::json!({ "email": "not-an-email", "password": "wrong" }))
+        .send()
+        .await
+        .expect("Failed to execute POST request");
+
+    assert!(response.status().is_client_error());
+    assert_eq!(response.headers()["x-request-id"], "trace-sign-in-456");
+    let body = response
+        .json::<Value>()
+        .await
+        .expect("Failed to parse JSON");
+    assert_eq!(body["request_id"], "trace-sign-in-456");
+}
@@ -0,0 +1,108 @@
+// tests/api/error_pages.rs
+
+// browsers get HTML error pages, other clients keep the JSON error body
+
+// dependencies
+use crate::helpers::spawn_app;
+use axum::http::StatusCode;
+use reqwest::header::{ACCEPT, CONTENT_TYPE};
+use serde_json::Value;
+
+// What a browser sends when navigating to a page
+const BROWSER_ACCEPT: &str = "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8";
+
+#[tokio::test]
+async fn unknown_short_code_renders_404_page_for_browsers() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .client
+        .get(app.url("/zzzzzzz"))
+        .header(ACCEPT, BROWSER_ACCEPT)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert!(
+        response.headers()[CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .starts_with("text/html")
+    );
+    let body = response.text().await.unwrap();
+    assert!(body.contains("<html"));
+    assert!(body.contains("URL not found"));
+}
+
+#[tokio::test]
+async fn unknown_path_renders_404_page_with_request_id() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .client
+        .get(app.url("/no/such/page"))
+        .header(ACCEPT, BROWSER_ACCEPT)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let request_id = response.headers()["x-request-id"]
+        .to_str()
+        .unwrap()
+        .to_string();
+    let body = response.text().await.unwrap();
+    assert!(body.contains("<html"));
+    assert!(body.contains("Page not found"));
+    assert!(body.contains(&request_id));
+}
+
+#[tokio::test]
+async fn unknown_path_returns_json_404_for_api_clients() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .client
+        .get(app.url("/no/such/page"))
+        .header(ACCEPT, "application/json")
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["success"], false);
+    assert_eq!(body["status"], 404);
+    assert_eq!(body["message"], "Page not found");
+}
+
+#[tokio::test]
+async fn client_errors_other_than_404_keep_the_json_body() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act: the bucket is rejected before the code is looked up
+    let response = app
+        .client
+        .get(app.url("/admin/urls/abc/analytics?bucket=week"))
+        .header("x-api-key", app.api_key.to_string())
+        .header(ACCEPT, BROWSER_ACCEPT)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["status"], 422);
+}
@@ -0,0 +1,96 @@
+// tests/api/expiry_reminders.rs
+
+// expiry reminder emails: the daily task warns the owner of a URL expiring
+// within expiry_reminders.days_before days, once
+
+// dependencies
+use crate::helpers::test_configuration;
+use crate::mock_repos::MockUserRepo;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use std::sync::{Arc, Mutex};
+use url_shortener_ztm_lib::database::{SqliteUrlDatabase, UrlDatabase};
+use url_shortener_ztm_lib::features::users::services::UserService;
+use url_shortener_ztm_lib::infrastructure::email::EmailSender;
+use url_shortener_ztm_lib::infrastructure::expiry_reminders::send_expiry_reminders;
+use url_shortener_ztm_lib::state::AppStateBuilder;
+
+/// Keeps every email instead of sending it.
+#[derive(Default)]
+struct RecordingMailer {
+    sent: Mutex<Vec<(String, String, String)>>,
+}
+
+#[async_trait]
+impl EmailSender for RecordingMailer {
+    async fn send_email(&self, to: &str, subject: &str, html_content: &str) -> Result<()> {
+        self.sent.lock().unwrap().push((
+            to.to_string(),
+            subject.to_string(),
+            html_content.to_string(),
+        ));
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn owner_is_reminded_once_of_a_url_expiring_soon() {
+    // Arrange
+    let configuration = test_configuration(|c| {
+        c.expiry_reminders.enabled = true;
+        c.expiry_reminders.days_before = 7;
+    });
+    let database = Arc::new(
+        SqliteUrlDatabase::in_memory_with_migrations()
+            .await
+            .expect("Failed to create database"),
+    );
+    let users = Arc::new(MockUserRepo::new().with_user("owner@example.com"));
+    let owner = users.users()[0].id;
+    let state = AppStateBuilder::new()
+        .config(configuration)
+        .database(database.clone())
+        .user_service(Arc::new(UserService::new(users)))
+        .build()
+        .await
+        .expect("Failed to build the app state");
+
+    let now = Utc::now();
+    for (code, expires_in) in [("soon05", 5), ("later30", 30)] {
+        let url = format!("https://www.example.com/{}", code);
+        database
+            .insert_url_with_expiry(code, &url, Some(now + Duration::days(expires_in)))
+            .await
+            .expect("Failed to insert URL");
+        database
+            .set_url_owner(code, owner)
+            .await
+            .expect("Failed to set owner");
+    }
+    database
+        .insert_url_with_expiry(
+            "nobody5",
+            "https://www.example.com/nobody5",
+            Some(now + Duration::days(5)),
+        )
+        .await
+        .expect("Failed to insert URL");
+    let mailer = RecordingMailer::default();
+
+    // Act
+    let first = send_expiry_reminders(&state, &mailer, now)
+        .await
+        .expect("Failed to send reminders");
+    let second = send_expiry_reminders(&state, &mailer, now + Duration::days(1))
+        .await
+        .expect("Failed to send reminders");
+
+    // Assert
+    assert_eq!((first, second), (1, 0));
+    let sent = mailer.sent.lock().unwrap();
+    assert_eq!(sent.len(), 1);
+    let (to, _, body) = &sent[0];
+    assert_eq!(to, "owner@example.com");
+    assert!(body.contains("/soon05"), "{}", body);
+}
@@ -0,0 +1,120 @@
+// tests/api/export.rs
+
+// integration tests for GET /api/urls/export
+
+// dependencies
+use crate::helpers::{TestApp, assert_json_ok, spawn_app};
+use axum::http::StatusCode;
+use serde_json::Value;
+
+async fn export_with_accept(app: &TestApp, accept: &str) -> reqwest::Response {
+    app.client
+        .get(app.api("/api/urls/export"))
+        .header("x-api-key", app.api_key.to_string())
+        .header("accept", accept)
+        .send()
+        .await
+        .expect("Failed to execute GET request")
+}
+
+#[tokio::test]
+async fn json_export_can_be_reimported() {
+    // Arrange
+    let source = spawn_app().await;
+    for (code, url) in [
+        ("expJson1", "https://www.example.com/one"),
+        ("expJson2", "https://www.example.com/two"),
+    ] {
+        source._database.insert_url(code, url).await.unwrap();
+    }
+
+    // Act
+    let response = export_with_accept(&source, "application/json").await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let disposition = response
+        .headers()
+        .get("content-disposition")
+        .and_then(|v| v.to_str().ok())
+        .unwrap()
+        .to_string();
+    assert!(disposition.starts_with("attachment; filename=\"urls-export-"));
+    assert!(disposition.ends_with(".json\""));
+
+    let exported: Value = response.json().await.expect("export was not valid JSON");
+    let rows = exported.as_array().unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0]["code"].as_str(), Some("expJson1"));
+    assert_eq!(rows[0]["click_count"].as_u64(), Some(0));
+    assert!(rows[0]["created_at"].is_string());
+
+    let target = spawn_app().await;
+    let response = target
+        .post_json_with_key("/api/urls/import", &exported)
+        .await;
+    let body = assert_json_ok(response).await;
+    assert_eq!(body["data"]["imported"].as_u64(), Some(2));
+    assert_eq!(
+        target._database.get_url("expJson2").await.unwrap(),
+        "https://www.example.com/two"
+    );
+}
+
+#[tokio::test]
+async fn csv_export_has_bom_and_headers() {
+    let app = spawn_app().await;
+    app._database
+        .insert_url("expCsv01", "https://www.example.com/csv")
+        .await
+        .unwrap();
+
+    let response = export_with_accept(&app, "text/csv").await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(
+        response
+            .headers()
+            .get("content-type")
+            .and_then(|v| v.to_str().ok())
+            .unwrap()
+            .starts_with("text/csv")
+    );
+    // Read raw bytes; `text()` would silently drop the BOM
+    let bytes = response.bytes().await.unwrap();
+    let text = bytes
+        .strip_prefix("\u{feff}".as_bytes())
+        .expect("CSV export did not start with a UTF-8 BOM");
+    let text = std::str::from_utf8(text).unwrap();
+    let mut lines = text.lines();
+    assert_eq!(lines.next(), Some("code,url,created_at,click_count"));
+    assert!(
+        lines
+            .next()
+            .unwrap()
+            .starts_with("expCsv01,https://www.example.com/csv,")
+    );
+    assert_eq!(lines.next(), None);
+}
+
+#[tokio::test]
+async fn large_export_streams_every_row() {
+    let app = spawn_app().await;
+    let rows: Vec<(String, String)> = (0..1_234)
+        .map(|i| (format!("big{i}"), format!("https://www.example.com/{i}")))
+        .collect();
+    let pairs: Vec<(&str, &str)> = rows
+        .iter()
+        .map(|(code, url)| (code.as_str(), url.as_str()))
+        .collect();
+    app._database.insert_urls_batch(&pairs).await.unwrap();
+
+    // No Accept header defaults to JSON
+    let response = app.get_api_with_key("/api/urls/export").await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let exported: Value = response.json().await.expect("export was not valid JSON");
+    let exported = exported.as_array().unwrap();
+    assert_eq!(exported.len(), 1_234);
+    assert_eq!(exported[1_233]["code"].as_str(), Some("big1233"));
+}
@@ -0,0 +1,76 @@
+// tests/api/geolocation.rs
+
+// click geolocation: countries from a stand-in GeoIP reader are stored with
+// each click and reported as top_countries by GET /api/urls/{id}/stats
+
+// dependencies
+use crate::helpers::{TestApp, assert_json_ok, spawn_app, spawn_app_with_state};
+use serde_json::{Value, json};
+use std::collections::VecDeque;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use url_shortener_ztm_lib::infrastructure::geo::CountryLookup;
+
+/// Answers each lookup with the next country of a fixed sequence.
+struct SequenceLookup(Mutex<VecDeque<Option<&'static str>>>);
+
+impl CountryLookup for SequenceLookup {
+    fn country_code(&self, _ip: IpAddr) -> Option<String> {
+        self.0
+            .lock()
+            .unwrap()
+            .pop_front()
+            .flatten()
+            .map(str::to_string)
+    }
+}
+
+async fn click_and_get_stats(app: &TestApp, clicks: usize) -> Value {
+    let id = app.shorten_url("https://www.example.com/").await;
+    for _ in 0..clicks {
+        app.get(&format!("/{}", id)).await;
+    }
+    assert_json_ok(
+        app.get_api_with_key(&format!("/api/urls/{}/stats", id))
+            .await,
+    )
+    .await
+}
+
+#[tokio::test]
+async fn stats_count_clicks_per_country() {
+    // Arrange
+    let countries = [Some("US"), Some("DE"), None, Some("US")];
+    let lookup = Arc::new(SequenceLookup(Mutex::new(countries.into())));
+    let app = spawn_app_with_state(
+        |c| c.geolocation.enabled = true,
+        |builder| builder.country_lookup(lookup),
+    )
+    .await;
+
+    // Act
+    let stats = click_and_get_stats(&app, countries.len()).await;
+
+    // Assert
+    assert_eq!(stats["data"]["total_clicks"], 4);
+    assert_eq!(
+        stats["data"]["top_countries"],
+        json!([
+            { "country_code": "US", "count": 2 },
+            { "country_code": "DE", "count": 1 }
+        ])
+    );
+}
+
+#[tokio::test]
+async fn stats_leave_out_countries_when_geolocation_is_disabled() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let stats = click_and_get_stats(&app, 2).await;
+
+    // Assert
+    assert_eq!(stats["data"]["total_clicks"], 2);
+    assert!(stats["data"].get("top_countries").is_none());
+}
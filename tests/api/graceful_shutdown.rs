@@ -0,0 +1,147 @@
+// tests/api/graceful_shutdown.rs
+
+// tests for the drain timeout of the binary's graceful shutdown
+
+// dependencies
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
+
+// Start the server on a free port, with `timeout_seconds` for in-flight
+// requests to finish on shutdown; its log lines arrive on the receiver
+fn start_server(timeout_seconds: u64) -> (Child, u16, Receiver<String>) {
+    let port = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_url-shortener-ztm"))
+        .env("APP_APPLICATION__HOST", "127.0.0.1")
+        .env("APP_APPLICATION__PORT", port.to_string())
+        .env(
+            "APP_APPLICATION__GRACEFUL_SHUTDOWN_TIMEOUT_SECONDS",
+            timeout_seconds.to_string(),
+        )
+        .env("APP_APPLICATION__REQUEST_TIMEOUT_MS", "60000")
+        .env("APP_DATABASE__URL", ":memory:")
+        .env("BLOOM_SNAPSHOTS", "1")
+        .env("RUST_LOG", "info")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("Failed to start the binary");
+    let logs = log_lines(&mut child);
+    (child, port, logs)
+}
+
+// Keep reading the server's stdout, so it never blocks on a full pipe
+fn log_lines(child: &mut Child) -> Receiver<String> {
+    let stdout = child.stdout.take().unwrap();
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            let _ = sender.send(line);
+        }
+    });
+    receiver
+}
+
+// Wait for a log line containing `needle`
+fn wait_for_log(logs: &Receiver<String>, needle: &str, within: Duration) {
+    let deadline = Instant::now() + within;
+    loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        match logs.recv_timeout(left) {
+            Ok(line) if line.contains(needle) => return,
+            Ok(_) => {}
+            Err(_) => panic!("server never logged {:?}", needle),
+        }
+    }
+}
+
+// Open a connection once the server accepts them
+fn connect(port: u16) -> TcpStream {
+    let deadline = Instant::now() + Duration::from_secs(30);
+    loop {
+        match TcpStream::connect(("127.0.0.1", port)) {
+            Ok(stream) => return stream,
+            Err(e) if Instant::now() > deadline => panic!("server never came up: {}", e),
+            Err(_) => std::thread::sleep(Duration::from_millis(100)),
+        }
+    }
+}
+
+fn wait_for_exit(child: &mut Child, within: Duration) -> Option<ExitStatus> {
+    let deadline = Instant::now() + within;
+    while Instant::now() < deadline {
+        if let Some(status) = child.try_wait().unwrap() {
+            return Some(status);
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    None
+}
+
+#[cfg(unix)]
+#[test]
+fn stuck_request_makes_shutdown_exit_with_1_after_the_timeout() {
+    // Arrange: a request whose body never arrives keeps its handler waiting
+    let (mut child, port, logs) = start_server(1);
+    let mut stream = connect(port);
+    stream
+        .write_all(
+            b"POST /api/public/shorten HTTP/1.1\r\nHost: localhost\r\n\
+              Content-Type: application/json\r\nContent-Length: 100\r\n\r\n{\"url\":",
+        )
+        .unwrap();
+    // The request is logged inside `track_in_flight`, so it is in flight now
+    wait_for_log(&logs, "uri: /api/public/shorten", Duration::from_secs(30));
+
+    // Act
+    let signalled = Instant::now();
+    let kill = Command::new("kill")
+        .args(["-TERM", &child.id().to_string()])
+        .status()
+        .unwrap();
+    assert!(kill.success());
+    let status = wait_for_exit(&mut child, Duration::from_secs(10));
+    let elapsed = signalled.elapsed();
+
+    // Assert
+    let Some(status) = status else {
+        child.kill().unwrap();
+        panic!("server still running 10s after SIGTERM");
+    };
+    assert_eq!(status.code(), Some(1));
+    assert!(
+        elapsed >= Duration::from_secs(1),
+        "exited after {:?}",
+        elapsed
+    );
+    drop(stream);
+}
+
+#[cfg(unix)]
+#[test]
+fn idle_server_shuts_down_cleanly() {
+    // Arrange
+    let (mut child, port, _logs) = start_server(30);
+    drop(connect(port));
+
+    // Act
+    let kill = Command::new("kill")
+        .args(["-TERM", &child.id().to_string()])
+        .status()
+        .unwrap();
+    assert!(kill.success());
+    let status = wait_for_exit(&mut child, Duration::from_secs(10));
+
+    // Assert
+    let Some(status) = status else {
+        child.kill().unwrap();
+        panic!("server still running 10s after SIGTERM");
+    };
+    assert!(status.success(), "exited with {:?}", status);
+}
@@ -0,0 +1,383 @@
+// tests/api/handlers.rs
+
+// handlers called directly with a MockUrlDatabase, without a server or SQLite
+
+// dependencies
+use crate::helpers::{build_test_state, test_configuration};
+use crate::mock_db::MockUrlDatabase;
+use axum::Json;
+use axum::extract::{Extension, Path, Query, State};
+use axum::http::uri::Authority;
+use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
+use axum::response::IntoResponse;
+use axum_extra::{TypedHeader, headers::Host};
+use std::collections::VecDeque;
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use url_shortener_ztm_lib::database::UrlDatabase;
+use url_shortener_ztm_lib::errors::ApiError;
+use url_shortener_ztm_lib::generator::{GeneratorError, ShortCodeGenerator};
+use url_shortener_ztm_lib::middleware::RealIp;
+use url_shortener_ztm_lib::routes::{
+    BatchShortenItem, RedirectParams, RedirectQueryParams, ShortenInput, ShortenParams,
+    ShortenRequest, get_redirect, post_shorten, post_shorten_batch,
+};
+use url_shortener_ztm_lib::state::{AppState, AppStateBuilder};
+
+async fn state_with(database: Arc<MockUrlDatabase>) -> AppState {
+    build_test_state(&test_configuration(|_| {}), database).await
+}
+
+async fn shorten(state: &AppState, url: &str) -> Result<String, ApiError> {
+    let response = post_shorten(
+        State(state.clone()),
+        TypedHeader(Host::from(Authority::from_static("localhost:8000"))),
+        None,
+        Query(ShortenParams {
+            alias: None,
+            tags: None,
+            redirect: None,
+        }),
+        ShortenInput::Json(ShortenRequest {
+            url: url.to_string(),
+            alias: None,
+            ttl_seconds: None,
+            password: None,
+            ab_targets: None,
+            mobile_url: None,
+            max_clicks: None,
+        }),
+    )
+    .await?;
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    Ok(body["data"]["id"]
+        .as_str()
+        .expect("shorten response has data")
+        .to_string())
+}
+
+async fn redirect(state: &AppState, code: &str, headers: HeaderMap) -> axum::response::Response {
+    get_redirect(
+        State(state.clone()),
+        Extension(RealIp(IpAddr::V4(Ipv4Addr::new(203, 0, 113, 7)))),
+        Path(code.to_string()),
+        Query(RedirectParams { redirect: None }),
+        Query(RedirectQueryParams::default()),
+        headers,
+    )
+    .await
+    .into_response()
+}
+
+#[tokio::test]
+async fn post_shorten_stores_the_normalized_url() {
+    // Arrange
+    let database = Arc::new(MockUrlDatabase::new());
+    let state = state_with(database.clone()).await;
+
+    // Act
+    let code = shorten(&state, "https://Example.com/path#top")
+        .await
+        .unwrap();
+
+    // Assert
+    let entry = database.entry(&code).expect("URL was not stored");
+    assert_eq!(entry.url, "https://example.com/path");
+    assert!(
+        database
+            .calls()
+            .contains(&"insert_url_with_expiry".to_string())
+    );
+    assert!(state.blooms().s2l.may_contain(&code));
+}
+
+#[tokio::test]
+async fn post_shorten_reuses_the_code_of_a_known_url() {
+    // Arrange
+    let database = Arc::new(MockUrlDatabase::new());
+    let state = state_with(database.clone()).await;
+    let first = shorten(&state, "https://example.com/").await.unwrap();
+
+    // Act
+    let second = shorten(&state, "https://example.com/").await.unwrap();
+
+    // Assert
+    assert_eq!(first, second);
+}
+
+#[tokio::test]
+async fn post_shorten_is_internal_error_when_insert_fails() {
+    // Arrange
+    let database = Arc::new(MockUrlDatabase::new());
+    let state = state_with(database.clone()).await;
+    database.with_error_on_next("insert_url_with_expiry");
+
+    // Act
+    let result = shorten(&state, "https://example.com/").await;
+
+    // Assert
+    assert!(matches!(result, Err(ApiError::Internal(_))));
+    assert!(database.list_urls(0, 10, None).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn post_shorten_backs_off_between_collision_retries() {
+    // Arrange
+    let database = Arc::new(MockUrlDatabase::new());
+    let state = state_with(database.clone()).await;
+    database.with_duplicates_on_next("insert_url_with_expiry", 3);
+    let started = Instant::now();
+
+    // Act
+    let code = shorten(&state, "https://example.com/").await.unwrap();
+
+    // Assert
+    assert!(
+        started.elapsed() >= Duration::from_millis(10 + 20 + 40),
+        "retried after {:?}",
+        started.elapsed()
+    );
+    let inserts = database
+        .calls()
+        .iter()
+        .filter(|op| *op == "insert_url_with_expiry")
+        .count();
+    assert_eq!(inserts, 4);
+    assert!(database.entry(&code).is_some());
+}
+
+#[tokio::test]
+async fn post_shorten_retries_transient_insert_errors() {
+    // Arrange
+    let database = Arc::new(MockUrlDatabase::new());
+    let state = state_with(database.clone()).await;
+    database.with_transient_errors_on_next("insert_url_with_expiry", 2);
+
+    // Act
+    let code = shorten(&state, "https://example.com/").await.unwrap();
+
+    // Assert
+    let inserts = database
+        .calls()
+        .iter()
+        .filter(|op| *op == "insert_url_with_expiry")
+        .count();
+    assert_eq!(inserts, 3);
+    assert!(database.entry(&code).is_some());
+}
+
+#[tokio::test]
+async fn get_redirect_records_the_click() {
+    // Arrange
+    let database = Arc::new(MockUrlDatabase::new());
+    let state = state_with(database.clone()).await;
+    let code = shorten(&state, "https://example.com/").await.unwrap();
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::REFERER,
+        HeaderValue::from_static("https://news.example/"),
+    );
+
+    // Act
+    let response = redirect(&state, &code, headers).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+    assert_eq!(response.headers()[header::LOCATION], "https://example.com/");
+    let clicks = database.entry(&code).unwrap().clicks;
+    assert_eq!(clicks.len(), 1);
+    assert_eq!(clicks[0].ip.as_deref(), Some("203.0.113.7"));
+    assert_eq!(clicks[0].referrer.as_deref(), Some("https://news.example/"));
+}
+
+#[tokio::test]
+async fn get_redirect_still_redirects_when_recording_the_click_fails() {
+    // Arrange
+    let database = Arc::new(MockUrlDatabase::new());
+    let state = state_with(database.clone()).await;
+    let code = shorten(&state, "https://example.com/").await.unwrap();
+    database.with_error_on_next("record_click");
+
+    // Act
+    let response = redirect(&state, &code, HeaderMap::new()).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+    assert!(database.entry(&code).unwrap().clicks.is_empty());
+}
+
+#[tokio::test]
+async fn get_redirect_serves_a_repeated_code_from_the_cache() {
+    // Arrange - the first redirect caches the target
+    let database = Arc::new(MockUrlDatabase::new());
+    let state = state_with(database.clone()).await;
+    let code = shorten(&state, "https://example.com/").await.unwrap();
+    redirect(&state, &code, HeaderMap::new()).await;
+    database.with_error_on_next("get_url_with_metadata");
+
+    // Act
+    let response = redirect(&state, &code, HeaderMap::new()).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+    assert_eq!(response.headers()[header::LOCATION], "https://example.com/");
+    let lookups = database
+        .calls()
+        .iter()
+        .filter(|call| *call == "get_url_with_metadata")
+        .count();
+    assert_eq!(lookups, 1);
+    assert_eq!(database.entry(&code).unwrap().clicks.len(), 2);
+}
+
+#[tokio::test]
+async fn get_redirect_is_internal_error_when_lookup_fails() {
+    // Arrange
+    let database = Arc::new(MockUrlDatabase::new());
+    let state = state_with(database.clone()).await;
+    let code = shorten(&state, "https://example.com/").await.unwrap();
+    database.with_error_on_next("get_url_with_metadata");
+
+    // Act
+    let response = redirect(&state, &code, HeaderMap::new()).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(!database.calls().contains(&"record_click".to_string()));
+}
+
+#[tokio::test]
+async fn get_redirect_retries_transient_lookup_errors() {
+    // Arrange
+    let database = Arc::new(MockUrlDatabase::new());
+    let state = state_with(database.clone()).await;
+    let code = shorten(&state, "https://example.com/").await.unwrap();
+    database.with_transient_errors_on_next("get_url_with_metadata", 2);
+
+    // Act
+    let response = redirect(&state, &code, HeaderMap::new()).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+    assert_eq!(response.headers()[header::LOCATION], "https://example.com/");
+    let lookups = database
+        .calls()
+        .iter()
+        .filter(|call| *call == "get_url_with_metadata")
+        .count();
+    assert_eq!(lookups, 3);
+}
+
+#[tokio::test]
+async fn get_redirect_falls_back_to_the_database_before_the_bloom_filter_is_ready() {
+    // Arrange: the background rebuild fails, so the filter never holds the
+    // stored code and never becomes ready
+    let database = Arc::new(MockUrlDatabase::new());
+    database
+        .insert_url("Abc123", "https://example.com/stored")
+        .await
+        .unwrap();
+    database.with_error_on_next("list_short_codes");
+    let state = AppStateBuilder::new()
+        .config(test_configuration(|_| {}))
+        .database(database.clone())
+        .build()
+        .await
+        .expect("Failed to build the app state");
+
+    // Act
+    let response = redirect(&state, "Abc123", HeaderMap::new()).await;
+
+    // Assert
+    assert!(!state.blooms().is_ready());
+    assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+    assert_eq!(
+        response.headers()[header::LOCATION],
+        "https://example.com/stored"
+    );
+    // The filter was skipped, so the lookup is no Bloom filter outcome
+    assert_eq!(state.blooms().metrics_snapshot(), Default::default());
+}
+
+#[tokio::test]
+async fn get_redirect_counts_a_true_positive_only_on_a_cache_miss() {
+    // Arrange
+    let database = Arc::new(MockUrlDatabase::new());
+    let state = state_with(database).await;
+    let code = shorten(&state, "https://example.com/counted")
+        .await
+        .unwrap();
+
+    // Act: the second redirect is served from the redirect cache
+    redirect(&state, &code, HeaderMap::new()).await;
+    redirect(&state, &code, HeaderMap::new()).await;
+
+    // Assert
+    assert_eq!(state.blooms().metrics_snapshot().s2l_hits, 1);
+}
+
+// Hands out the given codes in order, so a test can make one collide
+struct ScriptedGenerator(Mutex<VecDeque<&'static str>>);
+
+impl ShortCodeGenerator for ScriptedGenerator {
+    fn generate(&self) -> Result<String, GeneratorError> {
+        let mut codes = self.0.lock().unwrap();
+        codes
+            .pop_front()
+            .map(str::to_string)
+            .ok_or(GeneratorError::Internal("out of scripted codes"))
+    }
+
+    fn name(&self) -> &'static str {
+        "scripted"
+    }
+}
+
+#[tokio::test]
+async fn post_shorten_batch_reports_a_failed_retry_on_its_entry() {
+    // Arrange: the first entry's code is taken and storing it on its own fails
+    let database = Arc::new(MockUrlDatabase::new());
+    database
+        .insert_url("taken1", "https://www.example.com/older")
+        .await
+        .unwrap();
+    let generator = ScriptedGenerator(Mutex::new(VecDeque::from(["taken1", "fresh1", "retry1"])));
+    let state = AppStateBuilder::new()
+        .config(test_configuration(|_| {}))
+        .database(database.clone())
+        .code_generator(Arc::new(generator))
+        .build()
+        .await
+        .expect("Failed to build the app state");
+    state.blooms().wait_until_ready().await;
+    database.with_error_on_next("insert_url_with_expiry");
+    let items = [
+        "https://www.example.com/first",
+        "https://www.example.com/second",
+    ]
+    .into_iter()
+    .map(|url| BatchShortenItem {
+        url: url.to_string(),
+        alias: None,
+    })
+    .collect();
+
+    // Act
+    let response = post_shorten_batch(State(state), Json(items))
+        .await
+        .expect("the batch is answered with a 207");
+
+    // Assert
+    let results = response.data.unwrap();
+    assert!(!results[0].success);
+    assert!(results[0].error.is_some());
+    assert!(results[1].success, "{:?}", results[1].error);
+    assert_eq!(
+        database.entry("fresh1").unwrap().url,
+        "https://www.example.com/second"
+    );
+}
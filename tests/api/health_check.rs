@@ -1,7 +1,8 @@
 // tests/api/health_check.rs
 
 // dependencies
-use crate::helpers::{assert_json_ok, spawn_app};
+use crate::helpers::{assert_json_ok, spawn_app, spawn_app_with};
+use url_shortener_ztm_lib::generator::config::EngineKind;
 
 #[tokio::test]
 async fn health_check_returns_200_ok_and_json_envelope() {
@@ -13,5 +14,108 @@ async fn health_check_returns_200_ok_and_json_envelope() {
 
     // Assert standard JSON OK envelope
     let body = assert_json_ok(response).await;
-    assert!(body.get("data").unwrap().is_null());
+    let data = body.get("data").unwrap();
+    assert_eq!(data["db_ok"], true);
+    assert_eq!(data["db_type"], "sqlite");
+    assert_eq!(data["generator_engine"], "nanoid");
+    // nanoid keeps no counters, so no metrics are reported
+    assert!(data.get("generator").is_none());
+    assert!(data.get("generator_remaining_capacity").is_none());
+    assert!(data["uptime_seconds"].is_u64());
+}
+
+#[tokio::test]
+async fn health_check_reports_sequence_remaining_capacity() {
+    // Arrange
+    let app = spawn_app_with(|c| {
+        c.shortener.engine.kind = EngineKind::Sequence;
+        if let Some(sequence) = c.shortener.engine.sequence.as_mut() {
+            sequence.state_path = None;
+        }
+    })
+    .await;
+    app.post_api_with_key("/api/shorten", "https://www.example.com/capacity")
+        .await;
+
+    // Act
+    let response = app.get_api("/api/health_check").await;
+
+    // Assert
+    let body = assert_json_ok(response).await;
+    let data = &body["data"];
+    let next_value = data["generator"]["next_value"].as_u64().unwrap();
+    assert!(next_value > 0);
+    assert_eq!(
+        data["generator_remaining_capacity"].as_u64(),
+        Some(u64::MAX - next_value)
+    );
+}
+
+#[tokio::test]
+async fn health_check_reports_version_and_bloom_items() {
+    let app = spawn_app().await;
+    let shorten_response = app
+        .post_api_with_key("/api/shorten", "https://www.example.com/bloom")
+        .await;
+    assert_json_ok(shorten_response).await;
+
+    let response = app.get_api("/api/health_check").await;
+
+    let body = assert_json_ok(response).await;
+    let data = body.get("data").unwrap();
+    assert_eq!(data["version"], env!("CARGO_PKG_VERSION"));
+    assert!(data["bloom_items"].as_u64().unwrap() >= 1);
+}
+
+#[tokio::test]
+async fn health_check_reports_bloom_utilization() {
+    let app = spawn_app_with(|c| c.bloom.expected_items = 100).await;
+    let shorten_response = app
+        .post_api_with_key("/api/shorten", "https://www.example.com/utilization")
+        .await;
+    assert_json_ok(shorten_response).await;
+
+    let response = app.get_api("/api/health_check").await;
+
+    let body = assert_json_ok(response).await;
+    let pct = body["data"]["bloom_utilization_pct"].as_f64().unwrap();
+    assert!(pct > 0.0, "expected non-zero utilization, got {pct}");
+}
+
+#[tokio::test]
+async fn health_check_counts_bloom_lookup_outcomes() {
+    // A cached redirect would hide the row deleted below
+    let app = spawn_app_with(|c| c.cache.max_capacity = 0).await;
+    let body = assert_json_ok(
+        app.post_api_with_key("/api/shorten", "https://www.example.com/lookups")
+            .await,
+    )
+    .await;
+    let code = body["data"]["id"].as_str().unwrap().to_string();
+
+    // Passed by the filter and found in the database
+    app.get(&format!("/{}", code)).await;
+    // Never inserted, so ruled out by the filter
+    app.get("/neverIssued").await;
+    // Still in the filter after the row is removed behind its back
+    app._database.delete_url(&code).await.unwrap();
+    app.get(&format!("/{}", code)).await;
+
+    let body = assert_json_ok(app.get_api("/api/health_check").await).await;
+    let lookups = &body["data"]["bloom_lookups"];
+    assert_eq!(lookups["s2l_hits"], 1);
+    assert_eq!(lookups["s2l_misses"], 1);
+    assert_eq!(lookups["s2l_false_positives"], 1);
+}
+
+#[tokio::test]
+async fn ready_returns_200_when_migrations_are_applied() {
+    let app = spawn_app().await;
+
+    let response = app.get_api("/api/ready").await;
+
+    let body = assert_json_ok(response).await;
+    let data = body.get("data").unwrap();
+    assert_eq!(data["db_ok"], true);
+    assert_eq!(data["migrations_pending"], 0);
 }
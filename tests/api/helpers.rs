@@ -1,21 +1,21 @@
 // tests/api/helpers.rs
 
 // dependencies
+use crate::mock_repos::{MockAuthRepo, MockUserRepo, auth_service};
 use axum::http::StatusCode;
-use reqwest::header::CONTENT_TYPE;
+use reqwest::header::{CONTENT_TYPE, LOCATION};
 use serde_json::Value;
-use std::collections::HashSet;
 use std::sync::{Arc, LazyLock};
+use url_shortener_ztm_lib::configuration::{DatabaseType, SettingsOverrides, ValidatedSettings};
 use url_shortener_ztm_lib::core::security::jwt::JwtKeys;
 use url_shortener_ztm_lib::database::{SqliteUrlDatabase, UrlDatabase};
-use url_shortener_ztm_lib::generator::{self, build_generator};
-use url_shortener_ztm_lib::get_configuration;
-use url_shortener_ztm_lib::routes::shorten::normalize_url;
-use url_shortener_ztm_lib::shortcode::bloom_filter::build_bloom_state;
+use url_shortener_ztm_lib::features::users::repositories::UserRepository;
+use url_shortener_ztm_lib::features::users::services::UserService;
 use url_shortener_ztm_lib::startup::build_router;
-use url_shortener_ztm_lib::startup::build_services;
-use url_shortener_ztm_lib::state::AppState;
+use url_shortener_ztm_lib::state::{AppState, AppStateBuilder};
 use url_shortener_ztm_lib::telemetry::{get_subscriber, init_subscriber};
+use url_shortener_ztm_lib::validation::normalize_url;
+use url_shortener_ztm_lib::{Settings, get_configuration};
 use uuid::Uuid;
 
 // set up a static variable for the tracing configuration
@@ -43,61 +43,95 @@ pub struct TestApp {
 
 // Spin up an instance of our application and returns its address (i.e. http://localhost:XXXX)
 pub async fn spawn_app() -> TestApp {
-    // Ensure that the tracing is only initialized once
-    LazyLock::force(&TRACING);
-    unsafe { std::env::set_var("BLOOM_SNAPSHOTS", "1") };
+    spawn_app_with(|_| {}).await
+}
 
-    // Randomise configuration to ensure test isolation
-    let configuration = {
-        let mut c = get_configuration().expect("Failed to read configuration");
-        c.application.port = 0;
-        c.database.url = "sqlite::memory:".to_string();
-        // Use more lenient rate limiting for tests (higher rate, smaller burst)
-        c.rate_limiting.requests_per_second = 100; // 100 req/sec for fast tests
-        c.rate_limiting.burst_size = 2; // Smaller burst for predictable testing
-        c
-    };
-
-    // Create database and run migrations
-    let sqlite_db = SqliteUrlDatabase::from_config(&configuration.database)
-        .await
-        .expect("Failed to create database");
+// Spawn the app after letting the test adjust its configuration
+pub async fn spawn_app_with(configure: impl FnOnce(&mut Settings)) -> TestApp {
+    spawn_app_with_database(configure, |db| db).await
+}
 
-    sqlite_db.migrate().await.expect("Failed to run migrations");
-    let database: Arc<dyn UrlDatabase> = Arc::new(sqlite_db);
-    let code_generator = build_generator(&configuration.shortener);
+// Spawn the app with the test's configuration, letting it wrap the database
+// (e.g. to inject latency or failures) before the app state is built
+pub async fn spawn_app_with_database(
+    configure: impl FnOnce(&mut Settings),
+    wrap_database: impl FnOnce(Arc<dyn UrlDatabase>) -> Arc<dyn UrlDatabase>,
+) -> TestApp {
+    spawn(configure, wrap_database, |builder| builder).await
+}
 
-    let allowed_chars: HashSet<char> = {
-        let mut set: HashSet<char> = HashSet::new();
-        if let Some(alpha) = &configuration.shortener.alphabet {
-            set.extend(alpha.chars());
-        } else {
-            set.extend(generator::DEFAULT_ALPHABET);
-        }
+// Spawn the app after letting the test adjust how the app state is built
+// (e.g. to swap in services backed by in-memory repos)
+pub async fn spawn_app_with_state(
+    configure: impl FnOnce(&mut Settings),
+    adjust_state: impl FnOnce(AppStateBuilder) -> AppStateBuilder,
+) -> TestApp {
+    spawn(configure, |db| db, adjust_state).await
+}
 
-        set
-    };
-
-    // Store the API key for use in tests
-    let api_key = configuration.application.api_key;
-    let blooms = build_bloom_state(&database).await.unwrap();
-    let jwt = JwtKeys::new(configuration.application.api_key.as_bytes());
-
-    let (auth_svc, user_svc) = build_services(&configuration, &jwt).await.unwrap();
-
-    let test_app_state = AppState {
-        // db_pool: Arc::new(db_pool),
-        code_generator,
-        blooms,
-        allowed_chars,
-        api_key: configuration.application.api_key,
-        template_dir: configuration.application.templates.clone(),
-        config: configuration.clone(),
-        auth_service: auth_svc,
-        user_service: user_svc,
-        jwt,
-        database: database.clone(),
-    };
+// Spawn the app with the auth and user routes mounted, as on Postgres, their
+// services running over in-memory repos
+pub async fn spawn_app_with_accounts(configure: impl FnOnce(&mut Settings)) -> TestApp {
+    let users = Arc::new(MockUserRepo::new());
+    let auth_service = auth_service(users.clone(), Arc::new(MockAuthRepo::default()));
+    spawn_app_with_state(
+        |c| {
+            c.database.r#type = DatabaseType::Postgres;
+            configure(c);
+        },
+        |builder| {
+            builder
+                .auth_service(Arc::new(auth_service))
+                .user_service(Arc::new(UserService::new(users)))
+        },
+    )
+    .await
+}
+
+// Spawn the app with one signed-up user, letting the test adjust the
+// configuration knowing their id; returns the app and the `Cookie` header of
+// the user's browser session
+pub async fn spawn_app_with_session(
+    configure: impl FnOnce(&mut Settings, Uuid),
+) -> (TestApp, String) {
+    let users = Arc::new(MockUserRepo::new());
+    let user_id = users
+        .create("operator@example.com", b"unused", None)
+        .await
+        .unwrap()
+        .id;
+    let jwt = JwtKeys::new(b"test-secret");
+    let token = jwt.sign(user_id, 0, chrono::Duration::minutes(15)).unwrap();
+    let auth_service = auth_service(users, Arc::new(MockAuthRepo::default()));
+    let app = spawn_app_with_state(
+        |c| configure(c, user_id),
+        |builder| builder.jwt(jwt).auth_service(Arc::new(auth_service)),
+    )
+    .await;
+    (app, format!("access_token={token}"))
+}
+
+async fn spawn(
+    configure: impl FnOnce(&mut Settings),
+    wrap_database: impl FnOnce(Arc<dyn UrlDatabase>) -> Arc<dyn UrlDatabase>,
+    adjust_state: impl FnOnce(AppStateBuilder) -> AppStateBuilder,
+) -> TestApp {
+    let configuration = test_configuration(configure);
+
+    let database = wrap_database(test_database().await);
+    #[cfg(feature = "metrics")]
+    let database = url_shortener_ztm_lib::telemetry::metrics::MeteredDatabase::wrap(database);
+
+    // Store the first API key for use in tests
+    let api_key = configuration.application.api_keys[0]
+        .key
+        .plaintext()
+        .expect("the first test API key is not hashed");
+    let test_app_state = adjust_state(test_state_builder(&configuration, database.clone()))
+        .build()
+        .await
+        .expect("Failed to build the app state");
+    test_app_state.blooms().wait_until_ready().await;
 
     // Launch the application as a background task
     let test_app = build_router(test_app_state.clone())
@@ -139,6 +173,76 @@ pub async fn spawn_app() -> TestApp {
     }
 }
 
+// The database the app is spawned with: in-memory SQLite with its migrations
+// run, or with the `testing` feature and TEST_DATABASE=memory the
+// DashMap-backed InMemoryUrlDatabase, which starts faster
+async fn test_database() -> Arc<dyn UrlDatabase> {
+    #[cfg(feature = "testing")]
+    if std::env::var("TEST_DATABASE").is_ok_and(|db| db == "memory") {
+        return Arc::new(url_shortener_ztm_lib::database::InMemoryUrlDatabase::new());
+    }
+    Arc::new(
+        SqliteUrlDatabase::in_memory_with_migrations()
+            .await
+            .expect("Failed to create database"),
+    )
+}
+
+// Read the configuration with test defaults, then let the test adjust it
+pub fn test_configuration(configure: impl FnOnce(&mut Settings)) -> Settings {
+    // Ensure that the tracing is only initialized once
+    LazyLock::force(&TRACING);
+    unsafe { std::env::set_var("BLOOM_SNAPSHOTS", "1") };
+
+    // Randomise configuration to ensure test isolation
+    let mut c = get_configuration().expect("Failed to read configuration");
+    c.merge(SettingsOverrides::for_test());
+    configure(&mut c);
+    c
+}
+
+// A port nothing listens on right now, for building the real `Application`,
+// whose configuration must not use port 0
+pub fn free_port() -> u16 {
+    std::net::TcpListener::bind("[::]:0")
+        .or_else(|_| std::net::TcpListener::bind("127.0.0.1:0"))
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+// Test settings that pass validation, for building the real `Application`:
+// a free port, and a burst no smaller than the per-second limit
+pub fn app_configuration(configure: impl FnOnce(&mut Settings)) -> ValidatedSettings {
+    let config = test_configuration(|c| {
+        c.application.port = free_port();
+        c.rate_limiting.burst_size = c.rate_limiting.requests_per_second as u32;
+        configure(c);
+    });
+    ValidatedSettings::try_from(config).expect("Test settings should be valid")
+}
+
+// Build the app state around any database, for calling handlers directly;
+// waits for the Bloom filter to be filled, so lookups go through it
+pub async fn build_test_state(
+    configuration: &Settings,
+    database: Arc<dyn UrlDatabase>,
+) -> AppState {
+    let state = test_state_builder(configuration, database)
+        .build()
+        .await
+        .expect("Failed to build the app state");
+    state.blooms().wait_until_ready().await;
+    state
+}
+
+fn test_state_builder(configuration: &Settings, database: Arc<dyn UrlDatabase>) -> AppStateBuilder {
+    AppStateBuilder::new()
+        .config(configuration.clone())
+        .database(database)
+}
+
 // convenience helpers to reduce boilerplate in tests
 impl TestApp {
     // Build a full URL from a path
@@ -165,6 +269,16 @@ impl TestApp {
             .expect("Failed to execute GET request")
     }
 
+    // GET request with API key header, for admin pages outside /api
+    pub async fn get_with_key(&self, path: &str) -> reqwest::Response {
+        self.client
+            .get(self.url(path))
+            .header("x-api-key", self.api_key.to_string())
+            .send()
+            .await
+            .expect("Failed to execute GET request")
+    }
+
     // Simple API GET request
     pub async fn get_api(&self, path: &str) -> reqwest::Response {
         self.client
@@ -229,6 +343,100 @@ impl TestApp {
         }
     }
 
+    // Shorten a URL through the protected API and return its id
+    pub async fn shorten_url(&self, url: &str) -> String {
+        let response = self.post_api_with_key("/api/shorten", url).await;
+        shortened_id(response).await
+    }
+
+    // Shorten a URL under a custom alias and return the id, i.e. the alias
+    pub async fn shorten_url_with_alias(&self, url: &str, alias: &str) -> String {
+        let response = self
+            .post_api_with_key(&format!("/api/shorten?alias={}", alias), url)
+            .await;
+        shortened_id(response).await
+    }
+
+    // GET /{code} and return the Location header of a 308 redirect, if any
+    pub async fn get_redirect_location(&self, code: &str) -> Option<String> {
+        let response = self.get(&format!("/{}", code)).await;
+        if response.status() != StatusCode::PERMANENT_REDIRECT {
+            return None;
+        }
+        response
+            .headers()
+            .get(LOCATION)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string)
+    }
+
+    // Authenticated POST of a JSON body with API key header
+    pub async fn post_json_with_key(
+        &self,
+        path: &str,
+        body: &serde_json::Value,
+    ) -> reqwest::Response {
+        self.client
+            .post(self.api(path))
+            .header("x-api-key", self.api_key.to_string())
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute POST request")
+    }
+
+    // Authenticated POST of a raw body with an explicit Content-Type and API key header
+    pub async fn post_typed_with_key(
+        &self,
+        path: &str,
+        content_type: &str,
+        body: impl Into<String>,
+    ) -> reqwest::Response {
+        self.client
+            .post(self.api(path))
+            .header("x-api-key", self.api_key.to_string())
+            .header(CONTENT_TYPE, content_type)
+            .body(body.into())
+            .send()
+            .await
+            .expect("Failed to execute POST request")
+    }
+
+    // Authenticated API GET request with API key header
+    pub async fn get_api_with_key(&self, path: &str) -> reqwest::Response {
+        self.client
+            .get(self.api(path))
+            .header("x-api-key", self.api_key.to_string())
+            .send()
+            .await
+            .expect("Failed to execute GET request")
+    }
+
+    // Authenticated API DELETE request with API key header
+    pub async fn delete_api_with_key(&self, path: &str) -> reqwest::Response {
+        self.client
+            .delete(self.api(path))
+            .header("x-api-key", self.api_key.to_string())
+            .send()
+            .await
+            .expect("Failed to execute DELETE request")
+    }
+
+    // Authenticated API PATCH of a raw body with API key header
+    pub async fn patch_api_with_key(
+        &self,
+        path: &str,
+        body: impl Into<String>,
+    ) -> reqwest::Response {
+        self.client
+            .patch(self.api(path))
+            .header("x-api-key", self.api_key.to_string())
+            .body(body.into())
+            .send()
+            .await
+            .expect("Failed to execute PATCH request")
+    }
+
     // Admin route helpers
     #[allow(dead_code)]
     pub async fn get_admin_dashboard(&self) -> reqwest::Response {
@@ -300,6 +508,14 @@ pub async fn assert_json_ok(response: reqwest::Response) -> Value {
     body
 }
 
+async fn shortened_id(response: reqwest::Response) -> String {
+    let body = assert_json_ok(response).await;
+    body["data"]["id"]
+        .as_str()
+        .expect("shorten response did not include an id")
+        .to_string()
+}
+
 pub async fn assert_redirect_to(
     response: reqwest::Response,
     expected_location: &str,
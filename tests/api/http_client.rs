@@ -0,0 +1,68 @@
+// tests/api/http_client.rs
+
+// the one outbound HTTP client in AppState: reachability checks and webhook
+// deliveries both go through it; the hosts they call are only resolvable by
+// the client handed to the app state
+
+// dependencies
+use crate::helpers::spawn_app_with_state;
+use secrecy::SecretString;
+use std::time::Duration;
+use url_shortener_ztm_lib::configuration::{ValidationMode, WebhookConfig};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+#[tokio::test]
+async fn reachability_checks_and_webhooks_share_the_client() {
+    // Arrange
+    let server = MockServer::start().await;
+    Mock::given(method("HEAD"))
+        .and(path("/page"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/hook"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let port = server.address().port();
+    let client = reqwest::Client::builder()
+        .resolve("destination.test", *server.address())
+        .resolve("hooks.test", *server.address())
+        .build()
+        .expect("failed to build the test client");
+    let webhook = WebhookConfig {
+        url: format!("http://hooks.test:{}/hook", port),
+        secret: SecretString::from("webhook-secret"),
+        events: vec!["redirect".to_string()],
+        filter_code: None,
+    };
+    let app = spawn_app_with_state(
+        |c| {
+            c.url_validation.check_reachability = true;
+            c.url_validation.mode = ValidationMode::Strict;
+            c.webhooks = vec![webhook];
+        },
+        |builder| builder.http_client(client),
+    )
+    .await;
+
+    // Act
+    let code = app
+        .shorten_url(&format!("http://destination.test:{}/page", port))
+        .await;
+    app.get(&format!("/{}", code)).await;
+
+    // Assert: both requests arrived, so both were sent with the given client
+    for _ in 0..50 {
+        let received = server.received_requests().await.unwrap_or_default();
+        if received.len() >= 2 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    server.verify().await;
+}
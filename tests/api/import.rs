@@ -0,0 +1,115 @@
+// tests/api/import.rs
+
+// integration tests for POST /api/urls/import
+
+// dependencies
+use crate::helpers::{assert_json_ok, spawn_app};
+use axum::http::StatusCode;
+use serde_json::json;
+
+#[tokio::test]
+async fn json_import_keeps_given_codes() {
+    // Arrange
+    let app = spawn_app().await;
+    let payload = json!([
+        { "code": "impJson1", "url": "https://www.example.com/one" },
+        { "code": "impJson2", "url": "https://www.example.com/two" }
+    ]);
+
+    // Act
+    let response = app.post_json_with_key("/api/urls/import", &payload).await;
+
+    // Assert
+    let body = assert_json_ok(response).await;
+    assert_eq!(body["data"]["total"].as_u64(), Some(2));
+    assert_eq!(body["data"]["imported"].as_u64(), Some(2));
+    assert_eq!(body["data"]["skipped"].as_u64(), Some(0));
+    assert_eq!(body["data"]["errors"].as_array().unwrap().len(), 0);
+    assert_eq!(
+        app._database.get_url("impJson2").await.unwrap(),
+        "https://www.example.com/two"
+    );
+}
+
+#[tokio::test]
+async fn csv_import_handles_bom_and_quoted_fields() {
+    let app = spawn_app().await;
+    let csv = "\u{feff}code,url\n\
+               impCsv1,https://www.example.com/plain\n\
+               \"impCsv2\",\"https://www.example.com/search?q=a,b\"\n";
+
+    let response = app
+        .post_typed_with_key("/api/urls/import", "text/csv", csv)
+        .await;
+
+    let body = assert_json_ok(response).await;
+    assert_eq!(body["data"]["imported"].as_u64(), Some(2));
+    assert_eq!(
+        app._database.get_url("impCsv2").await.unwrap(),
+        "https://www.example.com/search?q=a,b"
+    );
+
+    // Imported codes are known to the redirect path straight away
+    let redirect = app.get("/impCsv1").await;
+    assert_eq!(redirect.status(), StatusCode::PERMANENT_REDIRECT);
+}
+
+#[tokio::test]
+async fn existing_codes_are_skipped_not_errors() {
+    let app = spawn_app().await;
+    app._database
+        .insert_url("impTaken", "https://www.example.com/original")
+        .await
+        .unwrap();
+    let payload = json!([
+        { "code": "impTaken", "url": "https://www.example.com/other" },
+        { "code": "impFresh", "url": "https://www.example.com/fresh" }
+    ]);
+
+    let response = app.post_json_with_key("/api/urls/import", &payload).await;
+
+    let body = assert_json_ok(response).await;
+    assert_eq!(body["data"]["imported"].as_u64(), Some(1));
+    assert_eq!(body["data"]["skipped"].as_u64(), Some(1));
+    assert_eq!(body["data"]["errors"].as_array().unwrap().len(), 0);
+    assert_eq!(
+        app._database.get_url("impTaken").await.unwrap(),
+        "https://www.example.com/original"
+    );
+}
+
+#[tokio::test]
+async fn invalid_url_is_reported_and_rest_imported() {
+    let app = spawn_app().await;
+    let payload = json!([
+        { "code": "impGood1", "url": "https://www.example.com/good" },
+        { "code": "impBad01", "url": "not a url" }
+    ]);
+
+    let response = app.post_json_with_key("/api/urls/import", &payload).await;
+
+    let body = assert_json_ok(response).await;
+    assert_eq!(body["data"]["total"].as_u64(), Some(2));
+    assert_eq!(body["data"]["imported"].as_u64(), Some(1));
+    let errors = body["data"]["errors"].as_array().unwrap();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0]["row"].as_u64(), Some(2));
+    assert_eq!(errors[0]["code"].as_str(), Some("impBad01"));
+    assert!(app._database.get_url("impBad01").await.is_err());
+}
+
+#[tokio::test]
+async fn import_over_row_limit_is_rejected() {
+    let app = spawn_app().await;
+    let mut csv = String::from("code,url\n");
+    for i in 0..10_001 {
+        csv.push_str(&format!("imp{i},https://www.example.com/{i}\n"));
+    }
+
+    let response = app
+        .post_typed_with_key("/api/urls/import", "text/csv", csv)
+        .await;
+
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(app._database.get_url("imp0").await.is_err());
+}
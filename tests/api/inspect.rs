@@ -0,0 +1,207 @@
+// tests/api/inspect.rs
+
+// integration tests for the /api/urls/{id} metadata endpoint
+// this endpoint should describe a short code without redirecting
+
+// dependencies
+use crate::helpers::{TestApp, assert_json_ok, spawn_app};
+use serde_json::Value;
+
+#[tokio::test]
+async fn inspect_returns_metadata_for_a_shortened_url() {
+    // Arrange
+    let app = spawn_app().await;
+    let shorten_response = app
+        .post_api_with_key("/api/shorten", "https://www.example.com/inspect")
+        .await;
+    let body = assert_json_ok(shorten_response).await;
+    let id = body["data"]["id"].as_str().expect("missing id").to_string();
+
+    // Act
+    let response = app.get_api(&format!("/api/urls/{}", id)).await;
+
+    // Assert
+    let body = assert_json_ok(response).await;
+    let data = &body["data"];
+    assert_eq!(data["code"].as_str(), Some(id.as_str()));
+    assert_eq!(
+        data["url"].as_str(),
+        Some("https://www.example.com/inspect")
+    );
+    assert_eq!(data["click_count"].as_u64(), Some(0));
+    assert_eq!(data["is_active"].as_bool(), Some(true));
+    assert!(data["expires_at"].is_null());
+    assert!(data["created_at"].as_str().is_some());
+}
+
+#[tokio::test]
+async fn inspect_resolves_aliases_to_their_target() {
+    // Arrange
+    let app = spawn_app().await;
+    let response = app
+        .post_api_with_key(
+            "/api/shorten?alias=inspectme",
+            "https://www.example.com/aliased",
+        )
+        .await;
+    assert_json_ok(response).await;
+
+    // Act
+    let response = app.get_api("/api/urls/inspectme").await;
+
+    // Assert
+    let body = assert_json_ok(response).await;
+    assert_eq!(body["data"]["code"].as_str(), Some("inspectme"));
+    assert_eq!(
+        body["data"]["url"].as_str(),
+        Some("https://www.example.com/aliased")
+    );
+}
+
+#[tokio::test]
+async fn inspect_returns_404_for_unknown_code() {
+    let app = spawn_app().await;
+
+    let response = app.get_api("/api/urls/nope404").await;
+
+    assert_eq!(response.status().as_u16(), 404);
+    let body: Value = response.json().await.expect("invalid JSON");
+    assert_eq!(body["success"].as_bool(), Some(false));
+}
+
+#[tokio::test]
+async fn inspect_counts_redirects_but_not_itself() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = app.shorten_url("https://www.example.com/counted").await;
+
+    // Act
+    let before = assert_json_ok(app.get_api(&format!("/api/urls/{}", id)).await).await;
+    app.get(&format!("/{}", id)).await;
+    let after = assert_json_ok(app.get_api(&format!("/api/urls/{}", id)).await).await;
+
+    // Assert
+    assert_eq!(before["data"]["click_count"].as_u64(), Some(0));
+    assert_eq!(after["data"]["click_count"].as_u64(), Some(1));
+}
+
+#[tokio::test]
+async fn head_answers_existence_without_a_body() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = app.shorten_url("https://www.example.com/head").await;
+
+    // Act
+    let found = app
+        .client
+        .head(app.url(&format!("/api/urls/{}", id)))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let missing = app
+        .client
+        .head(app.url("/api/urls/nope404"))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(found.status().as_u16(), 200);
+    assert!(found.bytes().await.expect("no body").is_empty());
+    assert_eq!(missing.status().as_u16(), 404);
+    assert!(missing.bytes().await.expect("no body").is_empty());
+}
+
+#[tokio::test]
+async fn shorten_response_includes_expires_at() {
+    let app = spawn_app().await;
+
+    let response = app
+        .post_api_with_key("/api/shorten", "https://www.example.com/expiry")
+        .await;
+
+    let body = assert_json_ok(response).await;
+    let data = body["data"].as_object().expect("missing data object");
+    assert!(data.contains_key("expires_at"));
+    assert!(data["expires_at"].is_null());
+}
+
+fn etag(response: &reqwest::Response) -> Option<String> {
+    response
+        .headers()
+        .get("etag")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+}
+
+async fn inspect_with_if_none_match(app: &TestApp, id: &str, tag: &str) -> reqwest::Response {
+    app.client
+        .get(app.url(&format!("/api/urls/{}", id)))
+        .header("if-none-match", tag)
+        .send()
+        .await
+        .expect("Failed to execute request.")
+}
+
+#[tokio::test]
+async fn inspect_sends_an_etag_and_must_revalidate() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = app.shorten_url("https://www.example.com/polled").await;
+
+    // Act
+    let response = app.get_api(&format!("/api/urls/{}", id)).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        response
+            .headers()
+            .get("cache-control")
+            .and_then(|v| v.to_str().ok()),
+        Some("private, no-cache")
+    );
+    let tag = etag(&response).expect("metadata should carry an ETag");
+    assert!(tag.starts_with('"') && tag.ends_with('"'), "{}", tag);
+    let body = assert_json_ok(response).await;
+    assert!(body["data"]["updated_at"].as_str().is_some());
+}
+
+#[tokio::test]
+async fn inspect_with_current_etag_gets_not_modified() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = app
+        .shorten_url("https://www.example.com/unchanged-meta")
+        .await;
+    let tag = etag(&app.get_api(&format!("/api/urls/{}", id)).await).expect("missing ETag");
+
+    // Act
+    let response = inspect_with_if_none_match(&app, &id, &tag).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 304);
+    assert_eq!(etag(&response), Some(tag));
+    assert!(response.bytes().await.expect("no body").is_empty());
+}
+
+#[tokio::test]
+async fn inspect_etag_changes_after_a_click() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = app
+        .shorten_url("https://www.example.com/clicked-meta")
+        .await;
+    let tag = etag(&app.get_api(&format!("/api/urls/{}", id)).await).expect("missing ETag");
+    app.get(&format!("/{}", id)).await;
+
+    // Act
+    let response = inspect_with_if_none_match(&app, &id, &tag).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 200);
+    let new_tag = etag(&response).expect("missing ETag");
+    assert_ne!(new_tag, tag);
+    let body = assert_json_ok(response).await;
+    assert_eq!(body["data"]["click_count"].as_u64(), Some(1));
+}
@@ -0,0 +1,73 @@
+// tests/api/ipv6.rs
+
+// tests for serving IPv6 clients with `application.bind_ipv6`
+
+// dependencies
+use crate::helpers::app_configuration;
+use reqwest::StatusCode;
+use url_shortener_ztm_lib::configuration::Settings;
+use url_shortener_ztm_lib::startup::Application;
+
+// Hosts without IPv6 (e.g. some containers) cannot run these tests
+fn ipv6_available() -> bool {
+    std::net::TcpListener::bind("[::1]:0").is_ok()
+}
+
+// Build the application on a free port with IPv6 on and run it in the background
+async fn start_app(configure: impl FnOnce(&mut Settings)) -> (u16, Vec<std::net::SocketAddr>) {
+    let config = app_configuration(|c| {
+        c.application.host = "127.0.0.1".to_string();
+        c.application.bind_ipv6 = true;
+        c.database.url = ":memory:".to_string();
+        configure(c);
+    });
+    let app = Application::build(config)
+        .await
+        .expect("Failed to build the application");
+    let port = app.port();
+    let addrs = app.local_addrs();
+    tokio::spawn(app.run_until_stopped());
+    (port, addrs)
+}
+
+async fn health_check(host: &str, port: u16) -> StatusCode {
+    reqwest::get(format!("http://{}:{}/api/health_check", host, port))
+        .await
+        .expect("Failed to execute request.")
+        .status()
+}
+
+#[tokio::test]
+async fn dual_stack_listener_answers_over_ipv4_and_ipv6() {
+    if !ipv6_available() {
+        return;
+    }
+
+    // Arrange
+    let (port, addrs) = start_app(|_| {}).await;
+
+    // Act / Assert
+    assert_eq!(health_check("[::1]", port).await, StatusCode::OK);
+    assert_eq!(health_check("127.0.0.1", port).await, StatusCode::OK);
+    assert_eq!(addrs.len(), 1);
+    assert!(addrs[0].is_ipv6());
+    assert_eq!(addrs[0].port(), port);
+}
+
+#[tokio::test]
+async fn ipv6_only_serves_ipv4_from_a_second_listener_on_the_same_port() {
+    if !ipv6_available() {
+        return;
+    }
+
+    // Arrange
+    let (port, addrs) = start_app(|c| c.application.ipv6_only = true).await;
+
+    // Act / Assert
+    assert_eq!(health_check("[::1]", port).await, StatusCode::OK);
+    assert_eq!(health_check("127.0.0.1", port).await, StatusCode::OK);
+    assert_eq!(addrs.len(), 2);
+    assert!(addrs.iter().any(|a| a.is_ipv4()));
+    assert!(addrs.iter().any(|a| a.is_ipv6()));
+    assert!(addrs.iter().all(|a| a.port() == port));
+}
@@ -0,0 +1,115 @@
+// tests/api/lockout.rs
+
+// locking an account after repeated failed sign-ins; the auth routes are only
+// mounted on Postgres, so AuthService is called directly with in-memory repos
+
+// dependencies
+use crate::mock_repos::{MockUserRepo, auth_service_with_user};
+use secrecy::SecretString;
+use std::sync::Arc;
+use url_shortener_ztm_lib::ApiError;
+use url_shortener_ztm_lib::configuration::LockoutSettings;
+use url_shortener_ztm_lib::features::auth::AuthService;
+use url_shortener_ztm_lib::features::auth::dto::SignInReq;
+use url_shortener_ztm_lib::middleware::ClientMeta;
+use uuid::Uuid;
+
+const EMAIL: &str = "someone@example.com";
+const PASSWORD: &str = "Correct-Horse-42";
+
+async fn service_with_user() -> (AuthService, Arc<MockUserRepo>, Uuid) {
+    let t = auth_service_with_user(EMAIL, PASSWORD).await;
+    let service = t.service.with_lockout(LockoutSettings {
+        max_attempts: 3,
+        window_minutes: 30,
+        lockout_duration_minutes: 60,
+    });
+    (service, t.users, t.user_id)
+}
+
+async fn sign_in(service: &AuthService, password: &str) -> anyhow::Result<()> {
+    service
+        .sign_in(
+            SignInReq {
+                email: EMAIL.to_string(),
+                password: SecretString::from(password),
+                device_id: None,
+            },
+            ClientMeta {
+                ip: Some("203.0.113.7".parse().unwrap()),
+                user_agent: None,
+            },
+        )
+        .await
+        .map(|_| ())
+}
+
+fn is_locked(result: anyhow::Result<()>) -> bool {
+    matches!(
+        result.err().and_then(|e| e.downcast::<ApiError>().ok()),
+        Some(ApiError::Forbidden(_))
+    )
+}
+
+#[tokio::test]
+async fn repeated_failures_lock_the_account() {
+    // Arrange
+    let (service, users, id) = service_with_user().await;
+    for _ in 0..2 {
+        assert!(!is_locked(sign_in(&service, "wrong-password").await));
+    }
+
+    // Act
+    let third = sign_in(&service, "wrong-password").await;
+    let correct = sign_in(&service, PASSWORD).await;
+
+    // Assert
+    assert!(is_locked(third));
+    assert!(
+        is_locked(correct),
+        "the right password is refused while locked"
+    );
+    let locked_until = users.users()[0].locked_until.expect("user was not locked");
+    let minutes = (locked_until - chrono::Utc::now()).num_minutes();
+    assert!(
+        (58..=60).contains(&minutes),
+        "locked for {} minutes",
+        minutes
+    );
+    assert_eq!(users.users()[0].id, id);
+}
+
+#[tokio::test]
+async fn a_successful_sign_in_resets_the_failure_count() {
+    // Arrange
+    let (service, users, _) = service_with_user().await;
+    for _ in 0..2 {
+        sign_in(&service, "wrong-password").await.unwrap_err();
+    }
+    sign_in(&service, PASSWORD).await.unwrap();
+
+    // Act
+    for _ in 0..2 {
+        assert!(!is_locked(sign_in(&service, "wrong-password").await));
+    }
+
+    // Assert
+    assert!(users.users()[0].locked_until.is_none());
+    sign_in(&service, PASSWORD).await.unwrap();
+}
+
+#[tokio::test]
+async fn the_lock_expires_after_its_duration() {
+    // Arrange
+    let (service, users, id) = service_with_user().await;
+    for _ in 0..3 {
+        sign_in(&service, "wrong-password").await.unwrap_err();
+    }
+    assert!(is_locked(sign_in(&service, PASSWORD).await));
+
+    // Act
+    users.expire_lock(id);
+
+    // Assert
+    sign_in(&service, PASSWORD).await.unwrap();
+}
@@ -1,10 +1,82 @@
 // tests/api/main.rs
 
+mod ab_testing;
+mod account_deletion;
+mod admin_config;
+mod admin_stats;
+mod admin_urls;
+mod alias_prefix;
 mod alias_validation_consistency;
+mod api_keys;
+mod api_version;
+mod auth_audit;
+mod bloom_rebuild;
+mod bloom_snapshot_store;
+#[cfg(feature = "testing")]
+mod catch_panic;
+mod cli;
+mod click_events;
+mod click_limits;
+mod compression;
+mod cors;
+mod delete;
+mod devices;
+mod docs;
 mod error_handling;
+mod error_pages;
+mod expiry_reminders;
+mod export;
+mod geolocation;
+mod graceful_shutdown;
+mod handlers;
 mod health_check;
 mod helpers;
+mod http_client;
+mod import;
+mod inspect;
+mod ipv6;
+mod lockout;
+#[cfg(feature = "metrics")]
+mod metrics;
+mod mobile_redirects;
+mod mock_db;
+mod mock_repos;
+mod oauth;
+mod preflight;
+mod preview;
+mod problem_details;
+mod qr;
 mod rate_limiting;
+mod reachability;
+mod real_ip;
 mod redirect;
+#[cfg(feature = "redis")]
+mod redis_rate_limiting;
+mod safe_browsing;
+mod search;
+mod security_headers;
 mod shorten;
+mod shorten_batch;
+mod shorten_form;
+mod sqlite_lookup;
 mod static_assets;
+mod tags;
+mod timeout;
+mod totp;
+#[cfg(unix)]
+mod unix_socket;
+mod update;
+mod url_analytics;
+mod url_filter;
+mod url_ownership;
+mod url_passwords;
+mod url_preview;
+mod url_status;
+mod url_validation_mode;
+mod user_me;
+mod user_profile;
+mod utm;
+mod vanity_domains;
+mod verification_codes;
+mod webhook_auth;
+mod webhooks;
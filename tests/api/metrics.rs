@@ -0,0 +1,55 @@
+// tests/api/metrics.rs
+
+// integration tests for GET /metrics (requires the `metrics` feature)
+
+// dependencies
+use crate::helpers::{assert_json_ok, spawn_app};
+use reqwest::StatusCode;
+
+// Value of the first sample line starting with `series`
+fn sample(body: &str, series: &str) -> Option<f64> {
+    body.lines()
+        .find(|line| line.starts_with(series))
+        .and_then(|line| line.rsplit(' ').next())
+        .and_then(|value| value.parse().ok())
+}
+
+#[tokio::test]
+async fn metrics_counts_redirects_per_code() {
+    // Arrange
+    let app = spawn_app().await;
+    let body = assert_json_ok(
+        app.post_api_with_key("/api/shorten", "https://www.example.com/metrics")
+            .await,
+    )
+    .await;
+    let code = body["data"]["id"].as_str().unwrap().to_string();
+
+    // Act
+    for _ in 0..2 {
+        let response = app.get(&format!("/{}", code)).await;
+        assert!(response.status().is_redirection());
+    }
+    let response = app.get("/metrics").await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(
+        response.headers()["content-type"]
+            .to_str()
+            .unwrap()
+            .starts_with("text/plain")
+    );
+    let body = response.text().await.unwrap();
+    // The recorder is process-wide, so only this test's code is asserted on
+    assert_eq!(
+        sample(&body, &format!("redirects_total{{code=\"{}\"}}", code)),
+        Some(2.0)
+    );
+    assert!(sample(&body, "short_codes_created_total").unwrap() >= 1.0);
+    // The second redirect is answered from the cache
+    assert!(sample(&body, "cache_hits_total{cache=\"redirect\"}").unwrap() >= 1.0);
+    assert!(body.contains("http_request_duration_seconds_bucket{method=\"GET\",route=\"/{id}\""));
+    assert!(body.contains("db_query_duration_seconds_bucket{operation=\"get_url_with_metadata\""));
+    assert!(body.contains("bloom_filter_hits_total{filter=\"s2l\",result=\"true_positive\"}"));
+}
@@ -0,0 +1,97 @@
+// tests/api/mobile_redirects.rs
+
+// device-based redirects: mobile visitors go to mobile_url, everyone else to
+// the shortened URL, and the stats count clicks per device type
+
+// dependencies
+use crate::helpers::{TestApp, assert_json_ok, spawn_app};
+use axum::http::StatusCode;
+use serde_json::json;
+
+const IPHONE: &str = "Mozilla/5.0 (iPhone; CPU iPhone OS 17_0 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.0 Mobile/15E148 Safari/604.1";
+const ANDROID: &str = "Mozilla/5.0 (Linux; Android 14; Pixel 8) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0 Mobile Safari/537.36";
+const DESKTOP: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0 Safari/537.36";
+
+async fn shorten_with_mobile_url(app: &TestApp) -> String {
+    let response = app
+        .post_json_with_key(
+            "/api/shorten",
+            &json!({
+                "url": "https://www.example.com/desktop",
+                "mobile_url": "https://m.example.com/"
+            }),
+        )
+        .await;
+    let body = assert_json_ok(response).await;
+    body["data"]["id"]
+        .as_str()
+        .expect("shorten response did not include an id")
+        .to_string()
+}
+
+async fn redirect_as(app: &TestApp, id: &str, user_agent: &str) -> reqwest::Response {
+    app.client
+        .get(app.url(&format!("/{}", id)))
+        .header("user-agent", user_agent)
+        .send()
+        .await
+        .expect("Failed to execute request.")
+}
+
+fn location(response: &reqwest::Response) -> Option<&str> {
+    response
+        .headers()
+        .get("location")
+        .and_then(|v| v.to_str().ok())
+}
+
+#[tokio::test]
+async fn mobile_user_agent_is_sent_to_the_mobile_url() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = shorten_with_mobile_url(&app).await;
+
+    // Act
+    let response = redirect_as(&app, &id, IPHONE).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+    assert_eq!(location(&response), Some("https://m.example.com/"));
+    assert_eq!(response.headers()["vary"], "user-agent");
+}
+
+#[tokio::test]
+async fn desktop_user_agent_is_sent_to_the_shortened_url() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = shorten_with_mobile_url(&app).await;
+
+    // Act
+    let response = redirect_as(&app, &id, DESKTOP).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+    assert_eq!(location(&response), Some("https://www.example.com/desktop"));
+}
+
+#[tokio::test]
+async fn stats_count_clicks_per_device_type() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = shorten_with_mobile_url(&app).await;
+
+    // Act
+    for user_agent in [IPHONE, ANDROID, DESKTOP] {
+        redirect_as(&app, &id, user_agent).await;
+    }
+    let stats = assert_json_ok(
+        app.get_api_with_key(&format!("/api/urls/{}/stats", id))
+            .await,
+    )
+    .await;
+
+    // Assert
+    assert_eq!(stats["data"]["total_clicks"], 3);
+    assert_eq!(stats["data"]["mobile_clicks"], 2);
+    assert_eq!(stats["data"]["desktop_clicks"], 1);
+}
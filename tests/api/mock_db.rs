@@ -0,0 +1,875 @@
+// tests/api/mock_db.rs
+
+// in-memory UrlDatabase for calling handlers directly, with error injection
+
+// dependencies
+use async_trait::async_trait;
+use chrono::{DateTime, Timelike, Utc};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Mutex;
+use url_shortener_ztm_lib::database::{CLICK_BUCKETS, DatabaseError, UrlDatabase, referrer_origin};
+use url_shortener_ztm_lib::models::{
+    AbTarget, BatchInsertResult, ClickBucket, ClickRecord, ClickSummary, CountryCount,
+    DatabaseStats, ExpiringUrl, ExportRecord, MaintenanceStep, ReferrerCount, UpsertResult,
+    UrlMetadata, UrlRecord, UrlStatus, Urls, VariantCount,
+};
+use uuid::Uuid;
+
+// A stored URL, keyed by its primary code
+#[derive(Debug, Clone)]
+pub struct UrlEntry {
+    pub id: i64,
+    pub url: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub preview_views: u64,
+    pub redirect_type: String,
+    pub owner_id: Option<Uuid>,
+    pub password_hash: Option<Vec<u8>>,
+    pub ab_targets: Vec<AbTarget>,
+    pub mobile_url: Option<String>,
+    pub max_clicks: Option<u64>,
+    pub deactivated_at: Option<DateTime<Utc>>,
+    pub expiry_reminder_sent_at: Option<DateTime<Utc>>,
+    pub clicks: Vec<Click>,
+}
+
+impl UrlEntry {
+    // The status the real databases report for this entry
+    fn status(&self) -> UrlStatus {
+        if self.deactivated_at.is_some() {
+            UrlStatus::MaxClicksReached
+        } else if self.expires_at.is_some_and(|at| at <= Utc::now()) {
+            UrlStatus::Expired
+        } else if self.password_hash.is_some() {
+            UrlStatus::PasswordProtected
+        } else {
+            UrlStatus::Active
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Click {
+    pub clicked_at: DateTime<Utc>,
+    pub ip: Option<String>,
+    pub referrer: Option<String>,
+    pub variant: Option<u8>,
+    pub device_type: Option<String>,
+    pub country_code: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+#[derive(Default)]
+struct MockState {
+    urls: HashMap<String, UrlEntry>,
+    aliases: HashMap<String, i64>,
+    tags: Vec<(String, String)>,
+    snapshots: HashMap<String, Vec<u8>>,
+    next_id: i64,
+    fail_next: HashSet<String>,
+    duplicates_next: HashMap<String, usize>,
+    transient_next: HashMap<String, usize>,
+    calls: Vec<String>,
+}
+
+impl MockState {
+    // Record the call, failing it if an error was injected for the operation
+    fn enter(&mut self, op: &str) -> Result<(), DatabaseError> {
+        self.calls.push(op.to_string());
+        if self.fail_next.remove(op) {
+            return Err(DatabaseError::QueryError(format!(
+                "injected {} failure",
+                op
+            )));
+        }
+        if let Some(left) = self.duplicates_next.get_mut(op).filter(|left| **left > 0) {
+            *left -= 1;
+            return Err(DatabaseError::Duplicate);
+        }
+        if let Some(left) = self.transient_next.get_mut(op).filter(|left| **left > 0) {
+            *left -= 1;
+            return Err(DatabaseError::Transient(format!(
+                "injected {} serialization failure",
+                op
+            )));
+        }
+        Ok(())
+    }
+
+    fn code_of(&self, id: i64) -> Option<&str> {
+        self.urls
+            .iter()
+            .find(|(_, entry)| entry.id == id)
+            .map(|(code, _)| code.as_str())
+    }
+
+    fn code_by_url(&self, url: &str) -> Option<&str> {
+        self.urls
+            .iter()
+            .find(|(_, entry)| entry.url == url)
+            .map(|(code, _)| code.as_str())
+    }
+
+    // Resolves a short code or alias to the primary code of its URL
+    fn primary_code(&self, code: &str) -> Result<String, DatabaseError> {
+        if self.urls.contains_key(code) {
+            return Ok(code.to_string());
+        }
+        self.aliases
+            .get(code)
+            .and_then(|id| self.code_of(*id))
+            .map(str::to_string)
+            .ok_or(DatabaseError::NotFound)
+    }
+
+    fn entry_mut(&mut self, code: &str) -> Result<&mut UrlEntry, DatabaseError> {
+        let primary = self.primary_code(code)?;
+        Ok(self.urls.get_mut(&primary).expect("primary code is stored"))
+    }
+
+    fn insert(&mut self, code: &str, url: &str) -> Result<(UpsertResult, Urls), DatabaseError> {
+        if let Some(existing) = self.code_by_url(url) {
+            let existing = existing.to_string();
+            let id = self.urls[&existing].id;
+            return Ok((
+                UpsertResult { id, created: false },
+                Urls { id, code: existing },
+            ));
+        }
+        if self.urls.contains_key(code) {
+            return Err(DatabaseError::Duplicate);
+        }
+
+        self.next_id += 1;
+        let id = self.next_id;
+        let now = Utc::now();
+        self.urls.insert(
+            code.to_string(),
+            UrlEntry {
+                id,
+                url: url.to_string(),
+                created_at: now,
+                updated_at: now,
+                expires_at: None,
+                preview_views: 0,
+                redirect_type: "permanent".to_string(),
+                owner_id: None,
+                password_hash: None,
+                ab_targets: Vec::new(),
+                mobile_url: None,
+                max_clicks: None,
+                deactivated_at: None,
+                expiry_reminder_sent_at: None,
+                clicks: Vec::new(),
+            },
+        );
+        Ok((
+            UpsertResult { id, created: true },
+            Urls {
+                id,
+                code: code.to_string(),
+            },
+        ))
+    }
+
+    // Primary codes in insertion order, like `ORDER BY id`
+    fn records(&self) -> Vec<(&String, &UrlEntry)> {
+        let mut records: Vec<_> = self.urls.iter().collect();
+        records.sort_by_key(|(_, entry)| entry.id);
+        records
+    }
+}
+
+// Keeps everything in memory behind a mutex. Every call is recorded for
+// `calls()`, `with_error_on_next` makes the next call of an operation fail
+// with a `QueryError`, and `with_duplicates_on_next` makes the next calls
+// collide.
+#[derive(Default)]
+pub struct MockUrlDatabase {
+    state: Mutex<MockState>,
+}
+
+impl MockUrlDatabase {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Fail the next call of `op` (a trait method name, e.g. "insert_url")
+    pub fn with_error_on_next(&self, op: &str) -> &Self {
+        self.state.lock().unwrap().fail_next.insert(op.to_string());
+        self
+    }
+
+    // Fail the next `count` calls of `op` with `Duplicate`, as if the generated
+    // code were already taken
+    pub fn with_duplicates_on_next(&self, op: &str, count: usize) -> &Self {
+        self.state
+            .lock()
+            .unwrap()
+            .duplicates_next
+            .insert(op.to_string(), count);
+        self
+    }
+
+    // Fail the next `count` calls of `op` with `Transient`, as a PostgreSQL
+    // serialization failure would
+    pub fn with_transient_errors_on_next(&self, op: &str, count: usize) -> &Self {
+        self.state
+            .lock()
+            .unwrap()
+            .transient_next
+            .insert(op.to_string(), count);
+        self
+    }
+
+    // Names of the trait methods called so far, in order
+    pub fn calls(&self) -> Vec<String> {
+        self.state.lock().unwrap().calls.clone()
+    }
+
+    // The stored entry for a short code or alias
+    pub fn entry(&self, code: &str) -> Option<UrlEntry> {
+        let state = self.state.lock().unwrap();
+        let primary = state.primary_code(code).ok()?;
+        state.urls.get(&primary).cloned()
+    }
+}
+
+fn page<T>(items: impl IntoIterator<Item = T>, offset: u64, limit: u64) -> Vec<T> {
+    items
+        .into_iter()
+        .skip(offset as usize)
+        .take(limit as usize)
+        .collect()
+}
+
+fn to_record((code, entry): (&String, &UrlEntry)) -> UrlRecord {
+    UrlRecord {
+        code: code.clone(),
+        url: entry.url.clone(),
+        owner_id: entry.owner_id,
+    }
+}
+
+// Clicks keep the referrer they were recorded with; it is reduced to its
+// origin here, as the real databases do when storing it
+fn count_referrers(clicks: &[Click], limit: u8) -> Vec<ReferrerCount> {
+    let mut origins: BTreeMap<String, u64> = BTreeMap::new();
+    for origin in clicks
+        .iter()
+        .filter_map(|c| c.referrer.as_deref().and_then(referrer_origin))
+    {
+        *origins.entry(origin).or_default() += 1;
+    }
+    let mut referrers: Vec<ReferrerCount> = origins
+        .into_iter()
+        .map(|(referrer_origin, count)| ReferrerCount {
+            referrer_origin,
+            count,
+        })
+        .collect();
+    // Stable sort keeps referrers with equal counts in name order
+    referrers.sort_by_key(|r| std::cmp::Reverse(r.count));
+    referrers.truncate(limit as usize);
+    referrers
+}
+
+#[async_trait]
+impl UrlDatabase for MockUrlDatabase {
+    async fn insert_url(
+        &self,
+        code: &str,
+        url: &str,
+    ) -> Result<(UpsertResult, Urls), DatabaseError> {
+        let mut state = self.state.lock().unwrap();
+        state.enter("insert_url")?;
+        state.insert(code, url)
+    }
+
+    async fn insert_url_with_expiry(
+        &self,
+        code: &str,
+        url: &str,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<(UpsertResult, Urls), DatabaseError> {
+        let mut state = self.state.lock().unwrap();
+        state.enter("insert_url_with_expiry")?;
+        let (upsert, urls) = state.insert(code, url)?;
+        if upsert.created {
+            state.entry_mut(code)?.expires_at = expires_at;
+        }
+        Ok((upsert, urls))
+    }
+
+    async fn insert_urls_batch(
+        &self,
+        items: &[(&str, &str)],
+    ) -> Result<Vec<BatchInsertResult>, DatabaseError> {
+        let mut state = self.state.lock().unwrap();
+        state.enter("insert_urls_batch")?;
+        let mut results = Vec::with_capacity(items.len());
+        for (code, url) in items {
+            // A taken code is skipped, like `INSERT OR IGNORE`
+            match state.insert(code, url) {
+                Ok((upsert, urls)) => results.push(BatchInsertResult {
+                    code: urls.code,
+                    url: url.to_string(),
+                    created: upsert.created,
+                }),
+                Err(DatabaseError::Duplicate) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(results)
+    }
+
+    async fn insert_alias(&self, alias_code: &str, code_id: i64) -> Result<(), DatabaseError> {
+        let mut state = self.state.lock().unwrap();
+        state.enter("insert_alias")?;
+        if state.aliases.contains_key(alias_code) || state.urls.contains_key(alias_code) {
+            return Err(DatabaseError::Duplicate);
+        }
+        if state.code_of(code_id).is_none() {
+            return Err(DatabaseError::QueryError(format!(
+                "no URL with id {}",
+                code_id
+            )));
+        }
+        state.aliases.insert(alias_code.to_string(), code_id);
+        Ok(())
+    }
+
+    async fn get_id_by_url(&self, url: &str) -> Result<Urls, DatabaseError> {
+        let mut state = self.state.lock().unwrap();
+        state.enter("get_id_by_url")?;
+        let code = state.code_by_url(url).ok_or(DatabaseError::NotFound)?;
+        Ok(Urls {
+            id: state.urls[code].id,
+            code: code.to_string(),
+        })
+    }
+
+    async fn get_url(&self, id: &str) -> Result<String, DatabaseError> {
+        let mut state = self.state.lock().unwrap();
+        state.enter("get_url")?;
+        let entry = state.entry_mut(id)?;
+        match entry.status() {
+            UrlStatus::Active | UrlStatus::PasswordProtected => Ok(entry.url.clone()),
+            status => Err(DatabaseError::Gone(status)),
+        }
+    }
+
+    async fn get_url_with_metadata(&self, code: &str) -> Result<UrlMetadata, DatabaseError> {
+        let mut state = self.state.lock().unwrap();
+        state.enter("get_url_with_metadata")?;
+        let entry = state.entry_mut(code)?;
+        Ok(UrlMetadata {
+            code: code.to_string(),
+            url: entry.url.clone(),
+            created_at: entry.created_at,
+            click_count: entry.clicks.len() as u64,
+            preview_views: entry.preview_views,
+            redirect_type: entry.redirect_type.clone(),
+            expires_at: entry.expires_at,
+            is_active: entry.deactivated_at.is_none()
+                && entry.expires_at.is_none_or(|at| at > Utc::now()),
+            owner_id: entry.owner_id,
+            password_hash: entry.password_hash.clone(),
+            mobile_url: entry.mobile_url.clone(),
+            max_clicks: entry.max_clicks.map(|max| max as i64),
+            deactivated_at: entry.deactivated_at,
+            updated_at: entry.updated_at,
+            status: entry.status(),
+        })
+    }
+
+    async fn delete_url(&self, code: &str) -> Result<(), DatabaseError> {
+        let mut state = self.state.lock().unwrap();
+        state.enter("delete_url")?;
+        if state.aliases.remove(code).is_some() {
+            return Ok(());
+        }
+        let entry = state.urls.remove(code).ok_or(DatabaseError::NotFound)?;
+        state.aliases.retain(|_, id| *id != entry.id);
+        state.tags.retain(|(tagged, _)| tagged != code);
+        Ok(())
+    }
+
+    async fn update_url(&self, code: &str, new_url: &str) -> Result<(), DatabaseError> {
+        let mut state = self.state.lock().unwrap();
+        state.enter("update_url")?;
+        let primary = state.primary_code(code)?;
+        if state
+            .code_by_url(new_url)
+            .is_some_and(|existing| existing != primary)
+        {
+            return Err(DatabaseError::Duplicate);
+        }
+        let entry = state.entry_mut(code)?;
+        entry.url = new_url.to_string();
+        entry.updated_at = Utc::now();
+        Ok(())
+    }
+
+    async fn record_preview_view(&self, code: &str) -> Result<(), DatabaseError> {
+        let mut state = self.state.lock().unwrap();
+        state.enter("record_preview_view")?;
+        state.entry_mut(code)?.preview_views += 1;
+        Ok(())
+    }
+
+    async fn record_click(
+        &self,
+        code: &str,
+        clicked_at: DateTime<Utc>,
+        ip: Option<&str>,
+        referrer: Option<&str>,
+        variant: Option<u8>,
+        device_type: Option<&str>,
+        country_code: Option<&str>,
+        user_agent: Option<&str>,
+    ) -> Result<(), DatabaseError> {
+        let mut state = self.state.lock().unwrap();
+        state.enter("record_click")?;
+        let entry = state.entry_mut(code)?;
+        entry.updated_at = clicked_at;
+        entry.clicks.push(Click {
+            clicked_at,
+            ip: ip.map(str::to_string),
+            referrer: referrer.map(str::to_string),
+            variant,
+            device_type: device_type.map(str::to_string),
+            country_code: country_code.map(str::to_string),
+            user_agent: user_agent.map(str::to_string),
+        });
+        Ok(())
+    }
+
+    async fn get_recent_clicks(
+        &self,
+        code: &str,
+        limit: u8,
+    ) -> Result<Vec<ClickRecord>, DatabaseError> {
+        let mut state = self.state.lock().unwrap();
+        state.enter("get_recent_clicks")?;
+        let primary = state.primary_code(code)?;
+        let mut records: Vec<ClickRecord> = state
+            .entry_mut(code)?
+            .clicks
+            .iter()
+            .enumerate()
+            .map(|(idx, click)| ClickRecord {
+                id: idx as i64 + 1,
+                code: primary.clone(),
+                ip: click.ip.clone(),
+                user_agent: click.user_agent.clone(),
+                referrer_origin: click.referrer.as_deref().and_then(referrer_origin),
+                country_code: click.country_code.clone(),
+                device_type: click.device_type.clone(),
+                clicked_at: click.clicked_at,
+            })
+            .collect();
+        records.sort_by(|a, b| b.clicked_at.cmp(&a.clicked_at).then(b.id.cmp(&a.id)));
+        records.truncate(usize::from(limit));
+        Ok(records)
+    }
+
+    async fn get_clicks_over_time(
+        &self,
+        code: &str,
+        bucket: &str,
+    ) -> Result<Vec<ClickBucket>, DatabaseError> {
+        let mut state = self.state.lock().unwrap();
+        state.enter("get_clicks_over_time")?;
+        if !CLICK_BUCKETS.contains(&bucket) {
+            return Err(DatabaseError::QueryError(format!(
+                "unsupported click bucket {:?}",
+                bucket
+            )));
+        }
+
+        let mut counts: BTreeMap<DateTime<Utc>, u64> = BTreeMap::new();
+        for click in &state.entry_mut(code)?.clicks {
+            let hour = click
+                .clicked_at
+                .with_nanosecond(0)
+                .and_then(|at| at.with_second(0))
+                .and_then(|at| at.with_minute(0));
+            let timestamp = match bucket {
+                "day" => hour.and_then(|at| at.with_hour(0)),
+                _ => hour,
+            }
+            .expect("truncated click time is valid");
+            *counts.entry(timestamp).or_default() += 1;
+        }
+        Ok(counts
+            .into_iter()
+            .map(|(timestamp, count)| ClickBucket { timestamp, count })
+            .collect())
+    }
+
+    async fn get_top_referrers(
+        &self,
+        code: &str,
+        limit: u8,
+    ) -> Result<Vec<ReferrerCount>, DatabaseError> {
+        let mut state = self.state.lock().unwrap();
+        state.enter("get_top_referrers")?;
+        Ok(count_referrers(&state.entry_mut(code)?.clicks, limit))
+    }
+
+    async fn get_clicks_by_country(&self, code: &str) -> Result<Vec<CountryCount>, DatabaseError> {
+        let mut state = self.state.lock().unwrap();
+        state.enter("get_clicks_by_country")?;
+        let mut counts: BTreeMap<String, u64> = BTreeMap::new();
+        for country in state
+            .entry_mut(code)?
+            .clicks
+            .iter()
+            .filter_map(|c| c.country_code.clone())
+        {
+            *counts.entry(country).or_default() += 1;
+        }
+        let mut countries: Vec<CountryCount> = counts
+            .into_iter()
+            .map(|(country_code, count)| CountryCount {
+                country_code,
+                count,
+            })
+            .collect();
+        // Stable sort keeps countries with equal counts in code order
+        countries.sort_by_key(|c| std::cmp::Reverse(c.count));
+        Ok(countries)
+    }
+
+    async fn get_click_summary(
+        &self,
+        code: &str,
+        top_referrers: u8,
+    ) -> Result<ClickSummary, DatabaseError> {
+        let mut state = self.state.lock().unwrap();
+        state.enter("get_click_summary")?;
+        let entry = state.entry_mut(code)?;
+        let clicks = &entry.clicks;
+
+        let unique_ips: HashSet<&str> = clicks.iter().filter_map(|c| c.ip.as_deref()).collect();
+        let referrers = count_referrers(clicks, top_referrers);
+        let variants = entry
+            .ab_targets
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, target)| {
+                let variant = idx as u8;
+                let count = clicks.iter().filter(|c| c.variant == Some(variant)).count() as u64;
+                (count > 0).then(|| VariantCount {
+                    variant,
+                    url: target.url.clone(),
+                    count,
+                })
+            })
+            .collect();
+
+        let device_clicks = |device: &str| {
+            clicks
+                .iter()
+                .filter(|c| c.device_type.as_deref() == Some(device))
+                .count() as u64
+        };
+
+        Ok(ClickSummary {
+            total_clicks: clicks.len() as u64,
+            unique_ips: unique_ips.len() as u64,
+            desktop_clicks: device_clicks("desktop"),
+            mobile_clicks: device_clicks("mobile"),
+            top_referrers: referrers,
+            variants,
+            top_countries: None,
+        })
+    }
+
+    async fn set_redirect_type(
+        &self,
+        code: &str,
+        redirect_type: &str,
+    ) -> Result<(), DatabaseError> {
+        let mut state = self.state.lock().unwrap();
+        state.enter("set_redirect_type")?;
+        state.entry_mut(code)?.redirect_type = redirect_type.to_string();
+        Ok(())
+    }
+
+    async fn set_url_owner(&self, code: &str, owner_id: Uuid) -> Result<(), DatabaseError> {
+        let mut state = self.state.lock().unwrap();
+        state.enter("set_url_owner")?;
+        state.entry_mut(code)?.owner_id = Some(owner_id);
+        Ok(())
+    }
+
+    async fn set_url_password(
+        &self,
+        code: &str,
+        password_hash: &[u8],
+    ) -> Result<(), DatabaseError> {
+        let mut state = self.state.lock().unwrap();
+        state.enter("set_url_password")?;
+        state.entry_mut(code)?.password_hash = Some(password_hash.to_vec());
+        Ok(())
+    }
+
+    async fn set_ab_targets(&self, code: &str, targets: &[AbTarget]) -> Result<(), DatabaseError> {
+        let mut state = self.state.lock().unwrap();
+        state.enter("set_ab_targets")?;
+        state.entry_mut(code)?.ab_targets = targets.to_vec();
+        Ok(())
+    }
+
+    async fn get_ab_targets(&self, code: &str) -> Result<Vec<AbTarget>, DatabaseError> {
+        let mut state = self.state.lock().unwrap();
+        state.enter("get_ab_targets")?;
+        Ok(state
+            .entry_mut(code)
+            .map(|entry| entry.ab_targets.clone())
+            .unwrap_or_default())
+    }
+
+    async fn set_mobile_url(&self, code: &str, mobile_url: &str) -> Result<(), DatabaseError> {
+        let mut state = self.state.lock().unwrap();
+        state.enter("set_mobile_url")?;
+        state.entry_mut(code)?.mobile_url = Some(mobile_url.to_string());
+        Ok(())
+    }
+
+    async fn set_max_clicks(&self, code: &str, max_clicks: u64) -> Result<(), DatabaseError> {
+        let mut state = self.state.lock().unwrap();
+        state.enter("set_max_clicks")?;
+        state.entry_mut(code)?.max_clicks = Some(max_clicks);
+        Ok(())
+    }
+
+    async fn get_click_count(&self, code: &str) -> Result<u64, DatabaseError> {
+        let mut state = self.state.lock().unwrap();
+        state.enter("get_click_count")?;
+        Ok(state.entry_mut(code)?.clicks.len() as u64)
+    }
+
+    async fn deactivate_url(&self, code: &str) -> Result<(), DatabaseError> {
+        let mut state = self.state.lock().unwrap();
+        state.enter("deactivate_url")?;
+        let entry = state.entry_mut(code)?;
+        entry.deactivated_at.get_or_insert_with(Utc::now);
+        Ok(())
+    }
+
+    async fn list_expiring_urls(
+        &self,
+        from: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<Vec<ExpiringUrl>, DatabaseError> {
+        let mut state = self.state.lock().unwrap();
+        state.enter("list_expiring_urls")?;
+        let mut expiring: Vec<ExpiringUrl> = state
+            .urls
+            .iter()
+            .filter(|(_, e)| e.expiry_reminder_sent_at.is_none() && e.deactivated_at.is_none())
+            .filter_map(|(code, e)| {
+                let expires_at = e.expires_at.filter(|at| (from..=until).contains(at))?;
+                Some(ExpiringUrl {
+                    code: code.clone(),
+                    url: e.url.clone(),
+                    expires_at,
+                    owner_id: e.owner_id?,
+                })
+            })
+            .collect();
+        expiring.sort_by_key(|u| u.expires_at);
+        Ok(expiring)
+    }
+
+    async fn mark_expiry_reminder_sent(
+        &self,
+        code: &str,
+        sent_at: DateTime<Utc>,
+    ) -> Result<(), DatabaseError> {
+        let mut state = self.state.lock().unwrap();
+        state.enter("mark_expiry_reminder_sent")?;
+        state.entry_mut(code)?.expiry_reminder_sent_at = Some(sent_at);
+        Ok(())
+    }
+
+    async fn list_short_codes(
+        &self,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<String>, DatabaseError> {
+        let mut state = self.state.lock().unwrap();
+        state.enter("list_short_codes")?;
+        let codes = state.urls.keys().chain(state.aliases.keys()).cloned();
+        Ok(page(codes, offset, limit))
+    }
+
+    async fn list_urls(
+        &self,
+        offset: u64,
+        limit: u64,
+        owner_id: Option<Uuid>,
+    ) -> Result<Vec<UrlRecord>, DatabaseError> {
+        let mut state = self.state.lock().unwrap();
+        state.enter("list_urls")?;
+        let owned = state
+            .records()
+            .into_iter()
+            .filter(|(_, entry)| owner_id.is_none() || entry.owner_id == owner_id);
+        Ok(page(owned.map(to_record), offset, limit))
+    }
+
+    async fn count_urls_by_owner(&self, owner_id: Uuid) -> Result<u64, DatabaseError> {
+        let mut state = self.state.lock().unwrap();
+        state.enter("count_urls_by_owner")?;
+        let owned = state
+            .urls
+            .values()
+            .filter(|entry| entry.owner_id == Some(owner_id));
+        Ok(owned.count() as u64)
+    }
+
+    async fn sum_clicks_by_owner(&self, owner_id: Uuid) -> Result<u64, DatabaseError> {
+        let mut state = self.state.lock().unwrap();
+        state.enter("sum_clicks_by_owner")?;
+        let owned = state
+            .urls
+            .values()
+            .filter(|entry| entry.owner_id == Some(owner_id));
+        Ok(owned.map(|entry| entry.clicks.len() as u64).sum())
+    }
+
+    async fn export_urls(
+        &self,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<ExportRecord>, DatabaseError> {
+        let mut state = self.state.lock().unwrap();
+        state.enter("export_urls")?;
+        let records = state
+            .records()
+            .into_iter()
+            .map(|(code, entry)| ExportRecord {
+                code: code.clone(),
+                url: entry.url.clone(),
+                created_at: entry.created_at,
+                click_count: entry.clicks.len() as u64,
+            });
+        Ok(page(records, offset, limit))
+    }
+
+    async fn add_tag(&self, code: &str, tag: &str) -> Result<(), DatabaseError> {
+        let mut state = self.state.lock().unwrap();
+        state.enter("add_tag")?;
+        let tagged = (state.primary_code(code)?, tag.to_string());
+        if !state.tags.contains(&tagged) {
+            state.tags.push(tagged);
+        }
+        Ok(())
+    }
+
+    async fn remove_tag(&self, code: &str, tag: &str) -> Result<(), DatabaseError> {
+        let mut state = self.state.lock().unwrap();
+        state.enter("remove_tag")?;
+        let primary = state.primary_code(code)?;
+        state.tags.retain(|(c, t)| *c != primary || t != tag);
+        Ok(())
+    }
+
+    async fn search_urls(
+        &self,
+        query: &str,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<UrlRecord>, DatabaseError> {
+        let mut state = self.state.lock().unwrap();
+        state.enter("search_urls")?;
+        // `LIKE` is case-insensitive for ASCII
+        let query = query.to_ascii_lowercase();
+        let matches = |text: &str| text.to_ascii_lowercase().contains(&query);
+        let records = state
+            .records()
+            .into_iter()
+            .filter(|(code, entry)| {
+                matches(&entry.url)
+                    || matches(code)
+                    || state
+                        .aliases
+                        .iter()
+                        .any(|(alias, id)| *id == entry.id && matches(alias))
+            })
+            .map(to_record);
+        Ok(page(records, offset, limit))
+    }
+
+    async fn get_urls_by_tag(
+        &self,
+        tag: &str,
+        offset: u64,
+        limit: u64,
+        owner_id: Option<Uuid>,
+    ) -> Result<Vec<UrlRecord>, DatabaseError> {
+        let mut state = self.state.lock().unwrap();
+        state.enter("get_urls_by_tag")?;
+        let records = state
+            .tags
+            .iter()
+            .filter(|(_, t)| t == tag)
+            .filter_map(|(code, _)| state.urls.get_key_value(code))
+            .filter(|(_, entry)| owner_id.is_none() || entry.owner_id == owner_id)
+            .map(to_record);
+        Ok(page(records, offset, limit))
+    }
+
+    async fn load_bloom_snapshot(&self, name: &str) -> Result<Option<Vec<u8>>, DatabaseError> {
+        let mut state = self.state.lock().unwrap();
+        state.enter("load_bloom_snapshot")?;
+        Ok(state.snapshots.get(name).cloned())
+    }
+
+    async fn save_bloom_snapshot(&self, name: &str, data: &[u8]) -> Result<(), DatabaseError> {
+        let mut state = self.state.lock().unwrap();
+        state.enter("save_bloom_snapshot")?;
+        state.snapshots.insert(name.to_string(), data.to_vec());
+        Ok(())
+    }
+
+    async fn health_check(&self) -> Result<(), DatabaseError> {
+        self.state.lock().unwrap().enter("health_check")
+    }
+
+    async fn get_stats(&self) -> Result<DatabaseStats, DatabaseError> {
+        let mut state = self.state.lock().unwrap();
+        state.enter("get_stats")?;
+        Ok(DatabaseStats {
+            total_urls: state.urls.len() as u64,
+            total_clicks: state.urls.values().map(|e| e.clicks.len() as u64).sum(),
+            size_bytes: 0,
+        })
+    }
+
+    async fn pending_migrations(&self) -> Result<usize, DatabaseError> {
+        self.state.lock().unwrap().enter("pending_migrations")?;
+        Ok(0)
+    }
+
+    async fn run_maintenance(&self) -> Vec<MaintenanceStep> {
+        let error = self
+            .state
+            .lock()
+            .unwrap()
+            .enter("run_maintenance")
+            .err()
+            .map(|e| e.to_string());
+        vec![MaintenanceStep {
+            operation: "VACUUM",
+            duration: std::time::Duration::ZERO,
+            error,
+        }]
+    }
+}
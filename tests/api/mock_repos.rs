@@ -0,0 +1,664 @@
+// tests/api/mock_repos.rs
+
+// in-memory user and auth repositories, for running AuthService without
+// Postgres, and the AuthService the auth tests build over them
+
+// dependencies
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use secrecy::SecretString;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use url_shortener_ztm_lib::configuration::Argon2Settings;
+use url_shortener_ztm_lib::core::security::jwt::JwtKeys;
+use url_shortener_ztm_lib::core::security::password::{NormalizedPassword, hash_password};
+use url_shortener_ztm_lib::features::auth::AuthService;
+use url_shortener_ztm_lib::features::auth::repositories::{
+    AuthAuditEntry, AuthEvent, AuthEventMeta, AuthRepoError, AuthRepository, AuthenticationAction,
+    AuthenticationChallenge, DeviceSummary, RefreshDevice,
+};
+use url_shortener_ztm_lib::features::users::repositories::{TotpState, User, UserRepository};
+use url_shortener_ztm_lib::infrastructure::email::EmailService;
+use uuid::Uuid;
+
+// The pepper the test AuthService hashes passwords with
+pub const PEPPER: &str = "test-pepper";
+
+// An OAuth identity as (user id, provider, subject id)
+pub type Identity = (Uuid, String, String);
+
+#[derive(Default)]
+struct MockUsers {
+    users: Vec<User>,
+    identities: Vec<Identity>,
+    totp: HashMap<Uuid, TotpState>,
+}
+
+#[derive(Default)]
+pub struct MockUserRepo {
+    state: Mutex<MockUsers>,
+}
+
+impl MockUserRepo {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_user(self, email: &str) -> Self {
+        self.state.lock().unwrap().users.push(new_user(email));
+        self
+    }
+
+    pub fn with_verified_user(self, email: &str) -> Self {
+        let user = User {
+            is_email_verified: true,
+            ..new_user(email)
+        };
+        self.state.lock().unwrap().users.push(user);
+        self
+    }
+
+    pub fn users(&self) -> Vec<User> {
+        self.state.lock().unwrap().users.clone()
+    }
+
+    pub fn identities(&self) -> Vec<Identity> {
+        self.state.lock().unwrap().identities.clone()
+    }
+
+    // Ends a lock now, as if its duration had gone by
+    pub fn expire_lock(&self, id: Uuid) {
+        let now = Utc::now();
+        self.update(id, |u| {
+            u.locked_until = Some(now);
+            u.fail_count_since = Some(now);
+        })
+        .unwrap();
+    }
+
+    pub fn totp(&self, id: Uuid) -> Option<TotpState> {
+        self.state.lock().unwrap().totp.get(&id).cloned()
+    }
+
+    fn update(&self, id: Uuid, change: impl FnOnce(&mut User)) -> anyhow::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let user = state
+            .users
+            .iter_mut()
+            .find(|u| u.id == id)
+            .ok_or_else(|| anyhow::anyhow!("user not found"))?;
+        change(user);
+        Ok(())
+    }
+}
+
+fn new_user(email: &str) -> User {
+    User {
+        id: Uuid::new_v4(),
+        email: email.to_string(),
+        password_hash: None,
+        display_name: None,
+        is_email_verified: false,
+        created_at: Utc::now(),
+        last_login_at: None,
+        jwt_token_version: 0,
+        locked_until: None,
+        fail_count_since: None,
+        vanity_domain: None,
+    }
+}
+
+#[async_trait]
+impl UserRepository for MockUserRepo {
+    async fn create(
+        &self,
+        email: &str,
+        password_hash: &[u8],
+        display: Option<String>,
+    ) -> anyhow::Result<User> {
+        let user = User {
+            password_hash: Some(password_hash.to_vec()),
+            display_name: display,
+            ..new_user(email)
+        };
+        self.state.lock().unwrap().users.push(user.clone());
+        Ok(user)
+    }
+
+    async fn find_user_by_email(&self, email: &str) -> anyhow::Result<Option<User>> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .users
+            .iter()
+            .find(|u| u.email.eq_ignore_ascii_case(email))
+            .cloned())
+    }
+
+    async fn find_user_by_id(&self, id: Uuid) -> anyhow::Result<Option<User>> {
+        let state = self.state.lock().unwrap();
+        Ok(state.users.iter().find(|u| u.id == id).cloned())
+    }
+
+    async fn email_exists(&self, email: &str) -> anyhow::Result<bool> {
+        Ok(self.find_user_by_email(email).await?.is_some())
+    }
+
+    async fn get_password_hash_by_id(&self, id: Uuid) -> anyhow::Result<Vec<u8>> {
+        self.find_user_by_id(id)
+            .await?
+            .and_then(|u| u.password_hash)
+            .ok_or_else(|| anyhow::anyhow!("user not found"))
+    }
+
+    async fn confirm_email(&self, id: Uuid) -> anyhow::Result<()> {
+        self.update(id, |u| u.is_email_verified = true)
+    }
+
+    async fn set_last_login(&self, id: Uuid, at: DateTime<Utc>) -> anyhow::Result<()> {
+        self.update(id, |u| u.last_login_at = Some(at))
+    }
+
+    async fn bump_jwt_version(&self, id: Uuid) -> anyhow::Result<()> {
+        self.update(id, |u| u.jwt_token_version += 1)
+    }
+
+    async fn update_password(&self, id: Uuid, new_hash: &[u8]) -> anyhow::Result<()> {
+        self.update(id, |u| u.password_hash = Some(new_hash.to_vec()))
+    }
+
+    async fn update_email(&self, id: Uuid, new_email: &str) -> anyhow::Result<()> {
+        self.update(id, |u| u.email = new_email.to_string())
+    }
+
+    async fn update_display_name(&self, id: Uuid, name: &str) -> anyhow::Result<()> {
+        self.update(id, |u| u.display_name = Some(name.to_string()))
+    }
+
+    async fn lock_user_until(&self, id: Uuid, until: DateTime<Utc>) -> anyhow::Result<()> {
+        self.update(id, |u| u.locked_until = Some(until))
+    }
+
+    async fn update_fail_count_since(&self, id: Uuid, since: DateTime<Utc>) -> anyhow::Result<()> {
+        self.update(id, |u| u.fail_count_since = Some(since))
+    }
+
+    async fn find_user_by_oauth_identity(
+        &self,
+        provider: &str,
+        subject_id: &str,
+    ) -> anyhow::Result<Option<User>> {
+        let state = self.state.lock().unwrap();
+        let user_id = state
+            .identities
+            .iter()
+            .find(|(_, p, s)| p == provider && s == subject_id)
+            .map(|(id, _, _)| *id);
+        Ok(user_id.and_then(|id| state.users.iter().find(|u| u.id == id).cloned()))
+    }
+
+    async fn link_oauth_identity(
+        &self,
+        user_id: Uuid,
+        provider: &str,
+        subject_id: &str,
+    ) -> anyhow::Result<()> {
+        self.state.lock().unwrap().identities.push((
+            user_id,
+            provider.to_string(),
+            subject_id.to_string(),
+        ));
+        Ok(())
+    }
+
+    async fn get_totp(&self, id: Uuid) -> anyhow::Result<Option<TotpState>> {
+        Ok(self.totp(id))
+    }
+
+    async fn set_totp_secret(&self, id: Uuid, secret_encrypted: &[u8]) -> anyhow::Result<()> {
+        self.state.lock().unwrap().totp.insert(
+            id,
+            TotpState {
+                secret_encrypted: secret_encrypted.to_vec(),
+                enabled: false,
+            },
+        );
+        Ok(())
+    }
+
+    async fn enable_totp(&self, id: Uuid) -> anyhow::Result<()> {
+        if let Some(totp) = self.state.lock().unwrap().totp.get_mut(&id) {
+            totp.enabled = true;
+        }
+        Ok(())
+    }
+
+    async fn clear_totp(&self, id: Uuid) -> anyhow::Result<()> {
+        self.state.lock().unwrap().totp.remove(&id);
+        Ok(())
+    }
+
+    async fn find_user_by_vanity_domain(&self, domain: &str) -> anyhow::Result<Option<User>> {
+        let state = self.state.lock().unwrap();
+        Ok(state
+            .users
+            .iter()
+            .find(|u| u.vanity_domain.as_deref() == Some(domain))
+            .cloned())
+    }
+
+    async fn set_vanity_domain(&self, id: Uuid, domain: &str) -> anyhow::Result<()> {
+        self.update(id, |u| u.vanity_domain = Some(domain.to_string()))
+    }
+
+    async fn delete_user(&self, id: Uuid) -> anyhow::Result<()> {
+        let mut state = self.state.lock().unwrap();
+        let before = state.users.len();
+        state.users.retain(|u| u.id != id);
+        if state.users.len() == before {
+            anyhow::bail!("User not found");
+        }
+        state.identities.retain(|(user_id, _, _)| *user_id != id);
+        state.totp.remove(&id);
+        Ok(())
+    }
+}
+
+// Remembers refresh devices, verification challenges, sign-in attempts for
+// the lockout check and the audit log
+#[derive(Default)]
+pub struct MockAuthRepo {
+    // with the time each device first signed in
+    devices: Mutex<Vec<(RefreshDevice, DateTime<Utc>)>>,
+    // unconfirmed emailed-code challenges
+    challenges: Mutex<Vec<AuthenticationChallenge>>,
+    // (user id, succeeded, attempted at)
+    attempts: Mutex<Vec<(Uuid, bool, DateTime<Utc>)>>,
+    events: Mutex<Vec<(Uuid, AuthAuditEntry)>>,
+}
+
+impl MockAuthRepo {
+    fn update_device(
+        &self,
+        matches: impl Fn(&RefreshDevice) -> bool,
+        change: impl Fn(&mut RefreshDevice),
+    ) {
+        for (dev, _) in self.devices.lock().unwrap().iter_mut() {
+            if matches(dev) {
+                change(dev);
+            }
+        }
+    }
+
+    // The events logged for `user_id`, oldest first
+    pub fn events(&self, user_id: Uuid) -> Vec<AuthEvent> {
+        self.events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(id, _)| *id == user_id)
+            .map(|(_, entry)| entry.event_type)
+            .collect()
+    }
+}
+
+#[async_trait]
+impl AuthRepository for MockAuthRepo {
+    async fn upsert_refresh_device(
+        &self,
+        user_id: Uuid,
+        device_id: &str,
+        current_hash: &[u8],
+        absolute_expires: DateTime<Utc>,
+        user_agent: Option<&str>,
+        ip: Option<IpAddr>,
+    ) -> anyhow::Result<i32> {
+        let mut devices = self.devices.lock().unwrap();
+        if let Some((dev, _)) = devices
+            .iter_mut()
+            .find(|(d, _)| d.user_id == user_id && d.device_id == device_id)
+        {
+            dev.previous_hash = Some(std::mem::replace(
+                &mut dev.current_hash,
+                current_hash.to_vec(),
+            ));
+            dev.last_rotated_at = Some(Utc::now());
+            dev.absolute_expires = absolute_expires;
+            dev.user_agent = user_agent.map(str::to_owned);
+            dev.ip = ip;
+            dev.revoked_at = None;
+            return Ok(dev.id);
+        }
+
+        let id = devices.len() as i32 + 1;
+        let dev = RefreshDevice {
+            id,
+            user_id,
+            device_id: device_id.to_owned(),
+            current_hash: current_hash.to_vec(),
+            previous_hash: None,
+            absolute_expires,
+            revoked_at: None,
+            user_agent: user_agent.map(str::to_owned),
+            ip,
+            last_rotated_at: None,
+        };
+        devices.push((dev, Utc::now()));
+        Ok(id)
+    }
+
+    async fn get_refresh_device_by_rt(
+        &self,
+        device_id: &str,
+        rt_hash: &[u8],
+    ) -> anyhow::Result<Option<RefreshDevice>> {
+        Ok(self
+            .devices
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(d, _)| d)
+            .find(|d| d.device_id == device_id && d.current_hash == rt_hash)
+            .cloned())
+    }
+
+    async fn get_refresh_device_by_user_id(
+        &self,
+        device_id: &str,
+        user_id: Uuid,
+    ) -> anyhow::Result<Option<RefreshDevice>> {
+        Ok(self
+            .devices
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(d, _)| d)
+            .find(|d| d.device_id == device_id && d.user_id == user_id)
+            .cloned())
+    }
+
+    async fn rotate_refresh_hash(
+        &self,
+        id: i32,
+        new_hash: &[u8],
+        rotated_at: DateTime<Utc>,
+    ) -> anyhow::Result<()> {
+        self.update_device(
+            |d| d.id == id,
+            |d| {
+                d.previous_hash = Some(std::mem::replace(&mut d.current_hash, new_hash.to_vec()));
+                d.last_rotated_at = Some(rotated_at);
+            },
+        );
+        Ok(())
+    }
+
+    async fn set_previous_hash(&self, id: i32, prev: Option<&[u8]>) -> anyhow::Result<()> {
+        self.update_device(
+            |d| d.id == id,
+            |d| d.previous_hash = prev.map(<[u8]>::to_vec),
+        );
+        Ok(())
+    }
+
+    async fn revoke_device(&self, id: i32) -> anyhow::Result<()> {
+        self.update_device(|d| d.id == id, |d| d.revoked_at = Some(Utc::now()));
+        Ok(())
+    }
+
+    async fn revoke_all(&self, user_id: Uuid) -> anyhow::Result<()> {
+        self.update_device(
+            |d| d.user_id == user_id,
+            |d| d.revoked_at = Some(Utc::now()),
+        );
+        Ok(())
+    }
+
+    async fn list_devices_for_user(&self, user_id: Uuid) -> anyhow::Result<Vec<DeviceSummary>> {
+        let now = Utc::now();
+        let mut devices: Vec<DeviceSummary> = self
+            .devices
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(d, _)| {
+                d.user_id == user_id && d.revoked_at.is_none() && d.absolute_expires > now
+            })
+            .map(|(d, created_at)| DeviceSummary {
+                id: d.id,
+                device_id: d.device_id.clone(),
+                user_agent: d.user_agent.clone(),
+                ip: d.ip.map(|ip| ip.to_string()),
+                created_at: *created_at,
+                last_used: d.last_rotated_at.unwrap_or(*created_at),
+                is_current: false,
+            })
+            .collect();
+        devices.sort_by(|a, b| b.last_used.cmp(&a.last_used).then(b.id.cmp(&a.id)));
+        Ok(devices)
+    }
+
+    async fn is_user_ip_blocked(
+        &self,
+        _user_id: &Uuid,
+        _ip: IpAddr,
+        _threshold: i32,
+        _window_mins: i32,
+        _fail_count_since: Option<DateTime<Utc>>,
+    ) -> Result<bool, AuthRepoError> {
+        Ok(false)
+    }
+
+    async fn should_lock_user_for_failures(
+        &self,
+        user_id: &Uuid,
+        threshold: i32,
+        window_mins: i32,
+        fail_count_since: Option<DateTime<Utc>>,
+    ) -> Result<bool, AuthRepoError> {
+        let window_start = Utc::now() - Duration::minutes(i64::from(window_mins));
+        let since = fail_count_since.map_or(window_start, |s| s.max(window_start));
+        let failures = self
+            .attempts
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(id, success, at)| id == user_id && !success && *at > since)
+            .count();
+        Ok(failures >= threshold as usize)
+    }
+
+    async fn add_sign_in_attempt(
+        &self,
+        user_id: &Uuid,
+        _ip: IpAddr,
+        _target: &str,
+        success: bool,
+        _user_agent: Option<&str>,
+    ) -> Result<(), AuthRepoError> {
+        self.attempts
+            .lock()
+            .unwrap()
+            .push((*user_id, success, Utc::now()));
+        Ok(())
+    }
+
+    // Mirrors create_or_refresh_auth_challenge() in the Postgres migrations
+    async fn create_or_refresh_auth_challenge(
+        &self,
+        user_id: Uuid,
+        action: AuthenticationAction,
+        target: Option<&str>,
+        code_hash: &[u8],
+        meta: Option<&Value>,
+        expires_at: DateTime<Utc>,
+        cooldown_secs: Option<i32>,
+    ) -> Result<(), AuthRepoError> {
+        let mut challenges = self.challenges.lock().unwrap();
+        let now = Utc::now();
+        let fresh = AuthenticationChallenge {
+            id: challenges.len() as i64 + 1,
+            user_id,
+            action: action.clone(),
+            target: target.map(str::to_owned),
+            code_hash: code_hash.to_vec(),
+            meta: meta.cloned(),
+            expires_at,
+            created_at: now,
+            confirmed_at: None,
+            attempts: 0,
+        };
+        let Some(existing) = challenges
+            .iter_mut()
+            .find(|c| c.user_id == user_id && c.action == action && c.confirmed_at.is_none())
+        else {
+            challenges.push(fresh);
+            return Ok(());
+        };
+
+        let next = existing.created_at + Duration::seconds(i64::from(cooldown_secs.unwrap_or(60)));
+        if now < next {
+            // whole seconds, rounded up
+            let remaining = ((next - now).num_milliseconds() + 999) / 1000;
+            return Err(AuthRepoError::Cooldown(remaining as i32));
+        }
+        *existing = AuthenticationChallenge {
+            id: existing.id,
+            ..fresh
+        };
+        Ok(())
+    }
+
+    async fn get_auth_challenge(
+        &self,
+        user_id: Uuid,
+        action: AuthenticationAction,
+    ) -> Result<Option<AuthenticationChallenge>, AuthRepoError> {
+        Ok(self
+            .challenges
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|c| c.user_id == user_id && c.action == action && c.confirmed_at.is_none())
+            .cloned())
+    }
+
+    async fn increase_auth_challenge_attempts(
+        &self,
+        challenge_id: i64,
+    ) -> Result<(), AuthRepoError> {
+        let mut challenges = self.challenges.lock().unwrap();
+        if let Some(c) = challenges
+            .iter_mut()
+            .find(|c| c.id == challenge_id && c.confirmed_at.is_none())
+        {
+            c.attempts += 1;
+        }
+        Ok(())
+    }
+
+    async fn confirm_authentication_challenge(
+        &self,
+        user_id: Uuid,
+        action: AuthenticationAction,
+        confirmed_at: DateTime<Utc>,
+    ) -> Result<(), AuthRepoError> {
+        let mut challenges = self.challenges.lock().unwrap();
+        if let Some(c) = challenges
+            .iter_mut()
+            .find(|c| c.user_id == user_id && c.action == action && c.confirmed_at.is_none())
+        {
+            c.confirmed_at = Some(confirmed_at);
+        }
+        Ok(())
+    }
+
+    async fn log_auth_event(
+        &self,
+        user_id: Uuid,
+        event: AuthEvent,
+        meta: AuthEventMeta,
+    ) -> Result<(), AuthRepoError> {
+        let mut events = self.events.lock().unwrap();
+        let entry = AuthAuditEntry {
+            id: events.len() as i64 + 1,
+            event_type: event,
+            ip: meta.ip,
+            user_agent: meta.user_agent,
+            metadata: meta.metadata,
+            created_at: Utc::now(),
+        };
+        events.push((user_id, entry));
+        Ok(())
+    }
+
+    async fn get_auth_events(
+        &self,
+        user_id: Uuid,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<AuthAuditEntry>, AuthRepoError> {
+        Ok(self
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .filter(|(id, _)| *id == user_id)
+            .skip(offset as usize)
+            .take(limit as usize)
+            .map(|(_, entry)| entry.clone())
+            .collect())
+    }
+}
+
+// the cheapest accepted hash, to keep the tests fast
+pub fn argon2() -> Argon2Settings {
+    Argon2Settings {
+        m_cost_kib: 8 * 1024,
+        t_cost: 1,
+        p_cost: 1,
+    }
+}
+
+// An AuthService over `users` and `auth`, signing tokens with the key
+// JwtKeys::new(b"test-secret") and hashing with argon2()
+pub fn auth_service(users: Arc<MockUserRepo>, auth: Arc<MockAuthRepo>) -> AuthService {
+    AuthService::new(
+        users,
+        auth,
+        JwtKeys::new(b"test-secret"),
+        Duration::minutes(15),
+        SecretString::from(PEPPER),
+        argon2(),
+        EmailService::new("", ""),
+    )
+}
+
+// An AuthService with the repos behind it and the one user they hold
+pub struct TestAuth {
+    pub service: AuthService,
+    pub users: Arc<MockUserRepo>,
+    pub auth: Arc<MockAuthRepo>,
+    pub user_id: Uuid,
+}
+
+// An AuthService whose only user signs in as `email` with `password`
+pub async fn auth_service_with_user(email: &str, password: &str) -> TestAuth {
+    let users = Arc::new(MockUserRepo::new());
+    let auth = Arc::new(MockAuthRepo::default());
+    let hash = hash_password(
+        &NormalizedPassword::try_from(password).unwrap(),
+        PEPPER,
+        &argon2(),
+    )
+    .unwrap();
+    let user_id = users.create(email, &hash, None).await.unwrap().id;
+    TestAuth {
+        service: auth_service(users.clone(), auth.clone()),
+        users,
+        auth,
+        user_id,
+    }
+}
@@ -0,0 +1,399 @@
+// tests/api/oauth.rs
+
+// signing in with GitHub and Google against wiremock stand-ins for the providers;
+// the auth routes are only mounted on Postgres, so the controllers are called directly
+
+// dependencies
+use crate::mock_repos::{self, MockAuthRepo, MockUserRepo};
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::http::header::{LOCATION, SET_COOKIE};
+use axum::response::{IntoResponse, Response};
+use axum_extra::extract::CookieJar;
+use axum_extra::extract::cookie::Cookie;
+use secrecy::SecretString;
+use std::sync::Arc;
+use url_shortener_ztm_lib::configuration::{OAuthProviderSettings, OAuthSettings};
+use url_shortener_ztm_lib::features::auth::AuthService;
+use url_shortener_ztm_lib::features::auth::controllers::{
+    AuthController, oauth_callback, oauth_start,
+};
+use url_shortener_ztm_lib::features::auth::dto::{AuthBundle, OAuthCallbackQuery, SignInOutcome};
+use url_shortener_ztm_lib::features::users::UserService;
+use url_shortener_ztm_lib::features::users::repositories::UserRepository;
+use wiremock::matchers::{body_string_contains, header, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn provider_settings(server: &MockServer) -> OAuthProviderSettings {
+    OAuthProviderSettings {
+        client_id: "client-id".to_string(),
+        client_secret: SecretString::from("client-secret"),
+        redirect_uri: "http://localhost/api/v1/auth/oauth/github/callback".to_string(),
+        authorize_url: Some(format!("{}/authorize", server.uri())),
+        token_url: Some(format!("{}/token", server.uri())),
+        user_info_url: Some(format!("{}/user", server.uri())),
+    }
+}
+
+fn auth_service(users: Arc<MockUserRepo>, oauth: OAuthSettings) -> AuthService {
+    mock_repos::auth_service(users, Arc::new(MockAuthRepo::default())).with_oauth(oauth)
+}
+
+// A provider that accepts the code "good-code" and says it belongs to `user_info`
+async fn mock_provider(user_info: serde_json::Value) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/token"))
+        .and(body_string_contains("code=good-code"))
+        .and(body_string_contains("client_secret=client-secret"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "access_token": "provider-token",
+            "token_type": "bearer"
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/user"))
+        .and(header("authorization", "Bearer provider-token"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(user_info))
+        .mount(&server)
+        .await;
+    server
+}
+
+fn authenticated(outcome: SignInOutcome) -> AuthBundle {
+    match outcome {
+        SignInOutcome::Authenticated(bundle) => bundle,
+        SignInOutcome::MfaRequired(_) => panic!("sign-in asked for a TOTP code"),
+    }
+}
+
+#[tokio::test]
+async fn github_sign_in_creates_a_verified_user_and_links_the_identity() {
+    // Arrange
+    let server = mock_provider(serde_json::json!({ "id": 42, "email": "octo@example.com" })).await;
+    let users = Arc::new(MockUserRepo::new());
+    let service = auth_service(
+        users.clone(),
+        OAuthSettings {
+            github: Some(provider_settings(&server)),
+            google: None,
+        },
+    );
+
+    // Act
+    let bundle = authenticated(service.oauth_exchange("github", "good-code").await.unwrap());
+
+    // Assert
+    let created = users.users();
+    assert_eq!(created.len(), 1);
+    assert_eq!(created[0].email, "octo@example.com");
+    assert!(created[0].is_email_verified);
+    assert_eq!(
+        users.identities(),
+        vec![(created[0].id, "github".to_string(), "42".to_string())]
+    );
+    let claims = service.verify_token(&bundle.access_token).await.unwrap();
+    assert_eq!(claims.sub, created[0].id);
+    assert!(!bundle.refresh_token.is_empty());
+}
+
+#[tokio::test]
+async fn a_linked_identity_signs_in_as_the_same_user() {
+    // Arrange
+    let server = mock_provider(serde_json::json!({ "id": 42, "email": "octo@example.com" })).await;
+    let users = Arc::new(MockUserRepo::new());
+    let service = auth_service(
+        users.clone(),
+        OAuthSettings {
+            github: Some(provider_settings(&server)),
+            google: None,
+        },
+    );
+    service.oauth_exchange("github", "good-code").await.unwrap();
+
+    // Act
+    let bundle = authenticated(service.oauth_exchange("github", "good-code").await.unwrap());
+
+    // Assert
+    assert_eq!(users.users().len(), 1);
+    assert_eq!(users.identities().len(), 1);
+    let claims = service.verify_token(&bundle.access_token).await.unwrap();
+    assert_eq!(claims.sub, users.users()[0].id);
+}
+
+#[tokio::test]
+async fn google_sign_in_links_an_existing_user_with_the_same_email() {
+    // Arrange
+    let server = mock_provider(serde_json::json!({
+        "sub": "google-subject",
+        "email": "someone@example.com",
+        "email_verified": true
+    }))
+    .await;
+    let users = Arc::new(MockUserRepo::new().with_verified_user("someone@example.com"));
+    let existing = users.users()[0].id;
+    let service = auth_service(
+        users.clone(),
+        OAuthSettings {
+            github: None,
+            google: Some(provider_settings(&server)),
+        },
+    );
+
+    // Act
+    service.oauth_exchange("google", "good-code").await.unwrap();
+
+    // Assert
+    assert_eq!(users.users().len(), 1);
+    assert_eq!(
+        users.identities(),
+        vec![(existing, "google".to_string(), "google-subject".to_string())]
+    );
+}
+
+#[tokio::test]
+async fn sign_in_does_not_link_a_user_whose_email_is_unverified() {
+    // Arrange: someone signed up with the address but never verified it
+    let server = mock_provider(serde_json::json!({
+        "sub": "google-subject",
+        "email": "someone@example.com",
+        "email_verified": true
+    }))
+    .await;
+    let users = Arc::new(MockUserRepo::new().with_user("someone@example.com"));
+    let service = auth_service(
+        users.clone(),
+        OAuthSettings {
+            github: None,
+            google: Some(provider_settings(&server)),
+        },
+    );
+
+    // Act
+    let result = service.oauth_exchange("google", "good-code").await;
+
+    // Assert
+    assert!(result.is_err());
+    assert!(users.identities().is_empty());
+    assert_eq!(users.users().len(), 1);
+}
+
+#[tokio::test]
+async fn google_sign_in_with_an_unverified_email_is_rejected() {
+    // Arrange
+    let server = mock_provider(serde_json::json!({
+        "sub": "google-subject",
+        "email": "someone@example.com",
+        "email_verified": false
+    }))
+    .await;
+    let users = Arc::new(MockUserRepo::new().with_user("someone@example.com"));
+    let service = auth_service(
+        users.clone(),
+        OAuthSettings {
+            github: None,
+            google: Some(provider_settings(&server)),
+        },
+    );
+
+    // Act
+    let result = service.oauth_exchange("google", "good-code").await;
+
+    // Assert
+    assert!(result.is_err());
+    assert!(users.identities().is_empty());
+}
+
+#[tokio::test]
+async fn sign_in_of_a_user_with_totp_asks_for_the_code() {
+    // Arrange
+    let server = mock_provider(serde_json::json!({ "id": 42, "email": "octo@example.com" })).await;
+    let users = Arc::new(MockUserRepo::new().with_verified_user("octo@example.com"));
+    let id = users.users()[0].id;
+    users.set_totp_secret(id, b"encrypted").await.unwrap();
+    users.enable_totp(id).await.unwrap();
+    let service = auth_service(
+        users.clone(),
+        OAuthSettings {
+            github: Some(provider_settings(&server)),
+            google: None,
+        },
+    );
+
+    // Act
+    let outcome = service.oauth_exchange("github", "good-code").await.unwrap();
+
+    // Assert
+    match outcome {
+        SignInOutcome::MfaRequired(challenge) => {
+            assert!(challenge.mfa_required);
+            assert!(!challenge.mfa_token.is_empty());
+        }
+        SignInOutcome::Authenticated(_) => panic!("the second factor was skipped"),
+    }
+}
+
+#[tokio::test]
+async fn a_code_the_provider_rejects_is_an_error() {
+    // Arrange
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/token"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(serde_json::json!({ "error": "bad_verification_code" })),
+        )
+        .mount(&server)
+        .await;
+    let users = Arc::new(MockUserRepo::new());
+    let service = auth_service(
+        users.clone(),
+        OAuthSettings {
+            github: Some(provider_settings(&server)),
+            google: None,
+        },
+    );
+
+    // Act
+    let result = service.oauth_exchange("github", "stale-code").await;
+
+    // Assert
+    let error = result.err().expect("exchange should fail").to_string();
+    assert!(error.contains("bad_verification_code"), "{}", error);
+    assert!(users.users().is_empty());
+}
+
+fn controller(service: AuthService, users: Arc<MockUserRepo>) -> AuthController {
+    AuthController {
+        auth_svc: Arc::new(service),
+        user_svc: Arc::new(UserService::new(users)),
+    }
+}
+
+async fn callback(ctrl: &AuthController, provider: &str, state: &str, jar: CookieJar) -> Response {
+    oauth_callback(
+        State(ctrl.clone()),
+        Path(provider.to_string()),
+        Query(OAuthCallbackQuery {
+            code: "good-code".to_string(),
+            state: state.to_string(),
+        }),
+        jar,
+    )
+    .await
+    .into_response()
+}
+
+fn set_cookies(response: &Response) -> Vec<String> {
+    response
+        .headers()
+        .get_all(SET_COOKIE)
+        .iter()
+        .map(|v| v.to_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn oauth_start_redirects_to_the_provider_with_a_state_cookie() {
+    // Arrange
+    let server = MockServer::start().await;
+    let users = Arc::new(MockUserRepo::new());
+    let ctrl = controller(
+        auth_service(
+            users.clone(),
+            OAuthSettings {
+                github: Some(provider_settings(&server)),
+                google: None,
+            },
+        ),
+        users,
+    );
+
+    // Act
+    let response = oauth_start(State(ctrl), Path("github".to_string()), CookieJar::new())
+        .await
+        .into_response();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    let location = response.headers()[LOCATION].to_str().unwrap().to_string();
+    assert!(location.starts_with(&format!("{}/authorize?", server.uri())));
+    assert!(location.contains("client_id=client-id"));
+    let cookies = set_cookies(&response);
+    let state = cookies[0]
+        .strip_prefix("oauth_state=")
+        .and_then(|rest| rest.split(';').next())
+        .expect("state cookie was not set");
+    assert!(location.contains(&format!("state={}", state)));
+}
+
+#[tokio::test]
+async fn oauth_callback_signs_in_and_sets_the_token_cookies() {
+    // Arrange
+    let server = mock_provider(serde_json::json!({ "id": 42, "email": "octo@example.com" })).await;
+    let users = Arc::new(MockUserRepo::new());
+    let ctrl = controller(
+        auth_service(
+            users.clone(),
+            OAuthSettings {
+                github: Some(provider_settings(&server)),
+                google: None,
+            },
+        ),
+        users.clone(),
+    );
+    let jar = CookieJar::new().add(Cookie::new("oauth_state", "expected"));
+
+    // Act
+    let response = callback(&ctrl, "github", "expected", jar).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let cookies = set_cookies(&response);
+    assert!(cookies.iter().any(|c| c.starts_with("access_token=")));
+    assert!(cookies.iter().any(|c| c.starts_with("refresh_token=")));
+    assert_eq!(users.users().len(), 1);
+}
+
+#[tokio::test]
+async fn oauth_callback_rejects_a_state_that_does_not_match_the_cookie() {
+    // Arrange
+    let server = mock_provider(serde_json::json!({ "id": 42, "email": "octo@example.com" })).await;
+    let users = Arc::new(MockUserRepo::new());
+    let ctrl = controller(
+        auth_service(
+            users.clone(),
+            OAuthSettings {
+                github: Some(provider_settings(&server)),
+                google: None,
+            },
+        ),
+        users.clone(),
+    );
+    let jar = CookieJar::new().add(Cookie::new("oauth_state", "expected"));
+
+    // Act
+    let forged = callback(&ctrl, "github", "forged", jar).await;
+    let without_cookie = callback(&ctrl, "github", "expected", CookieJar::new()).await;
+
+    // Assert
+    assert_eq!(forged.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(without_cookie.status(), StatusCode::UNAUTHORIZED);
+    assert!(server.received_requests().await.unwrap().is_empty());
+    assert!(users.users().is_empty());
+}
+
+#[tokio::test]
+async fn oauth_callback_is_not_found_for_an_unconfigured_provider() {
+    // Arrange
+    let users = Arc::new(MockUserRepo::new());
+    let ctrl = controller(auth_service(users.clone(), OAuthSettings::default()), users);
+    let jar = CookieJar::new().add(Cookie::new("oauth_state", "s"));
+
+    // Act
+    let response = callback(&ctrl, "google", "s", jar).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,80 @@
+// tests/api/preflight.rs
+
+// tests for the pre-flight checks of `Application::build`
+
+// dependencies
+use crate::helpers::app_configuration;
+use url_shortener_ztm_lib::configuration::Settings;
+use url_shortener_ztm_lib::startup::Application;
+use uuid::Uuid;
+
+async fn build(configure: impl FnOnce(&mut Settings)) -> anyhow::Result<Application> {
+    let config = app_configuration(|c| {
+        c.database.url = ":memory:".to_string();
+        configure(c);
+    });
+    Application::build(config).await
+}
+
+#[tokio::test]
+async fn healthy_setup_passes_the_checks() {
+    let app = build(|_| {}).await;
+
+    assert!(app.is_ok(), "{:?}", app.err());
+}
+
+#[tokio::test]
+async fn corrupt_database_file_fails_the_build() {
+    // Arrange: a database file that is not SQLite, so migrating it fails
+    let path = std::env::temp_dir().join(format!("corrupt-{}.db", Uuid::new_v4()));
+    std::fs::write(&path, b"this is not a SQLite database, just some bytes...").unwrap();
+
+    // Act
+    let result = build(|c| c.database.url = path.to_string_lossy().into_owned()).await;
+
+    // Assert
+    std::fs::remove_file(&path).unwrap();
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn unreadable_geolocation_database_fails_the_build() {
+    // Act
+    let result = build(|c| {
+        c.geolocation.enabled = true;
+        c.geolocation.db_path = "does-not-exist.mmdb".to_string();
+    })
+    .await;
+
+    // Assert
+    let error = format!("{:#}", result.err().expect("the build should fail"));
+    assert!(
+        error.contains("Pre-flight check geolocation failed"),
+        "{}",
+        error
+    );
+}
+
+#[tokio::test]
+async fn broken_template_fails_the_build() {
+    // Arrange: a template that does not parse
+    let directory = std::env::temp_dir().join(format!("templates-{}", Uuid::new_v4()));
+    std::fs::create_dir_all(&directory).unwrap();
+    std::fs::write(directory.join("broken.html"), "{% if %}").unwrap();
+
+    // Act
+    let result = build(|c| {
+        c.templates.dir = format!("{}/**/*", directory.display());
+        c.templates.hot_reload = false;
+    })
+    .await;
+
+    // Assert
+    std::fs::remove_dir_all(&directory).unwrap();
+    let error = format!("{:#}", result.err().expect("the build should fail"));
+    assert!(
+        error.contains("Pre-flight check templates failed"),
+        "{}",
+        error
+    );
+}
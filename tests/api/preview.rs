@@ -0,0 +1,129 @@
+// tests/api/preview.rs
+
+// integration tests for the GET /{id}/preview page
+// the page shows where a short code leads without redirecting
+
+// dependencies
+use crate::helpers::{assert_json_ok, assert_redirect_to, spawn_app};
+use axum::http::StatusCode;
+use serde_json::json;
+use std::time::Duration;
+
+#[tokio::test]
+async fn preview_page_shows_destination_and_continue_link_redirects() {
+    // Arrange
+    let app = spawn_app().await;
+    let shorten_response = app
+        .post_api_with_key("/api/shorten", "https://www.example.com/preview/me")
+        .await;
+    let body = assert_json_ok(shorten_response).await;
+    let id = body["data"]["id"]
+        .as_str()
+        .expect("shorten response did not include an id")
+        .to_string();
+
+    // Act
+    let response = app.get(&format!("/{}/preview", id)).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response
+            .headers()
+            .get("cache-control")
+            .and_then(|v| v.to_str().ok()),
+        Some("no-store")
+    );
+    // Tera escapes `/` as `&#x2F;`, undo that before matching
+    let html = response
+        .text()
+        .await
+        .expect("Failed to read preview body")
+        .replace("&#x2F;", "/");
+    assert!(html.contains("https://www.example.com/preview/me"));
+    assert!(html.contains("www.example.com"));
+    assert!(html.contains(&format!("href=\"/{}\"", id)));
+
+    // The "Continue" link still redirects to the destination
+    let redirect = app.get(&format!("/{}", id)).await;
+    assert_redirect_to(
+        redirect,
+        "https://www.example.com/preview/me",
+        StatusCode::PERMANENT_REDIRECT,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn preview_views_are_counted_separately_from_clicks() {
+    let app = spawn_app().await;
+    let shorten_response = app
+        .post_api_with_key("/api/shorten", "https://www.example.com/counted")
+        .await;
+    let body = assert_json_ok(shorten_response).await;
+    let id = body["data"]["id"].as_str().unwrap().to_string();
+
+    app.get(&format!("/{}/preview", id)).await;
+    app.get(&format!("/{}/preview", id)).await;
+
+    let meta = app._database.get_url_with_metadata(&id).await.unwrap();
+    assert_eq!(meta.preview_views, 2);
+    assert_eq!(meta.click_count, 0);
+}
+
+#[tokio::test]
+async fn preview_for_unknown_code_returns_404() {
+    let app = spawn_app().await;
+
+    let response = app.get("/doesNotExist/preview").await;
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn preview_of_expired_link_is_gone() {
+    // Arrange
+    let app = spawn_app().await;
+    let response = app
+        .post_json_with_key(
+            "/api/shorten",
+            &json!({ "url": "https://www.example.com/preview/expired", "ttl_seconds": 1 }),
+        )
+        .await;
+    let body = assert_json_ok(response).await;
+    let id = body["data"]["id"].as_str().unwrap().to_string();
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+
+    // Act
+    let response = app.get(&format!("/{}/preview", id)).await;
+
+    // Assert: the destination is not shown and the view is not counted
+    assert_eq!(response.status(), StatusCode::GONE);
+    let html = response.text().await.unwrap();
+    assert!(!html.contains("preview/expired"));
+    let meta = app._database.get_url_with_metadata(&id).await.unwrap();
+    assert_eq!(meta.preview_views, 0);
+}
+
+#[tokio::test]
+async fn preview_of_deactivated_link_is_gone() {
+    // Arrange: a link whose only click is used up
+    let app = spawn_app().await;
+    let response = app
+        .post_json_with_key(
+            "/api/shorten",
+            &json!({ "url": "https://www.example.com/preview/once", "max_clicks": 1 }),
+        )
+        .await;
+    let body = assert_json_ok(response).await;
+    let id = body["data"]["id"].as_str().unwrap().to_string();
+    app.get(&format!("/{}", id)).await;
+
+    // Act
+    let response = app.get(&format!("/{}/preview", id)).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::GONE);
+    let meta = app._database.get_url_with_metadata(&id).await.unwrap();
+    assert_eq!(meta.preview_views, 0);
+}
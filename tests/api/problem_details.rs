@@ -0,0 +1,79 @@
+// tests/api/problem_details.rs
+
+// errors are rendered as RFC 7807 Problem Details when the client asks for them
+
+// dependencies
+use crate::helpers::{spawn_app, spawn_app_with};
+use axum::http::StatusCode;
+use reqwest::header::{ACCEPT, CONTENT_TYPE};
+use serde_json::Value;
+
+const PROBLEM_JSON: &str = "application/problem+json";
+
+#[tokio::test]
+async fn not_found_is_problem_detail_when_accept_prefers_it() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .client
+        .get(app.url("/zzzzzzz"))
+        .header(ACCEPT, PROBLEM_JSON)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert_eq!(response.headers()[CONTENT_TYPE], PROBLEM_JSON);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["type"], format!("{}/problems/not-found", app.base_url));
+    assert_eq!(body["title"], "Not Found");
+    assert_eq!(body["status"], 404);
+    assert_eq!(body["detail"], "URL not found");
+    assert_eq!(body["instance"], "/zzzzzzz");
+    assert!(body.get("success").is_none());
+}
+
+#[tokio::test]
+async fn configured_problem_type_base_prefixes_type() {
+    let app = spawn_app_with(|c| {
+        c.application.problem_type_base = Some("https://errors.example.com/".to_string());
+    })
+    .await;
+
+    let response = app
+        .client
+        .post(app.api("/api/shorten"))
+        .header("x-api-key", app.api_key.to_string())
+        .header(ACCEPT, "application/json;q=0.5, application/problem+json")
+        .body("ftp://example.com")
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["type"], "https://errors.example.com/invalid-scheme");
+    assert_eq!(body["status"], 422);
+}
+
+#[tokio::test]
+async fn errors_keep_envelope_without_problem_accept() {
+    let app = spawn_app().await;
+
+    let response = app
+        .client
+        .get(app.url("/zzzzzzz"))
+        .header(ACCEPT, "application/json, application/problem+json;q=0.5")
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["success"], false);
+    assert_eq!(body["status"], 404);
+    assert!(body.get("type").is_none());
+}
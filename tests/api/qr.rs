@@ -0,0 +1,138 @@
+// tests/api/qr.rs
+
+// integration tests for GET /api/qr/{id} and GET /api/urls/{id}/qr.{format}
+// the endpoints return a PNG or SVG QR code encoding the short URL
+
+// dependencies
+use crate::helpers::{assert_json_ok, spawn_app};
+use axum::http::StatusCode;
+
+const PNG_SIGNATURE: &[u8] = &[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+#[tokio::test]
+async fn qr_code_is_returned_as_png() {
+    // Arrange
+    let app = spawn_app().await;
+    let shorten_response = app
+        .post_api_with_key("/api/shorten", "https://www.example.com/qr")
+        .await;
+    let body = assert_json_ok(shorten_response).await;
+    let id = body["data"]["id"]
+        .as_str()
+        .expect("shorten response did not include an id")
+        .to_string();
+
+    // Act
+    let response = app.get_api(&format!("/api/qr/{}?size=128", id)).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response
+            .headers()
+            .get("content-type")
+            .and_then(|v| v.to_str().ok()),
+        Some("image/png")
+    );
+    let bytes = response.bytes().await.expect("Failed to read QR body");
+    assert!(!bytes.is_empty());
+    assert!(bytes.starts_with(PNG_SIGNATURE));
+}
+
+#[tokio::test]
+async fn qr_code_repeat_request_is_served_identically() {
+    let app = spawn_app().await;
+    let shorten_response = app
+        .post_api_with_key("/api/shorten", "https://www.example.com/qr/cached")
+        .await;
+    let body = assert_json_ok(shorten_response).await;
+    let id = body["data"]["id"].as_str().unwrap().to_string();
+
+    let first = app.get_api(&format!("/api/qr/{}", id)).await;
+    let first = first.bytes().await.unwrap();
+    let second = app.get_api(&format!("/api/qr/{}", id)).await;
+    let second = second.bytes().await.unwrap();
+
+    assert_eq!(first, second);
+}
+
+#[tokio::test]
+async fn qr_code_for_unknown_code_returns_404_json() {
+    let app = spawn_app().await;
+
+    let response = app.get_api("/api/qr/doesNotExist").await;
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let body: serde_json::Value = response.json().await.expect("404 body was not JSON");
+    assert_eq!(body["success"].as_bool(), Some(false));
+}
+
+#[tokio::test]
+async fn qr_download_is_served_as_png_attachment() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = app.shorten_url("https://www.example.com/qr/png").await;
+
+    // Act
+    let response = app
+        .get_api(&format!("/api/urls/{}/qr.png?size=128", id))
+        .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(header(&response, "content-type"), Some("image/png"));
+    assert_eq!(
+        header(&response, "content-disposition"),
+        Some(format!("attachment; filename=\"{}.png\"", id).as_str())
+    );
+    let bytes = response.bytes().await.expect("Failed to read QR body");
+    assert!(bytes.starts_with(PNG_SIGNATURE));
+}
+
+#[tokio::test]
+async fn qr_download_is_served_as_svg() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = app.shorten_url("https://www.example.com/qr/svg").await;
+
+    // Act
+    let response = app
+        .get_api(&format!("/api/urls/{}/qr.svg?download=false", id))
+        .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(header(&response, "content-type"), Some("image/svg+xml"));
+    assert_eq!(header(&response, "content-disposition"), Some("inline"));
+    let body = response.text().await.expect("Failed to read QR body");
+    assert!(body.contains("<svg"), "{}", body);
+}
+
+#[tokio::test]
+async fn qr_download_in_an_unsupported_format_returns_404() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = app.shorten_url("https://www.example.com/qr/gif").await;
+
+    // Act
+    let response = app.get_api(&format!("/api/urls/{}/qr.gif", id)).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn qr_download_for_unknown_code_returns_404() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app.get_api("/api/urls/doesNotExist/qr.svg").await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+fn header<'a>(response: &'a reqwest::Response, name: &str) -> Option<&'a str> {
+    response.headers().get(name).and_then(|v| v.to_str().ok())
+}
@@ -3,9 +3,26 @@
 // tests for rate limiting functionality
 
 use axum::http::StatusCode;
+use url_shortener_ztm_lib::configuration::EndpointRateLimit;
 use url_shortener_ztm_lib::get_configuration;
 
-use crate::helpers::spawn_app;
+use crate::helpers::{TestApp, assert_json_ok, spawn_app, spawn_app_with};
+use serde_json::json;
+
+// Spawn an app whose redirects may be followed 3 times before being limited,
+// while shortening keeps the test limit of 2 requests
+async fn spawn_app_with_redirect_limit() -> TestApp {
+    spawn_app_with(|c| {
+        c.rate_limiting.endpoints.insert(
+            "redirect".to_string(),
+            EndpointRateLimit {
+                requests_per_second: 3,
+                burst_size: 3,
+            },
+        );
+    })
+    .await
+}
 
 #[tokio::test]
 async fn rate_limiting_blocks_excess_requests() {
@@ -116,6 +133,31 @@ async fn rate_limiting_provides_retry_after_headers() {
     );
 }
 
+#[tokio::test]
+async fn rate_limited_response_uses_error_envelope() {
+    let app = spawn_app().await;
+
+    let mut response = None;
+    for i in 0..3 {
+        response = Some(
+            app.client
+                .post(app.url("/api/public/shorten"))
+                .header("content-type", "text/plain")
+                .body(format!("https://www.example.com/envelope-{}", i))
+                .send()
+                .await
+                .expect("Failed to execute request."),
+        );
+    }
+    let response = response.unwrap();
+
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(response.headers().contains_key("retry-after"));
+    let body: serde_json::Value = response.json().await.expect("Failed to parse JSON");
+    assert_eq!(body["success"], false);
+    assert_eq!(body["error_code"], "RATE_LIMITED");
+}
+
 #[tokio::test]
 async fn rate_limiting_works_per_ip_address() {
     // This test would ideally test different IP addresses, but since we're running
@@ -263,3 +305,82 @@ async fn rate_limiting_configuration_is_loaded() {
         "Burst size should be at least 5"
     );
 }
+
+#[tokio::test]
+async fn hammering_redirects_does_not_use_up_the_shorten_limit() {
+    // Arrange
+    let app = spawn_app_with_redirect_limit().await;
+    let code = app.shorten_url("https://www.example.com/hammered").await;
+
+    // Act - follow the redirect until it is rate limited
+    let mut statuses = Vec::new();
+    for _ in 0..4 {
+        statuses.push(app.get(&format!("/{}", code)).await.status());
+    }
+    let shorten = app
+        .post_api_with_key("/api/shorten", "https://www.example.com/after")
+        .await;
+
+    // Assert
+    assert_eq!(statuses[..3], [StatusCode::PERMANENT_REDIRECT; 3]);
+    assert_eq!(statuses[3], StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(shorten.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn hammering_shorten_does_not_use_up_the_redirect_limit() {
+    // Arrange
+    let app = spawn_app_with_redirect_limit().await;
+    let code = app.shorten_url("https://www.example.com/target").await;
+
+    // Act - use up the rest of the shorten limit
+    let mut statuses = Vec::new();
+    for i in 0..2 {
+        let response = app
+            .post_api_with_key("/api/shorten", format!("https://www.example.com/{}", i))
+            .await;
+        statuses.push(response.status());
+    }
+    let redirect = app.get(&format!("/{}", code)).await;
+
+    // Assert
+    assert_eq!(statuses, [StatusCode::OK, StatusCode::TOO_MANY_REQUESTS]);
+    assert_eq!(redirect.status(), StatusCode::PERMANENT_REDIRECT);
+}
+
+#[tokio::test]
+async fn password_guesses_are_rate_limited() {
+    // Arrange
+    let app = spawn_app().await;
+    let response = app
+        .post_json_with_key(
+            "/api/shorten",
+            &json!({ "url": "https://www.example.com/locked", "password": "correct horse" }),
+        )
+        .await;
+    let body = assert_json_ok(response).await;
+    let code = body["data"]["id"].as_str().expect("missing id").to_string();
+
+    // Act - guess until the unlock limit of 2 requests is used up
+    let mut statuses = Vec::new();
+    for i in 0..3 {
+        let response = app
+            .client
+            .post(app.url(&format!("/{}/unlock", code)))
+            .form(&[("password", format!("guess {}", i))])
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        statuses.push(response.status());
+    }
+
+    // Assert
+    assert_eq!(
+        statuses,
+        [
+            StatusCode::FORBIDDEN,
+            StatusCode::FORBIDDEN,
+            StatusCode::TOO_MANY_REQUESTS
+        ]
+    );
+}
@@ -0,0 +1,188 @@
+// tests/api/reachability.rs
+
+// url_validation.check_reachability: destinations are sent a HEAD request
+// before they are shortened; the destination is served by wiremock behind a
+// host name the test client resolves to it; url_validation.mode is set back
+// to strict, as the test defaults skip destination checks
+
+// dependencies
+use crate::helpers::{TestApp, assert_json_ok, spawn_app_with, spawn_app_with_state};
+use serde_json::Value;
+use url_shortener_ztm_lib::configuration::{Settings, ValidationMode};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const HOST: &str = "destination.test";
+
+fn check_reachability(c: &mut Settings) {
+    c.url_validation.check_reachability = true;
+    c.url_validation.mode = ValidationMode::Strict;
+}
+
+async fn spawn_app_checking(server: &MockServer) -> TestApp {
+    // Redirects are followed by the check, as with the app's own client
+    let client = reqwest::Client::builder()
+        .resolve(HOST, *server.address())
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .expect("failed to build the test client");
+    spawn_app_with_state(check_reachability, |builder| builder.http_client(client)).await
+}
+
+fn destination(server: &MockServer, page: &str) -> String {
+    format!("http://{}:{}{}", HOST, server.address().port(), page)
+}
+
+async fn mount_head(server: &MockServer, page: &str, status: u16) {
+    Mock::given(method("HEAD"))
+        .and(path(page))
+        .respond_with(ResponseTemplate::new(status))
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn reachable_destination_is_shortened() {
+    // Arrange
+    let server = MockServer::start().await;
+    mount_head(&server, "/live", 200).await;
+    let app = spawn_app_checking(&server).await;
+
+    // Act
+    let response = app
+        .post_api_with_key("/api/shorten", destination(&server, "/live"))
+        .await;
+
+    // Assert
+    assert_json_ok(response).await;
+}
+
+#[tokio::test]
+async fn destination_answering_404_is_rejected() {
+    // Arrange
+    let server = MockServer::start().await;
+    mount_head(&server, "/gone", 404).await;
+    let app = spawn_app_checking(&server).await;
+
+    // Act
+    let response = app
+        .post_api_with_key("/api/shorten", destination(&server, "/gone"))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 422);
+    let body: Value = response.json().await.expect("invalid JSON");
+    assert_eq!(
+        body["message"].as_str(),
+        Some("Destination URL is not reachable (status: 404)")
+    );
+}
+
+#[tokio::test]
+async fn unreachable_destination_is_rejected() {
+    // Arrange
+    let server = MockServer::start().await;
+    let url = destination(&server, "/down");
+    let app = spawn_app_checking(&server).await;
+    drop(server);
+
+    // Act
+    let response = app.post_api_with_key("/api/shorten", url).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 422);
+}
+
+#[tokio::test]
+async fn loopback_destinations_are_not_checked() {
+    // Arrange
+    let server = MockServer::start().await;
+    mount_head(&server, "/local", 404).await;
+    let app = spawn_app_with(check_reachability).await;
+
+    // Act
+    let response = app
+        .post_api_with_key("/api/shorten", format!("{}/local", server.uri()))
+        .await;
+
+    // Assert
+    assert_json_ok(response).await;
+    assert!(
+        server
+            .received_requests()
+            .await
+            .unwrap_or_default()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn loopback_destinations_are_not_requested_by_a_client_that_reaches_them() {
+    // Arrange: SSRF protection is off in the test configuration, and the
+    // test client would reach the loopback server; only the check stops it
+    let server = MockServer::start().await;
+    mount_head(&server, "/local", 404).await;
+    let app = spawn_app_checking(&server).await;
+
+    // Act
+    let response = app
+        .post_api_with_key(
+            "/api/shorten",
+            format!("http://localhost:{}/local", server.address().port()),
+        )
+        .await;
+
+    // Assert
+    assert_json_ok(response).await;
+    assert!(
+        server
+            .received_requests()
+            .await
+            .unwrap_or_default()
+            .is_empty()
+    );
+}
+
+#[tokio::test]
+async fn redirects_to_private_hosts_are_not_followed() {
+    // Arrange: the redirect names a host that resolves to loopback
+    let server = MockServer::start().await;
+    Mock::given(method("HEAD"))
+        .and(path("/moved"))
+        .respond_with(ResponseTemplate::new(302).insert_header(
+            "location",
+            format!("http://localhost:{}/internal", server.address().port()).as_str(),
+        ))
+        .mount(&server)
+        .await;
+    Mock::given(method("HEAD"))
+        .and(path("/internal"))
+        .respond_with(ResponseTemplate::new(404))
+        .expect(0)
+        .mount(&server)
+        .await;
+    let app = spawn_app_checking(&server).await;
+
+    // Act
+    let response = app
+        .post_api_with_key("/api/shorten", destination(&server, "/moved"))
+        .await;
+
+    // Assert
+    assert_json_ok(response).await;
+    server.verify().await;
+}
+
+#[tokio::test]
+async fn check_is_off_by_default() {
+    // Arrange
+    let app = spawn_app_with(|_| {}).await;
+
+    // Act
+    let response = app
+        .post_api_with_key("/api/shorten", "https://unreachable.invalid/page")
+        .await;
+
+    // Assert
+    assert_json_ok(response).await;
+}
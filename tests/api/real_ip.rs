@@ -0,0 +1,94 @@
+// tests/api/real_ip.rs
+
+// the rate limiter is keyed on the resolved client IP, so separate budgets
+// show which address the real-IP middleware picked
+
+// dependencies
+use crate::helpers::{TestApp, spawn_app, spawn_app_with};
+use axum::http::StatusCode;
+
+async fn public_shorten_from(app: &TestApp, headers: &[(&str, &str)]) -> StatusCode {
+    let mut request = app
+        .client
+        .post(app.url("/api/public/shorten"))
+        .header("content-type", "text/plain")
+        .body("https://www.example.com/real-ip");
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+    request
+        .send()
+        .await
+        .expect("Failed to execute request.")
+        .status()
+}
+
+async fn spawn_app_behind_proxy() -> TestApp {
+    spawn_app_with(|c| {
+        c.application.trusted_proxies = vec!["127.0.0.1/32".parse().unwrap()];
+    })
+    .await
+}
+
+#[tokio::test]
+async fn forwarded_for_from_trusted_proxy_gives_each_client_its_own_limit() {
+    // Arrange
+    let app = spawn_app_behind_proxy().await;
+    let first = [("x-forwarded-for", "203.0.113.1, 127.0.0.1")];
+
+    // Act - use up the burst of 2 for the first client
+    for _ in 0..2 {
+        assert_eq!(public_shorten_from(&app, &first).await, StatusCode::OK);
+    }
+
+    // Assert
+    assert_eq!(
+        public_shorten_from(&app, &first).await,
+        StatusCode::TOO_MANY_REQUESTS
+    );
+    assert_eq!(
+        public_shorten_from(&app, &[("x-forwarded-for", "203.0.113.2")]).await,
+        StatusCode::OK
+    );
+}
+
+#[tokio::test]
+async fn cf_connecting_ip_takes_priority_over_forwarded_for() {
+    let app = spawn_app_behind_proxy().await;
+
+    for forwarded in ["203.0.113.10", "203.0.113.11"] {
+        let headers = [
+            ("cf-connecting-ip", "198.51.100.1"),
+            ("x-forwarded-for", forwarded),
+        ];
+        assert_eq!(public_shorten_from(&app, &headers).await, StatusCode::OK);
+    }
+
+    // Same CF-Connecting-IP, so the budget is already spent
+    let headers = [
+        ("cf-connecting-ip", "198.51.100.1"),
+        ("x-forwarded-for", "203.0.113.12"),
+    ];
+    assert_eq!(
+        public_shorten_from(&app, &headers).await,
+        StatusCode::TOO_MANY_REQUESTS
+    );
+}
+
+#[tokio::test]
+async fn forwarded_for_from_untrusted_peer_is_ignored() {
+    // No trusted proxies configured, so every request is keyed on 127.0.0.1
+    let app = spawn_app().await;
+
+    for client in ["203.0.113.1", "203.0.113.2"] {
+        assert_eq!(
+            public_shorten_from(&app, &[("x-forwarded-for", client)]).await,
+            StatusCode::OK
+        );
+    }
+
+    assert_eq!(
+        public_shorten_from(&app, &[("x-forwarded-for", "203.0.113.3")]).await,
+        StatusCode::TOO_MANY_REQUESTS
+    );
+}
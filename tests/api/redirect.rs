@@ -4,7 +4,7 @@
 // this endpoint should redirect the user to the shortened URL
 
 // dependencies
-use crate::helpers::{assert_json_ok, assert_redirect_to, spawn_app};
+use crate::helpers::{TestApp, assert_json_ok, assert_redirect_to, spawn_app};
 use axum::http::StatusCode;
 
 #[tokio::test]
@@ -13,24 +13,7 @@ async fn redirect_endpoint_sends_user_to_shortened_destination_url() {
     let app = spawn_app().await;
 
     // Ask the service to shorten a known URL through the public API
-    let original_url = "https://www.google.com";
-    let shorten_response = app.post_api_with_key("/api/shorten", original_url).await;
-    let body = assert_json_ok(shorten_response).await;
-
-    let data = body
-        .get("data")
-        .and_then(|value| value.as_object())
-        .expect("shorten response did not include a data object");
-
-    let generated_id = data
-        .get("id")
-        .and_then(|value| value.as_str())
-        .expect("shorten response did not include an id");
-
-    let normalized_url = data
-        .get("original_url")
-        .and_then(|value| value.as_str())
-        .expect("shorten response did not include the stored original_url");
+    let generated_id = app.shorten_url("https://www.google.com").await;
 
     // Act
     let response = app
@@ -38,7 +21,12 @@ async fn redirect_endpoint_sends_user_to_shortened_destination_url() {
         .await;
 
     // Assert - we expect a permanent redirect (HTTP 308) to the stored URL
-    assert_redirect_to(response, normalized_url, StatusCode::PERMANENT_REDIRECT).await;
+    assert_redirect_to(
+        response,
+        "https://www.google.com/",
+        StatusCode::PERMANENT_REDIRECT,
+    )
+    .await;
 }
 
 #[tokio::test]
@@ -82,3 +70,356 @@ async fn redirect_rejects_invalid_length() {
         "Expected 404 for id with length +1"
     );
 }
+
+/// Shortens `url` through the protected API with the given query string and
+/// returns the generated id.
+async fn shorten_with_query(app: &TestApp, url: &str, query: &str) -> String {
+    let response = app
+        .post_api_with_key(&format!("/api/shorten{}", query), url)
+        .await;
+    let body = assert_json_ok(response).await;
+    body["data"]["id"]
+        .as_str()
+        .expect("shorten response did not include an id")
+        .to_string()
+}
+
+fn cache_control(response: &reqwest::Response) -> Option<&str> {
+    response
+        .headers()
+        .get("cache-control")
+        .and_then(|v| v.to_str().ok())
+}
+
+#[tokio::test]
+async fn permanent_redirect_is_cacheable() {
+    let app = spawn_app().await;
+    let id = app.shorten_url("https://www.example.com/permanent").await;
+
+    let response = app.get_api(&format!("/api/redirect/{}", id)).await;
+
+    assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+    assert_eq!(
+        cache_control(&response),
+        Some("public, max-age=3600, must-revalidate")
+    );
+}
+
+#[tokio::test]
+async fn temporary_redirect_type_is_stored_and_not_cached() {
+    let app = spawn_app().await;
+    let id = shorten_with_query(
+        &app,
+        "https://www.example.com/campaign",
+        "?redirect=temporary",
+    )
+    .await;
+
+    let response = app.get_api(&format!("/api/redirect/{}", id)).await;
+
+    assert_eq!(response.status(), StatusCode::TEMPORARY_REDIRECT);
+    assert_eq!(cache_control(&response), Some("no-cache"));
+    assert_eq!(
+        response
+            .headers()
+            .get("location")
+            .and_then(|v| v.to_str().ok()),
+        Some("https://www.example.com/campaign")
+    );
+}
+
+#[tokio::test]
+async fn redirect_query_parameter_overrides_stored_type() {
+    let app = spawn_app().await;
+    let id = app.shorten_url("https://www.example.com/override").await;
+
+    let response = app
+        .get_api(&format!("/api/redirect/{}?redirect=temporary", id))
+        .await;
+
+    assert_eq!(response.status(), StatusCode::TEMPORARY_REDIRECT);
+    assert_eq!(cache_control(&response), Some("no-cache"));
+}
+
+#[tokio::test]
+async fn shorten_url_then_get_redirect_location_returns_the_original_url() {
+    let app = spawn_app().await;
+
+    let id = app.shorten_url("https://www.example.com/round-trip").await;
+    let alias = app
+        .shorten_url_with_alias("https://www.example.com/round-trip", "roundtrip")
+        .await;
+
+    assert_eq!(alias, "roundtrip");
+    for code in [id.as_str(), alias.as_str()] {
+        assert_eq!(
+            app.get_redirect_location(code).await.as_deref(),
+            Some("https://www.example.com/round-trip")
+        );
+    }
+    assert_eq!(app.get_redirect_location("zzzzzzz").await, None);
+}
+
+#[tokio::test]
+async fn a_taken_alias_keeps_redirecting_to_its_first_url() {
+    // Arrange
+    let app = spawn_app().await;
+    app.shorten_url_with_alias("https://www.example.com/first", "keepme")
+        .await;
+
+    // Act
+    let taken = app
+        .post_api_with_key(
+            "/api/shorten?alias=keepme",
+            "https://www.example.com/second",
+        )
+        .await;
+
+    // Assert
+    assert_eq!(taken.status(), StatusCode::CONFLICT);
+    assert_eq!(
+        app.get_redirect_location("keepme").await.as_deref(),
+        Some("https://www.example.com/first")
+    );
+}
+
+#[tokio::test]
+async fn head_redirect_sends_location_without_a_body_or_a_click() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = app.shorten_url("https://www.example.com/checked").await;
+
+    // Act
+    let response = app
+        .client
+        .head(app.url(&format!("/{}", id)))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+    let headers = response.headers().clone();
+    assert_eq!(
+        headers.get("location").and_then(|v| v.to_str().ok()),
+        Some("https://www.example.com/checked")
+    );
+    assert!(
+        headers
+            .get("cache-control")
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("public, max-age="))
+    );
+    assert!(response.bytes().await.expect("no body").is_empty());
+
+    let info = assert_json_ok(app.get_api(&format!("/api/urls/{}", id)).await).await;
+    assert_eq!(info["data"]["click_count"].as_u64(), Some(0));
+}
+
+#[tokio::test]
+async fn options_redirect_lists_the_allowed_methods() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app
+        .client
+        .request(reqwest::Method::OPTIONS, app.url("/abc123"))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert_eq!(
+        response
+            .headers()
+            .get("allow")
+            .and_then(|v| v.to_str().ok()),
+        Some("GET, HEAD, OPTIONS")
+    );
+}
+
+#[tokio::test]
+async fn redirect_links_to_its_metadata_and_destination() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = app.shorten_url("https://www.example.com/linked").await;
+
+    // Act
+    let response = app.get(&format!("/{}", id)).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+    let links: Vec<&str> = response
+        .headers()
+        .get_all("link")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .collect();
+    assert!(
+        links
+            .contains(&format!("<{}/api/urls/{}>; rel=\"describedby\"", app.base_url, id).as_str()),
+        "{:?}",
+        links
+    );
+    assert!(
+        links.contains(&"<https://www.example.com/linked>; rel=\"canonical\""),
+        "{:?}",
+        links
+    );
+}
+
+fn etag(response: &reqwest::Response) -> Option<String> {
+    response
+        .headers()
+        .get("etag")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+}
+
+async fn get_with_if_none_match(app: &TestApp, id: &str, tag: &str) -> reqwest::Response {
+    app.client
+        .get(app.url(&format!("/{}", id)))
+        .header("if-none-match", tag)
+        .send()
+        .await
+        .expect("Failed to execute request.")
+}
+
+#[tokio::test]
+async fn redirect_sends_an_etag_of_the_destination() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = app.shorten_url("https://www.example.com/tagged").await;
+
+    // Act
+    let response = app.get(&format!("/{}", id)).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+    let tag = etag(&response).expect("redirect should carry an ETag");
+    assert!(tag.starts_with('"') && tag.ends_with('"'), "{}", tag);
+    assert_eq!(tag.len(), 66);
+}
+
+#[tokio::test]
+async fn matching_if_none_match_gets_not_modified() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = app.shorten_url("https://www.example.com/unchanged").await;
+    let tag = etag(&app.get(&format!("/{}", id)).await).expect("missing ETag");
+
+    // Act
+    let response = get_with_if_none_match(&app, &id, &tag).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(etag(&response), Some(tag));
+    assert!(response.headers().get("location").is_none());
+}
+
+#[tokio::test]
+async fn not_modified_does_not_count_a_click() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = app.shorten_url("https://www.example.com/revalidated").await;
+    let tag = etag(&app.get(&format!("/{}", id)).await).expect("missing ETag");
+
+    // Act
+    let response = get_with_if_none_match(&app, &id, &tag).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(app._database.get_click_count(&id).await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn stale_if_none_match_gets_the_new_target_after_an_update() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = app.shorten_url("https://www.example.com/before").await;
+    let old_tag = etag(&app.get(&format!("/{}", id)).await).expect("missing ETag");
+    let response = app
+        .patch_api_with_key(
+            &format!("/api/urls/{}", id),
+            "https://www.example.com/after",
+        )
+        .await;
+    assert_json_ok(response).await;
+
+    // Act
+    let response = get_with_if_none_match(&app, &id, &old_tag).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+    let new_tag = etag(&response).expect("missing ETag");
+    assert_ne!(new_tag, old_tag);
+    assert_eq!(
+        response
+            .headers()
+            .get("location")
+            .and_then(|v| v.to_str().ok()),
+        Some("https://www.example.com/after")
+    );
+}
+
+#[tokio::test]
+async fn r_zero_returns_metadata_instead_of_redirecting() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = app.shorten_url("https://www.example.com/described").await;
+
+    // Act
+    let by_r = app.get(&format!("/{}?r=0", id)).await;
+    let by_alias = app.get(&format!("/{}?no_redirect=true", id)).await;
+
+    // Assert
+    let body = assert_json_ok(by_r).await;
+    assert_eq!(body["data"]["code"].as_str(), Some(id.as_str()));
+    assert_eq!(
+        body["data"]["url"].as_str(),
+        Some("https://www.example.com/described")
+    );
+    let body = assert_json_ok(by_alias).await;
+    assert_eq!(body["data"]["click_count"].as_u64(), Some(0));
+}
+
+#[tokio::test]
+async fn r_one_or_no_parameter_still_redirects() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = app.shorten_url("https://www.example.com/followed").await;
+
+    // Act
+    let explicit = app.get(&format!("/{}?r=1", id)).await;
+    let default = app.get(&format!("/{}", id)).await;
+
+    // Assert
+    assert_redirect_to(
+        explicit,
+        "https://www.example.com/followed",
+        StatusCode::PERMANENT_REDIRECT,
+    )
+    .await;
+    assert_redirect_to(
+        default,
+        "https://www.example.com/followed",
+        StatusCode::PERMANENT_REDIRECT,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn r_zero_with_an_unknown_code_is_a_json_not_found() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app.get("/nope42?r=0").await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    let body: serde_json::Value = response.json().await.expect("body should be JSON");
+    assert_eq!(body["success"].as_bool(), Some(false));
+}
@@ -0,0 +1,68 @@
+// tests/api/redis_rate_limiting.rs
+
+// tests for the rate limit shared between instances through Redis
+
+// dependencies
+use axum::http::StatusCode;
+use testcontainers_modules::{redis::Redis, testcontainers::runners::AsyncRunner};
+use url_shortener_ztm_lib::configuration::RateLimitBackend;
+
+use crate::helpers::{TestApp, spawn_app_with};
+
+async fn spawn_app_sharing(redis_url: &str) -> TestApp {
+    let redis_url = redis_url.to_string();
+    spawn_app_with(|c| {
+        c.rate_limiting.backend = RateLimitBackend::Redis;
+        c.rate_limiting.redis_url = Some(redis_url);
+        c.rate_limiting.requests_per_second = 1;
+        c.rate_limiting.burst_size = 3;
+    })
+    .await
+}
+
+async fn shorten(app: &TestApp, i: usize) -> StatusCode {
+    app.client
+        .post(app.url("/api/public/shorten"))
+        .header("content-type", "text/plain")
+        .body(format!("https://www.example.com/{}", i))
+        .send()
+        .await
+        .expect("Failed to execute request.")
+        .status()
+}
+
+/// Starts Redis in a container, so it needs a Docker daemon.
+///
+/// This test is ignored by default; run it explicitly when Docker is available.
+#[tokio::test]
+#[ignore]
+async fn instances_sharing_redis_enforce_a_combined_limit() {
+    // Arrange
+    let redis = Redis::default()
+        .start()
+        .await
+        .expect("Failed to start Redis");
+    let port = redis
+        .get_host_port_ipv4(6379)
+        .await
+        .expect("Failed to get the Redis port");
+    let redis_url = format!("redis://127.0.0.1:{}", port);
+    let first = spawn_app_sharing(&redis_url).await;
+    let second = spawn_app_sharing(&redis_url).await;
+
+    // Act - alternate between the instances; each stays under the limit of 3
+    // on its own, but together they exceed it within one window
+    let mut statuses = Vec::new();
+    for i in 0..6 {
+        let app = if i % 2 == 0 { &first } else { &second };
+        statuses.push(shorten(app, i).await);
+    }
+
+    // Assert
+    assert_eq!(statuses[0], StatusCode::OK);
+    assert!(
+        statuses.contains(&StatusCode::TOO_MANY_REQUESTS),
+        "the combined limit should be enforced: {:?}",
+        statuses
+    );
+}
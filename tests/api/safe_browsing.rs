@@ -0,0 +1,120 @@
+// tests/api/safe_browsing.rs
+
+// safe_browsing: every stored destination is looked up, not only the URL being
+// shortened; the Safe Browsing API is served by wiremock and flags one URL
+
+// dependencies
+use crate::helpers::{TestApp, assert_json_ok, spawn_app_with_state};
+use axum::http::StatusCode;
+use secrecy::SecretString;
+use serde_json::json;
+use std::sync::Arc;
+use url_shortener_ztm_lib::configuration::SafeBrowsingSettings;
+use url_shortener_ztm_lib::infrastructure::safe_browsing::SafeBrowsing;
+use wiremock::matchers::{body_string_contains, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const FLAGGED: &str = "https://malware.example.com/download";
+
+// Serves the app with Safe Browsing on, flagging only FLAGGED
+async fn spawn_app_screening(server: &MockServer) -> TestApp {
+    Mock::given(method("POST"))
+        .and(path("/v4/threatMatches:find"))
+        .and(body_string_contains(FLAGGED))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "matches": [{
+                "threatType": "MALWARE",
+                "platformType": "ANY_PLATFORM",
+                "threatEntryType": "URL",
+                "threat": { "url": FLAGGED },
+            }]
+        })))
+        .mount(server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v4/threatMatches:find"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+        .mount(server)
+        .await;
+
+    let settings = SafeBrowsingSettings {
+        enabled: true,
+        api_key: Some(SecretString::from("test-key")),
+        cache_ttl_seconds: 60,
+    };
+    let screener = SafeBrowsing::new(&settings, reqwest::Client::new())
+        .with_endpoint(format!("{}/v4/threatMatches:find", server.uri()));
+    spawn_app_with_state(
+        |c| c.safe_browsing = settings,
+        |builder| builder.safe_browsing(Arc::new(screener)),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn patching_to_a_flagged_url_is_rejected() {
+    // Arrange
+    let server = MockServer::start().await;
+    let app = spawn_app_screening(&server).await;
+    let id = app.shorten_url("https://www.example.com/clean").await;
+
+    // Act
+    let response = app
+        .patch_api_with_key(&format!("/api/urls/{}", id), FLAGGED)
+        .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body: serde_json::Value = response.json().await.expect("invalid JSON");
+    assert_eq!(body["message"], "URL flagged by Safe Browsing");
+    let redirect = app.get(&format!("/{}", id)).await;
+    assert_eq!(
+        redirect
+            .headers()
+            .get("location")
+            .and_then(|v| v.to_str().ok()),
+        Some("https://www.example.com/clean")
+    );
+}
+
+#[tokio::test]
+async fn a_flagged_mobile_url_is_rejected() {
+    // Arrange
+    let server = MockServer::start().await;
+    let app = spawn_app_screening(&server).await;
+
+    // Act
+    let response = app
+        .post_json_with_key(
+            "/api/shorten",
+            &json!({
+                "url": "https://www.example.com/desktop",
+                "mobile_url": FLAGGED,
+            }),
+        )
+        .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn clean_urls_are_still_shortened() {
+    // Arrange
+    let server = MockServer::start().await;
+    let app = spawn_app_screening(&server).await;
+
+    // Act
+    let response = app
+        .post_json_with_key(
+            "/api/shorten",
+            &json!({
+                "url": "https://www.example.com/desktop",
+                "mobile_url": "https://m.example.com/",
+            }),
+        )
+        .await;
+
+    // Assert
+    assert_json_ok(response).await;
+}
@@ -0,0 +1,96 @@
+// tests/api/search.rs
+
+// integration tests for GET /api/urls/search
+
+// dependencies
+use crate::helpers::{TestApp, assert_json_ok, spawn_app};
+use axum::http::StatusCode;
+
+async fn seed_five_urls(app: &TestApp) {
+    for (code, url) in [
+        ("srch001", "https://www.example.com/spring-sale"),
+        ("srch002", "https://shop.example.org/checkout"),
+        ("srch003", "https://www.example.com/autumn-sale"),
+        ("srch004", "https://docs.example.net/guide"),
+        ("srch005", "https://blog.example.io/post"),
+    ] {
+        app._database.insert_url(code, url).await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn search_returns_only_matching_urls() {
+    // Arrange
+    let app = spawn_app().await;
+    seed_five_urls(&app).await;
+
+    // Act
+    let response = app.get_api_with_key("/api/urls/search?q=SALE").await;
+
+    // Assert
+    let body = assert_json_ok(response).await;
+    let items = body["data"]["items"].as_array().unwrap();
+    let codes: Vec<&str> = items.iter().map(|i| i["code"].as_str().unwrap()).collect();
+    assert_eq!(codes, vec!["srch001", "srch003"]);
+    assert_eq!(body["data"]["page"].as_u64(), Some(1));
+    assert_eq!(body["data"]["has_more"].as_bool(), Some(false));
+}
+
+#[tokio::test]
+async fn search_paginates_results() {
+    let app = spawn_app().await;
+    seed_five_urls(&app).await;
+
+    let response = app
+        .get_api_with_key("/api/urls/search?q=example&page=2&per_page=2")
+        .await;
+
+    let body = assert_json_ok(response).await;
+    let items = body["data"]["items"].as_array().unwrap();
+    let codes: Vec<&str> = items.iter().map(|i| i["code"].as_str().unwrap()).collect();
+    assert_eq!(codes, vec!["srch003", "srch004"]);
+    assert_eq!(body["data"]["has_more"].as_bool(), Some(true));
+}
+
+#[tokio::test]
+async fn search_matches_aliases() {
+    let app = spawn_app().await;
+    seed_five_urls(&app).await;
+    let target = app
+        ._database
+        .get_id_by_url("https://docs.example.net/guide")
+        .await
+        .unwrap();
+    app._database
+        .insert_alias("userGuide", target.id)
+        .await
+        .unwrap();
+
+    let response = app.get_api_with_key("/api/urls/search?q=userguide").await;
+
+    let body = assert_json_ok(response).await;
+    let items = body["data"]["items"].as_array().unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["code"].as_str(), Some("srch004"));
+}
+
+#[tokio::test]
+async fn search_with_no_matches_returns_empty_list() {
+    let app = spawn_app().await;
+    seed_five_urls(&app).await;
+
+    // `%` must be matched literally rather than as a wildcard
+    let response = app.get_api_with_key("/api/urls/search?q=%25").await;
+
+    let body = assert_json_ok(response).await;
+    assert_eq!(body["data"]["items"].as_array().unwrap().len(), 0);
+}
+
+#[tokio::test]
+async fn search_requires_api_key() {
+    let app = spawn_app().await;
+
+    let response = app.get_api("/api/urls/search?q=example").await;
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
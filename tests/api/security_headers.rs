@@ -0,0 +1,85 @@
+// tests/api/security_headers.rs
+
+// dependencies
+use crate::helpers::{assert_json_ok, spawn_app, spawn_app_with, spawn_app_with_accounts};
+use reqwest::header::{
+    CONTENT_SECURITY_POLICY, REFERRER_POLICY, X_CONTENT_TYPE_OPTIONS, X_FRAME_OPTIONS,
+};
+use serde_json::json;
+use url_shortener_ztm_lib::middleware::security_headers::DEFAULT_CONTENT_SECURITY_POLICY;
+
+#[tokio::test]
+async fn admin_dashboard_sends_security_headers() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let response = app.get_admin_dashboard().await;
+
+    // Assert
+    let headers = response.headers();
+    assert_eq!(headers[X_FRAME_OPTIONS], "DENY");
+    assert_eq!(headers[X_CONTENT_TYPE_OPTIONS], "nosniff");
+    assert_eq!(headers[REFERRER_POLICY], "strict-origin-when-cross-origin");
+    assert_eq!(
+        headers[CONTENT_SECURITY_POLICY],
+        DEFAULT_CONTENT_SECURITY_POLICY
+    );
+}
+
+#[tokio::test]
+async fn configured_csp_replaces_default_policy() {
+    let app = spawn_app_with(|c| {
+        c.application.csp_header = Some("default-src 'none'".to_string());
+    })
+    .await;
+
+    let response = app.get_admin_dashboard().await;
+
+    assert_eq!(
+        response.headers()[CONTENT_SECURITY_POLICY],
+        "default-src 'none'"
+    );
+}
+
+#[tokio::test]
+async fn redirect_sends_no_referrer_policy() {
+    // Arrange
+    let app = spawn_app().await;
+    let response = app
+        .post_api_with_key("/api/shorten", "https://www.example.com/referrer")
+        .await;
+    let body = assert_json_ok(response).await;
+    let code = body["data"]["id"].as_str().unwrap();
+
+    // Act
+    let response = app.get(&format!("/{}", code)).await;
+
+    // Assert
+    assert!(response.status().is_redirection());
+    assert_eq!(response.headers()[REFERRER_POLICY], "no-referrer");
+    assert_eq!(response.headers()[X_FRAME_OPTIONS], "DENY");
+}
+
+#[tokio::test]
+async fn auth_routes_send_security_headers() {
+    // Arrange: the auth routes are only mounted on Postgres
+    let app = spawn_app_with_accounts(|_| {}).await;
+
+    // Act
+    let response = app
+        .client
+        .post(app.url("/api/v1/auth/sign-in"))
+        .json(&json!({ "email": "nobody@example.com", "password": "wrong" }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    let headers = response.headers();
+    assert_eq!(headers[X_FRAME_OPTIONS], "DENY");
+    assert_eq!(
+        headers[CONTENT_SECURITY_POLICY],
+        DEFAULT_CONTENT_SECURITY_POLICY
+    );
+}
@@ -6,11 +6,18 @@
 // - URL length validation (max 2048 characters)
 // - Edge cases (exact limit, exceeding limit)
 // - URL normalization and slash validation
+// - JSON request bodies with alias and ttl_seconds
+// - Short URLs built from the configured base_url
+// - Request body size limit (413 before the body is processed)
+// - SSRF protection for URLs pointing into private networks
 
-use crate::helpers::{assert_json_ok, spawn_app};
+use crate::helpers::{assert_json_ok, spawn_app, spawn_app_with};
 use axum::http::StatusCode;
+use chrono::{DateTime, Duration, Utc};
 use regex::Regex;
-use url_shortener_ztm_lib::routes::shorten::normalize_url;
+use serde_json::{Value, json};
+use url_shortener_ztm_lib::configuration::ValidationMode;
+use url_shortener_ztm_lib::validation::normalize_url;
 
 /// Test that the shorten endpoint successfully shortens a valid URL
 #[tokio::test]
@@ -134,6 +141,54 @@ async fn shorten_rejects_url_exceeding_max_length() {
     );
 }
 
+/// Test that SSRF protection rejects private destinations and keeps public ones
+#[tokio::test]
+async fn shorten_rejects_private_addresses_when_ssrf_protection_is_enabled() {
+    let app = spawn_app_with(|c| {
+        c.rate_limiting.enabled = false;
+        c.application.ssrf_protection.enabled = true;
+        c.application.ssrf_protection.extra_blocked_cidrs = vec!["203.0.113.0/24".parse().unwrap()];
+        c.url_validation.mode = ValidationMode::Strict;
+    })
+    .await;
+
+    for url in [
+        "http://192.168.1.1/admin",
+        "http://10.0.0.1/",
+        "http://[::1]:8080/",
+        "http://203.0.113.9/",
+    ] {
+        let response = app.post_api_with_key("/api/shorten", url).await;
+
+        assert_eq!(
+            response.status(),
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "{}",
+            url
+        );
+        let body = response.text().await.unwrap();
+        assert!(body.contains("private address"), "{}", body);
+    }
+
+    let response = app
+        .post_api_with_key("/api/shorten", "http://93.184.216.34/")
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+/// Test that an overlong URL carries the stable `URL_TOO_LONG` error code
+#[tokio::test]
+async fn shorten_overlong_url_returns_url_too_long_error_code() {
+    let app = spawn_app().await;
+    let url = make_url_with_total_len(2049);
+
+    let response = app.post_api_with_key("/api/shorten", &url).await;
+
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let body: Value = response.json().await.expect("Failed to parse JSON");
+    assert_eq!(body["error_code"], "URL_TOO_LONG");
+}
+
 /// Test that significantly oversized URLs are rejected
 #[tokio::test]
 async fn shorten_rejects_very_long_url() {
@@ -159,6 +214,175 @@ async fn shorten_rejects_very_long_url() {
     );
 }
 
+/// Test that the configured base_url is used even when the Host header differs
+#[tokio::test]
+async fn shorten_uses_configured_base_url_not_host_header() {
+    // Arrange
+    let app =
+        spawn_app_with(|c| c.application.base_url = "https://s.example.com".to_string()).await;
+
+    // Act
+    let response = app
+        .client
+        .post(app.api("/api/shorten"))
+        .header("x-api-key", app.api_key.to_string())
+        .header("host", "internal-proxy:9000")
+        .body("https://www.example.com/behind-proxy")
+        .send()
+        .await
+        .expect("Failed to execute POST request");
+
+    // Assert
+    let body = assert_json_ok(response).await;
+    let shortened_url = body["data"]["shortened_url"].as_str().unwrap();
+    let id = body["data"]["id"].as_str().unwrap();
+    assert_eq!(shortened_url, format!("https://s.example.com/{}", id));
+}
+
+/// Test that a body over `max_body_bytes` is refused with 413 before it is processed
+#[tokio::test]
+async fn shorten_rejects_oversized_body_with_413() {
+    // Arrange
+    let app = spawn_app().await;
+    let body = make_url_with_total_len(100 * 1024);
+
+    // Act
+    let response = app
+        .post_typed_with_key("/api/shorten", "text/plain", body)
+        .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    let body: serde_json::Value = response.json().await.expect("Response should be JSON");
+    assert_eq!(body["message"], "Request body too large");
+}
+
+/// Test that a small body is unaffected by the body size limit
+#[tokio::test]
+async fn shorten_accepts_small_body_under_limit() {
+    // Arrange
+    let app = spawn_app().await;
+    let body = "http://a.b";
+    assert_eq!(body.len(), 10);
+
+    // Act
+    let response = app
+        .post_typed_with_key("/api/shorten", "text/plain", body)
+        .await;
+
+    // Assert
+    assert_json_ok(response).await;
+}
+
+/// Test that a JSON body is accepted and its alias is used as the short code
+#[tokio::test]
+async fn shorten_accepts_json_body_with_alias() {
+    // Arrange
+    let app = spawn_app().await;
+    let body = json!({ "url": "https://www.example.com/json", "alias": "jsonalias" });
+
+    // Act
+    let response = app.post_json_with_key("/api/shorten", &body).await;
+
+    // Assert
+    let body = assert_json_ok(response).await;
+    assert_eq!(body["data"]["id"], "jsonalias");
+    assert_eq!(body["data"]["original_url"], "https://www.example.com/json");
+    assert!(body["data"]["expires_at"].is_null());
+}
+
+/// Test that `ttl_seconds` sets an expiry roughly that far in the future
+#[tokio::test]
+async fn shorten_json_ttl_seconds_sets_expires_at() {
+    // Arrange
+    let app = spawn_app().await;
+    let body = json!({ "url": "https://www.example.com/ttl", "ttl_seconds": 3600 });
+    let before = Utc::now();
+
+    // Act
+    let response = app.post_json_with_key("/api/shorten", &body).await;
+
+    // Assert
+    let body = assert_json_ok(response).await;
+    let expires_at: DateTime<Utc> = body["data"]["expires_at"]
+        .as_str()
+        .expect("expires_at should be set")
+        .parse()
+        .expect("expires_at should be RFC 3339");
+    assert!(expires_at >= before + Duration::seconds(3600));
+    assert!(expires_at <= Utc::now() + Duration::seconds(3600));
+}
+
+/// Test that a zero TTL is rejected
+#[tokio::test]
+async fn shorten_json_rejects_zero_ttl_seconds() {
+    // Arrange
+    let app = spawn_app().await;
+    let body = json!({ "url": "https://www.example.com/", "ttl_seconds": 0 });
+
+    // Act
+    let response = app.post_json_with_key("/api/shorten", &body).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+/// Test that JSON bodies go through the same URL and alias validation
+#[tokio::test]
+async fn shorten_json_reports_validation_errors() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let bad_url = app
+        .post_json_with_key("/api/shorten", &json!({ "url": "notaurl" }))
+        .await;
+    let bad_alias = app
+        .post_json_with_key(
+            "/api/shorten",
+            &json!({ "url": "https://www.example.com/", "alias": "not valid!" }),
+        )
+        .await;
+
+    // Assert
+    assert_eq!(bad_url.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(bad_alias.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+/// Test that a JSON body missing `url` or with broken syntax is rejected
+#[tokio::test]
+async fn shorten_json_rejects_malformed_bodies() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let missing_url = app
+        .post_json_with_key("/api/shorten", &json!({ "alias": "nourl" }))
+        .await;
+    let broken = app
+        .post_typed_with_key("/api/shorten", "application/json", "{\"url\":")
+        .await;
+
+    // Assert
+    assert_eq!(missing_url.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(broken.status(), StatusCode::BAD_REQUEST);
+}
+
+/// Test that a plain-text body still works alongside the query-string alias
+#[tokio::test]
+async fn shorten_plain_text_body_keeps_query_alias() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let id = app
+        .shorten_url_with_alias("https://www.example.com/plain", "plainalias")
+        .await;
+
+    // Assert
+    assert_eq!(id, "plainalias");
+}
+
 /// Unit tests for the normalize_url function
 /// Tests the slash validation functionality specifically
 #[cfg(test)]
@@ -291,8 +515,11 @@ mod normalize_url_tests {
 
             let error = result.unwrap_err();
             assert!(
-                matches!(error, ApiError::Unprocessable(_)),
-                "Expected ApiError::Unprocessable for URL: '{}'",
+                matches!(
+                    error,
+                    ApiError::InvalidScheme(_) | ApiError::Unprocessable(_)
+                ),
+                "Expected ApiError::InvalidScheme or Unprocessable for URL: '{}'",
                 url
             );
         }
@@ -341,4 +568,73 @@ mod normalize_url_tests {
         let normalized = result.unwrap();
         assert_eq!(normalized, "http://localhost:8080/");
     }
+
+    /// Test that only the fragment is stripped; the query string is kept
+    #[test]
+    fn normalize_url_keeps_the_query_string() {
+        // Fragment stripped, query kept
+        let normalized = normalize_url("https://example.com/path?q=1&r=2#section").unwrap();
+        assert_eq!(normalized, "https://example.com/path?q=1&r=2");
+
+        // A bare fragment leaves the root path
+        let normalized = normalize_url("https://example.com#only-fragment").unwrap();
+        assert_eq!(normalized, "https://example.com/");
+
+        // Percent-encoding in the query is preserved
+        let normalized = normalize_url("https://example.com?key=val%20ue").unwrap();
+        assert_eq!(normalized, "https://example.com/?key=val%20ue");
+    }
+
+    /// Test that non-ASCII paths are stored percent-encoded, and only once
+    #[test]
+    fn normalize_url_percent_encodes_non_ascii_paths() {
+        // Non-ASCII segments are percent-encoded
+        let normalized = normalize_url("https://example.com/日本語/path").unwrap();
+        assert_eq!(
+            normalized,
+            "https://example.com/%E6%97%A5%E6%9C%AC%E8%AA%9E/path"
+        );
+        assert!(normalized.is_ascii());
+
+        // Normalizing twice is idempotent
+        assert_eq!(normalize_url(&normalized).unwrap(), normalized);
+
+        // Existing escapes are not encoded again
+        let normalized = normalize_url("https://example.com/a%20b").unwrap();
+        assert_eq!(normalized, "https://example.com/a%20b");
+        assert_eq!(normalize_url(&normalized).unwrap(), normalized);
+
+        // Escapes differing only in case normalize to the same URL
+        assert_eq!(
+            normalize_url("https://example.com/%e6%97%a5").unwrap(),
+            normalize_url("https://example.com/日").unwrap()
+        );
+    }
+
+    /// Test that internationalized hosts are stored in their punycode form
+    #[test]
+    fn normalize_url_converts_unicode_hosts_to_punycode() {
+        // A Unicode host is converted
+        let normalized = normalize_url("https://münchen.de/path").unwrap();
+        assert_eq!(normalized, "https://xn--mnchen-3ya.de/path");
+
+        // An already punycode host is unchanged
+        let punycode = normalize_url("https://xn--mnchen-3ya.de/path").unwrap();
+        assert_eq!(punycode, "https://xn--mnchen-3ya.de/path");
+
+        // Both spellings dedupe to the same URL, whatever the case
+        assert_eq!(normalize_url("https://MÜNCHEN.de/path").unwrap(), punycode);
+    }
+
+    /// Test that a host that is not a valid IDN is rejected
+    #[test]
+    fn normalize_url_rejects_malformed_idn_hosts() {
+        // A label may not start with a combining mark
+        let result = normalize_url("https://\u{0301}abc.example/");
+        assert!(
+            matches!(result, Err(ApiError::Unprocessable(_))),
+            "{:?}",
+            result
+        );
+    }
 }
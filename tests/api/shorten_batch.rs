@@ -0,0 +1,338 @@
+// tests/api/shorten_batch.rs
+
+// integration tests for the /api/shorten/batch endpoint
+// valid entries are stored in one transaction, invalid ones are reported per item
+// with a 207 Multi-Status response
+
+// dependencies
+use crate::helpers::{assert_json_ok, spawn_app};
+use axum::http::StatusCode;
+use serde_json::{Value, json};
+use sqlx::{Connection, Executor, SqliteConnection};
+use url_shortener_ztm_lib::DatabaseType;
+use url_shortener_ztm_lib::configuration::DatabaseSettings;
+use url_shortener_ztm_lib::database::{BATCH_INSERT_CHUNK, SqliteUrlDatabase, UrlDatabase};
+use url_shortener_ztm_lib::routes::shorten::MAX_BATCH_ITEMS;
+use uuid::Uuid;
+
+// assert a 207 Multi-Status JSON envelope and return the parsed body
+async fn assert_multi_status(response: reqwest::Response) -> Value {
+    assert_eq!(response.status(), StatusCode::MULTI_STATUS);
+    let body: Value = response.json().await.expect("body should be JSON");
+    assert_eq!(body["success"].as_bool(), Some(true));
+    assert_eq!(body["status"].as_u64(), Some(207));
+    body
+}
+
+#[tokio::test]
+async fn batch_shortens_every_valid_entry() {
+    // Arrange
+    let app = spawn_app().await;
+    let payload = json!([
+        {"url": "https://www.example.com/one"},
+        {"url": "https://www.example.com/two"},
+        {"url": "https://www.example.com/three"}
+    ]);
+
+    // Act
+    let response = app.post_json_with_key("/api/shorten/batch", &payload).await;
+
+    // Assert
+    let body = assert_multi_status(response).await;
+    let items = body["data"].as_array().expect("data should be an array");
+    assert_eq!(items.len(), 3);
+    for (item, path) in items.iter().zip(["one", "two", "three"]) {
+        assert_eq!(item["success"].as_bool(), Some(true));
+        assert_eq!(
+            item["original_url"].as_str(),
+            Some(format!("https://www.example.com/{}", path).as_str())
+        );
+
+        // every stored entry should redirect
+        let id = item["id"].as_str().expect("missing id");
+        let redirect = app.get_api(&format!("/api/redirect/{}", id)).await;
+        assert_eq!(redirect.status(), StatusCode::PERMANENT_REDIRECT);
+    }
+}
+
+#[tokio::test]
+async fn batch_reports_invalid_entries_without_failing_the_rest() {
+    let app = spawn_app().await;
+    let payload = json!([
+        {"url": "https://www.example.com/good"},
+        {"url": "not a url"},
+        {"url": "ftp://example.com/file"}
+    ]);
+
+    let response = app.post_json_with_key("/api/shorten/batch", &payload).await;
+
+    let body = assert_multi_status(response).await;
+    let items = body["data"].as_array().expect("data should be an array");
+    assert_eq!(items[0]["success"].as_bool(), Some(true));
+    assert!(items[0]["error"].is_null());
+    assert_eq!(items[1]["success"].as_bool(), Some(false));
+    assert_eq!(items[1]["url"].as_str(), Some("not a url"));
+    assert!(items[1]["error"].as_str().is_some());
+    assert!(items[1].get("id").is_none());
+    assert_eq!(items[2]["success"].as_bool(), Some(false));
+}
+
+#[tokio::test]
+async fn batch_reuses_codes_for_duplicate_urls() {
+    let app = spawn_app().await;
+    let existing = app
+        .post_api_with_key("/api/shorten", "https://www.example.com/dup")
+        .await;
+    let existing: Value = assert_json_ok(existing).await;
+    let existing_id = existing["data"]["id"].as_str().unwrap().to_string();
+
+    let payload = json!([
+        {"url": "https://www.example.com/dup"},
+        {"url": "https://www.example.com/dup#fragment"}
+    ]);
+    let response = app.post_json_with_key("/api/shorten/batch", &payload).await;
+
+    let body = assert_multi_status(response).await;
+    let items = body["data"].as_array().unwrap();
+    assert_eq!(items[0]["id"].as_str(), Some(existing_id.as_str()));
+    assert_eq!(items[1]["id"].as_str(), Some(existing_id.as_str()));
+}
+
+#[tokio::test]
+async fn batch_rejects_more_than_the_maximum_entries() {
+    let app = spawn_app().await;
+    let payload: Vec<Value> = (0..=MAX_BATCH_ITEMS)
+        .map(|i| json!({"url": format!("https://www.example.com/{}", i)}))
+        .collect();
+
+    let response = app
+        .post_json_with_key("/api/shorten/batch", &Value::Array(payload))
+        .await;
+
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn batch_at_the_maximum_is_stored_in_full() {
+    let app = spawn_app().await;
+    let payload: Vec<Value> = (0..MAX_BATCH_ITEMS)
+        .map(|i| json!({"url": format!("https://www.example.com/max/{}", i)}))
+        .collect();
+
+    let response = app
+        .post_json_with_key("/api/shorten/batch", &Value::Array(payload))
+        .await;
+
+    let body = assert_multi_status(response).await;
+    let items = body["data"].as_array().unwrap();
+    assert_eq!(items.len(), MAX_BATCH_ITEMS);
+    assert!(items.iter().all(|i| i["success"].as_bool() == Some(true)));
+}
+
+#[tokio::test]
+async fn batch_entries_can_carry_aliases() {
+    let app = spawn_app().await;
+    let payload = json!([
+        {"url": "https://www.example.com/aliased", "alias": "batchAlias"},
+        {"url": "https://www.example.com/bad-alias", "alias": "bad alias!"}
+    ]);
+
+    let response = app.post_json_with_key("/api/shorten/batch", &payload).await;
+
+    let body = assert_multi_status(response).await;
+    let items = body["data"].as_array().unwrap();
+    assert_eq!(items[0]["success"].as_bool(), Some(true));
+    assert_eq!(items[0]["id"].as_str(), Some("batchAlias"));
+    assert_eq!(items[1]["success"].as_bool(), Some(false));
+    assert!(items[1]["error"].as_str().is_some());
+
+    let redirect = app.get_api("/api/redirect/batchAlias").await;
+    assert_eq!(redirect.status(), StatusCode::PERMANENT_REDIRECT);
+    // the entry with the invalid alias is not stored at all
+    let missing = app
+        ._database
+        .get_id_by_url("https://www.example.com/bad-alias")
+        .await;
+    assert!(missing.is_err());
+}
+
+#[tokio::test]
+async fn batch_reports_taken_aliases_per_entry() {
+    let app = spawn_app().await;
+    let taken = app
+        .post_api_with_key(
+            "/api/shorten?alias=takenAlias",
+            "https://www.example.com/first",
+        )
+        .await;
+    assert_json_ok(taken).await;
+
+    let payload = json!([
+        {"url": "https://www.example.com/second", "alias": "takenAlias"},
+        {"url": "https://www.example.com/third"}
+    ]);
+    let response = app.post_json_with_key("/api/shorten/batch", &payload).await;
+
+    let body = assert_multi_status(response).await;
+    let items = body["data"].as_array().unwrap();
+    assert_eq!(items[0]["success"].as_bool(), Some(false));
+    assert_eq!(items[0]["error"].as_str(), Some("Alias is already taken"));
+    assert_eq!(items[1]["success"].as_bool(), Some(true));
+}
+
+#[tokio::test]
+async fn batch_requires_api_key() {
+    let app = spawn_app().await;
+
+    let response = app
+        .client
+        .post(app.api("/api/shorten/batch"))
+        .json(&json!([{"url": "https://www.example.com/"}]))
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn insert_urls_batch_commits_all_rows_together() {
+    let app = spawn_app().await;
+    let db = &app._database;
+
+    let results = db
+        .insert_urls_batch(&[
+            ("batchA1", "https://www.example.com/a"),
+            ("batchB1", "https://www.example.com/b"),
+            ("batchC1", "https://www.example.com/a"),
+        ])
+        .await
+        .expect("batch insert failed");
+
+    assert_eq!(results.len(), 3);
+    assert!(results[0].created);
+    assert!(results[1].created);
+    // the third row repeats the first URL, so it resolves to the first code
+    assert!(!results[2].created);
+    assert_eq!(results[2].code, "batchA1");
+
+    assert_eq!(
+        db.get_url("batchA1").await.unwrap(),
+        "https://www.example.com/a"
+    );
+    assert_eq!(
+        db.get_url("batchB1").await.unwrap(),
+        "https://www.example.com/b"
+    );
+    assert!(db.get_url("batchC1").await.is_err());
+}
+
+#[tokio::test]
+async fn insert_urls_batch_skips_rows_whose_code_is_taken() {
+    let app = spawn_app().await;
+    let db = &app._database;
+    db.insert_url("taken12", "https://www.example.com/first")
+        .await
+        .unwrap();
+
+    let results = db
+        .insert_urls_batch(&[
+            ("taken12", "https://www.example.com/second"),
+            ("fresh12", "https://www.example.com/third"),
+        ])
+        .await
+        .expect("batch insert failed");
+
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].code, "fresh12");
+    assert_eq!(
+        db.get_url("taken12").await.unwrap(),
+        "https://www.example.com/first"
+    );
+}
+
+#[tokio::test]
+async fn insert_urls_batch_skips_rows_whose_code_is_an_alias() {
+    let app = spawn_app().await;
+    let db = &app._database;
+    let (upsert, _) = db
+        .insert_url("target1", "https://www.example.com/target")
+        .await
+        .unwrap();
+    db.insert_alias("alias12", upsert.id).await.unwrap();
+
+    let results = db
+        .insert_urls_batch(&[
+            ("alias12", "https://www.example.com/second"),
+            ("fresh12", "https://www.example.com/third"),
+        ])
+        .await
+        .expect("an alias collision should not fail the batch");
+
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].code, "fresh12");
+    assert!(
+        db.get_id_by_url("https://www.example.com/second")
+            .await
+            .is_err()
+    );
+    assert_eq!(
+        db.get_url("alias12").await.unwrap(),
+        "https://www.example.com/target"
+    );
+}
+
+#[tokio::test]
+async fn insert_urls_batch_rolls_back_every_chunk_when_one_fails() {
+    // Arrange: a file database with a trigger that fails one row of the
+    // second chunk
+    let path = std::env::temp_dir().join(format!("batch-{}.db", Uuid::new_v4()));
+    let db = SqliteUrlDatabase::from_config(&DatabaseSettings {
+        r#type: DatabaseType::Sqlite,
+        url: path.to_string_lossy().into_owned(),
+        create_if_missing: true,
+        max_connections: None,
+        min_connections: None,
+        busy_timeout_ms: None,
+    })
+    .await
+    .unwrap();
+    db.migrate().await.unwrap();
+    let mut connection = SqliteConnection::connect(&format!("sqlite://{}", path.display()))
+        .await
+        .unwrap();
+    connection
+        .execute(
+            "CREATE TRIGGER fail_poison BEFORE INSERT ON urls \
+             WHEN NEW.url = 'https://www.example.com/poison' \
+             BEGIN SELECT RAISE(ABORT, 'poisoned'); END",
+        )
+        .await
+        .unwrap();
+    connection.close().await.unwrap();
+
+    let mut items: Vec<(String, String)> = (0..BATCH_INSERT_CHUNK)
+        .map(|i| {
+            (
+                format!("roll{:03}", i),
+                format!("https://www.example.com/{}", i),
+            )
+        })
+        .collect();
+    items.push((
+        "poison1".to_string(),
+        "https://www.example.com/poison".to_string(),
+    ));
+    let items: Vec<(&str, &str)> = items
+        .iter()
+        .map(|(code, url)| (code.as_str(), url.as_str()))
+        .collect();
+
+    // Act
+    let result = db.insert_urls_batch(&items).await;
+
+    // Assert: the first chunk was not kept either
+    assert!(result.is_err());
+    assert!(db.get_url("roll000").await.is_err());
+    std::fs::remove_file(&path).unwrap();
+}
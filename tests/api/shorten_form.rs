@@ -0,0 +1,181 @@
+// tests/api/shorten_form.rs
+
+// integration tests for HTML form submissions to /api/shorten
+// (application/x-www-form-urlencoded), which are answered with a 303 redirect
+// to the shorten form page (GET /admin/shorten) and a one-time flash message
+
+// dependencies
+use crate::helpers::{TestApp, spawn_app_with, spawn_app_with_session};
+use axum::http::StatusCode;
+use axum_extra::extract::cookie::Cookie;
+use reqwest::header::{CONTENT_TYPE, COOKIE, LOCATION, SET_COOKIE};
+
+async fn spawn() -> TestApp {
+    spawn_app_with(|c| c.rate_limiting.enabled = false).await
+}
+
+async fn submit_form(app: &TestApp, body: &str) -> reqwest::Response {
+    app.client
+        .post(app.api("/api/shorten"))
+        .header("x-api-key", app.api_key.to_string())
+        .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .body(body.to_string())
+        .send()
+        .await
+        .expect("Failed to execute POST request")
+}
+
+// The decoded value of the flash cookie set by `response`
+fn flash(response: &reqwest::Response) -> String {
+    let cookie = response
+        .headers()
+        .get_all(SET_COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .find(|c| c.starts_with("flash="))
+        .expect("no flash cookie was set");
+    Cookie::parse_encoded(cookie).unwrap().value().to_string()
+}
+
+#[tokio::test]
+async fn a_form_submission_redirects_to_the_shorten_form() {
+    // Arrange
+    let app = spawn().await;
+
+    // Act
+    let response = submit_form(
+        &app,
+        "url=https%3A%2F%2Fwww.example.com%2Fform&alias=&ttl_seconds=",
+    )
+    .await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    assert_eq!(response.headers()[LOCATION], "/admin/shorten");
+    let message = flash(&response);
+    assert!(message.starts_with("Shortened to "), "{}", message);
+}
+
+#[tokio::test]
+async fn a_form_alias_is_used_for_the_short_link() {
+    // Arrange
+    let app = spawn().await;
+
+    // Act
+    submit_form(
+        &app,
+        "url=https%3A%2F%2Fwww.example.com%2Fdocs&alias=formdocs&ttl_seconds=3600",
+    )
+    .await;
+
+    // Assert
+    let location = app.get_redirect_location("formdocs").await;
+    assert_eq!(location.as_deref(), Some("https://www.example.com/docs"));
+}
+
+#[tokio::test]
+async fn an_invalid_url_is_reported_in_the_flash_message() {
+    // Arrange
+    let app = spawn().await;
+
+    // Act
+    let response = submit_form(&app, "url=ftp%3A%2F%2Fexample.com%2Ffile").await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    assert_eq!(response.headers()[LOCATION], "/admin/shorten");
+    let message = flash(&response);
+    assert!(message.contains("Unsupported scheme"), "{}", message);
+}
+
+#[tokio::test]
+async fn the_shorten_form_shows_the_flash_message_once() {
+    // Arrange
+    let app = spawn().await;
+
+    // Act
+    let response = app
+        .client
+        .get(app.url("/admin/shorten"))
+        .header("x-api-key", app.api_key.to_string())
+        .header(COOKIE, "flash=Shortened%20to%20somewhere")
+        .send()
+        .await
+        .expect("Failed to execute GET request");
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let cleared = response
+        .headers()
+        .get_all(SET_COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .any(|c| c.starts_with("flash=") && c.contains("Max-Age=0"));
+    assert!(cleared, "the flash cookie was not cleared");
+    let html = response.text().await.unwrap();
+    assert!(html.contains("Shortened to somewhere"));
+}
+
+#[tokio::test]
+async fn flash_messages_with_cookie_delimiters_survive_the_round_trip() {
+    // Arrange
+    let app = spawn().await;
+
+    // Act: the error message echoes the scheme, `x;"y`
+    let response = submit_form(&app, "url=x%3B%22y%3A%2F%2Fexample.com").await;
+    let set_cookie = response
+        .headers()
+        .get_all(SET_COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .find(|c| c.starts_with("flash="))
+        .expect("no flash cookie was set")
+        .to_string();
+    let pair = set_cookie.split(';').next().unwrap();
+    let page = app
+        .client
+        .get(app.url("/admin/shorten"))
+        .header("x-api-key", app.api_key.to_string())
+        .header(COOKIE, pair)
+        .send()
+        .await
+        .expect("Failed to execute GET request");
+
+    // Assert
+    assert!(!pair.contains('"'), "{}", set_cookie);
+    assert!(set_cookie.contains("Path=/admin"), "{}", set_cookie);
+    assert_eq!(flash(&response), "Unsupported scheme: x;\"y");
+    let html = page.text().await.unwrap();
+    assert!(html.contains("Unsupported scheme: x;&quot;y"), "{}", html);
+}
+
+#[tokio::test]
+async fn a_signed_in_user_is_sent_back_to_a_page_they_can_open() {
+    // Arrange
+    let (app, session) = spawn_app_with_session(|c, _| c.rate_limiting.enabled = false).await;
+
+    // Act
+    let response = app
+        .client
+        .post(app.api("/api/shorten"))
+        .header(COOKIE, &session)
+        .header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .body("url=https%3A%2F%2Fwww.example.com%2Fmine")
+        .send()
+        .await
+        .expect("Failed to execute POST request");
+    let location = response.headers()[LOCATION].to_str().unwrap().to_string();
+    let page = app
+        .client
+        .get(app.url(&location))
+        .header(COOKIE, format!("{}; flash={}", session, "done"))
+        .send()
+        .await
+        .expect("Failed to execute GET request");
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::SEE_OTHER);
+    assert!(flash(&response).starts_with("Shortened to "));
+    assert_eq!(page.status(), StatusCode::OK);
+    assert!(page.text().await.unwrap().contains("done"));
+}
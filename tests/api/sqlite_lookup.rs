@@ -0,0 +1,198 @@
+// tests/api/sqlite_lookup.rs
+
+// regression tests for SQLite lookups: short codes and aliases are resolved by
+// `code` through the all_short_codes view, and known URLs by their url_hash
+
+// dependencies
+use crate::helpers::{build_test_state, test_configuration};
+use axum::extract::{Extension, Path, Query, State};
+use axum::http::uri::Authority;
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::response::IntoResponse;
+use axum_extra::{TypedHeader, headers::Host};
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::Arc;
+use url_shortener_ztm_lib::database::{DatabaseError, SqliteUrlDatabase, UrlDatabase};
+use url_shortener_ztm_lib::middleware::RealIp;
+use url_shortener_ztm_lib::routes::{
+    RedirectParams, RedirectQueryParams, ShortenInput, ShortenParams, ShortenRequest, get_redirect,
+    post_shorten,
+};
+use url_shortener_ztm_lib::state::AppState;
+
+// Shortens `url` through the handler and returns the short code
+async fn shorten(state: &AppState, url: &str) -> String {
+    let response = post_shorten(
+        State(state.clone()),
+        TypedHeader(Host::from(Authority::from_static("localhost:8000"))),
+        None,
+        Query(ShortenParams {
+            alias: None,
+            tags: None,
+            redirect: None,
+        }),
+        ShortenInput::Json(ShortenRequest {
+            url: url.to_string(),
+            alias: None,
+            ttl_seconds: None,
+            password: None,
+            ab_targets: None,
+            mobile_url: None,
+            max_clicks: None,
+        }),
+    )
+    .await
+    .unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    body["data"]["id"].as_str().unwrap().to_string()
+}
+
+async fn migrated_database() -> Arc<SqliteUrlDatabase> {
+    let database = SqliteUrlDatabase::in_memory_with_migrations()
+        .await
+        .expect("Failed to create database");
+    Arc::new(database)
+}
+
+#[tokio::test]
+async fn get_url_finds_a_url_by_its_code() {
+    // Arrange
+    let database = migrated_database().await;
+    database
+        .insert_url("Abc123", "https://www.example.com/code")
+        .await
+        .unwrap();
+
+    // Act
+    let url = database.get_url("Abc123").await;
+
+    // Assert
+    assert_eq!(url.unwrap(), "https://www.example.com/code");
+}
+
+#[tokio::test]
+async fn get_url_finds_a_url_by_its_alias() {
+    // Arrange
+    let database = migrated_database().await;
+    let (upsert, _) = database
+        .insert_url("Abc123", "https://www.example.com/alias")
+        .await
+        .unwrap();
+    database.insert_alias("Alias99", upsert.id).await.unwrap();
+
+    // Act
+    let url = database.get_url("Alias99").await;
+
+    // Assert
+    assert_eq!(url.unwrap(), "https://www.example.com/alias");
+}
+
+#[tokio::test]
+async fn inserting_the_same_url_twice_returns_the_first_code() {
+    // Arrange
+    let database = migrated_database().await;
+    let (first, _) = database
+        .insert_url("Abc123", "https://www.example.com/twice")
+        .await
+        .unwrap();
+
+    // Act
+    let (second, urls) = database
+        .insert_url("Xyz789", "https://www.example.com/twice")
+        .await
+        .unwrap();
+
+    // Assert
+    assert!(first.created);
+    assert!(!second.created);
+    assert_eq!(second.id, first.id);
+    assert_eq!(urls.code, "Abc123");
+    assert!(database.get_url("Xyz789").await.is_err());
+}
+
+#[tokio::test]
+async fn get_id_by_url_finds_the_stored_code_by_hash() {
+    // Arrange
+    let database = migrated_database().await;
+    let (upsert, _) = database
+        .insert_url("Abc123", "https://www.example.com/hash")
+        .await
+        .unwrap();
+
+    // Act
+    let known = database.get_id_by_url("https://www.example.com/hash").await;
+    let unknown = database
+        .get_id_by_url("https://www.example.com/other")
+        .await;
+
+    // Assert
+    let known = known.unwrap();
+    assert_eq!(known.id, upsert.id);
+    assert_eq!(known.code, "Abc123");
+    assert!(matches!(unknown, Err(DatabaseError::NotFound)));
+}
+
+#[tokio::test]
+async fn shortening_the_same_url_twice_returns_the_same_code() {
+    // Arrange
+    let database = migrated_database().await;
+    let state = build_test_state(&test_configuration(|_| {}), database).await;
+
+    // Act
+    let first = shorten(&state, "https://www.example.com/again").await;
+    let second = shorten(&state, "https://www.example.com/again").await;
+
+    // Assert
+    assert_eq!(first, second);
+}
+
+#[tokio::test]
+async fn a_shortened_alias_is_in_the_bloom_filter_and_redirects() {
+    // Arrange
+    let database = migrated_database().await;
+    let state = build_test_state(&test_configuration(|_| {}), database).await;
+    post_shorten(
+        State(state.clone()),
+        TypedHeader(Host::from(Authority::from_static("localhost:8000"))),
+        None,
+        Query(ShortenParams {
+            alias: Some("Abc123".to_string()),
+            tags: None,
+            redirect: None,
+        }),
+        ShortenInput::Json(ShortenRequest {
+            url: "https://www.example.com/redirect".to_string(),
+            alias: None,
+            ttl_seconds: None,
+            password: None,
+            ab_targets: None,
+            mobile_url: None,
+            max_clicks: None,
+        }),
+    )
+    .await
+    .unwrap();
+
+    // Act
+    let response = get_redirect(
+        State(state.clone()),
+        Extension(RealIp(IpAddr::V4(Ipv4Addr::new(203, 0, 113, 7)))),
+        Path("Abc123".to_string()),
+        Query(RedirectParams { redirect: None }),
+        Query(RedirectQueryParams::default()),
+        HeaderMap::new(),
+    )
+    .await
+    .into_response();
+
+    // Assert
+    assert!(state.blooms().s2l.may_contain("Abc123"));
+    assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+    assert_eq!(
+        response.headers()[header::LOCATION],
+        "https://www.example.com/redirect"
+    );
+}
@@ -0,0 +1,172 @@
+// tests/api/tags.rs
+
+// integration tests for URL tagging and tag-filtered listing
+
+// dependencies
+use crate::helpers::{TestApp, assert_json_ok, spawn_app_with};
+use axum::http::StatusCode;
+use serde_json::{Value, json};
+
+// tagging flows need more requests than the test rate limit allows
+async fn spawn_app() -> TestApp {
+    spawn_app_with(|c| c.rate_limiting.enabled = false).await
+}
+
+async fn shorten(app: &TestApp, path: &str, url: &str) -> String {
+    let response = app.post_api_with_key(path, url).await;
+    let body = assert_json_ok(response).await;
+    body["data"]["id"].as_str().expect("missing id").to_string()
+}
+
+fn codes(body: &Value) -> Vec<&str> {
+    body["data"]
+        .as_array()
+        .expect("data should be an array")
+        .iter()
+        .map(|r| r["code"].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn tags_can_be_added_listed_and_removed() {
+    // Arrange
+    let app = spawn_app().await;
+    let tagged = shorten(&app, "/api/shorten", "https://www.example.com/tagged").await;
+    let _other = shorten(&app, "/api/shorten", "https://www.example.com/other").await;
+
+    // Act: tag one URL
+    let response = app
+        .post_json_with_key(
+            &format!("/api/urls/{}/tags", tagged),
+            &json!({"tag": "campaign-q3"}),
+        )
+        .await;
+    assert_json_ok(response).await;
+
+    // Assert: filtered listing only contains the tagged URL
+    let body = assert_json_ok(app.get_api_with_key("/api/urls?tag=campaign-q3").await).await;
+    assert_eq!(codes(&body), vec![tagged.as_str()]);
+    assert_eq!(
+        body["data"][0]["url"].as_str(),
+        Some("https://www.example.com/tagged")
+    );
+
+    // Act: remove the tag
+    let response = app
+        .delete_api_with_key(&format!("/api/urls/{}/tags/campaign-q3", tagged))
+        .await;
+    assert_json_ok(response).await;
+
+    // Assert: the tag no longer matches anything
+    let body = assert_json_ok(app.get_api_with_key("/api/urls?tag=campaign-q3").await).await;
+    assert!(codes(&body).is_empty());
+}
+
+#[tokio::test]
+async fn adding_a_tag_twice_is_idempotent() {
+    let app = spawn_app().await;
+    let code = shorten(&app, "/api/shorten", "https://www.example.com/twice").await;
+    let path = format!("/api/urls/{}/tags", code);
+
+    for _ in 0..2 {
+        let response = app.post_json_with_key(&path, &json!({"tag": "dup"})).await;
+        assert_json_ok(response).await;
+    }
+
+    let body = assert_json_ok(app.get_api_with_key("/api/urls?tag=dup").await).await;
+    assert_eq!(codes(&body), vec![code.as_str()]);
+}
+
+#[tokio::test]
+async fn tags_can_be_attached_at_creation_time() {
+    let app = spawn_app().await;
+
+    let code = shorten(
+        &app,
+        "/api/shorten?tags=client-a,project-x",
+        "https://www.example.com/created-tagged",
+    )
+    .await;
+
+    for tag in ["client-a", "project-x"] {
+        let body = assert_json_ok(
+            app.get_api_with_key(&format!("/api/urls?tag={}", tag))
+                .await,
+        )
+        .await;
+        assert_eq!(codes(&body), vec![code.as_str()]);
+    }
+}
+
+#[tokio::test]
+async fn tagging_an_alias_tags_its_target() {
+    let app = spawn_app().await;
+    let code = shorten(&app, "/api/shorten", "https://www.example.com/aliased").await;
+    let _alias = shorten(
+        &app,
+        "/api/shorten?alias=myalias",
+        "https://www.example.com/aliased",
+    )
+    .await;
+
+    let response = app
+        .post_json_with_key("/api/urls/myalias/tags", &json!({"tag": "via-alias"}))
+        .await;
+    assert_json_ok(response).await;
+
+    let body = assert_json_ok(app.get_api_with_key("/api/urls?tag=via-alias").await).await;
+    assert_eq!(codes(&body), vec![code.as_str()]);
+}
+
+#[tokio::test]
+async fn invalid_tags_are_rejected() {
+    let app = spawn_app().await;
+    let code = shorten(&app, "/api/shorten", "https://www.example.com/invalid-tag").await;
+    let path = format!("/api/urls/{}/tags", code);
+
+    for tag in ["", "has space", "under_score", &"x".repeat(33)] {
+        let response = app.post_json_with_key(&path, &json!({"tag": tag})).await;
+        assert_eq!(
+            response.status(),
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "{tag:?}"
+        );
+    }
+
+    let response = app
+        .post_api_with_key("/api/shorten?tags=bad!tag", "https://www.example.com/x")
+        .await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+    let response = app.get_api_with_key("/api/urls?tag=bad!tag").await;
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn tagging_an_unknown_code_returns_404() {
+    let app = spawn_app().await;
+
+    let response = app
+        .post_json_with_key("/api/urls/nope404/tags", &json!({"tag": "ghost"}))
+        .await;
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn listing_without_tag_returns_all_urls_and_requires_api_key() {
+    let app = spawn_app().await;
+    let first = shorten(&app, "/api/shorten", "https://www.example.com/list-1").await;
+    let second = shorten(&app, "/api/shorten", "https://www.example.com/list-2").await;
+
+    let body = assert_json_ok(app.get_api_with_key("/api/urls").await).await;
+    let listed = codes(&body);
+    assert!(listed.contains(&first.as_str()));
+    assert!(listed.contains(&second.as_str()));
+
+    let body = assert_json_ok(app.get_api_with_key("/api/urls?limit=1").await).await;
+    assert_eq!(codes(&body).len(), 1);
+
+    let response = app.get_api("/api/urls").await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
@@ -0,0 +1,383 @@
+// tests/api/timeout.rs
+
+// dependencies
+use crate::helpers::{TestApp, assert_json_ok, spawn_app_with, spawn_app_with_database};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use reqwest::header::ACCEPT;
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use url_shortener_ztm_lib::configuration::{Environment, Settings};
+use url_shortener_ztm_lib::database::{DatabaseError, UrlDatabase};
+use url_shortener_ztm_lib::models::{
+    AbTarget, BatchInsertResult, ClickBucket, ClickRecord, ClickSummary, CountryCount,
+    DatabaseStats, ExpiringUrl, ExportRecord, MaintenanceStep, ReferrerCount, UpsertResult,
+    UrlMetadata, UrlRecord, Urls,
+};
+use uuid::Uuid;
+
+// Database wrapper whose health check stalls, standing in for a hung database
+struct SlowDatabase {
+    inner: Arc<dyn UrlDatabase>,
+    delay: Duration,
+}
+
+#[async_trait]
+impl UrlDatabase for SlowDatabase {
+    async fn insert_url(
+        &self,
+        code: &str,
+        url: &str,
+    ) -> Result<(UpsertResult, Urls), DatabaseError> {
+        self.inner.insert_url(code, url).await
+    }
+
+    async fn insert_url_with_expiry(
+        &self,
+        code: &str,
+        url: &str,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<(UpsertResult, Urls), DatabaseError> {
+        self.inner
+            .insert_url_with_expiry(code, url, expires_at)
+            .await
+    }
+
+    async fn insert_urls_batch(
+        &self,
+        items: &[(&str, &str)],
+    ) -> Result<Vec<BatchInsertResult>, DatabaseError> {
+        self.inner.insert_urls_batch(items).await
+    }
+
+    async fn insert_alias(&self, alias_code: &str, code_id: i64) -> Result<(), DatabaseError> {
+        self.inner.insert_alias(alias_code, code_id).await
+    }
+
+    async fn get_id_by_url(&self, url: &str) -> Result<Urls, DatabaseError> {
+        self.inner.get_id_by_url(url).await
+    }
+
+    async fn get_url(&self, id: &str) -> Result<String, DatabaseError> {
+        self.inner.get_url(id).await
+    }
+
+    async fn get_url_with_metadata(&self, code: &str) -> Result<UrlMetadata, DatabaseError> {
+        self.inner.get_url_with_metadata(code).await
+    }
+
+    async fn delete_url(&self, code: &str) -> Result<(), DatabaseError> {
+        self.inner.delete_url(code).await
+    }
+
+    async fn update_url(&self, code: &str, new_url: &str) -> Result<(), DatabaseError> {
+        self.inner.update_url(code, new_url).await
+    }
+
+    async fn record_preview_view(&self, code: &str) -> Result<(), DatabaseError> {
+        self.inner.record_preview_view(code).await
+    }
+
+    async fn record_click(
+        &self,
+        code: &str,
+        clicked_at: DateTime<Utc>,
+        ip: Option<&str>,
+        referrer: Option<&str>,
+        variant: Option<u8>,
+        device_type: Option<&str>,
+        country_code: Option<&str>,
+        user_agent: Option<&str>,
+    ) -> Result<(), DatabaseError> {
+        self.inner
+            .record_click(
+                code,
+                clicked_at,
+                ip,
+                referrer,
+                variant,
+                device_type,
+                country_code,
+                user_agent,
+            )
+            .await
+    }
+
+    async fn get_recent_clicks(
+        &self,
+        code: &str,
+        limit: u8,
+    ) -> Result<Vec<ClickRecord>, DatabaseError> {
+        self.inner.get_recent_clicks(code, limit).await
+    }
+
+    async fn get_clicks_over_time(
+        &self,
+        code: &str,
+        bucket: &str,
+    ) -> Result<Vec<ClickBucket>, DatabaseError> {
+        self.inner.get_clicks_over_time(code, bucket).await
+    }
+
+    async fn get_top_referrers(
+        &self,
+        code: &str,
+        limit: u8,
+    ) -> Result<Vec<ReferrerCount>, DatabaseError> {
+        self.inner.get_top_referrers(code, limit).await
+    }
+
+    async fn get_clicks_by_country(&self, code: &str) -> Result<Vec<CountryCount>, DatabaseError> {
+        self.inner.get_clicks_by_country(code).await
+    }
+
+    async fn get_click_summary(
+        &self,
+        code: &str,
+        top_referrers: u8,
+    ) -> Result<ClickSummary, DatabaseError> {
+        self.inner.get_click_summary(code, top_referrers).await
+    }
+
+    async fn set_redirect_type(
+        &self,
+        code: &str,
+        redirect_type: &str,
+    ) -> Result<(), DatabaseError> {
+        self.inner.set_redirect_type(code, redirect_type).await
+    }
+
+    async fn list_short_codes(
+        &self,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<String>, DatabaseError> {
+        self.inner.list_short_codes(offset, limit).await
+    }
+
+    async fn set_url_owner(&self, code: &str, owner_id: Uuid) -> Result<(), DatabaseError> {
+        self.inner.set_url_owner(code, owner_id).await
+    }
+
+    async fn set_url_password(
+        &self,
+        code: &str,
+        password_hash: &[u8],
+    ) -> Result<(), DatabaseError> {
+        self.inner.set_url_password(code, password_hash).await
+    }
+
+    async fn set_ab_targets(&self, code: &str, targets: &[AbTarget]) -> Result<(), DatabaseError> {
+        self.inner.set_ab_targets(code, targets).await
+    }
+
+    async fn get_ab_targets(&self, code: &str) -> Result<Vec<AbTarget>, DatabaseError> {
+        self.inner.get_ab_targets(code).await
+    }
+
+    async fn set_mobile_url(&self, code: &str, mobile_url: &str) -> Result<(), DatabaseError> {
+        self.inner.set_mobile_url(code, mobile_url).await
+    }
+
+    async fn set_max_clicks(&self, code: &str, max_clicks: u64) -> Result<(), DatabaseError> {
+        self.inner.set_max_clicks(code, max_clicks).await
+    }
+
+    async fn get_click_count(&self, code: &str) -> Result<u64, DatabaseError> {
+        self.inner.get_click_count(code).await
+    }
+
+    async fn deactivate_url(&self, code: &str) -> Result<(), DatabaseError> {
+        self.inner.deactivate_url(code).await
+    }
+
+    async fn list_expiring_urls(
+        &self,
+        from: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<Vec<ExpiringUrl>, DatabaseError> {
+        self.inner.list_expiring_urls(from, until).await
+    }
+
+    async fn mark_expiry_reminder_sent(
+        &self,
+        code: &str,
+        sent_at: DateTime<Utc>,
+    ) -> Result<(), DatabaseError> {
+        self.inner.mark_expiry_reminder_sent(code, sent_at).await
+    }
+
+    async fn list_urls(
+        &self,
+        offset: u64,
+        limit: u64,
+        owner_id: Option<Uuid>,
+    ) -> Result<Vec<UrlRecord>, DatabaseError> {
+        self.inner.list_urls(offset, limit, owner_id).await
+    }
+
+    async fn count_urls_by_owner(&self, owner_id: Uuid) -> Result<u64, DatabaseError> {
+        self.inner.count_urls_by_owner(owner_id).await
+    }
+
+    async fn sum_clicks_by_owner(&self, owner_id: Uuid) -> Result<u64, DatabaseError> {
+        self.inner.sum_clicks_by_owner(owner_id).await
+    }
+
+    async fn export_urls(
+        &self,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<ExportRecord>, DatabaseError> {
+        self.inner.export_urls(offset, limit).await
+    }
+
+    async fn add_tag(&self, code: &str, tag: &str) -> Result<(), DatabaseError> {
+        self.inner.add_tag(code, tag).await
+    }
+
+    async fn remove_tag(&self, code: &str, tag: &str) -> Result<(), DatabaseError> {
+        self.inner.remove_tag(code, tag).await
+    }
+
+    async fn search_urls(
+        &self,
+        query: &str,
+        offset: u64,
+        limit: u64,
+    ) -> Result<Vec<UrlRecord>, DatabaseError> {
+        self.inner.search_urls(query, offset, limit).await
+    }
+
+    async fn get_urls_by_tag(
+        &self,
+        tag: &str,
+        offset: u64,
+        limit: u64,
+        owner_id: Option<Uuid>,
+    ) -> Result<Vec<UrlRecord>, DatabaseError> {
+        self.inner
+            .get_urls_by_tag(tag, offset, limit, owner_id)
+            .await
+    }
+
+    async fn load_bloom_snapshot(&self, name: &str) -> Result<Option<Vec<u8>>, DatabaseError> {
+        self.inner.load_bloom_snapshot(name).await
+    }
+
+    async fn save_bloom_snapshot(&self, name: &str, data: &[u8]) -> Result<(), DatabaseError> {
+        self.inner.save_bloom_snapshot(name, data).await
+    }
+
+    async fn health_check(&self) -> Result<(), DatabaseError> {
+        tokio::time::sleep(self.delay).await;
+        self.inner.health_check().await
+    }
+
+    async fn get_stats(&self) -> Result<DatabaseStats, DatabaseError> {
+        self.inner.get_stats().await
+    }
+
+    async fn pending_migrations(&self) -> Result<usize, DatabaseError> {
+        self.inner.pending_migrations().await
+    }
+
+    async fn run_maintenance(&self) -> Vec<MaintenanceStep> {
+        self.inner.run_maintenance().await
+    }
+}
+
+#[tokio::test]
+async fn slow_request_returns_504_once_timeout_elapses() {
+    // Arrange: a 1s timeout in front of a database that stalls for 5s
+    let app = spawn_app_with_database(
+        |c| c.application.request_timeout_ms = 1000,
+        |inner| {
+            Arc::new(SlowDatabase {
+                inner,
+                delay: Duration::from_secs(5),
+            })
+        },
+    )
+    .await;
+
+    // Act
+    let started = Instant::now();
+    let response = app.get_api("/api/health_check").await;
+    let elapsed = started.elapsed();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+    assert!(
+        elapsed < Duration::from_secs(2),
+        "timeout took {:?}",
+        elapsed
+    );
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["success"], false);
+    assert_eq!(body["status"], 504);
+    assert_eq!(body["message"], "Request timed out");
+}
+
+// A 1s timeout in front of a database whose health check stalls for 5s
+async fn spawn_timing_out_app(configure: impl FnOnce(&mut Settings)) -> TestApp {
+    spawn_app_with_database(
+        |c| {
+            c.application.request_timeout_ms = 1000;
+            configure(c);
+        },
+        |inner| {
+            Arc::new(SlowDatabase {
+                inner,
+                delay: Duration::from_secs(5),
+            })
+        },
+    )
+    .await
+}
+
+async fn get_as_browser(app: &TestApp, path: &str) -> reqwest::Response {
+    app.client
+        .get(app.url(path))
+        .header(ACCEPT, "text/html,*/*;q=0.8")
+        .send()
+        .await
+        .expect("Failed to execute request.")
+}
+
+#[tokio::test]
+async fn timed_out_request_renders_error_page_for_browsers() {
+    let app = spawn_timing_out_app(|_| {}).await;
+
+    let response = get_as_browser(&app, "/api/health_check").await;
+
+    assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+    let body = response.text().await.unwrap();
+    assert!(body.contains("<html"));
+    assert!(body.contains("504"));
+    assert!(body.contains("Request timed out"));
+}
+
+#[tokio::test]
+async fn error_page_hides_the_error_message_in_production() {
+    let app = spawn_timing_out_app(|c| c.environment = Environment::Production).await;
+
+    let response = get_as_browser(&app, "/api/health_check").await;
+
+    assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+    let body = response.text().await.unwrap();
+    assert!(body.contains("<html"));
+    assert!(!body.contains("Request timed out"));
+    assert!(body.contains("Something went wrong on our end"));
+}
+
+#[tokio::test]
+async fn fast_request_completes_before_timeout() {
+    let app = spawn_app_with(|c| c.application.request_timeout_ms = 1000).await;
+
+    let response = app.get_api("/api/health_check").await;
+
+    assert_json_ok(response).await;
+}
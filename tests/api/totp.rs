@@ -0,0 +1,186 @@
+// tests/api/totp.rs
+
+// enrolling in TOTP and signing in with a second factor; the auth routes are
+// only mounted on Postgres, so AuthService is called directly with in-memory repos
+
+// dependencies
+use crate::mock_repos::{MockUserRepo, auth_service_with_user};
+use secrecy::SecretString;
+use std::sync::Arc;
+use totp_rs::Secret;
+use url_shortener_ztm_lib::features::auth::AuthService;
+use url_shortener_ztm_lib::features::auth::dto::{MfaSignInReq, SignInOutcome, SignInReq};
+use url_shortener_ztm_lib::features::auth::totp;
+use url_shortener_ztm_lib::middleware::ClientMeta;
+use uuid::Uuid;
+
+const EMAIL: &str = "someone@example.com";
+const PASSWORD: &str = "Correct-Horse-42";
+
+async fn service_with_user() -> (AuthService, Arc<MockUserRepo>, Uuid) {
+    let t = auth_service_with_user(EMAIL, PASSWORD).await;
+    (t.service, t.users, t.user_id)
+}
+
+// What the user's authenticator app shows right now for `secret`
+fn current_code(secret: &str) -> String {
+    let bytes = Secret::Encoded(secret.to_string()).to_bytes().unwrap();
+    totp::totp(bytes, EMAIL)
+        .unwrap()
+        .generate_current()
+        .unwrap()
+}
+
+// Enrolls and confirms, returning the base32 secret
+async fn enable_totp(service: &AuthService, id: Uuid) -> String {
+    let enrollment = service.totp_enroll(id).await.unwrap();
+    service
+        .totp_confirm(id, &current_code(&enrollment.secret), meta())
+        .await
+        .unwrap();
+    enrollment.secret
+}
+
+async fn sign_in(service: &AuthService) -> SignInOutcome {
+    service
+        .sign_in(
+            SignInReq {
+                email: EMAIL.to_string(),
+                password: SecretString::from(PASSWORD),
+                device_id: None,
+            },
+            meta(),
+        )
+        .await
+        .unwrap()
+}
+
+fn meta() -> ClientMeta {
+    ClientMeta {
+        ip: Some("203.0.113.7".parse().unwrap()),
+        user_agent: None,
+    }
+}
+
+fn mfa_token(outcome: SignInOutcome) -> String {
+    match outcome {
+        SignInOutcome::MfaRequired(challenge) => {
+            assert!(challenge.mfa_required);
+            challenge.mfa_token
+        }
+        SignInOutcome::Authenticated(_) => panic!("sign-in did not ask for a TOTP code"),
+    }
+}
+
+#[tokio::test]
+async fn enrollment_is_pending_until_a_code_is_confirmed() {
+    // Arrange
+    let (service, users, id) = service_with_user().await;
+
+    // Act
+    let enrollment = service.totp_enroll(id).await.unwrap();
+
+    // Assert
+    assert!(enrollment.otpauth_url.starts_with("otpauth://totp/"));
+    assert!(enrollment.otpauth_url.contains(&enrollment.secret));
+    let stored = users.totp(id).expect("secret was not stored");
+    assert!(!stored.enabled);
+    assert!(
+        !stored
+            .secret_encrypted
+            .windows(enrollment.secret.len())
+            .any(|w| w == enrollment.secret.as_bytes()),
+        "the secret is stored encrypted"
+    );
+    assert!(matches!(
+        sign_in(&service).await,
+        SignInOutcome::Authenticated(_)
+    ));
+}
+
+#[tokio::test]
+async fn confirming_with_a_wrong_code_leaves_totp_off() {
+    // Arrange
+    let (service, users, id) = service_with_user().await;
+    service.totp_enroll(id).await.unwrap();
+
+    // Act
+    let result = service.totp_confirm(id, "000000x", meta()).await;
+
+    // Assert
+    assert!(result.is_err());
+    assert!(!users.totp(id).unwrap().enabled);
+}
+
+#[tokio::test]
+async fn sign_in_with_totp_needs_the_current_code() {
+    // Arrange
+    let (service, _, id) = service_with_user().await;
+    let secret = enable_totp(&service, id).await;
+    let token = mfa_token(sign_in(&service).await);
+
+    // Act
+    let wrong = service
+        .sign_in_mfa(
+            MfaSignInReq {
+                mfa_token: token.clone(),
+                code: "000000x".to_string(),
+                device_id: None,
+            },
+            meta(),
+        )
+        .await;
+    let right = service
+        .sign_in_mfa(
+            MfaSignInReq {
+                mfa_token: token,
+                code: current_code(&secret),
+                device_id: None,
+            },
+            meta(),
+        )
+        .await;
+
+    // Assert
+    assert!(wrong.is_err());
+    let bundle = right.unwrap();
+    let claims = service.verify_token(&bundle.access_token).await.unwrap();
+    assert_eq!(claims.sub, id);
+}
+
+#[tokio::test]
+async fn the_mfa_token_is_not_an_access_token() {
+    // Arrange
+    let (service, _, id) = service_with_user().await;
+    enable_totp(&service, id).await;
+
+    // Act
+    let token = mfa_token(sign_in(&service).await);
+
+    // Assert
+    assert!(service.verify_token(&token).await.is_err());
+}
+
+#[tokio::test]
+async fn disabling_totp_needs_the_password() {
+    // Arrange
+    let (service, users, id) = service_with_user().await;
+    enable_totp(&service, id).await;
+
+    // Act
+    let wrong = service
+        .totp_disable(id, &SecretString::from("wrong-password"), meta())
+        .await;
+    let right = service
+        .totp_disable(id, &SecretString::from(PASSWORD), meta())
+        .await;
+
+    // Assert
+    assert!(wrong.is_err());
+    right.unwrap();
+    assert!(users.totp(id).is_none());
+    assert!(matches!(
+        sign_in(&service).await,
+        SignInOutcome::Authenticated(_)
+    ));
+}
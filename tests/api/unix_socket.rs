@@ -0,0 +1,46 @@
+// tests/api/unix_socket.rs
+
+// tests for serving on a Unix domain socket with `application.unix_socket_path`
+
+// dependencies
+use crate::helpers::app_configuration;
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::UnixStream;
+use url_shortener_ztm_lib::startup::Application;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn health_check_answers_over_the_unix_socket() {
+    // Arrange: a socket in a directory that does not exist yet
+    let directory = std::env::temp_dir().join(format!("unix-socket-{}", Uuid::new_v4()));
+    let path: PathBuf = directory.join("run").join("app.sock");
+    let config = app_configuration(|c| {
+        c.application.unix_socket_path = Some(path.to_string_lossy().into_owned());
+        c.database.url = ":memory:".to_string();
+    });
+    let app = Application::build(config)
+        .await
+        .expect("Failed to build the application");
+    tokio::spawn(app.run_until_stopped());
+
+    // Act
+    let mut stream = UnixStream::connect(&path)
+        .await
+        .expect("Failed to connect to the Unix socket");
+    stream
+        .write_all(
+            b"GET /api/health_check HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+        )
+        .await
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+
+    // Assert
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+    let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o660);
+    std::fs::remove_dir_all(&directory).unwrap();
+}
@@ -0,0 +1,142 @@
+// tests/api/update.rs
+
+// integration tests for PATCH /api/urls/{id}
+
+// dependencies
+use crate::helpers::{assert_json_ok, assert_redirect_to, spawn_app, spawn_app_with};
+use axum::http::StatusCode;
+
+#[tokio::test]
+async fn patch_retargets_the_code_and_redirect_follows() {
+    // Arrange - shorten through the API so the code is known to the bloom filter
+    let app = spawn_app().await;
+    let shorten_response = app
+        .post_api_with_key("/api/shorten", "https://www.example.com/broken")
+        .await;
+    let body = assert_json_ok(shorten_response).await;
+    let id = body["data"]["id"]
+        .as_str()
+        .expect("shorten response did not include an id")
+        .to_string();
+
+    // Act
+    let response = app
+        .patch_api_with_key(
+            &format!("/api/urls/{}", id),
+            "https://www.example.com/fixed",
+        )
+        .await;
+
+    // Assert
+    let body = assert_json_ok(response).await;
+    assert_eq!(body["data"]["id"].as_str(), Some(id.as_str()));
+    assert_eq!(
+        body["data"]["original_url"].as_str(),
+        Some("https://www.example.com/fixed")
+    );
+
+    let redirect = app.get_api(&format!("/api/redirect/{}", id)).await;
+    assert_redirect_to(
+        redirect,
+        "https://www.example.com/fixed",
+        StatusCode::PERMANENT_REDIRECT,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn patch_drops_the_cached_target_of_the_code_and_its_aliases() {
+    // Arrange - redirect once so both codes are cached
+    let app = spawn_app_with(|c| c.rate_limiting.enabled = false).await;
+    let code = app.shorten_url("https://www.example.com/old").await;
+    let alias = app
+        .shorten_url_with_alias("https://www.example.com/old", "cachedAlias")
+        .await;
+    for id in [&code, &alias] {
+        assert_eq!(
+            app.get_redirect_location(id).await.as_deref(),
+            Some("https://www.example.com/old")
+        );
+    }
+
+    // Act
+    let response = app
+        .patch_api_with_key(
+            &format!("/api/urls/{}", code),
+            "https://www.example.com/new",
+        )
+        .await;
+
+    // Assert
+    assert_json_ok(response).await;
+    for id in [&code, &alias] {
+        assert_eq!(
+            app.get_redirect_location(id).await.as_deref(),
+            Some("https://www.example.com/new")
+        );
+    }
+}
+
+#[tokio::test]
+async fn patch_with_invalid_url_returns_422() {
+    let app = spawn_app().await;
+    app._database
+        .insert_url("keepIt1", "https://www.example.com/keep")
+        .await
+        .unwrap();
+
+    let response = app
+        .patch_api_with_key("/api/urls/keepIt1", "not a url")
+        .await;
+
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(
+        app._database.get_url("keepIt1").await.unwrap(),
+        "https://www.example.com/keep"
+    );
+}
+
+#[tokio::test]
+async fn patch_unknown_code_returns_404() {
+    let app = spawn_app().await;
+
+    let response = app
+        .patch_api_with_key("/api/urls/nothere", "https://www.example.com/")
+        .await;
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn patch_without_api_key_returns_401() {
+    let app = spawn_app().await;
+
+    let response = app
+        .client
+        .patch(app.api("/api/urls/anycode"))
+        .body("https://www.example.com/")
+        .send()
+        .await
+        .expect("Failed to execute PATCH request");
+
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn patch_to_an_already_shortened_url_returns_409() {
+    let app = spawn_app().await;
+    app._database
+        .insert_url("first12", "https://www.example.com/one")
+        .await
+        .unwrap();
+    app._database
+        .insert_url("second1", "https://www.example.com/two")
+        .await
+        .unwrap();
+
+    let response = app
+        .patch_api_with_key("/api/urls/second1", "https://www.example.com/one")
+        .await;
+
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+}
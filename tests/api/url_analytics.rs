@@ -0,0 +1,294 @@
+// tests/api/url_analytics.rs
+
+// integration tests for the per-URL analytics page (GET /admin/urls/{id}/analytics),
+// the click statistics of GET /api/urls/{id}/stats and the click history of
+// GET /api/urls/{id}/clicks
+
+// dependencies
+use crate::helpers::{TestApp, assert_json_ok, spawn_app};
+use axum::http::StatusCode;
+use chrono::{DateTime, TimeZone, Utc};
+use serde_json::Value;
+
+fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, 10, 23, hour, minute, 0).unwrap()
+}
+
+// Five clicks: three in the 10:00 hour, two in the 12:00 hour
+async fn seed_clicks(app: &TestApp) {
+    app._database
+        .insert_url("stat001", "https://www.example.com/campaign")
+        .await
+        .unwrap();
+    for (time, ip, referrer) in [
+        (at(10, 5), "203.0.113.1", Some("https://news.example.org/")),
+        (at(10, 20), "203.0.113.2", Some("https://news.example.org/")),
+        (at(10, 59), "203.0.113.1", None),
+        (
+            at(12, 0),
+            "203.0.113.3",
+            Some("https://social.example.net/"),
+        ),
+        (at(12, 30), "203.0.113.1", Some("https://news.example.org/")),
+    ] {
+        app._database
+            .record_click("stat001", time, Some(ip), referrer, None, None, None, None)
+            .await
+            .unwrap();
+    }
+}
+
+// The chart data is embedded as JSON for admin.js
+fn embedded_buckets(html: &str) -> Value {
+    let start = html.find("<script id=\"click-buckets\" type=\"application/json\">");
+    let json = &html[start.expect("click buckets not embedded")..];
+    let json = &json[json.find('>').unwrap() + 1..json.find("</script>").unwrap()];
+    serde_json::from_str(json).expect("click buckets are not valid JSON")
+}
+
+fn counts(buckets: &Value) -> Vec<(String, u64)> {
+    buckets
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|b| {
+            let timestamp: DateTime<Utc> = b["timestamp"].as_str().unwrap().parse().unwrap();
+            (timestamp.to_rfc3339(), b["count"].as_u64().unwrap())
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn analytics_page_buckets_clicks_per_hour() {
+    // Arrange
+    let app = spawn_app().await;
+    seed_clicks(&app).await;
+
+    // Act
+    let response = app.get_with_key("/admin/urls/stat001/analytics").await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let html = response.text().await.unwrap();
+    assert_eq!(
+        counts(&embedded_buckets(&html)),
+        vec![(at(10, 0).to_rfc3339(), 3), (at(12, 0).to_rfc3339(), 2)]
+    );
+}
+
+#[tokio::test]
+async fn analytics_page_buckets_clicks_per_day() {
+    let app = spawn_app().await;
+    seed_clicks(&app).await;
+
+    let html = app
+        .get_with_key("/admin/urls/stat001/analytics?bucket=day")
+        .await
+        .text()
+        .await
+        .unwrap();
+
+    assert_eq!(
+        counts(&embedded_buckets(&html)),
+        vec![(at(0, 0).to_rfc3339(), 5)]
+    );
+}
+
+#[tokio::test]
+async fn analytics_summary_counts_unique_ips_and_referrers() {
+    let app = spawn_app().await;
+    seed_clicks(&app).await;
+
+    let summary = app
+        ._database
+        .get_click_summary("stat001", 10)
+        .await
+        .unwrap();
+
+    assert_eq!(summary.total_clicks, 5);
+    assert_eq!(summary.unique_ips, 3);
+    let referrers: Vec<(&str, u64)> = summary
+        .top_referrers
+        .iter()
+        .map(|r| (r.referrer_origin.as_str(), r.count))
+        .collect();
+    assert_eq!(
+        referrers,
+        vec![
+            ("https://news.example.org", 3),
+            ("https://social.example.net", 1)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn redirects_are_recorded_as_clicks() {
+    let app = spawn_app().await;
+    // Through the API, so the code is added to the Bloom filter
+    let body = assert_json_ok(
+        app.post_api_with_key("/api/shorten", "https://www.example.com/")
+            .await,
+    )
+    .await;
+    let code = body["data"]["id"].as_str().unwrap();
+
+    let response = app
+        .client
+        .get(app.url(&format!("/{code}")))
+        .header("referer", "https://news.example.org/")
+        .send()
+        .await
+        .expect("Failed to execute GET request");
+
+    assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+    let summary = app._database.get_click_summary(code, 10).await.unwrap();
+    assert_eq!(summary.total_clicks, 1);
+    assert_eq!(summary.unique_ips, 1);
+    assert_eq!(
+        summary.top_referrers[0].referrer_origin,
+        "https://news.example.org"
+    );
+}
+
+#[tokio::test]
+async fn analytics_page_rejects_unknown_codes_and_buckets() {
+    let app = spawn_app().await;
+    seed_clicks(&app).await;
+
+    let unknown = app.get_with_key("/admin/urls/missing1/analytics").await;
+    let bad_bucket = app
+        .get_with_key("/admin/urls/stat001/analytics?bucket=week")
+        .await;
+
+    assert_eq!(unknown.status(), StatusCode::NOT_FOUND);
+    assert_eq!(bad_bucket.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn stats_break_down_clicks_by_referrer_origin() {
+    let app = spawn_app().await;
+    let body = assert_json_ok(
+        app.post_api_with_key("/api/shorten", "https://www.example.com/")
+            .await,
+    )
+    .await;
+    let code = body["data"]["id"].as_str().unwrap();
+
+    for referer in [
+        "https://news.example.org/story?id=1",
+        "https://social.example.net/feed",
+        "https://news.example.org/frontpage",
+    ] {
+        let response = app
+            .client
+            .get(app.url(&format!("/{code}")))
+            .header("referer", referer)
+            .send()
+            .await
+            .expect("Failed to execute GET request");
+        assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+    }
+
+    let stats = assert_json_ok(
+        app.get_api_with_key(&format!("/api/urls/{code}/stats"))
+            .await,
+    )
+    .await;
+    assert_eq!(stats["data"]["total_clicks"], 3);
+    assert_eq!(
+        stats["data"]["top_referrers"],
+        serde_json::json!([
+            { "referrer_origin": "https://news.example.org", "count": 2 },
+            { "referrer_origin": "https://social.example.net", "count": 1 }
+        ])
+    );
+}
+
+// The clicked_at of each click in a click history response
+fn click_times(body: &Value) -> Vec<DateTime<Utc>> {
+    body["data"]
+        .as_array()
+        .expect("click history is not a list")
+        .iter()
+        .map(|click| {
+            click["clicked_at"]
+                .as_str()
+                .and_then(|at| at.parse().ok())
+                .expect("clicked_at should be RFC 3339")
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn click_history_lists_clicks_newest_first() {
+    let app = spawn_app().await;
+    seed_clicks(&app).await;
+
+    let body = assert_json_ok(app.get_api_with_key("/api/urls/stat001/clicks").await).await;
+
+    assert_eq!(
+        click_times(&body),
+        [at(12, 30), at(12, 0), at(10, 59), at(10, 20), at(10, 5)]
+    );
+    let newest = &body["data"][0];
+    assert_eq!(newest["code"], "stat001");
+    assert_eq!(newest["ip"], "203.0.113.1");
+    assert_eq!(newest["referrer_origin"], "https://news.example.org");
+    assert!(body["data"][2]["referrer_origin"].is_null());
+}
+
+#[tokio::test]
+async fn click_history_honours_the_limit() {
+    let app = spawn_app().await;
+    seed_clicks(&app).await;
+
+    let two = assert_json_ok(
+        app.get_api_with_key("/api/urls/stat001/clicks?limit=2")
+            .await,
+    )
+    .await;
+    let capped = assert_json_ok(
+        app.get_api_with_key("/api/urls/stat001/clicks?limit=100000")
+            .await,
+    )
+    .await;
+
+    assert_eq!(click_times(&two), [at(12, 30), at(12, 0)]);
+    assert_eq!(click_times(&capped).len(), 5);
+}
+
+#[tokio::test]
+async fn click_history_records_the_user_agent_of_redirects() {
+    let app = spawn_app().await;
+    let code = app.shorten_url("https://www.example.com/agents").await;
+    let user_agent = "Mozilla/5.0 (iPhone; CPU iPhone OS 17_0 like Mac OS X) Mobile/15E148";
+
+    let response = app
+        .client
+        .get(app.url(&format!("/{code}")))
+        .header("user-agent", user_agent)
+        .send()
+        .await
+        .expect("Failed to execute GET request");
+    assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+
+    let body = assert_json_ok(
+        app.get_api_with_key(&format!("/api/urls/{code}/clicks"))
+            .await,
+    )
+    .await;
+    assert_eq!(body["data"][0]["user_agent"], user_agent);
+    assert_eq!(body["data"][0]["device_type"], "mobile");
+}
+
+#[tokio::test]
+async fn click_history_requires_a_key_and_a_known_code() {
+    let app = spawn_app().await;
+    seed_clicks(&app).await;
+
+    let anonymous = app.get_api("/api/urls/stat001/clicks").await;
+    let unknown = app.get_api_with_key("/api/urls/missing1/clicks").await;
+
+    assert_eq!(anonymous.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(unknown.status(), StatusCode::NOT_FOUND);
+}
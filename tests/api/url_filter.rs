@@ -0,0 +1,84 @@
+// tests/api/url_filter.rs
+
+// application.url_filter allowlist and blocklist of shortenable domains
+
+// dependencies
+use crate::helpers::{TestApp, assert_json_ok, spawn_app_with};
+use serde_json::Value;
+use url_shortener_ztm_lib::configuration::{UrlFilterMode, ValidationMode};
+
+async fn spawn_app_with_filter(mode: UrlFilterMode, domains: &[&str]) -> TestApp {
+    let domains: Vec<String> = domains.iter().map(|d| d.to_string()).collect();
+    spawn_app_with(move |c| {
+        c.application.url_filter.mode = mode;
+        c.application.url_filter.domains = domains;
+        c.url_validation.mode = ValidationMode::Strict;
+    })
+    .await
+}
+
+async fn assert_domain_not_allowed(response: reqwest::Response) {
+    assert_eq!(response.status().as_u16(), 422);
+    let body: Value = response.json().await.expect("invalid JSON");
+    assert_eq!(body["message"].as_str(), Some("Domain not allowed"));
+}
+
+#[tokio::test]
+async fn allowlist_accepts_an_approved_domain() {
+    // Arrange
+    let app = spawn_app_with_filter(UrlFilterMode::Allowlist, &["*.company.com"]).await;
+
+    // Act
+    let response = app
+        .post_api_with_key("/api/shorten", "https://wiki.company.com/handbook")
+        .await;
+
+    // Assert
+    assert_json_ok(response).await;
+}
+
+#[tokio::test]
+async fn allowlist_rejects_an_unapproved_domain() {
+    // Arrange
+    let app = spawn_app_with_filter(UrlFilterMode::Allowlist, &["*.company.com"]).await;
+
+    // Act
+    let response = app
+        .post_api_with_key("/api/shorten", "https://www.example.com/elsewhere")
+        .await;
+
+    // Assert
+    assert_domain_not_allowed(response).await;
+}
+
+#[tokio::test]
+async fn blocklist_rejects_a_blocked_domain() {
+    // Arrange
+    let app = spawn_app_with_filter(UrlFilterMode::Blocklist, &["spam.test", "*.spam.test"]).await;
+
+    // Act
+    let bare = app
+        .post_api_with_key("/api/shorten", "https://spam.test/win")
+        .await;
+    let sub = app
+        .post_api_with_key("/api/shorten", "https://www.spam.test/win")
+        .await;
+
+    // Assert
+    assert_domain_not_allowed(bare).await;
+    assert_domain_not_allowed(sub).await;
+}
+
+#[tokio::test]
+async fn blocklist_accepts_other_domains() {
+    // Arrange
+    let app = spawn_app_with_filter(UrlFilterMode::Blocklist, &["*.spam.test"]).await;
+
+    // Act
+    let response = app
+        .post_api_with_key("/api/shorten", "https://www.example.com/fine")
+        .await;
+
+    // Assert
+    assert_json_ok(response).await;
+}
@@ -0,0 +1,213 @@
+// tests/api/url_ownership.rs
+
+// short links owned by the signed-in user who shortened them; accounts are only
+// backed by Postgres, so the app is served with an AuthService over in-memory repos
+
+// dependencies
+use crate::helpers::{TestApp, assert_json_ok, spawn_app_with_state};
+use crate::mock_repos::{MockAuthRepo, MockUserRepo};
+use reqwest::StatusCode;
+use secrecy::SecretString;
+use std::sync::Arc;
+use url_shortener_ztm_lib::configuration::Argon2Settings;
+use url_shortener_ztm_lib::core::security::jwt::JwtKeys;
+use url_shortener_ztm_lib::features::auth::AuthService;
+use url_shortener_ztm_lib::features::users::repositories::UserRepository;
+use url_shortener_ztm_lib::infrastructure::email::EmailService;
+use url_shortener_ztm_lib::models::UrlRecord;
+use uuid::Uuid;
+
+struct Account {
+    id: Uuid,
+    token: String,
+}
+
+// Serves the app with two signed-up users, Alice and Bob
+async fn app_with_two_users() -> (TestApp, Account, Account) {
+    let users = Arc::new(MockUserRepo::new());
+    let alice = users
+        .create("alice@example.com", b"unused", None)
+        .await
+        .unwrap()
+        .id;
+    let bob = users
+        .create("bob@example.com", b"unused", None)
+        .await
+        .unwrap()
+        .id;
+
+    let jwt = JwtKeys::new(b"test-secret");
+    let mut tokens = Vec::new();
+    for id in [alice, bob] {
+        let token = jwt.sign(id, 0, chrono::Duration::minutes(15));
+        tokens.push(token.unwrap());
+    }
+    let auth_service = Arc::new(AuthService::new(
+        users.clone(),
+        Arc::new(MockAuthRepo::default()),
+        jwt.clone(),
+        chrono::Duration::minutes(15),
+        SecretString::from("test-pepper"),
+        Argon2Settings::default(),
+        EmailService::new("", ""),
+    ));
+    let app = spawn_app_with_state(
+        |c| c.rate_limiting.enabled = false,
+        |builder| builder.jwt(jwt).auth_service(auth_service),
+    )
+    .await;
+
+    let bob_token = tokens.pop().unwrap();
+    let alice_token = tokens.pop().unwrap();
+    (
+        app,
+        Account {
+            id: alice,
+            token: alice_token,
+        },
+        Account {
+            id: bob,
+            token: bob_token,
+        },
+    )
+}
+
+// Shortens `url` through the protected API while signed in as `account`
+async fn shorten_as(app: &TestApp, account: &Account, url: &str) -> String {
+    let response = app
+        .client
+        .post(app.api("/api/shorten"))
+        .header("x-api-key", app.api_key.to_string())
+        .bearer_auth(&account.token)
+        .body(url.to_string())
+        .send()
+        .await
+        .expect("Failed to execute POST request");
+    let body = assert_json_ok(response).await;
+    body["data"]["id"].as_str().unwrap().to_string()
+}
+
+fn codes(body: &serde_json::Value) -> Vec<&str> {
+    body["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["code"].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn a_signed_in_user_lists_only_their_own_urls() {
+    // Arrange
+    let (app, alice, bob) = app_with_two_users().await;
+    let mine = shorten_as(&app, &alice, "https://www.example.com/alice").await;
+    shorten_as(&app, &bob, "https://www.example.com/bob").await;
+    app.shorten_url("https://www.example.com/anonymous").await;
+
+    // Act
+    let response = app
+        .client
+        .get(app.api("/api/urls"))
+        .bearer_auth(&alice.token)
+        .send()
+        .await
+        .expect("Failed to execute GET request");
+
+    // Assert
+    let body = assert_json_ok(response).await;
+    assert_eq!(codes(&body), vec![mine.as_str()]);
+    assert_eq!(body["data"][0]["owner_id"], alice.id.to_string());
+}
+
+#[tokio::test]
+async fn an_api_key_lists_every_url() {
+    // Arrange
+    let (app, alice, bob) = app_with_two_users().await;
+    shorten_as(&app, &alice, "https://www.example.com/alice").await;
+    shorten_as(&app, &bob, "https://www.example.com/bob").await;
+    app.shorten_url("https://www.example.com/anonymous").await;
+
+    // Act
+    let response = app.get_api_with_key("/api/urls").await;
+
+    // Assert
+    let body = assert_json_ok(response).await;
+    assert_eq!(codes(&body).len(), 3);
+    let owners: Vec<&serde_json::Value> = body["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| &r["owner_id"])
+        .collect();
+    assert_eq!(owners[0], &alice.id.to_string());
+    assert_eq!(owners[1], &bob.id.to_string());
+    assert!(owners[2].is_null());
+}
+
+#[tokio::test]
+async fn the_listing_deserializes_into_url_records() {
+    // Arrange
+    let (app, alice, _) = app_with_two_users().await;
+    let mine = shorten_as(&app, &alice, "https://www.example.com/alice").await;
+    app.shorten_url("https://www.example.com/anonymous").await;
+
+    // Act
+    let response = app.get_api_with_key("/api/urls").await;
+
+    // Assert
+    let body = assert_json_ok(response).await;
+    let records: Vec<UrlRecord> =
+        serde_json::from_value(body["data"].clone()).expect("data should be a list of UrlRecord");
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].code, mine);
+    assert_eq!(records[0].url, "https://www.example.com/alice");
+    assert_eq!(records[0].owner_id, Some(alice.id));
+    assert_eq!(records[1].owner_id, None);
+}
+
+#[tokio::test]
+async fn deleting_another_users_url_is_forbidden() {
+    // Arrange
+    let (app, alice, bob) = app_with_two_users().await;
+    let code = shorten_as(&app, &alice, "https://www.example.com/alice").await;
+
+    // Act
+    let by_bob = app
+        .client
+        .delete(app.api(&format!("/api/urls/{}", code)))
+        .bearer_auth(&bob.token)
+        .send()
+        .await
+        .expect("Failed to execute DELETE request");
+    let by_alice = app
+        .client
+        .delete(app.api(&format!("/api/urls/{}", code)))
+        .bearer_auth(&alice.token)
+        .send()
+        .await
+        .expect("Failed to execute DELETE request");
+
+    // Assert
+    assert_eq!(by_bob.status(), StatusCode::FORBIDDEN);
+    assert_eq!(by_alice.status(), StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+async fn listing_without_a_key_or_a_valid_token_is_unauthorized() {
+    // Arrange
+    let (app, _, _) = app_with_two_users().await;
+
+    // Act
+    let anonymous = app.get_api("/api/urls").await;
+    let forged = app
+        .client
+        .get(app.api("/api/urls"))
+        .bearer_auth("not-a-token")
+        .send()
+        .await
+        .expect("Failed to execute GET request");
+
+    // Assert
+    assert_eq!(anonymous.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(forged.status(), StatusCode::UNAUTHORIZED);
+}
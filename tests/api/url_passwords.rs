@@ -0,0 +1,129 @@
+// tests/api/url_passwords.rs
+
+// password-protected short URLs: the challenge page, unlocking with the
+// right and wrong password, and the unlock cookie
+
+// dependencies
+use crate::helpers::{TestApp, assert_json_ok, spawn_app};
+use axum::http::StatusCode;
+use serde_json::json;
+
+const PASSWORD: &str = "correct horse battery";
+
+async fn shorten_with_password(app: &TestApp, url: &str) -> String {
+    let response = app
+        .post_json_with_key("/api/shorten", &json!({ "url": url, "password": PASSWORD }))
+        .await;
+    let body = assert_json_ok(response).await;
+    body["data"]["id"]
+        .as_str()
+        .expect("shorten response did not include an id")
+        .to_string()
+}
+
+async fn unlock(app: &TestApp, id: &str, password: &str) -> reqwest::Response {
+    app.client
+        .post(app.url(&format!("/{}/unlock", id)))
+        .form(&[("password", password)])
+        .send()
+        .await
+        .expect("Failed to execute request.")
+}
+
+#[tokio::test]
+async fn protected_url_without_password_gets_the_challenge() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = shorten_with_password(&app, "https://www.example.com/secret").await;
+
+    // Act
+    let response = app.get(&format!("/{}", id)).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        response
+            .headers()
+            .get("www-authenticate")
+            .and_then(|v| v.to_str().ok()),
+        Some(format!("URL-Password realm=\"{}\"", id).as_str())
+    );
+    assert!(response.headers().get("location").is_none());
+    let body = response.text().await.expect("no body");
+    assert!(body.contains(&format!("/{}/unlock", id)));
+    assert!(!body.contains("https://www.example.com/secret"));
+}
+
+#[tokio::test]
+async fn correct_password_unlocks_the_redirect() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = shorten_with_password(&app, "https://www.example.com/secret").await;
+
+    // Act
+    let unlocked = unlock(&app, &id, PASSWORD).await;
+
+    // Assert
+    assert_eq!(unlocked.status(), StatusCode::SEE_OTHER);
+    assert_eq!(
+        unlocked
+            .headers()
+            .get("location")
+            .and_then(|v| v.to_str().ok()),
+        Some(format!("/{}", id).as_str())
+    );
+    let cookie = unlocked
+        .headers()
+        .get("set-cookie")
+        .and_then(|v| v.to_str().ok())
+        .expect("unlock should set a cookie")
+        .to_string();
+    assert!(cookie.starts_with("unlock_token="), "{}", cookie);
+    assert!(cookie.contains(&format!("Path=/{}", id)), "{}", cookie);
+
+    let token = cookie.split(';').next().unwrap();
+    let response = app
+        .client
+        .get(app.url(&format!("/{}", id)))
+        .header("cookie", token)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+    assert_eq!(
+        response
+            .headers()
+            .get("location")
+            .and_then(|v| v.to_str().ok()),
+        Some("https://www.example.com/secret")
+    );
+}
+
+#[tokio::test]
+async fn wrong_password_is_forbidden() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = shorten_with_password(&app, "https://www.example.com/secret").await;
+
+    // Act
+    let response = unlock(&app, &id, "not the password").await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert!(response.headers().get("set-cookie").is_none());
+}
+
+#[tokio::test]
+async fn inspect_does_not_reveal_a_protected_destination() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = shorten_with_password(&app, "https://www.example.com/secret").await;
+
+    // Act
+    let response = app.get_api(&format!("/api/urls/{}", id)).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let body = response.text().await.expect("no body");
+    assert!(!body.contains("https://www.example.com/secret"));
+}
@@ -0,0 +1,221 @@
+// tests/api/url_preview.rs
+
+// the Open Graph preview behind GET /api/urls/{id}/preview; destinations are
+// served by wiremock behind a host name the test client resolves to it, as
+// previews never fetch a loopback address
+
+// dependencies
+use crate::helpers::{TestApp, assert_json_ok, spawn_app_with, spawn_app_with_state};
+use wiremock::matchers::{header_exists, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const ARTICLE: &str = r#"<!doctype html>
+<html>
+  <head>
+    <title>Fallback title</title>
+    <meta property="og:title" content="Rust &amp; Friends">
+    <meta content='Notes from the meetup' property='og:description' />
+    <meta property="og:image" content="/images/cover.png">
+  </head>
+  <body>Hello</body>
+</html>"#;
+
+const HOST: &str = "preview.test";
+
+async fn spawn_app_previewing(server: &MockServer) -> TestApp {
+    let client = reqwest::Client::builder()
+        .resolve(HOST, *server.address())
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .expect("failed to build the test client");
+    spawn_app_with_state(
+        |c| c.rate_limiting.enabled = false,
+        |builder| builder.http_client(client),
+    )
+    .await
+}
+
+fn page(server: &MockServer, route: &str) -> String {
+    format!("http://{}:{}{}", HOST, server.address().port(), route)
+}
+
+async fn destination(route: &str, body: &str) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(route))
+        .and(header_exists("user-agent"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(body, "text/html"))
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn preview_returns_the_open_graph_tags() {
+    // Arrange
+    let server = destination("/article", ARTICLE).await;
+    let app = spawn_app_previewing(&server).await;
+    let url = page(&server, "/article");
+    let id = app.shorten_url(&url).await;
+
+    // Act
+    let response = app
+        .get_api_with_key(&format!("/api/urls/{}/preview", id))
+        .await;
+
+    // Assert
+    let body = assert_json_ok(response).await;
+    let data = &body["data"];
+    assert_eq!(data["url"].as_str(), Some(url.as_str()));
+    assert_eq!(data["title"].as_str(), Some("Rust & Friends"));
+    assert_eq!(data["description"].as_str(), Some("Notes from the meetup"));
+    assert_eq!(
+        data["image_url"].as_str(),
+        Some(page(&server, "/images/cover.png").as_str())
+    );
+    assert!(data["fetched_at"].as_str().is_some());
+}
+
+#[tokio::test]
+async fn preview_falls_back_to_the_page_title() {
+    // Arrange
+    let server = destination(
+        "/plain",
+        "<html><head><title> Plain page </title></head></html>",
+    )
+    .await;
+    let app = spawn_app_previewing(&server).await;
+    let id = app.shorten_url(&page(&server, "/plain")).await;
+
+    // Act
+    let response = app
+        .get_api_with_key(&format!("/api/urls/{}/preview", id))
+        .await;
+
+    // Assert
+    let body = assert_json_ok(response).await;
+    assert_eq!(body["data"]["title"].as_str(), Some("Plain page"));
+    assert!(body["data"]["description"].is_null());
+    assert!(body["data"]["image_url"].is_null());
+}
+
+#[tokio::test]
+async fn previews_are_cached_per_destination() {
+    // Arrange
+    let server = MockServer::start().await;
+    let app = spawn_app_previewing(&server).await;
+    Mock::given(method("GET"))
+        .and(path("/cached"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(ARTICLE, "text/html"))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let id = app.shorten_url(&page(&server, "/cached")).await;
+
+    // Act
+    for _ in 0..2 {
+        let response = app
+            .get_api_with_key(&format!("/api/urls/{}/preview", id))
+            .await;
+        assert_json_ok(response).await;
+    }
+
+    // Assert
+    server.verify().await;
+}
+
+#[tokio::test]
+async fn an_unreachable_destination_has_an_empty_preview() {
+    // Arrange
+    let server = MockServer::start().await;
+    let app = spawn_app_previewing(&server).await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&server)
+        .await;
+    let id = app.shorten_url(&page(&server, "/broken")).await;
+
+    // Act
+    let response = app
+        .get_api_with_key(&format!("/api/urls/{}/preview", id))
+        .await;
+
+    // Assert
+    let body = assert_json_ok(response).await;
+    assert!(body["data"]["title"].is_null());
+    assert!(body["data"]["description"].is_null());
+    assert!(body["data"]["image_url"].is_null());
+}
+
+#[tokio::test]
+async fn a_loopback_destination_is_not_fetched() {
+    // Arrange: SSRF protection is off in the test configuration
+    let server = destination("/article", ARTICLE).await;
+    let app = spawn_app_previewing(&server).await;
+    let id = app.shorten_url(&format!("{}/article", server.uri())).await;
+
+    // Act
+    let response = app
+        .get_api_with_key(&format!("/api/urls/{}/preview", id))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 422);
+    assert!(server.received_requests().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn a_redirect_to_a_loopback_host_is_not_followed() {
+    // Arrange
+    let server = MockServer::start().await;
+    let app = spawn_app_previewing(&server).await;
+    Mock::given(method("GET"))
+        .and(path("/moved"))
+        .respond_with(ResponseTemplate::new(302).insert_header(
+            "location",
+            format!("http://localhost:{}/internal", server.address().port()).as_str(),
+        ))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/internal"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(ARTICLE, "text/html"))
+        .expect(0)
+        .mount(&server)
+        .await;
+    let id = app.shorten_url(&page(&server, "/moved")).await;
+
+    // Act
+    let response = app
+        .get_api_with_key(&format!("/api/urls/{}/preview", id))
+        .await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 422);
+    server.verify().await;
+}
+
+#[tokio::test]
+async fn preview_requires_an_api_key() {
+    // Arrange
+    let app = spawn_app_with(|c| c.rate_limiting.enabled = false).await;
+    let id = app.shorten_url("https://www.example.com/").await;
+
+    // Act
+    let response = app.get_api(&format!("/api/urls/{}/preview", id)).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn preview_of_an_unknown_code_is_not_found() {
+    // Arrange
+    let app = spawn_app_with(|c| c.rate_limiting.enabled = false).await;
+
+    // Act
+    let response = app.get_api_with_key("/api/urls/nope404/preview").await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 404);
+}
@@ -0,0 +1,72 @@
+// tests/api/url_status.rs
+
+// URL lifecycle status: expired links answer 410 Gone, deleted links 404 and
+// free their destination for a new code
+
+// dependencies
+use crate::helpers::{assert_json_ok, spawn_app, spawn_app_with};
+use axum::http::StatusCode;
+use serde_json::json;
+use std::time::Duration;
+
+#[tokio::test]
+async fn expired_link_is_gone() {
+    // Arrange
+    let app = spawn_app().await;
+    let response = app
+        .post_json_with_key(
+            "/api/shorten",
+            &json!({ "url": "https://www.example.com/flash-sale", "ttl_seconds": 1 }),
+        )
+        .await;
+    let body = assert_json_ok(response).await;
+    let id = body["data"]["id"].as_str().unwrap().to_string();
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+
+    // Act
+    let redirect = app.get(&format!("/{}", id)).await;
+    let qr = app.get_api(&format!("/api/qr/{}", id)).await;
+
+    // Assert
+    assert_eq!(redirect.status(), StatusCode::GONE);
+    assert!(redirect.headers().get("location").is_none());
+    assert_eq!(qr.status(), StatusCode::GONE);
+    let info = assert_json_ok(app.get_api(&format!("/api/urls/{}", id)).await).await;
+    assert_eq!(info["data"]["status"], "expired");
+    assert_eq!(info["data"]["is_active"], false);
+}
+
+#[tokio::test]
+async fn new_link_reports_active_status() {
+    // Arrange
+    let app = spawn_app().await;
+    let id = app.shorten_url("https://www.example.com/status").await;
+
+    // Act
+    let info = assert_json_ok(app.get_api(&format!("/api/urls/{}", id)).await).await;
+
+    // Assert
+    assert_eq!(info["data"]["status"], "active");
+}
+
+#[tokio::test]
+async fn deleted_link_is_not_found_and_its_url_gets_a_new_code() {
+    // Arrange
+    let url = "https://www.example.com/retired";
+    let app = spawn_app_with(|c| c.rate_limiting.enabled = false).await;
+    let id = app.shorten_url(url).await;
+
+    // Act
+    let deleted = app.delete_api_with_key(&format!("/api/urls/{}", id)).await;
+    let redirect = app.get(&format!("/{}", id)).await;
+    let again = app.shorten_url(url).await;
+
+    // Assert
+    assert_eq!(deleted.status(), StatusCode::NO_CONTENT);
+    assert_eq!(redirect.status(), StatusCode::NOT_FOUND);
+    assert_ne!(again, id);
+    assert_eq!(
+        app.get_redirect_location(&again).await.as_deref(),
+        Some(url)
+    );
+}
@@ -0,0 +1,61 @@
+// tests/api/url_validation_mode.rs
+
+// url_validation.mode: strict rejects destinations failing the private address
+// check, warn logs and accepts them, permissive skips the check; every mode
+// still requires an http or https URL
+
+// dependencies
+use crate::helpers::{TestApp, assert_json_ok, spawn_app_with};
+use url_shortener_ztm_lib::configuration::ValidationMode;
+
+const PRIVATE_URL: &str = "http://192.168.1.1/admin";
+
+async fn spawn_app_in(mode: ValidationMode) -> TestApp {
+    spawn_app_with(move |c| {
+        c.application.ssrf_protection.enabled = true;
+        c.url_validation.mode = mode;
+    })
+    .await
+}
+
+#[tokio::test]
+async fn strict_mode_rejects_private_addresses() {
+    // Arrange
+    let app = spawn_app_in(ValidationMode::Strict).await;
+
+    // Act
+    let response = app.post_api_with_key("/api/shorten", PRIVATE_URL).await;
+
+    // Assert
+    assert_eq!(response.status().as_u16(), 422);
+    let body = response.text().await.unwrap();
+    assert!(body.contains("private address"), "{}", body);
+}
+
+#[tokio::test]
+async fn warn_mode_accepts_private_addresses() {
+    // Arrange
+    let app = spawn_app_in(ValidationMode::Warn).await;
+
+    // Act
+    let response = app.post_api_with_key("/api/shorten", PRIVATE_URL).await;
+
+    // Assert
+    assert_json_ok(response).await;
+}
+
+#[tokio::test]
+async fn permissive_mode_accepts_any_url_with_a_valid_scheme() {
+    // Arrange
+    let app = spawn_app_in(ValidationMode::Permissive).await;
+
+    // Act
+    let private = app.post_api_with_key("/api/shorten", PRIVATE_URL).await;
+    let ftp = app
+        .post_api_with_key("/api/shorten", "ftp://192.168.1.1/file")
+        .await;
+
+    // Assert
+    assert_json_ok(private).await;
+    assert_eq!(ftp.status().as_u16(), 422);
+}
@@ -0,0 +1,111 @@
+// tests/api/user_me.rs
+
+// GET /api/v1/user/me: the signed-in user's profile with their URL and click
+// totals; the user routes are only mounted on Postgres, so the controller is
+// called directly with in-memory repos over the served app's database
+
+// dependencies
+use crate::helpers::{TestApp, assert_json_ok, spawn_app_with_state};
+use crate::mock_repos::{MockAuthRepo, MockUserRepo};
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use secrecy::SecretString;
+use std::sync::Arc;
+use url_shortener_ztm_lib::configuration::{Argon2Settings, CacheSettings};
+use url_shortener_ztm_lib::core::extractors::auth_user::AuthenticatedUser;
+use url_shortener_ztm_lib::core::security::jwt::JwtKeys;
+use url_shortener_ztm_lib::features::auth::AuthService;
+use url_shortener_ztm_lib::features::users::UserService;
+use url_shortener_ztm_lib::features::users::controllers::{UserController, get_me};
+use url_shortener_ztm_lib::features::users::repositories::UserRepository;
+use url_shortener_ztm_lib::infrastructure::email::EmailService;
+use url_shortener_ztm_lib::routes::redirect::build_redirect_cache;
+
+// Shortens `url` through the protected API while signed in with `token`
+async fn shorten_as(app: &TestApp, token: &str, url: &str) -> String {
+    let response = app
+        .client
+        .post(app.api("/api/shorten"))
+        .header("x-api-key", app.api_key.to_string())
+        .bearer_auth(token)
+        .body(url.to_string())
+        .send()
+        .await
+        .expect("Failed to execute POST request");
+    let body = assert_json_ok(response).await;
+    body["data"]["id"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn me_returns_the_profile_with_url_and_click_totals() {
+    // Arrange: a freshly signed-up user who shortened 3 URLs, 2 of them clicked
+    let users = Arc::new(MockUserRepo::new());
+    let user_id = users
+        .create("someone@example.com", b"unused", None)
+        .await
+        .unwrap()
+        .id;
+    let jwt = JwtKeys::new(b"test-secret");
+    let token = jwt.sign(user_id, 0, chrono::Duration::minutes(15)).unwrap();
+    let auth_service = Arc::new(AuthService::new(
+        users.clone(),
+        Arc::new(MockAuthRepo::default()),
+        jwt.clone(),
+        chrono::Duration::minutes(15),
+        SecretString::from("test-pepper"),
+        Argon2Settings::default(),
+        EmailService::new("", ""),
+    ));
+    let user_service = Arc::new(UserService::new(users));
+    let app = spawn_app_with_state(
+        |c| c.rate_limiting.enabled = false,
+        |builder| {
+            builder
+                .jwt(jwt)
+                .auth_service(auth_service.clone())
+                .user_service(user_service.clone())
+        },
+    )
+    .await;
+    let mut codes = Vec::new();
+    for page in ["one", "two", "three"] {
+        let url = format!("https://www.example.com/{}", page);
+        codes.push(shorten_as(&app, &token, &url).await);
+    }
+    app.shorten_url("https://www.example.com/anonymous").await;
+    for code in &codes[..2] {
+        app.get(&format!("/{}", code)).await;
+    }
+    let ctrl = UserController {
+        svc: user_service,
+        auth_svc: auth_service,
+        database: app._database.clone(),
+        redirect_cache: build_redirect_cache(&CacheSettings::default()),
+    };
+
+    // Act
+    let response = get_me(
+        State(ctrl),
+        AuthenticatedUser {
+            user_id,
+            token_version: 0,
+        },
+    )
+    .await
+    .into_response();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let me = &body["data"];
+    assert_eq!(me["user"]["id"], user_id.to_string());
+    assert_eq!(me["user"]["email"], "someone@example.com");
+    assert_eq!(me["total_urls"], 3);
+    assert_eq!(me["total_clicks"], 2);
+    assert_eq!(me["email_verified"], false);
+    assert_eq!(me["member_since"], me["user"]["created_at"]);
+}
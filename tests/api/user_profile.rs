@@ -0,0 +1,140 @@
+// tests/api/user_profile.rs
+
+// PATCH /api/v1/user/profile: changing the display name without the password;
+// the user routes are only mounted on Postgres, so the controller is called
+// directly with in-memory repos
+
+// dependencies
+use crate::mock_repos::{MockAuthRepo, MockUserRepo};
+use axum::Json;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use secrecy::SecretString;
+use std::sync::Arc;
+use url_shortener_ztm_lib::configuration::{Argon2Settings, CacheSettings};
+use url_shortener_ztm_lib::core::extractors::auth_user::AuthenticatedUser;
+use url_shortener_ztm_lib::core::security::jwt::JwtKeys;
+use url_shortener_ztm_lib::database::SqliteUrlDatabase;
+use url_shortener_ztm_lib::features::auth::AuthService;
+use url_shortener_ztm_lib::features::users::UserService;
+use url_shortener_ztm_lib::features::users::controllers::{UserController, patch_user_profile};
+use url_shortener_ztm_lib::features::users::dto::UpdateProfileReq;
+use url_shortener_ztm_lib::features::users::repositories::UserRepository;
+use url_shortener_ztm_lib::infrastructure::email::EmailService;
+use url_shortener_ztm_lib::routes::redirect::build_redirect_cache;
+
+async fn controller_with_user() -> (UserController, Arc<MockUserRepo>, AuthenticatedUser) {
+    let users = Arc::new(MockUserRepo::new());
+    let user_id = users
+        .create(
+            "someone@example.com",
+            b"unused",
+            Some("Old Name".to_string()),
+        )
+        .await
+        .unwrap()
+        .id;
+    let auth_svc = AuthService::new(
+        users.clone(),
+        Arc::new(MockAuthRepo::default()),
+        JwtKeys::new(b"test-secret"),
+        chrono::Duration::minutes(15),
+        SecretString::from("test-pepper"),
+        Argon2Settings::default(),
+        EmailService::new("", ""),
+    );
+    let ctrl = UserController {
+        svc: Arc::new(UserService::new(users.clone())),
+        auth_svc: Arc::new(auth_svc),
+        database: Arc::new(
+            SqliteUrlDatabase::in_memory_with_migrations()
+                .await
+                .expect("Failed to create database"),
+        ),
+        redirect_cache: build_redirect_cache(&CacheSettings::default()),
+    };
+    let user = AuthenticatedUser {
+        user_id,
+        token_version: 0,
+    };
+    (ctrl, users, user)
+}
+
+async fn patch(
+    ctrl: &UserController,
+    user: AuthenticatedUser,
+    display_name: &str,
+) -> axum::response::Response {
+    patch_user_profile(
+        State(ctrl.clone()),
+        user,
+        Json(UpdateProfileReq {
+            display_name: display_name.to_string(),
+        }),
+    )
+    .await
+    .into_response()
+}
+
+async fn stored_name(users: &MockUserRepo, user: &AuthenticatedUser) -> Option<String> {
+    users
+        .find_user_by_id(user.user_id)
+        .await
+        .unwrap()
+        .unwrap()
+        .display_name
+}
+
+#[tokio::test]
+async fn a_valid_display_name_is_stored_and_returned() {
+    // Arrange
+    let (ctrl, users, user) = controller_with_user().await;
+
+    // Act
+    let response = patch(&ctrl, user.clone(), "New Name").await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["data"]["display_name"], "New Name");
+    assert_eq!(
+        stored_name(&users, &user).await.as_deref(),
+        Some("New Name")
+    );
+}
+
+#[tokio::test]
+async fn a_display_name_over_30_characters_returns_422() {
+    // Arrange
+    let (ctrl, users, user) = controller_with_user().await;
+
+    // Act
+    let response = patch(&ctrl, user.clone(), &"a".repeat(31)).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(
+        stored_name(&users, &user).await.as_deref(),
+        Some("Old Name")
+    );
+}
+
+#[tokio::test]
+async fn a_display_name_with_zero_width_characters_returns_422() {
+    // Arrange
+    let (ctrl, users, user) = controller_with_user().await;
+
+    // Act
+    let response = patch(&ctrl, user.clone(), "New\u{200B}Name").await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(
+        stored_name(&users, &user).await.as_deref(),
+        Some("Old Name")
+    );
+}
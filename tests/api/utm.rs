@@ -0,0 +1,87 @@
+// tests/api/utm.rs
+
+// UTM parameters appended to shortened URLs from application.utm
+
+// dependencies
+use crate::helpers::{TestApp, assert_json_ok, spawn_app, spawn_app_with};
+use serde_json::json;
+
+async fn spawn_app_with_utm() -> TestApp {
+    spawn_app_with(|c| {
+        c.application.utm.auto_append = true;
+        c.application.utm.source = Some("shortener".to_string());
+        c.application.utm.medium = Some("link".to_string());
+        c.application.utm.campaign = Some("launch".to_string());
+    })
+    .await
+}
+
+async fn shortened_original_url(app: &TestApp, url: &str) -> String {
+    let response = app
+        .post_json_with_key("/api/shorten", &json!({ "url": url }))
+        .await;
+    let body = assert_json_ok(response).await;
+    body["data"]["original_url"]
+        .as_str()
+        .expect("shorten response did not include original_url")
+        .to_string()
+}
+
+#[tokio::test]
+async fn url_without_utm_gets_the_configured_parameters() {
+    // Arrange
+    let app = spawn_app_with_utm().await;
+
+    // Act
+    let original = shortened_original_url(&app, "https://www.example.com/page?id=7").await;
+
+    // Assert
+    assert_eq!(
+        original,
+        "https://www.example.com/page?id=7&utm_source=shortener&utm_medium=link&utm_campaign=launch"
+    );
+}
+
+#[tokio::test]
+async fn url_with_its_own_utm_source_keeps_it() {
+    // Arrange
+    let app = spawn_app_with_utm().await;
+
+    // Act
+    let original =
+        shortened_original_url(&app, "https://www.example.com/page?utm_source=newsletter").await;
+
+    // Assert
+    assert_eq!(
+        original,
+        "https://www.example.com/page?utm_source=newsletter&utm_medium=link&utm_campaign=launch"
+    );
+}
+
+#[tokio::test]
+async fn urls_are_unchanged_when_utm_appending_is_disabled() {
+    // Arrange
+    let app = spawn_app().await;
+
+    // Act
+    let original = shortened_original_url(&app, "https://www.example.com/page").await;
+
+    // Assert
+    assert_eq!(original, "https://www.example.com/page");
+}
+
+#[tokio::test]
+async fn redirect_goes_to_the_url_with_utm_parameters() {
+    // Arrange
+    let app = spawn_app_with_utm().await;
+    let id = app.shorten_url("https://www.example.com/").await;
+
+    // Act
+    let location = app.get_redirect_location(&id).await;
+
+    // Assert
+    assert_eq!(
+        location.as_deref(),
+        Some("https://www.example.com/?utm_source=shortener&utm_medium=link&utm_campaign=launch")
+    );
+}
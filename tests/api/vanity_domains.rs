@@ -0,0 +1,285 @@
+// tests/api/vanity_domains.rs
+
+// vanity domains: a user's short URLs are shared on their own host, which only
+// leads to the codes they own; the user routes are only mounted on Postgres,
+// so the domain is set through the controller over in-memory repos
+
+// dependencies
+use crate::helpers::{TestApp, assert_json_ok, spawn_app_with_state};
+use crate::mock_repos::{MockAuthRepo, MockUserRepo};
+use axum::Json;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use secrecy::SecretString;
+use serde_json::json;
+use std::sync::Arc;
+use url_shortener_ztm_lib::configuration::{Argon2Settings, CacheSettings};
+use url_shortener_ztm_lib::core::extractors::auth_user::AuthenticatedUser;
+use url_shortener_ztm_lib::core::security::jwt::JwtKeys;
+use url_shortener_ztm_lib::features::auth::AuthService;
+use url_shortener_ztm_lib::features::users::UserService;
+use url_shortener_ztm_lib::features::users::controllers::{
+    UserController, get_vanity_domain, set_vanity_domain,
+};
+use url_shortener_ztm_lib::features::users::dto::VanityDomainReq;
+use url_shortener_ztm_lib::features::users::repositories::UserRepository;
+use url_shortener_ztm_lib::infrastructure::email::EmailService;
+use url_shortener_ztm_lib::routes::redirect::build_redirect_cache;
+use uuid::Uuid;
+
+const DOMAIN: &str = "go.mycompany.com";
+
+struct VanityApp {
+    app: TestApp,
+    ctrl: UserController,
+    alice: Uuid,
+    alice_token: String,
+    bob: Uuid,
+}
+
+// Serves the app with two signed-up users, Alice and Bob
+async fn spawn_vanity_app() -> VanityApp {
+    let users = Arc::new(MockUserRepo::new());
+    let alice = users
+        .create("alice@example.com", b"unused", None)
+        .await
+        .unwrap()
+        .id;
+    let bob = users
+        .create("bob@example.com", b"unused", None)
+        .await
+        .unwrap()
+        .id;
+
+    let jwt = JwtKeys::new(b"test-secret");
+    let alice_token = jwt.sign(alice, 0, chrono::Duration::minutes(15)).unwrap();
+    let auth_service = Arc::new(AuthService::new(
+        users.clone(),
+        Arc::new(MockAuthRepo::default()),
+        jwt.clone(),
+        chrono::Duration::minutes(15),
+        SecretString::from("test-pepper"),
+        Argon2Settings::default(),
+        EmailService::new("", ""),
+    ));
+    let user_service = Arc::new(UserService::new(users));
+    let app = spawn_app_with_state(
+        |c| c.rate_limiting.enabled = false,
+        |builder| {
+            builder
+                .jwt(jwt)
+                .auth_service(auth_service.clone())
+                .user_service(user_service.clone())
+        },
+    )
+    .await;
+    let ctrl = UserController {
+        svc: user_service,
+        auth_svc: auth_service,
+        database: app._database.clone(),
+        redirect_cache: build_redirect_cache(&CacheSettings::default()),
+    };
+
+    VanityApp {
+        app,
+        ctrl,
+        alice,
+        alice_token,
+        bob,
+    }
+}
+
+fn signed_in(user_id: Uuid) -> AuthenticatedUser {
+    AuthenticatedUser {
+        user_id,
+        token_version: 0,
+    }
+}
+
+async fn claim(ctrl: &UserController, user_id: Uuid, domain: &str) -> axum::response::Response {
+    set_vanity_domain(
+        State(ctrl.clone()),
+        signed_in(user_id),
+        Json(VanityDomainReq {
+            domain: domain.to_string(),
+        }),
+    )
+    .await
+    .into_response()
+}
+
+// Shortens `url` through the protected API, signed in when `token` is given
+async fn shorten(app: &TestApp, token: Option<&str>, url: &str) -> serde_json::Value {
+    let mut request = app
+        .client
+        .post(app.api("/api/shorten"))
+        .header("x-api-key", app.api_key.to_string())
+        .body(url.to_string());
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    let response = request.send().await.expect("Failed to execute request.");
+    assert_json_ok(response).await
+}
+
+async fn read_json(response: axum::response::Response) -> serde_json::Value {
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+async fn get_on_host(app: &TestApp, host: &str, path: &str) -> reqwest::Response {
+    app.client
+        .get(app.url(path))
+        .header("host", host)
+        .send()
+        .await
+        .expect("Failed to execute request.")
+}
+
+#[tokio::test]
+async fn short_urls_of_a_user_with_a_vanity_domain_use_it() {
+    // Arrange
+    let vanity = spawn_vanity_app().await;
+    claim(&vanity.ctrl, vanity.alice, DOMAIN).await;
+
+    // Act
+    let body = shorten(
+        &vanity.app,
+        Some(&vanity.alice_token),
+        "https://www.example.com/launch",
+    )
+    .await;
+
+    // Assert
+    let id = body["data"]["id"].as_str().unwrap();
+    assert_eq!(
+        body["data"]["shortened_url"],
+        format!("http://{}/{}", DOMAIN, id)
+    );
+}
+
+#[tokio::test]
+async fn the_vanity_domain_is_returned_lowercased() {
+    // Arrange
+    let vanity = spawn_vanity_app().await;
+    let before = get_vanity_domain(State(vanity.ctrl.clone()), signed_in(vanity.alice))
+        .await
+        .into_response();
+
+    // Act
+    let claimed = claim(&vanity.ctrl, vanity.alice, "Go.MyCompany.com").await;
+    let after = get_vanity_domain(State(vanity.ctrl.clone()), signed_in(vanity.alice))
+        .await
+        .into_response();
+
+    // Assert
+    assert_eq!(
+        read_json(before).await["data"]["vanity_domain"],
+        json!(null)
+    );
+    assert_eq!(claimed.status(), StatusCode::OK);
+    assert_eq!(read_json(after).await["data"]["vanity_domain"], DOMAIN);
+}
+
+#[tokio::test]
+async fn redirect_on_the_vanity_domain_leads_to_the_owners_url() {
+    // Arrange
+    let vanity = spawn_vanity_app().await;
+    claim(&vanity.ctrl, vanity.alice, DOMAIN).await;
+    let body = shorten(
+        &vanity.app,
+        Some(&vanity.alice_token),
+        "https://www.example.com/launch",
+    )
+    .await;
+    let id = body["data"]["id"].as_str().unwrap();
+
+    // Act
+    let response = get_on_host(&vanity.app, DOMAIN, &format!("/{}", id)).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+    assert_eq!(
+        response
+            .headers()
+            .get("location")
+            .and_then(|v| v.to_str().ok()),
+        Some("https://www.example.com/launch")
+    );
+}
+
+#[tokio::test]
+async fn codes_of_other_users_are_not_found_on_a_vanity_domain() {
+    // Arrange
+    let vanity = spawn_vanity_app().await;
+    claim(&vanity.ctrl, vanity.alice, DOMAIN).await;
+    let body = shorten(&vanity.app, None, "https://www.example.com/other").await;
+    let id = body["data"]["id"].as_str().unwrap();
+
+    // Act
+    let on_vanity = get_on_host(&vanity.app, DOMAIN, &format!("/{}", id)).await;
+    let elsewhere = vanity.app.get(&format!("/{}", id)).await;
+
+    // Assert
+    assert_eq!(on_vanity.status(), StatusCode::NOT_FOUND);
+    assert_eq!(elsewhere.status(), StatusCode::PERMANENT_REDIRECT);
+}
+
+#[tokio::test]
+async fn claiming_a_domain_takes_effect_on_hosts_already_looked_up() {
+    // Arrange: the unclaimed host is remembered as owned by no one
+    let vanity = spawn_vanity_app().await;
+    let body = shorten(&vanity.app, None, "https://www.example.com/other").await;
+    let id = body["data"]["id"].as_str().unwrap();
+    let before = get_on_host(&vanity.app, DOMAIN, &format!("/{}", id)).await;
+
+    // Act
+    claim(&vanity.ctrl, vanity.alice, DOMAIN).await;
+    let after = get_on_host(&vanity.app, DOMAIN, &format!("/{}", id)).await;
+
+    // Assert
+    assert_eq!(before.status(), StatusCode::PERMANENT_REDIRECT);
+    assert_eq!(after.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn a_domain_claimed_by_another_user_is_a_conflict() {
+    // Arrange
+    let vanity = spawn_vanity_app().await;
+    claim(&vanity.ctrl, vanity.alice, DOMAIN).await;
+
+    // Act
+    let response = claim(&vanity.ctrl, vanity.bob, DOMAIN).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn invalid_hostnames_are_rejected() {
+    // Arrange
+    let vanity = spawn_vanity_app().await;
+
+    for domain in [
+        "localhost",
+        "go.mycompany.com:8080",
+        "-go.mycompany.com",
+        "go..mycompany.com",
+        "go_links.mycompany.com",
+        "10.0.0.1",
+    ] {
+        // Act
+        let response = claim(&vanity.ctrl, vanity.alice, domain).await;
+
+        // Assert
+        assert_eq!(
+            response.status(),
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "{} was accepted",
+            domain
+        );
+    }
+}
@@ -0,0 +1,179 @@
+// tests/api/verification_codes.rs
+
+// the configurable resend cooldown, lifetime and attempt limit of emailed
+// verification codes; the auth routes are only mounted on Postgres, so the
+// service and controller are called directly with in-memory repos
+
+// dependencies
+use crate::mock_repos::{TestAuth, auth_service_with_user};
+use axum::extract::State;
+use std::sync::Arc;
+use url_shortener_ztm_lib::configuration::AuthSettings;
+use url_shortener_ztm_lib::features::auth::controllers::{AuthController, auth_config};
+use url_shortener_ztm_lib::features::auth::repositories::{
+    AuthRepoError, AuthRepository, AuthenticationAction,
+};
+use url_shortener_ztm_lib::features::users::UserService;
+
+const EMAIL: &str = "someone@example.com";
+const PASSWORD: &str = "Correct-Horse-42";
+// well-formed, so it is checked against the stored hash and counts as a guess
+const WRONG_CODE: &str = "AAAAAAAA";
+
+async fn service_with(settings: AuthSettings) -> TestAuth {
+    let t = auth_service_with_user(EMAIL, PASSWORD).await;
+    TestAuth {
+        service: t.service.with_verification(settings),
+        ..t
+    }
+}
+
+// Sends a code; the challenge is stored before the email goes out, so an email
+// the test environment cannot deliver still leaves a code to check
+async fn send_code(t: &TestAuth) -> anyhow::Result<()> {
+    t.service
+        .send_verification_code(
+            t.user_id,
+            EMAIL,
+            None,
+            AuthenticationAction::VerifyEmail,
+            None,
+        )
+        .await
+}
+
+fn is_cooldown(result: &anyhow::Result<()>) -> bool {
+    matches!(
+        result
+            .as_ref()
+            .err()
+            .and_then(|e| e.downcast_ref::<AuthRepoError>()),
+        Some(AuthRepoError::Cooldown(_))
+    )
+}
+
+async fn stored_code_hash(t: &TestAuth) -> Vec<u8> {
+    t.auth
+        .get_auth_challenge(t.user_id, AuthenticationAction::VerifyEmail)
+        .await
+        .unwrap()
+        .expect("no challenge was stored")
+        .code_hash
+}
+
+#[tokio::test]
+async fn a_resend_within_the_cooldown_is_refused() {
+    // Arrange
+    let t = service_with(AuthSettings::default()).await;
+    let _ = send_code(&t).await;
+
+    // Act
+    let resend = send_code(&t).await;
+
+    // Assert
+    assert!(is_cooldown(&resend));
+}
+
+#[tokio::test]
+async fn a_zero_cooldown_allows_an_immediate_resend() {
+    // Arrange
+    let t = service_with(AuthSettings {
+        verification_cooldown_seconds: 0,
+        ..AuthSettings::default()
+    })
+    .await;
+    let _ = send_code(&t).await;
+    let first = stored_code_hash(&t).await;
+
+    // Act
+    let resend = send_code(&t).await;
+
+    // Assert
+    assert!(!is_cooldown(&resend));
+    assert_ne!(stored_code_hash(&t).await, first, "a new code was issued");
+}
+
+#[tokio::test]
+async fn codes_expire_after_the_configured_ttl() {
+    // Arrange
+    let t = service_with(AuthSettings {
+        verification_ttl_hours: 3,
+        ..AuthSettings::default()
+    })
+    .await;
+
+    // Act
+    let _ = send_code(&t).await;
+
+    // Assert
+    let challenge = t
+        .auth
+        .get_auth_challenge(t.user_id, AuthenticationAction::VerifyEmail)
+        .await
+        .unwrap()
+        .unwrap();
+    let minutes = (challenge.expires_at - challenge.created_at).num_minutes();
+    assert!(
+        (179..=180).contains(&minutes),
+        "expires after {} minutes",
+        minutes
+    );
+}
+
+#[tokio::test]
+async fn guessing_past_max_attempts_needs_a_new_code() {
+    // Arrange
+    let t = service_with(AuthSettings {
+        max_verification_attempts: 2,
+        ..AuthSettings::default()
+    })
+    .await;
+    let _ = send_code(&t).await;
+    for _ in 0..2 {
+        let err = t
+            .service
+            .verify_code(t.user_id, AuthenticationAction::VerifyEmail, WRONG_CODE)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("invalid code"), "{}", err);
+    }
+
+    // Act
+    let result = t
+        .service
+        .verify_code(t.user_id, AuthenticationAction::VerifyEmail, WRONG_CODE)
+        .await;
+
+    // Assert
+    let err = result.unwrap_err();
+    assert!(err.to_string().contains("too many attempts"), "{}", err);
+}
+
+#[tokio::test]
+async fn auth_config_reports_the_configured_limits() {
+    // Arrange
+    let t = service_with(AuthSettings {
+        verification_cooldown_seconds: 30,
+        verification_ttl_hours: 2,
+        max_verification_attempts: 3,
+    })
+    .await;
+    let ctrl = AuthController {
+        auth_svc: Arc::new(t.service),
+        user_svc: Arc::new(UserService::new(t.users)),
+    };
+
+    // Act
+    let response = auth_config(State(ctrl)).await;
+
+    // Assert
+    let body = serde_json::to_value(response.data.unwrap()).unwrap();
+    assert_eq!(
+        body,
+        serde_json::json!({
+            "cooldown_seconds": 30,
+            "code_ttl_seconds": 7200,
+            "max_attempts": 3
+        })
+    );
+}
@@ -0,0 +1,96 @@
+// tests/api/webhook_auth.rs
+
+// signature verification of webhook callbacks POSTed to
+// /api/webhooks/receive
+
+// dependencies
+use crate::helpers::{TestApp, spawn_app_with};
+use axum::http::StatusCode;
+use secrecy::SecretString;
+use url_shortener_ztm_lib::infrastructure::webhooks::sign;
+
+const SECRET: &str = "callback-secret";
+const BODY: &str = r#"{"event":"malware","url":"https://bad.example"}"#;
+
+async fn spawn_app_receiving() -> TestApp {
+    spawn_app_with(|c| {
+        c.incoming_webhooks.secret = Some(SecretString::from(SECRET));
+    })
+    .await
+}
+
+async fn post_callback(app: &TestApp, body: &str, signature: Option<&str>) -> reqwest::Response {
+    let mut request = app
+        .client
+        .post(app.url("/api/webhooks/receive"))
+        .header("content-type", "application/json")
+        .body(body.to_string());
+    if let Some(signature) = signature {
+        request = request.header("X-Signature-256", signature);
+    }
+    request.send().await.expect("Failed to execute request")
+}
+
+#[tokio::test]
+async fn correctly_signed_callback_is_accepted() {
+    // Arrange
+    let app = spawn_app_receiving().await;
+    let signature = sign(SECRET.as_bytes(), BODY.as_bytes());
+
+    // Act
+    let response = post_callback(&app, BODY, Some(&signature)).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+async fn wrongly_signed_callback_is_rejected() {
+    // Arrange
+    let app = spawn_app_receiving().await;
+    let signature = sign(b"another-secret", BODY.as_bytes());
+
+    // Act
+    let response = post_callback(&app, BODY, Some(&signature)).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn callback_without_a_signature_is_rejected() {
+    // Arrange
+    let app = spawn_app_receiving().await;
+
+    // Act
+    let response = post_callback(&app, BODY, None).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn empty_callback_with_its_signature_is_accepted() {
+    // Arrange
+    let app = spawn_app_receiving().await;
+    let signature = sign(SECRET.as_bytes(), b"");
+
+    // Act
+    let response = post_callback(&app, "", Some(&signature)).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+async fn callbacks_are_rejected_without_a_configured_secret() {
+    // Arrange
+    let app = spawn_app_with(|_| {}).await;
+    let signature = sign(SECRET.as_bytes(), BODY.as_bytes());
+
+    // Act
+    let response = post_callback(&app, BODY, Some(&signature)).await;
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
@@ -0,0 +1,139 @@
+// tests/api/webhooks.rs
+
+// webhooks fired after a redirect; the receiving endpoint is served by
+// wiremock
+
+// dependencies
+use crate::helpers::{TestApp, spawn_app_with};
+use axum::http::StatusCode;
+use secrecy::SecretString;
+use std::time::Duration;
+use url_shortener_ztm_lib::configuration::WebhookConfig;
+use url_shortener_ztm_lib::infrastructure::webhooks::sign;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+const SECRET: &str = "webhook-secret";
+
+async fn spawn_app_notifying(server: &MockServer, filter_code: Option<&str>) -> TestApp {
+    let webhook = WebhookConfig {
+        url: format!("{}/hook", server.uri()),
+        secret: SecretString::from(SECRET),
+        events: vec!["redirect".to_string()],
+        filter_code: filter_code.map(str::to_string),
+    };
+    spawn_app_with(|c| {
+        c.rate_limiting.enabled = false;
+        c.webhooks = vec![webhook];
+    })
+    .await
+}
+
+// Deliveries run in the background, so wait for `count` of them to arrive
+async fn wait_for_deliveries(server: &MockServer, count: usize) -> Vec<Request> {
+    for _ in 0..50 {
+        let received = server.received_requests().await.unwrap_or_default();
+        if received.len() >= count {
+            return received;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    server.received_requests().await.unwrap_or_default()
+}
+
+#[tokio::test]
+async fn redirect_fires_a_signed_webhook() {
+    // Arrange
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/hook"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+    let app = spawn_app_notifying(&server, None).await;
+    let code = app.shorten_url("https://www.example.com/hooked").await;
+
+    // Act
+    let response = app
+        .client
+        .get(app.url(&format!("/{}", code)))
+        .header("user-agent", "webhook-test")
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+    let received = wait_for_deliveries(&server, 1).await;
+    assert_eq!(received.len(), 1);
+    let delivery = &received[0];
+    let signature = delivery
+        .headers
+        .get("x-signature-256")
+        .and_then(|v| v.to_str().ok())
+        .expect("the delivery should be signed");
+    assert_eq!(signature, sign(SECRET.as_bytes(), &delivery.body));
+
+    let payload: serde_json::Value =
+        serde_json::from_slice(&delivery.body).expect("Failed to parse JSON");
+    assert_eq!(payload["event"], "redirect");
+    assert_eq!(payload["code"], code.as_str());
+    assert_eq!(payload["original_url"], "https://www.example.com/hooked");
+    assert_eq!(payload["ip"], "127.0.0.1");
+    assert_eq!(payload["user_agent"], "webhook-test");
+    assert!(payload["timestamp"].as_str().is_some());
+}
+
+#[tokio::test]
+async fn webhook_with_a_filter_code_skips_other_codes() {
+    // Arrange
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+    let app = spawn_app_notifying(&server, Some("watched")).await;
+    let watched = app
+        .shorten_url_with_alias("https://www.example.com/watched", "watched")
+        .await;
+    let other = app.shorten_url("https://www.example.com/other").await;
+
+    // Act
+    app.get(&format!("/{}", other)).await;
+    app.get(&format!("/{}", watched)).await;
+
+    // Assert
+    let received = wait_for_deliveries(&server, 1).await;
+    // Give a delivery for the other code the chance to arrive late
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let received = server.received_requests().await.unwrap_or(received);
+    assert_eq!(received.len(), 1);
+    let payload: serde_json::Value =
+        serde_json::from_slice(&received[0].body).expect("Failed to parse JSON");
+    assert_eq!(payload["code"], "watched");
+}
+
+#[tokio::test]
+async fn failed_webhook_deliveries_are_retried() {
+    // Arrange
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(500))
+        .up_to_n_times(2)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&server)
+        .await;
+    let app = spawn_app_notifying(&server, None).await;
+    let code = app.shorten_url("https://www.example.com/flaky").await;
+
+    // Act
+    app.get(&format!("/{}", code)).await;
+
+    // Assert
+    let received = wait_for_deliveries(&server, 3).await;
+    assert_eq!(received.len(), 3);
+    assert_eq!(received[0].body, received[2].body);
+}
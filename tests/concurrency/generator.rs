@@ -0,0 +1,173 @@
+// tests/concurrency/generator.rs
+
+// concurrency stress tests for the short code generators
+// 1000 tasks generating 100 codes each must never see a duplicate, and
+// sequence engines sharing one state file must never leave it corrupted
+
+// dependencies
+use std::collections::HashSet;
+use std::fs;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use url_shortener_ztm_lib::generator::{
+    DEFAULT_ALPHABET, NanoIdEngine, SequenceEngine, ShortCodeGenerator, decode_base_n,
+};
+use uuid::Uuid;
+
+const TASKS: usize = 1000;
+const CODES_PER_TASK: usize = 100;
+const CODE_LEN: usize = 7;
+// random codes need room to avoid birthday collisions: 100_000 draws from 62^7
+// would collide about once in 700 runs, from 62^21 practically never
+const NANOID_LEN: usize = 21;
+
+// spawn TASKS tasks that each call generate() CODES_PER_TASK times and return
+// every task's codes in the order that task received them
+async fn generate_concurrently(engine: Arc<dyn ShortCodeGenerator>) -> Vec<Vec<String>> {
+    let handles: Vec<_> = (0..TASKS)
+        .map(|_| {
+            let engine = engine.clone();
+            tokio::spawn(async move {
+                (0..CODES_PER_TASK)
+                    .map(|_| engine.generate().expect("generate should succeed"))
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect();
+
+    let mut per_task = Vec::with_capacity(TASKS);
+    for handle in handles {
+        per_task.push(handle.await.expect("generator task panicked"));
+    }
+    per_task
+}
+
+// collect every code into one set, asserting none was handed out twice
+fn assert_unique_and_well_formed(per_task: &[Vec<String>], len: usize) {
+    let mut seen = HashSet::with_capacity(TASKS * CODES_PER_TASK);
+    for code in per_task.iter().flatten() {
+        assert_eq!(code.chars().count(), len, "wrong length: {code}");
+        assert!(
+            code.chars().all(|c| DEFAULT_ALPHABET.contains(&c)),
+            "character outside the alphabet: {code}"
+        );
+        assert!(seen.insert(code.as_str()), "duplicate code: {code}");
+    }
+    assert_eq!(seen.len(), TASKS * CODES_PER_TASK);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+#[ignore]
+async fn nanoid_engine_issues_no_duplicates_under_concurrency() {
+    // Arrange
+    let engine: Arc<dyn ShortCodeGenerator> = Arc::new(NanoIdEngine::new(NANOID_LEN, None));
+
+    // Act
+    let per_task = generate_concurrently(engine).await;
+
+    // Assert
+    assert_unique_and_well_formed(&per_task, NANOID_LEN);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+#[ignore]
+async fn sequence_engine_issues_increasing_codes_under_concurrency() {
+    // Arrange
+    let engine: Arc<dyn ShortCodeGenerator> =
+        Arc::new(SequenceEngine::new(CODE_LEN, None, 10, 1, None));
+
+    // Act
+    let per_task = generate_concurrently(engine).await;
+
+    // Assert
+    assert_unique_and_well_formed(&per_task, CODE_LEN);
+    let mut all = Vec::with_capacity(TASKS * CODES_PER_TASK);
+    for codes in &per_task {
+        let values: Vec<u128> = codes
+            .iter()
+            .map(|code| decode_base_n(code, DEFAULT_ALPHABET).expect("code should decode"))
+            .collect();
+        // one engine hands out values in lock order, so each task's codes increase
+        assert!(
+            values.windows(2).all(|pair| pair[0] < pair[1]),
+            "codes went backwards within a task: {codes:?}"
+        );
+        all.extend(values);
+    }
+    // and no block was skipped: the values are exactly 0..100_000
+    all.sort_unstable();
+    assert!(all.iter().copied().eq(0..(TASKS * CODES_PER_TASK) as u128));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+#[ignore]
+async fn sequence_engines_racing_on_one_state_file_never_corrupt_it() {
+    // Arrange
+    let directory = std::env::temp_dir().join(format!("sequence-race-{}", Uuid::new_v4()));
+    let path = directory.join("state.bin");
+    let block_size = 10;
+    let engines: Vec<_> = (0..8)
+        .map(|_| {
+            Arc::new(SequenceEngine::new(
+                CODE_LEN,
+                None,
+                block_size,
+                1,
+                Some(path.clone()),
+            ))
+        })
+        .collect();
+
+    // a reader checks the file while the engines are writing it
+    let done = Arc::new(AtomicBool::new(false));
+    let reader = {
+        let (done, path) = (done.clone(), path.clone());
+        tokio::task::spawn_blocking(move || {
+            while !done.load(Ordering::Relaxed) {
+                if let Ok(bytes) = fs::read(&path) {
+                    assert_eq!(bytes.len(), 8, "reader saw a partial state file");
+                }
+            }
+        })
+    };
+
+    // Act
+    let handles: Vec<_> = engines
+        .iter()
+        .flat_map(|engine| (0..TASKS / engines.len()).map(move |_| engine.clone()))
+        .map(|engine| {
+            tokio::spawn(async move {
+                for _ in 0..CODES_PER_TASK {
+                    engine
+                        .generate()
+                        .expect("generate should persist its state");
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.await.expect("generator task panicked");
+    }
+    done.store(true, Ordering::Relaxed);
+    reader.await.expect("reader found a corrupted state file");
+
+    // Assert
+    let bytes = fs::read(&path).expect("state file should exist");
+    let stored = u64::from_le_bytes(bytes.try_into().expect("state should be 8 bytes"));
+    let highest = engines
+        .iter()
+        .map(|engine| engine.metrics().next_value)
+        .max()
+        .unwrap();
+    // last writer wins, but whatever landed is a whole cursor some engine held
+    assert_eq!(stored % block_size, 0);
+    assert!(stored > 0 && stored <= highest);
+    // every temp file was renamed into place
+    let entries: Vec<_> = fs::read_dir(&directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    assert_eq!(entries, vec!["state.bin"]);
+
+    let _ = fs::remove_dir_all(&directory);
+}
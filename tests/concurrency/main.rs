@@ -0,0 +1,6 @@
+// tests/concurrency/main.rs
+
+// stress tests that hammer shared components from many tasks at once
+// they are slow, so every test is #[ignore]d and run by CI with --ignored
+
+mod generator;
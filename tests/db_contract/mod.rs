@@ -0,0 +1,222 @@
+// tests/db_contract/mod.rs
+
+// one behavioural contract for every UrlDatabase backend
+// run_contract_tests runs the same checks against SQLite, Postgres and, with
+// the `testing` feature, the in-memory backend, so edge
+// cases such as duplicates, missing codes and paging cannot drift apart
+// the Postgres run is ignored by default; run it with DATABASE_URL set:
+// DATABASE_URL=postgres://... cargo test --test db_contract -- --ignored
+
+// dependencies
+use chrono::Utc;
+use url_shortener_ztm_lib::database::{
+    DatabaseError, PostgresUrlDatabase, SqliteUrlDatabase, UrlDatabase,
+};
+use uuid::Uuid;
+
+// fails to compile once a method makes the trait unusable as Arc<dyn UrlDatabase>
+static_assertions::assert_obj_safe!(UrlDatabase);
+
+// codes and URLs unique to one run, so the suite can be repeated against a
+// Postgres database that keeps its rows; codes stay within VARCHAR(12)
+struct Run {
+    tag: String,
+}
+
+impl Run {
+    fn new() -> Self {
+        Self {
+            tag: Uuid::new_v4().simple().to_string()[..8].to_string(),
+        }
+    }
+
+    fn code(&self, name: &str) -> String {
+        format!("{}{}", name, self.tag)
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("https://example.com/{}/{}", self.tag, path)
+    }
+}
+
+pub async fn run_contract_tests(db: &dyn UrlDatabase) {
+    let run = Run::new();
+    insert_then_get_roundtrips(db, &run).await;
+    inserting_a_stored_url_returns_its_code(db, &run).await;
+    getting_a_missing_code_is_not_found(db, &run).await;
+    deleted_codes_are_not_found(db, &run).await;
+    listing_pages_by_offset_and_limit(db, &run).await;
+    clicks_are_recorded_and_counted(db, &run).await;
+    bloom_snapshots_are_saved_and_loaded(db, &run).await;
+}
+
+async fn insert_then_get_roundtrips(db: &dyn UrlDatabase, run: &Run) {
+    let (code, url) = (run.code("rt"), run.url("roundtrip"));
+
+    let (upsert, urls) = db.insert_url(&code, &url).await.unwrap();
+
+    assert!(upsert.created);
+    assert_eq!(urls.code, code);
+    assert_eq!(urls.id, upsert.id);
+    assert_eq!(db.get_url(&code).await.unwrap(), url);
+}
+
+async fn inserting_a_stored_url_returns_its_code(db: &dyn UrlDatabase, run: &Run) {
+    let url = run.url("duplicate");
+    let (first, stored) = db.insert_url(&run.code("da"), &url).await.unwrap();
+
+    let (second, again) = db.insert_url(&run.code("db"), &url).await.unwrap();
+
+    assert!(!second.created);
+    assert_eq!(second.id, first.id);
+    assert_eq!(again.code, stored.code);
+    // the second code was never stored
+    assert!(matches!(
+        db.get_url(&run.code("db")).await,
+        Err(DatabaseError::NotFound)
+    ));
+}
+
+async fn getting_a_missing_code_is_not_found(db: &dyn UrlDatabase, run: &Run) {
+    assert!(matches!(
+        db.get_url(&run.code("nf")).await,
+        Err(DatabaseError::NotFound)
+    ));
+}
+
+async fn deleted_codes_are_not_found(db: &dyn UrlDatabase, run: &Run) {
+    let code = run.code("de");
+    db.insert_url(&code, &run.url("deleted")).await.unwrap();
+
+    db.delete_url(&code).await.unwrap();
+
+    assert!(matches!(
+        db.get_url(&code).await,
+        Err(DatabaseError::NotFound)
+    ));
+    assert!(matches!(
+        db.delete_url(&code).await,
+        Err(DatabaseError::NotFound)
+    ));
+}
+
+async fn listing_pages_by_offset_and_limit(db: &dyn UrlDatabase, run: &Run) {
+    // an owner of its own keeps rows from other runs out of the pages
+    let owner = Uuid::new_v4();
+    let codes: Vec<String> = (0..5).map(|i| run.code(&format!("p{}", i))).collect();
+    for (i, code) in codes.iter().enumerate() {
+        db.insert_url(code, &run.url(&format!("page/{}", i)))
+            .await
+            .unwrap();
+        db.set_url_owner(code, owner).await.unwrap();
+    }
+
+    let page = |offset, limit| async move {
+        db.list_urls(offset, limit, Some(owner))
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|record| record.code)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(page(0, 2).await, codes[0..2]);
+    assert_eq!(page(1, 2).await, codes[1..3]);
+    assert_eq!(page(4, 10).await, codes[4..]);
+    assert!(page(5, 10).await.is_empty());
+}
+
+async fn clicks_are_recorded_and_counted(db: &dyn UrlDatabase, run: &Run) {
+    let code = run.code("cl");
+    db.insert_url(&code, &run.url("clicks")).await.unwrap();
+    assert_eq!(db.get_click_count(&code).await.unwrap(), 0);
+
+    for _ in 0..3 {
+        db.record_click(
+            &code,
+            Utc::now(),
+            Some("203.0.113.7"),
+            Some("https://referrer.example/page"),
+            None,
+            Some("desktop"),
+            None,
+            Some("contract-agent/1.0"),
+        )
+        .await
+        .unwrap();
+    }
+
+    assert_eq!(db.get_click_count(&code).await.unwrap(), 3);
+    let recent = db.get_recent_clicks(&code, 2).await.unwrap();
+    assert_eq!(recent.len(), 2);
+    assert!(recent[0].id > recent[1].id);
+    assert_eq!(recent[0].code, code);
+    assert_eq!(recent[0].user_agent.as_deref(), Some("contract-agent/1.0"));
+    assert_eq!(
+        recent[0].referrer_origin.as_deref(),
+        Some("https://referrer.example")
+    );
+    let missing = run.code("cm");
+    assert!(matches!(
+        db.record_click(&missing, Utc::now(), None, None, None, None, None, None)
+            .await,
+        Err(DatabaseError::NotFound)
+    ));
+    assert!(matches!(
+        db.get_recent_clicks(&missing, 10).await,
+        Err(DatabaseError::NotFound)
+    ));
+    assert!(matches!(
+        db.get_click_count(&missing).await,
+        Err(DatabaseError::NotFound)
+    ));
+}
+
+async fn bloom_snapshots_are_saved_and_loaded(db: &dyn UrlDatabase, run: &Run) {
+    let name = format!("contract-{}", run.tag);
+    assert_eq!(db.load_bloom_snapshot(&name).await.unwrap(), None);
+
+    db.save_bloom_snapshot(&name, b"first").await.unwrap();
+    assert_eq!(
+        db.load_bloom_snapshot(&name).await.unwrap().as_deref(),
+        Some(&b"first"[..])
+    );
+
+    // saving again replaces the snapshot
+    db.save_bloom_snapshot(&name, b"second").await.unwrap();
+    assert_eq!(
+        db.load_bloom_snapshot(&name).await.unwrap().as_deref(),
+        Some(&b"second"[..])
+    );
+}
+
+#[cfg(feature = "testing")]
+#[tokio::test]
+async fn in_memory_satisfies_the_contract() {
+    let db = url_shortener_ztm_lib::database::InMemoryUrlDatabase::new();
+
+    run_contract_tests(&db).await;
+}
+
+#[tokio::test]
+async fn sqlite_satisfies_the_contract() {
+    let db = SqliteUrlDatabase::in_memory_with_migrations()
+        .await
+        .expect("Failed to create the in-memory database");
+
+    run_contract_tests(&db).await;
+}
+
+/// Needs a running Postgres database; `DATABASE_URL` must point at it.
+///
+/// This test is ignored by default; run it explicitly when Postgres is available.
+#[tokio::test]
+#[ignore]
+async fn postgres_satisfies_the_contract() {
+    let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let db = PostgresUrlDatabase::for_test(&url)
+        .await
+        .expect("Failed to connect to Postgres");
+
+    run_contract_tests(&db).await;
+}